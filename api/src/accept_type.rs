@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_api_types::mime_types::{BCS, JSON};
+use aptos_api_types::mime_types::{BCS, BCS_STREAM, JSON};
 use poem::{web::Accept, FromRequest, Request, RequestBody, Result};
 
 /// Accept types from input headers
@@ -13,6 +13,10 @@ pub enum AcceptType {
     Json,
     /// Take types with as little conversion as possible from the database
     Bcs,
+    /// Like `Bcs`, but list endpoints emit each item as its own BCS encoded
+    /// byte vector, so consumers can decode items one at a time. Endpoints
+    /// returning a single item treat this the same as `Bcs`.
+    BcsStream,
 }
 
 /// This impl allows us to get the data straight from the arguments to the
@@ -35,6 +39,9 @@ fn parse_accept(accept: &Accept) -> Result<AcceptType> {
         if matches!(mime.as_ref(), BCS) {
             return Ok(AcceptType::Bcs);
         }
+        if matches!(mime.as_ref(), BCS_STREAM) {
+            return Ok(AcceptType::BcsStream);
+        }
     }
 
     // Default to returning content as JSON.
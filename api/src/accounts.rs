@@ -246,7 +246,7 @@ impl Account {
                 &self.latest_ledger_info,
                 BasicResponseStatus::Ok,
             )),
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_encoded((
                 state_value,
                 &self.latest_ledger_info,
                 BasicResponseStatus::Ok,
@@ -363,7 +363,7 @@ impl Account {
                 ))
                 .map(|v| v.with_cursor(next_state_key))
             },
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 // Put resources in a BTreeMap to ensure they're ordered the same every time
                 let resources: BTreeMap<StructTag, Vec<u8>> = resources.into_iter().collect();
                 BasicResponse::try_from_bcs((
@@ -435,7 +435,7 @@ impl Account {
                 ))
                 .map(|v| v.with_cursor(next_state_key))
             },
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 // Sort modules by name
                 let modules: BTreeMap<MoveModuleId, Vec<u8>> = modules
                     .into_iter()
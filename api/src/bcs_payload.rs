@@ -7,7 +7,7 @@
 // Previously the Bcs payload type took a T, not Vec<u8>. For more information
 // about that effort, see https://github.com/aptos-labs/aptos-core/issues/2277.
 
-use aptos_api_types::mime_types::BCS;
+use aptos_api_types::mime_types::{BCS, BCS_STREAM};
 use poem::{http::header, FromRequest, IntoResponse, Request, RequestBody, Response, Result};
 use poem_openapi::{
     impl_apirequest_for_payload,
@@ -88,3 +88,53 @@ impl ApiResponse for Bcs {
 }
 
 impl_apirequest_for_payload!(Bcs);
+
+/// A wrapper struct for a payload containing a stream of BCS encoded items.
+///
+/// Each item is written as a BCS encoded `Vec<u8>`, i.e. a ULEB128 length
+/// prefix followed by the BCS bytes of the item. Unlike a BCS encoded vector
+/// of items, this lets a consumer decode (or skip) one item at a time without
+/// knowing its type layout up front.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BcsStream(pub Vec<u8>);
+
+impl BcsStream {
+    /// Encodes each of the given items as a length-prefixed frame
+    pub fn from_items<T: serde::Serialize>(items: &[T]) -> bcs::Result<Self> {
+        let mut stream = Vec::new();
+        for item in items {
+            let frame = bcs::to_bytes(&bcs::to_bytes(item)?)?;
+            stream.extend_from_slice(&frame);
+        }
+        Ok(Self(stream))
+    }
+}
+
+impl Deref for BcsStream {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Payload for BcsStream {
+    const CONTENT_TYPE: &'static str = BCS_STREAM;
+
+    fn schema_ref() -> MetaSchemaRef {
+        Vec::<u8>::schema_ref()
+    }
+
+    #[allow(unused_variables)]
+    fn register(registry: &mut Registry) {
+        Vec::<u8>::register(registry);
+    }
+}
+
+impl IntoResponse for BcsStream {
+    fn into_response(self) -> Response {
+        Response::builder()
+            .header(header::CONTENT_TYPE, Self::CONTENT_TYPE)
+            .body(self.0)
+    }
+}
@@ -163,7 +163,7 @@ impl BlocksApi {
                 };
                 BasicResponse::try_from_json((block, &latest_ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_bcs((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_bcs((
                 bcs_block,
                 &latest_ledger_info,
                 BasicResponseStatus::Ok,
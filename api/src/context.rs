@@ -751,6 +751,39 @@ impl Context {
             .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
     }
 
+    pub fn get_account_participated_transactions<E: InternalError>(
+        &self,
+        address: AccountAddress,
+        start_version: u64,
+        limit: u16,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<TransactionOnChainData>, E> {
+        let ledger_version = ledger_info.version();
+        let versions = self
+            .db
+            .get_account_participation_versions(
+                address,
+                start_version,
+                limit as u64,
+                ledger_version,
+            )
+            .context("Failed to retrieve account participated transactions")
+            .map_err(|err| {
+                E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info)
+            })?;
+        versions
+            .into_iter()
+            .map(|version| {
+                let txn = self
+                    .db
+                    .get_transaction_by_version(version, ledger_version, true)?;
+                self.convert_into_transaction_on_chain_data(txn)
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse account participated transactions")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
+    }
+
     pub fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -1269,7 +1302,7 @@ impl Context {
                     return Err(json_api_disabled(api_name));
                 }
             },
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 if !self.node_config.api.bcs_output_enabled {
                     return Err(bcs_api_disabled(api_name));
                 }
@@ -200,7 +200,7 @@ impl EventsApi {
 
                 BasicResponse::try_from_json((events, &latest_ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 BasicResponse::try_from_bcs((events, &latest_ledger_info, BasicResponseStatus::Ok))
             },
         }
@@ -49,7 +49,7 @@ impl IndexApi {
                     BasicResponseStatus::Ok,
                 ))
             },
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 let index_response = IndexResponseBcs::new(ledger_info.clone(), node_role);
                 BasicResponse::try_from_bcs((index_response, &ledger_info, BasicResponseStatus::Ok))
             },
@@ -18,10 +18,13 @@ mod index;
 mod log;
 pub mod metrics;
 mod page;
+mod projection;
+mod rate_limit;
 mod response;
 mod runtime;
 mod set_failpoints;
 mod state;
+mod state_override;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
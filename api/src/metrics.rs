@@ -105,3 +105,12 @@ pub static WAIT_TRANSACTION_POLL_TIME: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static RATE_LIMITED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_rate_limited_requests",
+        "API requests rejected by the per client rate limiter, grouped by request category",
+        &["category"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,137 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Field projection for transaction listings.
+//!
+//! Converting write sets and events into their JSON representation dominates
+//! the cost of rendering a page of transactions. Clients that only need e.g.
+//! hashes and success flags can ask for these fields to be excluded, in which
+//! case they are dropped before any conversion takes place and returned empty.
+//! An excluded payload of a user transaction is left out of its JSON. BCS
+//! transactions are signed, so their payloads cannot be excluded.
+
+use crate::{accept_type::AcceptType, response::BadRequestError};
+use aptos_api_types::{AptosErrorCode, Transaction, TransactionOnChainData};
+
+/// Name of the write set changes field of a transaction
+const CHANGES_FIELD: &str = "changes";
+/// Name of the events field of a transaction
+const EVENTS_FIELD: &str = "events";
+/// Name of the payload field of a user transaction
+const PAYLOAD_FIELD: &str = "payload";
+
+/// The set of transaction fields to leave out of a listing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TransactionFieldFilter {
+    exclude_changes: bool,
+    exclude_events: bool,
+    exclude_payload: bool,
+}
+
+impl TransactionFieldFilter {
+    /// Parses a comma separated list of field names, e.g. `changes,events,payload`,
+    /// for a listing in the format of `accept_type`
+    pub fn parse<E: BadRequestError>(
+        exclude: Option<&str>,
+        accept_type: &AcceptType,
+    ) -> Result<Self, E> {
+        let mut filter = Self::default();
+        for field in exclude
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+        {
+            match field {
+                CHANGES_FIELD => filter.exclude_changes = true,
+                EVENTS_FIELD => filter.exclude_events = true,
+                PAYLOAD_FIELD => filter.exclude_payload = true,
+                _ => {
+                    return Err(E::bad_request_with_code_no_info(
+                        format!(
+                            "Field '{}' cannot be excluded, supported fields are: {}, {}, {}",
+                            field, CHANGES_FIELD, EVENTS_FIELD, PAYLOAD_FIELD
+                        ),
+                        AptosErrorCode::InvalidInput,
+                    ))
+                },
+            }
+        }
+        if filter.exclude_payload && accept_type != &AcceptType::Json {
+            return Err(E::bad_request_with_code_no_info(
+                format!(
+                    "Field '{}' can only be excluded from JSON, BCS transactions are returned \
+                     whole with their signatures",
+                    PAYLOAD_FIELD
+                ),
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+        Ok(filter)
+    }
+
+    /// Drops the excluded fields from the transactions before they are rendered
+    pub fn apply(&self, data: &mut [TransactionOnChainData]) {
+        if !self.exclude_changes && !self.exclude_events {
+            return;
+        }
+        for txn in data.iter_mut() {
+            if self.exclude_changes {
+                txn.changes = Default::default();
+            }
+            if self.exclude_events {
+                txn.events.clear();
+            }
+        }
+    }
+
+    /// Drops the excluded fields from the rendered JSON transactions
+    pub fn apply_to_rendered(&self, txns: &mut [Transaction]) {
+        if !self.exclude_payload {
+            return;
+        }
+        for txn in txns.iter_mut() {
+            if let Transaction::UserTransaction(user_txn) = txn {
+                user_txn.request.payload = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionFieldFilter;
+    use crate::{accept_type::AcceptType, response::BasicError};
+
+    #[test]
+    fn test_parse_transaction_field_filter() {
+        let json = AcceptType::Json;
+        let filter = TransactionFieldFilter::parse::<BasicError>(None, &json).unwrap();
+        assert_eq!(filter, TransactionFieldFilter::default());
+
+        let filter =
+            TransactionFieldFilter::parse::<BasicError>(Some("changes, events"), &json).unwrap();
+        assert!(filter.exclude_changes);
+        assert!(filter.exclude_events);
+
+        let filter = TransactionFieldFilter::parse::<BasicError>(Some("events,"), &json).unwrap();
+        assert!(!filter.exclude_changes);
+        assert!(filter.exclude_events);
+        assert!(!filter.exclude_payload);
+
+        let filter = TransactionFieldFilter::parse::<BasicError>(Some("payload"), &json).unwrap();
+        assert!(filter.exclude_payload);
+
+        assert!(TransactionFieldFilter::parse::<BasicError>(Some("sender"), &json).is_err());
+
+        // Signed BCS transactions can't be returned without their payload
+        for accept_type in [AcceptType::Bcs, AcceptType::BcsStream] {
+            assert!(
+                TransactionFieldFilter::parse::<BasicError>(Some("events"), &accept_type).is_ok()
+            );
+            assert!(
+                TransactionFieldFilter::parse::<BasicError>(Some("payload"), &accept_type).is_err()
+            );
+        }
+    }
+}
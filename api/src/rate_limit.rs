@@ -0,0 +1,373 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::RATE_LIMITED_REQUESTS;
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_config::config::{ApiRateLimitConfig, RequestBudget};
+use aptos_crypto::HashValue;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use hyper::Method;
+use poem::{
+    http::{header::RETRY_AFTER, StatusCode},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use poem_openapi::payload::Json;
+use std::{
+    collections::HashSet,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The categories of requests, each of which has its own budget per client
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestCategory {
+    Submission,
+    Simulation,
+    ViewFunction,
+    Read,
+}
+
+impl RequestCategory {
+    /// Determines the category of a request from its method and path
+    pub fn from_request(method: &Method, path: &str) -> Self {
+        if method != Method::POST {
+            return RequestCategory::Read;
+        }
+        let path = path.trim_end_matches('/');
+        if path.ends_with("/transactions/simulate") {
+            RequestCategory::Simulation
+        } else if path.ends_with("/transactions") || path.ends_with("/transactions/batch") {
+            RequestCategory::Submission
+        } else if path.ends_with("/view") {
+            RequestCategory::ViewFunction
+        } else {
+            RequestCategory::Read
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestCategory::Submission => "submission",
+            RequestCategory::Simulation => "simulation",
+            RequestCategory::ViewFunction => "view_function",
+            RequestCategory::Read => "read",
+        }
+    }
+}
+
+/// The identity a client is rate limited by
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// The hash of the API key, so keys don't end up in the limiter's logs
+    ApiKey(HashValue),
+    Ip(IpAddr),
+    /// The remote address is not an IP address, e.g. a unix socket
+    Unknown,
+}
+
+/// This middleware applies per client token bucket rate limits to requests.
+/// Clients are identified by the configured API key header if it holds one of
+/// the configured API keys, and by their IP address otherwise. The IP address
+/// is taken from the configured client IP header if a proxy sets it, and is
+/// the address the request comes from otherwise. Every request consumes a single token from the client's bucket for the category of the
+/// request. Requests that find the bucket empty are rejected with a 429 and a
+/// Retry-After header. The buckets of idle clients are dropped periodically.
+pub struct RequestRateLimit {
+    limiters: Arc<RequestRateLimiters>,
+}
+
+struct RequestRateLimiters {
+    api_key_header: Option<String>,
+    api_keys: HashSet<String>,
+    client_ip_header: Option<String>,
+    idle_bucket_gc_interval: Duration,
+    last_idle_bucket_gc: Mutex<Instant>,
+    submission: TokenBucketRateLimiter<ClientKey>,
+    simulation: TokenBucketRateLimiter<ClientKey>,
+    view_function: TokenBucketRateLimiter<ClientKey>,
+    read: TokenBucketRateLimiter<ClientKey>,
+}
+
+impl RequestRateLimit {
+    pub fn new(config: &ApiRateLimitConfig) -> Self {
+        let limiter = |label: &'static str, budget: &RequestBudget| {
+            if config.enabled {
+                TokenBucketRateLimiter::new(
+                    label,
+                    String::new(),
+                    config.initial_bucket_fill_percentage,
+                    budget.burst_size,
+                    budget.requests_per_second,
+                    None,
+                )
+            } else {
+                TokenBucketRateLimiter::open(label)
+            }
+        };
+        Self {
+            limiters: Arc::new(RequestRateLimiters {
+                api_key_header: config.api_key_header.clone(),
+                api_keys: config.api_keys.iter().cloned().collect(),
+                client_ip_header: config.client_ip_header.clone(),
+                idle_bucket_gc_interval: Duration::from_secs(config.idle_bucket_gc_interval_secs),
+                last_idle_bucket_gc: Mutex::new(Instant::now()),
+                submission: limiter("api_submission", &config.submission),
+                simulation: limiter("api_simulation", &config.simulation),
+                view_function: limiter("api_view_function", &config.view_function),
+                read: limiter("api_read", &config.read),
+            }),
+        }
+    }
+}
+
+impl RequestRateLimiters {
+    fn client_key(&self, req: &Request) -> ClientKey {
+        let api_key = self
+            .api_key_header
+            .as_ref()
+            .and_then(|header| req.headers().get(header))
+            .and_then(|value| value.to_str().ok())
+            .filter(|api_key| self.api_keys.contains(*api_key));
+        if let Some(api_key) = api_key {
+            return ClientKey::ApiKey(HashValue::sha3_256_of(api_key.as_bytes()));
+        }
+        let forwarded_ip = self
+            .client_ip_header
+            .as_ref()
+            .and_then(|header| req.headers().get(header))
+            .and_then(|value| value.to_str().ok())
+            .and_then(forwarded_ip);
+        match forwarded_ip {
+            Some(ip) => ClientKey::Ip(ip),
+            None => match req.remote_addr().as_socket_addr() {
+                Some(address) => ClientKey::Ip(address.ip()),
+                None => ClientKey::Unknown,
+            },
+        }
+    }
+
+    fn limiters(&self) -> [&TokenBucketRateLimiter<ClientKey>; 4] {
+        [
+            &self.submission,
+            &self.simulation,
+            &self.view_function,
+            &self.read,
+        ]
+    }
+
+    fn limiter(&self, category: RequestCategory) -> &TokenBucketRateLimiter<ClientKey> {
+        match category {
+            RequestCategory::Submission => &self.submission,
+            RequestCategory::Simulation => &self.simulation,
+            RequestCategory::ViewFunction => &self.view_function,
+            RequestCategory::Read => &self.read,
+        }
+    }
+
+    /// Takes a token for the request, or returns how many seconds the client
+    /// should wait before retrying
+    fn acquire(&self, client_key: ClientKey, category: RequestCategory) -> Result<(), u64> {
+        let bucket = self.limiter(category).bucket(client_key);
+        let result = bucket.lock().acquire_all_tokens(1);
+        result.map_err(|retry_at| match retry_at {
+            Some(retry_at) => {
+                let wait = retry_at.saturating_duration_since(Instant::now());
+                // Round up, so clients don't retry before a token is available
+                wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+            },
+            // The bucket can never hold enough tokens, which the config
+            // sanitizer rules out. Ask the client to back off anyway.
+            None => 1,
+        })
+    }
+
+    /// Drops the buckets of idle clients if the GC interval has passed since
+    /// the last time, and returns the number of buckets dropped
+    fn garbage_collect_idle_buckets(&self) -> usize {
+        // If another request is collecting already, there's nothing to do
+        let Ok(mut last_idle_bucket_gc) = self.last_idle_bucket_gc.try_lock() else {
+            return 0;
+        };
+        if last_idle_bucket_gc.elapsed() < self.idle_bucket_gc_interval {
+            return 0;
+        }
+        *last_idle_bucket_gc = Instant::now();
+        self.limiters()
+            .iter()
+            .map(|limiter| limiter.garbage_collect_idle_buckets())
+            .sum()
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RequestRateLimit {
+    type Output = RequestRateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RequestRateLimitEndpoint {
+            inner: ep,
+            limiters: self.limiters.clone(),
+        }
+    }
+}
+
+/// Endpoint for RequestRateLimit middleware.
+pub struct RequestRateLimitEndpoint<E> {
+    inner: E,
+    limiters: Arc<RequestRateLimiters>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RequestRateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let category = RequestCategory::from_request(req.method(), req.uri().path());
+        self.limiters.garbage_collect_idle_buckets();
+        let client_key = self.limiters.client_key(&req);
+        if let Err(retry_after_secs) = self.limiters.acquire(client_key, category) {
+            RATE_LIMITED_REQUESTS
+                .with_label_values(&[category.as_str()])
+                .inc();
+            return Ok(rate_limited_response(category, retry_after_secs));
+        }
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+/// Returns the last address of a comma separated list of addresses, as in the
+/// `X-Forwarded-For` header, which is the one appended by the closest proxy
+fn forwarded_ip(value: &str) -> Option<IpAddr> {
+    value.rsplit(',').next()?.trim().parse().ok()
+}
+
+fn rate_limited_response(category: RequestCategory, retry_after_secs: u64) -> Response {
+    let error = AptosError::new_with_error_code(
+        format!(
+            "Rate limit exceeded for {} requests, retry after {} seconds",
+            category.as_str(),
+            retry_after_secs
+        ),
+        AptosErrorCode::RateLimitExceeded,
+    );
+    let mut response = Json(error).into_response();
+    response.set_status(StatusCode::TOO_MANY_REQUESTS);
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, retry_after_secs.into());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{forwarded_ip, ClientKey, RequestCategory, RequestRateLimit};
+    use aptos_config::config::{ApiRateLimitConfig, RequestBudget};
+    use aptos_crypto::HashValue;
+    use hyper::Method;
+    use poem::Request;
+    use std::{net::IpAddr, thread::sleep, time::Duration};
+
+    #[test]
+    fn test_request_category() {
+        let cases = [
+            (
+                Method::POST,
+                "/v1/transactions",
+                RequestCategory::Submission,
+            ),
+            (
+                Method::POST,
+                "/v1/transactions/batch",
+                RequestCategory::Submission,
+            ),
+            (
+                Method::POST,
+                "/v1/transactions/simulate",
+                RequestCategory::Simulation,
+            ),
+            (Method::POST, "/v1/view", RequestCategory::ViewFunction),
+            (Method::GET, "/v1/transactions", RequestCategory::Read),
+            (
+                Method::POST,
+                "/v1/transactions/encode_submission",
+                RequestCategory::Read,
+            ),
+            (
+                Method::GET,
+                "/v1/accounts/0x1/resources",
+                RequestCategory::Read,
+            ),
+        ];
+        for (method, path, category) in cases {
+            assert_eq!(RequestCategory::from_request(&method, path), category);
+        }
+    }
+
+    #[test]
+    fn test_idle_buckets_are_dropped() {
+        let rate_limit = RequestRateLimit::new(&ApiRateLimitConfig {
+            enabled: true,
+            idle_bucket_gc_interval_secs: 0,
+            read: RequestBudget::new(1, 1),
+            ..Default::default()
+        });
+        let limiters = &rate_limit.limiters;
+        let client = ClientKey::ApiKey(HashValue::sha3_256_of(b"client"));
+
+        // A client that used up its budget keeps its bucket
+        limiters
+            .acquire(client.clone(), RequestCategory::Read)
+            .unwrap();
+        let retry_after_secs = limiters
+            .acquire(client.clone(), RequestCategory::Read)
+            .unwrap_err();
+        assert_eq!(limiters.garbage_collect_idle_buckets(), 0);
+
+        // Once its bucket is refilled, the client is idle
+        sleep(Duration::from_secs(retry_after_secs));
+        assert_eq!(limiters.garbage_collect_idle_buckets(), 1);
+        limiters.acquire(client, RequestCategory::Read).unwrap();
+    }
+
+    #[test]
+    fn test_client_key() {
+        let rate_limit = RequestRateLimit::new(&ApiRateLimitConfig {
+            enabled: true,
+            api_key_header: Some("x-api-key".to_string()),
+            api_keys: vec!["secret".to_string()],
+            client_ip_header: Some("x-forwarded-for".to_string()),
+            ..Default::default()
+        });
+        let limiters = &rate_limit.limiters;
+
+        // API keys are only kept as a hash
+        let req = Request::builder().header("x-api-key", "secret").finish();
+        let client_key = limiters.client_key(&req);
+        assert_eq!(
+            client_key,
+            ClientKey::ApiKey(HashValue::sha3_256_of(b"secret"))
+        );
+        assert!(!format!("{:?}", client_key).contains("secret"));
+
+        // Without a valid key, the address set by the proxy is used
+        let req = Request::builder()
+            .header("x-api-key", "guess")
+            .header("x-forwarded-for", "10.0.0.1, 192.0.2.7")
+            .finish();
+        assert_eq!(
+            limiters.client_key(&req),
+            ClientKey::Ip("192.0.2.7".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_forwarded_ip() {
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+        assert_eq!(forwarded_ip("192.0.2.7"), ip("192.0.2.7"));
+        assert_eq!(forwarded_ip("10.0.0.1, 192.0.2.7"), ip("192.0.2.7"));
+        assert_eq!(forwarded_ip("2001:db8::1"), ip("2001:db8::1"));
+        assert_eq!(forwarded_ip("10.0.0.1, unknown"), None);
+        assert_eq!(forwarded_ip(""), None);
+    }
+}
@@ -29,7 +29,10 @@
 
 // TODO: https://github.com/aptos-labs/aptos-core/issues/2279
 
-use super::{accept_type::AcceptType, bcs_payload::Bcs};
+use super::{
+    accept_type::AcceptType,
+    bcs_payload::{Bcs, BcsStream},
+};
 use aptos_api_types::{Address, AptosError, AptosErrorCode, HashValue, LedgerInfo};
 use move_core_types::{
     identifier::{IdentStr, Identifier},
@@ -50,6 +53,10 @@ pub enum AptosResponseContent<T: ToJSON + Send + Sync> {
     /// from either an internal Rust type being serialized into bytes, or just
     /// the bytes directly from storage.
     Bcs(Bcs),
+
+    /// Return a list of items as a stream of length-prefixed BCS items. See
+    /// [`BcsStream`] for the framing.
+    BcsStream(BcsStream),
 }
 
 /// This trait defines common functions that all error responses should impl.
@@ -390,6 +397,22 @@ macro_rules! generate_success_response {
             }
         }
 
+        // Generate a From impl that builds a response from a BcsStream and friends.
+        impl<T: poem_openapi::types::ToJSON + Send + Sync> From<($crate::bcs_payload::BcsStream, &aptos_api_types::LedgerInfo, [<$enum_name Status>])>
+            for $enum_name<T>
+        {
+            fn from(
+                (value, ledger_info, status): (
+                    $crate::bcs_payload::BcsStream,
+                    &aptos_api_types::LedgerInfo,
+                    [<$enum_name Status>]
+                ),
+            ) -> Self {
+                let content = $crate::response::AptosResponseContent::BcsStream(value);
+                Self::from((content, ledger_info, status))
+            }
+        }
+
         // Generate a TryFrom impl that builds a response from a T, an AcceptType,
         // and all the other usual suspects. It expects to be called with a generic
         // parameter E: InternalError, with which we can build an internal error
@@ -404,7 +427,7 @@ macro_rules! generate_success_response {
                 ),
             ) -> Result<Self, E> {
                 match accept_type {
-                    AcceptType::Bcs => Ok(Self::from((
+                    AcceptType::Bcs | AcceptType::BcsStream => Ok(Self::from((
                         $crate::bcs_payload::Bcs(
                             bcs::to_bytes(&value)
                                 .map_err(|e| E::internal_with_code(
@@ -459,6 +482,25 @@ macro_rules! generate_success_response {
                )))
             }
 
+            pub fn try_from_bcs_stream<B: serde::Serialize, E: $crate::response::InternalError>(
+                (values, ledger_info, status): (
+                    Vec<B>,
+                    &aptos_api_types::LedgerInfo,
+                    [<$enum_name Status>],
+                ),
+            ) -> Result<Self, E> {
+               Ok(Self::from((
+                    $crate::bcs_payload::BcsStream::from_items(&values)
+                        .map_err(|e| E::internal_with_code(
+                            e,
+                            aptos_api_types::AptosErrorCode::InternalError,
+                            ledger_info
+                        ))?,
+                    ledger_info,
+                    status
+               )))
+            }
+
             pub fn try_from_encoded<E: $crate::response::InternalError>(
                 (value, ledger_info, status): (
                     Vec<u8>,
@@ -5,8 +5,8 @@
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
     context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    log::middleware_log, rate_limit::RequestRateLimit, set_failpoints, state::StateApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
@@ -162,6 +162,7 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let rate_limit = RequestRateLimit::new(&config.api.rate_limit);
 
     let api_service = get_api_service(context.clone());
 
@@ -233,6 +234,7 @@ pub fn attach_poem_to_runtime(
             )
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
+            .with(rate_limit)
             // NOTE: Make sure to keep this after all the `with` middleware.
             .catch_all_error(convert_error)
             .around(middleware_log);
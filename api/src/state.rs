@@ -324,7 +324,7 @@ impl StateApi {
 
                 BasicResponse::try_from_json((resource, &ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_encoded((
                 bytes.to_vec(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
@@ -374,7 +374,7 @@ impl StateApi {
 
                 BasicResponse::try_from_json((module, &ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_encoded((
                 bytes.to_vec(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
@@ -469,7 +469,7 @@ impl StateApi {
 
                 BasicResponse::try_from_json((move_value, &ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_encoded((
                 bytes.to_vec(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
@@ -522,7 +522,7 @@ impl StateApi {
                 "Get raw table item",
                 "Please use get table item instead.",
             )),
-            AcceptType::Bcs => BasicResponse::try_from_encoded((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_encoded((
                 bytes.to_vec(),
                 &ledger_info,
                 BasicResponseStatus::Ok,
@@ -593,7 +593,7 @@ impl StateApi {
                 "Get raw state value",
                 "This serves only bytes. Use other APIs for Json.",
             )),
-            AcceptType::Bcs => {
+            AcceptType::Bcs | AcceptType::BcsStream => {
                 BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
             },
         }
@@ -0,0 +1,233 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! State overrides for simulation.
+//!
+//! Simulations can be run on top of state that does not exist on chain, e.g.
+//! to answer "what if this account had X coins" or "what if this module were
+//! upgraded". The overrides are applied as an overlay on top of the latest
+//! ledger state and never reach storage.
+
+use anyhow::{bail, format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{AsConverter, BalanceOverride, MoveModuleBytecode, StateOverrides};
+use aptos_db_indexer::table_info_reader::TableInfoReader;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    account_address::create_derived_object_address,
+    account_config::{
+        fungible_store::FungibleStoreResource, CoinStoreResource, ObjectGroupResource,
+    },
+    state_store::{
+        errors::StateviewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, StateView, TStateView,
+    },
+};
+use bytes::Bytes;
+use move_core_types::{
+    account_address::AccountAddress, ident_str, language_storage::StructTag,
+    move_resource::MoveStructType,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// A state view serving overridden state values on top of a base view
+pub struct StateOverrideView<S> {
+    base_view: S,
+    overrides: HashMap<StateKey, StateValue>,
+    has_module_overrides: bool,
+}
+
+impl<S: StateView> StateOverrideView<S> {
+    /// Resolves the given overrides against the base view. Modules are
+    /// applied first, so that resources can be of types they define.
+    pub fn new(
+        base_view: S,
+        state_overrides: StateOverrides,
+        db: Arc<dyn DbReader>,
+        table_info_reader: Option<Arc<dyn TableInfoReader>>,
+    ) -> Result<Self> {
+        let mut view = Self {
+            base_view,
+            overrides: HashMap::new(),
+            has_module_overrides: !state_overrides.modules.is_empty(),
+        };
+
+        for module in state_overrides.modules {
+            let abi = MoveModuleBytecode::new(module.bytecode.0.clone())
+                .try_parse_abi()?
+                .abi
+                .ok_or_else(|| format_err!("Invalid module bytecode"))?;
+            let key = StateKey::module(&abi.address.into(), &abi.name);
+            view.insert(key, module.bytecode.0);
+        }
+
+        for resource in state_overrides.resources {
+            let tag: StructTag = resource.resource_type.clone().try_into()?;
+            let (key, bytes) = {
+                let converter = view.as_converter(db.clone(), table_info_reader.clone());
+                let value = converter
+                    .try_into_vm_value(&tag.clone().into(), resource.data)
+                    .with_context(|| {
+                        format!("Invalid value for resource {}", resource.resource_type)
+                    })?;
+                let bytes = value
+                    .simple_serialize()
+                    .ok_or_else(|| format_err!("Failed to serialize resource {}", tag))?;
+                converter.try_into_resource_write(&view, resource.address, &tag, bytes.into())?
+            };
+            view.insert(key, bytes);
+        }
+
+        for balance in state_overrides.balances {
+            view.override_balance(balance)?;
+        }
+
+        Ok(view)
+    }
+
+    /// Sets the balance of an account, see `BalanceOverride` for where it's
+    /// stored
+    fn override_balance(&mut self, balance: BalanceOverride) -> Result<()> {
+        let owner: AccountAddress = balance.address.into();
+        let amount = balance.amount.0;
+        let (metadata, in_coin_store) = match balance.fungible_asset_metadata {
+            Some(metadata) => (metadata.into(), false),
+            None => (
+                AccountAddress::TEN,
+                self.override_apt_coin_store(owner, amount)?,
+            ),
+        };
+        // With the amount in the coin store, the primary store must be empty
+        let fungible_store_amount = if in_coin_store { 0 } else { amount };
+        let in_fungible_store = self.override_primary_fungible_store(
+            create_derived_object_address(owner, metadata),
+            fungible_store_amount,
+        )?;
+        if !in_coin_store && !in_fungible_store {
+            bail!(
+                "Account {} has no store for the balance of {}, override the resource instead",
+                owner,
+                metadata
+            );
+        }
+        Ok(())
+    }
+
+    /// Sets the balance of the APT coin store of `owner`, if it has one
+    fn override_apt_coin_store(&mut self, owner: AccountAddress, amount: u64) -> Result<bool> {
+        let key = StateKey::resource_typed::<CoinStoreResource>(&owner)?;
+        let coin_store: CoinStoreResource = match self.get_state_value_bytes(&key)? {
+            Some(bytes) => bcs::from_bytes(&bytes)?,
+            None => return Ok(false),
+        };
+        let coin_store = CoinStoreResource::new(
+            amount,
+            coin_store.frozen(),
+            coin_store.deposit_events().clone(),
+            coin_store.withdraw_events().clone(),
+        );
+        self.insert(key, bcs::to_bytes(&coin_store)?);
+        Ok(true)
+    }
+
+    /// Sets the balance of the fungible store at `store_address`, if there is
+    /// one there
+    fn override_primary_fungible_store(
+        &mut self,
+        store_address: AccountAddress,
+        amount: u64,
+    ) -> Result<bool> {
+        let key = StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag());
+        let mut group: BTreeMap<StructTag, Bytes> = match self.get_state_value_bytes(&key)? {
+            Some(bytes) => bcs::from_bytes(&bytes)?,
+            None => return Ok(false),
+        };
+        let store_tag = FungibleStoreResource::struct_tag();
+        let store: FungibleStoreResource = match group.get(&store_tag) {
+            Some(bytes) => bcs::from_bytes(bytes)?,
+            None => return Ok(false),
+        };
+        // The balance of a store with a concurrent balance is held by an
+        // aggregator, which can't be set from here
+        if group.contains_key(&concurrent_fungible_balance_tag()) {
+            bail!(
+                "The fungible store {} has a concurrent balance, override the resource instead",
+                store_address
+            );
+        }
+        let store = FungibleStoreResource::new(store.metadata(), amount, store.frozen());
+        group.insert(store_tag, bcs::to_bytes(&store)?.into());
+        self.insert(key, bcs::to_bytes(&group)?);
+        Ok(true)
+    }
+
+    /// The APT balance of `owner`, the sum of its coin store and primary
+    /// fungible store balances, read without running Move code
+    pub fn apt_balance(&self, owner: AccountAddress) -> Result<u64> {
+        let key = StateKey::resource_typed::<CoinStoreResource>(&owner)?;
+        let coin_balance = match self.get_state_value_bytes(&key)? {
+            Some(bytes) => bcs::from_bytes::<CoinStoreResource>(&bytes)?.coin(),
+            None => 0,
+        };
+        let store_address = create_derived_object_address(owner, AccountAddress::TEN);
+        let key = StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag());
+        let group: BTreeMap<StructTag, Bytes> = match self.get_state_value_bytes(&key)? {
+            Some(bytes) => bcs::from_bytes(&bytes)?,
+            None => return Ok(coin_balance),
+        };
+        if group.contains_key(&concurrent_fungible_balance_tag()) {
+            bail!(
+                "The fungible store {} has a concurrent balance, which can't be read with module overrides",
+                store_address
+            );
+        }
+        let fungible_balance = match group.get(&FungibleStoreResource::struct_tag()) {
+            Some(bytes) => bcs::from_bytes::<FungibleStoreResource>(bytes)?.balance(),
+            None => 0,
+        };
+        coin_balance
+            .checked_add(fungible_balance)
+            .ok_or_else(|| format_err!("APT balance of {} overflows", owner))
+    }
+
+    fn insert(&mut self, key: StateKey, bytes: impl Into<bytes::Bytes>) {
+        self.overrides
+            .insert(key, StateValue::new_legacy(bytes.into()));
+    }
+
+    /// Number of state items that differ from the base view
+    pub fn num_overrides(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Whether any code differs from the base view
+    pub fn has_module_overrides(&self) -> bool {
+        self.has_module_overrides
+    }
+}
+
+fn concurrent_fungible_balance_tag() -> StructTag {
+    StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("fungible_asset").to_owned(),
+        name: ident_str!("ConcurrentFungibleBalance").to_owned(),
+        type_args: vec![],
+    }
+}
+
+impl<S: StateView> TStateView for StateOverrideView<S> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        match self.overrides.get(state_key) {
+            Some(value) => Ok(Some(value.clone())),
+            None => self.base_view.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        self.base_view.get_usage()
+    }
+}
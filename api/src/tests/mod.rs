@@ -11,6 +11,7 @@ mod invalid_post_request_test;
 mod modules;
 mod multisig_transactions_test;
 mod objects;
+mod rate_limit_test;
 mod resource_groups;
 mod secp256k1_ecdsa;
 mod simulation_test;
@@ -31,3 +32,7 @@ fn new_test_context(test_name: String) -> TestContext {
 fn new_test_context_with_config(test_name: String, node_config: NodeConfig) -> TestContext {
     super_new_test_context(test_name, node_config, false)
 }
+
+fn new_test_context_with_indexer(test_name: String) -> TestContext {
+    super_new_test_context(test_name, NodeConfig::default(), true)
+}
@@ -0,0 +1,122 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context_with_config;
+use aptos_api_test_context::current_function_name;
+use aptos_config::config::{ApiRateLimitConfig, NodeConfig, RequestBudget};
+
+const API_KEY_HEADER: &str = "x-api-key";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_requests_are_rate_limited_per_client() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.rate_limit = ApiRateLimitConfig {
+        enabled: true,
+        api_key_header: Some(API_KEY_HEADER.to_string()),
+        api_keys: vec!["first".to_string(), "second".to_string()],
+        read: RequestBudget::new(1, 1),
+        ..Default::default()
+    };
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let request = |api_key: &str| {
+        warp::test::request()
+            .method("GET")
+            .header(API_KEY_HEADER, api_key)
+            .path("/v1/transactions")
+    };
+
+    // The first request of a client is let through
+    let resp = context.reply(request("first")).await;
+    assert_eq!(resp.status(), 200);
+
+    // The second one exceeds the client's budget
+    let resp = context.reply(request("first")).await;
+    assert_eq!(resp.status(), 429);
+    let retry_after: u64 = resp.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after <= 1);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["error_code"], "rate_limit_exceeded");
+
+    // Other clients have their own budget
+    let resp = context.reply(request("second")).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_api_key_rotation() {
+    let rate_limit_config = |api_keys: &[&str]| {
+        let mut node_config = NodeConfig::default();
+        node_config.api.rate_limit = ApiRateLimitConfig {
+            enabled: true,
+            api_key_header: Some(API_KEY_HEADER.to_string()),
+            api_keys: api_keys.iter().map(|key| key.to_string()).collect(),
+            read: RequestBudget::new(1, 1),
+            ..Default::default()
+        };
+        node_config
+    };
+    let request = |api_key: Option<&str>| {
+        let request = warp::test::request().method("GET").path("/v1/transactions");
+        match api_key {
+            Some(api_key) => request.header(API_KEY_HEADER, api_key),
+            None => request,
+        }
+    };
+
+    // While a key is rotated, both the old and the new key are accepted
+    let context =
+        new_test_context_with_config(current_function_name!(), rate_limit_config(&["old", "new"]));
+    let resp = context.reply(request(Some("old"))).await;
+    assert_eq!(resp.status(), 200);
+    let resp = context.reply(request(Some("new"))).await;
+    assert_eq!(resp.status(), 200);
+
+    // Once the old key is removed, it's limited by IP address like any other
+    // request without a valid key
+    let context = new_test_context_with_config(
+        format!("{}_rotated", current_function_name!()),
+        rate_limit_config(&["new"]),
+    );
+    let resp = context.reply(request(Some("old"))).await;
+    assert_eq!(resp.status(), 200);
+    let resp = context.reply(request(None)).await;
+    assert_eq!(resp.status(), 429);
+    let resp = context.reply(request(Some("made up"))).await;
+    assert_eq!(resp.status(), 429);
+    let resp = context.reply(request(Some("new"))).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_by_forwarded_ip() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.rate_limit = ApiRateLimitConfig {
+        enabled: true,
+        client_ip_header: Some("x-forwarded-for".to_string()),
+        read: RequestBudget::new(1, 1),
+        ..Default::default()
+    };
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+    let request = |forwarded_for: &str| {
+        warp::test::request()
+            .method("GET")
+            .header("x-forwarded-for", forwarded_for)
+            .path("/v1/transactions")
+    };
+
+    // Clients behind the same proxy have their own budget
+    let resp = context.reply(request("192.0.2.1")).await;
+    assert_eq!(resp.status(), 200);
+    let resp = context.reply(request("192.0.2.2")).await;
+    assert_eq!(resp.status(), 200);
+
+    // The client is the last address, added by the proxy, so a spoofed
+    // address in front of it doesn't get the client a fresh budget
+    let resp = context.reply(request("198.51.100.1, 192.0.2.1")).await;
+    assert_eq!(resp.status(), 429);
+}
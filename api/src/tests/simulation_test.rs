@@ -153,3 +153,194 @@ async fn test_simulate_txn_with_aggregator() {
         unreachable!("Simulation uses Ed25519 authenticator.");
     }
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_balance_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), LARGE_TRANSFER_AMOUNT);
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        let resp = context
+            .expect_status_code(200)
+            .post(
+                "/transactions/simulate",
+                json!({
+                    "sender": txn.sender().to_string(),
+                    "sequence_number": txn.sequence_number().to_string(),
+                    "max_gas_amount": txn.max_gas_amount().to_string(),
+                    "gas_unit_price": txn.gas_unit_price().to_string(),
+                    "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": "0x1::aptos_account::transfer",
+                        "type_arguments": [],
+                        "arguments": [
+                            bob.address().to_standard_string(),
+                            LARGE_TRANSFER_AMOUNT.to_string(),
+                        ]
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": public_key.to_string(),
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    },
+                    "state_overrides": {
+                        "balances": [{
+                            "address": alice.address().to_standard_string(),
+                            "amount": (2 * LARGE_TRANSFER_AMOUNT).to_string(),
+                        }]
+                    }
+                }),
+            )
+            .await;
+        assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+        assert_eq!(resp[0]["state_overrides"]["non_authoritative"], json!(true));
+        assert_eq!(
+            resp[0]["state_overrides"]["overridden_state_items"],
+            json!("1")
+        );
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+
+    // The override is not persisted
+    let resp = simulate_aptos_transfer(&mut context, false, LARGE_TRANSFER_AMOUNT, 200, true).await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+    assert!(resp[0].get("state_overrides").is_none());
+}
+
+/// Whether the frame or any of its callees emitted events
+fn emits_events(frame: &serde_json::Value) -> bool {
+    !frame["events"].as_array().unwrap().is_empty()
+        || frame["calls"].as_array().unwrap().iter().any(emits_events)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_profile() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        let resp = context
+            .expect_status_code(200)
+            .post(
+                "/transactions/simulate?profile=true",
+                json!({
+                    "sender": txn.sender().to_string(),
+                    "sequence_number": txn.sequence_number().to_string(),
+                    "max_gas_amount": txn.max_gas_amount().to_string(),
+                    "gas_unit_price": txn.gas_unit_price().to_string(),
+                    "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": "0x1::aptos_account::transfer",
+                        "type_arguments": [],
+                        "arguments": [
+                            bob.address().to_standard_string(),
+                            SMALL_TRANSFER_AMOUNT.to_string(),
+                        ]
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": public_key.to_string(),
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    }
+                }),
+            )
+            .await;
+        assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+        let gas_profile = &resp[0]["gas_profile"];
+        assert_eq!(
+            gas_profile["call_graph"]["name"],
+            json!("0x1::aptos_account::transfer")
+        );
+        assert!(gas_profile["call_graph"]["cost"].as_u64().unwrap() > 0);
+        assert!(!gas_profile["call_graph"]["calls"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+        // Transfers emit events, which are attributed to the frames emitting them
+        assert!(emits_events(&gas_profile["call_graph"]));
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+
+    // Profiles are only included when asked for
+    let resp = simulate_aptos_transfer(&mut context, false, SMALL_TRANSFER_AMOUNT, 200, true).await;
+    assert!(resp[0].get("gas_profile").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_estimate_max_gas_amount_with_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Overriding a module with its own code still runs the simulation on an
+    // isolated loader
+    let module = context.get("/accounts/0x1/module/aptos_account").await;
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let balance = 1000 * txn.gas_unit_price();
+    if let TransactionAuthenticator::Ed25519 {
+        public_key,
+        signature: _,
+    } = txn.authenticator_ref()
+    {
+        let resp = context
+            .expect_status_code(200)
+            .post(
+                "/transactions/simulate?estimate_max_gas_amount=true",
+                json!({
+                    "sender": txn.sender().to_string(),
+                    "sequence_number": txn.sequence_number().to_string(),
+                    "max_gas_amount": txn.max_gas_amount().to_string(),
+                    "gas_unit_price": txn.gas_unit_price().to_string(),
+                    "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": "0x1::aptos_account::transfer",
+                        "type_arguments": [],
+                        "arguments": [
+                            bob.address().to_standard_string(),
+                            SMALL_TRANSFER_AMOUNT.to_string(),
+                        ]
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": public_key.to_string(),
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    },
+                    "state_overrides": {
+                        "modules": [{ "bytecode": module["bytecode"] }],
+                        "balances": [{
+                            "address": alice.address().to_standard_string(),
+                            "amount": balance.to_string(),
+                        }]
+                    }
+                }),
+            )
+            .await;
+        // The estimate is bounded by the overridden balance, not the minted one
+        assert_eq!(resp[0]["max_gas_amount"], json!("1000"));
+    } else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    }
+}
@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::new_test_context;
-use crate::tests::new_test_context_with_config;
+use crate::tests::{new_test_context_with_config, new_test_context_with_indexer};
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_types::{mime_types, TransactionOnChainData};
 use aptos_config::config::{GasEstimationStaticOverride, NodeConfig};
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519Signature},
@@ -125,6 +126,96 @@ async fn test_get_transactions_output_user_transaction_with_entry_function_paylo
     context.check_golden_output(txns);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_with_excluded_fields() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let txns = context
+        .get("/transactions?start=1&exclude=changes,events")
+        .await;
+    let txns = txns.as_array().unwrap();
+    assert_eq!(3, txns.len());
+    for txn in txns {
+        assert_eq!(txn["changes"], json!([]));
+        assert_eq!(txn["events"], json!([]));
+        assert!(txn["success"].as_bool().unwrap());
+    }
+
+    let txns = context.get("/transactions?start=1&exclude=payload").await;
+    let user_txns = txns
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|txn| txn["type"] == json!("user_transaction"))
+        .collect::<Vec<_>>();
+    assert_eq!(1, user_txns.len());
+    assert!(user_txns[0].get("payload").is_none());
+    assert!(user_txns[0].get("signature").is_some());
+    assert_ne!(user_txns[0]["changes"], json!([]));
+
+    let address = account.address().to_hex_literal();
+    let txns = context
+        .get(&format!(
+            "/accounts/{}/transactions?exclude=events",
+            context.root_account().await.address().to_hex_literal()
+        ))
+        .await;
+    for txn in txns.as_array().unwrap() {
+        assert_eq!(txn["events"], json!([]));
+        assert_ne!(txn["changes"], json!([]));
+    }
+
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!(
+            "/accounts/{}/transactions?exclude=sender",
+            address
+        ))
+        .await;
+    assert_eq!(resp["error_code"], json!("invalid_input"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_as_bcs_stream() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", mime_types::BCS_STREAM)
+        .path("/v1/transactions?start=1&limit=3&exclude=changes");
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], mime_types::BCS_STREAM);
+
+    // Each item is framed as a BCS encoded byte vector, so prefixing the item
+    // count turns the stream into a BCS encoded vector of frames.
+    let mut stream = bcs::from_bytes::<Vec<Vec<u8>>>(
+        &[bcs::to_bytes(&3u8).unwrap(), resp.body().to_vec()].concat(),
+    )
+    .unwrap()
+    .into_iter();
+    for version in 1..4 {
+        let txn: TransactionOnChainData = bcs::from_bytes(&stream.next().unwrap()).unwrap();
+        assert_eq!(txn.version, version);
+        assert!(txn.changes.is_empty());
+    }
+    assert!(stream.next().is_none());
+
+    // Signed transactions can't be returned without their payload
+    let req = warp::test::request()
+        .method("GET")
+        .header("Accept", mime_types::BCS_STREAM)
+        .path("/v1/transactions?start=1&limit=3&exclude=payload");
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 400);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_post_bcs_format_transaction() {
     let mut context = new_test_context(current_function_name!());
@@ -771,6 +862,56 @@ async fn test_get_account_transactions() {
     assert_json(txns, expected_txns);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_participated_transactions() {
+    let mut context = new_test_context_with_indexer(current_function_name!());
+    let mut alice = context.gen_account();
+    let bob = context.gen_account();
+    let txn = context.mint_user_account(&alice).await;
+    context.commit_block(&vec![txn]).await;
+    let txn = context.account_transfer_to(&mut alice, bob.address(), 10);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    // Bob never sent a transaction, but was created by a transfer
+    let path = format!("/accounts/{}/participated_transactions", bob.address());
+    let txns = context.get(&path).await;
+    let txns = txns.as_array().unwrap();
+    assert!(!txns.is_empty());
+    assert_eq!(
+        txns.last().unwrap()["hash"],
+        json!(txn.committed_hash().to_hex_literal())
+    );
+    let versions: Vec<u64> = txns
+        .iter()
+        .map(|txn| txn["version"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert!(versions.windows(2).all(|w| w[0] < w[1]));
+
+    // Pages start from the given version
+    let last_version = *versions.last().unwrap();
+    let txns = context
+        .get(&format!("{}?start={}", path, last_version))
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    let txns = context
+        .get(&format!("{}?start={}", path, last_version + 1))
+        .await;
+    assert!(txns.as_array().unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_participated_transactions_without_indexer() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    context
+        .expect_status_code(403)
+        .get(&format!(
+            "/accounts/{}/participated_transactions",
+            account.address()
+        ))
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_transactions_filter_transactions_by_start_sequence_number() {
     let mut context = new_test_context(current_function_name!());
@@ -11,30 +11,36 @@ use crate::{
     generate_error_response, generate_success_response, metrics,
     metrics::WAIT_TRANSACTION_GAUGE,
     page::Page,
+    projection::TransactionFieldFilter,
     response::{
         api_disabled, api_forbidden, transaction_not_found_by_hash,
         transaction_not_found_by_version, version_pruned, BadRequestError, BasicError,
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         ForbiddenError, InsufficientStorageError, InternalError,
     },
+    state_override::StateOverrideView,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    verify_function_identifier, verify_module_identifier, Address, AppliedStateOverrides,
+    AptosError, AptosErrorCode, AsConverter, EncodeSubmissionRequest, GasEstimation,
+    GasEstimationBcs, HashValue, HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction,
+    SimulateTransactionRequest, StateOverrides, SubmitTransactionRequest, Transaction,
+    TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    state_store::StateView,
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload,
+        RawTransactionWithData, SignedTransaction, TransactionOutput, TransactionPayload,
     },
     vm_status::StatusCode,
     APTOS_COIN_TYPE,
@@ -102,6 +108,26 @@ impl VerifyInput for SubmitTransactionPost {
     }
 }
 
+// Simulation requests can carry state overrides in addition to the transaction,
+// which is only supported for JSON.
+#[derive(ApiRequest, Debug)]
+pub enum SimulateTransactionPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<SimulateTransactionRequest>),
+
+    #[oai(content_type = "application/x.aptos.signed_transaction+bcs")]
+    Bcs(Bcs),
+}
+
+impl VerifyInput for SimulateTransactionPost {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            SimulateTransactionPost::Json(inner) => inner.0.verify(),
+            SimulateTransactionPost::Bcs(_) => Ok(()),
+        }
+    }
+}
+
 // We need a custom type here because we use different types for each of the
 // content types possible for the POST data.
 #[derive(ApiRequest, Debug)]
@@ -163,10 +189,18 @@ impl TransactionsApi {
         ///
         /// If not provided, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Comma separated list of fields to leave out of each transaction
+        ///
+        /// Supported fields are `changes`, `events` and `payload`. Excluded
+        /// changes and events are returned empty, and an excluded payload is
+        /// left out. Payloads can only be excluded from JSON. If not provided,
+        /// all fields are returned.
+        exclude: Query<Option<String>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_transactions")?;
         self.context
             .check_api_output_enabled("Get transactions", &accept_type)?;
+        let filter = TransactionFieldFilter::parse(exclude.0.as_deref(), &accept_type)?;
         let page = Page::new(
             start.0.map(|v| v.0),
             limit.0,
@@ -174,7 +208,7 @@ impl TransactionsApi {
         );
 
         let api = self.clone();
-        api_spawn_blocking(move || api.list(&accept_type, page)).await
+        api_spawn_blocking(move || api.list(&accept_type, page, filter)).await
     }
 
     /// Get transaction by hash
@@ -331,17 +365,82 @@ impl TransactionsApi {
         ///
         /// If not provided, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Comma separated list of fields to leave out of each transaction
+        ///
+        /// Supported fields are `changes`, `events` and `payload`. Excluded
+        /// changes and events are returned empty, and an excluded payload is
+        /// left out. Payloads can only be excluded from JSON. If not provided,
+        /// all fields are returned.
+        exclude: Query<Option<String>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_accounts_transactions")?;
         self.context
             .check_api_output_enabled("Get account transactions", &accept_type)?;
+        let filter = TransactionFieldFilter::parse(exclude.0.as_deref(), &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+        let api = self.clone();
+        api_spawn_blocking(move || api.list_by_account(&accept_type, page, address.0, filter)).await
+    }
+
+    /// Get account participated transactions
+    ///
+    /// Retrieves on-chain committed transactions the account participated in,
+    /// in ascending order of version. Unlike /accounts/:address/transactions,
+    /// this includes transactions the account did not send, i.e. ones it was
+    /// a secondary signer or the fee payer of, emitted events to the event
+    /// handles of, had resources written for, or had fungible assets moved in
+    /// or out of the fungible stores of. This requires the internal indexer to
+    /// be enabled on the node.
+    ///
+    /// To page through the transactions, start the next page from the version
+    /// after the last transaction of the current page. If the start version is
+    /// too far in the past, a 410 will be returned.
+    #[oai(
+        path = "/accounts/:address/participated_transactions",
+        method = "get",
+        operation_id = "get_account_participated_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_accounts_participated_transactions(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to start list of transactions
+        ///
+        /// If not provided, defaults to the oldest version available
+        start: Query<Option<U64>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+        /// Comma separated list of fields to leave out of each transaction
+        ///
+        /// Supported fields are `changes`, `events` and `payload`. Excluded
+        /// changes and events are returned empty, and an excluded payload is
+        /// left out. Payloads can only be excluded from JSON. If not provided,
+        /// all fields are returned.
+        exclude: Query<Option<String>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_get_accounts_participated_transactions")?;
+        self.context
+            .check_api_output_enabled("Get account participated transactions", &accept_type)?;
+        if !self.context.db.indexer_enabled() {
+            return Err(api_disabled("Get account participated transactions"));
+        }
+        let filter = TransactionFieldFilter::parse(exclude.0.as_deref(), &accept_type)?;
         let page = Page::new(
             start.0.map(|v| v.0),
             limit.0,
             self.context.max_transactions_page_size(),
         );
         let api = self.clone();
-        api_spawn_blocking(move || api.list_by_account(&accept_type, page, address.0)).await
+        api_spawn_blocking(move || api.list_by_participant(&accept_type, page, address.0, filter))
+            .await
     }
 
     /// Submit transaction
@@ -471,6 +570,13 @@ impl TransactionsApi {
     ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    ///
+    /// JSON requests may additionally contain state overrides: resources, modules
+    /// and balances to apply on top of the latest ledger state for the duration of
+    /// the simulation. None of the overrides are persisted. A simulation with state
+    /// overrides is computed against state that does not exist on chain, which is
+    /// marked in the `state_overrides` field of its result. Only JSON is supported
+    /// for such simulations.
     #[oai(
         path = "/transactions/simulate",
         method = "post",
@@ -489,7 +595,11 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
-        data: SubmitTransactionPost,
+        /// If set to true, the transaction will be run with the gas profiler, and
+        /// its gas profile will be included in the output. Only supported for JSON
+        /// outputs and for entry function and script payloads
+        profile: Query<Option<bool>>,
+        data: SimulateTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
             .context("Simulated transaction invalid")
@@ -510,7 +620,25 @@ impl TransactionsApi {
         let context = self.context.clone();
         api_spawn_blocking(move || {
             let ledger_info = context.get_latest_ledger_info()?;
-            let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
+            // JSON transactions are converted against the overridden state, as they may
+            // refer to types which only exist in overridden modules
+            let (mut signed_transaction, state_view) = match data {
+                SimulateTransactionPost::Json(data) => {
+                    let SimulateTransactionRequest {
+                        transaction,
+                        state_overrides,
+                    } = data.0;
+                    let state_view =
+                        api.state_override_view(&ledger_info, state_overrides.unwrap_or_default())?;
+                    let signed_transaction =
+                        api.signed_transaction_from_json(&ledger_info, &state_view, transaction)?;
+                    (signed_transaction, state_view)
+                },
+                SimulateTransactionPost::Bcs(data) => (
+                    api.get_signed_transaction(&ledger_info, SubmitTransactionPost::Bcs(data))?,
+                    api.state_override_view(&ledger_info, StateOverrides::default())?,
+                ),
+            };
 
             // Confirm the simulation filter allows the transaction. We use HashValue::zero()
             // here for the block ID because we don't allow filtering by block ID for the
@@ -555,35 +683,40 @@ impl TransactionsApi {
                     u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
 
                 // Retrieve account balance to determine max gas available, right now this is using
-                // a view function, but we may want to re-evaluate this based on performance
-                let (_, _, state_view) = context
-                    .state_view::<BasicErrorWith404>(Option::None)
-                    .map_err(|err| {
+                // a view function, but we may want to re-evaluate this based on performance.
+                // View functions run on the shared loader, so overridden code must not be loaded
+                // through it, and the balance is read from the overridden stores directly instead.
+                let balance: u64 = if state_view.has_module_overrides() {
+                    state_view
+                        .apt_balance(signed_transaction.sender())
+                        .map_err(|err| {
+                            SubmitTransactionError::bad_request_with_code_no_info(
+                                err,
+                                AptosErrorCode::InvalidInput,
+                            )
+                        })?
+                } else {
+                    let output = AptosVM::execute_view_function(
+                        &state_view,
+                        ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+                        ident_str!("balance").into(),
+                        vec![APTOS_COIN_TYPE.clone()],
+                        vec![signed_transaction.sender().to_vec()],
+                        context.node_config.api.max_gas_view_function,
+                    );
+                    let values = output.values.map_err(|err| {
                         SubmitTransactionError::bad_request_with_code_no_info(
                             err,
                             AptosErrorCode::InvalidInput,
                         )
                     })?;
-                let output = AptosVM::execute_view_function(
-                    &state_view,
-                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
-                    ident_str!("balance").into(),
-                    vec![APTOS_COIN_TYPE.clone()],
-                    vec![signed_transaction.sender().to_vec()],
-                    context.node_config.api.max_gas_view_function,
-                );
-                let values = output.values.map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
+                    bcs::from_bytes(&values[0]).map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code_no_info(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                        )
+                    })?
+                };
 
                 let gas_unit_price =
                     estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
@@ -617,7 +750,13 @@ impl TransactionsApi {
                 );
             }
 
-            api.simulate(&accept_type, ledger_info, signed_transaction)
+            api.simulate(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                profile.0.unwrap_or_default(),
+                state_view,
+            )
         })
         .await
     }
@@ -724,7 +863,7 @@ impl TransactionsApi {
                     &latest_ledger_info,
                     BasicResponseStatus::Ok,
                 )),
-                AcceptType::Bcs => {
+                AcceptType::Bcs | AcceptType::BcsStream => {
                     let gas_estimation_bcs = GasEstimationBcs {
                         gas_estimate: gas_estimation.gas_estimate,
                     };
@@ -742,13 +881,18 @@ impl TransactionsApi {
 
 impl TransactionsApi {
     /// List all transactions paging by ledger version
-    fn list(&self, accept_type: &AcceptType, page: Page) -> BasicResultWith404<Vec<Transaction>> {
+    fn list(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        filter: TransactionFieldFilter,
+    ) -> BasicResultWith404<Vec<Transaction>> {
         let latest_ledger_info = self.context.get_latest_ledger_info()?;
         let ledger_version = latest_ledger_info.version();
 
         let limit = page.limit(&latest_ledger_info)?;
         let start_version = page.compute_start(limit, ledger_version, &latest_ledger_info)?;
-        let data = self
+        let mut data = self
             .context
             .get_transactions(start_version, limit, ledger_version)
             .context("Failed to read raw transactions from storage")
@@ -759,25 +903,29 @@ impl TransactionsApi {
                     &latest_ledger_info,
                 )
             })?;
+        filter.apply(&mut data);
 
         match accept_type {
             AcceptType::Json => {
                 let timestamp = self
                     .context
                     .get_block_timestamp(&latest_ledger_info, start_version)?;
-                BasicResponse::try_from_json((
-                    self.context.render_transactions_sequential(
-                        &latest_ledger_info,
-                        data,
-                        timestamp,
-                    )?,
+                let mut txns = self.context.render_transactions_sequential(
                     &latest_ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
+                    data,
+                    timestamp,
+                )?;
+                filter.apply_to_rendered(&mut txns);
+                BasicResponse::try_from_json((txns, &latest_ledger_info, BasicResponseStatus::Ok))
             },
             AcceptType::Bcs => {
                 BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
             },
+            AcceptType::BcsStream => BasicResponse::try_from_bcs_stream((
+                data,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
         }
     }
 
@@ -928,7 +1076,7 @@ impl TransactionsApi {
 
                 BasicResponse::try_from_json((transaction, ledger_info, BasicResponseStatus::Ok))
             },
-            AcceptType::Bcs => BasicResponse::try_from_bcs((
+            AcceptType::Bcs | AcceptType::BcsStream => BasicResponse::try_from_bcs((
                 transaction_data,
                 ledger_info,
                 BasicResponseStatus::Ok,
@@ -987,6 +1135,7 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         page: Page,
         address: Address,
+        filter: TransactionFieldFilter,
     ) -> BasicResultWith404<Vec<Transaction>> {
         // Verify the account exists
         let account = Account::new(self.context.clone(), address, None, None, None)?;
@@ -994,23 +1143,71 @@ impl TransactionsApi {
 
         let latest_ledger_info = account.latest_ledger_info;
         // TODO: Return more specific errors from within this function.
-        let data = self.context.get_account_transactions(
+        let mut data = self.context.get_account_transactions(
             address.into(),
             page.start_option(),
             page.limit(&latest_ledger_info)?,
             latest_ledger_info.version(),
             &latest_ledger_info,
         )?;
+        filter.apply(&mut data);
         match accept_type {
-            AcceptType::Json => BasicResponse::try_from_json((
-                self.context
-                    .render_transactions_non_sequential(&latest_ledger_info, data)?,
+            AcceptType::Json => {
+                let mut txns = self
+                    .context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?;
+                filter.apply_to_rendered(&mut txns);
+                BasicResponse::try_from_json((txns, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::BcsStream => BasicResponse::try_from_bcs_stream((
+                data,
                 &latest_ledger_info,
                 BasicResponseStatus::Ok,
             )),
+        }
+    }
+
+    /// List transactions an account participated in
+    fn list_by_participant(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        address: Address,
+        filter: TransactionFieldFilter,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let oldest_version = latest_ledger_info.oldest_ledger_version.0;
+        let start_version = page.start_option().unwrap_or(oldest_version);
+        if start_version < oldest_version {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        }
+
+        let mut data = self.context.get_account_participated_transactions(
+            address.into(),
+            start_version,
+            page.limit(&latest_ledger_info)?,
+            &latest_ledger_info,
+        )?;
+        filter.apply(&mut data);
+        match accept_type {
+            AcceptType::Json => {
+                let mut txns = self
+                    .context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?;
+                filter.apply_to_rendered(&mut txns);
+                BasicResponse::try_from_json((txns, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
             AcceptType::Bcs => {
                 BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
             },
+            AcceptType::BcsStream => BasicResponse::try_from_bcs_stream((
+                data,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
         }
     }
 
@@ -1089,25 +1286,60 @@ impl TransactionsApi {
 
                 Ok(signed_transaction)
             },
-            SubmitTransactionPost::Json(data) => self
-                .context
-                .latest_state_view_poem(ledger_info)?
-                .as_converter(
-                    self.context.db.clone(),
-                    self.context.table_info_reader.clone(),
-                )
-                .try_into_signed_transaction_poem(data.0, self.context.chain_id())
-                .context("Failed to create SignedTransaction from SubmitTransactionRequest")
-                .map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                        ledger_info,
-                    )
-                }),
+            SubmitTransactionPost::Json(data) => self.signed_transaction_from_json(
+                ledger_info,
+                &self.context.latest_state_view_poem(ledger_info)?,
+                data.0,
+            ),
         }
     }
 
+    /// Converts a JSON transaction into a signed transaction, resolving its types
+    /// against the given state view
+    fn signed_transaction_from_json(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        request: SubmitTransactionRequest,
+    ) -> Result<SignedTransaction, SubmitTransactionError> {
+        state_view
+            .as_converter(
+                self.context.db.clone(),
+                self.context.table_info_reader.clone(),
+            )
+            .try_into_signed_transaction_poem(request, self.context.chain_id())
+            .context("Failed to create SignedTransaction from SubmitTransactionRequest")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })
+    }
+
+    /// Applies the state overrides on top of the latest ledger state
+    fn state_override_view(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_overrides: StateOverrides,
+    ) -> Result<StateOverrideView<DbStateView>, SubmitTransactionError> {
+        StateOverrideView::new(
+            self.context.latest_state_view_poem(ledger_info)?,
+            state_overrides,
+            self.context.db.clone(),
+            self.context.table_info_reader.clone(),
+        )
+        .context("Failed to apply state overrides")
+        .map_err(|err| {
+            SubmitTransactionError::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            )
+        })
+    }
+
     // Validates that the module, function, and args in EntryFunction payload are correctly
     // formatted.
     fn validate_entry_function_payload_format(
@@ -1281,11 +1513,9 @@ impl TransactionsApi {
                 // With BCS, we don't return the pending transaction for efficiency, because there
                 // is no new information.  The hash can be retrieved by hashing the original
                 // transaction.
-                AcceptType::Bcs => SubmitTransactionResponse::try_from_bcs((
-                    (),
-                    ledger_info,
-                    SubmitTransactionResponseStatus::Accepted,
-                )),
+                AcceptType::Bcs | AcceptType::BcsStream => SubmitTransactionResponse::try_from_bcs(
+                    ((), ledger_info, SubmitTransactionResponseStatus::Accepted),
+                ),
             },
             Err(error) => match error.error_code {
                 AptosErrorCode::InternalError => Err(
@@ -1358,21 +1588,153 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
+        profile: bool,
+        state_view: StateOverrideView<DbStateView>,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
-        // The caller must ensure that the signature is not valid, as otherwise
-        // a malicious actor could execute the transaction without their knowledge
+        Self::ensure_invalid_signature(&ledger_info, &txn)?;
+        if profile && accept_type != &AcceptType::Json {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "BCS is not supported for simulation with profiling",
+                AptosErrorCode::BcsNotSupported,
+                &ledger_info,
+            ));
+        }
+        // The result only makes sense together with the marker that it was
+        // computed against modified state, which BCS output can't carry
+        let overridden_state_items = state_view.num_overrides();
+        if overridden_state_items > 0 && accept_type != &AcceptType::Json {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "BCS is not supported for simulation with state overrides",
+                AptosErrorCode::BcsNotSupported,
+                &ledger_info,
+            ));
+        }
+
+        // Simulate transaction. Overridden code must not be loaded through the
+        // shared loader, so it's simulated on a VM with an isolated one.
+        let isolated_loader = state_view.has_module_overrides();
+        let (vm_status, output, gas_profile) = if profile {
+            let (vm_status, output, gas_log) =
+                Self::simulate_with_gas_profiler(&ledger_info, &txn, &state_view, isolated_loader)?;
+            (vm_status, output, Some(gas_log.to_json()))
+        } else if isolated_loader {
+            let (vm_status, output) =
+                AptosSimulationVM::create_vm_and_simulate_signed_transaction_with_overridden_code(
+                    &txn,
+                    &state_view,
+                );
+            (vm_status, output, None)
+        } else {
+            let (vm_status, output) =
+                AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
+            (vm_status, output, None)
+        };
+        let simulated_txn = self.simulated_transaction_data(&ledger_info, txn, &vm_status, &output);
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                // Render against the overridden state, as the outputs may refer to
+                // types that only exist in overridden modules
+                let mut user_transactions = self.render_simulated_transaction(
+                    &ledger_info,
+                    &state_view,
+                    &vm_status,
+                    simulated_txn,
+                )?;
+                for txn in user_transactions.iter_mut() {
+                    txn.gas_profile = gas_profile.clone();
+                    if overridden_state_items > 0 {
+                        txn.state_overrides = Some(AppliedStateOverrides {
+                            non_authoritative: true,
+                            overridden_state_items: (overridden_state_items as u64).into(),
+                        });
+                    }
+                }
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs | AcceptType::BcsStream => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+    }
+
+    /// Simulates a transaction with the gas profiler attached to the gas meter
+    fn simulate_with_gas_profiler(
+        ledger_info: &LedgerInfo,
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+        isolated_loader: bool,
+    ) -> Result<(VMStatus, TransactionOutput, TransactionGasLog), SubmitTransactionError> {
+        match txn.payload() {
+            TransactionPayload::Script(_) | TransactionPayload::EntryFunction(_) => (),
+            TransactionPayload::Multisig(_) | TransactionPayload::ModuleBundle(_) => {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    "Profiling is only supported for entry function and script payloads",
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                ))
+            },
+        }
+
+        let (vm_status, output, gas_profiler) =
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction_with_modified_gas_meter(
+                txn,
+                state_view,
+                isolated_loader,
+                |gas_meter| match txn.payload() {
+                    TransactionPayload::EntryFunction(entry_func) => GasProfiler::new_function(
+                        gas_meter,
+                        entry_func.module().clone(),
+                        entry_func.function().to_owned(),
+                        entry_func.ty_args().to_vec(),
+                    ),
+                    _ => GasProfiler::new_script(gas_meter),
+                },
+            )
+            .map_err(|vm_status| {
+                SubmitTransactionError::internal_with_code(
+                    format!(
+                        "Failed to simulate transaction with profiling: {}",
+                        vm_status
+                    ),
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+        Ok((vm_status, output, gas_profiler.finish()))
+    }
+
+    /// The caller must ensure that the signature is not valid, as otherwise
+    /// a malicious actor could execute the transaction without their knowledge
+    fn ensure_invalid_signature(
+        ledger_info: &LedgerInfo,
+        txn: &SignedTransaction,
+    ) -> Result<(), SubmitTransactionError> {
         if txn.verify_signature().is_ok() {
             return Err(SubmitTransactionError::bad_request_with_code(
                 "Simulated transactions must not have a valid signature",
                 AptosErrorCode::InvalidInput,
-                &ledger_info,
+                ledger_info,
             ));
         }
+        Ok(())
+    }
 
-        // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
+    /// Builds up a transaction from the outputs of a simulation, and records
+    /// its stats
+    fn simulated_transaction_data(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        vm_status: &VMStatus,
+        output: &TransactionOutput,
+    ) -> TransactionOnChainData {
         let version = ledger_info.version();
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
@@ -1406,7 +1768,6 @@ impl TransactionsApi {
             .simulate_txn_stats()
             .increment(stats_key, output.gas_used());
 
-        // Build up a transaction from the outputs
         // All state hashes are invalid, and will be filled with 0s
         let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
         let zero_hash = aptos_crypto::HashValue::zero();
@@ -1418,64 +1779,69 @@ impl TransactionsApi {
             output.gas_used(),
             exe_status,
         );
-        let simulated_txn = TransactionOnChainData {
+        TransactionOnChainData {
             version,
             transaction: txn,
             info,
             events: output.events().to_vec(),
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
-        };
+        }
+    }
 
-        let result = match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => {
-                            let mut txn = *user_txn;
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
-                        },
+    /// Renders a simulated transaction as JSON, using the state view it was
+    /// simulated against
+    fn render_simulated_transaction(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        vm_status: &VMStatus,
+        simulated_txn: TransactionOnChainData,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let timestamp = self
+            .context
+            .get_block_timestamp(ledger_info, simulated_txn.version)?;
+        let transaction = state_view
+            .as_converter(
+                self.context.db.clone(),
+                self.context.table_info_reader.clone(),
+            )
+            .try_into_onchain_transaction(timestamp, simulated_txn)
+            .context("Failed to convert simulated transaction")
+            .map_err(|err| {
+                SubmitTransactionError::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the Transaction into a UserTransaction.
+        match transaction {
+            Transaction::UserTransaction(user_txn) => {
+                let mut txn = *user_txn;
+                match vm_status {
+                    VMStatus::Error {
+                        message: Some(msg), ..
                     }
+                    | VMStatus::ExecutionFailure {
+                        message: Some(msg), ..
+                    } => {
+                        txn.info.vm_status +=
+                            format!("\nExecution failed with message: {}", msg).as_str();
+                    },
+                    _ => (),
                 }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+                // Note: a Vec is returned for backwards compatibility
+                Ok(vec![txn])
             },
-        };
-
-        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+            _ => Err(SubmitTransactionError::internal_with_code(
+                "Simulation transaction resulted in a non-UserTransaction",
+                AptosErrorCode::InternalError,
+                ledger_info,
+            )),
+        }
     }
 
     /// Encode message as BCS
@@ -1485,7 +1851,7 @@ impl TransactionsApi {
         request: EncodeSubmissionRequest,
     ) -> BasicResult<HexEncodedBytes> {
         // We don't want to encourage people to use this API if they can sign the request directly
-        if accept_type == &AcceptType::Bcs {
+        if accept_type != &AcceptType::Json {
             return Err(BasicError::bad_request_with_code_no_info(
                 "BCS is not supported for encode submission",
                 AptosErrorCode::BcsNotSupported,
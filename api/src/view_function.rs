@@ -144,7 +144,7 @@ fn view_request(
         BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
     })?;
     let result = match accept_type {
-        AcceptType::Bcs => {
+        AcceptType::Bcs | AcceptType::BcsStream => {
             // The return values are already BCS encoded, but we still need to encode the outside
             // vector without re-encoding the inside values
             let num_vals = values.len();
@@ -133,6 +133,31 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         })
     }
 
+    /// Returns the state key and value to write in order to store `bytes` as
+    /// the resource `tag` under `address`. Members of resource groups are
+    /// merged into their group as found in `state_view`.
+    pub fn try_into_resource_write(
+        &self,
+        state_view: &impl StateView,
+        address: Address,
+        tag: &StructTag,
+        bytes: Bytes,
+    ) -> Result<(StateKey, Bytes)> {
+        Ok(match self.inner.view_resource_group_member(tag) {
+            Some(group_tag) => {
+                let key = StateKey::resource_group(&address.into(), &group_tag);
+                let mut group: BTreeMap<StructTag, Bytes> =
+                    match state_view.get_state_value_bytes(&key)? {
+                        Some(group_bytes) => bcs::from_bytes(&group_bytes)?,
+                        None => BTreeMap::new(),
+                    };
+                group.insert(tag.clone(), bytes);
+                (key, bcs::to_bytes(&group)?.into())
+            },
+            None => (StateKey::resource(&address.into(), tag)?, bytes),
+        })
+    }
+
     pub fn try_into_resources_from_resource_group(
         &self,
         bytes: &[u8],
@@ -605,7 +630,9 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         Ok(RawTransaction::new(
             sender.into(),
             sequence_number.into(),
-            self.try_into_aptos_core_transaction_payload(payload)?,
+            self.try_into_aptos_core_transaction_payload(
+                payload.ok_or_else(|| format_err!("missing payload"))?,
+            )?,
             max_gas_amount.into(),
             gas_unit_price.into(),
             expiration_timestamp_secs.into(),
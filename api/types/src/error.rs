@@ -110,6 +110,8 @@ pub enum AptosErrorCode {
     BcsNotSupported = 602,
     /// API Disabled
     ApiDisabled = 603,
+    /// The client has exceeded its request rate limit
+    RateLimitExceeded = 604,
 }
 
 impl AptosErrorCode {
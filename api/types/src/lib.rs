@@ -17,6 +17,7 @@ mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod simulation;
 mod state;
 mod table;
 pub mod transaction;
@@ -41,6 +42,10 @@ pub use move_types::{
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
 use serde::{Deserialize, Deserializer};
+pub use simulation::{
+    AppliedStateOverrides, BalanceOverride, ModuleOverride, ResourceOverride,
+    SimulateTransactionRequest, StateOverrides,
+};
 pub use state::RawStateValueRequest;
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
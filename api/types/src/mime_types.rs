@@ -12,3 +12,6 @@ pub const JSON: &str = "application/json";
 
 /// MIME type to get BCS output
 pub const BCS: &str = "application/x-bcs";
+
+/// MIME type to get a stream of length-prefixed BCS items as output
+pub const BCS_STREAM: &str = "application/x.aptos.stream+bcs";
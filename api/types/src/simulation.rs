@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    Address, HexEncodedBytes, MoveStructTag, SubmitTransactionRequest, VerifyInput,
+    VerifyInputWithRecursion, U64,
+};
+use anyhow::bail;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// A request to simulate a transaction
+///
+/// This is a transaction with a zero-padded signature, as for submission,
+/// optionally along with state to simulate it on top of
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionRequest {
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub transaction: SubmitTransactionRequest,
+    /// State to apply on top of the latest ledger state for the simulation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub state_overrides: Option<StateOverrides>,
+}
+
+impl VerifyInput for SimulateTransactionRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()?;
+        match &self.state_overrides {
+            Some(state_overrides) => state_overrides.verify(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// State overrides for a simulation
+///
+/// Overrides are applied in order: modules first, then resources, then
+/// balances. Later overrides of the same state replace earlier ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// Resources to write, replacing the resource if it exists
+    #[serde(default)]
+    #[oai(default)]
+    pub resources: Vec<ResourceOverride>,
+    /// Modules to write, replacing the module if it exists
+    #[serde(default)]
+    #[oai(default)]
+    pub modules: Vec<ModuleOverride>,
+    /// Balances to set on existing coin and fungible asset stores
+    #[serde(default)]
+    #[oai(default)]
+    pub balances: Vec<BalanceOverride>,
+}
+
+impl StateOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty() && self.modules.is_empty() && self.balances.is_empty()
+    }
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        for resource in &self.resources {
+            resource.resource_type.verify(0)?;
+        }
+        for module in &self.modules {
+            if module.bytecode.is_empty() {
+                bail!("Move module bytecode is empty")
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A resource to write for a simulation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    /// Account the resource is stored under
+    pub address: Address,
+    /// Type of the resource
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub resource_type: MoveStructTag,
+    /// Value of the resource, in the same JSON format the API returns resources in
+    pub data: serde_json::Value,
+}
+
+/// A module to write for a simulation
+///
+/// The address and name of the module are taken from the bytecode.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub bytecode: HexEncodedBytes,
+}
+
+/// A balance to set for a simulation
+///
+/// APT balances are set in the account's `0x1::coin::CoinStore` if it has
+/// one, and in its primary fungible store otherwise. If the account has both,
+/// the whole amount is put in the `CoinStore` and the primary fungible store
+/// is emptied. Balances of other fungible assets are set in the account's
+/// primary fungible store for the asset.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BalanceOverride {
+    /// Account holding the balance
+    pub address: Address,
+    /// Amount in the smallest unit of the asset, e.g. octas for APT
+    pub amount: U64,
+    /// Address of the metadata object of the fungible asset. Defaults to APT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub fungible_asset_metadata: Option<Address>,
+}
+
+/// Marks a simulation as computed on top of state overrides
+///
+/// The transaction was executed against state that does not exist on chain.
+/// Its outputs are not indicative of what executing it on chain would produce.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AppliedStateOverrides {
+    /// Always true, marks the result as computed against modified state
+    pub non_authoritative: bool,
+    /// Number of state items that were overridden for the simulation
+    pub overridden_state_items: U64,
+}
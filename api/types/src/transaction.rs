@@ -3,9 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, AptosError, EntryFunctionId, EventGuid, HashValue, HexEncodedBytes,
-    MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
-    MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
+    Address, AppliedStateOverrides, AptosError, EntryFunctionId, EventGuid, HashValue,
+    HexEncodedBytes, MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode,
+    MoveStructTag, MoveType, MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use anyhow::{bail, Context as AnyhowContext};
 use aptos_crypto::{
@@ -294,6 +294,8 @@ impl
             request: (txn, payload).into(),
             events,
             timestamp: timestamp.into(),
+            gas_profile: None,
+            state_overrides: None,
         }))
     }
 }
@@ -349,7 +351,7 @@ impl From<(&SignedTransaction, TransactionPayload)> for UserTransactionRequest {
             gas_unit_price: txn.gas_unit_price().into(),
             expiration_timestamp_secs: txn.expiration_timestamp_secs().into(),
             signature: Some(txn.authenticator().into()),
-            payload,
+            payload: Some(payload),
         }
     }
 }
@@ -420,6 +422,16 @@ pub struct UserTransaction {
     /// Events generated by the transaction
     pub events: Vec<Event>,
     pub timestamp: U64,
+    /// Gas profile of the transaction, including the call graph with the gas
+    /// used by every frame. Only present for simulations with profiling enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub gas_profile: Option<serde_json::Value>,
+    /// Present for simulations on top of state overrides, whose outputs were
+    /// computed against state that does not exist on chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub state_overrides: Option<AppliedStateOverrides>,
 }
 
 /// A state checkpoint transaction
@@ -518,7 +530,9 @@ pub struct UserTransactionRequest {
     pub max_gas_amount: U64,
     pub gas_unit_price: U64,
     pub expiration_timestamp_secs: U64,
-    pub payload: TransactionPayload,
+    /// The payload, which is only left out of transaction listings that exclude it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<TransactionPayload>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<TransactionSignature>,
 }
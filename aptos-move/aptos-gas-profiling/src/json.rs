@@ -0,0 +1,192 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    log::{CallFrame, ExecutionGasEvent, FrameName, TransactionGasLog},
+    render::Render,
+};
+use aptos_gas_algebra::InternalGas;
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
+use serde_json::{json, Value};
+
+/// Returns the type of the event if the frame is a call to one of the functions
+/// in `0x1::event` which emit events.
+fn emitted_event_type(name: &FrameName) -> Option<&TypeTag> {
+    match name {
+        FrameName::Function {
+            module_id,
+            name,
+            ty_args,
+        } if module_id.address() == &AccountAddress::ONE
+            && module_id.name().as_str() == "event"
+            && matches!(name.as_str(), "emit" | "emit_event") =>
+        {
+            ty_args.first()
+        },
+        _ => None,
+    }
+}
+
+fn convert_op((name, hits, cost): (String, usize, InternalGas)) -> Value {
+    json!({
+        "name": name,
+        "hits": hits,
+        "cost": u64::from(cost),
+    })
+}
+
+impl CallFrame {
+    /// Renders the frame and all its callees, returning the total cost of the
+    /// frame along with it.
+    fn to_json(&self) -> (Value, InternalGas) {
+        use ExecutionGasEvent::*;
+
+        let mut self_cost = InternalGas::zero();
+        let mut child_cost = InternalGas::zero();
+        let mut natives = vec![];
+        let mut resource_loads = vec![];
+        let mut events = vec![];
+        let mut calls = vec![];
+
+        for event in &self.events {
+            match event {
+                Loc(..) => (),
+                Bytecode { cost, .. } | CreateTy { cost } => self_cost += *cost,
+                Call(frame) => {
+                    // The natives writing events are called by `0x1::event`, so the
+                    // events are attributed to the frame calling into it instead.
+                    let event_ty = emitted_event_type(&frame.name);
+                    let (frame, cost) = frame.to_json();
+                    child_cost += cost;
+                    if let Some(ty) = event_ty {
+                        events.push(json!({
+                            "type": ty.to_string(),
+                            "cost": u64::from(cost),
+                        }));
+                    }
+                    calls.push(frame);
+                },
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                } => {
+                    self_cost += *cost;
+                    natives.push(json!({
+                        "name": format!(
+                            "{}",
+                            Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                        ),
+                        "cost": u64::from(*cost),
+                    }));
+                },
+                LoadResource { addr, ty, cost } => {
+                    self_cost += *cost;
+                    resource_loads.push(json!({
+                        "address": addr.to_hex_literal(),
+                        "type": ty.to_string(),
+                        "cost": u64::from(*cost),
+                    }));
+                },
+            }
+        }
+
+        let total = self_cost + child_cost;
+        let frame = json!({
+            "name": self.name.to_string(),
+            "cost": u64::from(total),
+            "self_cost": u64::from(self_cost),
+            "natives": natives,
+            "resource_loads": resource_loads,
+            "events": events,
+            "calls": calls,
+        });
+        (frame, total)
+    }
+}
+
+impl TransactionGasLog {
+    /// Renders the log as a JSON document, suitable for consumption by external tools.
+    ///
+    /// Execution & IO costs are in internal gas units, which need to be divided by
+    /// `gas_scaling_factor` to get external gas units. Storage fees are in octas.
+    ///
+    /// Every frame of the call graph lists the natives it called, the resources it
+    /// loaded and the events it emitted through `0x1::event`. The cost of an event
+    /// is the execution cost of the call emitting it, which is also included in the
+    /// calls of the frame. Storage fees of events are not attributed to frames, and
+    /// are only listed for the transaction as a whole.
+    pub fn to_json(&self) -> Value {
+        let exec_io = &self.exec_io;
+        let storage = &self.storage;
+        let aggregated = exec_io.aggregate_gas_events();
+
+        let mut deps = exec_io.dependencies.clone();
+        deps.sort_by(|lhs, rhs| rhs.cost.cmp(&lhs.cost));
+
+        let mut storage_writes = storage.write_set_storage.clone();
+        storage_writes.sort_by(|lhs, rhs| rhs.cost.cmp(&lhs.cost));
+
+        let mut storage_events = storage.events.clone();
+        storage_events.sort_by(|lhs, rhs| rhs.cost.cmp(&lhs.cost));
+
+        json!({
+            "gas_scaling_factor": u64::from(exec_io.gas_scaling_factor),
+            "execution_and_io": {
+                "total": u64::from(exec_io.total),
+                "intrinsic": u64::from(exec_io.intrinsic_cost),
+                "keyless": u64::from(exec_io.keyless_cost),
+                "dependencies": deps
+                    .iter()
+                    .map(|dep| json!({
+                        "name": format!("{}", Render(&dep.id)),
+                        "is_new": dep.is_new,
+                        "size": u64::from(dep.size),
+                        "cost": u64::from(dep.cost),
+                    }))
+                    .collect::<Vec<_>>(),
+                "operations": aggregated.ops.into_iter().map(convert_op).collect::<Vec<_>>(),
+                "storage_reads": aggregated
+                    .storage_reads
+                    .into_iter()
+                    .map(convert_op)
+                    .collect::<Vec<_>>(),
+                "storage_writes": aggregated
+                    .storage_writes
+                    .into_iter()
+                    .map(convert_op)
+                    .collect::<Vec<_>>(),
+                "transaction_write": u64::from(aggregated.transaction_write),
+                "event_writes": aggregated
+                    .event_writes
+                    .into_iter()
+                    .map(convert_op)
+                    .collect::<Vec<_>>(),
+            },
+            "storage": {
+                "total": u64::from(storage.total),
+                "total_refund": u64::from(storage.total_refund),
+                "transaction": u64::from(storage.txn_storage),
+                "event_discount": u64::from(storage.event_discount),
+                "writes": storage_writes
+                    .iter()
+                    .map(|write| json!({
+                        "name": format!("{}", Render(&write.key)),
+                        "op": format!("{}", Render(&write.op_type)),
+                        "cost": u64::from(write.cost),
+                        "refund": u64::from(write.refund),
+                    }))
+                    .collect::<Vec<_>>(),
+                "events": storage_events
+                    .iter()
+                    .map(|event| json!({
+                        "type": event.ty.to_string(),
+                        "cost": u64::from(event.cost),
+                    }))
+                    .collect::<Vec<_>>(),
+            },
+            "call_graph": exec_io.call_graph.to_json().0,
+        })
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A description of the entry functions, view functions and structs of the modules of a compiled
+//! package, from which the TypeScript and Python bindings are generated.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{SignatureToken, StructDefinition, StructFieldInformation, Visibility},
+    CompiledModule,
+};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The names of the files generated besides those of the modules.
+const RESERVED_FILE_NAMES: &[&str] = &["__init__", "codec", "index"];
+
+/// A Move type, as it appears in the signature of a function or the fields of a struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<MoveType>),
+    Struct(StructRef),
+    /// The type parameter of the function or struct at this index.
+    TypeParameter(u16),
+    Reference(Box<MoveType>),
+}
+
+/// An instantiation of a struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructRef {
+    pub module: ModuleId,
+    pub name: String,
+    pub type_args: Vec<MoveType>,
+}
+
+/// Structs of the framework which have a natural representation in other languages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WellKnownStruct {
+    /// `0x1::string::String`, a UTF-8 string.
+    String,
+    /// `0x1::option::Option<T>`, a vector of at most one element.
+    Option,
+    /// `0x1::object::Object<T>`, the address of an object.
+    Object,
+}
+
+impl StructRef {
+    pub fn well_known(&self) -> Option<WellKnownStruct> {
+        if self.module.address() != &AccountAddress::ONE {
+            return None;
+        }
+        match (self.module.name().as_str(), self.name.as_str()) {
+            ("string", "String") => Some(WellKnownStruct::String),
+            ("option", "Option") => Some(WellKnownStruct::Option),
+            ("object", "Object") => Some(WellKnownStruct::Object),
+            _ => None,
+        }
+    }
+}
+
+impl MoveType {
+    /// The type in Move syntax, with type parameters named `T0`, `T1`, ...
+    pub fn to_move_string(&self) -> String {
+        use MoveType::*;
+        match self {
+            Bool => "bool".into(),
+            U8 => "u8".into(),
+            U16 => "u16".into(),
+            U32 => "u32".into(),
+            U64 => "u64".into(),
+            U128 => "u128".into(),
+            U256 => "u256".into(),
+            Address => "address".into(),
+            Signer => "signer".into(),
+            Vector(ty) => format!("vector<{}>", ty.to_move_string()),
+            Struct(s) if s.type_args.is_empty() => {
+                format!("{}::{}", module_string(&s.module), s.name)
+            },
+            Struct(s) => format!(
+                "{}::{}<{}>",
+                module_string(&s.module),
+                s.name,
+                s.type_args
+                    .iter()
+                    .map(MoveType::to_move_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeParameter(idx) => format!("T{}", idx),
+            Reference(ty) => format!("&{}", ty.to_move_string()),
+        }
+    }
+
+    /// Whether this is `signer` or `&signer`, which is not passed as an argument of transactions.
+    pub fn is_signer(&self) -> bool {
+        match self {
+            MoveType::Signer => true,
+            MoveType::Reference(ty) => ty.is_signer(),
+            _ => false,
+        }
+    }
+
+    /// Calls `f` on each struct in this type.
+    pub(crate) fn visit_structs(&self, f: &mut impl FnMut(&StructRef)) {
+        match self {
+            MoveType::Vector(ty) | MoveType::Reference(ty) => ty.visit_structs(f),
+            MoveType::Struct(s) => {
+                f(s);
+                for ty in &s.type_args {
+                    ty.visit_structs(f);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+/// The module in Move syntax, e.g. `0x1::coin`.
+pub fn module_string(id: &ModuleId) -> String {
+    format!("{}::{}", id.address().to_hex_literal(), id.name())
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub name: String,
+    pub doc: String,
+    /// The abilities each type parameter is constrained to, e.g. `["store"]`.
+    pub type_params: Vec<Vec<String>>,
+    /// The parameters passed by callers, without the leading signers of entry functions.
+    pub params: Vec<(String, MoveType)>,
+    pub returns: Vec<MoveType>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Struct {
+    pub name: String,
+    /// Whether each type parameter is phantom. Phantom type parameters don't appear in the
+    /// layout of the struct, so they are left out of the generated types.
+    pub type_params: Vec<bool>,
+    pub fields: Vec<(String, MoveType)>,
+    /// Native structs have no fields to generate a layout from.
+    pub is_native: bool,
+    /// Whether the struct has the `key` ability, so it can be stored as a resource.
+    pub is_resource: bool,
+    /// Whether the struct is declared as an `#[event]`.
+    pub is_event: bool,
+}
+
+impl Struct {
+    /// The indices of the type parameters which appear in the layout of the struct.
+    pub fn layout_type_params(&self) -> impl Iterator<Item = usize> + '_ {
+        self.type_params
+            .iter()
+            .enumerate()
+            .filter(|(_, is_phantom)| !**is_phantom)
+            .map(|(idx, _)| idx)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub id: ModuleId,
+    /// The name of the generated file, without extension. This is the name of the module, suffixed
+    /// with its address if several modules have the same name or it is reserved.
+    pub file_name: String,
+    pub entry_functions: Vec<Function>,
+    pub view_functions: Vec<Function>,
+    pub structs: Vec<Struct>,
+}
+
+/// A module of the package, with the information the bytecode doesn't contain.
+pub struct ModuleSource<'a> {
+    pub module: &'a CompiledModule,
+    /// The names of the view functions, from the metadata of the module.
+    pub view_functions: BTreeSet<String>,
+    /// The names of the event structs, from the metadata of the module.
+    pub events: BTreeSet<String>,
+    /// The names of the parameters of functions, from the source map of the module. Parameters
+    /// without a name are named `arg0`, `arg1`, ...
+    pub param_names: BTreeMap<String, Vec<String>>,
+    /// The doc comments of functions, from the ABIs of the package.
+    pub docs: BTreeMap<String, String>,
+}
+
+/// The bindings of a package: the entry functions, view functions and structs of its modules, and
+/// the structs of its dependencies which they use.
+#[derive(Clone, Debug)]
+pub struct PackageBindings {
+    pub modules: Vec<Module>,
+}
+
+impl PackageBindings {
+    pub fn new(sources: &[ModuleSource], dependencies: &[&CompiledModule]) -> Self {
+        let mut modules: BTreeMap<ModuleId, Module> = BTreeMap::new();
+        let mut pending = Vec::new();
+        for source in sources {
+            let module = describe_module(source);
+            for function in module.entry_functions.iter().chain(&module.view_functions) {
+                for (_, ty) in &function.params {
+                    ty.visit_structs(&mut |s| pending.push(s.clone()));
+                }
+                for ty in &function.returns {
+                    ty.visit_structs(&mut |s| pending.push(s.clone()));
+                }
+            }
+            for s in &module.structs {
+                for (_, ty) in &s.fields {
+                    ty.visit_structs(&mut |s| pending.push(s.clone()));
+                }
+            }
+            modules.insert(module.id.clone(), module);
+        }
+
+        // Add the structs used from other modules, and the structs they use in turn
+        let mut visited = BTreeSet::new();
+        while let Some(s) = pending.pop() {
+            if s.well_known().is_some() || !visited.insert((s.module.clone(), s.name.clone())) {
+                continue;
+            }
+            let StructRef {
+                module: module_id,
+                name,
+                ..
+            } = s;
+            if let Some(module) = modules.get(&module_id) {
+                if module.structs.iter().any(|s| s.name == name) {
+                    continue;
+                }
+            }
+            let compiled = match sources
+                .iter()
+                .map(|source| source.module)
+                .chain(dependencies.iter().copied())
+                .find(|module| module.self_id() == module_id)
+            {
+                Some(compiled) => compiled,
+                // Structs whose module isn't available are generated as opaque values
+                None => continue,
+            };
+            let def = match compiled
+                .struct_defs()
+                .iter()
+                .find(|def| struct_name(compiled, def) == name)
+            {
+                Some(def) => def,
+                None => continue,
+            };
+            let s = describe_struct(compiled, def, &BTreeSet::new());
+            for (_, ty) in &s.fields {
+                ty.visit_structs(&mut |s| pending.push(s.clone()));
+            }
+            modules
+                .entry(module_id.clone())
+                .or_insert_with(|| Module {
+                    id: module_id.clone(),
+                    file_name: String::new(),
+                    entry_functions: vec![],
+                    view_functions: vec![],
+                    structs: vec![],
+                })
+                .structs
+                .push(s);
+        }
+
+        let mut modules: Vec<Module> = modules.into_values().collect();
+        let mut name_counts = BTreeMap::new();
+        for module in &modules {
+            *name_counts.entry(module.id.name().to_string()).or_insert(0) += 1;
+        }
+        for module in &mut modules {
+            module.structs.sort_by(|a, b| a.name.cmp(&b.name));
+            module.file_name = if name_counts[module.id.name().as_str()] > 1
+                || RESERVED_FILE_NAMES.contains(&module.id.name().as_str())
+            {
+                format!(
+                    "{}_{}",
+                    module.id.name(),
+                    module.id.address().short_str_lossless()
+                )
+            } else {
+                module.id.name().to_string()
+            };
+        }
+        Self { modules }
+    }
+
+    pub fn module(&self, id: &ModuleId) -> Option<&Module> {
+        self.modules.iter().find(|module| &module.id == id)
+    }
+
+    /// The definition of a struct, if it is part of the bindings.
+    pub fn find_struct(&self, s: &StructRef) -> Option<(&Module, &Struct)> {
+        let module = self.module(&s.module)?;
+        let def = module.structs.iter().find(|def| def.name == s.name)?;
+        Some((module, def))
+    }
+}
+
+fn describe_module(source: &ModuleSource) -> Module {
+    let module = source.module;
+    let mut entry_functions = vec![];
+    let mut view_functions = vec![];
+    for def in module.function_defs() {
+        let handle = module.function_handle_at(def.function);
+        let name = module.identifier_at(handle.name).to_string();
+        let is_view = source.view_functions.contains(&name);
+        if !def.is_entry && !is_view {
+            continue;
+        }
+        let names = source.param_names.get(&name);
+        let params: Vec<_> = module
+            .signature_at(handle.parameters)
+            .0
+            .iter()
+            .enumerate()
+            .map(|(idx, token)| {
+                let name = names
+                    .and_then(|names| names.get(idx).cloned())
+                    .unwrap_or_else(|| format!("arg{}", idx));
+                (name, move_type(module, token))
+            })
+            .collect();
+        let function = Function {
+            doc: source.docs.get(&name).cloned().unwrap_or_default(),
+            name,
+            type_params: handle
+                .type_parameters
+                .iter()
+                .map(|abilities| abilities.into_iter().map(|a| a.to_string()).collect())
+                .collect(),
+            params,
+            returns: module
+                .signature_at(handle.return_)
+                .0
+                .iter()
+                .map(|token| move_type(module, token))
+                .collect(),
+        };
+        if is_view && def.visibility == Visibility::Public {
+            view_functions.push(function.clone());
+        }
+        if def.is_entry {
+            let signers = function
+                .params
+                .iter()
+                .take_while(|(_, ty)| ty.is_signer())
+                .count();
+            entry_functions.push(Function {
+                params: function.params[signers..].to_vec(),
+                ..function
+            });
+        }
+    }
+    Module {
+        id: module.self_id(),
+        file_name: String::new(),
+        entry_functions,
+        view_functions,
+        structs: module
+            .struct_defs()
+            .iter()
+            .map(|def| describe_struct(module, def, &source.events))
+            .collect(),
+    }
+}
+
+fn struct_name(module: &CompiledModule, def: &StructDefinition) -> String {
+    let handle = module.struct_handle_at(def.struct_handle);
+    module.identifier_at(handle.name).to_string()
+}
+
+fn describe_struct(
+    module: &CompiledModule,
+    def: &StructDefinition,
+    events: &BTreeSet<String>,
+) -> Struct {
+    let handle = module.struct_handle_at(def.struct_handle);
+    let name = struct_name(module, def);
+    let (fields, is_native) = match &def.field_information {
+        StructFieldInformation::Native => (vec![], true),
+        StructFieldInformation::Declared(fields) => (
+            fields
+                .iter()
+                .map(|field| {
+                    (
+                        module.identifier_at(field.name).to_string(),
+                        move_type(module, &field.signature.0),
+                    )
+                })
+                .collect(),
+            false,
+        ),
+    };
+    Struct {
+        is_event: events.contains(&name),
+        name,
+        type_params: handle
+            .type_parameters
+            .iter()
+            .map(|param| param.is_phantom)
+            .collect(),
+        fields,
+        is_native,
+        is_resource: handle.abilities.has_key(),
+    }
+}
+
+fn move_type(module: &CompiledModule, token: &SignatureToken) -> MoveType {
+    use SignatureToken::*;
+    let struct_ref = |idx, type_args: &[SignatureToken]| {
+        let handle = module.struct_handle_at(idx);
+        MoveType::Struct(StructRef {
+            module: module.module_id_for_handle(module.module_handle_at(handle.module)),
+            name: module.identifier_at(handle.name).to_string(),
+            type_args: type_args.iter().map(|ty| move_type(module, ty)).collect(),
+        })
+    };
+    match token {
+        Bool => MoveType::Bool,
+        U8 => MoveType::U8,
+        U16 => MoveType::U16,
+        U32 => MoveType::U32,
+        U64 => MoveType::U64,
+        U128 => MoveType::U128,
+        U256 => MoveType::U256,
+        Address => MoveType::Address,
+        Signer => MoveType::Signer,
+        Vector(ty) => MoveType::Vector(Box::new(move_type(module, ty))),
+        Struct(idx) => struct_ref(*idx, &[]),
+        StructInstantiation(idx, type_args) => struct_ref(*idx, type_args),
+        Reference(ty) | MutableReference(ty) => {
+            MoveType::Reference(Box::new(move_type(module, ty)))
+        },
+        TypeParameter(idx) => MoveType::TypeParameter(*idx),
+    }
+}
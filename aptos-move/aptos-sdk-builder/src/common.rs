@@ -2,11 +2,12 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::bindings::{Module, PackageBindings};
 use aptos_types::transaction::{
     ArgumentABI, EntryABI, EntryFunctionABI, TransactionScriptABI, TypeArgumentABI,
 };
 use heck::ToUpperCamelCase;
-use move_core_types::language_storage::{StructTag, TypeTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use once_cell::sync::Lazy;
 use serde_reflection::{ContainerFormat, Format, Named, VariantFormat};
 use std::{
@@ -181,3 +182,36 @@ pub(crate) fn entry_function_abis(abis: &[EntryABI]) -> Vec<EntryFunctionABI> {
         })
         .collect::<Vec<_>>()
 }
+
+/// The other modules of the package whose structs the bindings of `module` use.
+pub(crate) fn used_modules(package: &PackageBindings, module: &Module) -> BTreeSet<ModuleId> {
+    let mut modules = BTreeSet::new();
+    let types = module
+        .structs
+        .iter()
+        .flat_map(|s| s.fields.iter().map(|(_, ty)| ty))
+        .chain(
+            module
+                .entry_functions
+                .iter()
+                .chain(&module.view_functions)
+                .flat_map(|function| {
+                    function
+                        .params
+                        .iter()
+                        .map(|(_, ty)| ty)
+                        .chain(&function.returns)
+                }),
+        );
+    for ty in types {
+        ty.visit_structs(&mut |s| {
+            if s.module != module.id
+                && s.well_known().is_none()
+                && package.find_struct(s).is_some()
+            {
+                modules.insert(s.module.clone());
+            }
+        });
+    }
+    modules
+}
@@ -5,8 +5,11 @@
 use aptos_types::transaction::EntryABI;
 use std::{ffi::OsStr, fs, io::Read, path::Path};
 
+pub mod bindings;
 pub mod golang;
+pub mod python;
 pub mod rust;
+pub mod typescript;
 
 /// Internals shared between languages.
 mod common;
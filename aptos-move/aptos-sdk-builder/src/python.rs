@@ -0,0 +1,639 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bindings::{
+        module_string, Function, Module, MoveType, PackageBindings, Struct, WellKnownStruct,
+    },
+    common,
+};
+use heck::ToSnakeCase;
+use move_core_types::language_storage::ModuleId;
+use serde_generate::indent::{IndentConfig, IndentedWriter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Result, Write},
+    path::PathBuf,
+};
+
+/// The runtime shared by the generated modules, in `codec.py`.
+const CODEC_RUNTIME: &str = r#"# This file is generated by `aptos move generate-bindings`. Do not edit it.
+from dataclasses import dataclass
+from typing import Any, Callable, Generic, List, Optional, TypeVar
+
+from aptos_sdk.account_address import AccountAddress
+from aptos_sdk.bcs import Deserializer, Serializer
+
+T = TypeVar("T")
+
+
+@dataclass
+class Codec(Generic[T]):
+    """Encodes and decodes the values of a Move type in BCS, and from and to the JSON of the API"""
+
+    serialize: Callable[[Serializer, T], None]
+    deserialize: Callable[[Deserializer], T]
+    from_json: Callable[[Any], T]
+    to_json: Callable[[T], Any]
+
+
+def _identity(value: Any) -> Any:
+    return value
+
+
+bool_ = Codec(Serializer.bool, Deserializer.bool, bool, _identity)
+u8 = Codec(Serializer.u8, Deserializer.u8, int, _identity)
+u16 = Codec(Serializer.u16, Deserializer.u16, int, _identity)
+u32 = Codec(Serializer.u32, Deserializer.u32, int, _identity)
+u64 = Codec(Serializer.u64, Deserializer.u64, int, str)
+u128 = Codec(Serializer.u128, Deserializer.u128, int, str)
+u256 = Codec(Serializer.u256, Deserializer.u256, int, str)
+address = Codec(
+    lambda serializer, value: value.serialize(serializer),
+    AccountAddress.deserialize,
+    AccountAddress.from_str,
+    str,
+)
+# `0x1::string::String`
+string = Codec(Serializer.str, Deserializer.str, str, _identity)
+# `vector<u8>`, which the API represents as a hex string
+bytes_ = Codec(
+    Serializer.to_bytes,
+    Deserializer.to_bytes,
+    lambda value: bytes.fromhex(value[2:]),
+    lambda value: "0x" + value.hex(),
+)
+
+
+def vector(element: Codec[T]) -> Codec[List[T]]:
+    return Codec(
+        lambda serializer, value: serializer.sequence(value, element.serialize),
+        lambda deserializer: deserializer.sequence(element.deserialize),
+        lambda value: [element.from_json(e) for e in value],
+        lambda value: [element.to_json(e) for e in value],
+    )
+
+
+def option(element: Codec[T]) -> Codec[Optional[T]]:
+    """`0x1::option::Option<T>`, which is a vector of at most one element"""
+    return Codec(
+        lambda serializer, value: vector(element).serialize(
+            serializer, [] if value is None else [value]
+        ),
+        lambda deserializer: next(iter(vector(element).deserialize(deserializer)), None),
+        lambda value: element.from_json(value["vec"][0]) if value["vec"] else None,
+        lambda value: {"vec": [] if value is None else [element.to_json(value)]},
+    )
+
+
+# `0x1::object::Object<T>`, which is the address of the object
+object_ = Codec(
+    address.serialize,
+    address.deserialize,
+    lambda value: AccountAddress.from_str(value["inner"]),
+    str,
+)
+
+
+def opaque(name: str) -> Codec[Any]:
+    """A type whose layout is not known, whose values are left as the JSON of the API"""
+
+    def fail(*_: Any) -> Any:
+        raise ValueError(f"The layout of {name} is not known")
+
+    return Codec(fail, fail, _identity, _identity)
+
+
+def to_bcs(codec: Codec[T], value: T) -> bytes:
+    serializer = Serializer()
+    codec.serialize(serializer, value)
+    return serializer.output()
+
+
+def from_bcs(codec: Codec[T], data: bytes) -> T:
+    return codec.deserialize(Deserializer(data))
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// The names of the generated functions use for themselves besides their parameters.
+const FUNCTION_LOCALS: &[&str] = &["client", "codec", "json", "result", "type_arguments"];
+
+fn escape(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn escape_parameter(name: &str) -> String {
+    if FUNCTION_LOCALS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        escape(name)
+    }
+}
+
+fn codec_name(struct_name: &str) -> String {
+    format!("{}_codec", struct_name.to_snake_case())
+}
+
+/// Output the Python bindings of a module, which import the runtime from `.codec` and the
+/// modules whose structs they use from `.<module>`.
+pub fn output(out: &mut dyn Write, package: &PackageBindings, module: &Module) -> Result<()> {
+    let mut emitter = PythonEmitter {
+        out: IndentedWriter::new(out, IndentConfig::Space(4)),
+        package,
+        module,
+        aliases: BTreeMap::new(),
+    };
+    emitter.output_preamble()?;
+    for s in &module.structs {
+        emitter.output_struct(s)?;
+    }
+    for function in &module.entry_functions {
+        emitter.output_entry_function(function)?;
+    }
+    for function in &module.view_functions {
+        emitter.output_view_function(function)?;
+    }
+    Ok(())
+}
+
+/// Shared state for the Python code generator.
+struct PythonEmitter<'a, T> {
+    /// Writer.
+    out: IndentedWriter<T>,
+    package: &'a PackageBindings,
+    module: &'a Module,
+    /// The names the other modules are imported as.
+    aliases: BTreeMap<ModuleId, String>,
+}
+
+impl<'a, T> PythonEmitter<'a, T>
+where
+    T: Write,
+{
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            "# This file is generated by `aptos move generate-bindings` from `{}`. Do not edit it.",
+            module_string(&self.module.id)
+        )?;
+        writeln!(
+            self.out,
+            r#"from __future__ import annotations
+
+import json
+from dataclasses import dataclass
+from typing import Any, ClassVar, Generic, List, Optional, Tuple, TypeVar
+
+from aptos_sdk.account_address import AccountAddress
+from aptos_sdk.async_client import RestClient
+from aptos_sdk.bcs import Deserializer, Serializer
+from aptos_sdk.transactions import EntryFunction, TransactionArgument
+from aptos_sdk.type_tag import TypeTag
+
+from . import codec"#
+        )?;
+
+        // Import the modules whose structs are used, under names which don't shadow ours
+        let mut local_names: BTreeSet<String> = ["codec".to_string(), "json".to_string()]
+            .into_iter()
+            .collect();
+        for s in &self.module.structs {
+            local_names.insert(s.name.clone());
+            local_names.insert(codec_name(&s.name));
+        }
+        for function in self
+            .module
+            .entry_functions
+            .iter()
+            .chain(&self.module.view_functions)
+        {
+            local_names.insert(escape(&function.name));
+        }
+        for id in common::used_modules(self.package, self.module) {
+            let file_name = &self.package.module(&id).unwrap().file_name;
+            let alias = if local_names.contains(file_name) || KEYWORDS.contains(&file_name.as_str())
+            {
+                writeln!(
+                    self.out,
+                    "from . import {} as {}_module",
+                    file_name, file_name
+                )?;
+                format!("{}_module", file_name)
+            } else {
+                writeln!(self.out, "from . import {}", file_name)?;
+                file_name.clone()
+            };
+            self.aliases.insert(id, alias);
+        }
+
+        let max_type_params = self
+            .module
+            .structs
+            .iter()
+            .map(|s| s.type_params.len())
+            .max()
+            .unwrap_or(0);
+        if max_type_params > 0 {
+            writeln!(self.out)?;
+            for idx in 0..max_type_params {
+                writeln!(self.out, "T{} = TypeVar(\"T{}\")", idx, idx)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn output_comment(&mut self, doc: &str) -> Result<()> {
+        let doc = common::prepare_doc_string(doc);
+        if doc.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "\"\"\"")?;
+        for line in doc.lines() {
+            writeln!(self.out, "{}", line.replace("\"\"\"", "\\\"\\\"\\\""))?;
+        }
+        writeln!(self.out, "\"\"\"")
+    }
+
+    /// The name of a definition of the module `id`, qualified if it is imported.
+    fn qualified(&self, id: &ModuleId, name: &str) -> String {
+        match self.aliases.get(id) {
+            Some(alias) => format!("{}.{}", alias, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The Python type of the values of `ty`. Type parameters are generic only in structs.
+    fn quote_type(&self, ty: &MoveType, in_struct: bool) -> String {
+        use MoveType::*;
+        match ty {
+            Bool => "bool".into(),
+            U8 | U16 | U32 | U64 | U128 | U256 => "int".into(),
+            Address | Signer => "AccountAddress".into(),
+            Vector(ty) if **ty == U8 => "bytes".into(),
+            Vector(ty) => format!("List[{}]", self.quote_type(ty, in_struct)),
+            Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::String) => "str".into(),
+                Some(WellKnownStruct::Option) => {
+                    format!("Optional[{}]", self.quote_type(&s.type_args[0], in_struct))
+                },
+                Some(WellKnownStruct::Object) => "AccountAddress".into(),
+                None => match self.package.find_struct(s) {
+                    Some((module, def)) => {
+                        let name = self.qualified(&module.id, &s.name);
+                        let type_args: Vec<_> = def
+                            .layout_type_params()
+                            .map(|idx| self.quote_type(&s.type_args[idx], in_struct))
+                            .collect();
+                        if type_args.is_empty() || def.is_native {
+                            name
+                        } else {
+                            format!("{}[{}]", name, type_args.join(", "))
+                        }
+                    },
+                    None => "Any".into(),
+                },
+            },
+            TypeParameter(idx) if in_struct => format!("T{}", idx),
+            TypeParameter(_) => "Any".into(),
+            Reference(ty) => self.quote_type(ty, in_struct),
+        }
+    }
+
+    /// The codec of the values of `ty`.
+    fn quote_codec(&self, ty: &MoveType, in_struct: bool) -> String {
+        use MoveType::*;
+        match ty {
+            Bool => "codec.bool_".into(),
+            U8 => "codec.u8".into(),
+            U16 => "codec.u16".into(),
+            U32 => "codec.u32".into(),
+            U64 => "codec.u64".into(),
+            U128 => "codec.u128".into(),
+            U256 => "codec.u256".into(),
+            Address => "codec.address".into(),
+            Vector(ty) if **ty == U8 => "codec.bytes_".into(),
+            Vector(ty) => format!("codec.vector({})", self.quote_codec(ty, in_struct)),
+            Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::String) => "codec.string".into(),
+                Some(WellKnownStruct::Option) => {
+                    format!(
+                        "codec.option({})",
+                        self.quote_codec(&s.type_args[0], in_struct)
+                    )
+                },
+                Some(WellKnownStruct::Object) => "codec.object_".into(),
+                None => match self.package.find_struct(s) {
+                    Some((module, def)) if !def.is_native => format!(
+                        "{}({})",
+                        self.qualified(&module.id, &codec_name(&s.name)),
+                        def.layout_type_params()
+                            .map(|idx| self.quote_codec(&s.type_args[idx], in_struct))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    _ => format!("codec.opaque(\"{}\")", ty.to_move_string()),
+                },
+            },
+            TypeParameter(idx) if in_struct => format!("t{}", idx),
+            Reference(ty) => self.quote_codec(ty, in_struct),
+            Signer | TypeParameter(_) => format!("codec.opaque(\"{}\")", ty.to_move_string()),
+        }
+    }
+
+    fn output_struct(&mut self, s: &Struct) -> Result<()> {
+        let name = format!("{}::{}", module_string(&self.module.id), s.name);
+        let kind = match (s.is_resource, s.is_event) {
+            (true, _) => ", a resource",
+            (false, true) => ", an event",
+            (false, false) => "",
+        };
+
+        writeln!(self.out, "\n")?;
+        if s.is_native {
+            writeln!(self.out, "# `{}`{}", name, kind)?;
+            writeln!(self.out, "{} = Any", s.name)?;
+            return Ok(());
+        }
+        let type_params: Vec<_> = s
+            .layout_type_params()
+            .map(|idx| format!("T{}", idx))
+            .collect();
+        let (generics, generic_base) = if type_params.is_empty() {
+            (String::new(), String::new())
+        } else {
+            (
+                format!("[{}]", type_params.join(", ")),
+                format!("(Generic[{}])", type_params.join(", ")),
+            )
+        };
+        let value_type = format!("{}{}", s.name, generics);
+
+        writeln!(self.out, "@dataclass")?;
+        writeln!(self.out, "class {}{}:", s.name, generic_base)?;
+        self.out.indent();
+        writeln!(self.out, "\"\"\"`{}`{}\"\"\"\n", name, kind)?;
+        if s.is_resource || s.is_event {
+            writeln!(self.out, "TYPE: ClassVar[str] = \"{}\"\n", name)?;
+        }
+        for (field, ty) in &s.fields {
+            writeln!(self.out, "{}: {}", escape(field), self.quote_type(ty, true))?;
+        }
+        self.out.unindent();
+
+        let codec_params: Vec<_> = s
+            .layout_type_params()
+            .map(|idx| format!("t{}: codec.Codec[T{}]", idx, idx))
+            .collect();
+        writeln!(
+            self.out,
+            "\n\ndef {}({}) -> codec.Codec[{}]:",
+            codec_name(&s.name),
+            codec_params.join(", "),
+            value_type
+        )?;
+        self.out.indent();
+
+        writeln!(
+            self.out,
+            "def serialize(serializer: Serializer, value: {}) -> None:",
+            value_type
+        )?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}.serialize(serializer, value.{})",
+                self.quote_codec(ty, true),
+                escape(field)
+            )?;
+        }
+        self.out.unindent();
+
+        writeln!(
+            self.out,
+            "\ndef deserialize(deserializer: Deserializer) -> {}:",
+            value_type
+        )?;
+        self.out.indent();
+        writeln!(self.out, "return {}(", s.name)?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}={}.deserialize(deserializer),",
+                escape(field),
+                self.quote_codec(ty, true)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, ")")?;
+        self.out.unindent();
+
+        writeln!(self.out, "\ndef from_json(value: Any) -> {}:", value_type)?;
+        self.out.indent();
+        writeln!(self.out, "return {}(", s.name)?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}={}.from_json(value[\"{}\"]),",
+                escape(field),
+                self.quote_codec(ty, true),
+                field
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, ")")?;
+        self.out.unindent();
+
+        writeln!(self.out, "\ndef to_json(value: {}) -> Any:", value_type)?;
+        self.out.indent();
+        writeln!(self.out, "return {{")?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "\"{}\": {}.to_json(value.{}),",
+                field,
+                self.quote_codec(ty, true),
+                escape(field)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+
+        writeln!(
+            self.out,
+            "\nreturn codec.Codec(serialize, deserialize, from_json, to_json)"
+        )?;
+        self.out.unindent();
+        Ok(())
+    }
+
+    /// The parameters shared by entry and view functions: the arguments and type arguments.
+    fn quote_parameters(&self, function: &Function, type_argument: &str) -> Vec<String> {
+        let mut params: Vec<_> = function
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", escape_parameter(name), self.quote_type(ty, false)))
+            .collect();
+        if !function.type_params.is_empty() {
+            params.push(format!("type_arguments: List[{}]", type_argument));
+        }
+        params
+    }
+
+    fn quote_type_arguments(function: &Function) -> &'static str {
+        if function.type_params.is_empty() {
+            "[]"
+        } else {
+            "type_arguments"
+        }
+    }
+
+    fn output_entry_function(&mut self, function: &Function) -> Result<()> {
+        writeln!(self.out, "\n")?;
+        writeln!(
+            self.out,
+            "def {}({}) -> EntryFunction:",
+            escape(&function.name),
+            self.quote_parameters(function, "TypeTag").join(", ")
+        )?;
+        self.out.indent();
+        self.output_comment(&function.doc)?;
+        writeln!(self.out, "return EntryFunction.natural(")?;
+        self.out.indent();
+        writeln!(self.out, "\"{}\",", module_string(&self.module.id))?;
+        writeln!(self.out, "\"{}\",", function.name)?;
+        writeln!(self.out, "{},", Self::quote_type_arguments(function))?;
+        writeln!(self.out, "[")?;
+        self.out.indent();
+        for (name, ty) in &function.params {
+            writeln!(
+                self.out,
+                "TransactionArgument({}, {}.serialize),",
+                escape_parameter(name),
+                self.quote_codec(ty, false)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "],")?;
+        self.out.unindent();
+        writeln!(self.out, ")")?;
+        self.out.unindent();
+        Ok(())
+    }
+
+    fn output_view_function(&mut self, function: &Function) -> Result<()> {
+        let returns: Vec<_> = function
+            .returns
+            .iter()
+            .map(|ty| self.quote_type(ty, false))
+            .collect();
+        let return_type = match returns.as_slice() {
+            [ty] => ty.clone(),
+            [] => "Tuple[()]".into(),
+            _ => format!("Tuple[{}]", returns.join(", ")),
+        };
+        let mut params = vec!["client: RestClient".to_string()];
+        params.extend(self.quote_parameters(function, "str"));
+
+        writeln!(self.out, "\n")?;
+        writeln!(
+            self.out,
+            "async def {}({}) -> {}:",
+            escape(&function.name),
+            params.join(", "),
+            return_type
+        )?;
+        self.out.indent();
+        self.output_comment(&function.doc)?;
+        writeln!(self.out, "result = json.loads(")?;
+        self.out.indent();
+        writeln!(self.out, "await client.view(")?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "\"{}::{}\",",
+            module_string(&self.module.id),
+            function.name
+        )?;
+        writeln!(self.out, "{},", Self::quote_type_arguments(function))?;
+        writeln!(self.out, "[")?;
+        self.out.indent();
+        for (name, ty) in &function.params {
+            writeln!(
+                self.out,
+                "{}.to_json({}),",
+                self.quote_codec(ty, false),
+                escape_parameter(name)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "],")?;
+        self.out.unindent();
+        writeln!(self.out, ")")?;
+        self.out.unindent();
+        writeln!(self.out, ")")?;
+        let values: Vec<_> = function
+            .returns
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| format!("{}.from_json(result[{}])", self.quote_codec(ty, false), idx))
+            .collect();
+        match values.as_slice() {
+            [value] => writeln!(self.out, "return {}", value)?,
+            [] => writeln!(self.out, "return ()")?,
+            _ => writeln!(self.out, "return ({})", values.join(", "))?,
+        }
+        self.out.unindent();
+        Ok(())
+    }
+}
+
+pub struct Installer {
+    install_dir: PathBuf,
+}
+
+impl Installer {
+    pub fn new(install_dir: PathBuf) -> Self {
+        Installer { install_dir }
+    }
+
+    /// Write a Python package with `codec.py` and a file per module, returning the paths of the
+    /// files written.
+    pub fn install_bindings(&self, package: &PackageBindings) -> Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(&self.install_dir)?;
+        let mut paths = vec![];
+
+        let path = self.install_dir.join("__init__.py");
+        std::fs::write(
+            &path,
+            "# This file is generated by `aptos move generate-bindings`. Do not edit it.\n",
+        )?;
+        paths.push(path);
+
+        let path = self.install_dir.join("codec.py");
+        std::fs::write(&path, CODEC_RUNTIME)?;
+        paths.push(path);
+
+        for module in &package.modules {
+            let path = self.install_dir.join(format!("{}.py", module.file_name));
+            let mut file = std::fs::File::create(&path)?;
+            output(&mut file, package, module)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,742 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bindings::{
+        module_string, Function, Module, MoveType, PackageBindings, Struct, WellKnownStruct,
+    },
+    common,
+};
+use move_core_types::language_storage::ModuleId;
+use serde_generate::indent::{IndentConfig, IndentedWriter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Result, Write},
+    path::PathBuf,
+};
+
+/// The runtime shared by the generated modules, in `codec.ts`.
+const CODEC_RUNTIME: &str = r#"// This file is generated by `aptos move generate-bindings`. Do not edit it.
+import { AccountAddress, Deserializer, Hex, Serializer } from "@aptos-labs/ts-sdk";
+
+/** Encodes and decodes the values of a Move type in BCS, and decodes them from the JSON of the API */
+export interface Codec<T> {
+  serialize(serializer: Serializer, value: T): void;
+  deserialize(deserializer: Deserializer): T;
+  fromJson(json: any): T;
+}
+
+export const bool: Codec<boolean> = {
+  serialize: (s, v) => s.serializeBool(v),
+  deserialize: (d) => d.deserializeBool(),
+  fromJson: (json) => json as boolean,
+};
+
+export const u8: Codec<number> = {
+  serialize: (s, v) => s.serializeU8(v),
+  deserialize: (d) => d.deserializeU8(),
+  fromJson: (json) => Number(json),
+};
+
+export const u16: Codec<number> = {
+  serialize: (s, v) => s.serializeU16(v),
+  deserialize: (d) => d.deserializeU16(),
+  fromJson: (json) => Number(json),
+};
+
+export const u32: Codec<number> = {
+  serialize: (s, v) => s.serializeU32(v),
+  deserialize: (d) => d.deserializeU32(),
+  fromJson: (json) => Number(json),
+};
+
+export const u64: Codec<bigint> = {
+  serialize: (s, v) => s.serializeU64(v),
+  deserialize: (d) => d.deserializeU64(),
+  fromJson: (json) => BigInt(json),
+};
+
+export const u128: Codec<bigint> = {
+  serialize: (s, v) => s.serializeU128(v),
+  deserialize: (d) => d.deserializeU128(),
+  fromJson: (json) => BigInt(json),
+};
+
+export const u256: Codec<bigint> = {
+  serialize: (s, v) => s.serializeU256(v),
+  deserialize: (d) => d.deserializeU256(),
+  fromJson: (json) => BigInt(json),
+};
+
+export const address: Codec<AccountAddress> = {
+  serialize: (s, v) => v.serialize(s),
+  deserialize: (d) => AccountAddress.deserialize(d),
+  fromJson: (json) => AccountAddress.from(json),
+};
+
+/** `0x1::string::String` */
+export const string: Codec<string> = {
+  serialize: (s, v) => s.serializeStr(v),
+  deserialize: (d) => d.deserializeStr(),
+  fromJson: (json) => json as string,
+};
+
+/** `vector<u8>`, which the API returns as a hex string */
+export const bytes: Codec<Uint8Array> = {
+  serialize: (s, v) => s.serializeBytes(v),
+  deserialize: (d) => d.deserializeBytes(),
+  fromJson: (json) => Hex.fromHexInput(json).toUint8Array(),
+};
+
+export function vector<T>(element: Codec<T>): Codec<T[]> {
+  return {
+    serialize: (s, v) => {
+      s.serializeU32AsUleb128(v.length);
+      v.forEach((e) => element.serialize(s, e));
+    },
+    deserialize: (d) => {
+      const length = d.deserializeUleb128AsU32();
+      return Array.from({ length }, () => element.deserialize(d));
+    },
+    fromJson: (json) => (json as any[]).map((e) => element.fromJson(e)),
+  };
+}
+
+/** `0x1::option::Option<T>`, which is a vector of at most one element */
+export function option<T>(element: Codec<T>): Codec<T | undefined> {
+  return {
+    serialize: (s, v) => vector(element).serialize(s, v === undefined ? [] : [v]),
+    deserialize: (d) => vector(element).deserialize(d)[0],
+    fromJson: (json) => (json.vec.length === 0 ? undefined : element.fromJson(json.vec[0])),
+  };
+}
+
+/** `0x1::object::Object<T>`, which is the address of the object */
+export const object: Codec<AccountAddress> = {
+  serialize: (s, v) => v.serialize(s),
+  deserialize: (d) => AccountAddress.deserialize(d),
+  fromJson: (json) => AccountAddress.from(json.inner),
+};
+
+/** A type whose layout is not known, whose values are left as the JSON of the API */
+export function opaque(name: string): Codec<any> {
+  return {
+    serialize: () => {
+      throw new Error(`The layout of ${name} is not known`);
+    },
+    deserialize: () => {
+      throw new Error(`The layout of ${name} is not known`);
+    },
+    fromJson: (json) => json,
+  };
+}
+
+export function toBcs<T>(codec: Codec<T>, value: T): Uint8Array {
+  const serializer = new Serializer();
+  codec.serialize(serializer, value);
+  return serializer.toUint8Array();
+}
+
+export function fromBcs<T>(codec: Codec<T>, bytes: Uint8Array): T {
+  return codec.deserialize(new Deserializer(bytes));
+}
+"#;
+
+const RESERVED_WORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+fn escape(name: &str) -> String {
+    if RESERVED_WORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Output the TypeScript bindings of a module, which import the runtime from `./codec` and the
+/// modules whose structs they use from `./<module>`.
+pub fn output(out: &mut dyn Write, package: &PackageBindings, module: &Module) -> Result<()> {
+    let mut emitter = TypeScriptEmitter {
+        out: IndentedWriter::new(out, IndentConfig::Space(2)),
+        package,
+        module,
+        aliases: BTreeMap::new(),
+    };
+    emitter.output_preamble()?;
+    for s in &module.structs {
+        emitter.output_struct(s)?;
+    }
+    for function in &module.entry_functions {
+        emitter.output_entry_function(function)?;
+    }
+    for function in &module.view_functions {
+        emitter.output_view_function(function)?;
+    }
+    Ok(())
+}
+
+/// Shared state for the TypeScript code generator.
+struct TypeScriptEmitter<'a, T> {
+    /// Writer.
+    out: IndentedWriter<T>,
+    package: &'a PackageBindings,
+    module: &'a Module,
+    /// The names the other modules are imported as.
+    aliases: BTreeMap<ModuleId, String>,
+}
+
+impl<'a, T> TypeScriptEmitter<'a, T>
+where
+    T: Write,
+{
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            "// This file is generated by `aptos move generate-bindings` from `{}`. Do not edit it.",
+            module_string(&self.module.id)
+        )?;
+        writeln!(
+            self.out,
+            r#"import {{
+  AccountAddress,
+  AccountAddressInput,
+  Aptos,
+  Bool,
+  InputEntryFunctionData,
+  MoveOption,
+  MoveString,
+  MoveVector,
+  TypeTag,
+  U128,
+  U16,
+  U256,
+  U32,
+  U64,
+  U8,
+  parseTypeTag,
+}} from "@aptos-labs/ts-sdk";
+import * as codec from "./codec";"#
+        )?;
+
+        // Import the modules whose structs are used, under names which don't shadow ours
+        let mut local_names: BTreeSet<String> = ["codec".to_string()].into_iter().collect();
+        for s in &self.module.structs {
+            local_names.insert(s.name.clone());
+            local_names.insert(format!("{}Codec", s.name));
+            local_names.insert(format!("{}Type", s.name));
+        }
+        for function in self
+            .module
+            .entry_functions
+            .iter()
+            .chain(&self.module.view_functions)
+        {
+            local_names.insert(escape(&function.name));
+        }
+        for id in common::used_modules(self.package, self.module) {
+            let file_name = &self.package.module(&id).unwrap().file_name;
+            let alias = if local_names.contains(file_name) {
+                format!("{}_module", file_name)
+            } else {
+                file_name.clone()
+            };
+            writeln!(self.out, "import * as {} from \"./{}\";", alias, file_name)?;
+            self.aliases.insert(id, alias);
+        }
+        Ok(())
+    }
+
+    fn output_comment(&mut self, doc: &str) -> Result<()> {
+        let doc = common::prepare_doc_string(doc);
+        if doc.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.out, "/**")?;
+        for line in doc.lines() {
+            writeln!(self.out, " * {}", line)?;
+        }
+        writeln!(self.out, " */")
+    }
+
+    /// The name of a definition of the module `id`, qualified if it is imported.
+    fn qualified(&self, id: &ModuleId, name: &str) -> String {
+        match self.aliases.get(id) {
+            Some(alias) => format!("{}.{}", alias, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The TypeScript type of the values of `ty`. Type parameters are generic only in structs.
+    fn quote_type(&self, ty: &MoveType, in_struct: bool) -> String {
+        use MoveType::*;
+        match ty {
+            Bool => "boolean".into(),
+            U8 | U16 | U32 => "number".into(),
+            U64 | U128 | U256 => "bigint".into(),
+            Address | Signer => "AccountAddress".into(),
+            Vector(ty) if **ty == U8 => "Uint8Array".into(),
+            Vector(ty) => format!("Array<{}>", self.quote_type(ty, in_struct)),
+            Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::String) => "string".into(),
+                Some(WellKnownStruct::Option) => {
+                    format!(
+                        "{} | undefined",
+                        self.quote_type(&s.type_args[0], in_struct)
+                    )
+                },
+                Some(WellKnownStruct::Object) => "AccountAddress".into(),
+                None => match self.package.find_struct(s) {
+                    Some((module, def)) => {
+                        let name = self.qualified(&module.id, &s.name);
+                        let type_args: Vec<_> = def
+                            .layout_type_params()
+                            .map(|idx| self.quote_type(&s.type_args[idx], in_struct))
+                            .collect();
+                        if type_args.is_empty() {
+                            name
+                        } else {
+                            format!("{}<{}>", name, type_args.join(", "))
+                        }
+                    },
+                    None => "any".into(),
+                },
+            },
+            TypeParameter(idx) if in_struct => format!("T{}", idx),
+            TypeParameter(_) => "any".into(),
+            Reference(ty) => self.quote_type(ty, in_struct),
+        }
+    }
+
+    /// The codec of the values of `ty`.
+    fn quote_codec(&self, ty: &MoveType, in_struct: bool) -> String {
+        use MoveType::*;
+        match ty {
+            Bool => "codec.bool".into(),
+            U8 => "codec.u8".into(),
+            U16 => "codec.u16".into(),
+            U32 => "codec.u32".into(),
+            U64 => "codec.u64".into(),
+            U128 => "codec.u128".into(),
+            U256 => "codec.u256".into(),
+            Address => "codec.address".into(),
+            Vector(ty) if **ty == U8 => "codec.bytes".into(),
+            Vector(ty) => format!("codec.vector({})", self.quote_codec(ty, in_struct)),
+            Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::String) => "codec.string".into(),
+                Some(WellKnownStruct::Option) => {
+                    format!(
+                        "codec.option({})",
+                        self.quote_codec(&s.type_args[0], in_struct)
+                    )
+                },
+                Some(WellKnownStruct::Object) => "codec.object".into(),
+                None => match self.package.find_struct(s) {
+                    Some((module, def)) if !def.is_native => format!(
+                        "{}({})",
+                        self.qualified(&module.id, &format!("{}Codec", s.name)),
+                        def.layout_type_params()
+                            .map(|idx| self.quote_codec(&s.type_args[idx], in_struct))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    _ => format!("codec.opaque(\"{}\")", ty.to_move_string()),
+                },
+            },
+            TypeParameter(idx) if in_struct => format!("t{}", idx),
+            Reference(ty) => self.quote_codec(ty, in_struct),
+            Signer | TypeParameter(_) => format!("codec.opaque(\"{}\")", ty.to_move_string()),
+        }
+    }
+
+    /// The TypeScript type of the arguments of type `ty` passed to functions.
+    fn quote_argument_type(&self, ty: &MoveType) -> String {
+        match ty {
+            MoveType::Address => "AccountAddressInput".into(),
+            MoveType::Vector(inner) if **inner != MoveType::U8 => {
+                format!("Array<{}>", self.quote_argument_type(inner))
+            },
+            MoveType::Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::Option) => {
+                    format!("{} | undefined", self.quote_argument_type(&s.type_args[0]))
+                },
+                Some(WellKnownStruct::Object) => "AccountAddressInput".into(),
+                _ => self.quote_type(ty, false),
+            },
+            _ => self.quote_type(ty, false),
+        }
+    }
+
+    /// Converts the expression `value` of type `ty` to an argument of a transaction or view.
+    fn quote_argument(&self, ty: &MoveType, value: &str) -> String {
+        use MoveType::*;
+        match ty {
+            Bool => format!("new Bool({})", value),
+            U8 => format!("new U8({})", value),
+            U16 => format!("new U16({})", value),
+            U32 => format!("new U32({})", value),
+            U64 => format!("new U64({})", value),
+            U128 => format!("new U128({})", value),
+            U256 => format!("new U256({})", value),
+            Address => format!("AccountAddress.from({})", value),
+            Vector(ty) if **ty == U8 => format!("MoveVector.U8({})", value),
+            Vector(ty) => format!(
+                "new MoveVector({}.map((e) => {}))",
+                value,
+                self.quote_argument(ty, "e")
+            ),
+            Struct(s) => match s.well_known() {
+                Some(WellKnownStruct::String) => format!("new MoveString({})", value),
+                Some(WellKnownStruct::Option) => format!(
+                    "new MoveOption({} === undefined ? undefined : {})",
+                    value,
+                    self.quote_argument(&s.type_args[0], value)
+                ),
+                Some(WellKnownStruct::Object) => format!("AccountAddress.from({})", value),
+                None => value.to_string(),
+            },
+            _ => value.to_string(),
+        }
+    }
+
+    fn output_struct(&mut self, s: &Struct) -> Result<()> {
+        let name = format!("{}::{}", module_string(&self.module.id), s.name);
+        let kind = match (s.is_resource, s.is_event) {
+            (true, _) => ", a resource",
+            (false, true) => ", an event",
+            (false, false) => "",
+        };
+        let type_params: Vec<_> = s
+            .layout_type_params()
+            .map(|idx| format!("T{}", idx))
+            .collect();
+        let generics = if type_params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", type_params.join(", "))
+        };
+
+        writeln!(self.out)?;
+        if s.is_resource || s.is_event {
+            writeln!(self.out, "export const {}Type = \"{}\";\n", s.name, name)?;
+        }
+        writeln!(self.out, "/** `{}`{} */", name, kind)?;
+        if s.is_native {
+            writeln!(self.out, "export type {}{} = any;", s.name, generics)?;
+            return Ok(());
+        }
+        writeln!(self.out, "export interface {}{} {{", s.name, generics)?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(self.out, "{}: {};", field, self.quote_type(ty, true))?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        let codec_params: Vec<_> = s
+            .layout_type_params()
+            .map(|idx| format!("t{}: codec.Codec<T{}>", idx, idx))
+            .collect();
+        writeln!(
+            self.out,
+            "\nexport function {}Codec{}({}): codec.Codec<{}{}> {{",
+            s.name,
+            generics,
+            codec_params.join(", "),
+            s.name,
+            generics
+        )?;
+        self.out.indent();
+        writeln!(self.out, "return {{")?;
+        self.out.indent();
+        writeln!(self.out, "serialize: (s, v) => {{")?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}.serialize(s, v.{});",
+                self.quote_codec(ty, true),
+                field
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}},")?;
+        writeln!(self.out, "deserialize: (d) => ({{")?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}: {}.deserialize(d),",
+                field,
+                self.quote_codec(ty, true)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}),")?;
+        writeln!(self.out, "fromJson: (json) => ({{")?;
+        self.out.indent();
+        for (field, ty) in &s.fields {
+            writeln!(
+                self.out,
+                "{}: {}.fromJson(json.{}),",
+                field,
+                self.quote_codec(ty, true),
+                field
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}),")?;
+        self.out.unindent();
+        writeln!(self.out, "}};")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    /// The parameters shared by entry and view functions: the arguments and type arguments.
+    fn quote_parameters(&self, function: &Function) -> Vec<String> {
+        let mut params = vec![];
+        if !function.params.is_empty() {
+            params.push(format!(
+                "args: {{ {} }}",
+                function
+                    .params
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {}", name, self.quote_argument_type(ty)))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ));
+        }
+        if !function.type_params.is_empty() {
+            params.push(format!(
+                "typeArguments: [{}]",
+                vec!["TypeTag | string"; function.type_params.len()].join(", ")
+            ));
+        }
+        params
+    }
+
+    /// The fields of the payload of an entry function or view function call.
+    fn output_payload(&mut self, function: &Function, is_view: bool) -> Result<()> {
+        writeln!(
+            self.out,
+            "function: \"{}::{}\",",
+            module_string(&self.module.id),
+            function.name
+        )?;
+        if function.type_params.is_empty() {
+            writeln!(self.out, "typeArguments: [],")?;
+        } else {
+            writeln!(self.out, "typeArguments,")?;
+        }
+        let args: Vec<_> = function
+            .params
+            .iter()
+            .map(|(name, ty)| self.quote_argument(ty, &format!("args.{}", name)))
+            .collect();
+        writeln!(self.out, "functionArguments: [{}],", args.join(", "))?;
+
+        let parse = |ty: &MoveType| {
+            format!(
+                "parseTypeTag(\"{}\", {{ allowGenerics: true }})",
+                ty.to_move_string()
+            )
+        };
+        writeln!(self.out, "abi: {{")?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "typeParameters: [{}],",
+            function
+                .type_params
+                .iter()
+                .map(|abilities| format!(
+                    "{{ constraints: [{}] }}",
+                    abilities
+                        .iter()
+                        .map(|ability| format!("\"{}\"", ability))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            self.out,
+            "parameters: [{}],",
+            function
+                .params
+                .iter()
+                .map(|(_, ty)| parse(ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if is_view {
+            writeln!(
+                self.out,
+                "returnTypes: [{}],",
+                function
+                    .returns
+                    .iter()
+                    .map(parse)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}},")
+    }
+
+    fn output_entry_function(&mut self, function: &Function) -> Result<()> {
+        writeln!(self.out)?;
+        self.output_comment(&function.doc)?;
+        writeln!(
+            self.out,
+            "export function {}({}): InputEntryFunctionData {{",
+            escape(&function.name),
+            self.quote_parameters(function).join(", ")
+        )?;
+        self.out.indent();
+        writeln!(self.out, "return {{")?;
+        self.out.indent();
+        self.output_payload(function, false)?;
+        self.out.unindent();
+        writeln!(self.out, "}};")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_view_function(&mut self, function: &Function) -> Result<()> {
+        let returns: Vec<_> = function
+            .returns
+            .iter()
+            .map(|ty| self.quote_type(ty, false))
+            .collect();
+        let return_type = match returns.as_slice() {
+            [ty] => ty.clone(),
+            _ => format!("[{}]", returns.join(", ")),
+        };
+        let mut params = vec!["aptos: Aptos".to_string()];
+        params.extend(self.quote_parameters(function));
+
+        writeln!(self.out)?;
+        self.output_comment(&function.doc)?;
+        writeln!(
+            self.out,
+            "export async function {}({}): Promise<{}> {{",
+            escape(&function.name),
+            params.join(", "),
+            return_type
+        )?;
+        self.out.indent();
+        writeln!(self.out, "const result = await aptos.view({{")?;
+        self.out.indent();
+        writeln!(self.out, "payload: {{")?;
+        self.out.indent();
+        self.output_payload(function, true)?;
+        self.out.unindent();
+        writeln!(self.out, "}},")?;
+        self.out.unindent();
+        writeln!(self.out, "}});")?;
+        let values: Vec<_> = function
+            .returns
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| format!("{}.fromJson(result[{}])", self.quote_codec(ty, false), idx))
+            .collect();
+        match values.as_slice() {
+            [value] => writeln!(self.out, "return {};", value)?,
+            _ => writeln!(self.out, "return [{}];", values.join(", "))?,
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+}
+
+pub struct Installer {
+    install_dir: PathBuf,
+}
+
+impl Installer {
+    pub fn new(install_dir: PathBuf) -> Self {
+        Installer { install_dir }
+    }
+
+    /// Write `codec.ts`, a file per module and `index.ts` exporting them, returning the paths of
+    /// the files written.
+    pub fn install_bindings(&self, package: &PackageBindings) -> Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(&self.install_dir)?;
+        let mut paths = vec![];
+
+        let path = self.install_dir.join("codec.ts");
+        std::fs::write(&path, CODEC_RUNTIME)?;
+        paths.push(path);
+
+        let mut index = String::from(
+            "// This file is generated by `aptos move generate-bindings`. Do not edit it.\n\
+             export * as codec from \"./codec\";\n",
+        );
+        for module in &package.modules {
+            let path = self.install_dir.join(format!("{}.ts", module.file_name));
+            let mut file = std::fs::File::create(&path)?;
+            output(&mut file, package, module)?;
+            paths.push(path);
+            index.push_str(&format!(
+                "export * as {} from \"./{}\";\n",
+                escape(&module.file_name),
+                module.file_name
+            ));
+        }
+
+        let path = self.install_dir.join("index.ts");
+        std::fs::write(&path, index)?;
+        paths.push(path);
+        Ok(paths)
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_framework::get_metadata_from_compiled_module;
+use aptos_sdk_builder::{
+    bindings::{Module, ModuleSource, MoveType, PackageBindings, Struct},
+    python, typescript,
+};
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use serde::Serialize;
+use std::{collections::BTreeMap, io::Write, process::Command};
+use tempfile::tempdir;
+
+/// The bindings of `0x1::coin`, with the structs it uses from the rest of the framework
+fn coin_bindings() -> PackageBindings {
+    let modules = aptos_cached_packages::head_release_bundle().compiled_modules();
+    let coin = modules
+        .iter()
+        .find(|module| module.self_id().name().as_str() == "coin")
+        .unwrap();
+    let metadata = get_metadata_from_compiled_module(coin).unwrap();
+    let source = ModuleSource {
+        module: coin,
+        view_functions: metadata
+            .fun_attributes
+            .iter()
+            .filter(|(_, attrs)| attrs.iter().any(|attr| attr.is_view_function()))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        events: metadata
+            .struct_attributes
+            .iter()
+            .filter(|(_, attrs)| attrs.iter().any(|attr| attr.is_event()))
+            .map(|(name, _)| name.clone())
+            .collect(),
+        param_names: BTreeMap::new(),
+        docs: BTreeMap::new(),
+    };
+    let dependencies: Vec<&CompiledModule> = modules.iter().collect();
+    PackageBindings::new(&[source], &dependencies)
+}
+
+type Output = fn(&mut dyn Write, &PackageBindings, &Module) -> std::io::Result<()>;
+
+fn generate(output: Output) -> String {
+    let bindings = coin_bindings();
+    let coin = bindings
+        .modules
+        .iter()
+        .find(|module| module.id.name().as_str() == "coin")
+        .unwrap();
+    let mut out = Vec::new();
+    output(&mut out, &bindings, coin).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn assert_contains(source: &str, expected: &str) {
+    assert!(
+        source.contains(expected),
+        "Expected to find:\n{}\nin:\n{}",
+        expected,
+        source
+    );
+}
+
+#[test]
+fn test_typescript() {
+    let source = generate(typescript::output);
+    assert_contains(&source, "import * as event from \"./event\";");
+    assert_contains(
+        &source,
+        "export const CoinStoreType = \"0x1::coin::CoinStore\";",
+    );
+    assert_contains(
+        &source,
+        "export interface CoinStore {\n  coin: Coin;\n  frozen: boolean;\n  deposit_events: event.EventHandle;",
+    );
+    assert_contains(&source, "      CoinCodec().serialize(s, v.coin);");
+    assert_contains(&source, "      frozen: codec.bool.deserialize(d),");
+    assert_contains(&source, "/** `0x1::coin::CoinDeposit`, an event */");
+    assert_contains(
+        &source,
+        "export function transfer(args: { arg1: AccountAddressInput; arg2: bigint }, \
+         typeArguments: [TypeTag | string]): InputEntryFunctionData {",
+    );
+    assert_contains(
+        &source,
+        "functionArguments: [AccountAddress.from(args.arg1), new U64(args.arg2)],",
+    );
+    assert_contains(
+        &source,
+        "export async function balance(aptos: Aptos, args: { arg0: AccountAddressInput }, \
+         typeArguments: [TypeTag | string]): Promise<bigint> {",
+    );
+    assert_contains(&source, "return codec.u64.fromJson(result[0]);");
+}
+
+#[test]
+fn test_python() {
+    let source = generate(python::output);
+    assert_contains(&source, "from . import event\n");
+    assert_contains(
+        &source,
+        "@dataclass\nclass CoinStore:\n    \"\"\"`0x1::coin::CoinStore`, a resource\"\"\"\n\n    \
+         TYPE: ClassVar[str] = \"0x1::coin::CoinStore\"\n\n    coin: Coin\n    frozen: bool\n",
+    );
+    assert_contains(&source, "def coin_store_codec() -> codec.Codec[CoinStore]:");
+    assert_contains(
+        &source,
+        "            frozen=codec.bool_.deserialize(deserializer),",
+    );
+    assert_contains(
+        &source,
+        "def transfer(arg1: AccountAddress, arg2: int, type_arguments: List[TypeTag]) -> EntryFunction:",
+    );
+    assert_contains(
+        &source,
+        "            TransactionArgument(arg2, codec.u64.serialize),",
+    );
+    assert_contains(
+        &source,
+        "async def balance(client: RestClient, arg0: AccountAddress, type_arguments: List[str]) -> int:",
+    );
+    assert_contains(&source, "    return codec.u64.from_json(result[0])");
+}
+
+#[test]
+fn test_install() {
+    let bindings = coin_bindings();
+    let dir = tempdir().unwrap();
+    let paths = typescript::Installer::new(dir.path().join("ts"))
+        .install_bindings(&bindings)
+        .unwrap();
+    for file in ["codec.ts", "index.ts", "coin.ts", "event.ts", "guid.ts"] {
+        assert!(
+            paths.contains(&dir.path().join("ts").join(file)),
+            "{}",
+            file
+        );
+    }
+    let index = std::fs::read_to_string(dir.path().join("ts/index.ts")).unwrap();
+    assert_contains(&index, "export * as coin from \"./coin\";");
+
+    let paths = python::Installer::new(dir.path().join("py"))
+        .install_bindings(&bindings)
+        .unwrap();
+    for file in ["__init__.py", "codec.py", "coin.py", "event.py", "guid.py"] {
+        assert!(
+            paths.contains(&dir.path().join("py").join(file)),
+            "{}",
+            file
+        );
+    }
+}
+
+/// The Rust counterpart of the `Record` struct of `record_bindings`
+#[derive(Serialize)]
+struct Record {
+    id: u64,
+    amount: u128,
+    tags: Vec<u64>,
+    owner: AccountAddress,
+}
+
+/// The bindings of a module with a `Record` struct
+fn record_bindings() -> PackageBindings {
+    let record = Struct {
+        name: "Record".to_string(),
+        type_params: vec![],
+        fields: vec![
+            ("id".to_string(), MoveType::U64),
+            ("amount".to_string(), MoveType::U128),
+            (
+                "tags".to_string(),
+                MoveType::Vector(Box::new(MoveType::U64)),
+            ),
+            ("owner".to_string(), MoveType::Address),
+        ],
+        is_native: false,
+        is_resource: true,
+        is_event: false,
+    };
+    PackageBindings {
+        modules: vec![Module {
+            id: ModuleId::new(
+                AccountAddress::from_hex_literal("0xcafe").unwrap(),
+                "records".parse().unwrap(),
+            ),
+            file_name: "records".to_string(),
+            entry_functions: vec![],
+            view_functions: vec![],
+            structs: vec![record],
+        }],
+    }
+}
+
+/// Serializes a value with the generated Python codec and checks it is serialized as by
+/// `bcs`, and deserialized back. This needs `python3` with the `aptos-sdk` package.
+#[test]
+fn test_python_codec_round_trip() {
+    let python = match which::which("python3") {
+        Ok(python) => python,
+        Err(_) => {
+            eprintln!("python3 is not installed, skipping the round trip");
+            return;
+        },
+    };
+    let has_sdk = Command::new(&python)
+        .args(["-c", "import aptos_sdk"])
+        .output()
+        .unwrap()
+        .status
+        .success();
+    if !has_sdk {
+        eprintln!("The aptos-sdk Python package is not installed, skipping the round trip");
+        return;
+    }
+
+    let record = Record {
+        id: 42,
+        amount: u128::MAX - 1,
+        tags: vec![1, 300, u64::MAX],
+        owner: AccountAddress::from_hex_literal("0xcafe").unwrap(),
+    };
+    let dir = tempdir().unwrap();
+    python::Installer::new(dir.path().join("bindings"))
+        .install_bindings(&record_bindings())
+        .unwrap();
+    let script = format!(
+        r#"from aptos_sdk.account_address import AccountAddress
+from bindings import codec, records
+
+value = records.Record(
+    id={},
+    amount={},
+    tags=[{}],
+    owner=AccountAddress.from_str("{}"),
+)
+data = codec.to_bcs(records.record_codec(), value)
+assert codec.from_bcs(records.record_codec(), data) == value
+print(data.hex())
+"#,
+        record.id,
+        record.amount,
+        record
+            .tags
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        record.owner.to_standard_string(),
+    );
+    let output = Command::new(&python)
+        .current_dir(dir.path())
+        .args(["-c", &script])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        hex::encode(bcs::to_bytes(&record).unwrap())
+    );
+}
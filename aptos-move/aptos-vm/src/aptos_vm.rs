@@ -263,6 +263,18 @@ impl AptosVM {
     pub fn new(
         resolver: &impl AptosMoveResolver,
         override_is_delayed_field_optimization_capable: Option<bool>,
+    ) -> Self {
+        Self::new_impl(
+            resolver,
+            override_is_delayed_field_optimization_capable,
+            /*isolated_loader=*/ false,
+        )
+    }
+
+    fn new_impl(
+        resolver: &impl AptosMoveResolver,
+        override_is_delayed_field_optimization_capable: Option<bool>,
+        isolated_loader: bool,
     ) -> Self {
         let _timer = TIMER.timer_with(&["AptosVM::new"]);
         let features = Features::fetch_config(resolver).unwrap_or_default();
@@ -291,15 +303,27 @@ impl AptosVM {
         let aggregator_v2_type_tagging = override_is_delayed_field_optimization_capable
             && features.is_aggregator_v2_delayed_fields_enabled();
 
-        let move_vm = MoveVmExt::new(
-            gas_feature_version,
-            gas_params.as_ref(),
-            chain_id.id(),
-            features,
-            timed_features.clone(),
-            resolver,
-            aggregator_v2_type_tagging,
-        )
+        let move_vm = if isolated_loader {
+            MoveVmExt::new_with_isolated_loader(
+                gas_feature_version,
+                gas_params.as_ref(),
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        } else {
+            MoveVmExt::new(
+                gas_feature_version,
+                gas_params.as_ref(),
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        }
         .expect("should be able to create Move VM; check if there are duplicated natives");
 
         // We use an `Option` to handle the VK not being set on-chain, or an incorrect VK being set
@@ -2758,9 +2782,14 @@ pub struct AptosSimulationVM(AptosVM);
 
 impl AptosSimulationVM {
     pub fn new(resolver: &impl AptosMoveResolver) -> Self {
-        let mut vm = AptosVM::new(
+        Self::new_impl(resolver, /*isolated_loader=*/ false)
+    }
+
+    fn new_impl(resolver: &impl AptosMoveResolver, isolated_loader: bool) -> Self {
+        let mut vm = AptosVM::new_impl(
             resolver,
             /*override_is_delayed_field_optimization_capable=*/ Some(false),
+            isolated_loader,
         );
         vm.is_simulation = true;
         Self(vm)
@@ -2789,6 +2818,68 @@ impl AptosSimulationVM {
             .expect("Materializing aggregator V1 deltas should never fail");
         (vm_status, txn_output)
     }
+
+    /// Same as `create_vm_and_simulate_signed_transaction`, but allows the production
+    /// gas meter to be wrapped, e.g. to profile the simulated transaction. The
+    /// wrapped gas meter is returned alongside the output. If the state view
+    /// serves code that is not in storage, `isolated_loader` must be set, see
+    /// `create_vm_and_simulate_signed_transaction_with_overridden_code`.
+    pub fn create_vm_and_simulate_signed_transaction_with_modified_gas_meter<G, F>(
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        isolated_loader: bool,
+        modify_gas_meter: F,
+    ) -> Result<(VMStatus, TransactionOutput, G), VMStatus>
+    where
+        F: FnOnce(ProdGasMeter) -> G,
+        G: AptosGasMeter,
+    {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        let resolver = state_view.as_move_resolver();
+        let vm = Self::new_impl(&resolver, isolated_loader);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+        let (vm_status, vm_output, gas_meter) =
+            vm.0.execute_user_transaction_with_modified_gas_meter(
+                &resolver,
+                transaction,
+                &log_context,
+                modify_gas_meter,
+            )?;
+        let txn_output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .expect("Materializing aggregator V1 deltas should never fail");
+        Ok((vm_status, txn_output, gas_meter))
+    }
+
+    /// Same as `create_vm_and_simulate_signed_transaction`, but for state views
+    /// serving code that is not in storage. The loader of the VMs in the warm
+    /// VM cache is shared with concurrent executions, so the simulation runs
+    /// in a VM with a loader of its own instead, which is dropped afterwards.
+    pub fn create_vm_and_simulate_signed_transaction_with_overridden_code(
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        let resolver = state_view.as_move_resolver();
+        let vm = Self::new_impl(&resolver, /*isolated_loader=*/ true);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+        let (vm_status, vm_output) =
+            vm.0.execute_user_transaction(&resolver, transaction, &log_context);
+        let txn_output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .expect("Materializing aggregator V1 deltas should never fail");
+        (vm_status, txn_output)
+    }
 }
 
 fn create_account_if_does_not_exist(
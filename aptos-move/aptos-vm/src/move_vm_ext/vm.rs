@@ -4,6 +4,7 @@
 use crate::{
     aptos_vm::{aptos_default_ty_builder, aptos_prod_ty_builder},
     move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId},
+    natives::aptos_natives_with_builder,
 };
 use aptos_framework::natives::{
     aggregator_natives::NativeAggregatorContext,
@@ -45,6 +46,7 @@ impl MoveVmExt {
         gas_hook: Option<F>,
         resolver: &impl AptosMoveResolver,
         aggregator_v2_type_tagging: bool,
+        use_warm_vm_cache: bool,
     ) -> VMResult<Self>
     where
         F: Fn(DynamicExpression) + Send + Sync + 'static,
@@ -92,8 +94,16 @@ impl MoveVmExt {
             paranoid_type_checks,
         );
 
+        // A VM which is not taken from the warm VM cache has a loader of its own, so the code it
+        // loads is never seen by other VMs.
+        let inner = if use_warm_vm_cache {
+            WarmVmCache::get_warm_vm(builder, vm_config, resolver)?
+        } else {
+            MoveVM::new_with_config(aptos_natives_with_builder(&mut builder), vm_config)?
+        };
+
         Ok(Self {
-            inner: WarmVmCache::get_warm_vm(builder, vm_config, resolver)?,
+            inner,
             chain_id,
             features,
         })
@@ -117,6 +127,32 @@ impl MoveVmExt {
             None,
             resolver,
             aggregator_v2_type_tagging,
+            true,
+        )
+    }
+
+    /// Same as `new`, but the VM does not share its loader with any other VM. This is needed
+    /// if the resolver serves code which is not in storage, as the shared loader would otherwise
+    /// hand out that code to other VMs.
+    pub fn new_with_isolated_loader(
+        gas_feature_version: u64,
+        gas_params: Result<&AptosGasParameters, &String>,
+        chain_id: u8,
+        features: Features,
+        timed_features: TimedFeatures,
+        resolver: &impl AptosMoveResolver,
+        aggregator_v2_type_tagging: bool,
+    ) -> VMResult<Self> {
+        Self::new_impl::<fn(DynamicExpression)>(
+            gas_feature_version,
+            gas_params,
+            chain_id,
+            features,
+            timed_features,
+            None,
+            resolver,
+            aggregator_v2_type_tagging,
+            false,
         )
     }
 
@@ -142,6 +178,7 @@ impl MoveVmExt {
             gas_hook,
             resolver,
             aggregator_v2_type_tagging,
+            true,
         )
     }
 
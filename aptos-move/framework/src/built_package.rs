@@ -19,6 +19,7 @@ use itertools::Itertools;
 use move_binary_format::CompiledModule;
 use move_command_line_common::files::MOVE_COMPILED_EXTENSION;
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_compiler_v2::linter::{LintConfig, LintDiagnostic};
 use move_core_types::{language_storage::ModuleId, metadata::Metadata};
 use move_model::{
     metadata::{CompilerVersion, LanguageVersion},
@@ -144,7 +145,61 @@ pub fn build_model(
     skip_attribute_checks: bool,
     known_attributes: BTreeSet<String>,
 ) -> anyhow::Result<GlobalEnv> {
-    let build_config = BuildConfig {
+    let build_config = model_build_config(
+        dev_mode,
+        additional_named_addresses,
+        bytecode_version,
+        compiler_version,
+        language_version,
+        skip_attribute_checks,
+        known_attributes,
+    );
+    let compiler_version = compiler_version.unwrap_or_default();
+    let language_version = language_version.unwrap_or_default();
+    compiler_version.check_language_support(language_version)?;
+    build_config.move_model_for_package(package_path, ModelConfig {
+        target_filter,
+        all_files_as_targets: false,
+        compiler_version,
+        language_version,
+    })
+}
+
+/// Type checks the package with the v2 compiler and runs the linter on it, returning the
+/// model and the violations of lint rules.
+pub fn lint_package(
+    dev_mode: bool,
+    package_path: &Path,
+    additional_named_addresses: BTreeMap<String, AccountAddress>,
+    language_version: Option<LanguageVersion>,
+    skip_attribute_checks: bool,
+    known_attributes: BTreeSet<String>,
+    lint_config: &LintConfig,
+) -> anyhow::Result<(GlobalEnv, Vec<LintDiagnostic>)> {
+    let build_config = model_build_config(
+        dev_mode,
+        additional_named_addresses,
+        None,
+        Some(CompilerVersion::V2_0),
+        language_version,
+        skip_attribute_checks,
+        known_attributes,
+    );
+    let language_version = language_version.unwrap_or_default();
+    CompilerVersion::V2_0.check_language_support(language_version)?;
+    build_config.lint_package(package_path, language_version, lint_config)
+}
+
+fn model_build_config(
+    dev_mode: bool,
+    additional_named_addresses: BTreeMap<String, AccountAddress>,
+    bytecode_version: Option<u32>,
+    compiler_version: Option<CompilerVersion>,
+    language_version: Option<LanguageVersion>,
+    skip_attribute_checks: bool,
+    known_attributes: BTreeSet<String>,
+) -> BuildConfig {
+    BuildConfig {
         dev_mode,
         additional_named_addresses,
         architecture: None,
@@ -165,16 +220,7 @@ pub fn build_model(
             skip_attribute_checks,
             known_attributes,
         },
-    };
-    let compiler_version = compiler_version.unwrap_or_default();
-    let language_version = language_version.unwrap_or_default();
-    compiler_version.check_language_support(language_version)?;
-    build_config.move_model_for_package(package_path, ModelConfig {
-        target_filter,
-        all_files_as_targets: false,
-        compiler_version,
-        language_version,
-    })
+    }
 }
 
 impl BuiltPackage {
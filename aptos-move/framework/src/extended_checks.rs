@@ -5,6 +5,7 @@ use crate::{KnownAttribute, RandomnessAnnotation, RuntimeModuleMetadataV1};
 use move_binary_format::file_format::{Ability, AbilitySet, Visibility};
 use move_cli::base::test_validation;
 use move_compiler::shared::known_attributes;
+use move_compiler_v2::linter::{LINT_ALLOW_ATTRIBUTE, LINT_DENY_ATTRIBUTE};
 use move_core_types::{
     account_address::AccountAddress,
     errmap::{ErrorDescription, ErrorMapping},
@@ -52,9 +53,11 @@ const RANDOMNESS_MODULE_NAME: &str = "randomness";
 
 // top-level attribute names, only.
 pub fn get_all_attribute_names() -> &'static BTreeSet<String> {
-    const ALL_ATTRIBUTE_NAMES: [&str; 8] = [
+    const ALL_ATTRIBUTE_NAMES: [&str; 10] = [
         ALLOW_UNSAFE_RANDOMNESS_ATTRIBUTE,
         FMT_SKIP_ATTRIBUTE,
+        LINT_ALLOW_ATTRIBUTE,
+        LINT_DENY_ATTRIBUTE,
         LEGACY_ENTRY_FUN_ATTRIBUTE,
         RESOURCE_GROUP,
         RESOURCE_GROUP_MEMBER,
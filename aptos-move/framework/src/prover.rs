@@ -84,6 +84,11 @@ pub struct ProverOptions {
     #[clap(long)]
     pub dump: bool,
 
+    /// Whether to verify all functions, even those which have been verified before and did not
+    /// change since. Verification results are cached in `build/prover_cache` in the package.
+    #[clap(long)]
+    pub force: bool,
+
     #[clap(skip)]
     pub for_test: bool,
 }
@@ -106,6 +111,7 @@ impl Default for ProverOptions {
             loop_unroll: None,
             stable_test_output: false,
             dump: false,
+            force: false,
             for_test: false,
         }
     }
@@ -137,7 +143,7 @@ impl ProverOptions {
             skip_attribute_checks,
             known_attributes.clone(),
         )?;
-        let mut options = self.convert_options();
+        let mut options = self.convert_options(package_path);
         // Need to ensure a distinct output.bpl file for concurrent execution. In non-test
         // mode, we actually want to use the static output.bpl for debugging purposes
         let _temp_holder = if for_test {
@@ -174,7 +180,7 @@ impl ProverOptions {
         Ok(())
     }
 
-    fn convert_options(self) -> move_prover::cli::Options {
+    fn convert_options(self, package_path: &Path) -> move_prover::cli::Options {
         let verbosity_level = if let Some(level) = self.verbosity {
             level
         } else if self.for_test {
@@ -185,6 +191,19 @@ impl ProverOptions {
         let opts = move_prover::cli::Options {
             output_path: "".to_string(),
             verbosity_level,
+            // Tests run concurrently on the same packages, so results are not cached for them.
+            cache_dir: if self.for_test {
+                None
+            } else {
+                Some(
+                    package_path
+                        .join("build")
+                        .join("prover_cache")
+                        .display()
+                        .to_string(),
+                )
+            },
+            force: self.force,
             prover: move_prover_bytecode_pipeline::options::ProverOptions {
                 stable_test_output: self.stable_test_output,
                 auto_trace_level: if self.trace {
@@ -38,6 +38,7 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
         aptos_test_natives(),
         aptos_test_feature_flags_genesis(),
         /* cost_table */ None,
+        /* gas_meter_hook */ None,
         /* compute_coverage */ false,
         &mut std::io::stdout(),
     )
@@ -56,6 +57,7 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
             aptos_test_natives(),
             aptos_test_feature_flags_genesis(),
             /* cost_table */ None,
+            /* gas_meter_hook */ None,
             /* compute_coverage */ false,
             &mut std::io::stdout(),
         )
@@ -46,6 +46,7 @@ pub fn run_tests_for_pkg(
         aptos_test_natives(),
         aptos_test_feature_flags_genesis(),
         /* cost_table */ None,
+        /* gas_meter_hook */ None,
         /* compute_coverage */ false,
         &mut std::io::stdout(),
     )
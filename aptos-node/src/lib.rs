@@ -40,11 +40,16 @@ use std::{
         Arc,
     },
     thread,
+    time::Duration,
 };
+pub use storage::create_rocksdb_checkpoint;
 use tokio::runtime::Runtime;
 
 const EPOCH_LENGTH_SECS: u64 = 60;
 
+/// How often a running node checks whether it should stop
+const TERM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Runs an Aptos validator or fullnode
 #[derive(Clone, Debug, Parser)]
 #[clap(name = "Aptos Node", author, version)]
@@ -214,6 +219,22 @@ pub fn start(
     config: NodeConfig,
     log_file: Option<PathBuf>,
     create_global_rayon_pool: bool,
+) -> anyhow::Result<()> {
+    start_until(
+        config,
+        log_file,
+        create_global_rayon_pool,
+        Arc::new(AtomicBool::new(false)),
+    )
+}
+
+/// Start an Aptos node, and stop it once `term` is set. The runtimes of the node
+/// are dropped when it stops, so it's no longer writing to its DBs once this returns.
+pub fn start_until(
+    config: NodeConfig,
+    log_file: Option<PathBuf>,
+    create_global_rayon_pool: bool,
+    term: Arc<AtomicBool>,
 ) -> anyhow::Result<()> {
     // Setup panic handler
     aptos_crash_handler::setup_panic_handler();
@@ -254,9 +275,8 @@ pub fn start(
     // Set up the node environment and start it
     let _node_handle =
         setup_environment_and_start_node(config, remote_log_receiver, Some(logger_filter_update))?;
-    let term = Arc::new(AtomicBool::new(false));
     while !term.load(Ordering::Acquire) {
-        thread::park();
+        thread::park_timeout(TERM_POLL_INTERVAL);
     }
 
     Ok(())
@@ -316,6 +336,21 @@ pub fn start_test_environment_node(
     config: NodeConfig,
     test_dir: PathBuf,
     enable_lazy_mode: bool,
+) -> anyhow::Result<()> {
+    start_test_environment_node_until(
+        config,
+        test_dir,
+        enable_lazy_mode,
+        Arc::new(AtomicBool::new(false)),
+    )
+}
+
+/// Like `start_test_environment_node`, but stops the node once `term` is set.
+pub fn start_test_environment_node_until(
+    config: NodeConfig,
+    test_dir: PathBuf,
+    enable_lazy_mode: bool,
+    term: Arc<AtomicBool>,
 ) -> anyhow::Result<()> {
     let aptos_root_key_path = test_dir.join("mint.key");
 
@@ -349,7 +384,7 @@ pub fn start_test_environment_node(
     }
     println!("\nAptos is running, press ctrl-c to exit\n");
 
-    start(config, Some(log_file), false)
+    start_until(config, Some(log_file), false, term)
 }
 
 /// Creates a simple test environment and starts the node.
@@ -118,27 +118,42 @@ fn create_rocksdb_checkpoint_and_change_working_dir(
     let checkpoint_dir = node_config.storage.dir();
     assert!(source_dir != checkpoint_dir);
 
-    // Create rocksdb checkpoint directory
-    fs::create_dir_all(&checkpoint_dir).unwrap();
-
-    // Open the database and create a checkpoint
-    AptosDB::create_checkpoint(
+    create_rocksdb_checkpoint(
         &source_dir,
         &checkpoint_dir,
         node_config.storage.rocksdb_configs.enable_storage_sharding,
     )
-    .expect("AptosDB checkpoint creation failed.");
+    .expect("RocksDB checkpoint creation failed.");
+}
+
+/// Creates a RocksDb checkpoint for the consensus_db, state_sync_db,
+/// ledger_db and state_merkle_db in `source_dir` and saves it to the
+/// checkpoint_dir. The databases must not be open, i.e. the node must
+/// not be running.
+pub fn create_rocksdb_checkpoint(
+    source_dir: &Path,
+    checkpoint_dir: &Path,
+    enable_storage_sharding: bool,
+) -> Result<()> {
+    // Create rocksdb checkpoint directory
+    fs::create_dir_all(checkpoint_dir)?;
+
+    // Open the database and create a checkpoint
+    AptosDB::create_checkpoint(source_dir, checkpoint_dir, enable_storage_sharding)
+        .map_err(|err| anyhow!("AptosDB checkpoint creation failed: {}", err))?;
 
     // Create a consensus db checkpoint
-    aptos_consensus::create_checkpoint(&source_dir, &checkpoint_dir)
-        .expect("ConsensusDB checkpoint creation failed.");
+    aptos_consensus::create_checkpoint(source_dir, checkpoint_dir)
+        .map_err(|err| anyhow!("ConsensusDB checkpoint creation failed: {}", err))?;
 
     // Create a state sync db checkpoint
     let state_sync_db =
-        aptos_state_sync_driver::metadata_storage::PersistentMetadataStorage::new(&source_dir);
+        aptos_state_sync_driver::metadata_storage::PersistentMetadataStorage::new(source_dir);
     state_sync_db
-        .create_checkpoint(&checkpoint_dir)
-        .expect("StateSyncDB checkpoint creation failed.");
+        .create_checkpoint(checkpoint_dir)
+        .map_err(|err| anyhow!("StateSyncDB checkpoint creation failed: {}", err))?;
+
+    Ok(())
 }
 
 /// Creates any rocksdb checkpoints, opens the storage database,
@@ -89,6 +89,8 @@ pub struct ApiConfig {
     pub wait_by_hash_poll_interval_ms: u64,
     /// The number of active wait_by_hash requests that can be active at any given time.
     pub wait_by_hash_max_active_connections: usize,
+    /// Configuration for rate limiting requests per client
+    pub rate_limit: ApiRateLimitConfig,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -141,6 +143,7 @@ impl Default for ApiConfig {
             wait_by_hash_timeout_ms: 1_000,
             wait_by_hash_poll_interval_ms: 20,
             wait_by_hash_max_active_connections: 100,
+            rate_limit: ApiRateLimitConfig::default(),
         }
     }
 }
@@ -200,6 +203,36 @@ impl ConfigSanitizer for ApiConfig {
             }
         }
 
+        // Verify that the rate limit budgets can be used to build token buckets
+        if api_config.rate_limit.enabled {
+            for (name, budget) in api_config.rate_limit.budgets() {
+                if budget.requests_per_second == 0 || budget.burst_size < budget.requests_per_second
+                {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!(
+                            "The {} rate limit must be non-zero and no larger than its burst size!",
+                            name
+                        ),
+                    ));
+                }
+            }
+            if api_config.rate_limit.initial_bucket_fill_percentage > 100 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "initial_bucket_fill_percentage must be at most 100!".into(),
+                ));
+            }
+            if api_config.rate_limit.api_key_header.is_some()
+                && api_config.rate_limit.api_keys.is_empty()
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "api_keys must be set if api_key_header is!".into(),
+                ));
+            }
+        }
+
         // Sanitize the gas estimation config
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
 
@@ -249,6 +282,87 @@ impl ViewFilter {
     }
 }
 
+/// Per client rate limits for the API. Each client gets a token bucket per
+/// category of request, where every request consumes a single token.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    /// Enables rate limiting of API requests
+    pub enabled: bool,
+    /// Optional header carrying an API key. Requests with one of the keys in
+    /// `api_keys` are limited by the key, all others by their IP address.
+    pub api_key_header: Option<String>,
+    /// The API keys accepted in `api_key_header`. To rotate a key, add the new
+    /// key, move clients over to it, and then remove the old one.
+    pub api_keys: Vec<String>,
+    /// Optional header carrying the IP address of the client, e.g. `X-Forwarded-For`,
+    /// as set by a reverse proxy in front of the node. If the header holds a list
+    /// of addresses, the last one, which the proxy appended, is used. Only set this
+    /// if all requests pass through such a proxy, as clients can otherwise set the
+    /// header themselves. If not set, clients are limited by the address they
+    /// connect from, so all clients behind a proxy share a single bucket.
+    pub client_ip_header: Option<String>,
+    /// Interval at which the buckets of idle clients are dropped
+    pub idle_bucket_gc_interval_secs: u64,
+    /// Amount of tokens initially in a client's bucket, as a percentage of its size
+    pub initial_bucket_fill_percentage: u8,
+    /// Limits for transaction submission
+    pub submission: RequestBudget,
+    /// Limits for transaction simulation
+    pub simulation: RequestBudget,
+    /// Limits for view function calls
+    pub view_function: RequestBudget,
+    /// Limits for all other requests
+    pub read: RequestBudget,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_disabled(),
+            api_key_header: None,
+            api_keys: vec![],
+            client_ip_header: None,
+            idle_bucket_gc_interval_secs: 60,
+            initial_bucket_fill_percentage: 100,
+            submission: RequestBudget::new(10, 20),
+            simulation: RequestBudget::new(10, 20),
+            view_function: RequestBudget::new(50, 100),
+            read: RequestBudget::new(100, 200),
+        }
+    }
+}
+
+impl ApiRateLimitConfig {
+    /// Returns all budgets along with a name for each of them
+    pub fn budgets(&self) -> [(&'static str, &RequestBudget); 4] {
+        [
+            ("submission", &self.submission),
+            ("simulation", &self.simulation),
+            ("view_function", &self.view_function),
+            ("read", &self.read),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequestBudget {
+    /// Number of requests per second a single client may make
+    pub requests_per_second: usize,
+    /// Maximum number of requests a single client may burst
+    pub burst_size: usize,
+}
+
+impl RequestBudget {
+    pub fn new(requests_per_second: usize, burst_size: usize) -> Self {
+        Self {
+            requests_per_second,
+            burst_size,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -315,4 +429,32 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_invalid_rate_limit() {
+        // Create a node config with a burst size smaller than the fill rate
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                rate_limit: ApiRateLimitConfig {
+                    enabled: true,
+                    read: RequestBudget::new(10, 5),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Disable rate limiting and verify that the config is accepted
+        let mut node_config = node_config;
+        node_config.api.rate_limit.enabled = false;
+        ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet())).unwrap();
+    }
 }
@@ -77,6 +77,9 @@ pub struct ConsensusConfig {
     pub broadcast_vote: bool,
     pub proof_cache_capacity: u64,
     pub rand_rb_config: ReliableBroadcastConfig,
+    // Lets the admin service pause block production, produce blocks on demand and move the
+    // consensus clock ahead. Only set by the localnet, and only allowed on the test chain.
+    pub enable_localnet_controls: bool,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -323,6 +326,7 @@ impl Default for ConsensusConfig {
                 backoff_policy_max_delay_ms: 10000,
                 rpc_timeout_ms: 10000,
             },
+            enable_localnet_controls: false,
         }
     }
 }
@@ -467,6 +471,17 @@ impl ConfigSanitizer for ConsensusConfig {
             }
         }
 
+        // Verify that the localnet controls are only enabled on the test chain
+        if let Some(chain_id) = chain_id {
+            if node_config.consensus.enable_localnet_controls && chain_id != ChainId::test() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "enable_localnet_controls should only be enabled on the test chain!"
+                        .to_string(),
+                ));
+            }
+        }
+
         // Sender block limits must be <= receiver block limits
         Self::sanitize_send_recv_block_limits(&sanitizer_name, &node_config.consensus)?;
 
@@ -500,6 +515,26 @@ mod test {
         serde_yaml::from_str::<ConsensusConfig>(&s).unwrap();
     }
 
+    #[test]
+    fn test_localnet_controls() {
+        // Create a node config with the localnet controls enabled
+        let node_config = NodeConfig {
+            consensus: ConsensusConfig {
+                enable_localnet_controls: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it only succeeds on the test chain
+        ConsensusConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::test()))
+            .unwrap();
+        let error =
+            ConsensusConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
     #[test]
     fn test_send_recv_block_txn_limits() {
         // Create a node config with invalid block txn limits
@@ -0,0 +1,43 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The clock that consensus timestamps blocks with. It follows the system clock, but a
+//! node with `consensus.enable_localnet_controls` set lets its admin service move it
+//! ahead to skip time, e.g. to get to the end of a lockup without waiting for it.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+static OFFSET_USECS: AtomicU64 = AtomicU64::new(0);
+
+/// Gives the duration since the Unix epoch of the consensus clock.
+pub fn duration_since_epoch() -> Duration {
+    aptos_infallible::duration_since_epoch() + offset()
+}
+
+/// How far the consensus clock is ahead of the system clock.
+pub fn offset() -> Duration {
+    Duration::from_micros(OFFSET_USECS.load(Ordering::Relaxed))
+}
+
+/// Moves the consensus clock ahead. It can't be moved back, as block timestamps must
+/// always increase.
+pub fn advance(duration: Duration) {
+    OFFSET_USECS.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance() {
+        let before = duration_since_epoch();
+        let offset = offset();
+        advance(Duration::from_secs(3600));
+        assert_eq!(super::offset(), offset + Duration::from_secs(3600));
+        assert!(duration_since_epoch() >= before + Duration::from_secs(3600));
+    }
+}
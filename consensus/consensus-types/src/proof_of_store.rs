@@ -135,7 +135,7 @@ impl BatchInfo {
     }
 
     pub fn is_expired(&self) -> bool {
-        self.expiration() < aptos_infallible::duration_since_epoch().as_micros() as u64
+        self.expiration() < crate::clock::duration_since_epoch().as_micros() as u64
     }
 }
 
@@ -230,13 +230,12 @@ impl SignedBatchInfo {
         }
 
         if self.expiration()
-            > aptos_infallible::duration_since_epoch().as_micros() as u64
-                + max_batch_expiry_gap_usecs
+            > crate::clock::duration_since_epoch().as_micros() as u64 + max_batch_expiry_gap_usecs
         {
             bail!(
                 "Batch expiration too far in future: {} > {}",
                 self.expiration(),
-                aptos_infallible::duration_since_epoch().as_micros() as u64
+                crate::clock::duration_since_epoch().as_micros() as u64
                     + max_batch_expiry_gap_usecs
             );
         }
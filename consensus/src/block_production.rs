@@ -0,0 +1,152 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Controls for pausing block production and producing blocks on demand, so tests can
+//! decide when a single node test network produces blocks. These are driven by the
+//! admin service, and only affect the node they are used on. Unless
+//! `consensus.enable_localnet_controls` is set, the admin service doesn't expose them
+//! and the round manager ignores them, so the node always proposes.
+
+use aptos_consensus_types::common::Round;
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockProductionState {
+    /// If set, the node only proposes the blocks it is asked to.
+    pub paused: bool,
+    /// The number of blocks the node has been asked to propose while paused, but hasn't.
+    pub blocks_to_produce: u64,
+    /// The epoch and round of the last block proposed while paused. Proposals continue
+    /// until this block is ordered, which takes a block on top of it.
+    pub last_produced: Option<(u64, Round)>,
+}
+
+impl BlockProductionState {
+    /// Whether the node should propose a block for the given round, given the highest
+    /// ordered round. This counts the block as produced if it was asked for, returning
+    /// whether the state changed along with the decision.
+    fn should_propose(
+        &mut self,
+        epoch: u64,
+        round: Round,
+        highest_ordered_round: Round,
+    ) -> (bool, bool) {
+        if !self.paused {
+            (true, false)
+        } else if self.blocks_to_produce > 0 {
+            self.blocks_to_produce -= 1;
+            self.last_produced = Some((epoch, round));
+            (true, true)
+        } else {
+            // Keep proposing until the last block asked for is ordered, so that it is
+            // committed.
+            let propose = matches!(
+                self.last_produced,
+                Some(last_produced) if last_produced > (epoch, highest_ordered_round)
+            );
+            (propose, false)
+        }
+    }
+}
+
+static STATE: Lazy<watch::Sender<BlockProductionState>> =
+    Lazy::new(|| watch::channel(BlockProductionState::default()).0);
+
+pub fn state() -> BlockProductionState {
+    *STATE.borrow()
+}
+
+/// Subscribes to changes of the block production state.
+pub fn subscribe() -> watch::Receiver<BlockProductionState> {
+    STATE.subscribe()
+}
+
+/// Stops the node from proposing blocks until it is resumed, or asked to produce some.
+/// Rounds don't time out while block production is paused.
+pub fn pause() {
+    STATE.send_if_modified(|state| {
+        let was_paused = state.paused;
+        state.paused = true;
+        !was_paused
+    });
+}
+
+pub fn resume() {
+    STATE.send_replace(BlockProductionState::default());
+}
+
+/// Asks a paused node to propose the given number of blocks.
+pub fn produce(blocks: u64) {
+    STATE.send_modify(|state| state.blocks_to_produce += blocks);
+}
+
+/// Whether the node should propose a block for the given round, given the highest
+/// ordered round. This counts the block as produced if it was asked for.
+pub(crate) fn should_propose(epoch: u64, round: Round, highest_ordered_round: Round) -> bool {
+    let mut propose = false;
+    STATE.send_if_modified(|state| {
+        let (should_propose, modified) = state.should_propose(epoch, round, highest_ordered_round);
+        propose = should_propose;
+        modified
+    });
+    propose
+}
+
+/// Notifies the round manager of changes to the block production state.
+pub(crate) struct BlockProductionChanges {
+    receiver: Option<watch::Receiver<BlockProductionState>>,
+}
+
+impl BlockProductionChanges {
+    /// Changes are only notified if the localnet controls are enabled.
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            receiver: enabled.then(subscribe),
+        }
+    }
+
+    /// Resolves once the block production state changes, which never happens unless
+    /// the localnet controls are enabled.
+    pub(crate) async fn changed(&mut self) {
+        if let Some(receiver) = &mut self.receiver {
+            if receiver.changed().await.is_ok() {
+                return;
+            }
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_propose_while_running() {
+        let mut state = BlockProductionState::default();
+        assert_eq!(state.should_propose(1, 10, 8), (true, false));
+        assert_eq!(state, BlockProductionState::default());
+    }
+
+    #[test]
+    fn test_should_propose_while_paused() {
+        let mut state = BlockProductionState {
+            paused: true,
+            ..Default::default()
+        };
+        assert_eq!(state.should_propose(1, 10, 8), (false, false));
+
+        // Each block asked for is proposed once.
+        state.blocks_to_produce = 2;
+        assert_eq!(state.should_propose(1, 10, 8), (true, true));
+        assert_eq!(state.should_propose(1, 11, 9), (true, true));
+        assert_eq!(state.blocks_to_produce, 0);
+        assert_eq!(state.last_produced, Some((1, 11)));
+
+        // Proposals continue until the last block asked for is ordered.
+        assert_eq!(state.should_propose(1, 12, 10), (true, false));
+        assert_eq!(state.should_propose(1, 13, 11), (false, false));
+        assert_eq!(state.should_propose(2, 1, 0), (false, false));
+    }
+}
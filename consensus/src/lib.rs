@@ -17,6 +17,7 @@ extern crate scopeguard;
 
 extern crate core;
 
+pub mod block_production;
 mod block_storage;
 mod consensusdb;
 mod dag;
@@ -13,6 +13,7 @@ use crate::{
 };
 use aptos_config::config::QuorumStoreConfig;
 use aptos_consensus_types::{
+    clock,
     common::{TransactionInProgress, TransactionSummary},
     proof_of_store::{BatchId, BatchInfo},
 };
@@ -92,7 +93,7 @@ impl BatchGenerator {
             id.increment();
             id
         } else {
-            BatchId::new(aptos_infallible::duration_since_epoch().as_micros() as u64)
+            BatchId::new(clock::duration_since_epoch().as_micros() as u64)
         };
         debug!("Initialized with batch_id of {}", batch_id);
         let mut incremented_batch_id = batch_id;
@@ -363,7 +364,7 @@ impl BatchGenerator {
         counters::BATCH_CREATION_DURATION.observe_duration(self.last_end_batch_time.elapsed());
 
         let bucket_compute_start = Instant::now();
-        let expiry_time = aptos_infallible::duration_since_epoch().as_micros() as u64
+        let expiry_time = clock::duration_since_epoch().as_micros() as u64
             + self.config.batch_expiry_gap_when_init_usecs;
         let batches = self.bucket_into_batches(&mut pulled_txns, expiry_time);
         self.last_end_batch_time = Instant::now();
@@ -378,7 +379,7 @@ impl BatchGenerator {
         batch_id: BatchId,
         txns: Vec<SignedTransaction>,
     ) {
-        let expiry_time_usecs = aptos_infallible::duration_since_epoch().as_micros() as u64
+        let expiry_time_usecs = clock::duration_since_epoch().as_micros() as u64
             + self.config.remote_batch_expiry_gap_when_init_usecs;
         self.insert_batch(author, batch_id, txns, expiry_time_usecs);
     }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    block_production::BlockProductionChanges,
     block_storage::{
         tracing::{observe_block, BlockStage},
         BlockReader, BlockRetriever, BlockStore,
@@ -250,6 +251,9 @@ pub struct RoundManager {
     // To avoid duplicate broadcasts for the same block, we keep track of blocks for
     // which we recently broadcasted fast shares.
     blocks_with_broadcasted_fast_shares: LruCache<HashValue, ()>,
+    // The round this node didn't propose for because block production is paused. The
+    // proposal is made if a block is asked for while the round is still current.
+    deferred_proposal: Option<NewRoundEvent>,
 }
 
 impl RoundManager {
@@ -298,6 +302,7 @@ impl RoundManager {
             fast_rand_config,
             pending_order_votes: PendingOrderVotes::new(),
             blocks_with_broadcasted_fast_shares: LruCache::new(5),
+            deferred_proposal: None,
         }
     }
 
@@ -354,21 +359,60 @@ impl RoundManager {
             .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
         {
             self.log_collected_vote_stats(&new_round_event);
-            self.round_state.setup_leader_timeout();
-            let proposal_msg = self.generate_proposal(new_round_event).await?;
-            #[cfg(feature = "failpoints")]
+            if self.local_config.enable_localnet_controls
+                && !self.should_propose(new_round_event.round)
             {
-                if self.check_whether_to_inject_reconfiguration_error() {
-                    self.attempt_to_inject_reconfiguration_error(&proposal_msg)
-                        .await?;
-                }
+                info!(
+                    self.new_log(LogEvent::NewRound),
+                    "Block production is paused, not proposing"
+                );
+                self.deferred_proposal = Some(new_round_event);
+                return Ok(());
+            }
+            self.propose(new_round_event).await?;
+        }
+        Ok(())
+    }
+
+    async fn propose(&mut self, new_round_event: NewRoundEvent) -> anyhow::Result<()> {
+        self.round_state.setup_leader_timeout();
+        let proposal_msg = self.generate_proposal(new_round_event).await?;
+        #[cfg(feature = "failpoints")]
+        {
+            if self.check_whether_to_inject_reconfiguration_error() {
+                self.attempt_to_inject_reconfiguration_error(&proposal_msg)
+                    .await?;
             }
-            self.network.broadcast_proposal(proposal_msg).await;
-            counters::PROPOSALS_COUNT.inc();
         }
+        self.network.broadcast_proposal(proposal_msg).await;
+        counters::PROPOSALS_COUNT.inc();
         Ok(())
     }
 
+    fn should_propose(&self, round: Round) -> bool {
+        crate::block_production::should_propose(
+            self.epoch_state.epoch,
+            round,
+            self.block_store.ordered_root().round(),
+        )
+    }
+
+    /// Makes the proposal that was deferred while block production was paused, if it
+    /// can now be made.
+    async fn process_block_production_change(&mut self) -> anyhow::Result<()> {
+        match self.deferred_proposal.take() {
+            Some(new_round_event) if new_round_event.round == self.round_state.current_round() => {
+                if self.should_propose(new_round_event.round) {
+                    self.propose(new_round_event).await
+                } else {
+                    self.deferred_proposal = Some(new_round_event);
+                    Ok(())
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+
     fn log_collected_vote_stats(&self, new_round_event: &NewRoundEvent) {
         let prev_round_votes_for_li = new_round_event
             .prev_round_votes
@@ -642,6 +686,11 @@ impl RoundManager {
             return Ok(());
         }
 
+        // While block production is paused, rounds wait for blocks to be asked for.
+        if self.local_config.enable_localnet_controls && crate::block_production::state().paused {
+            return Ok(());
+        }
+
         if self.sync_only() {
             self.network
                 .broadcast_sync_info(self.block_store.sync_info())
@@ -1305,6 +1354,8 @@ impl RoundManager {
     ) {
         info!(epoch = self.epoch_state().epoch, "RoundManager started");
         let mut close_rx = close_rx.into_stream();
+        let mut block_production_changes =
+            BlockProductionChanges::new(self.local_config.enable_localnet_controls);
         loop {
             tokio::select! {
                 biased;
@@ -1327,6 +1378,12 @@ impl RoundManager {
                         }
                     }
                 },
+                () = block_production_changes.changed() => {
+                    if let Err(e) = self.process_block_production_change().await {
+                        counters::ERROR_COUNT.inc();
+                        warn!(error = ?e, kind = error_kind(&e), RoundStateLogSchema::new(self.round_state()));
+                    }
+                },
                 proposal = buffered_proposal_rx.select_next_some() => {
                     let mut proposals = vec![proposal];
                     while let Some(Some(proposal)) = buffered_proposal_rx.next().now_or_never() {
@@ -120,7 +120,7 @@ impl TimeService for ClockTimeService {
     }
 
     fn get_current_timestamp(&self) -> Duration {
-        aptos_infallible::duration_since_epoch()
+        aptos_consensus_types::clock::duration_since_epoch()
     }
 
     async fn sleep(&self, t: Duration) {
@@ -0,0 +1,183 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_consensus::block_production;
+use aptos_consensus_types::clock;
+use aptos_logger::info;
+use aptos_storage_interface::DbReaderWriter;
+use aptos_system_utils::utils::reply_with_status;
+use hyper::{Body, Request, Response, StatusCode};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// How long a request to mine blocks waits for them to be committed.
+const MINE_TIMEOUT: Duration = Duration::from_secs(60);
+const COMMIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub async fn handle_state_request(_req: Request<Body>) -> hyper::Result<Response<Body>> {
+    let state = if block_production::state().paused {
+        "paused"
+    } else {
+        "running"
+    };
+    Ok(reply_with_status(StatusCode::OK, state))
+}
+
+pub async fn handle_pause_request(_req: Request<Body>) -> hyper::Result<Response<Body>> {
+    info!("Pausing block production.");
+    block_production::pause();
+    Ok(reply_with_status(
+        StatusCode::OK,
+        "Block production paused.",
+    ))
+}
+
+pub async fn handle_resume_request(_req: Request<Body>) -> hyper::Result<Response<Body>> {
+    info!("Resuming block production.");
+    block_production::resume();
+    Ok(reply_with_status(
+        StatusCode::OK,
+        "Block production resumed.",
+    ))
+}
+
+pub async fn handle_mine_request(
+    req: Request<Body>,
+    aptos_db: Arc<DbReaderWriter>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let blocks: u64 = match query_pairs.get("blocks") {
+        Some(val) => match val.parse() {
+            Ok(val) => val,
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => 1,
+    };
+    if blocks == 0 {
+        return Ok(reply_with_status(
+            StatusCode::BAD_REQUEST,
+            "At least one block must be mined.",
+        ));
+    }
+
+    if !block_production::state().paused {
+        return Ok(reply_with_status(
+            StatusCode::BAD_REQUEST,
+            "Block production must be paused to mine blocks.",
+        ));
+    }
+
+    info!("Mining {blocks} blocks.");
+    match tokio::time::timeout(MINE_TIMEOUT, mine(blocks, aptos_db)).await {
+        Ok(Ok(version)) => Ok(reply_with_status(StatusCode::OK, version.to_string())),
+        Ok(Err(e)) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+        Err(_) => Ok(reply_with_status(
+            StatusCode::GATEWAY_TIMEOUT,
+            "Timed out waiting for the blocks to be committed.",
+        )),
+    }
+}
+
+/// Asks consensus for the given number of blocks, and waits for them to be committed,
+/// returning the latest committed version.
+async fn mine(blocks: u64, aptos_db: Arc<DbReaderWriter>) -> Result<u64> {
+    let mut state_rx = block_production::subscribe();
+    block_production::produce(blocks);
+    let state = *state_rx
+        .wait_for(|state| !state.paused || state.blocks_to_produce == 0)
+        .await?;
+    if !state.paused {
+        bail!("Block production was resumed while mining.");
+    }
+
+    let last_produced = match state.last_produced {
+        Some(last_produced) => last_produced,
+        None => bail!("No block was produced."),
+    };
+    loop {
+        let ledger_info = aptos_db.reader.get_latest_ledger_info()?;
+        let ledger_info = ledger_info.ledger_info();
+        if (ledger_info.epoch(), ledger_info.round()) >= last_produced {
+            return Ok(ledger_info.version());
+        }
+        tokio::time::sleep(COMMIT_POLL_INTERVAL).await;
+    }
+}
+
+/// Replies with how far the consensus clock is ahead of the system clock, in
+/// microseconds. The clock is not persisted, so this lets it be set again when the node
+/// is restarted.
+pub async fn handle_time_offset_request(_req: Request<Body>) -> hyper::Result<Response<Body>> {
+    Ok(reply_with_status(
+        StatusCode::OK,
+        clock::offset().as_micros().to_string(),
+    ))
+}
+
+pub async fn handle_time_request(req: Request<Body>) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let parse = |key: &str| -> Result<Option<u64>, String> {
+        query_pairs
+            .get(key)
+            .map(|val| {
+                val.parse()
+                    .map_err(|err: std::num::ParseIntError| err.to_string())
+            })
+            .transpose()
+    };
+    let (advance_secs, advance_usecs, timestamp_secs) = match (
+        parse("advance_secs"),
+        parse("advance_usecs"),
+        parse("timestamp_secs"),
+    ) {
+        (Ok(advance_secs), Ok(advance_usecs), Ok(timestamp_secs)) => {
+            (advance_secs, advance_usecs, timestamp_secs)
+        },
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+            return Ok(reply_with_status(StatusCode::BAD_REQUEST, err))
+        },
+    };
+
+    let now = clock::duration_since_epoch();
+    let advance = match (advance_secs, advance_usecs, timestamp_secs) {
+        (Some(advance_secs), None, None) => Duration::from_secs(advance_secs),
+        (None, Some(advance_usecs), None) => Duration::from_micros(advance_usecs),
+        (None, None, Some(timestamp_secs)) => {
+            match Duration::from_secs(timestamp_secs).checked_sub(now) {
+                Some(advance) => advance,
+                None => {
+                    return Ok(reply_with_status(
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "The time can't go back, it is already {} seconds.",
+                            now.as_secs()
+                        ),
+                    ))
+                },
+            }
+        },
+        (None, None, None) => Duration::ZERO,
+        _ => {
+            return Ok(reply_with_status(
+                StatusCode::BAD_REQUEST,
+                "Only one of advance_secs, advance_usecs and timestamp_secs can be given.",
+            ))
+        },
+    };
+
+    if !advance.is_zero() {
+        info!("Advancing the consensus clock by {advance:?}.");
+        clock::advance(advance);
+    }
+    Ok(reply_with_status(
+        StatusCode::OK,
+        clock::duration_since_epoch().as_micros().to_string(),
+    ))
+}
@@ -13,6 +13,7 @@ use aptos_system_utils::utils::reply_with_status;
 use aptos_system_utils::{
     profiling::handle_cpu_profiling_request, thread_dump::handle_thread_dump_request,
 };
+use aptos_types::chain_id::ChainId;
 use hyper::{
     service::{make_service_fn, service_fn},
     Body, Request, Response, Server, StatusCode,
@@ -25,6 +26,7 @@ use std::{
 };
 use tokio::runtime::Runtime;
 
+mod block_production;
 mod consensus;
 
 #[derive(Default)]
@@ -34,6 +36,8 @@ pub struct Context {
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+
+    localnet_controls_enabled: bool,
 }
 
 impl Context {
@@ -83,6 +87,7 @@ impl AdminService {
             runtime,
             context: Arc::new(Context {
                 authentication_configs: node_config.admin_service.authentication_configs.clone(),
+                localnet_controls_enabled: node_config.consensus.enable_localnet_controls,
                 ..Default::default()
             }),
         };
@@ -210,7 +215,64 @@ impl AdminService {
                     ))
                 }
             },
+            (_, path)
+                if !context.localnet_controls_enabled
+                    && (path.starts_with("/debug/consensus/block_production")
+                        || path.starts_with("/debug/consensus/time")) =>
+            {
+                Ok(reply_with_status(
+                    StatusCode::NOT_FOUND,
+                    "Localnet controls are not enabled.",
+                ))
+            },
+            (hyper::Method::GET, "/debug/consensus/block_production") => {
+                block_production::handle_state_request(req).await
+            },
+                        (hyper::Method::POST, path)
+                if path.starts_with("/debug/consensus/block_production/") =>
+            {
+                if !Self::is_test_chain() {
+                    return Ok(reply_with_status(
+                        StatusCode::FORBIDDEN,
+                        "Block production can only be controlled on a test chain.",
+                    ));
+                }
+                match path.trim_start_matches("/debug/consensus/block_production/") {
+                    "pause" => block_production::handle_pause_request(req).await,
+                    "resume" => block_production::handle_resume_request(req).await,
+                    "mine" => {
+                        let aptos_db = context.aptos_db.read().clone();
+                        if let Some(aptos_db) = aptos_db {
+                            block_production::handle_mine_request(req, aptos_db).await
+                        } else {
+                            Ok(reply_with_status(
+                                StatusCode::NOT_FOUND,
+                                "Aptos db is not available.",
+                            ))
+                        }
+                    },
+                    _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
+                }
+            },
+                        (hyper::Method::GET, "/debug/consensus/time/offset") => {
+                block_production::handle_time_offset_request(req).await
+            },
+                        (hyper::Method::POST, "/debug/consensus/time") => {
+                if !Self::is_test_chain() {
+                    return Ok(reply_with_status(
+                        StatusCode::FORBIDDEN,
+                        "The consensus clock can only be changed on a test chain.",
+                    ));
+                }
+                block_production::handle_time_request(req).await
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
+
+    /// Whether the node runs a single node test chain, e.g. a localnet. Controls that
+    /// would break consensus with other validators are limited to such chains.
+        fn is_test_chain() -> bool {
+        aptos_node_identity::chain_id() == Some(ChainId::test())
+    }
 }
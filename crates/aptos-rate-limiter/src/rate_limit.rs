@@ -174,6 +174,17 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects all buckets which are not in use and have been idle long enough to
+    /// refill completely.  A new bucket for the same key starts with at most as many tokens, so
+    /// this never grants a key more tokens.  Returns the number of buckets removed.
+    pub fn garbage_collect_idle_buckets(&self) -> usize {
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        // Nobody else can get hold of an unshared bucket while we hold the write lock
+        buckets.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.lock().is_full());
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -373,6 +384,12 @@ impl Bucket {
         self.tokens = min(self.size, self.tokens.saturating_add(new_tokens));
     }
 
+    /// Tells us if the bucket holds as many tokens as it can
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.size
+    }
+
     /// Returns tokens that were unused
     pub fn return_tokens(&mut self, new_tokens: usize) {
         self.allowed_in_period = self.allowed_in_period.saturating_sub(new_tokens);
@@ -549,4 +566,30 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_idle_garbage_collection() {
+        let rate_limiter = TokenBucketRateLimiter::test(1, 1);
+
+        // Create a bucket to hold onto, one which is idle and one which is used up
+        let _bucket_arc = rate_limiter.bucket("in use");
+        rate_limiter.bucket("idle");
+        let used_arc = rate_limiter.bucket("used");
+        let time_of_next_refill = {
+            let mut bucket = used_arc.lock();
+            bucket.acquire_all_tokens(1).unwrap();
+            bucket.time_of_next_refill()
+        };
+        drop(used_arc);
+        assert_num_keys(&rate_limiter, 3);
+
+        // Only the idle bucket is collected
+        assert_eq!(1, rate_limiter.garbage_collect_idle_buckets());
+        assert_num_keys(&rate_limiter, 2);
+
+        // Once refilled, the used bucket is idle too
+        sleep(time_of_next_refill.duration_since(Instant::now()));
+        assert_eq!(1, rate_limiter.garbage_collect_idle_buckets());
+        assert_num_keys(&rate_limiter, 1);
+    }
 }
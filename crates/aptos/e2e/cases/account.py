@@ -2,6 +2,7 @@
 # SPDX-License-Identifier: Apache-2.0
 
 import json
+import os
 import secrets
 
 from common import OTHER_ACCOUNT_ONE, TestError
@@ -275,3 +276,65 @@ def test_account_resource_account(run_helper: RunHelper, test_name=None):
         raise TestError(
             "Cannot find the resource account in the account list after resource account creation"
         )
+
+
+@test_case
+async def test_account_bulk_transfer_resume(run_helper: RunHelper, test_name=None):
+    # Transfer to new accounts, one per transaction, so the transfer takes long enough
+    # to be interrupted halfway through.
+    recipients = [f"0x{secrets.token_hex(32)}" for _ in range(10)]
+    amount = 1000
+    csv_path = os.path.join(run_helper.host_working_directory, "bulk_transfer.csv")
+    with open(csv_path, "w") as f:
+        f.write("address,amount\n")
+        for recipient in recipients:
+            f.write(f"{recipient},{amount}\n")
+    state_path = os.path.join(
+        run_helper.host_working_directory, "bulk_transfer.state.json"
+    )
+    command = [
+        "aptos",
+        "account",
+        "bulk-transfer",
+        "--csv",
+        "bulk_transfer.csv",
+        "--batch-size",
+        "1",
+        "--max-in-flight",
+        "1",
+        "--assume-yes",
+    ]
+
+    # Interrupt the transfer once some, but not all, of the transactions are committed.
+    def some_succeeded():
+        try:
+            with open(state_path) as f:
+                batches = json.load(f)["batches"]
+        except (OSError, ValueError):
+            return False
+        statuses = [batch["status"] for batch in batches]
+        return "succeeded" in statuses and "pending" in statuses
+
+    interrupted = run_helper.run_command_until_interrupted(
+        test_name, command, some_succeeded
+    )
+    if not interrupted:
+        raise TestError("The bulk transfer finished before it could be interrupted")
+
+    # Running the transfer again picks up where it left off.
+    result = run_helper.run_command(test_name, command)
+    summary = json.loads(result.stdout)["Result"]
+    if summary["succeeded_transfers"] != len(recipients):
+        raise TestError(
+            f"{summary['succeeded_transfers']} of {len(recipients)} transfers succeeded"
+        )
+    if summary["failed_transfers"] or summary["balance_discrepancies"]:
+        raise TestError(f"The bulk transfer didn't complete cleanly: {summary}")
+
+    # Each recipient was paid exactly once.
+    for recipient in recipients:
+        balance = int(await run_helper.api_client.account_balance(recipient))
+        if balance != amount:
+            raise TestError(
+                f"Account {recipient} has balance {balance}, expected {amount}"
+            )
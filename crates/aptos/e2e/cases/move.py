@@ -2,6 +2,9 @@
 # SPDX-License-Identifier: Apache-2.0
 
 import json
+import os
+import re
+import subprocess
 
 from common import TestError
 from test_helpers import RunHelper
@@ -289,3 +292,75 @@ def test_move_view(run_helper: RunHelper, test_name=None):
     response = json.loads(response.stdout)
     if response["Result"] == None or len(response["Result"]) != 9:
         raise TestError(f"View function [test_vector] did not return correct result")
+
+
+@test_case
+def test_move_test_gas_snapshot(run_helper: RunHelper, test_name=None):
+    # Write a package with a test to the working directory, which is mounted into the
+    # container, so the CLI can write the gas snapshot next to it.
+    package_dir = "move/gas-snapshot"
+    host_package_dir = os.path.join(run_helper.host_working_directory, package_dir)
+    os.makedirs(os.path.join(host_package_dir, "sources"), exist_ok=True)
+    with open(os.path.join(host_package_dir, "Move.toml"), "w") as f:
+        f.write(
+            '[package]\nname = "GasSnapshot"\nversion = "0.0.0"\n\n'
+            '[addresses]\naddr = "0xcafe"\n'
+        )
+    with open(os.path.join(host_package_dir, "sources", "counter.move"), "w") as f:
+        f.write(
+            "module addr::counter {\n"
+            "    #[test]\n"
+            "    fun test_count() {\n"
+            "        let i = 0;\n"
+            "        while (i < 100) i = i + 1;\n"
+            "        assert!(i == 100, 0);\n"
+            "    }\n"
+            "}\n"
+        )
+    snapshot_path = os.path.join(host_package_dir, ".gas-snapshot")
+
+    def read_snapshot():
+        with open(snapshot_path) as f:
+            entries = re.findall(r"^(.+) \(gas: (\d+)\)$", f.read(), re.MULTILINE)
+        return {name: int(gas) for name, gas in entries}
+
+    def run_test(*args):
+        return run_helper.run_command(
+            test_name,
+            ["aptos", "move", "test", "--package-dir", package_dir, "--gas-snapshot"]
+            + list(args),
+        )
+
+    # The first run writes the snapshot.
+    response = run_test()
+    if "Wrote the gas used by 1 tests to the gas snapshot" not in response.stdout:
+        raise TestError(f"Gas snapshot was not written: {response.stdout}")
+    snapshot = read_snapshot()
+    if len(snapshot) != 1 or list(snapshot.values())[0] <= 0:
+        raise TestError(f"Unexpected gas snapshot: {snapshot}")
+    (test, gas) = list(snapshot.items())[0]
+    if not test.endswith("::counter::test_count"):
+        raise TestError(f"Unexpected test in the gas snapshot: {test}")
+
+    # Pretend the test used twice as much gas before, which is a 50% decrease.
+    with open(snapshot_path, "w") as f:
+        f.write(f"{test} (gas: {gas * 2})\n")
+
+    # Within the tolerance the tests pass, and the snapshot is not changed.
+    run_test("--gas-tolerance", "60")
+    if read_snapshot() != {test: gas * 2}:
+        raise TestError("Gas snapshot was changed without --update-gas-snapshot")
+
+    # Outside of the tolerance the tests fail.
+    try:
+        run_test("--gas-tolerance", "10")
+        raise TestError("Tests passed though the gas used changed by 50%")
+    except subprocess.CalledProcessError as e:
+        if "changed beyond the tolerance of 10%" not in e.stdout:
+            raise TestError(f"Unexpected failure: {e.stdout} {e.stderr}")
+
+    # Updating the snapshot accepts the change, after which the tests pass again.
+    run_test("--update-gas-snapshot")
+    if read_snapshot() != {test: gas}:
+        raise TestError(f"Gas snapshot was not updated: {read_snapshot()}")
+    run_test()
@@ -33,6 +33,7 @@ import shutil
 import sys
 
 from cases.account import (
+    test_account_bulk_transfer_resume,
     test_account_create_and_transfer,
     test_account_fund_with_faucet,
     test_account_list,
@@ -47,6 +48,7 @@ from cases.move import (
     test_move_compile_script,
     test_move_publish,
     test_move_run,
+    test_move_test_gas_snapshot,
     test_move_view,
 )
 from cases.node import (
@@ -150,6 +152,7 @@ async def run_tests(run_helper):
     # Run account tests.
     await test_account_fund_with_faucet(run_helper)
     await test_account_create_and_transfer(run_helper)
+    await test_account_bulk_transfer_resume(run_helper)
     test_account_list(run_helper)
     test_account_lookup_address(run_helper)
     test_account_resource_account(run_helper)
@@ -163,6 +166,7 @@ async def run_tests(run_helper):
     test_move_publish(run_helper)
     test_move_run(run_helper)
     test_move_view(run_helper)
+    test_move_test_gas_snapshot(run_helper)
 
     # Run stake subcommand group tests.
     """
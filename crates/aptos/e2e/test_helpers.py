@@ -7,6 +7,7 @@ import os
 import pathlib
 import shutil
 import subprocess
+import time
 import traceback
 from dataclasses import dataclass
 
@@ -65,43 +66,7 @@ class RunHelper:
         file_name = f"{self.test_count:03}_{test_name}"
         self.test_count += 1
 
-        # If we're in a CI environment it is necessary to set the --user, otherwise it
-        # is not possible to interact with the files in the bindmount. For more details
-        # see here: https://github.com/community/community/discussions/44243.
-        if os.environ.get("CI"):
-            user_args = ["--user", f"{os.getuid()}:{os.getgid()}"]
-        else:
-            user_args = []
-
-        # Build command.
-        if self.image_tag:
-            full_command = (
-                [
-                    "docker",
-                    "run",
-                ]
-                + user_args
-                + [
-                    "-e",
-                    # This is necessary to force the CLI to place the `.move` directory
-                    # inside the bindmount dir, which is the only writeable directory
-                    # inside the container when in CI. It's fine to do it outside of CI
-                    # as well.
-                    f"HOME={WORKING_DIR_IN_CONTAINER}",
-                    "--rm",
-                    "--network",
-                    "host",
-                    "-i",
-                    "-v",
-                    f"{self.host_working_directory}:{WORKING_DIR_IN_CONTAINER}",
-                    "--workdir",
-                    WORKING_DIR_IN_CONTAINER,
-                    self.build_image_name(),
-                ]
-                + command
-            )
-        else:
-            full_command = [self.cli_path] + command[1:]
+        full_command = self.build_command(command)
         LOG.debug(f"Running command: {full_command}")
 
         # Create the output directory if necessary.
@@ -150,6 +115,101 @@ class RunHelper:
 
             raise
 
+    # This function runs a CLI command like run_command, but kills it as soon as
+    # should_interrupt returns true, to test how the CLI recovers from being
+    # interrupted. Returns whether the command was interrupted before it finished.
+    def run_command_until_interrupted(
+        self, test_name, command, should_interrupt, timeout_secs=120
+    ):
+        file_name = f"{self.test_count:03}_{test_name}"
+        self.test_count += 1
+
+        # Name the container, so it can be killed rather than just the docker client.
+        container_name = f"aptos-cli-e2e-{file_name}".replace("_", "-")
+        full_command = self.build_command(command, container_name=container_name)
+        LOG.debug(f"Running command until interrupted: {full_command}")
+
+        out_path = os.path.join(self.host_working_directory, "out")
+        pathlib.Path(out_path).mkdir(exist_ok=True)
+        with open(os.path.join(out_path, f"{file_name}.command"), "w") as f:
+            f.write(" ".join(command))
+
+        process = subprocess.Popen(
+            full_command,
+            cwd=self.host_working_directory if self.cli_path else None,
+            stdout=subprocess.PIPE,
+            stderr=subprocess.PIPE,
+            universal_newlines=True,
+        )
+        interrupted = False
+        deadline = time.time() + timeout_secs
+        while process.poll() is None:
+            if should_interrupt():
+                if self.image_tag:
+                    subprocess.run(["docker", "kill", container_name], check=False)
+                process.kill()
+                interrupted = True
+                break
+            if time.time() > deadline:
+                process.kill()
+                raise RuntimeError(f"{test_name} was not interrupted in time")
+            time.sleep(0.1)
+        stdout, stderr = process.communicate()
+        write_subprocess_out(
+            out_path,
+            file_name,
+            subprocess.CompletedProcess(
+                full_command, process.returncode, stdout, stderr
+            ),
+        )
+        LOG.debug(f"Subcommand interrupted: {interrupted}")
+        return interrupted
+
+    # Builds the command to run the CLI with, in a docker container if an image tag
+    # is set.
+    def build_command(self, command, container_name=None):
+        # If we're in a CI environment it is necessary to set the --user, otherwise it
+        # is not possible to interact with the files in the bindmount. For more details
+        # see here: https://github.com/community/community/discussions/44243.
+        if os.environ.get("CI"):
+            user_args = ["--user", f"{os.getuid()}:{os.getgid()}"]
+        else:
+            user_args = []
+
+        # Build command.
+        if self.image_tag:
+            full_command = (
+                [
+                    "docker",
+                    "run",
+                ]
+                + user_args
+                + [
+                    "-e",
+                    # This is necessary to force the CLI to place the `.move` directory
+                    # inside the bindmount dir, which is the only writeable directory
+                    # inside the container when in CI. It's fine to do it outside of CI
+                    # as well.
+                    f"HOME={WORKING_DIR_IN_CONTAINER}",
+                    "--rm",
+                ]
+                + (["--name", container_name] if container_name else [])
+                + [
+                    "--network",
+                    "host",
+                    "-i",
+                    "-v",
+                    f"{self.host_working_directory}:{WORKING_DIR_IN_CONTAINER}",
+                    "--workdir",
+                    WORKING_DIR_IN_CONTAINER,
+                    self.build_image_name(),
+                ]
+                + command
+            )
+        else:
+            full_command = [self.cli_path] + command[1:]
+        return full_command
+
     # Top level function to run any preparation.
     def prepare(self):
         self.prepare_move()
@@ -0,0 +1,1055 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{
+        load_account_arg, AccountType, CliCommand, CliError, CliTypedResult, MoveType,
+        TransactionOptions,
+    },
+    utils::{
+        create_dir_if_not_exist, get_account_with_state, prompt_yes_with_override, read_from_file,
+        write_to_file,
+    },
+};
+use aptos_api_types::ViewFunction;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    HashValue,
+};
+use aptos_global_constants::adjust_gas_headroom;
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode},
+    error::{AptosErrorResponse, RestError},
+    Client,
+};
+use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+    utility_coin::APTOS_COIN_TYPE,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use futures::{future::join_all, stream, Future, StreamExt, TryStreamExt};
+use move_core_types::{
+    ident_str,
+    language_storage::{ModuleId, TypeTag},
+    parser::parse_type_tag,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const US_IN_SECS: u64 = 1_000_000;
+
+/// Transfer APT, a coin or a fungible asset to many accounts
+///
+/// Recipients are read from a CSV file with an `address,amount` row per transfer, and an
+/// optional header row. Coins are transferred in batches with
+/// `0x1::aptos_account::batch_transfer` or `0x1::aptos_account::batch_transfer_coins`.
+/// As there is no batch transfer for fungible assets, each fungible asset transfer is a
+/// transaction of its own.
+///
+/// Transactions are signed with explicit sequence numbers and several of them are in
+/// flight at once. Failed batches are retried. Progress is recorded in a state file, so
+/// an interrupted or partially failed run picks up where it left off when run again.
+/// Once done, the balances of the recipients are checked against the amounts that were
+/// transferred to them.
+///
+/// Only accounts with a local private key are supported.
+#[derive(Debug, Parser)]
+pub struct BulkTransfer {
+    /// CSV file of `address,amount` rows
+    ///
+    /// Amounts are in the smallest unit of the asset, e.g. Octas for APT.
+    #[clap(long, value_parser)]
+    pub(crate) csv: PathBuf,
+
+    /// Type of the coin to transfer
+    ///
+    /// Defaults to APT. Example: `0x1::aptos_coin::AptosCoin`
+    #[clap(long, conflicts_with = "fa_metadata")]
+    pub(crate) coin_type: Option<MoveType>,
+
+    /// Address of the metadata object of the fungible asset to transfer
+    #[clap(long, value_parser = load_account_arg)]
+    pub(crate) fa_metadata: Option<AccountAddress>,
+
+    /// Maximum number of recipients per transaction
+    ///
+    /// Gas is estimated by simulating the largest batch. If recipients' accounts are
+    /// created by the transfers, set `--max-gas` to leave room for them.
+    #[clap(long, default_value_t = 100)]
+    pub(crate) batch_size: usize,
+
+    /// Maximum number of transactions submitted at once
+    #[clap(long, default_value_t = 8)]
+    pub(crate) max_in_flight: usize,
+
+    /// Number of times a failed batch is retried before giving up on it
+    #[clap(long, default_value_t = 3)]
+    pub(crate) max_retries: u32,
+
+    /// File to record progress in
+    ///
+    /// Defaults to `<csv>.state.json` next to the CSV file.
+    #[clap(long, value_parser)]
+    pub(crate) state_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+/// A transfer read from the CSV file
+#[derive(Clone, Debug)]
+struct Transfer {
+    line: u64,
+    address: AccountAddress,
+    amount: u64,
+}
+
+enum Asset {
+    Coin(TypeTag),
+    FungibleAsset(AccountAddress),
+}
+
+impl Asset {
+    fn describe(&self) -> String {
+        match self {
+            Asset::Coin(coin_type) => coin_type.to_string(),
+            Asset::FungibleAsset(metadata) => metadata.to_hex_literal(),
+        }
+    }
+
+    fn payload(&self, transfers: &[Transfer]) -> TransactionPayload {
+        let recipients = transfers.iter().map(|transfer| transfer.address).collect();
+        let amounts = transfers.iter().map(|transfer| transfer.amount).collect();
+        match self {
+            Asset::Coin(coin_type) if coin_type == &*APTOS_COIN_TYPE => {
+                aptos_stdlib::aptos_account_batch_transfer(recipients, amounts)
+            },
+            Asset::Coin(coin_type) => aptos_stdlib::aptos_account_batch_transfer_coins(
+                coin_type.clone(),
+                recipients,
+                amounts,
+            ),
+            Asset::FungibleAsset(metadata) => {
+                // Fungible asset batches always have a single transfer
+                let transfer = &transfers[0];
+                TransactionPayload::EntryFunction(EntryFunction::new(
+                    ModuleId::new(
+                        AccountAddress::ONE,
+                        ident_str!("primary_fungible_store").to_owned(),
+                    ),
+                    ident_str!("transfer").to_owned(),
+                    vec![fungible_asset_metadata_type()],
+                    vec![
+                        bcs::to_bytes(metadata).unwrap(),
+                        bcs::to_bytes(&transfer.address).unwrap(),
+                        bcs::to_bytes(&transfer.amount).unwrap(),
+                    ],
+                ))
+            },
+        }
+    }
+
+    fn balance_view(&self, owner: AccountAddress) -> ViewFunction {
+        match self {
+            Asset::Coin(coin_type) => ViewFunction {
+                module: ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned()),
+                function: ident_str!("balance").to_owned(),
+                ty_args: vec![coin_type.clone()],
+                args: vec![bcs::to_bytes(&owner).unwrap()],
+            },
+            Asset::FungibleAsset(metadata) => ViewFunction {
+                module: ModuleId::new(
+                    AccountAddress::ONE,
+                    ident_str!("primary_fungible_store").to_owned(),
+                ),
+                function: ident_str!("balance").to_owned(),
+                ty_args: vec![fungible_asset_metadata_type()],
+                args: vec![
+                    bcs::to_bytes(&owner).unwrap(),
+                    bcs::to_bytes(metadata).unwrap(),
+                ],
+            },
+        }
+    }
+}
+
+fn fungible_asset_metadata_type() -> TypeTag {
+    parse_type_tag("0x1::fungible_asset::Metadata").unwrap()
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchStatus {
+    Pending,
+    /// Submitted, but not known to be committed or expired
+    Submitted,
+    Succeeded,
+    /// Out of retries
+    Failed,
+}
+
+/// A transaction's worth of transfers
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Batch {
+    /// Index of the first transfer of the batch
+    start: usize,
+    /// Index one past the last transfer of the batch
+    end: usize,
+    status: BatchStatus,
+    attempts: u32,
+    /// The last transaction submitted for the batch
+    transaction_hash: Option<HashValue>,
+    expiration_timestamp_secs: Option<u64>,
+    version: Option<u64>,
+    error: Option<String>,
+}
+
+/// Splits the transfers into batches of at most `batch_size` transfers each.
+fn plan_batches(transfers: usize, batch_size: usize) -> Vec<Batch> {
+    (0..transfers)
+        .step_by(batch_size)
+        .map(|start| Batch {
+            start,
+            end: std::cmp::min(start + batch_size, transfers),
+            status: BatchStatus::Pending,
+            attempts: 0,
+            transaction_hash: None,
+            expiration_timestamp_secs: None,
+            version: None,
+            error: None,
+        })
+        .collect()
+}
+
+/// Progress of a bulk transfer, as recorded in the state file
+#[derive(Debug, Deserialize, Serialize)]
+struct BulkTransferState {
+    /// Hash of the CSV file, so that progress isn't applied to a different list
+    csv_hash: HashValue,
+    asset: String,
+    sender: AccountAddress,
+    /// Balances of the recipients before anything was transferred
+    initial_balances: BTreeMap<AccountAddress, u64>,
+    batches: Vec<Batch>,
+}
+
+impl BulkTransferState {
+    fn load(path: &Path) -> CliTypedResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&read_from_file(path)?)
+            .map(Some)
+            .map_err(|err| CliError::UnableToReadFile(path.display().to_string(), err.to_string()))
+    }
+
+    fn save(&self, path: &Path) -> CliTypedResult<()> {
+        if let Some(dir) = path.parent() {
+            create_dir_if_not_exist(dir)?;
+        }
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(path, "Bulk transfer state", &bytes)
+    }
+
+    fn with_status(&self, status: BatchStatus) -> impl Iterator<Item = usize> + '_ {
+        self.batches
+            .iter()
+            .enumerate()
+            .filter(move |(_, batch)| batch.status == status)
+            .map(|(index, _)| index)
+    }
+
+    fn pending(&self) -> impl Iterator<Item = usize> + '_ {
+        self.with_status(BatchStatus::Pending)
+    }
+
+    /// Gives the batches that ran out of retries in a previous run another chance.
+    fn retry_failed(&mut self) {
+        for batch in &mut self.batches {
+            if batch.status == BatchStatus::Failed {
+                batch.status = BatchStatus::Pending;
+                batch.attempts = 0;
+            }
+        }
+    }
+
+    /// Records the transaction of a batch before it is submitted, so that a later run can
+    /// tell whether it was committed if this one is interrupted.
+    fn record_submitted(
+        &mut self,
+        index: usize,
+        transaction_hash: HashValue,
+        expiration_timestamp_secs: u64,
+    ) {
+        let batch = &mut self.batches[index];
+        batch.status = BatchStatus::Submitted;
+        batch.attempts += 1;
+        batch.transaction_hash = Some(transaction_hash);
+        batch.expiration_timestamp_secs = Some(expiration_timestamp_secs);
+        batch.error = None;
+    }
+
+    /// Records that submitting the transaction of the batch at `position` of `indices`
+    /// failed, where the batches were submitted in order.
+    fn record_submit_failure(&mut self, indices: &[usize], position: usize, err: &RestError) {
+        // A transaction the node rejected can't be committed. Any other failure leaves it
+        // submitted, to be looked up once it has expired.
+        if let RestError::Api(AptosErrorResponse { status_code, .. }) = err {
+            if status_code.is_client_error() {
+                let batch = &mut self.batches[indices[position]];
+                batch.status = BatchStatus::Pending;
+                batch.error = Some(err.to_string());
+            }
+        }
+        // The following transactions can't be executed without this one's sequence number,
+        // so they are not submitted
+        for index in &indices[position + 1..] {
+            let batch = &mut self.batches[*index];
+            batch.status = BatchStatus::Pending;
+            batch.attempts -= 1;
+            batch.transaction_hash = None;
+            batch.expiration_timestamp_secs = None;
+        }
+    }
+
+    /// Gives up on the pending batches which are out of retries.
+    fn fail_exhausted(&mut self, max_retries: u32) {
+        for batch in &mut self.batches {
+            if batch.status == BatchStatus::Pending && batch.attempts > max_retries {
+                batch.status = BatchStatus::Failed;
+            }
+        }
+    }
+}
+
+/// A transfer that didn't go through
+#[derive(Debug, Serialize)]
+pub struct FailedTransfer {
+    /// Line of the transfer in the CSV file
+    pub line: u64,
+    pub address: AccountAddress,
+    pub amount: u64,
+    pub transaction_hash: Option<HashValue>,
+    pub error: Option<String>,
+}
+
+/// A recipient whose balance isn't what the transfers would make it
+///
+/// This is expected if the recipient's balance changed for other reasons during the
+/// transfer.
+#[derive(Debug, Serialize)]
+pub struct BalanceDiscrepancy {
+    pub address: AccountAddress,
+    pub initial_balance: u64,
+    pub expected_balance: u64,
+    pub balance: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkTransferSummary {
+    pub asset: String,
+    pub sender: AccountAddress,
+    pub state_file: PathBuf,
+    pub transfers: usize,
+    pub succeeded_transfers: usize,
+    pub amount_transferred: u128,
+    pub transactions: usize,
+    pub failed_transfers: Vec<FailedTransfer>,
+    pub balance_discrepancies: Vec<BalanceDiscrepancy>,
+}
+
+#[async_trait]
+impl CliCommand<BulkTransferSummary> for BulkTransfer {
+    fn command_name(&self) -> &'static str {
+        "BulkTransfer"
+    }
+
+    async fn execute(self) -> CliTypedResult<BulkTransferSummary> {
+        if self.txn_options.fee_payer_options.signer()?.is_some()
+            || self.txn_options.fee_payer_options.signature()?.is_some()
+        {
+            return Err(CliError::CommandArgumentError(
+                "Bulk transfers can't be paid for by a fee payer".to_string(),
+            ));
+        }
+        if !matches!(
+            self.txn_options.get_transaction_account_type()?,
+            AccountType::Local
+        ) {
+            return Err(CliError::CommandArgumentError(
+                "Bulk transfers can only be signed with a local private key".to_string(),
+            ));
+        }
+        if self.batch_size == 0 || self.max_in_flight == 0 {
+            return Err(CliError::CommandArgumentError(
+                "--batch-size and --max-in-flight must be greater than 0".to_string(),
+            ));
+        }
+
+        let asset = match (&self.coin_type, self.fa_metadata) {
+            (Some(coin_type), _) => Asset::Coin(
+                TypeTag::try_from(coin_type.clone())
+                    .map_err(|err| CliError::UnableToParse("--coin-type", err.to_string()))?,
+            ),
+            (None, Some(metadata)) => Asset::FungibleAsset(metadata),
+            (None, None) => Asset::Coin(APTOS_COIN_TYPE.clone()),
+        };
+        let batch_size = match asset {
+            Asset::Coin(_) => self.batch_size,
+            Asset::FungibleAsset(_) => 1,
+        };
+
+        let csv_bytes = read_from_file(&self.csv)?;
+        let transfers = parse_transfers(&self.csv, &csv_bytes)?;
+        let csv_hash = HashValue::sha3_256_of(&csv_bytes);
+        let recipients: BTreeSet<_> = transfers.iter().map(|transfer| transfer.address).collect();
+        let state_file = self
+            .state_file
+            .clone()
+            .unwrap_or_else(|| self.csv.with_extension("state.json"));
+
+        let client = self.txn_options.rest_client()?;
+        let (private_key, sender) = self.txn_options.get_key_and_address()?;
+
+        let mut state = match BulkTransferState::load(&state_file)? {
+            Some(state) => {
+                if state.csv_hash != csv_hash {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "{} has changed since {} was written, use another state file to start over",
+                        self.csv.display(),
+                        state_file.display()
+                    )));
+                }
+                if state.asset != asset.describe() || state.sender != sender {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "{} records a transfer of {} from {}, use another state file to start over",
+                        state_file.display(),
+                        state.asset,
+                        state.sender
+                    )));
+                }
+                eprintln!("Resuming from {}", state_file.display());
+                state
+            },
+            None => {
+                let initial_balances =
+                    get_balances(&client, &asset, &recipients, self.max_in_flight).await?;
+                let batches = plan_batches(transfers.len(), batch_size);
+                BulkTransferState {
+                    csv_hash,
+                    asset: asset.describe(),
+                    sender,
+                    initial_balances,
+                    batches,
+                }
+            },
+        };
+        state.retry_failed();
+        state.save(&state_file)?;
+
+        // Transactions of a previous run that may or may not have been committed
+        let wait = |hash, expiration_timestamp_secs| {
+            wait_for_transaction(&client, hash, expiration_timestamp_secs)
+        };
+        let result = resolve_submitted(&mut state, wait).await;
+        state.save(&state_file)?;
+        result?;
+
+        let mut gas = None;
+        while state.pending().next().is_some() {
+            let (account, ledger) = get_account_with_state(&client, sender).await?;
+            let chain_id = ChainId::new(ledger.chain_id);
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+                .as_secs();
+            // Expire relative to the blockchain clock if it is ahead
+            let expiration_timestamp_secs = std::cmp::max(now, ledger.timestamp_usecs / US_IN_SECS)
+                + self.txn_options.gas_options.expiration_secs;
+
+            let (gas_unit_price, max_gas) = match gas {
+                Some(gas) => gas,
+                None => {
+                    let largest = state
+                        .pending()
+                        .max_by_key(|index| state.batches[*index].end - state.batches[*index].start)
+                        .map(|index| &state.batches[index])
+                        .unwrap();
+                    let estimate = self
+                        .estimate_gas(
+                            &client,
+                            chain_id,
+                            Ed25519PublicKey::from(&private_key),
+                            sender,
+                            account.sequence_number,
+                            expiration_timestamp_secs,
+                            asset.payload(&transfers[largest.start..largest.end]),
+                            state.pending().count(),
+                        )
+                        .await?;
+                    gas = Some(estimate);
+                    estimate
+                },
+            };
+
+            let transaction_factory = TransactionFactory::new(chain_id)
+                .with_gas_unit_price(gas_unit_price)
+                .with_max_gas_amount(max_gas)
+                .with_transaction_expiration_time(expiration_timestamp_secs - now);
+            let mut sender_account =
+                LocalAccount::new(sender, private_key.clone(), account.sequence_number);
+            let transactions: Vec<(usize, SignedTransaction)> = state
+                .pending()
+                .take(self.max_in_flight)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|index| {
+                    let batch = &state.batches[index];
+                    let payload = asset.payload(&transfers[batch.start..batch.end]);
+                    let transaction = sender_account
+                        .sign_with_transaction_builder(transaction_factory.payload(payload));
+                    (index, transaction)
+                })
+                .collect();
+
+            // Recorded before submitting, so that a later run can tell whether they were
+            // committed if this one is interrupted
+            for (index, transaction) in &transactions {
+                state.record_submitted(
+                    *index,
+                    transaction.committed_hash(),
+                    transaction.expiration_timestamp_secs(),
+                );
+            }
+            state.save(&state_file)?;
+
+            let indices: Vec<usize> = transactions.iter().map(|(index, _)| *index).collect();
+            for (position, (_, transaction)) in transactions.iter().enumerate() {
+                if let Err(err) = client.submit(transaction).await {
+                    state.record_submit_failure(&indices, position, &err);
+                    break;
+                }
+            }
+            state.save(&state_file)?;
+
+            let result = resolve_submitted(&mut state, wait).await;
+            state.fail_exhausted(self.max_retries);
+            state.save(&state_file)?;
+            result?;
+
+            let done = state
+                .batches
+                .iter()
+                .filter(|batch| batch.status == BatchStatus::Succeeded)
+                .count();
+            eprintln!("{} of {} transactions succeeded", done, state.batches.len());
+        }
+
+        // Reconcile the transfers against the balances on chain
+        let balances = get_balances(&client, &asset, &recipients, self.max_in_flight).await?;
+        let mut expected_balances = state.initial_balances.clone();
+        let mut failed_transfers = vec![];
+        let mut succeeded_transfers = 0;
+        let mut amount_transferred = 0u128;
+        for batch in &state.batches {
+            for transfer in &transfers[batch.start..batch.end] {
+                if batch.status == BatchStatus::Succeeded {
+                    let expected = expected_balances.entry(transfer.address).or_default();
+                    *expected = expected.saturating_add(transfer.amount);
+                    succeeded_transfers += 1;
+                    amount_transferred += transfer.amount as u128;
+                } else {
+                    failed_transfers.push(FailedTransfer {
+                        line: transfer.line,
+                        address: transfer.address,
+                        amount: transfer.amount,
+                        transaction_hash: batch.transaction_hash,
+                        error: batch.error.clone(),
+                    });
+                }
+            }
+        }
+        let balance_discrepancies: Vec<_> = recipients
+            .iter()
+            .filter_map(|address| {
+                let initial_balance = state
+                    .initial_balances
+                    .get(address)
+                    .copied()
+                    .unwrap_or_default();
+                let expected_balance = expected_balances.get(address).copied().unwrap_or_default();
+                let balance = balances.get(address).copied().unwrap_or_default();
+                (balance != expected_balance).then_some(BalanceDiscrepancy {
+                    address: *address,
+                    initial_balance,
+                    expected_balance,
+                    balance,
+                })
+            })
+            .collect();
+
+        if !failed_transfers.is_empty() {
+            eprintln!(
+                "{} transfers failed, run the command again to retry them",
+                failed_transfers.len()
+            );
+        }
+        if !balance_discrepancies.is_empty() {
+            eprintln!(
+                "{} recipients don't have the expected balance",
+                balance_discrepancies.len()
+            );
+        }
+
+        Ok(BulkTransferSummary {
+            asset: state.asset,
+            sender,
+            state_file,
+            transfers: transfers.len(),
+            succeeded_transfers,
+            amount_transferred,
+            transactions: state.batches.len(),
+            failed_transfers,
+            balance_discrepancies,
+        })
+    }
+}
+
+impl BulkTransfer {
+    /// Returns the gas unit price and max gas for the transactions, and asks to confirm
+    /// their cost
+    async fn estimate_gas(
+        &self,
+        client: &Client,
+        chain_id: ChainId,
+        public_key: Ed25519PublicKey,
+        sender: AccountAddress,
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+        largest_payload: TransactionPayload,
+        transactions: usize,
+    ) -> CliTypedResult<(u64, u64)> {
+        let gas_options = &self.txn_options.gas_options;
+        let gas_unit_price = match gas_options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => client.estimate_gas_price().await?.into_inner().gas_estimate,
+        };
+        let max_gas = match gas_options.max_gas {
+            Some(max_gas) => max_gas,
+            None => {
+                let unsigned_transaction = TransactionFactory::new(chain_id)
+                    .with_gas_unit_price(gas_unit_price)
+                    .payload(largest_payload)
+                    .sender(sender)
+                    .sequence_number(sequence_number)
+                    .expiration_timestamp_secs(expiration_timestamp_secs)
+                    .build();
+                let signed_transaction = SignedTransaction::new(
+                    unsigned_transaction,
+                    public_key,
+                    Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+                );
+                let simulated_transactions = client
+                    .simulate_with_gas_estimation(&signed_transaction, true, false)
+                    .await?
+                    .into_inner();
+                let simulated_transaction = match simulated_transactions.first() {
+                    Some(transaction) => transaction,
+                    None => {
+                        return Err(CliError::UnexpectedError(
+                            "No simulation result returned by the node".to_string(),
+                        ))
+                    },
+                };
+                if !simulated_transaction.info.success {
+                    return Err(CliError::SimulationError(
+                        simulated_transaction.info.vm_status.clone(),
+                    ));
+                }
+                adjust_gas_headroom(
+                    simulated_transaction.info.gas_used.0,
+                    simulated_transaction.request.max_gas_amount.0,
+                )
+            },
+        };
+
+        let message = format!(
+            "Do you want to submit {} transactions for a maximum of {} Octas at a gas unit price of {} Octas?",
+            transactions,
+            transactions as u128 * max_gas as u128 * gas_unit_price as u128,
+            gas_unit_price
+        );
+        prompt_yes_with_override(&message, self.txn_options.prompt_options)?;
+        Ok((gas_unit_price, max_gas))
+    }
+}
+
+fn parse_transfers(path: &Path, bytes: &[u8]) -> CliTypedResult<Vec<Transfer>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(bytes);
+    let mut transfers = vec![];
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|err| {
+            CliError::UnableToReadFile(path.display().to_string(), err.to_string())
+        })?;
+        let line = record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or_default();
+        if record.len() != 2 {
+            return Err(CliError::UnableToParse(
+                "CSV file",
+                format!("line {} is not an `address,amount` row", line),
+            ));
+        }
+        if index == 0 && record[0].eq_ignore_ascii_case("address") {
+            continue;
+        }
+        let address = load_account_arg(&record[0]).map_err(|err| {
+            CliError::UnableToParse("CSV file", format!("line {}: {}", line, err))
+        })?;
+        let amount = record[1].parse::<u64>().map_err(|err| {
+            CliError::UnableToParse("CSV file", format!("line {}: {}", line, err))
+        })?;
+        transfers.push(Transfer {
+            line,
+            address,
+            amount,
+        });
+    }
+    if transfers.is_empty() {
+        return Err(CliError::CommandArgumentError(format!(
+            "No transfers in {}",
+            path.display()
+        )));
+    }
+    Ok(transfers)
+}
+
+async fn get_balances(
+    client: &Client,
+    asset: &Asset,
+    addresses: &BTreeSet<AccountAddress>,
+    parallelism: usize,
+) -> CliTypedResult<BTreeMap<AccountAddress, u64>> {
+    stream::iter(addresses.iter().map(|address| async move {
+        let response = client
+            .view_bcs_with_json_response(&asset.balance_view(*address), None)
+            .await?;
+        let balance = response
+            .inner()
+            .first()
+            .and_then(|value| value.as_str())
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| {
+                CliError::UnexpectedError(format!(
+                    "Unexpected balance of {} returned by the node",
+                    address
+                ))
+            })?;
+        Ok::<_, CliError>((*address, balance))
+    }))
+    .buffer_unordered(parallelism)
+    .try_collect()
+    .await
+}
+
+/// What became of a submitted transaction
+enum Outcome {
+    /// Committed at the version
+    Succeeded(Option<u64>),
+    /// Committed, but failed with the VM status
+    Failed(String),
+    Expired,
+}
+
+/// Waits for the transactions of submitted batches with `wait`, and records whether they
+/// succeeded
+///
+/// Batches whose transaction can't be found before it expires are left submitted, and
+/// the error is returned.
+async fn resolve_submitted<F, Fut>(state: &mut BulkTransferState, wait: F) -> CliTypedResult<()>
+where
+    F: Fn(HashValue, u64) -> Fut,
+    Fut: Future<Output = CliTypedResult<Outcome>>,
+{
+    let submitted: Vec<usize> = state.with_status(BatchStatus::Submitted).collect();
+    let outcomes = join_all(submitted.iter().map(|index| {
+        let batch = &state.batches[*index];
+        wait(
+            batch.transaction_hash.unwrap(),
+            batch.expiration_timestamp_secs.unwrap(),
+        )
+    }))
+    .await;
+
+    let mut result = Ok(());
+    for (index, outcome) in submitted.into_iter().zip(outcomes) {
+        let batch = &mut state.batches[index];
+        match outcome {
+            Ok(Outcome::Succeeded(version)) => {
+                batch.status = BatchStatus::Succeeded;
+                batch.version = version;
+            },
+            Ok(Outcome::Failed(vm_status)) => {
+                batch.status = BatchStatus::Pending;
+                batch.error = Some(vm_status);
+            },
+            Ok(Outcome::Expired) => {
+                batch.status = BatchStatus::Pending;
+                batch.error = Some("Transaction expired".to_string());
+            },
+            Err(err) => result = Err(err),
+        }
+    }
+    result
+}
+
+async fn wait_for_transaction(
+    client: &Client,
+    hash: HashValue,
+    expiration_timestamp_secs: u64,
+) -> CliTypedResult<Outcome> {
+    // Failed transactions are errors for the wait, so the outcome is looked up after it
+    let waited = client
+        .wait_for_transaction_by_hash(hash, expiration_timestamp_secs, None, None)
+        .await;
+    match client.get_transaction_by_hash(hash).await {
+        Ok(response) if !response.inner().is_pending() => {
+            let transaction = response.into_inner();
+            Ok(if transaction.success() {
+                Outcome::Succeeded(transaction.version())
+            } else {
+                Outcome::Failed(transaction.vm_status())
+            })
+        },
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::TransactionNotFound,
+                    ..
+                },
+            state: Some(state),
+            ..
+        })) if state.timestamp_usecs / US_IN_SECS >= expiration_timestamp_secs => {
+            Ok(Outcome::Expired)
+        },
+        _ => Err(CliError::ApiError(format!(
+            "Unable to tell whether transaction {} was committed: {}",
+            hash,
+            waited.err().map(|err| err.to_string()).unwrap_or_default()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn parse(csv: &str) -> CliTypedResult<Vec<Transfer>> {
+        parse_transfers(Path::new("transfers.csv"), csv.as_bytes())
+    }
+
+    fn hash(index: usize) -> HashValue {
+        HashValue::sha3_256_of(&index.to_le_bytes())
+    }
+
+    fn new_state(transfers: usize, batch_size: usize) -> BulkTransferState {
+        BulkTransferState {
+            csv_hash: HashValue::zero(),
+            asset: APTOS_COIN_TYPE.to_string(),
+            sender: AccountAddress::ONE,
+            initial_balances: BTreeMap::new(),
+            batches: plan_batches(transfers, batch_size),
+        }
+    }
+
+    fn statuses(state: &BulkTransferState) -> Vec<BatchStatus> {
+        state.batches.iter().map(|batch| batch.status).collect()
+    }
+
+    #[test]
+    fn test_parse_transfers() {
+        let transfers = parse(
+            "address,amount\n\
+             0x1, 100\n\
+             # a comment\n\
+             \x20 0x2 ,7\n",
+        )
+        .unwrap();
+        let transfers: Vec<_> = transfers
+            .iter()
+            .map(|transfer| (transfer.line, transfer.address, transfer.amount))
+            .collect();
+        assert_eq!(
+            transfers,
+            vec![(2, AccountAddress::ONE, 100), (4, AccountAddress::TWO, 7),]
+        );
+
+        // The header row is optional.
+        assert_eq!(parse("0x1,5").unwrap()[0].amount, 5);
+    }
+
+    #[test]
+    fn test_parse_invalid_transfers() {
+        for csv in ["0x1", "0x1,5,6", "0x1,-5", "0x1,five", "not an address,5"] {
+            assert!(
+                matches!(parse(csv), Err(CliError::UnableToParse("CSV file", _))),
+                "{}",
+                csv
+            );
+        }
+        match parse("0x1,5\n0x2,x\n") {
+            Err(CliError::UnableToParse(_, message)) => assert!(message.starts_with("line 2:")),
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        }
+        assert!(matches!(
+            parse("address,amount\n"),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_batches() {
+        let ranges: Vec<_> = plan_batches(7, 3)
+            .iter()
+            .map(|batch| (batch.start, batch.end))
+            .collect();
+        assert_eq!(ranges, vec![(0, 3), (3, 6), (6, 7)]);
+        assert_eq!(plan_batches(6, 3).len(), 2);
+        assert_eq!(plan_batches(1, 100).len(), 1);
+        assert!(plan_batches(2, 1)
+            .iter()
+            .all(|batch| batch.status == BatchStatus::Pending && batch.attempts == 0));
+    }
+
+    #[test]
+    fn test_submit_failures() {
+        let mut state = new_state(4, 1);
+        let indices = [0, 1, 2, 3];
+        for index in indices {
+            state.record_submitted(index, hash(index), 100);
+        }
+        assert_eq!(state.pending().count(), 0);
+
+        // A transaction which may have reached the node stays submitted, but the ones
+        // after it are not submitted.
+        state.record_submit_failure(&indices, 1, &RestError::Timeout("submit"));
+        assert_eq!(
+            statuses(&state),
+            vec![
+                BatchStatus::Submitted,
+                BatchStatus::Submitted,
+                BatchStatus::Pending,
+                BatchStatus::Pending,
+            ]
+        );
+        assert_eq!(state.batches[1].transaction_hash, Some(hash(1)));
+        assert_eq!(state.batches[2].attempts, 0);
+        assert_eq!(state.batches[2].transaction_hash, None);
+        assert_eq!(state.batches[2].expiration_timestamp_secs, None);
+
+        // A transaction the node rejected is retried.
+        let mut state = new_state(2, 1);
+        state.record_submitted(0, hash(0), 100);
+        state.record_submitted(1, hash(1), 100);
+        let rejected = RestError::from((
+            AptosError::new_with_error_code("invalid", AptosErrorCode::InvalidInput),
+            None,
+            StatusCode::BAD_REQUEST,
+        ));
+        state.record_submit_failure(&[0, 1], 0, &rejected);
+        assert_eq!(
+            statuses(&state),
+            vec![BatchStatus::Pending, BatchStatus::Pending]
+        );
+        assert_eq!(state.batches[0].attempts, 1);
+        assert!(state.batches[0].error.is_some());
+        assert_eq!(state.batches[1].attempts, 0);
+    }
+
+    #[test]
+    fn test_retries() {
+        let mut state = new_state(2, 1);
+        for _ in 0..3 {
+            state.record_submitted(0, hash(0), 100);
+            state.batches[0].status = BatchStatus::Pending;
+        }
+        state.fail_exhausted(3);
+        assert_eq!(state.batches[0].status, BatchStatus::Pending);
+
+        state.record_submitted(0, hash(0), 100);
+        state.batches[0].status = BatchStatus::Pending;
+        state.fail_exhausted(3);
+        assert_eq!(
+            statuses(&state),
+            vec![BatchStatus::Failed, BatchStatus::Pending]
+        );
+
+        // Failed batches get another round of retries when the transfer is run again.
+        state.retry_failed();
+        assert_eq!(state.batches[0].status, BatchStatus::Pending);
+        assert_eq!(state.batches[0].attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_submitted() {
+        let mut state = new_state(5, 1);
+        for index in 0..4 {
+            state.record_submitted(index, hash(index), 100);
+        }
+        let result = resolve_submitted(&mut state, |transaction_hash, expiration| async move {
+            assert_eq!(expiration, 100);
+            match (0..4).find(|index| hash(*index) == transaction_hash) {
+                Some(0) => Ok(Outcome::Succeeded(Some(10))),
+                Some(1) => Ok(Outcome::Failed("Move abort".to_string())),
+                Some(2) => Ok(Outcome::Expired),
+                _ => Err(CliError::ApiError("unreachable".to_string())),
+            }
+        })
+        .await;
+
+        // Batches which can't be resolved stay submitted, and the error is returned.
+        assert!(matches!(result, Err(CliError::ApiError(_))));
+        assert_eq!(
+            statuses(&state),
+            vec![
+                BatchStatus::Succeeded,
+                BatchStatus::Pending,
+                BatchStatus::Pending,
+                BatchStatus::Submitted,
+                BatchStatus::Pending,
+            ]
+        );
+        assert_eq!(state.batches[0].version, Some(10));
+        assert_eq!(state.batches[1].error.as_deref(), Some("Move abort"));
+        assert_eq!(
+            state.batches[2].error.as_deref(),
+            Some("Transaction expired")
+        );
+
+        // Only the batch still submitted is waited for again.
+        let result = resolve_submitted(&mut state, |transaction_hash, _| async move {
+            assert_eq!(transaction_hash, hash(3));
+            Ok(Outcome::Succeeded(Some(11)))
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(state.batches[3].status, BatchStatus::Succeeded);
+        assert_eq!(state.with_status(BatchStatus::Submitted).count(), 0);
+    }
+}
@@ -4,6 +4,7 @@
 use crate::common::types::{CliCommand, CliResult};
 use clap::Subcommand;
 
+pub mod bulk_transfer;
 pub mod create;
 pub mod create_resource_account;
 pub mod derive_resource_account;
@@ -19,6 +20,7 @@ pub mod transfer;
 /// account's resources, and transfer resources between accounts.
 #[derive(Debug, Subcommand)]
 pub enum AccountTool {
+    BulkTransfer(bulk_transfer::BulkTransfer),
     Create(create::CreateAccount),
     CreateResourceAccount(create_resource_account::CreateResourceAccount),
     DeriveResourceAccountAddress(derive_resource_account::DeriveResourceAccount),
@@ -32,6 +34,7 @@ pub enum AccountTool {
 impl AccountTool {
     pub async fn execute(self) -> CliResult {
         match self {
+            AccountTool::BulkTransfer(tool) => tool.execute_serialized().await,
             AccountTool::Create(tool) => tool.execute_serialized().await,
             AccountTool::CreateResourceAccount(tool) => tool.execute_serialized().await,
             AccountTool::DeriveResourceAccountAddress(tool) => tool.execute_serialized().await,
@@ -17,13 +17,15 @@ use crate::{
     config::GlobalConfig,
     genesis::git::from_yaml,
     move_tool::{ArgWithType, FunctionArgType, MemberId},
+    txn::OfflineTransaction,
 };
 use anyhow::Context;
 use aptos_api_types::ViewFunction;
 use aptos_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     encoding_type::{EncodingError, EncodingType},
-    x25519, PrivateKey, ValidCryptoMaterialStringExt,
+    hash::CryptoHash,
+    signing_message, x25519, PrivateKey, SigningKey, ValidCryptoMaterialStringExt,
 };
 use aptos_global_constants::adjust_gas_headroom;
 use aptos_keygen::KeyGen;
@@ -41,8 +43,9 @@ use aptos_sdk::{
 use aptos_types::{
     chain_id::ChainId,
     transaction::{
-        authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload, Script,
-        SignedTransaction, TransactionArgument, TransactionPayload, TransactionStatus,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
+        EntryFunction, MultisigTransactionPayload, Script, SignedTransaction, TransactionArgument,
+        TransactionPayload, TransactionStatus,
     },
 };
 use aptos_vm_types::output::VMOutput;
@@ -1505,6 +1508,199 @@ pub enum AccountType {
     HardwareWallet,
 }
 
+/// Signs on behalf of an account, either with a local private key or with a Ledger device
+pub(crate) enum AccountSigner {
+    Local(Ed25519PrivateKey),
+    HardwareWallet {
+        public_key: Ed25519PublicKey,
+        derivation_path: String,
+    },
+}
+
+impl AccountSigner {
+    /// Loads the signer of a profile, preferring its private key over its Ledger derivation path
+    pub fn from_profile(profile: &ProfileConfig, profile_name: &str) -> CliTypedResult<Self> {
+        if let Some(private_key) = &profile.private_key {
+            Ok(AccountSigner::Local(private_key.clone()))
+        } else if let (Some(public_key), Some(derivation_path)) =
+            (&profile.public_key, &profile.derivation_path)
+        {
+            Ok(AccountSigner::HardwareWallet {
+                public_key: public_key.clone(),
+                derivation_path: derivation_path.clone(),
+            })
+        } else {
+            Err(CliError::CommandArgumentError(format!(
+                "Profile {} has neither a private key nor a Ledger derivation path",
+                profile_name
+            )))
+        }
+    }
+
+    /// Loads the signer from `--private-key` or `--private-key-file`, with fallback to the
+    /// profile, along with the address it signs for
+    pub fn load_with_address(
+        private_key_options: &PrivateKeyInputOptions,
+        encoding: EncodingType,
+        profile_options: &ProfileOptions,
+        maybe_address: Option<AccountAddress>,
+    ) -> CliTypedResult<(Self, AccountAddress)> {
+        // Order of operations
+        // 1. CLI inputs
+        // 2. Profile
+        // 3. Derived
+        let (signer, maybe_config_address) =
+            if let Some(private_key) = private_key_options.extract_private_key_cli(encoding)? {
+                (AccountSigner::Local(private_key), None)
+            } else {
+                let profile = profile_options.profile()?;
+                let signer = AccountSigner::from_profile(
+                    &profile,
+                    profile_options.profile_name().unwrap_or(DEFAULT_PROFILE),
+                )?;
+                (signer, profile.account)
+            };
+        let address = maybe_address
+            .or(maybe_config_address)
+            .unwrap_or_else(|| account_address_from_public_key(&signer.public_key()));
+        Ok((signer, address))
+    }
+
+    pub fn public_key(&self) -> Ed25519PublicKey {
+        match self {
+            AccountSigner::Local(private_key) => private_key.public_key(),
+            AccountSigner::HardwareWallet { public_key, .. } => public_key.clone(),
+        }
+    }
+
+    /// Signs a transaction message, which is a `RawTransaction` for single signer transactions
+    /// and a `RawTransactionWithData` for multi-agent and fee payer transactions
+    pub fn sign<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+    ) -> CliTypedResult<AccountAuthenticator> {
+        let signature = match self {
+            AccountSigner::Local(private_key) => private_key.sign(message)?,
+            AccountSigner::HardwareWallet {
+                public_key,
+                derivation_path,
+            } => HardwareWalletAccount::new(
+                AuthenticationKey::ed25519(public_key).account_address(),
+                public_key.clone(),
+                derivation_path.clone(),
+                HardwareWalletType::Ledger,
+                0,
+            )
+            .sign_arbitrary_message(&signing_message(message)?)?,
+        };
+        Ok(AccountAuthenticator::ed25519(self.public_key(), signature))
+    }
+}
+
+/// Options for having another account pay the gas of a transaction
+#[derive(Debug, Default, Parser)]
+pub struct FeePayerOptions {
+    /// Profile of the account paying the gas of the transaction
+    ///
+    /// The fee payer signs with the profile's private key, or with its Ledger device if
+    /// the profile was created with `--ledger`.
+    /// Mutually exclusive with `--fee-payer-transaction-file`
+    #[clap(long, group = "fee_payer_input")]
+    pub(crate) fee_payer_profile: Option<String>,
+
+    /// Address of the account paying the gas of the transaction
+    ///
+    /// Overrides the account address of `--fee-payer-profile`, in the event that its
+    /// authentication key was rotated.  When used with `--fee-payer-transaction-file`, it
+    /// must match the fee payer of the transaction in the file.
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) fee_payer_address: Option<AccountAddress>,
+
+    /// File containing a transaction signed by its fee payer
+    ///
+    /// The transaction is built with `aptos txn build --fee-payer-address` and signed
+    /// offline by the fee payer with `aptos txn sign`.  The sender signs the exact
+    /// transaction in the file, so its gas and expiration settings take precedence over
+    /// the command's gas options.
+    /// Mutually exclusive with `--fee-payer-profile`
+    #[clap(long, group = "fee_payer_input", value_parser)]
+    pub(crate) fee_payer_transaction_file: Option<PathBuf>,
+}
+
+impl FeePayerOptions {
+    /// Loads the address and signer of the fee payer from `--fee-payer-profile`, if any
+    pub fn signer(&self) -> CliTypedResult<Option<(AccountAddress, AccountSigner)>> {
+        let profile_name = match &self.fee_payer_profile {
+            Some(profile_name) => profile_name,
+            None if self.fee_payer_address.is_some() && self.fee_payer_transaction_file.is_none() => {
+                return Err(CliError::CommandArgumentError(
+                    "--fee-payer-address requires one of ['--fee-payer-profile', '--fee-payer-transaction-file']"
+                        .to_string(),
+                ))
+            },
+            None => return Ok(None),
+        };
+        let profile = ProfileOptions {
+            profile: Some(profile_name.clone()),
+        }
+        .profile()?;
+        let signer = AccountSigner::from_profile(&profile, profile_name)?;
+        let address = if let Some(address) = self.fee_payer_address.or(profile.account) {
+            address
+        } else {
+            account_address_from_public_key(&signer.public_key())
+        };
+        Ok(Some((address, signer)))
+    }
+
+    /// Loads the transaction from `--fee-payer-transaction-file`, if any, and checks that it
+    /// is the transaction of `sender_address` with `payload`, signed by its fee payer
+    pub fn transaction(
+        &self,
+        sender_address: AccountAddress,
+        payload: &TransactionPayload,
+    ) -> CliTypedResult<Option<OfflineTransaction>> {
+        let path = match &self.fee_payer_transaction_file {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let txn = OfflineTransaction::load(path)?;
+        let fee_payer_address = txn.fee_payer_address.ok_or_else(|| {
+            CliError::CommandArgumentError(
+                "Fee payer transaction file has no fee payer".to_string(),
+            )
+        })?;
+        if let Some(address) = self.fee_payer_address {
+            if address != fee_payer_address {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Fee payer transaction file is paid by {}, not {}",
+                    fee_payer_address, address
+                )));
+            }
+        }
+        if !txn.secondary_signer_addresses.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "Fee payer transaction file has secondary signers, use `aptos txn sign` and `aptos txn submit` instead"
+                    .to_string(),
+            ));
+        }
+        if txn.raw_txn.sender() != sender_address {
+            return Err(CliError::CommandArgumentError(format!(
+                "Fee payer transaction file is sent by {}, not {}",
+                txn.raw_txn.sender(),
+                sender_address
+            )));
+        }
+        if txn.raw_txn.payload() != payload {
+            return Err(CliError::CommandArgumentError(
+                "Fee payer transaction file has a different payload".to_string(),
+            ));
+        }
+        txn.verify_signature(fee_payer_address)?;
+        Ok(Some(txn))
+    }
+}
+
 /// Common options for interacting with an account for a validator
 #[derive(Debug, Default, Parser)]
 pub struct TransactionOptions {
@@ -1527,6 +1723,8 @@ pub struct TransactionOptions {
     pub(crate) gas_options: GasOptions,
     #[clap(flatten)]
     pub(crate) prompt_options: PromptOptions,
+    #[clap(flatten)]
+    pub(crate) fee_payer_options: FeePayerOptions,
 
     /// If this option is set, simulate the transaction locally.
     #[clap(long)]
@@ -1628,6 +1826,22 @@ impl TransactionOptions {
         let client = self.rest_client()?;
         let (sender_public_key, sender_address) = self.get_public_key_and_address()?;
 
+        // A fee payer that signed offline has already fixed every field of the transaction
+        if let Some(txn) = self
+            .fee_payer_options
+            .transaction(sender_address, &payload)?
+        {
+            txn.summary().print()?;
+            prompt_yes_with_override(
+                "Do you want to submit this transaction?",
+                self.prompt_options,
+            )?;
+            return self
+                .submit_fee_payer_transaction(&client, txn, sender_address, sender_public_key)
+                .await;
+        }
+        let fee_payer = self.fee_payer_options.signer()?;
+
         // Ask to confirm price if the gas unit price is estimated above the lowest value when
         // it is automatically estimated
         let ask_to_confirm_price;
@@ -1657,7 +1871,10 @@ impl TransactionOptions {
         if now_usecs < state.timestamp_usecs - ACCEPTED_CLOCK_SKEW_US {
             eprintln!("Local clock is is skewed from blockchain clock.  Clock is more than {} seconds behind the blockchain {}", ACCEPTED_CLOCK_SKEW_US, state.timestamp_usecs / US_IN_SECS );
         }
-        let expiration_time_secs = now + self.gas_options.expiration_secs;
+        // Expire relative to the blockchain clock if it is ahead, e.g. because time was
+        // advanced on a localnet, or the transaction would already be expired.
+        let expiration_time_secs = std::cmp::max(now, state.timestamp_usecs / US_IN_SECS)
+            + self.gas_options.expiration_secs;
 
         let chain_id = ChainId::new(state.chain_id);
         // TODO: Check auth key against current private key and provide a better message
@@ -1680,11 +1897,27 @@ impl TransactionOptions {
                 .expiration_timestamp_secs(expiration_time_secs)
                 .build();
 
-            let signed_transaction = SignedTransaction::new(
-                unsigned_transaction,
-                sender_public_key.clone(),
-                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
-            );
+            let empty_signature = Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap();
+            let signed_transaction = if let Some((fee_payer_address, fee_payer_signer)) = &fee_payer
+            {
+                SignedTransaction::new_fee_payer(
+                    unsigned_transaction,
+                    AccountAuthenticator::ed25519(
+                        sender_public_key.clone(),
+                        empty_signature.clone(),
+                    ),
+                    vec![],
+                    vec![],
+                    *fee_payer_address,
+                    AccountAuthenticator::ed25519(fee_payer_signer.public_key(), empty_signature),
+                )
+            } else {
+                SignedTransaction::new(
+                    unsigned_transaction,
+                    sender_public_key.clone(),
+                    empty_signature,
+                )
+            };
 
             let txns = client
                 .simulate_with_gas_estimation(&signed_transaction, true, false)
@@ -1720,7 +1953,20 @@ impl TransactionOptions {
         let transaction_factory = TransactionFactory::new(chain_id)
             .with_gas_unit_price(gas_unit_price)
             .with_max_gas_amount(max_gas)
-            .with_transaction_expiration_time(self.gas_options.expiration_secs);
+            .with_transaction_expiration_time(expiration_time_secs - now);
+
+        if let Some((fee_payer_address, fee_payer_signer)) = fee_payer {
+            let raw_txn = transaction_factory
+                .payload(payload)
+                .sender(sender_address)
+                .sequence_number(sequence_number)
+                .build();
+            let mut txn = OfflineTransaction::new(raw_txn, vec![], Some(fee_payer_address));
+            txn.sign(fee_payer_address, &fee_payer_signer)?;
+            return self
+                .submit_fee_payer_transaction(&client, txn, sender_address, sender_public_key)
+                .await;
+        }
 
         match self.get_transaction_account_type() {
             Ok(AccountType::Local) => {
@@ -1760,6 +2006,35 @@ impl TransactionOptions {
         }
     }
 
+    /// Signs a transaction already signed by its fee payer as the sender, and submits it
+    async fn submit_fee_payer_transaction(
+        &self,
+        client: &Client,
+        mut txn: OfflineTransaction,
+        sender_address: AccountAddress,
+        sender_public_key: Ed25519PublicKey,
+    ) -> CliTypedResult<Transaction> {
+        let sender_signer = match self.get_transaction_account_type()? {
+            AccountType::Local => AccountSigner::Local(self.get_key_and_address()?.0),
+            AccountType::HardwareWallet => AccountSigner::HardwareWallet {
+                public_key: sender_public_key,
+                derivation_path: self.profile_options.derivation_path()?.ok_or_else(|| {
+                    CliError::CommandArgumentError(
+                        "Derivation path is missing from profile".to_string(),
+                    )
+                })?,
+            },
+        };
+        txn.sign(sender_address, &sender_signer)?;
+
+        let response = client
+            .submit_and_wait(&txn.into_signed_transaction()?)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+
+        Ok(response.into_inner())
+    }
+
     /// Simulates a transaction locally, using the debugger to fetch required data from remote.
     async fn simulate_using_debugger<F>(
         &self,
@@ -1782,6 +2057,10 @@ impl TransactionOptions {
         const DEFAULT_MAX_GAS: u64 = 2_000_000;
 
         let (sender_key, sender_address) = self.get_key_and_address()?;
+        let fee_payer_txn = self
+            .fee_payer_options
+            .transaction(sender_address, &payload)?;
+        let fee_payer = self.fee_payer_options.signer()?;
         let gas_unit_price = self
             .gas_options
             .gas_unit_price
@@ -1791,8 +2070,14 @@ impl TransactionOptions {
         let chain_id = ChainId::new(state.chain_id);
         let sequence_number = account.sequence_number;
 
+        // The gas is limited by the balance of the account paying for it
+        let gas_payer_address = fee_payer_txn
+            .as_ref()
+            .and_then(|txn| txn.fee_payer_address)
+            .or_else(|| fee_payer.as_ref().map(|(address, _)| *address))
+            .unwrap_or(sender_address);
         let balance = client
-            .get_account_balance_at_version(sender_address, version)
+            .get_account_balance_at_version(gas_payer_address, version)
             .await
             .map_err(|err| CliError::ApiError(err.to_string()))?
             .into_inner();
@@ -1809,9 +2094,25 @@ impl TransactionOptions {
             .with_gas_unit_price(gas_unit_price)
             .with_max_gas_amount(max_gas)
             .with_transaction_expiration_time(self.gas_options.expiration_secs);
-        let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
-        let transaction =
-            sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
+        let transaction = if let Some(mut txn) = fee_payer_txn {
+            txn.sign(sender_address, &AccountSigner::Local(sender_key))?;
+            txn.into_signed_transaction()?
+        } else if let Some((fee_payer_address, fee_payer_signer)) = fee_payer {
+            let raw_txn = transaction_factory
+                .payload(payload)
+                .sender(sender_address)
+                .sequence_number(sequence_number)
+                .build();
+            let mut txn = OfflineTransaction::new(raw_txn, vec![], Some(fee_payer_address));
+            txn.sign(fee_payer_address, &fee_payer_signer)?;
+            txn.sign(sender_address, &AccountSigner::Local(sender_key))?;
+            txn.into_signed_transaction()?
+        } else {
+            let sender_account =
+                &mut LocalAccount::new(sender_address, sender_key, sequence_number);
+            sender_account.sign_with_transaction_builder(transaction_factory.payload(payload))
+        };
+        let gas_unit_price = transaction.gas_unit_price();
         let hash = transaction.committed_hash();
 
         let debugger = AptosDebugger::rest_client(client).unwrap();
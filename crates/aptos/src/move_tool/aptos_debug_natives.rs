@@ -2,10 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_framework::extended_checks;
-use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters, LATEST_GAS_FEATURE_VERSION};
+use aptos_gas_meter::{AptosGasMeter, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_schedule::{
+    AptosGasParameters, MiscGasParameters, NativeGasParameters, LATEST_GAS_FEATURE_VERSION,
+};
 use aptos_types::on_chain_config::{Features, TimedFeaturesBuilder};
 use aptos_vm::natives;
-use move_vm_runtime::native_functions::NativeFunctionTable;
+use aptos_vm_types::storage::StorageGasParameters;
+use move_core_types::{identifier::IdentStr, language_storage::ModuleId};
+use move_unit_test::gas_meter::GasMeterHook;
+use move_vm_runtime::{
+    module_traversal::TraversalContext, native_functions::NativeFunctionTable, session::Session,
+};
 
 // move_stdlib has the testing feature enabled to include debug native functions
 pub fn aptos_debug_natives(
@@ -25,3 +33,36 @@ pub fn aptos_debug_natives(
         Features::default(),
     )
 }
+
+/// A hook which meters unit tests with the production gas meter and the given gas parameters, so
+/// that the gas used by a test is the execution and IO gas it would use on chain, in gas units.
+pub fn production_gas_meter_for_unit_test(gas_params: &AptosGasParameters) -> GasMeterHook {
+    let vm_gas_params = gas_params.vm.clone();
+    let storage_gas_params = StorageGasParameters::latest();
+    Box::new(
+        move |session: &mut Session,
+              module_id: &ModuleId,
+              function_name: &IdentStr,
+              args: &[Vec<u8>],
+              gas_limit: u64,
+              traversal_context: &mut TraversalContext| {
+            let mut gas_meter = StandardGasMeter::new(StandardGasAlgebra::new(
+                LATEST_GAS_FEATURE_VERSION,
+                vm_gas_params.clone(),
+                storage_gas_params.clone(),
+                false,
+                gas_limit,
+            ));
+            let result = session.execute_function_bypass_visibility(
+                module_id,
+                function_name,
+                vec![],
+                args.to_vec(),
+                &mut gas_meter,
+                traversal_context,
+            );
+            let gas_used = gas_meter.execution_gas_used() + gas_meter.io_gas_used();
+            (result, gas_used.into())
+        },
+    )
+}
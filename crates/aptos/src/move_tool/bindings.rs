@@ -0,0 +1,156 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::IncludedArtifactsArgs;
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::{get_metadata_from_compiled_module, BuildOptions, BuiltPackage};
+use aptos_sdk_builder::{
+    bindings::{ModuleSource, PackageBindings},
+    python, typescript,
+};
+use aptos_types::transaction::EntryABI;
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use move_binary_format::{access::ModuleAccess, file_format::FunctionDefinitionIndex};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum BindingsLanguage {
+    /// TypeScript, for the `@aptos-labs/ts-sdk` package
+    #[value(name = "ts")]
+    TypeScript,
+    /// Python, for the `aptos-sdk` package
+    Python,
+}
+
+/// Generate TypeScript or Python bindings for the modules of the package
+///
+/// For each module, this generates a function building the payload of each entry function, an
+/// async function calling each view function and decoding its return values, and a type with a
+/// BCS serializer and deserializer for each struct, e.g. for resources and events. Structs used
+/// from dependencies are generated as well.
+#[derive(Parser)]
+pub struct GenerateBindings {
+    /// The language to generate bindings in
+    #[clap(long, value_enum)]
+    lang: BindingsLanguage,
+
+    /// The directory to write the bindings to, which defaults to `bindings/<lang>` in the package
+    #[clap(long, value_parser)]
+    bindings_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    included_artifacts_args: IncludedArtifactsArgs,
+
+    #[clap(flatten)]
+    move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for GenerateBindings {
+    fn command_name(&self) -> &'static str {
+        "GenerateBindings"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            with_abis: true,
+            ..self
+                .included_artifacts_args
+                .included_artifacts
+                .build_options(
+                    self.move_options.dev,
+                    self.move_options.skip_fetch_latest_git_deps,
+                    self.move_options.named_addresses(),
+                    self.move_options.override_std.clone(),
+                    self.move_options.bytecode_version,
+                    self.move_options.compiler_version,
+                    self.move_options.language_version,
+                    self.move_options.skip_attribute_checks,
+                    self.move_options.check_test_code,
+                )
+        };
+        let package_path = self.move_options.get_package_path()?;
+        let package = BuiltPackage::build(package_path.clone(), build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let abis = package.extract_abis().unwrap_or_default();
+
+        let mut sources = vec![];
+        for unit in package.package.root_modules() {
+            let (module, source_map) = match &unit.unit {
+                CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) => (module, source_map),
+                CompiledUnit::Script(_) => continue,
+            };
+            let mut source = ModuleSource {
+                module,
+                view_functions: Default::default(),
+                events: Default::default(),
+                param_names: BTreeMap::new(),
+                docs: BTreeMap::new(),
+            };
+            if let Some(metadata) = get_metadata_from_compiled_module(module) {
+                for (name, attrs) in metadata.fun_attributes {
+                    if attrs.iter().any(|attr| attr.is_view_function()) {
+                        source.view_functions.insert(name);
+                    }
+                }
+                for (name, attrs) in metadata.struct_attributes {
+                    if attrs.iter().any(|attr| attr.is_event()) {
+                        source.events.insert(name);
+                    }
+                }
+            }
+            for (idx, def) in module.function_defs().iter().enumerate() {
+                let name = module
+                    .identifier_at(module.function_handle_at(def.function).name)
+                    .to_string();
+                if let Ok(function_map) =
+                    source_map.get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                {
+                    let names = function_map
+                        .parameters
+                        .iter()
+                        .map(|(name, _)| name.clone())
+                        .collect();
+                    source.param_names.insert(name, names);
+                }
+            }
+            for abi in &abis {
+                if let EntryABI::EntryFunction(abi) = abi {
+                    if abi.module_name() == &module.self_id() {
+                        source
+                            .docs
+                            .insert(abi.name().to_string(), abi.doc().to_string());
+                    }
+                }
+            }
+            sources.push(source);
+        }
+        let dependencies: Vec<_> = package.all_modules().collect();
+        let bindings = PackageBindings::new(&sources, &dependencies);
+
+        let bindings_dir = self.bindings_dir.unwrap_or_else(|| {
+            package_path.join("bindings").join(match self.lang {
+                BindingsLanguage::TypeScript => "ts",
+                BindingsLanguage::Python => "python",
+            })
+        });
+        let paths = match self.lang {
+            BindingsLanguage::TypeScript => {
+                typescript::Installer::new(bindings_dir.clone()).install_bindings(&bindings)
+            },
+            BindingsLanguage::Python => {
+                python::Installer::new(bindings_dir.clone()).install_bindings(&bindings)
+            },
+        }
+        .map_err(|err| CliError::IO(bindings_dir.display().to_string(), err))?;
+        Ok(paths
+            .into_iter()
+            .map(|path| path.display().to_string())
+            .collect())
+    }
+}
@@ -0,0 +1,874 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{stored_package::CachedPackageRegistry, IncludedArtifacts};
+use crate::common::{
+    types::{
+        load_account_arg, CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions,
+        RestOptions,
+    },
+    utils::get_feature_flag,
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuiltPackage};
+use aptos_types::{account_address::AccountAddress, on_chain_config::FeatureFlag};
+use async_trait::async_trait;
+use clap::Parser;
+use itertools::Itertools;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::Compatibility,
+    file_format::{
+        AbilitySet, Bytecode, FunctionDefinition, SignatureIndex, StructTypeParameter, Visibility,
+    },
+    normalized, CompiledModule,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Checks whether a package can be published as an upgrade of a package on chain
+///
+/// The package is compiled and compared with the package of the same name at the given
+/// address, applying the same compatibility rules as the chain. Every change which
+/// would make the upgrade fail is listed, e.g. removed public functions, changed
+/// struct layouts, removed abilities and removed friends. Changes which are allowed,
+/// but might break users of the package, are reported as warnings, e.g. changed code
+/// of entry functions and added abilities. The command fails if the upgrade would be
+/// rejected.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account or object the package is published at
+    #[clap(long, value_parser = load_account_arg)]
+    pub(crate) against: AccountAddress,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// A change made by an upgrade.
+#[derive(Debug, Serialize)]
+pub struct UpgradeChange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    pub message: String,
+}
+
+/// The changes made by an upgrade, split into the ones which make it fail, and the
+/// ones which might break users of the package.
+#[derive(Debug, Serialize)]
+pub struct UpgradeReport {
+    pub package: String,
+    pub address: AccountAddress,
+    pub compatible: bool,
+    pub errors: Vec<UpgradeChange>,
+    pub warnings: Vec<UpgradeChange>,
+}
+
+impl UpgradeReport {
+    fn error(&mut self, module: Option<&str>, message: String) {
+        self.errors.push(UpgradeChange {
+            module: module.map(str::to_string),
+            message,
+        });
+    }
+
+    fn warning(&mut self, module: Option<&str>, message: String) {
+        self.warnings.push(UpgradeChange {
+            module: module.map(str::to_string),
+            message,
+        });
+    }
+
+    /// Records a change which breaks compatibility, as an error if compatibility is
+    /// checked for the package, and as a warning otherwise.
+    fn incompatible(&mut self, checked: bool, module: &str, message: String) {
+        if checked {
+            self.error(Some(module), message)
+        } else {
+            self.warning(Some(module), message)
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<UpgradeReport> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeReport> {
+        let move_options = &self.move_options;
+        let options = IncludedArtifacts::Sparse.build_options(
+            move_options.dev,
+            move_options.skip_fetch_latest_git_deps,
+            move_options.named_addresses(),
+            move_options.override_std.clone(),
+            move_options.bytecode_version,
+            move_options.compiler_version,
+            move_options.language_version,
+            move_options.skip_attribute_checks,
+            move_options.check_test_code,
+        );
+        let package = BuiltPackage::build(move_options.get_package_path()?, options)
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let metadata = package.extract_metadata()?;
+
+        let url = self.rest_options.url(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(url, self.against, true).await?;
+        let old_package = registry
+            .get_package(package.name())
+            .await
+            .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+        let client = self.rest_options.client(&self.profile_options)?;
+        let check_friends =
+            !get_feature_flag(&client, FeatureFlag::TREAT_FRIEND_AS_PRIVATE).await?;
+
+        let mut report = UpgradeReport {
+            package: package.name().to_string(),
+            address: self.against,
+            compatible: false,
+            errors: vec![],
+            warnings: vec![],
+        };
+
+        // The checks of the upgrade of the package in `code.move`.
+        let old_policy = old_package.upgrade_policy();
+        check_policy(&mut report, old_policy, metadata.upgrade_policy);
+        let new_module_names: BTreeSet<_> =
+            metadata.modules.iter().map(|m| m.name.as_str()).collect();
+        for name in old_package.module_names() {
+            if !new_module_names.contains(name) {
+                report.error(Some(name), "Module was removed".to_string());
+            }
+        }
+        for other in registry.package_names() {
+            if other == package.name() {
+                continue;
+            }
+            let other_package = registry.get_package(other).await?;
+            for name in other_package.module_names() {
+                if new_module_names.contains(name) {
+                    report.error(
+                        Some(name),
+                        format!("Module already belongs to package `{}`", other),
+                    );
+                }
+            }
+        }
+
+        // The checks of the modules by the VM, which are skipped for `arbitrary` packages.
+        let checked = old_policy != UpgradePolicy::arbitrary();
+        for new_module in package.modules() {
+            let name = new_module.self_id().name().to_string();
+            if *new_module.self_id().address() != self.against {
+                report.error(
+                    Some(&name),
+                    format!(
+                        "Module is compiled for address {}, use --named-addresses to \
+                        compile it for {}",
+                        new_module.self_id().address(),
+                        self.against
+                    ),
+                );
+                continue;
+            }
+            let old_module = match registry.get_bytecode(&name).await? {
+                Some(bytes) => CompiledModule::deserialize(bytes).map_err(|err| {
+                    CliError::UnexpectedError(format!(
+                        "Failed to deserialize module {}: {}",
+                        name, err
+                    ))
+                })?,
+                None => continue,
+            };
+            compare_modules(
+                &mut report,
+                &name,
+                &old_module,
+                new_module,
+                checked,
+                check_friends,
+            );
+        }
+
+        report.compatible = report.errors.is_empty();
+        for change in &report.errors {
+            eprintln!("error: {}", describe(change));
+        }
+        for change in &report.warnings {
+            eprintln!("warning: {}", describe(change));
+        }
+        if !report.compatible {
+            return Err(CliError::UnexpectedError(format!(
+                "The package can't be upgraded, {} incompatible change(s) found",
+                report.errors.len()
+            )));
+        }
+        Ok(report)
+    }
+}
+
+fn describe(change: &UpgradeChange) -> String {
+    match &change.module {
+        Some(module) => format!("{}: {}", module, change.message),
+        None => change.message.clone(),
+    }
+}
+
+/// Checks that the upgrade policy of the package allows the upgrade, and isn't weakened by
+/// it.
+fn check_policy(report: &mut UpgradeReport, old_policy: UpgradePolicy, new_policy: UpgradePolicy) {
+    if old_policy == UpgradePolicy::immutable() {
+        report.error(None, "The package is immutable".to_string());
+    }
+    if new_policy.policy < old_policy.policy {
+        report.error(
+            None,
+            format!(
+                "The upgrade policy can't be weakened from `{}` to `{}`",
+                old_policy, new_policy
+            ),
+        );
+    }
+}
+
+fn compare_modules(
+    report: &mut UpgradeReport,
+    name: &str,
+    old_module: &CompiledModule,
+    new_module: &CompiledModule,
+    checked: bool,
+    check_friends: bool,
+) {
+    let old = normalized::Module::new(old_module);
+    let new = normalized::Module::new(new_module);
+    let errors = report.errors.len();
+
+    for (struct_name, old_struct) in &old.structs {
+        let new_struct = match new.structs.get(struct_name) {
+            Some(new_struct) => new_struct,
+            None => {
+                report.incompatible(
+                    checked,
+                    name,
+                    format!("Struct `{}` was removed", struct_name),
+                );
+                continue;
+            },
+        };
+        let removed = old_struct.abilities.setminus(new_struct.abilities);
+        if removed != AbilitySet::EMPTY {
+            report.incompatible(
+                checked,
+                name,
+                format!("Struct `{}` lost the abilities {}", struct_name, removed),
+            );
+        }
+        let added = new_struct.abilities.setminus(old_struct.abilities);
+        if added != AbilitySet::EMPTY {
+            report.warning(
+                Some(name),
+                format!(
+                    "Struct `{}` gained the abilities {}, check that values can't be \
+                    misused with them",
+                    struct_name, added
+                ),
+            );
+        }
+        let old_params = &old_struct.type_parameters;
+        let new_params = &new_struct.type_parameters;
+        if old_params.len() != new_params.len()
+            || old_params.iter().zip(new_params).any(|(old, new)| {
+                (old.is_phantom && !new.is_phantom) || !new.constraints.is_subset(old.constraints)
+            })
+        {
+            report.incompatible(
+                checked,
+                name,
+                format!(
+                    "Type parameters of struct `{}` changed from {} to {}",
+                    struct_name,
+                    struct_type_params(old_params),
+                    struct_type_params(new_params)
+                ),
+            );
+        }
+        if old_struct.fields != new_struct.fields {
+            report.incompatible(
+                checked,
+                name,
+                format!(
+                    "Layout of struct `{}` changed from {{ {} }} to {{ {} }}",
+                    struct_name,
+                    fields(&old_struct.fields),
+                    fields(&new_struct.fields)
+                ),
+            );
+        }
+    }
+
+    for (fun_name, old_fun) in &old.exposed_functions {
+        // Changes to friend functions only matter if friends are checked.
+        let is_friend = old_fun.visibility == Visibility::Friend;
+        let fun_checked = checked && (!is_friend || check_friends);
+        let new_fun = match new.exposed_functions.get(fun_name) {
+            Some(new_fun) => new_fun,
+            None => {
+                report.incompatible(
+                    fun_checked,
+                    name,
+                    format!("{} function `{}` was removed", kind(old_fun), fun_name),
+                );
+                continue;
+            },
+        };
+        let visibility_ok = match (old_fun.visibility, new_fun.visibility) {
+            (Visibility::Public, new) => new == Visibility::Public,
+            (Visibility::Friend, new) => new != Visibility::Private,
+            (Visibility::Private, _) => true,
+        };
+        if !visibility_ok {
+            report.incompatible(
+                fun_checked,
+                name,
+                format!(
+                    "Function `{}` changed from {} to {}",
+                    fun_name,
+                    kind(old_fun),
+                    kind(new_fun)
+                ),
+            );
+        } else if old_fun.visibility != new_fun.visibility {
+            report.warning(
+                Some(name),
+                format!(
+                    "Function `{}` changed from {} to {}, it can now be called by more modules",
+                    fun_name,
+                    kind(old_fun),
+                    kind(new_fun)
+                ),
+            );
+        }
+        if old_fun.is_entry && !new_fun.is_entry {
+            report.incompatible(
+                fun_checked,
+                name,
+                format!("Function `{}` is no longer an entry function", fun_name),
+            );
+        } else if !old_fun.is_entry && new_fun.is_entry {
+            report.warning(
+                Some(name),
+                format!(
+                    "Function `{}` became an entry function, it can now be called by any \
+                    transaction",
+                    fun_name
+                ),
+            );
+        }
+        if old_fun.parameters != new_fun.parameters || old_fun.return_ != new_fun.return_ {
+            report.incompatible(
+                fun_checked,
+                name,
+                format!(
+                    "Signature of function `{}` changed from ({}){} to ({}){}",
+                    fun_name,
+                    types(&old_fun.parameters),
+                    returns(&old_fun.return_),
+                    types(&new_fun.parameters),
+                    returns(&new_fun.return_)
+                ),
+            );
+        }
+        let old_params = &old_fun.type_parameters;
+        let new_params = &new_fun.type_parameters;
+        if old_params.len() != new_params.len()
+            || old_params
+                .iter()
+                .zip(new_params)
+                .any(|(old, new)| !new.is_subset(*old))
+        {
+            report.incompatible(
+                fun_checked,
+                name,
+                format!(
+                    "Type parameters of function `{}` changed from <{}> to <{}>",
+                    fun_name,
+                    old_params.iter().join(", "),
+                    new_params.iter().join(", ")
+                ),
+            );
+        }
+        if old_fun.is_entry
+            && new_fun.is_entry
+            && entry_code_changed(old_module, new_module, fun_name.as_str())
+        {
+            report.warning(
+                Some(name),
+                format!(
+                    "Code of entry function `{}` changed, check that transactions calling \
+                    it still behave as expected",
+                    fun_name
+                ),
+            );
+        }
+    }
+
+    let new_friends: BTreeSet<_> = new.friends.iter().collect();
+    let old_friends: BTreeSet<_> = old.friends.iter().collect();
+    for friend in old_friends.difference(&new_friends) {
+        report.incompatible(
+            checked && check_friends,
+            name,
+            format!("Friend `{}` was removed", friend),
+        );
+    }
+    for friend in new_friends.difference(&old_friends) {
+        report.warning(
+            Some(name),
+            format!(
+                "Friend `{}` was added, it can call the friend functions",
+                friend
+            ),
+        );
+    }
+
+    // The checker of the VM is the reference, in case it rejects a change not found above.
+    if checked && report.errors.len() == errors {
+        if let Err(err) = Compatibility::new(true, true, check_friends).check(&old, &new) {
+            report.error(Some(name), err.to_string());
+        }
+    }
+}
+
+fn kind(fun: &normalized::Function) -> &'static str {
+    match (fun.visibility, fun.is_entry) {
+        (Visibility::Public, true) => "public entry",
+        (Visibility::Public, false) => "public",
+        (Visibility::Friend, true) => "friend entry",
+        (Visibility::Friend, false) => "friend",
+        (Visibility::Private, true) => "entry",
+        (Visibility::Private, false) => "private",
+    }
+}
+
+fn struct_type_params(params: &[StructTypeParameter]) -> String {
+    let params = params
+        .iter()
+        .map(|param| {
+            let phantom = if param.is_phantom { "phantom " } else { "" };
+            if param.constraints == AbilitySet::EMPTY {
+                format!("{}T", phantom)
+            } else {
+                format!("{}T: {}", phantom, param.constraints)
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", params.join(", "))
+}
+
+fn fields(fields: &[normalized::Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn types(types: &[normalized::Type]) -> String {
+    types
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn returns(return_: &[normalized::Type]) -> String {
+    match return_ {
+        [] => String::new(),
+        [ty] => format!(": {}", ty),
+        _ => format!(": ({})", types(return_)),
+    }
+}
+
+/// Whether the code of the given function differs between the modules, comparing the
+/// instructions with the indices into the tables of the modules resolved.
+fn entry_code_changed(
+    old_module: &CompiledModule,
+    new_module: &CompiledModule,
+    name: &str,
+) -> bool {
+    match (
+        find_function(old_module, name),
+        find_function(new_module, name),
+    ) {
+        (Some(old), Some(new)) => resolved_code(old_module, old) != resolved_code(new_module, new),
+        _ => false,
+    }
+}
+
+fn find_function<'a>(module: &'a CompiledModule, name: &str) -> Option<&'a FunctionDefinition> {
+    module.function_defs().iter().find(|def| {
+        module
+            .identifier_at(module.function_handle_at(def.function).name)
+            .as_str()
+            == name
+    })
+}
+
+fn resolved_code(module: &CompiledModule, def: &FunctionDefinition) -> Vec<String> {
+    let code = match &def.code {
+        Some(code) => code,
+        None => return vec![],
+    };
+    let signature = |idx: SignatureIndex| {
+        module
+            .signature_at(idx)
+            .0
+            .iter()
+            .map(|token| normalized::Type::new(module, token).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let function = |idx| {
+        let handle = module.function_handle_at(idx);
+        let module_handle = module.module_handle_at(handle.module);
+        format!(
+            "{}::{}::{}",
+            module.address_identifier_at(module_handle.address),
+            module.identifier_at(module_handle.name),
+            module.identifier_at(handle.name)
+        )
+    };
+    let struct_def = |idx| {
+        let handle = module.struct_handle_at(module.struct_def_at(idx).struct_handle);
+        module.identifier_at(handle.name).to_string()
+    };
+    let struct_inst = |idx| {
+        let inst = module.struct_instantiation_at(idx);
+        format!(
+            "{}<{}>",
+            struct_def(inst.def),
+            signature(inst.type_parameters)
+        )
+    };
+    let field = |idx| {
+        let handle = module.field_handle_at(idx);
+        let def = module.struct_def_at(handle.owner);
+        let field_name = def
+            .field(handle.field as usize)
+            .map(|field| module.identifier_at(field.name).to_string())
+            .unwrap_or_default();
+        format!("{}.{}", struct_def(handle.owner), field_name)
+    };
+
+    let mut resolved = vec![format!("locals({})", signature(code.locals))];
+    for instruction in &code.code {
+        use Bytecode::*;
+        resolved.push(match instruction {
+            LdConst(idx) => {
+                let constant = module.constant_at(*idx);
+                format!(
+                    "LdConst({:?}, {})",
+                    constant.type_,
+                    hex::encode(&constant.data)
+                )
+            },
+            Call(idx) => format!("Call({})", function(*idx)),
+            CallGeneric(idx) => {
+                let inst = module.function_instantiation_at(*idx);
+                format!(
+                    "Call({}<{}>)",
+                    function(inst.handle),
+                    signature(inst.type_parameters)
+                )
+            },
+            Pack(idx) => format!("Pack({})", struct_def(*idx)),
+            PackGeneric(idx) => format!("Pack({})", struct_inst(*idx)),
+            Unpack(idx) => format!("Unpack({})", struct_def(*idx)),
+            UnpackGeneric(idx) => format!("Unpack({})", struct_inst(*idx)),
+            MutBorrowField(idx) => format!("MutBorrowField({})", field(*idx)),
+            ImmBorrowField(idx) => format!("ImmBorrowField({})", field(*idx)),
+            MutBorrowFieldGeneric(idx) => {
+                let inst = module.field_instantiation_at(*idx);
+                format!(
+                    "MutBorrowField({}<{}>)",
+                    field(inst.handle),
+                    signature(inst.type_parameters)
+                )
+            },
+            ImmBorrowFieldGeneric(idx) => {
+                let inst = module.field_instantiation_at(*idx);
+                format!(
+                    "ImmBorrowField({}<{}>)",
+                    field(inst.handle),
+                    signature(inst.type_parameters)
+                )
+            },
+            MutBorrowGlobal(idx) => format!("MutBorrowGlobal({})", struct_def(*idx)),
+            MutBorrowGlobalGeneric(idx) => format!("MutBorrowGlobal({})", struct_inst(*idx)),
+            ImmBorrowGlobal(idx) => format!("ImmBorrowGlobal({})", struct_def(*idx)),
+            ImmBorrowGlobalGeneric(idx) => format!("ImmBorrowGlobal({})", struct_inst(*idx)),
+            Exists(idx) => format!("Exists({})", struct_def(*idx)),
+            ExistsGeneric(idx) => format!("Exists({})", struct_inst(*idx)),
+            MoveFrom(idx) => format!("MoveFrom({})", struct_def(*idx)),
+            MoveFromGeneric(idx) => format!("MoveFrom({})", struct_inst(*idx)),
+            MoveTo(idx) => format!("MoveTo({})", struct_def(*idx)),
+            MoveToGeneric(idx) => format!("MoveTo({})", struct_inst(*idx)),
+            VecPack(idx, n) => format!("VecPack({}, {})", signature(*idx), n),
+            VecUnpack(idx, n) => format!("VecUnpack({}, {})", signature(*idx), n),
+            VecLen(idx) => format!("VecLen({})", signature(*idx)),
+            VecImmBorrow(idx) => format!("VecImmBorrow({})", signature(*idx)),
+            VecMutBorrow(idx) => format!("VecMutBorrow({})", signature(*idx)),
+            VecPushBack(idx) => format!("VecPushBack({})", signature(*idx)),
+            VecPopBack(idx) => format!("VecPopBack({})", signature(*idx)),
+            VecSwap(idx) => format!("VecSwap({})", signature(*idx)),
+            instruction => format!("{:?}", instruction),
+        });
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use move_compiler::{
+        compiled_unit::AnnotatedCompiledUnit,
+        shared::{known_attributes::KnownAttribute, Flags, NumericalAddress},
+        Compiler,
+    };
+    use std::collections::BTreeMap;
+
+    /// Compiles the source and returns the module `0x42::m` of it.
+    fn compile(source: &str) -> CompiledModule {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let path = dir.path().join("m.move");
+        std::fs::write(&path, source).unwrap();
+        let (_, units) = Compiler::from_files(
+            vec![path.to_string_lossy().to_string()],
+            vec![],
+            BTreeMap::<String, NumericalAddress>::new(),
+            Flags::empty(),
+            KnownAttribute::get_all_attribute_names(),
+        )
+        .build_and_report()
+        .unwrap();
+        units
+            .into_iter()
+            .find_map(|unit| match unit {
+                AnnotatedCompiledUnit::Module(module)
+                    if module.named_module.module.self_id().name().as_str() == "m" =>
+                {
+                    Some(module.named_module.module)
+                },
+                _ => None,
+            })
+            .expect("module m")
+    }
+
+    fn new_report() -> UpgradeReport {
+        UpgradeReport {
+            package: "package".to_string(),
+            address: AccountAddress::from_hex_literal("0x42").unwrap(),
+            compatible: false,
+            errors: vec![],
+            warnings: vec![],
+        }
+    }
+
+    fn compare(old: &str, new: &str, checked: bool, check_friends: bool) -> UpgradeReport {
+        let mut report = new_report();
+        compare_modules(
+            &mut report,
+            "m",
+            &compile(old),
+            &compile(new),
+            checked,
+            check_friends,
+        );
+        report
+    }
+
+    fn messages(changes: &[UpgradeChange]) -> Vec<&str> {
+        changes
+            .iter()
+            .map(|change| change.message.as_str())
+            .collect()
+    }
+
+    const FUNCTIONS: &str = "module 0x42::m {
+        public fun f(x: u64): u64 { x }
+        public fun g() {}
+    }";
+
+    #[test]
+    fn test_unchanged_module() {
+        let report = compare(FUNCTIONS, FUNCTIONS, true, true);
+        assert!(report.errors.is_empty());
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_removed_public_function() {
+        let new = "module 0x42::m {
+            public fun f(x: u64): u64 { x }
+        }";
+        let report = compare(FUNCTIONS, new, true, true);
+        assert_eq!(
+            messages(&report.errors),
+            vec!["public function `g` was removed"]
+        );
+        assert_eq!(report.errors[0].module.as_deref(), Some("m"));
+
+        // Without compatibility checks, the change is only a warning.
+        let report = compare(FUNCTIONS, new, false, true);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            messages(&report.warnings),
+            vec!["public function `g` was removed"]
+        );
+    }
+
+    #[test]
+    fn test_changed_function_signature() {
+        let new = "module 0x42::m {
+            public fun f(x: u64, y: u64): u64 { x + y }
+            public(friend) fun g() {}
+        }";
+        let report = compare(FUNCTIONS, new, true, true);
+        assert_eq!(
+            messages(&report.errors),
+            vec![
+                "Signature of function `f` changed from (u64): u64 to (u64, u64): u64",
+                "Function `g` changed from public to friend",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_struct_layout() {
+        let old = "module 0x42::m {
+            struct S { x: u64 }
+        }";
+        let new = "module 0x42::m {
+            struct S { x: u64, y: bool }
+        }";
+        let report = compare(old, new, true, true);
+        assert_eq!(
+            messages(&report.errors),
+            vec!["Layout of struct `S` changed from { x: u64 } to { x: u64, y: bool }"]
+        );
+        assert!(report.warnings.is_empty());
+
+        let new = "module 0x42::m {}";
+        let report = compare(old, new, true, true);
+        assert_eq!(messages(&report.errors), vec!["Struct `S` was removed"]);
+    }
+
+    #[test]
+    fn test_changed_struct_abilities() {
+        let old = "module 0x42::m {
+            struct S has copy, drop { x: u64 }
+        }";
+        let new = "module 0x42::m {
+            struct S has drop, store { x: u64 }
+        }";
+        let report = compare(old, new, true, true);
+        assert_eq!(
+            messages(&report.errors),
+            vec!["Struct `S` lost the abilities copy"]
+        );
+        assert_eq!(
+            messages(&report.warnings),
+            vec![
+                "Struct `S` gained the abilities store, check that values can't be misused \
+             with them"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_removed_friend() {
+        let old = "module 0x42::f {}
+        module 0x42::m {
+            friend 0x42::f;
+            public(friend) fun f() {}
+        }";
+        let new = "module 0x42::f {}
+        module 0x42::m {}";
+        let friend = format!(
+            "Friend `{}::f` was removed",
+            AccountAddress::from_hex_literal("0x42").unwrap().to_hex()
+        );
+        let report = compare(old, new, true, true);
+        assert_eq!(
+            messages(&report.errors),
+            vec!["friend function `f` was removed", friend.as_str(),]
+        );
+
+        // Friends don't matter once they are treated as private.
+        let report = compare(old, new, true, false);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            messages(&report.warnings),
+            vec!["friend function `f` was removed", friend.as_str(),]
+        );
+    }
+
+    #[test]
+    fn test_changed_entry_code() {
+        let old = "module 0x42::m {
+            public entry fun run(x: u64) { assert!(x > 0, 1) }
+        }";
+        let new = "module 0x42::m {
+            public entry fun run(x: u64) { assert!(x > 1, 1) }
+        }";
+        let report = compare(old, new, true, true);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            messages(&report.warnings),
+            vec![
+                "Code of entry function `run` changed, check that transactions calling it \
+             still behave as expected"
+            ]
+        );
+
+        assert!(entry_code_changed(&compile(old), &compile(new), "run"));
+        assert!(!entry_code_changed(&compile(old), &compile(old), "run"));
+        assert!(!entry_code_changed(&compile(old), &compile(new), "missing"));
+    }
+
+    #[test]
+    fn test_upgrade_policy() {
+        let mut report = new_report();
+        check_policy(
+            &mut report,
+            UpgradePolicy::compat(),
+            UpgradePolicy::immutable(),
+        );
+        assert!(report.errors.is_empty());
+
+        check_policy(
+            &mut report,
+            UpgradePolicy::compat(),
+            UpgradePolicy::arbitrary(),
+        );
+        assert_eq!(
+            messages(&report.errors),
+            vec!["The upgrade policy can't be weakened from `compatible` to `arbitrary`"]
+        );
+
+        let mut report = new_report();
+        check_policy(
+            &mut report,
+            UpgradePolicy::immutable(),
+            UpgradePolicy::immutable(),
+        );
+        assert_eq!(messages(&report.errors), vec!["The package is immutable"]);
+        assert_eq!(report.errors[0].module, None);
+    }
+}
@@ -1,17 +1,26 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir};
+use crate::common::{
+    types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir},
+    utils::write_to_file,
+};
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
     coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    line_coverage::LineCoverage, source_coverage::SourceCoverageBuilder,
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -142,6 +151,88 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoverageFormat {
+    /// An LCOV tracefile
+    Lcov,
+    /// A Cobertura XML report
+    Cobertura,
+}
+
+/// Export line coverage of the package's source files for other tools
+///
+/// Bytecode coverage is mapped back to source lines with the source maps of the modules, and
+/// written as an LCOV tracefile or a Cobertura XML report, e.g. for CI coverage dashboards.
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// The format of the report
+    #[clap(long, value_enum)]
+    pub format: CoverageFormat,
+    /// The file to write the report to, instead of standard output
+    #[clap(long, value_parser)]
+    pub output_file: Option<PathBuf>,
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<()> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let package_path = self.move_options.get_package_path()?;
+        // Source paths of compiled units are canonical, and Cobertura paths are relative to this
+        let source_root = package_path.canonicalize().unwrap_or(package_path);
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let coverage_map = coverage_map.to_unified_exec_map();
+
+        let mut line_coverage = LineCoverage::new();
+        for unit in package.root_modules() {
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &unit.unit
+            {
+                line_coverage
+                    .add_module(module, &coverage_map, source_map, &unit.source_path)
+                    .map_err(|err| {
+                        CliError::UnexpectedError(format!(
+                            "Failed to map coverage of {} to its source: {:#}",
+                            unit.unit.name(),
+                            err
+                        ))
+                    })?;
+            }
+        }
+
+        let mut report = Vec::new();
+        match self.format {
+            CoverageFormat::Lcov => line_coverage.output_lcov(&mut report),
+            CoverageFormat::Cobertura => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+                line_coverage.output_cobertura(
+                    package.compiled_package_info.package_name.as_str(),
+                    &source_root,
+                    timestamp,
+                    &mut report,
+                )
+            },
+        }
+        .map_err(|err| CliError::IO("coverage report".to_string(), err))?;
+
+        match self.output_file {
+            Some(output_file) => write_to_file(&output_file, "coverage report", &report),
+            None => std::io::stdout()
+                .write_all(&report)
+                .map_err(|err| CliError::IO("standard output".to_string(), err)),
+        }
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageDir,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -178,6 +269,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -186,6 +278,7 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized_success().await,
         }
     }
 }
@@ -0,0 +1,121 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir},
+    move_tool::aptos_debug_natives::aptos_debug_natives,
+};
+use aptos_framework::extended_checks;
+use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_types::on_chain_config::aptos_test_feature_flags_genesis;
+use async_trait::async_trait;
+use clap::Parser;
+use move_cli::base::test::{debug_move_unit_test, UnitTestResult};
+use move_package::{BuildConfig, CompilerConfig};
+use move_unit_test::UnitTestingConfig;
+use std::net::TcpListener;
+use tokio::task;
+
+/// Debugs a Move unit test
+///
+/// Runs the test under a Debug Adapter Protocol server, so that it can be stepped through
+/// from an editor such as VS Code: breakpoints can be set in the Move sources, execution
+/// stepped in, over and out of functions, and the call stack, locals and global resources
+/// inspected. Setting the exception breakpoint `abort` stops the test before it aborts.
+///
+/// The server speaks over stdin and stdout, unless a port is given. Output of
+/// `std::debug::print` is also written to stdout, so debug tests that print over TCP.
+#[derive(Parser)]
+pub struct DebugTest {
+    /// The test to debug, as its function name, optionally qualified by its module
+    /// (e.g. `coin_tests::test_transfer`) and address
+    pub test_name: String,
+
+    /// Listen for the editor on this local TCP port, instead of speaking over stdin and stdout
+    #[clap(long)]
+    pub port: Option<u16>,
+
+    /// A boolean value to skip warnings.
+    #[clap(long)]
+    pub ignore_compile_warnings: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+
+    /// The maximum number of instructions that can be executed by the test
+    #[clap(long = "instructions", default_value_t = 100000)]
+    pub instruction_execution_bound: u64,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for DebugTest {
+    fn command_name(&self) -> &'static str {
+        "DebugTest"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let config = BuildConfig {
+            dev_mode: self.move_options.dev,
+            additional_named_addresses: self.move_options.named_addresses(),
+            test_mode: true,
+            full_model_generation: self.move_options.check_test_code,
+            install_dir: self.move_options.output_dir.clone(),
+            skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            compiler_config: CompilerConfig {
+                known_attributes: extended_checks::get_all_attribute_names().clone(),
+                skip_attribute_checks: self.move_options.skip_attribute_checks,
+                compiler_version: self.move_options.compiler_version,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let path = self.move_options.get_package_path()?;
+        let unit_test_config = UnitTestingConfig {
+            report_stacktrace_on_abort: true,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            ..UnitTestingConfig::default_with_bound(Some(self.instruction_execution_bound))
+        };
+        let natives = aptos_debug_natives(NativeGasParameters::zeros(), MiscGasParameters::zeros());
+        let genesis = aptos_test_feature_flags_genesis();
+        let test_name = self.test_name;
+        let port = self.port;
+
+        let result = task::spawn_blocking(move || match port {
+            Some(port) => {
+                let listener = TcpListener::bind(("127.0.0.1", port))?;
+                eprintln!("Waiting for the debugger on 127.0.0.1:{}", port);
+                let (stream, _) = listener.accept()?;
+                debug_move_unit_test(
+                    path.as_path(),
+                    config,
+                    unit_test_config,
+                    natives,
+                    genesis,
+                    None,
+                    &test_name,
+                    stream.try_clone()?,
+                    stream,
+                )
+            },
+            None => debug_move_unit_test(
+                path.as_path(),
+                config,
+                unit_test_config,
+                natives,
+                genesis,
+                None,
+                &test_name,
+                std::io::stdin(),
+                std::io::stdout(),
+            ),
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to debug test: {:#}", err)))?;
+
+        match result {
+            UnitTestResult::Success => Ok("Success"),
+            UnitTestResult::Failure => Err(CliError::MoveTestError),
+        }
+    }
+}
@@ -0,0 +1,822 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{IncludedArtifactsArgs, MAX_PUBLISH_PACKAGE_SIZE};
+use crate::{
+    account::derive_resource_account::{ResourceAccountSeed, SeedEncoding},
+    common::{
+        types::{
+            load_account_arg, ArgWithTypeJSON, CliCommand, CliError, CliTypedResult,
+            EntryFunctionArguments, EntryFunctionArgumentsJSON, OverrideSizeCheckOption,
+            TransactionOptions, TransactionSummary,
+        },
+        utils::{create_dir_if_not_exist, read_from_file, write_to_file},
+    },
+};
+use aptos_crypto::HashValue;
+use aptos_framework::BuiltPackage;
+use aptos_types::{
+    account_address::{create_resource_address, AccountAddress},
+    object_address::create_object_code_deployment_address,
+    transaction::{EntryFunction, TransactionPayload},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Deploys a set of Move packages as described by a deployment manifest
+///
+/// The manifest is a YAML file, or a TOML file if it has the `.toml` extension, with a
+/// list of steps run in order. A step either publishes a package to the sender's
+/// account, an object or a resource account, runs an entry function, or asserts the
+/// result of a view function. Strings in steps can refer to the sender with
+/// `${sender}`, and to the address a package was published to with `${<step>.address}`.
+///
+/// What has been deployed is recorded in a state file, so running the manifest again
+/// only does what changed: packages are republished or upgraded if their code changed,
+/// and entry functions are run again if their arguments changed. Assertions are always
+/// checked.
+#[derive(Parser)]
+pub struct DeployPackages {
+    /// Path to the deployment manifest
+    #[clap(long, value_parser)]
+    pub(crate) manifest: PathBuf,
+
+    /// Path to the file recording what has been deployed
+    ///
+    /// Defaults to `<manifest>.<profile>.state.json` next to the manifest.
+    #[clap(long, value_parser)]
+    pub(crate) state_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) override_size_check_option: OverrideSizeCheckOption,
+    #[clap(flatten)]
+    pub(crate) included_artifacts_args: IncludedArtifactsArgs,
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+}
+
+/// A deployment manifest, the steps to deploy a set of packages.
+#[derive(Deserialize)]
+pub struct DeploymentManifest {
+    pub steps: Vec<DeploymentStep>,
+}
+
+#[derive(Deserialize)]
+pub struct DeploymentStep {
+    /// The name of the step, which must be unique in the manifest.
+    pub name: String,
+    #[serde(flatten)]
+    pub action: DeploymentAction,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentAction {
+    Publish(PublishStep),
+    Run(FunctionStep),
+    Assert(AssertStep),
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishTarget {
+    #[default]
+    Account,
+    Object,
+    ResourceAccount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublishStep {
+    /// Path to the package, relative to the manifest.
+    pub package_dir: PathBuf,
+    #[serde(default)]
+    pub to: PublishTarget,
+    /// The named address the address the package is published to is given as.
+    pub address_name: Option<String>,
+    /// The seed of the resource account, when publishing to a resource account.
+    pub seed: Option<String>,
+    pub seed_encoding: Option<String>,
+    /// Named addresses for compiling the package, as addresses or profile names.
+    #[serde(default)]
+    pub named_addresses: BTreeMap<String, String>,
+    #[serde(default)]
+    pub dev: bool,
+}
+
+#[derive(Deserialize)]
+pub struct FunctionStep {
+    pub function_id: String,
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<ArgWithTypeJSON>,
+}
+
+#[derive(Deserialize)]
+pub struct AssertStep {
+    #[serde(flatten)]
+    pub view: FunctionStep,
+    /// The values the view function must return, as returned by `aptos move view`.
+    pub expected: Vec<serde_json::Value>,
+}
+
+/// What has been deployed by a manifest.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DeploymentState {
+    pub sender: Option<AccountAddress>,
+    pub steps: BTreeMap<String, StepState>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StepState {
+    /// The address the package was published to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AccountAddress>,
+    /// Hash of the published code, or of the entry function run.
+    pub hash: HashValue,
+    pub transaction_hash: HashValue,
+}
+
+impl StepState {
+    /// Whether the step was last done with the code or entry function of the given hash,
+    /// so it doesn't need to be done again.
+    fn is_unchanged(previous: Option<&Self>, hash: &HashValue) -> bool {
+        previous.map_or(false, |previous| &previous.hash == hash)
+    }
+}
+
+/// Hashes the code published, or the entry function run, by a step.
+fn step_hash<T: Serialize>(value: &T) -> CliTypedResult<HashValue> {
+    Ok(HashValue::sha3_256_of(&bcs::to_bytes(value)?))
+}
+
+/// The outcome of a step of a deployment.
+#[derive(Debug, Serialize)]
+pub struct DeploymentStepResult {
+    pub name: String,
+    pub action: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionSummary>,
+}
+
+impl DeploymentManifest {
+    pub fn load(path: &Path) -> CliTypedResult<Self> {
+        let bytes = read_from_file(path)?;
+        let contents = String::from_utf8(bytes).map_err(|err| {
+            CliError::UnableToReadFile(path.display().to_string(), err.to_string())
+        })?;
+        let manifest: Self = if path.extension().map_or(false, |ext| ext == "toml") {
+            toml::from_str(&contents).map_err(|err| err.to_string())
+        } else {
+            serde_yaml::from_str(&contents).map_err(|err| err.to_string())
+        }
+        .map_err(|err| CliError::UnableToParse("deployment manifest", err))?;
+
+        let mut names = BTreeSet::new();
+        for step in &manifest.steps {
+            if !names.insert(step.name.as_str()) {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Step '{}' is defined more than once in the manifest",
+                    step.name
+                )));
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+impl DeploymentState {
+    fn load(path: &Path) -> CliTypedResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_json::from_slice(&read_from_file(path)?)
+            .map_err(|err| CliError::UnableToReadFile(path.display().to_string(), err.to_string()))
+    }
+
+    fn save(&self, path: &Path) -> CliTypedResult<()> {
+        if let Some(dir) = path.parent() {
+            create_dir_if_not_exist(dir)?;
+        }
+        let bytes = serde_json::to_vec_pretty(self)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        write_to_file(path, "Deployment state", &bytes)
+    }
+}
+
+/// The values `${...}` in the strings of a manifest are replaced with.
+struct Variables(BTreeMap<String, String>);
+
+impl Variables {
+    fn new(sender: AccountAddress) -> Self {
+        Self(BTreeMap::from([(
+            "sender".to_string(),
+            sender.to_hex_literal(),
+        )]))
+    }
+
+    /// Makes the address a step published to available to the steps after it.
+    fn add_step_address(&mut self, step: &str, address: AccountAddress) {
+        self.0
+            .insert(format!("{}.address", step), address.to_hex_literal());
+    }
+
+    fn substitute(&self, input: &str) -> CliTypedResult<String> {
+        let mut output = String::new();
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..].find('}').ok_or_else(|| {
+                CliError::CommandArgumentError(format!("Unterminated variable in '{}'", input))
+            })? + start;
+            let name = rest[start + 2..end].trim();
+            let value = self.0.get(name).ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Unknown variable '{}' in '{}', the address of a step can only be used \
+                    by the steps after it",
+                    name, input
+                ))
+            })?;
+            output.push_str(&rest[..start]);
+            output.push_str(value);
+            rest = &rest[end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn substitute_json(&self, value: serde_json::Value) -> CliTypedResult<serde_json::Value> {
+        use serde_json::Value;
+        Ok(match value {
+            Value::String(s) => Value::String(self.substitute(&s)?),
+            Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| self.substitute_json(value))
+                    .collect::<CliTypedResult<_>>()?,
+            ),
+            Value::Object(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, self.substitute_json(value)?)))
+                    .collect::<CliTypedResult<_>>()?,
+            ),
+            value => value,
+        })
+    }
+
+    fn named_addresses(
+        &self,
+        named_addresses: &BTreeMap<String, String>,
+    ) -> CliTypedResult<BTreeMap<String, AccountAddress>> {
+        named_addresses
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), load_account_arg(&self.substitute(value)?)?)))
+            .collect()
+    }
+
+    fn function_args(&self, step: FunctionStep) -> CliTypedResult<EntryFunctionArguments> {
+        EntryFunctionArgumentsJSON {
+            function_id: self.substitute(&step.function_id)?,
+            type_args: step
+                .type_args
+                .iter()
+                .map(|type_arg| self.substitute(type_arg))
+                .collect::<CliTypedResult<_>>()?,
+            args: step
+                .args
+                .into_iter()
+                .map(|arg| {
+                    Ok(ArgWithTypeJSON {
+                        arg_type: arg.arg_type,
+                        value: self.substitute_json(arg.value)?,
+                    })
+                })
+                .collect::<CliTypedResult<_>>()?,
+        }
+        .try_into()
+    }
+}
+
+impl DeployPackages {
+    fn state_file(&self) -> PathBuf {
+        if let Some(state_file) = &self.state_file {
+            return state_file.clone();
+        }
+        let profile = self
+            .txn_options
+            .profile_options
+            .profile_name()
+            .unwrap_or("default");
+        let stem = self
+            .manifest
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.manifest
+            .with_file_name(format!("{}.{}.state.json", stem, profile))
+    }
+
+    async fn publish(
+        &self,
+        step: PublishStep,
+        previous: Option<&StepState>,
+        sender: AccountAddress,
+        variables: &Variables,
+        manifest_dir: &Path,
+    ) -> CliTypedResult<(&'static str, Option<StepState>, Option<TransactionSummary>)> {
+        let seed = match step.to {
+            PublishTarget::ResourceAccount => {
+                let seed_encoding = match &step.seed_encoding {
+                    Some(seed_encoding) => SeedEncoding::from_str(seed_encoding)?,
+                    None => SeedEncoding::default(),
+                };
+                let seed = step.seed.clone().ok_or_else(|| {
+                    CliError::CommandArgumentError(
+                        "A seed is required to publish to a resource account".to_string(),
+                    )
+                })?;
+                Some(
+                    ResourceAccountSeed {
+                        seed: variables.substitute(&seed)?,
+                        seed_encoding,
+                    }
+                    .seed()?,
+                )
+            },
+            _ => None,
+        };
+        let address = match (step.to, previous.and_then(|previous| previous.address)) {
+            // An object's address depends on the sequence number it was created with.
+            (PublishTarget::Object, Some(address)) => address,
+            (PublishTarget::Object, None) => {
+                let sequence_number = self.txn_options.sequence_number(sender).await? + 1;
+                create_object_code_deployment_address(sender, sequence_number)
+            },
+            (PublishTarget::ResourceAccount, _) => {
+                create_resource_address(sender, seed.as_deref().unwrap_or_default())
+            },
+            (PublishTarget::Account, _) => sender,
+        };
+
+        let mut named_addresses = variables.named_addresses(&step.named_addresses)?;
+        match &step.address_name {
+            Some(address_name) => {
+                named_addresses.insert(address_name.clone(), address);
+            },
+            None if step.to == PublishTarget::Object => {
+                return Err(CliError::CommandArgumentError(
+                    "An address name is required to publish to an object".to_string(),
+                ))
+            },
+            None => {},
+        }
+
+        let options = self
+            .included_artifacts_args
+            .included_artifacts
+            .build_options(
+                step.dev,
+                false,
+                named_addresses,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+            );
+        let package = BuiltPackage::build(manifest_dir.join(&step.package_dir), options)
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let code = package.extract_code();
+        let hash = step_hash(&code)?;
+        if StepState::is_unchanged(previous, &hash) {
+            return Ok(("unchanged", None, None));
+        }
+        let metadata =
+            bcs::to_bytes(&package.extract_metadata()?).expect("PackageMetadata has BCS");
+
+        let (action, payload) = match (step.to, previous) {
+            (PublishTarget::Account, _) => (
+                if previous.is_some() {
+                    "upgraded"
+                } else {
+                    "published"
+                },
+                aptos_cached_packages::aptos_stdlib::code_publish_package_txn(metadata, code),
+            ),
+            (PublishTarget::Object, None) => (
+                "published",
+                aptos_cached_packages::aptos_stdlib::object_code_deployment_publish(
+                    metadata, code,
+                ),
+            ),
+            (PublishTarget::Object, Some(_)) => (
+                "upgraded",
+                aptos_cached_packages::aptos_stdlib::object_code_deployment_upgrade(
+                    metadata, code, address,
+                ),
+            ),
+            (PublishTarget::ResourceAccount, None) => (
+                "published",
+                aptos_cached_packages::aptos_stdlib::resource_account_create_resource_account_and_publish_package(
+                    seed.unwrap_or_default(),
+                    metadata,
+                    code,
+                ),
+            ),
+            (PublishTarget::ResourceAccount, Some(_)) => {
+                return Err(CliError::CommandArgumentError(format!(
+                    "The package at resource account {} changed, but packages can only be \
+                    upgraded by the resource account itself",
+                    address
+                )))
+            },
+        };
+        let size = bcs::serialized_size(&payload)?;
+        if !self.override_size_check_option.override_size_check && size > MAX_PUBLISH_PACKAGE_SIZE {
+            return Err(CliError::UnexpectedError(format!(
+                "The package is larger than {} bytes ({} bytes)! To lower the size \
+                you may want to include less artifacts via `--included-artifacts`. \
+                You can also override this check with `--override-size-check",
+                MAX_PUBLISH_PACKAGE_SIZE, size
+            )));
+        }
+
+        let summary = self.submit(payload).await?;
+        let state = StepState {
+            address: Some(address),
+            hash,
+            transaction_hash: summary.transaction_hash.into(),
+        };
+        Ok((action, Some(state), Some(summary)))
+    }
+
+    async fn submit(&self, payload: TransactionPayload) -> CliTypedResult<TransactionSummary> {
+        let summary = TransactionSummary::from(self.txn_options.submit_transaction(payload).await?);
+        if summary.success == Some(false) {
+            return Err(CliError::ApiError(format!(
+                "Transaction {} failed: {}",
+                summary.transaction_hash,
+                summary.vm_status.clone().unwrap_or_default()
+            )));
+        }
+        Ok(summary)
+    }
+}
+
+#[async_trait]
+impl CliCommand<Vec<DeploymentStepResult>> for DeployPackages {
+    fn command_name(&self) -> &'static str {
+        "DeployPackages"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<DeploymentStepResult>> {
+        let manifest = DeploymentManifest::load(&self.manifest)?;
+        let manifest_dir = self
+            .manifest
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let state_file = self.state_file();
+        let mut state = DeploymentState::load(&state_file)?;
+
+        let sender = self.txn_options.sender_address()?;
+        match state.sender {
+            Some(deployer) if deployer != sender => {
+                return Err(CliError::CommandArgumentError(format!(
+                    "{} was deployed by {}, not {}",
+                    state_file.display(),
+                    deployer,
+                    sender
+                )))
+            },
+            _ => state.sender = Some(sender),
+        }
+
+        let mut variables = Variables::new(sender);
+
+        let mut results = vec![];
+        for step in manifest.steps {
+            let previous = state.steps.get(&step.name).cloned();
+            let (action, step_state, transaction) = match step.action {
+                DeploymentAction::Publish(publish) => {
+                    self.publish(
+                        publish,
+                        previous.as_ref(),
+                        sender,
+                        &variables,
+                        &manifest_dir,
+                    )
+                    .await?
+                },
+                DeploymentAction::Run(run) => {
+                    let entry_function: EntryFunction = variables.function_args(run)?.try_into()?;
+                    let hash = step_hash(&entry_function)?;
+                    if StepState::is_unchanged(previous.as_ref(), &hash) {
+                        ("unchanged", None, None)
+                    } else {
+                        let summary = self
+                            .submit(TransactionPayload::EntryFunction(entry_function))
+                            .await?;
+                        let step_state = StepState {
+                            address: None,
+                            hash,
+                            transaction_hash: summary.transaction_hash.into(),
+                        };
+                        ("ran", Some(step_state), Some(summary))
+                    }
+                },
+                DeploymentAction::Assert(assertion) => {
+                    let result = self
+                        .txn_options
+                        .view(variables.function_args(assertion.view)?.try_into()?)
+                        .await?;
+                    if result != assertion.expected {
+                        return Err(CliError::UnexpectedError(format!(
+                            "Assertion '{}' failed: expected {}, got {}",
+                            step.name,
+                            serde_json::Value::Array(assertion.expected),
+                            serde_json::Value::Array(result),
+                        )));
+                    }
+                    ("asserted", None, None)
+                },
+            };
+
+            // Record each step as it is done, so a failed deployment resumes from there.
+            if let Some(step_state) = step_state {
+                state.steps.insert(step.name.clone(), step_state);
+                state.save(&state_file)?;
+            }
+            let address = state
+                .steps
+                .get(&step.name)
+                .and_then(|step_state| step_state.address);
+            if let Some(address) = address {
+                variables.add_step_address(&step.name, address);
+            }
+            results.push(DeploymentStepResult {
+                name: step.name,
+                action,
+                address,
+                transaction,
+            });
+        }
+        state.save(&state_file)?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    fn write_manifest(dir: &TempPath, file_name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(file_name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn entry_function(variables: &Variables, step: &str) -> EntryFunction {
+        let step: FunctionStep = serde_yaml::from_str(step).unwrap();
+        variables.function_args(step).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_load_manifest() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+
+        let yaml = write_manifest(
+            &dir,
+            "deploy.yaml",
+            r#"
+steps:
+  - name: counter
+    publish:
+      package_dir: counter
+      to: object
+      address_name: counter
+      named_addresses:
+        admin: ${sender}
+  - name: init
+    run:
+      function_id: ${counter.address}::counter::init
+      args:
+        - type: u64
+          value: "5"
+  - name: check
+    assert:
+      function_id: ${counter.address}::counter::get
+      expected: ["5"]
+"#,
+        );
+        let manifest = DeploymentManifest::load(&yaml).unwrap();
+        let names: Vec<_> = manifest
+            .steps
+            .iter()
+            .map(|step| step.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["counter", "init", "check"]);
+        match &manifest.steps[0].action {
+            DeploymentAction::Publish(publish) => {
+                assert_eq!(publish.package_dir, PathBuf::from("counter"));
+                assert_eq!(publish.to, PublishTarget::Object);
+                assert_eq!(publish.address_name.as_deref(), Some("counter"));
+                assert_eq!(publish.named_addresses["admin"], "${sender}");
+                assert!(!publish.dev);
+            },
+            _ => panic!("expected a publish step"),
+        }
+        match &manifest.steps[1].action {
+            DeploymentAction::Run(run) => {
+                assert_eq!(run.function_id, "${counter.address}::counter::init");
+                assert!(run.type_args.is_empty());
+                assert_eq!(run.args.len(), 1);
+            },
+            _ => panic!("expected a run step"),
+        }
+        match &manifest.steps[2].action {
+            DeploymentAction::Assert(assertion) => {
+                assert_eq!(
+                    assertion.view.function_id,
+                    "${counter.address}::counter::get"
+                );
+                assert_eq!(assertion.expected, vec![serde_json::json!("5")]);
+            },
+            _ => panic!("expected an assert step"),
+        }
+
+        // TOML manifests are parsed the same way, with the target defaulting to the
+        // sender's account.
+        let toml = write_manifest(
+            &dir,
+            "deploy.toml",
+            r#"
+[[steps]]
+name = "counter"
+[steps.publish]
+package_dir = "counter"
+"#,
+        );
+        let manifest = DeploymentManifest::load(&toml).unwrap();
+        match &manifest.steps[0].action {
+            DeploymentAction::Publish(publish) => {
+                assert_eq!(publish.to, PublishTarget::Account);
+                assert_eq!(publish.address_name, None);
+            },
+            _ => panic!("expected a publish step"),
+        }
+    }
+
+    #[test]
+    fn test_load_manifest_with_duplicate_steps() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        let path = write_manifest(
+            &dir,
+            "deploy.yaml",
+            r#"
+steps:
+  - name: counter
+    publish:
+      package_dir: counter
+  - name: counter
+    run:
+      function_id: 0x1::counter::init
+"#,
+        );
+        assert!(matches!(
+            DeploymentManifest::load(&path),
+            Err(CliError::CommandArgumentError(_))
+        ));
+
+        let path = write_manifest(&dir, "invalid.yaml", "steps:\n  - name: counter\n");
+        assert!(matches!(
+            DeploymentManifest::load(&path),
+            Err(CliError::UnableToParse(..))
+        ));
+    }
+
+    #[test]
+    fn test_substitute_step_addresses() {
+        let sender = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let counter = AccountAddress::from_hex_literal("0xc0ffee").unwrap();
+        let mut variables = Variables::new(sender);
+
+        // The address of a step is only known to the steps after it.
+        assert!(variables.substitute("${counter.address}::counter").is_err());
+        variables.add_step_address("counter", counter);
+
+        assert_eq!(
+            variables
+                .substitute("${counter.address}::counter::init(${ sender })")
+                .unwrap(),
+            format!(
+                "{}::counter::init({})",
+                counter.to_hex_literal(),
+                sender.to_hex_literal()
+            )
+        );
+        assert_eq!(
+            variables.substitute("no variables").unwrap(),
+            "no variables"
+        );
+        assert!(variables.substitute("${sender").is_err());
+
+        let named_addresses = variables
+            .named_addresses(&BTreeMap::from([
+                ("admin".to_string(), "${sender}".to_string()),
+                ("counter".to_string(), "${counter.address}".to_string()),
+                ("std".to_string(), "0x1".to_string()),
+            ]))
+            .unwrap();
+        assert_eq!(
+            named_addresses,
+            BTreeMap::from([
+                ("admin".to_string(), sender),
+                ("counter".to_string(), counter),
+                ("std".to_string(), AccountAddress::ONE),
+            ])
+        );
+
+        assert_eq!(
+            variables
+                .substitute_json(serde_json::json!({
+                    "owners": ["${sender}", "${counter.address}"],
+                    "amount": 5,
+                }))
+                .unwrap(),
+            serde_json::json!({
+                "owners": [sender.to_hex_literal(), counter.to_hex_literal()],
+                "amount": 5,
+            })
+        );
+
+        let function = entry_function(
+            &variables,
+            r#"
+function_id: ${counter.address}::counter::transfer
+args:
+  - type: address
+    value: ${sender}
+"#,
+        );
+        assert_eq!(*function.module().address(), counter);
+        assert_eq!(function.args(), &[bcs::to_bytes(&sender).unwrap()]);
+    }
+
+    #[test]
+    fn test_unchanged_steps_are_skipped() {
+        let sender = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let variables = Variables::new(sender);
+        let step = r#"
+function_id: 0x1::counter::set
+args:
+  - type: u64
+    value: "5"
+"#;
+        let hash = step_hash(&entry_function(&variables, step)).unwrap();
+        assert!(!StepState::is_unchanged(None, &hash));
+
+        // The state file records the hash of what each step did.
+        let state_file = TempPath::new();
+        let mut state = DeploymentState {
+            sender: Some(sender),
+            ..Default::default()
+        };
+        state.steps.insert(
+            "set".to_string(),
+            StepState {
+                address: None,
+                hash,
+                transaction_hash: HashValue::random(),
+            },
+        );
+        state.save(state_file.path()).unwrap();
+        let state = DeploymentState::load(state_file.path()).unwrap();
+        assert_eq!(state.sender, Some(sender));
+        let previous = state.steps.get("set");
+
+        // Running the same step again is skipped, but not with other arguments.
+        let hash = step_hash(&entry_function(&variables, step)).unwrap();
+        assert!(StepState::is_unchanged(previous, &hash));
+        let changed =
+            step_hash(&entry_function(&variables, &step.replace("\"5\"", "\"6\""))).unwrap();
+        assert!(!StepState::is_unchanged(previous, &changed));
+
+        // A state file which doesn't exist yet has no steps.
+        let state = DeploymentState::load(TempPath::new().path()).unwrap();
+        assert!(state.sender.is_none() && state.steps.is_empty());
+    }
+}
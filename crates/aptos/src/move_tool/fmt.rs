@@ -0,0 +1,74 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use async_trait::async_trait;
+use clap::Parser;
+use move_fmt::{format_source, package_files, FormatConfig};
+use std::{fs, path::PathBuf};
+
+/// Formats the Move sources of a package
+///
+/// Line breaks and comments are kept, while indentation, spacing around operators and
+/// punctuation, and blank lines are made canonical. Attributes are put on their own lines,
+/// and adjacent use declarations are sorted.
+///
+/// The indentation size, the maximum number of consecutive blank lines and whether use
+/// declarations are sorted can be set by `indent_size`, `max_blank_lines` and `sort_uses`
+/// in a `movefmt.toml` file at the root of the package.
+#[derive(Parser)]
+pub struct FormatPackage {
+    /// Check whether the sources are formatted, without changing them
+    ///
+    /// Fails, listing the files which aren't formatted, if any.
+    #[clap(long)]
+    pub check: bool,
+
+    /// Files to format, instead of all sources of the package
+    #[clap(long, value_parser, num_args = 1..)]
+    pub files: Vec<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for FormatPackage {
+    fn command_name(&self) -> &'static str {
+        "FormatPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let path = self.move_options.get_package_path()?;
+        let config = FormatConfig::load(&path)
+            .map_err(|err| CliError::UnableToParse("movefmt.toml", err.to_string()))?;
+        let files = if self.files.is_empty() {
+            package_files(&path).map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        } else {
+            self.files
+        };
+
+        let mut changed = vec![];
+        for file in files {
+            let name = file.display().to_string();
+            let source =
+                fs::read_to_string(&file).map_err(|err| CliError::IO(name.clone(), err))?;
+            let formatted = format_source(&name, &source, &config)
+                .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+            if formatted != source {
+                if !self.check {
+                    fs::write(&file, formatted).map_err(|err| CliError::IO(name.clone(), err))?;
+                }
+                changed.push(name);
+            }
+        }
+
+        if self.check && !changed.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "Files are not formatted, run `aptos move fmt` to format them: {}",
+                changed.join(", ")
+            )));
+        }
+        Ok(changed)
+    }
+}
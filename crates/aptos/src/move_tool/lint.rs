@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::{built_package, extended_checks};
+use async_trait::async_trait;
+use clap::Parser;
+use codespan_reporting::{
+    diagnostic::Severity,
+    term::termcolor::{ColorChoice, StandardStream},
+};
+use move_compiler_v2::linter::{
+    report_lint_diagnostics, LintConfig, LintDiagnostic, LintLevel, LintRule,
+};
+use move_model::model::GlobalEnv;
+use serde::Serialize;
+use tokio::task;
+
+/// Lints a Move package
+///
+/// Checks the package for common mistakes and unidiomatic code. Every rule is reported
+/// as a warning unless configured otherwise, either with the flags of this command, or
+/// with `#[lint::allow(<rule>)]` and `#[lint::deny(<rule>)]` attributes on modules and
+/// functions. The command fails if a denied rule is violated.
+///
+/// The rules are: needless_borrow, unnecessary_copy, bool_comparison, unused_acquires,
+/// public_entry_returns_value and self_assignment.
+#[derive(Parser)]
+pub struct LintPackage {
+    /// Rules to not check
+    #[clap(long, value_parser = parse_lint_rule)]
+    pub(crate) allow: Vec<LintRule>,
+
+    /// Rules to report as warnings
+    #[clap(long, value_parser = parse_lint_rule)]
+    pub(crate) warn: Vec<LintRule>,
+
+    /// Rules to report as errors
+    #[clap(long, value_parser = parse_lint_rule)]
+    pub(crate) deny: Vec<LintRule>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+fn parse_lint_rule(rule: &str) -> Result<LintRule, String> {
+    rule.parse()
+}
+
+/// A violation of a lint rule, as returned by `aptos move lint`.
+#[derive(Debug, Serialize)]
+pub struct LintFinding {
+    pub rule: String,
+    pub severity: String,
+    pub message: String,
+    pub file: Option<String>,
+    /// The 1-based line of the violation.
+    pub line: Option<u32>,
+    /// The 1-based column of the violation.
+    pub column: Option<u32>,
+}
+
+impl LintFinding {
+    fn new(env: &GlobalEnv, diag: &LintDiagnostic) -> Self {
+        let (file, line, column) = match env.get_file_and_location(&diag.loc) {
+            Some((file, location)) => (
+                Some(file),
+                Some(location.line.0 + 1),
+                Some(location.column.0 + 1),
+            ),
+            None => (None, None, None),
+        };
+        let severity = if diag.severity >= Severity::Error {
+            "error"
+        } else {
+            "warning"
+        };
+        Self {
+            rule: diag.rule.to_string(),
+            severity: severity.to_string(),
+            message: diag.message.clone(),
+            file,
+            line,
+            column,
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<Vec<LintFinding>> for LintPackage {
+    fn command_name(&self) -> &'static str {
+        "LintPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<LintFinding>> {
+        let mut config = LintConfig::default();
+        for (rules, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for rule in rules {
+                config.set_level(*rule, level);
+            }
+        }
+        let move_options = self.move_options;
+
+        task::spawn_blocking(move || {
+            let (env, diags) = built_package::lint_package(
+                move_options.dev,
+                move_options.get_package_path()?.as_path(),
+                move_options.named_addresses(),
+                move_options.language_version,
+                move_options.skip_attribute_checks,
+                extended_checks::get_all_attribute_names().clone(),
+                &config,
+            )
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+
+            let mut writer = StandardStream::stderr(ColorChoice::Auto);
+            if env.has_errors() {
+                env.report_diag(&mut writer, Severity::Warning);
+                return Err(CliError::MoveCompilationError(
+                    "Exiting with compilation errors".to_string(),
+                ));
+            }
+            report_lint_diagnostics(&env, &diags);
+            env.report_diag(&mut writer, Severity::Warning);
+
+            let findings: Vec<_> = diags
+                .iter()
+                .map(|diag| LintFinding::new(&env, diag))
+                .collect();
+            let denied = findings
+                .iter()
+                .filter(|finding| finding.severity == "error")
+                .count();
+            if denied > 0 {
+                return Err(CliError::MoveCompilationError(format!(
+                    "{} denied lint violation(s) found",
+                    denied
+                )));
+            }
+            Ok(findings)
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+    }
+}
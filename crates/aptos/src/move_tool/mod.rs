@@ -30,7 +30,9 @@ use aptos_framework::{
     docgen::DocgenOptions, extended_checks, natives::code::UpgradePolicy, prover::ProverOptions,
     BuildOptions, BuiltPackage,
 };
-use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_gas_schedule::{
+    AptosGasParameters, InitialGasSchedule, MiscGasParameters, NativeGasParameters,
+};
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
 use aptos_rest_client::{
     aptos_api_types::{EntryFunctionId, HexEncodedBytes, IdentifierWrapper, MoveModuleId},
@@ -69,12 +71,20 @@ use tokio::task;
 use url::Url;
 
 mod aptos_debug_natives;
+mod bindings;
 mod bytecode;
+mod check_upgrade;
 pub mod coverage;
+mod debug_test;
+mod deploy;
+mod fmt;
+mod lint;
 mod manifest;
+mod mutate;
 pub mod package_hooks;
 mod show;
 pub mod stored_package;
+mod update;
 
 /// Tool for Move related operations
 ///
@@ -84,6 +94,7 @@ pub mod stored_package;
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(check_upgrade::CheckUpgrade),
     Clean(CleanPackage),
     Compile(CompilePackage),
     CompileScript(CompileScript),
@@ -93,11 +104,17 @@ pub enum MoveTool {
     UpgradeObjectPackage(UpgradeObjectPackage),
     CreateResourceAccountAndPublishPackage(CreateResourceAccountAndPublishPackage),
     Disassemble(Disassemble),
+    DebugTest(debug_test::DebugTest),
     Decompile(Decompile),
+    Deploy(deploy::DeployPackages),
     Document(DocumentPackage),
     Download(DownloadPackage),
+    Fmt(fmt::FormatPackage),
+    GenerateBindings(bindings::GenerateBindings),
     Init(InitPackage),
+    Lint(lint::LintPackage),
     List(ListPackage),
+    Mutate(mutate::MutatePackage),
     Prove(ProvePackage),
     Publish(PublishPackage),
     Run(RunFunction),
@@ -105,6 +122,7 @@ pub enum MoveTool {
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
     Test(TestPackage),
+    Update(update::UpdateDependencies),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
@@ -114,6 +132,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
@@ -126,17 +145,24 @@ impl MoveTool {
                 tool.execute_serialized_success().await
             },
             MoveTool::Disassemble(tool) => tool.execute_serialized().await,
+            MoveTool::DebugTest(tool) => tool.execute_serialized().await,
             MoveTool::Decompile(tool) => tool.execute_serialized().await,
+            MoveTool::Deploy(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
+            MoveTool::Fmt(tool) => tool.execute_serialized().await,
+            MoveTool::GenerateBindings(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
+            MoveTool::Lint(tool) => tool.execute_serialized().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
+            MoveTool::Mutate(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::RunScript(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::Update(tool) => tool.execute_serialized().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
@@ -467,6 +493,61 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Seed for the generated arguments of `#[fuzz]` tests
+    ///
+    /// A random seed is used if not given. Failing `#[fuzz]` tests report the seed they were
+    /// run with, so that the failure can be reproduced.
+    #[clap(long)]
+    pub fuzz_seed: Option<u64>,
+
+    /// Write a machine-readable report of the test results, as `<format>=<path>`
+    ///
+    /// The only format is `junit`, a JUnit XML report with the duration, gas used and failure
+    /// message of every test, e.g. `--report junit=test-results.xml`.
+    #[clap(long)]
+    pub report: Option<TestReport>,
+
+    /// Compare the gas used by each test with the gas snapshot `.gas-snapshot` in the package
+    ///
+    /// Tests are run with the production gas schedule, so the gas used is as on chain. The
+    /// snapshot is written if it doesn't exist yet. Otherwise, the changes are printed, and the
+    /// tests fail if the gas used by a test changed by more than `--gas-tolerance`.
+    #[clap(long)]
+    pub gas_snapshot: bool,
+
+    /// Write the gas used by each test to the gas snapshot, instead of comparing with it
+    #[clap(long, requires = "gas_snapshot")]
+    pub update_gas_snapshot: bool,
+
+    /// The change of the gas used by a test, in percent, up to which it still matches the gas
+    /// snapshot
+    #[clap(long, default_value_t = 0.0, requires = "gas_snapshot")]
+    pub gas_tolerance: f64,
+}
+
+/// The file in the package which holds the gas snapshot of `aptos move test --gas-snapshot`
+const GAS_SNAPSHOT_FILE: &str = ".gas-snapshot";
+
+/// A machine-readable report of the results of `aptos move test`
+#[derive(Clone, Debug)]
+pub enum TestReport {
+    Junit(PathBuf),
+}
+
+impl FromStr for TestReport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("junit", path)) if !path.is_empty() => Ok(TestReport::Junit(PathBuf::from(path))),
+            Some((format, _)) if format != "junit" => Err(format!(
+                "Unknown report format '{}', the only format is 'junit'",
+                format
+            )),
+            _ => Err("Expected a report as <format>=<path>, e.g. junit=results.xml".to_string()),
+        }
+    }
 }
 
 #[async_trait]
@@ -494,6 +575,29 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
+        let (natives, gas_limit, gas_meter_hook) = if self.gas_snapshot {
+            let gas_params = AptosGasParameters::initial();
+            (
+                aptos_debug_natives::aptos_debug_natives(
+                    gas_params.natives.clone(),
+                    gas_params.vm.misc.clone(),
+                ),
+                Some(u64::from(gas_params.vm.txn.maximum_number_of_gas_units)),
+                Some(aptos_debug_natives::production_gas_meter_for_unit_test(
+                    &gas_params,
+                )),
+            )
+        } else {
+            // TODO(Gas): we may want to switch to non-zero costs in the future
+            (
+                aptos_debug_natives::aptos_debug_natives(
+                    NativeGasParameters::zeros(),
+                    MiscGasParameters::zeros(),
+                ),
+                None,
+                None,
+            )
+        };
         let result = move_cli::base::test::run_move_unit_tests(
             path.as_path(),
             config.clone(),
@@ -502,15 +606,19 @@ impl CliCommand<&'static str> for TestPackage {
                 report_stacktrace_on_abort: true,
                 report_storage_on_error: self.dump_state,
                 ignore_compile_warnings: self.ignore_compile_warnings,
-                ..UnitTestingConfig::default_with_bound(None)
+                fuzz_seed: self.fuzz_seed,
+                junit_report: self.report.as_ref().map(|report| match report {
+                    TestReport::Junit(path) => path.clone(),
+                }),
+                gas_snapshot: self.gas_snapshot.then(|| path.join(GAS_SNAPSHOT_FILE)),
+                update_gas_snapshot: self.update_gas_snapshot,
+                gas_snapshot_tolerance: self.gas_tolerance,
+                ..UnitTestingConfig::default_with_bound(gas_limit)
             },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
-            ),
+            natives,
             aptos_test_feature_flags_genesis(),
             None,
+            gas_meter_hook,
             self.compute_coverage,
             &mut std::io::stdout(),
         )
@@ -2134,3 +2242,30 @@ impl FromStr for MemberId {
         parse_member_id(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_test_report() {
+        assert!(matches!(
+            TestReport::from_str("junit=build/results.xml"),
+            Ok(TestReport::Junit(path)) if path == Path::new("build/results.xml")
+        ));
+        assert!(matches!(
+            TestReport::from_str("junit=a=b.xml"),
+            Ok(TestReport::Junit(path)) if path == Path::new("a=b.xml")
+        ));
+        assert_eq!(
+            TestReport::from_str("tap=results.tap").unwrap_err(),
+            "Unknown report format 'tap', the only format is 'junit'"
+        );
+        for report in ["junit", "junit=", "results.xml"] {
+            assert_eq!(
+                TestReport::from_str(report).unwrap_err(),
+                "Expected a report as <format>=<path>, e.g. junit=results.xml"
+            );
+        }
+    }
+}
@@ -0,0 +1,585 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use async_trait::async_trait;
+use clap::Parser;
+use move_command_line_common::files::{find_move_filenames, FileHash};
+use move_compiler::parser::lexer::{Lexer, Tok};
+use move_package::source_package::layout::SourcePackageLayout;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+use tokio::task;
+
+/// Attributes of items which only exist for testing, and aren't mutated
+const TEST_ATTRIBUTES: &[&str] = &["test", "test_only", "expected_failure", "verify_only"];
+
+/// Runs the unit tests of a package against mutants of its sources
+///
+/// A mutant is a copy of the package with a single change to a source: a binary operator
+/// swapped (e.g. `+` for `-` or `&&` for `||`), a comparison flipped (e.g. `<` for `>=`),
+/// an `assert!` removed, or a number or boolean constant changed. A mutant is killed if a
+/// test fails on it. A mutant which survives is a change to the code the tests don't notice,
+/// and is reported with its location. Tests, test-only code and specifications aren't mutated.
+///
+/// Mutants are tested in parallel, each in a copy of the package next to it. Mutants which
+/// don't compile don't count towards the score.
+#[derive(Parser)]
+pub struct MutatePackage {
+    /// Only run the tests whose names contain this
+    #[clap(long, short)]
+    pub filter: Option<String>,
+
+    /// Only mutate these source files, instead of all sources of the package
+    #[clap(long, value_parser, num_args = 1..)]
+    pub files: Vec<PathBuf>,
+
+    /// The number of mutants tested at once
+    ///
+    /// Defaults to the number of CPUs.
+    #[clap(long)]
+    pub jobs: Option<usize>,
+
+    /// The maximum number of instructions that can be executed by a test
+    #[clap(long = "instructions", default_value_t = 100000)]
+    pub instruction_execution_bound: u64,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+/// A mutant the tests didn't kill
+#[derive(Debug, Serialize)]
+pub struct SurvivingMutant {
+    pub file: String,
+    /// The 1-based line of the mutation
+    pub line: usize,
+    /// The 1-based column of the mutation
+    pub column: usize,
+    pub mutation: String,
+}
+
+/// The result of `aptos move mutate`
+#[derive(Debug, Serialize)]
+pub struct MutationSummary {
+    pub mutants: usize,
+    pub killed: usize,
+    pub not_compiled: usize,
+    /// The percentage of the mutants which compile that were killed
+    pub score: f64,
+    pub survived: Vec<SurvivingMutant>,
+}
+
+/// A change to a source
+#[derive(Debug, PartialEq, Eq)]
+struct Mutation {
+    /// The byte offsets of the replaced source
+    start: usize,
+    end: usize,
+    replacement: String,
+    description: String,
+}
+
+/// A source file of the package, with its mutations
+struct MutatedFile {
+    /// The path relative to the package
+    path: PathBuf,
+    source: String,
+    mutations: Vec<Mutation>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Killed,
+    Survived,
+    NotCompiled,
+}
+
+#[async_trait]
+impl CliCommand<MutationSummary> for MutatePackage {
+    fn command_name(&self) -> &'static str {
+        "MutatePackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<MutationSummary> {
+        task::spawn_blocking(move || self.run())
+            .await
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+    }
+}
+
+impl MutatePackage {
+    fn run(self) -> CliTypedResult<MutationSummary> {
+        let package = canonicalize(&self.move_options.get_package_path()?)?;
+        let paths = if self.files.is_empty() {
+            find_move_filenames(&[package.join(SourcePackageLayout::Sources.path())], false)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+                .into_iter()
+                .map(PathBuf::from)
+                .collect()
+        } else {
+            self.files.clone()
+        };
+        let mut files = vec![];
+        for path in paths {
+            let path = canonicalize(&path)?;
+            let relative = match path.strip_prefix(&package) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "{} is not in the package",
+                        path.display()
+                    )))
+                },
+            };
+            let source = fs::read_to_string(&path)
+                .map_err(|err| CliError::IO(path.display().to_string(), err))?;
+            let mutations = mutations(&source);
+            files.push(MutatedFile {
+                path: relative,
+                source,
+                mutations,
+            });
+        }
+        let mutants: Vec<(&MutatedFile, &Mutation)> = files
+            .iter()
+            .flat_map(|file| file.mutations.iter().map(move |mutation| (file, mutation)))
+            .collect();
+
+        if self.run_tests(&package, self.move_options.skip_fetch_latest_git_deps)?
+            != Outcome::Survived
+        {
+            return Err(CliError::UnexpectedError(
+                "The tests of the package fail without mutations".to_string(),
+            ));
+        }
+        eprintln!("Testing {} mutants", mutants.len());
+
+        let jobs = self.jobs.unwrap_or_else(|| {
+            thread::available_parallelism().map_or(1, |parallelism| parallelism.get())
+        });
+        let next = AtomicUsize::new(0);
+        let outcomes = Mutex::new(vec![None; mutants.len()]);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs.max(1))
+                .map(|_| {
+                    scope.spawn(|| -> CliTypedResult<()> {
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            let (file, mutation) = match mutants.get(index) {
+                                Some(mutant) => *mutant,
+                                None => return Ok(()),
+                            };
+                            let outcome = self.test_mutant(&package, file, mutation)?;
+                            let (line, column) = line_and_column(&file.source, mutation.start);
+                            eprintln!(
+                                "{}:{}:{}: {}: {:?}",
+                                file.path.display(),
+                                line,
+                                column,
+                                mutation.description,
+                                outcome
+                            );
+                            outcomes.lock().unwrap()[index] = Some(outcome);
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().unwrap())
+        })?;
+
+        let outcomes = outcomes.into_inner().unwrap();
+        let count = |expected| {
+            outcomes
+                .iter()
+                .filter(|outcome| **outcome == Some(expected))
+                .count()
+        };
+        let killed = count(Outcome::Killed);
+        let not_compiled = count(Outcome::NotCompiled);
+        let survived: Vec<SurvivingMutant> = mutants
+            .iter()
+            .zip(&outcomes)
+            .filter(|(_, outcome)| **outcome == Some(Outcome::Survived))
+            .map(|((file, mutation), _)| {
+                let (line, column) = line_and_column(&file.source, mutation.start);
+                SurvivingMutant {
+                    file: file.path.display().to_string(),
+                    line,
+                    column,
+                    mutation: mutation.description.clone(),
+                }
+            })
+            .collect();
+        let compiled = killed + survived.len();
+        Ok(MutationSummary {
+            mutants: mutants.len(),
+            killed,
+            not_compiled,
+            score: if compiled == 0 {
+                100.0
+            } else {
+                killed as f64 * 100.0 / compiled as f64
+            },
+            survived,
+        })
+    }
+
+    /// Tests a mutant in a copy of the package, next to the package so that relative paths to
+    /// dependencies still resolve
+    fn test_mutant(
+        &self,
+        package: &Path,
+        file: &MutatedFile,
+        mutation: &Mutation,
+    ) -> CliTypedResult<Outcome> {
+        let parent = package.parent().unwrap_or(package);
+        let copy = tempfile::Builder::new()
+            .prefix(".mutant-")
+            .tempdir_in(parent)
+            .map_err(|err| CliError::IO(parent.display().to_string(), err))?;
+        copy_package(package, copy.path())?;
+        let mut source = file.source.clone();
+        source.replace_range(mutation.start..mutation.end, &mutation.replacement);
+        let path = copy.path().join(&file.path);
+        fs::write(&path, source).map_err(|err| CliError::IO(path.display().to_string(), err))?;
+        self.run_tests(copy.path(), true)
+    }
+
+    /// Runs `aptos move test` on the package in a separate process, as compilation errors
+    /// end the process compiling
+    fn run_tests(
+        &self,
+        package: &Path,
+        skip_fetch_latest_git_deps: bool,
+    ) -> CliTypedResult<Outcome> {
+        let exe = std::env::current_exe()
+            .map_err(|err| CliError::UnexpectedError(format!("Failed to find the CLI: {}", err)))?;
+        let mut command = Command::new(&exe);
+        command
+            .args(["move", "test", "--ignore-compile-warnings", "--fuzz-seed", "0"])
+            .args(["--instructions", &self.instruction_execution_bound.to_string()])
+            .arg("--package-dir")
+            .arg(package);
+        if skip_fetch_latest_git_deps {
+            command.arg("--skip-fetch-latest-git-deps");
+        }
+        if let Some(filter) = &self.filter {
+            command.args(["--filter", filter]);
+        }
+        let named_addresses: Vec<String> = self
+            .move_options
+            .named_addresses()
+            .iter()
+            .map(|(name, address)| format!("{}={}", name, address.to_hex_literal()))
+            .collect();
+        if !named_addresses.is_empty() {
+            command.args(["--named-addresses", &named_addresses.join(",")]);
+        }
+        if let Some(version) = self.move_options.compiler_version {
+            command.args(["--compiler-version", &version.to_string()]);
+        }
+        if let Some(version) = self.move_options.language_version {
+            command.args(["--language-version", &version.to_string()]);
+        }
+        if self.move_options.skip_attribute_checks {
+            command.arg("--skip-attribute-checks");
+        }
+
+        let output = command
+            .output()
+            .map_err(|err| CliError::IO(exe.display().to_string(), err))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(if output.status.success() {
+            Outcome::Survived
+        } else if stdout.contains(&CliError::MoveTestError.to_string()) {
+            Outcome::Killed
+        } else {
+            Outcome::NotCompiled
+        })
+    }
+}
+
+fn canonicalize(path: &Path) -> CliTypedResult<PathBuf> {
+    path.canonicalize()
+        .map_err(|err| CliError::IO(path.display().to_string(), err))
+}
+
+/// Copies the package at `from` to `to`, without build artifacts and hidden files
+fn copy_package(from: &Path, to: &Path) -> CliTypedResult<()> {
+    let to_cli_error = |path: &Path, err| CliError::IO(path.display().to_string(), err);
+    for entry in fs::read_dir(from).map_err(|err| to_cli_error(from, err))? {
+        let entry = entry.map_err(|err| to_cli_error(from, err))?;
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.')
+            || name == SourcePackageLayout::Build.location_str()
+        {
+            continue;
+        }
+        let path = entry.path();
+        let target = to.join(&name);
+        if path.is_dir() {
+            fs::create_dir(&target).map_err(|err| to_cli_error(&target, err))?;
+            copy_package(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).map_err(|err| to_cli_error(&target, err))?;
+        }
+    }
+    Ok(())
+}
+
+/// The 1-based line and column of a byte offset of `source`
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+struct Token<'a> {
+    tok: Tok,
+    start: usize,
+    text: &'a str,
+    space_before: bool,
+}
+
+impl Token<'_> {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    /// Whether the token can end an operand of a binary operator
+    fn ends_operand(&self) -> bool {
+        matches!(
+            self.tok,
+            Tok::Identifier
+                | Tok::NumValue
+                | Tok::NumTypedValue
+                | Tok::ByteStringValue
+                | Tok::True
+                | Tok::False
+                | Tok::RParen
+                | Tok::RBracket
+        )
+    }
+}
+
+fn tokenize(source: &str) -> Option<Vec<Token<'_>>> {
+    let mut lexer = Lexer::new(source, FileHash::new(source));
+    let mut tokens = vec![];
+    let mut prev_end = 0;
+    loop {
+        lexer.advance().ok()?;
+        if lexer.peek() == Tok::EOF {
+            return Some(tokens);
+        }
+        let start = lexer.start_loc();
+        tokens.push(Token {
+            tok: lexer.peek(),
+            start,
+            text: lexer.content(),
+            space_before: source[prev_end..start].ends_with(char::is_whitespace),
+        });
+        prev_end = start + lexer.content().len();
+    }
+}
+
+/// The index of the token closing the bracket opened at `open`
+fn closing(tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.tok {
+            Tok::LParen | Tok::LBracket | Tok::LBrace => depth += 1,
+            Tok::RParen | Tok::RBracket | Tok::RBrace => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            },
+            _ => (),
+        }
+    }
+    tokens.len() - 1
+}
+
+/// The index of the last token of the item, or spec block, starting at `start`
+fn item_end(tokens: &[Token], start: usize) -> usize {
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token.tok {
+            Tok::Semicolon => return i,
+            Tok::LBrace => return closing(tokens, i),
+            _ => (),
+        }
+    }
+    tokens.len() - 1
+}
+
+/// The mutations of a source file. Specifications and test-only items aren't mutated.
+fn mutations(source: &str) -> Vec<Mutation> {
+    let tokens = match tokenize(source) {
+        Some(tokens) => tokens,
+        None => return vec![],
+    };
+    let mut mutations = vec![];
+    let mut skip_item = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let prev = i.checked_sub(1).map(|j| &tokens[j]);
+        let next = tokens.get(i + 1);
+        if token.tok == Tok::NumSign && next.map(|next| next.tok) == Some(Tok::LBracket) {
+            let end = closing(&tokens, i + 1);
+            skip_item |= tokens[i + 1..end].iter().any(|token| {
+                token.tok == Tok::Identifier && TEST_ATTRIBUTES.contains(&token.text)
+            });
+            i = end + 1;
+            continue;
+        }
+        if skip_item || token.tok == Tok::Spec {
+            skip_item = false;
+            i = item_end(&tokens, i) + 1;
+            continue;
+        }
+
+        let replace = |replacement: &str| Mutation {
+            start: token.start,
+            end: token.end(),
+            replacement: replacement.to_string(),
+            description: format!("replaced `{}` with `{}`", token.text, replacement),
+        };
+        let binary = prev.map_or(false, Token::ends_operand);
+        // `<` and `>` are also type argument brackets, which aren't spaced
+        let spaced = token.space_before && next.map_or(false, |next| next.space_before);
+        let replacement = match token.tok {
+            Tok::Plus if binary => Some("-"),
+            Tok::Minus if binary => Some("+"),
+            Tok::Star if binary => Some("/"),
+            Tok::Slash if binary => Some("*"),
+            Tok::Percent if binary => Some("*"),
+            Tok::AmpAmp if binary => Some("||"),
+            Tok::PipePipe if binary => Some("&&"),
+            Tok::EqualEqual if binary => Some("!="),
+            Tok::ExclaimEqual if binary => Some("=="),
+            Tok::LessEqual if binary => Some(">"),
+            Tok::GreaterEqual if binary => Some("<"),
+            Tok::Less if binary && spaced => Some(">="),
+            Tok::Greater if binary && spaced => Some("<="),
+            Tok::True => Some("false"),
+            Tok::False => Some("true"),
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            mutations.push(replace(replacement));
+        }
+
+        match token.tok {
+            Tok::Identifier
+                if token.text == "assert"
+                    && next.map(|next| next.tok) == Some(Tok::Exclaim)
+                    && tokens.get(i + 2).map(|paren| paren.tok) == Some(Tok::LParen) =>
+            {
+                mutations.push(Mutation {
+                    start: token.start,
+                    end: tokens[closing(&tokens, i + 2)].end(),
+                    replacement: "()".to_string(),
+                    description: "removed `assert!`".to_string(),
+                });
+            },
+            // Numbers which aren't addresses
+            Tok::NumValue | Tok::NumTypedValue
+                if prev.map(|prev| prev.tok) != Some(Tok::AtSign)
+                    && prev.map(|prev| prev.text) != Some("address")
+                    && next.map(|next| next.tok) != Some(Tok::ColonColon) =>
+            {
+                if let Some(replacement) = changed_number(token.text) {
+                    mutations.push(replace(&replacement));
+                }
+            },
+            _ => (),
+        }
+        i += 1;
+    }
+    mutations
+}
+
+/// A number literal changed to a different value of the same type, without overflowing it
+fn changed_number(text: &str) -> Option<String> {
+    let text = text.replace('_', "");
+    let (digits, suffix) = match text.find('u') {
+        Some(suffix) => text.split_at(suffix),
+        None => (text.as_str(), ""),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<u128>().ok()?,
+    };
+    let changed = if value == 0 { 1 } else { value - 1 };
+    Some(format!("{}{}", changed, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mutated(source: &str) -> Vec<String> {
+        mutations(source)
+            .into_iter()
+            .map(|mutation| {
+                let mut mutated = source.to_string();
+                mutated.replace_range(mutation.start..mutation.end, &mutation.replacement);
+                mutated
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mutates_operators_asserts_and_constants() {
+        let source = "module 0x1::m {
+            const E: u64 = 0x10;
+            fun f(x: u64, v: vector<u64>): bool {
+                assert!(x < 10u8, E);
+                x + *vector::borrow(&v, 0) > 1 && true
+            }
+        }";
+        assert_eq!(mutated(source), vec![
+            source.replace("0x10", "15"),
+            source.replace("assert!(x < 10u8, E)", "()"),
+            source.replace("x < 10u8", "x >= 10u8"),
+            source.replace("10u8", "9u8"),
+            source.replace("x + *", "x - *"),
+            source.replace("&v, 0)", "&v, 1)"),
+            source.replace(") > 1", ") <= 1"),
+            source.replace("> 1 &&", "> 0 &&"),
+            source.replace("1 && true", "1 || true"),
+            source.replace("&& true", "&& false"),
+        ]);
+    }
+
+    #[test]
+    fn skips_specs_and_tests() {
+        let source = "module 0x1::m {
+            fun f(): u64 { 1 }
+            spec f { ensures result == 1; }
+            #[test(a = @0x2)]
+            fun test_f(a: signer) { assert!(f() == 1, 0); }
+        }
+        #[test_only]
+        module 0x1::m_tests {
+            fun g(): u64 { 2 + 2 }
+        }";
+        assert_eq!(mutated(source), vec![source.replace("{ 1 }", "{ 0 }")]);
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::extended_checks;
+use async_trait::async_trait;
+use clap::Parser;
+use move_package::{BuildConfig, CompilerConfig};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Updates the versions of registry dependencies locked in `Move.lock`
+///
+/// Dependencies on versions of packages, e.g. `Foo = "^1.2"` or
+/// `Foo = { version = "^1.2", registry = "../registry" }`, are resolved in a registry: a directory
+/// or git repository with the metadata of the versions of its packages in `index/<package>.toml`.
+/// The registry defaults to the `MOVE_REGISTRY` environment variable. The versions resolved are
+/// locked in `Move.lock` with the digests of their sources, and are kept until updated by this
+/// command to the latest versions which satisfy the requirements.
+#[derive(Parser)]
+pub struct UpdateDependencies {
+    /// Only update these packages, instead of all registry dependencies
+    #[clap(long, value_parser, num_args = 1..)]
+    pub packages: Vec<String>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+/// A dependency whose locked version changed
+#[derive(Debug, Serialize)]
+pub struct UpdatedDependency {
+    pub package: String,
+    /// The version locked before, if any
+    pub from: Option<String>,
+    /// The version locked now, if any
+    pub to: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<UpdatedDependency>> for UpdateDependencies {
+    fn command_name(&self) -> &'static str {
+        "UpdateDependencies"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<UpdatedDependency>> {
+        let config = BuildConfig {
+            additional_named_addresses: self.move_options.named_addresses(),
+            skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            compiler_config: CompilerConfig {
+                known_attributes: extended_checks::get_all_attribute_names().clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let path = self.move_options.get_package_path()?;
+        let (old, new) = config
+            .update_dependencies(&path, &self.packages, &mut std::io::stderr())
+            .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+
+        let packages: BTreeSet<&String> = old
+            .packages
+            .iter()
+            .chain(&new.packages)
+            .map(|locked| &locked.name)
+            .collect();
+        Ok(packages
+            .into_iter()
+            .filter_map(|package| {
+                let from = old.get(package).map(|locked| locked.version.to_string());
+                let to = new.get(package).map(|locked| locked.version.to_string());
+                (from != to).then(|| UpdatedDependency {
+                    package: package.clone(),
+                    from,
+                    to,
+                })
+            })
+            .collect())
+    }
+}
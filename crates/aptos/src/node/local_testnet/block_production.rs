@@ -0,0 +1,298 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions};
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress, account_config::BlockResource,
+    on_chain_config::ConfigurationResource,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use reqwest::{Method, Url};
+use std::time::{Duration, Instant};
+
+const US_IN_SECS: u64 = 1_000_000;
+
+/// How long to wait for an epoch to end once its interval has passed.
+const EPOCH_CHANGE_TIMEOUT: Duration = Duration::from_secs(60);
+const EPOCH_CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Parser)]
+pub struct AdminServiceOptions {
+    /// URL of the admin service of the localnet node
+    #[clap(long, default_value = "http://127.0.0.1:9102")]
+    pub(crate) admin_service_url: Url,
+}
+
+impl AdminServiceOptions {
+    /// Sends a request to the admin service of the node, returning the body of the
+    /// response.
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> CliTypedResult<String> {
+        let url = self
+            .admin_service_url
+            .join(path)
+            .map_err(|err| CliError::UnableToParse("admin-service-url", err.to_string()))?;
+        let response = reqwest::Client::new()
+            .request(method, url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        if !status.is_success() {
+            return Err(CliError::ApiError(format!("{}: {}", status, body)));
+        }
+        Ok(body)
+    }
+
+    /// Returns the time of the consensus clock of the node in microseconds, after
+    /// applying the given query.
+    async fn time(&self, query: &[(&str, String)]) -> CliTypedResult<u64> {
+        let time = self
+            .request(Method::POST, "debug/consensus/time", query)
+            .await?;
+        time.trim()
+            .parse()
+            .map_err(|err: std::num::ParseIntError| CliError::UnexpectedError(err.to_string()))
+    }
+
+    async fn mine(&self, blocks: u64) -> CliTypedResult<u64> {
+        let version = self
+            .request(Method::POST, "debug/consensus/block_production/mine", &[(
+                "blocks",
+                blocks.to_string(),
+            )])
+            .await?;
+        version
+            .trim()
+            .parse()
+            .map_err(|err: std::num::ParseIntError| CliError::UnexpectedError(err.to_string()))
+    }
+
+    async fn is_paused(&self) -> CliTypedResult<bool> {
+        let state = self
+            .request(Method::GET, "debug/consensus/block_production", &[])
+            .await?;
+        Ok(state.trim() == "paused")
+    }
+}
+
+/// Pause block production of a running localnet
+///
+/// Transactions are accepted, but not committed until blocks are mined with
+/// `aptos node localnet mine`, or block production is resumed.
+#[derive(Debug, Parser)]
+pub struct PauseLocalnet {
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for PauseLocalnet {
+    fn command_name(&self) -> &'static str {
+        "PauseLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        self.admin_service_options
+            .request(Method::POST, "debug/consensus/block_production/pause", &[])
+            .await
+    }
+}
+
+/// Resume block production of a paused localnet
+#[derive(Debug, Parser)]
+pub struct ResumeLocalnet {
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for ResumeLocalnet {
+    fn command_name(&self) -> &'static str {
+        "ResumeLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        self.admin_service_options
+            .request(Method::POST, "debug/consensus/block_production/resume", &[])
+            .await
+    }
+}
+
+/// Mine blocks on a paused localnet
+///
+/// Returns the latest committed version once the blocks are committed.
+#[derive(Debug, Parser)]
+pub struct MineLocalnet {
+    /// Number of blocks to mine
+    #[clap(long, default_value_t = 1)]
+    pub(crate) blocks: u64,
+
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+}
+
+#[async_trait]
+impl CliCommand<u64> for MineLocalnet {
+    fn command_name(&self) -> &'static str {
+        "MineLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<u64> {
+        self.admin_service_options.mine(self.blocks).await
+    }
+}
+
+/// Advance the clock of a running localnet
+///
+/// Blocks are timestamped with the clock of the node, so the on-chain time jumps ahead
+/// with the next block. The clock can't be turned back. Returns the time of the
+/// clock in microseconds.
+#[derive(Debug, Parser)]
+pub struct AdvanceTimeLocalnet {
+    /// Number of seconds to advance the clock by
+    #[clap(long)]
+    pub(crate) seconds: u64,
+
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+}
+
+#[async_trait]
+impl CliCommand<u64> for AdvanceTimeLocalnet {
+    fn command_name(&self) -> &'static str {
+        "AdvanceTimeLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<u64> {
+        self.admin_service_options
+            .time(&[("advance_secs", self.seconds.to_string())])
+            .await
+    }
+}
+
+/// Set the clock of a running localnet
+///
+/// The time must not be before the current time of the clock. Returns the time of the
+/// clock in microseconds.
+#[derive(Debug, Parser)]
+pub struct SetTimeLocalnet {
+    /// Unix timestamp in seconds to set the clock to
+    #[clap(long)]
+    pub(crate) timestamp: u64,
+
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+}
+
+#[async_trait]
+impl CliCommand<u64> for SetTimeLocalnet {
+    fn command_name(&self) -> &'static str {
+        "SetTimeLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<u64> {
+        self.admin_service_options
+            .time(&[("timestamp_secs", self.timestamp.to_string())])
+            .await
+    }
+}
+
+/// Fast-forward a running localnet by a number of epochs
+///
+/// The clock is advanced to the end of each epoch, and blocks are mined if block
+/// production is paused, until the epoch changes. Returns the new epoch.
+#[derive(Debug, Parser)]
+pub struct AdvanceEpochLocalnet {
+    /// Number of epochs to advance by
+    #[clap(long, default_value_t = 1)]
+    pub(crate) epochs: u64,
+
+    #[clap(flatten)]
+    pub(crate) admin_service_options: AdminServiceOptions,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<u64> for AdvanceEpochLocalnet {
+    fn command_name(&self) -> &'static str {
+        "AdvanceEpochLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<u64> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let admin = &self.admin_service_options;
+
+        let mut epoch = get_configuration(&client).await?.epoch();
+        for _ in 0..self.epochs {
+            let configuration = get_configuration(&client).await?;
+            let epoch_interval = client
+                .get_account_resource_bcs::<BlockResource>(
+                    AccountAddress::ONE,
+                    "0x1::block::BlockResource",
+                )
+                .await
+                .map_err(|err| CliError::ApiError(err.to_string()))?
+                .into_inner()
+                .epoch_interval();
+
+            // The epoch ends with the first block after its interval has passed.
+            let epoch_end_usecs = configuration.last_reconfiguration_time() + epoch_interval;
+            let now_usecs = admin.time(&[]).await?;
+            if now_usecs < epoch_end_usecs {
+                let advance_secs = (epoch_end_usecs - now_usecs).div_ceil(US_IN_SECS);
+                admin
+                    .time(&[("advance_secs", advance_secs.to_string())])
+                    .await?;
+            }
+
+            let start = Instant::now();
+            loop {
+                if admin.is_paused().await? {
+                    admin.mine(1).await?;
+                } else {
+                    tokio::time::sleep(EPOCH_CHANGE_POLL_INTERVAL).await;
+                }
+                let new_epoch = get_configuration(&client).await?.epoch();
+                if new_epoch > epoch {
+                    epoch = new_epoch;
+                    break;
+                }
+                if start.elapsed() > EPOCH_CHANGE_TIMEOUT {
+                    return Err(CliError::UnexpectedError(format!(
+                        "Timed out waiting for epoch {} to end",
+                        epoch
+                    )));
+                }
+            }
+        }
+        Ok(epoch)
+    }
+}
+
+async fn get_configuration(client: &Client) -> CliTypedResult<ConfigurationResource> {
+    Ok(client
+        .get_account_resource_bcs::<ConfigurationResource>(
+            AccountAddress::ONE,
+            "0x1::reconfiguration::Configuration",
+        )
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner())
+}
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
+use super::{
+    health_checker::HealthChecker, snapshot::NodeRestarts, traits::ServiceManager, RunLocalnet,
+};
 use anyhow::Result;
 use aptos_faucet_core::server::{FunderKeyEnum, RunConfig};
 use async_trait::async_trait;
@@ -42,6 +44,9 @@ pub struct FaucetArgs {
 pub struct FaucetManager {
     config: RunConfig,
     prerequisite_health_checkers: HashSet<HealthChecker>,
+    /// If the node can be snapshotted, the faucet is restarted whenever the node is,
+    /// since it caches the sequence numbers of its accounts.
+    node_restarts: Option<NodeRestarts>,
 }
 
 impl FaucetManager {
@@ -51,6 +56,7 @@ impl FaucetManager {
         bind_to: Ipv4Addr,
         test_dir: PathBuf,
         node_api_url: Url,
+        node_restarts: Option<NodeRestarts>,
     ) -> Result<Self> {
         Ok(Self {
             config: RunConfig::build_for_cli(
@@ -62,6 +68,7 @@ impl FaucetManager {
                 None,
             ),
             prerequisite_health_checkers,
+            node_restarts,
         })
     }
 }
@@ -84,6 +91,13 @@ impl ServiceManager for FaucetManager {
     }
 
     async fn run_service(self: Box<Self>) -> Result<()> {
-        self.config.run().await
+        match &self.node_restarts {
+            Some(node_restarts) => {
+                node_restarts
+                    .run_service("faucet", || self.config.clone().run())
+                    .await
+            },
+            None => self.config.run().await,
+        }
     }
 }
@@ -0,0 +1,626 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::state::ForkState;
+use anyhow::{anyhow, bail, Context};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_mempool::SubmissionStatus;
+use aptos_storage_interface::{AptosDbError, DbReader, Order, Result};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{AccountResource, BlockResource, ChainIdResource, NewBlockEvent},
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    chain_id::ChainId,
+    contract_event::EventWithVersion,
+    event::EventKey,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    on_chain_config::{ConfigurationResource, CurrentTimeMicroseconds},
+    proof::{
+        accumulator::InMemoryEventAccumulator, TransactionAccumulatorProof,
+        TransactionAccumulatorRangeProof, TransactionInfoListWithProof, TransactionInfoWithProof,
+    },
+    state_store::{
+        state_key::{prefix::StateKeyPrefix, StateKey},
+        state_storage_usage::StateStorageUsage,
+        state_value::StateValue,
+        Result as StateViewResult, TStateView,
+    },
+    transaction::{
+        authenticator::AccountAuthenticator, AccountTransactionsWithProof, SignedTransaction,
+        Transaction, TransactionInfo, TransactionOutput, TransactionOutputListWithProof,
+        TransactionStatus, TransactionWithProof, Version,
+    },
+    write_set::TransactionWrite,
+};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM, VMValidator};
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A transaction executed on top of the fork, along with the block it was executed in.
+struct ForkTransaction {
+    transaction: Transaction,
+    output: TransactionOutput,
+    info: TransactionInfo,
+    block: NewBlockEvent,
+}
+
+#[derive(Default)]
+struct ForkLedger {
+    /// Values written on top of the forked state, by key and then by version.
+    writes: HashMap<StateKey, BTreeMap<Version, Option<StateValue>>>,
+    /// Transactions executed on top of the fork, starting at the version after it.
+    transactions: Vec<ForkTransaction>,
+    by_hash: HashMap<HashValue, Version>,
+    by_account: HashMap<AccountAddress, BTreeMap<u64, Version>>,
+}
+
+impl ForkLedger {
+    fn write(&mut self, key: StateKey, version: Version, value: Option<StateValue>) {
+        self.writes.entry(key).or_default().insert(version, value);
+    }
+}
+
+/// A ledger that starts from the state of another network at a given version, and
+/// executes each transaction submitted to it on top of that state in a block of its
+/// own. Only the transactions executed by the fork are served, and there are no
+/// proofs.
+pub struct ForkDb {
+    state: ForkState,
+    /// Accounts that any key may sign for.
+    impersonated: HashSet<AccountAddress>,
+    /// The block the fork was taken at.
+    fork_block: NewBlockEvent,
+    ledger: RwLock<ForkLedger>,
+}
+
+impl ForkDb {
+    pub fn new(state: ForkState, impersonated: HashSet<AccountAddress>) -> anyhow::Result<Self> {
+        let version = state.version();
+        let epoch = read_resource::<ConfigurationResource>(
+            &state,
+            &StateKey::on_chain_config::<ConfigurationResource>()?,
+        )?
+        .epoch();
+        let timestamp = read_resource::<CurrentTimeMicroseconds>(
+            &state,
+            &StateKey::on_chain_config::<CurrentTimeMicroseconds>()?,
+        )?
+        .microseconds;
+        let height = read_resource::<BlockResource>(
+            &state,
+            &StateKey::resource_typed::<BlockResource>(&CORE_CODE_ADDRESS)?,
+        )?
+        .height();
+        let fork_block = NewBlockEvent::new(
+            AccountAddress::ZERO,
+            epoch,
+            0,
+            height,
+            vec![],
+            AccountAddress::ZERO,
+            vec![],
+            timestamp,
+        );
+
+        // Use the test chain id, so transactions signed for the forked network can't be
+        // replayed on the fork, nor the other way around.
+        let mut ledger = ForkLedger::default();
+        let chain_id_key = StateKey::resource_typed::<ChainIdResource>(&CORE_CODE_ADDRESS)?;
+        let chain_id = state
+            .get(&chain_id_key)?
+            .ok_or_else(|| anyhow!("The forked network has no chain id"))?
+            .map_bytes(|_| Ok(bcs::to_bytes(&ChainId::test().id())?.into()))?;
+        ledger.write(chain_id_key, version, Some(chain_id));
+
+        Ok(Self {
+            state,
+            impersonated,
+            fork_block,
+            ledger: RwLock::new(ledger),
+        })
+    }
+
+    pub fn state(&self) -> &ForkState {
+        &self.state
+    }
+
+    fn latest_version(&self, ledger: &ForkLedger) -> Version {
+        self.state.version() + ledger.transactions.len() as Version
+    }
+
+    fn transaction<'a>(
+        &self,
+        ledger: &'a ForkLedger,
+        version: Version,
+    ) -> Result<&'a ForkTransaction> {
+        version
+            .checked_sub(self.state.version() + 1)
+            .and_then(|index| ledger.transactions.get(index as usize))
+            .ok_or_else(|| {
+                AptosDbError::NotFound(format!("Transaction at version {} of the fork", version))
+            })
+    }
+
+    fn block<'a>(&'a self, ledger: &'a ForkLedger, version: Version) -> Result<&'a NewBlockEvent> {
+        if version == self.state.version() {
+            Ok(&self.fork_block)
+        } else {
+            Ok(&self.transaction(ledger, version)?.block)
+        }
+    }
+
+    fn state_value(
+        &self,
+        ledger: &ForkLedger,
+        key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        if version < self.state.version() {
+            return Err(AptosDbError::NotFound(format!(
+                "State at version {}, before the fork",
+                version
+            )));
+        }
+        if let Some((_, value)) = ledger
+            .writes
+            .get(key)
+            .and_then(|writes| writes.range(..=version).next_back())
+        {
+            return Ok(value.clone());
+        }
+        Ok(self.state.get(key)?)
+    }
+
+    fn transaction_with_proof(
+        &self,
+        ledger: &ForkLedger,
+        version: Version,
+        fetch_events: bool,
+    ) -> Result<TransactionWithProof> {
+        let txn = self.transaction(ledger, version)?;
+        Ok(TransactionWithProof::new(
+            version,
+            txn.transaction.clone(),
+            fetch_events.then(|| txn.output.events().to_vec()),
+            TransactionInfoWithProof::new(
+                TransactionAccumulatorProof::new(vec![]),
+                txn.info.clone(),
+            ),
+        ))
+    }
+
+    /// Validates a transaction against the latest state of the fork, then executes it
+    /// and commits it in a block of its own.
+    ///
+    /// Impersonated accounts take the authentication key of whoever signs for them only
+    /// for the transaction, and get their own key back once it's committed.
+    ///
+    /// Transactions must be submitted one at a time.
+    pub fn submit(&self, txn: SignedTransaction) -> anyhow::Result<SubmissionStatus> {
+        let (version, block, writes, output, restored_keys) = {
+            let ledger = self.ledger.read().unwrap();
+            let latest_version = self.latest_version(&ledger);
+            let latest_block = self.block(&ledger, latest_version)?;
+            let view = ForkStateView {
+                db: self,
+                ledger: &ledger,
+                version: latest_version,
+                pending: HashMap::new(),
+            };
+
+            // There is no block prologue on the fork, so time is moved forward here.
+            let timestamp = std::cmp::max(
+                latest_block.timestamp + 1,
+                SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros() as u64,
+            );
+            let mut pending = HashMap::new();
+            let timestamp_key = StateKey::on_chain_config::<CurrentTimeMicroseconds>()?;
+            if let Some(value) = view.get_state_value(&timestamp_key)? {
+                let value = value.map_bytes(|_| {
+                    Ok(bcs::to_bytes(&CurrentTimeMicroseconds {
+                        microseconds: timestamp,
+                    })?
+                    .into())
+                })?;
+                pending.insert(timestamp_key, Some(value));
+            }
+
+            // Impersonated accounts take the authentication key of whoever signs for them.
+            let mut impersonations = vec![];
+            for (address, authenticator) in signers(&txn) {
+                if !self.impersonated.contains(&address) {
+                    continue;
+                }
+                let account_key = StateKey::resource_typed::<AccountResource>(&address)?;
+                if let Some(value) = view.get_state_value(&account_key)? {
+                    let mut account: AccountResource = bcs::from_bytes(value.bytes())?;
+                    let original_key = account.authentication_key().to_vec();
+                    let signer_key = authenticator.authentication_key().to_vec();
+                    account.set_authentication_key(signer_key.clone());
+                    let value = value.map_bytes(|_| Ok(bcs::to_bytes(&account)?.into()))?;
+                    pending.insert(account_key.clone(), Some(value));
+                    impersonations.push((account_key, original_key, signer_key));
+                }
+            }
+
+            let view = ForkStateView { pending, ..view };
+            let resolver = view.as_move_resolver();
+            let vm = AptosVM::new(&resolver, None);
+            if let Some(status) = vm.validate_transaction(txn.clone(), &view).status() {
+                return Ok((MempoolStatus::new(MempoolStatusCode::VmError), Some(status)));
+            }
+            let log_context = AdapterLogSchema::new(view.id(), 0);
+            let (_, vm_output) = vm.execute_user_transaction(&resolver, &txn, &log_context);
+            let output = vm_output
+                .try_materialize_into_transaction_output(&resolver)
+                .map_err(|status| anyhow!("Failed to materialize output: {:?}", status))?;
+            if let TransactionStatus::Discard(status) = output.status() {
+                return Ok((
+                    MempoolStatus::new(MempoolStatusCode::VmError),
+                    Some(*status),
+                ));
+            }
+
+            // Put the keys of impersonated accounts back, unless the transaction rotated
+            // them itself.
+            let mut restored_keys = vec![];
+            for (account_key, original_key, signer_key) in impersonations {
+                let value = match output.write_set().get(&account_key) {
+                    Some(op) => op.as_state_value(),
+                    None => view.pending[&account_key].clone(),
+                };
+                let Some(value) = value else {
+                    continue;
+                };
+                let mut account: AccountResource = bcs::from_bytes(value.bytes())?;
+                if account.authentication_key() != signer_key.as_slice() {
+                    continue;
+                }
+                account.set_authentication_key(original_key);
+                let value = value.map_bytes(|_| Ok(bcs::to_bytes(&account)?.into()))?;
+                restored_keys.push((account_key, value));
+            }
+
+            let block = NewBlockEvent::new(
+                AccountAddress::new(*txn.committed_hash()),
+                latest_block.epoch,
+                latest_block.round + 1,
+                latest_block.height + 1,
+                vec![],
+                AccountAddress::ZERO,
+                vec![],
+                timestamp,
+            );
+            (
+                latest_version + 1,
+                block,
+                view.pending,
+                output,
+                restored_keys,
+            )
+        };
+
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            _ => bail!("Transaction {} was not kept", txn.committed_hash()),
+        };
+        let event_hashes: Vec<_> = output.events().iter().map(CryptoHash::hash).collect();
+        let transaction = Transaction::UserTransaction(txn.clone());
+        let info = TransactionInfo::new(
+            CryptoHash::hash(&transaction),
+            CryptoHash::hash(output.write_set()),
+            InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash(),
+            None,
+            output.gas_used(),
+            status,
+        );
+
+        let mut ledger = self.ledger.write().unwrap();
+        for (key, value) in writes {
+            ledger.write(key, version, value);
+        }
+        for (key, op) in output.write_set() {
+            ledger.write(key.clone(), version, op.as_state_value());
+        }
+        for (key, value) in restored_keys {
+            ledger.write(key, version, Some(value));
+        }
+        ledger.by_hash.insert(txn.committed_hash(), version);
+        ledger
+            .by_account
+            .entry(txn.sender())
+            .or_default()
+            .insert(txn.sequence_number(), version);
+        ledger.transactions.push(ForkTransaction {
+            transaction,
+            output,
+            info,
+            block,
+        });
+        Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None))
+    }
+}
+
+impl fmt::Debug for ForkDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForkDb")
+            .field("fork_version", &self.state.version())
+            .field("impersonated", &self.impersonated)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DbReader for ForkDb {
+    fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
+        let ledger = self.ledger.read().unwrap();
+        let version = self.latest_version(&ledger);
+        let block = self.block(&ledger, version)?;
+        let block_info = BlockInfo::new(
+            block.epoch,
+            block.round,
+            HashValue::zero(),
+            HashValue::zero(),
+            version,
+            block.timestamp,
+            None,
+        );
+        Ok(Some(LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info, HashValue::zero()),
+            AggregateSignature::empty(),
+        )))
+    }
+
+    fn get_latest_state_checkpoint_version(&self) -> Result<Option<Version>> {
+        Ok(Some(self.latest_version(&self.ledger.read().unwrap())))
+    }
+
+    fn get_first_viable_block(&self) -> Result<(Version, u64)> {
+        Ok((self.state.version(), self.fork_block.height))
+    }
+
+    fn get_block_info_by_version(
+        &self,
+        version: Version,
+    ) -> Result<(Version, Version, NewBlockEvent)> {
+        let ledger = self.ledger.read().unwrap();
+        Ok((version, version, self.block(&ledger, version)?.clone()))
+    }
+
+    fn get_block_info_by_height(&self, height: u64) -> Result<(Version, Version, NewBlockEvent)> {
+        let version = height
+            .checked_sub(self.fork_block.height)
+            .map(|offset| self.state.version() + offset)
+            .ok_or_else(|| AptosDbError::NotFound(format!("Block {} of the fork", height)))?;
+        self.get_block_info_by_version(version)
+    }
+
+    fn get_block_timestamp(&self, version: Version) -> Result<u64> {
+        let ledger = self.ledger.read().unwrap();
+        Ok(self.block(&ledger, version)?.timestamp)
+    }
+
+    fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        self.state_value(&self.ledger.read().unwrap(), state_key, version)
+    }
+
+    fn get_state_storage_usage(&self, _version: Option<Version>) -> Result<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+
+    fn get_prefixed_state_value_iterator(
+        &self,
+        _key_prefix: &StateKeyPrefix,
+        _cursor: Option<&StateKey>,
+        _version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + '_>> {
+        Err(AptosDbError::Other(
+            "Listing state is not supported on a fork".to_string(),
+        ))
+    }
+
+    fn get_transaction_by_version(
+        &self,
+        version: Version,
+        _ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<TransactionWithProof> {
+        self.transaction_with_proof(&self.ledger.read().unwrap(), version, fetch_events)
+    }
+
+    fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        let ledger = self.ledger.read().unwrap();
+        match ledger.by_hash.get(&hash) {
+            Some(version) if *version <= ledger_version => Ok(Some(self.transaction_with_proof(
+                &ledger,
+                *version,
+                fetch_events,
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_transaction_outputs(
+        &self,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<TransactionOutputListWithProof> {
+        let ledger = self.ledger.read().unwrap();
+        let end_version = std::cmp::min(start_version.saturating_add(limit), ledger_version + 1);
+        let mut transactions_and_outputs = vec![];
+        let mut infos = vec![];
+        for version in start_version..end_version {
+            let txn = self.transaction(&ledger, version)?;
+            transactions_and_outputs.push((txn.transaction.clone(), txn.output.clone()));
+            infos.push(txn.info.clone());
+        }
+        let first_version = (!transactions_and_outputs.is_empty()).then_some(start_version);
+        Ok(TransactionOutputListWithProof::new(
+            transactions_and_outputs,
+            first_version,
+            TransactionInfoListWithProof::new(TransactionAccumulatorRangeProof::new_empty(), infos),
+        ))
+    }
+
+    fn get_account_transaction(
+        &self,
+        address: AccountAddress,
+        seq_num: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<Option<TransactionWithProof>> {
+        let ledger = self.ledger.read().unwrap();
+        match ledger
+            .by_account
+            .get(&address)
+            .and_then(|versions| versions.get(&seq_num))
+        {
+            Some(version) if *version <= ledger_version => Ok(Some(self.transaction_with_proof(
+                &ledger,
+                *version,
+                include_events,
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn get_account_transactions(
+        &self,
+        address: AccountAddress,
+        seq_num: u64,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionsWithProof> {
+        let ledger = self.ledger.read().unwrap();
+        let txns = match ledger.by_account.get(&address) {
+            Some(versions) => versions
+                .range(seq_num..)
+                .map(|(_, version)| *version)
+                .filter(|version| *version <= ledger_version)
+                .take(limit as usize)
+                .map(|version| self.transaction_with_proof(&ledger, version, include_events))
+                .collect::<Result<_>>()?,
+            None => vec![],
+        };
+        Ok(AccountTransactionsWithProof::new(txns))
+    }
+
+    fn get_events(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        let ledger = self.ledger.read().unwrap();
+        let mut events: Vec<_> = ledger
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, txn)| (self.state.version() + 1 + index as Version, txn))
+            .filter(|(version, _)| *version <= ledger_version)
+            .flat_map(|(version, txn)| {
+                txn.output
+                    .events()
+                    .iter()
+                    .filter(|event| event.event_key() == Some(event_key))
+                    .map(move |event| EventWithVersion::new(version, event.clone()))
+            })
+            .filter(|event| {
+                let seq_num = event
+                    .event
+                    .v1()
+                    .map(|event| event.sequence_number())
+                    .unwrap_or_default();
+                match order {
+                    Order::Ascending => seq_num >= start,
+                    Order::Descending => seq_num <= start,
+                }
+            })
+            .collect();
+        if order == Order::Descending {
+            events.reverse();
+        }
+        events.truncate(limit as usize);
+        Ok(events)
+    }
+
+    fn get_accumulator_root_hash(&self, _version: Version) -> Result<HashValue> {
+        Ok(HashValue::zero())
+    }
+
+    fn indexer_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// The state of the fork at a version, with writes about to be committed on top.
+struct ForkStateView<'a> {
+    db: &'a ForkDb,
+    ledger: &'a ForkLedger,
+    version: Version,
+    pending: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl<'a> TStateView for ForkStateView<'a> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        if let Some(value) = self.pending.get(state_key) {
+            return Ok(value.clone());
+        }
+        self.db
+            .state_value(self.ledger, state_key, self.version)
+            .map_err(Into::into)
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+fn read_resource<T: DeserializeOwned>(state: &ForkState, key: &StateKey) -> anyhow::Result<T> {
+    let value = state
+        .get(key)?
+        .ok_or_else(|| anyhow!("{:?} not found in the forked state", key))?;
+    bcs::from_bytes(value.bytes()).with_context(|| format!("Failed to deserialize {:?}", key))
+}
+
+/// Every account that signs a transaction, along with its signature.
+fn signers(txn: &SignedTransaction) -> Vec<(AccountAddress, AccountAuthenticator)> {
+    let authenticator = txn.authenticator();
+    let mut signers = vec![(txn.sender(), authenticator.sender())];
+    signers.extend(
+        authenticator
+            .secondary_signer_addresses()
+            .into_iter()
+            .zip(authenticator.secondary_signers()),
+    );
+    if let (Some(address), Some(signer)) = (
+        authenticator.fee_payer_address(),
+        authenticator.fee_payer_signer(),
+    ) {
+        signers.push((address, signer));
+    }
+    signers
+}
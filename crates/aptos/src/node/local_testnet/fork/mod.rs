@@ -0,0 +1,350 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod db;
+mod state;
+
+pub use self::{
+    db::ForkDb,
+    state::{ForkFixture, ForkState},
+};
+use super::traits::ShutdownStep;
+use anyhow::{bail, Context, Result};
+use aptos_config::config::NodeConfig;
+use aptos_mempool::MempoolClientRequest;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
+use aptos_validator_interface::{AptosValidatorInterface, RestDebuggerInterface};
+use async_trait::async_trait;
+use clap::Parser;
+use futures::StreamExt;
+use reqwest::Url;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// The file in the test directory that a fork records the state it reads to.
+pub const FORK_FIXTURE_FILE: &str = "fork-fixture.bcs";
+
+const MEMPOOL_CHANNEL_SIZE: usize = 1_024;
+
+/// Args for running the localnet as a fork of another network.
+#[derive(Debug, Parser)]
+pub struct ForkArgs {
+    /// REST API URL of a network to fork, e.g. https://fullnode.mainnet.aptoslabs.com
+    ///
+    /// Instead of running its own genesis, the node starts from the state of this
+    /// network, fetching it as it is read, and executes transactions submitted to it on
+    /// top. The fork uses the chain id of a test network, so transactions can't be
+    /// replayed between the two. There is no faucet, transaction stream or indexer API
+    /// for a fork, and resources can't be listed.
+    #[clap(long, conflicts_with = "fork_fixture")]
+    pub fork_url: Option<Url>,
+
+    /// Version of the network to fork at
+    ///
+    /// Defaults to the latest version.
+    #[clap(long, requires = "fork_url")]
+    pub fork_version: Option<u64>,
+
+    /// Fork from a fixture rather than from a network
+    ///
+    /// A fork of a network records the state it reads to `fork-fixture.bcs` in the test
+    /// directory, so the fork can be run again without network access.
+    #[clap(long, value_parser)]
+    pub fork_fixture: Option<PathBuf>,
+
+    /// Accounts of the forked network that transactions can be signed for with any key
+    #[clap(long, num_args = 0.., value_parser = crate::common::types::load_account_arg)]
+    pub impersonate: Vec<AccountAddress>,
+}
+
+impl ForkArgs {
+    /// Opens the state to fork from, if the localnet is a fork.
+    pub async fn open(&self) -> Result<Option<Arc<ForkDb>>> {
+        let state = if let Some(fork_url) = &self.fork_url {
+            let interface = Arc::new(RestDebuggerInterface::new(aptos_rest_client::Client::new(
+                fork_url.clone(),
+            )));
+            let version = match self.fork_version {
+                Some(version) => version,
+                None => interface
+                    .get_latest_ledger_info_version()
+                    .await
+                    .context("Failed to get the latest version of the network to fork")?,
+            };
+            ForkState::remote(interface, version)?
+        } else if let Some(fork_fixture) = &self.fork_fixture {
+            ForkState::fixture(ForkFixture::load(fork_fixture)?)
+        } else {
+            if !self.impersonate.is_empty() {
+                bail!("--impersonate can only be used with --fork-url or --fork-fixture");
+            }
+            return Ok(None);
+        };
+
+        info!("Forking at version {}", state.version());
+        let impersonated = self.impersonate.iter().copied().collect();
+        // Reading the forked state blocks until the state is fetched.
+        let db = tokio::task::spawn_blocking(move || ForkDb::new(state, impersonated))
+            .await?
+            .context("Failed to read the state to fork from")?;
+        Ok(Some(Arc::new(db)))
+    }
+}
+
+/// Runs the node API of a fork, executing transactions as they are submitted. This
+/// blocks forever, so it should be run on its own thread.
+///
+/// The state read from the forked network is saved as a fixture when the localnet
+/// shuts down, see [`SaveForkFixtureShutdownStep`].
+pub fn run_fork_node(config: NodeConfig, db: Arc<ForkDb>) -> Result<()> {
+    let (mempool_sender, mut mempool_receiver) =
+        futures::channel::mpsc::channel(MEMPOOL_CHANNEL_SIZE);
+    let runtime = aptos_api::bootstrap(&config, ChainId::test(), db.clone(), mempool_sender, None)
+        .context("Failed to start the node API")?;
+
+    runtime.block_on(async move {
+        while let Some(request) = mempool_receiver.next().await {
+            match request {
+                MempoolClientRequest::SubmitTransaction(txn, callback) => {
+                    let db = db.clone();
+                    let result = tokio::task::spawn_blocking(move || db.submit(txn)).await?;
+                    let _ = callback.send(result);
+                },
+                // Transactions are committed as they are submitted, none are ever pending.
+                MempoolClientRequest::GetTransactionByHash(_, callback) => {
+                    let _ = callback.send(None);
+                },
+            }
+        }
+        bail!("The node API stopped submitting transactions")
+    })
+}
+
+/// Saves the state a fork of a network has read, so the fork can be run offline.
+fn save_fixture(db: &ForkDb, path: &Path) -> Result<()> {
+    if db.state().is_remote() {
+        db.state().to_fixture().save(path)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct SaveForkFixtureShutdownStep {
+    db: Arc<ForkDb>,
+    path: PathBuf,
+}
+
+impl SaveForkFixtureShutdownStep {
+    pub fn new(db: Arc<ForkDb>, test_dir: &Path) -> Self {
+        Self {
+            db,
+            path: test_dir.join(FORK_FIXTURE_FILE),
+        }
+    }
+}
+
+#[async_trait]
+impl ShutdownStep for SaveForkFixtureShutdownStep {
+    async fn run(self: Box<Self>) -> Result<()> {
+        tokio::task::spawn_blocking(move || save_fixture(&self.db, &self.path)).await??;
+        eprintln!(
+            "Saved the forked state that was read to {}",
+            FORK_FIXTURE_FILE
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+    use aptos_framework::natives::code::PackageMetadata;
+    use aptos_keygen::KeyGen;
+    use aptos_mempool::SubmissionStatus;
+    use aptos_sdk::transaction_builder::TransactionFactory;
+    use aptos_storage_interface::DbReader;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        account_config::{aptos_test_root_address, AccountResource},
+        mempool_status::MempoolStatusCode,
+        state_store::{state_key::StateKey, state_value::StateValue},
+        transaction::{
+            authenticator::AuthenticationKey, SignedTransaction, Transaction, TransactionInfo,
+            Version,
+        },
+        write_set::TransactionWrite,
+    };
+    use aptos_validator_interface::FilterCondition;
+    use aptos_vm_genesis::{test_genesis_change_set_and_validators, GENESIS_KEYPAIR};
+    use move_core_types::language_storage::ModuleId;
+    use std::collections::{HashMap, HashSet};
+
+    /// A network which only has its genesis state, standing in for a network to fork.
+    struct GenesisInterface(HashMap<StateKey, StateValue>);
+
+    impl GenesisInterface {
+        fn new() -> Self {
+            let (change_set, _) = test_genesis_change_set_and_validators(Some(1));
+            Self(
+                change_set
+                    .write_set()
+                    .into_iter()
+                    .filter_map(|(key, op)| Some((key.clone(), op.as_state_value()?)))
+                    .collect(),
+            )
+        }
+    }
+
+    #[async_trait]
+    impl AptosValidatorInterface for GenesisInterface {
+        async fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            _version: Version,
+        ) -> Result<Option<StateValue>> {
+            Ok(self.0.get(state_key).cloned())
+        }
+
+        async fn get_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+            bail!("not supported by the genesis mock")
+        }
+
+        async fn get_and_filter_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+            _filter_condition: FilterCondition,
+            _package_cache: &mut HashMap<
+                ModuleId,
+                (
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                ),
+            >,
+        ) -> Result<
+            Vec<(
+                u64,
+                Transaction,
+                Option<(
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                )>,
+            )>,
+        > {
+            bail!("not supported by the genesis mock")
+        }
+
+        async fn get_latest_ledger_info_version(&self) -> Result<Version> {
+            Ok(0)
+        }
+
+        async fn get_version_by_account_sequence(
+            &self,
+            _account: AccountAddress,
+            _seq: u64,
+        ) -> Result<Option<Version>> {
+            bail!("not supported by the genesis mock")
+        }
+    }
+
+    fn remote_fork(impersonated: HashSet<AccountAddress>) -> ForkDb {
+        let state = ForkState::remote(Arc::new(GenesisInterface::new()), 0).unwrap();
+        ForkDb::new(state, impersonated).unwrap()
+    }
+
+    /// A transfer from the root account of the test genesis, signed with `private_key`.
+    fn transfer(private_key: &Ed25519PrivateKey) -> SignedTransaction {
+        TransactionFactory::new(ChainId::test())
+            .payload(aptos_stdlib::aptos_account_transfer(
+                AccountAddress::from_hex_literal("0xcafe").unwrap(),
+                100,
+            ))
+            .sender(aptos_test_root_address())
+            .sequence_number(0)
+            .build()
+            .sign(private_key, private_key.public_key())
+            .unwrap()
+            .into_inner()
+    }
+
+    fn assert_committed(db: &ForkDb, status: SubmissionStatus) -> u64 {
+        assert_eq!(status.0.code, MempoolStatusCode::Accepted, "{:?}", status);
+        let version = db.get_latest_ledger_info_version().unwrap();
+        assert_eq!(version, 1);
+        let txn = db
+            .get_transaction_by_version(version, version, false)
+            .unwrap();
+        assert!(txn.proof.transaction_info.status().is_success());
+        txn.proof.transaction_info.gas_used()
+    }
+
+    #[test]
+    fn test_fork_replays_from_fixture() {
+        let db = remote_fork(HashSet::new());
+        let gas_used = assert_committed(&db, db.submit(transfer(&GENESIS_KEYPAIR.0)).unwrap());
+
+        // Everything the transaction read was recorded, so it can be executed offline
+        let path = TempPath::new();
+        db.state().to_fixture().save(path.path()).unwrap();
+        let fixture = ForkFixture::load(path.path()).unwrap();
+        assert_eq!(fixture.version, 0);
+        assert!(!fixture.state.is_empty());
+
+        let offline_db = ForkDb::new(ForkState::fixture(fixture), HashSet::new()).unwrap();
+        assert!(!offline_db.state().is_remote());
+        let offline_gas_used = assert_committed(
+            &offline_db,
+            offline_db.submit(transfer(&GENESIS_KEYPAIR.0)).unwrap(),
+        );
+        assert_eq!(offline_gas_used, gas_used);
+    }
+
+    #[test]
+    fn test_fixture_misses_unread_state() {
+        let state = ForkState::fixture(ForkFixture::default());
+        let key = StateKey::raw(b"never read");
+        let err = state.get(&key).unwrap_err();
+        assert!(err.to_string().contains("not in the fork fixture"));
+    }
+
+    #[test]
+    fn test_impersonation() {
+        let private_key = KeyGen::from_seed([7; 32]).generate_ed25519_private_key();
+
+        // Without impersonation, the key doesn't match the account
+        let db = remote_fork(HashSet::new());
+        let (status, vm_status) = db.submit(transfer(&private_key)).unwrap();
+        assert_eq!(status.code, MempoolStatusCode::VmError);
+        assert!(vm_status.is_some());
+
+        let db = remote_fork(HashSet::from([aptos_test_root_address()]));
+        assert_committed(&db, db.submit(transfer(&private_key)).unwrap());
+
+        // The impersonated account keeps its own key
+        let account_key =
+            StateKey::resource_typed::<AccountResource>(&aptos_test_root_address()).unwrap();
+        let account: AccountResource = bcs::from_bytes(
+            db.get_state_value_by_version(&account_key, 1)
+                .unwrap()
+                .unwrap()
+                .bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            account.authentication_key(),
+            AuthenticationKey::ed25519(&GENESIS_KEYPAIR.1).to_vec()
+        );
+        assert_eq!(account.sequence_number(), 1);
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue, TStateView},
+    transaction::Version,
+};
+use aptos_validator_interface::{AptosValidatorInterface, DebuggerStateView};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use tokio::runtime::Runtime;
+
+/// The state of the forked network that was read by a fork, saved so the fork can be
+/// started again without network access.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ForkFixture {
+    pub version: Version,
+    pub state: BTreeMap<StateKey, Option<StateValue>>,
+}
+
+impl ForkFixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read fork fixture {}", path.display()))?;
+        bcs::from_bytes(&bytes)
+            .with_context(|| format!("Failed to parse fork fixture {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, bcs::to_bytes(self)?)
+            .with_context(|| format!("Failed to write fork fixture {}", path.display()))
+    }
+}
+
+enum ForkStateSource {
+    /// Read lazily from the network being forked.
+    Remote {
+        view: DebuggerStateView,
+        // The view fetches state on tasks of this runtime and blocks the caller until
+        // they finish, so it must not share a runtime with its callers.
+        runtime: Option<Runtime>,
+    },
+    /// Read from a fixture recorded by an earlier fork.
+    Fixture(BTreeMap<StateKey, Option<StateValue>>),
+}
+
+/// The state of the forked network at the fork version. Every key read is kept, so
+/// the reads can be saved as a fixture.
+pub struct ForkState {
+    source: ForkStateSource,
+    version: Version,
+    fetched: Mutex<BTreeMap<StateKey, Option<StateValue>>>,
+}
+
+impl ForkState {
+    pub fn remote(
+        interface: Arc<dyn AptosValidatorInterface + Send>,
+        version: Version,
+    ) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("fork-state")
+            .enable_all()
+            .build()
+            .context("Failed to create runtime for fetching forked state")?;
+        // The view reads the state as of the end of `version - 1`.
+        let view = {
+            let _guard = runtime.enter();
+            DebuggerStateView::new(interface, version + 1)
+        };
+        Ok(Self {
+            source: ForkStateSource::Remote {
+                view,
+                runtime: Some(runtime),
+            },
+            version,
+            fetched: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn fixture(fixture: ForkFixture) -> Self {
+        Self {
+            source: ForkStateSource::Fixture(fixture.state),
+            version: fixture.version,
+            fetched: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self.source, ForkStateSource::Remote { .. })
+    }
+
+    /// The version of the forked network the state is read at.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn get(&self, key: &StateKey) -> Result<Option<StateValue>> {
+        if let Some(value) = self.fetched.lock().unwrap().get(key) {
+            return Ok(value.clone());
+        }
+
+        let value = match &self.source {
+            ForkStateSource::Remote { view, .. } => view
+                .get_state_value(key)
+                .map_err(|err| anyhow!("Failed to fetch {:?} from the network: {}", key, err))?,
+            ForkStateSource::Fixture(state) => match state.get(key) {
+                Some(value) => value.clone(),
+                None => bail!(
+                    "{:?} is not in the fork fixture, record the fixture again with --fork-url",
+                    key
+                ),
+            },
+        };
+        self.fetched
+            .lock()
+            .unwrap()
+            .insert(key.clone(), value.clone());
+        Ok(value)
+    }
+
+    /// Every key read so far, in a form that can be used to start a fork offline.
+    pub fn to_fixture(&self) -> ForkFixture {
+        ForkFixture {
+            version: self.version,
+            state: self.fetched.lock().unwrap().clone(),
+        }
+    }
+}
+
+impl Drop for ForkState {
+    fn drop(&mut self) {
+        // A runtime can't be dropped in an async context, which the last reference to
+        // the state may well be dropped in.
+        if let ForkStateSource::Remote { runtime, .. } = &mut self.source {
+            if let Some(runtime) = runtime.take() {
+                runtime.shutdown_background();
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod block_production;
 mod docker;
 mod faucet;
+mod fork;
 mod health_checker;
 mod indexer_api;
 mod logging;
@@ -10,6 +12,7 @@ mod node;
 mod postgres;
 mod processors;
 mod ready_server;
+mod snapshot;
 mod traits;
 mod utils;
 
@@ -24,6 +27,7 @@ use self::{
     ready_server::ReadyServerArgs,
     traits::{PostHealthyStep, ServiceManager},
 };
+pub use self::{node::RunLocalnetNode, snapshot::LocalnetTool};
 use crate::{
     common::{
         types::{CliCommand, CliError, CliTypedResult, ConfigSearchMode, PromptOptions},
@@ -57,6 +61,10 @@ const TESTNET_FOLDER: &str = "testnet";
 /// This localnet will run it's own genesis and run as a single node network
 /// locally. A faucet and grpc transaction stream will run alongside the node unless
 /// you specify otherwise with --no-faucet and --no-txn-stream respectively.
+///
+/// With --fork-url the localnet is instead a fork of another network, see --fork-url.
+/// With --with-snapshots the localnet can be snapshotted and reverted to a snapshot,
+/// see `aptos node localnet`.
 #[derive(Parser)]
 pub struct RunLocalnet {
     /// The directory to save all files for the node
@@ -248,19 +256,38 @@ impl CliCommand<()> for RunLocalnet {
 
         let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
 
+        // If the localnet is a fork, open the state to fork from.
+        let fork = self
+            .node_args
+            .fork_args
+            .open()
+            .await
+            .context("Failed to open the network to fork")?;
+        if fork.is_some() && self.indexer_api_args.with_indexer_api {
+            return Err(CliError::CommandArgumentError(
+                "A fork can't be run with an indexer API".to_string(),
+            ));
+        }
+
         // Build the node manager. We do this unconditionally.
-        let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
+        let node_manager = NodeManager::new(&self, bind_to, test_dir.clone(), fork.clone())
             .context("Failed to build node service manager")?;
         let node_health_checkers = node_manager.get_health_checkers();
-
-        // If configured to do so, build the faucet manager.
-        if !self.faucet_args.no_faucet {
+        let snapshot_request_sender = node_manager.get_snapshot_request_sender();
+        let node_restarts = node_manager.get_node_restarts();
+
+        // If configured to do so, build the faucet manager. A fork has no mint key, so
+        // it can't have a faucet.
+        if fork.is_some() && !self.faucet_args.no_faucet {
+            eprintln!("Not running a faucet, as there is none for a fork");
+        } else if !self.faucet_args.no_faucet {
             let faucet_manager = FaucetManager::new(
                 &self,
                 node_health_checkers.clone(),
                 bind_to,
                 test_dir.clone(),
                 node_manager.get_node_api_url(),
+                node_restarts.clone(),
             )
             .context("Failed to build faucet service manager")?;
             managers.push(Box::new(faucet_manager));
@@ -282,6 +309,7 @@ impl CliCommand<()> for RunLocalnet {
                 processor_preqrequisite_healthcheckers,
                 node_manager.get_data_service_url(),
                 self.postgres_args.get_connection_string(None, true),
+                node_restarts,
             )
             .context("Failed to build processor service managers")?;
 
@@ -323,6 +351,7 @@ impl CliCommand<()> for RunLocalnet {
             &self,
             bind_to,
             health_checkers.clone(),
+            snapshot_request_sender,
         )?));
 
         // Collect steps to run on shutdown. We run these in reverse. This is somewhat
@@ -1,11 +1,22 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
-use crate::node::local_testnet::utils::socket_addr_to_url;
-use anyhow::{anyhow, Context, Result};
+use super::{
+    fork::{run_fork_node, ForkArgs, ForkDb, SaveForkFixtureShutdownStep},
+    health_checker::HealthChecker,
+    snapshot::{NodeRestarts, PostgresSnapshots, SnapshotAction, SnapshotRequest, Snapshots},
+    traits::{ServiceManager, ShutdownStep},
+    RunLocalnet,
+};
+use crate::{
+    common::types::{CliCommand, CliError, CliTypedResult},
+    node::local_testnet::utils::socket_addr_to_url,
+};
+use anyhow::{anyhow, bail, Context, Result};
 use aptos_config::config::{NodeConfig, DEFAULT_GRPC_STREAM_PORT};
-use aptos_node::{load_node_config, start_test_environment_node};
+use aptos_node::{
+    load_node_config, start_test_environment_node, start_test_environment_node_until,
+};
 use async_trait::async_trait;
 use clap::Parser;
 use maplit::hashset;
@@ -14,10 +25,29 @@ use reqwest::Url;
 use std::{
     collections::HashSet,
     net::{IpAddr, Ipv4Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     thread,
     time::Duration,
 };
+use tokio::{
+    process::{Child, Command},
+    sync::{mpsc, oneshot, RwLockWriteGuard},
+};
+
+/// The file in the data directory of the node that the config of a node run in its
+/// own process is saved to.
+const NODE_PROCESS_CONFIG_FILE: &str = "localnet-node.yaml";
+
+const SNAPSHOT_REQUEST_CHANNEL_SIZE: usize = 16;
+
+/// How long the node process gets to stop once asked to, before it is killed.
+#[cfg(unix)]
+const NODE_STOP_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Args specific to running a node (and its components, e.g. the txn stream) in the
 /// localnet.
@@ -81,17 +111,69 @@ pub struct NodeArgs {
     // Because this flag is a bit of a footgun we hide it from regular users.
     #[clap(long, hide = true)]
     pub no_node: bool,
+
+    #[clap(flatten)]
+    pub fork_args: ForkArgs,
+
+    /// Allow snapshots of the localnet to be taken and reverted to.
+    ///
+    /// The node is run in its own process so it can be stopped to snapshot its data
+    /// or revert it, see `aptos node localnet snapshot`. Snapshots are saved to the
+    /// `snapshots` folder of the test directory. If the localnet runs an indexer API,
+    /// the indexer DB is snapshotted with the node. The faucet and the processors are
+    /// restarted whenever the node is, and clients of the txn stream must reconnect.
+    #[clap(
+        long,
+        conflicts_with_all = ["no_node", "fork_url", "fork_fixture"],
+    )]
+    pub with_snapshots: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct NodeManager {
     config: NodeConfig,
     test_dir: PathBuf,
     no_node: bool,
+    fork: Option<Arc<ForkDb>>,
+    snapshots: Option<NodeSnapshots>,
+}
+
+/// What a node needs to be snapshotted: the snapshots of the node and of the indexer
+/// DB, the services to stop while the node is, where to take requests for snapshots
+/// from, and where the node's config is saved so the node can be run in its own process.
+#[derive(Debug)]
+struct NodeSnapshots {
+    snapshots: Snapshots,
+    postgres_snapshots: Option<PostgresSnapshots>,
+    restarts: NodeRestarts,
+    request_sender: mpsc::Sender<SnapshotRequest>,
+    request_receiver: mpsc::Receiver<SnapshotRequest>,
+    config_path: PathBuf,
 }
 
 impl NodeManager {
-    pub fn new(args: &RunLocalnet, bind_to: Ipv4Addr, test_dir: PathBuf) -> Result<Self> {
+    pub fn new(
+        args: &RunLocalnet,
+        bind_to: Ipv4Addr,
+        test_dir: PathBuf,
+        fork: Option<Arc<ForkDb>>,
+    ) -> Result<Self> {
+        // A fork serves the node API on top of the forked state, with none of the
+        // other components of a node.
+        if fork.is_some() {
+            let mut node_config = NodeConfig::default();
+            node_config.api.address.set_ip(IpAddr::V4(bind_to));
+            node_config.api.gas_estimation.enabled = false;
+            node_config.indexer_grpc.enabled = false;
+            return Ok(NodeManager {
+                config: node_config,
+                test_dir,
+                no_node: args.node_args.no_node,
+                fork,
+                snapshots: None,
+            });
+        }
+
         let rng = args
             .node_args
             .seed
@@ -134,10 +216,43 @@ impl NodeManager {
         node_config.admin_service.address = bind_to.to_string();
         node_config.inspection_service.address = bind_to.to_string();
 
+        // Let `aptos node localnet` pause block production and move the clock ahead.
+        node_config.consensus.enable_localnet_controls = true;
+
+        // A node that can be snapshotted runs in its own process, which reads the final
+        // config from disk.
+        let snapshots = if args.node_args.with_snapshots {
+            let config_path = node_config.get_data_dir().join(NODE_PROCESS_CONFIG_FILE);
+            node_config
+                .clone()
+                .save_to_path(&config_path)
+                .context("Failed to save config for node")?;
+            let (request_sender, request_receiver) = mpsc::channel(SNAPSHOT_REQUEST_CHANNEL_SIZE);
+            let postgres_snapshots = args.indexer_api_args.with_indexer_api.then(|| {
+                PostgresSnapshots::new(
+                    args.postgres_args
+                        .get_connection_string(Some("postgres"), true),
+                    args.postgres_args.postgres_database.clone(),
+                )
+            });
+            Some(NodeSnapshots {
+                snapshots: Snapshots::new(&node_config, &test_dir),
+                postgres_snapshots,
+                restarts: NodeRestarts::new(),
+                request_sender,
+                request_receiver,
+                config_path,
+            })
+        } else {
+            None
+        };
+
         Ok(NodeManager {
             config: node_config,
             test_dir,
             no_node: args.node_args.no_node,
+            fork: None,
+            snapshots,
         })
     }
 
@@ -148,6 +263,258 @@ impl NodeManager {
     pub fn get_data_service_url(&self) -> Url {
         socket_addr_to_url(&self.config.indexer_grpc.address, "http").unwrap()
     }
+
+    /// Where to send requests to snapshot the node, if it can be snapshotted.
+    pub fn get_snapshot_request_sender(&self) -> Option<mpsc::Sender<SnapshotRequest>> {
+        self.snapshots
+            .as_ref()
+            .map(|snapshots| snapshots.request_sender.clone())
+    }
+
+    /// What the services that depend on the state of the node must be run with, so
+    /// they are restarted whenever the node is, if it can be snapshotted.
+    pub fn get_node_restarts(&self) -> Option<NodeRestarts> {
+        self.snapshots
+            .as_ref()
+            .map(|snapshots| snapshots.restarts.clone())
+    }
+
+    /// Runs the node in its own process, stopping it whenever a snapshot is requested
+    /// and starting it again once the snapshot is taken or reverted to. The node can't
+    /// be stopped if it runs in this process.
+    async fn run_node_process(self, mut snapshots: NodeSnapshots) -> Result<()> {
+        let health_checker = HealthChecker::NodeApi(self.get_node_api_url());
+        let clock = NodeClock::new(&self.config)?;
+        let restarts = snapshots.restarts.clone();
+        let mut pending_reply: Option<(
+            oneshot::Sender<Result<String>>,
+            Result<(u64, Duration)>,
+            RwLockWriteGuard<'_, ()>,
+        )> = None;
+        loop {
+            let mut child = spawn_node_process(
+                &snapshots.config_path,
+                &self.test_dir,
+                pending_reply.is_none(),
+            )?;
+
+            // Set the clock of the node again, start the services stopped with the node
+            // and reply to the request that stopped it once it is running again.
+            if let Some((reply, result, stopped)) = pending_reply.take() {
+                tokio::select! {
+                    status = child.wait() => {
+                        bail!("Node process exited unexpectedly after a snapshot: {:?}", status)
+                    },
+                    health = health_checker.wait(None) => {
+                        let result = match (health, result) {
+                            (Ok(()), Ok((id, clock_offset))) => {
+                                clock.advance(clock_offset).await.map(|()| id.to_string())
+                            },
+                            (Err(err), _) | (_, Err(err)) => Err(err),
+                        };
+                        restarts.start(stopped);
+                        let _ = reply.send(result);
+                    },
+                }
+            }
+
+            let request: SnapshotRequest = tokio::select! {
+                status = child.wait() => {
+                    bail!("Node process exited unexpectedly: {:?}", status)
+                },
+                request = snapshots.request_receiver.recv() => {
+                    request.context("Snapshot request channel closed")?
+                },
+            };
+
+            // The consensus clock of the node isn't persisted, so it is set again once
+            // the node is restarted.
+            let clock_offset = match clock.offset().await {
+                Ok(clock_offset) => clock_offset,
+                Err(err) => {
+                    let _ = request.reply.send(Err(err));
+                    continue;
+                },
+            };
+
+            // Stop the services that depend on the node first, so the processors don't
+            // write to the indexer DB while it is snapshotted.
+            let stopped = restarts.stop().await;
+            stop_node_process(&mut child).await?;
+            let result = snapshots.apply(request.action, clock_offset).await;
+            pending_reply = Some((request.reply, result, stopped));
+        }
+    }
+}
+
+impl NodeSnapshots {
+    /// Takes or reverts to a snapshot of the node and the indexer DB, returning the id
+    /// of the snapshot and how far the clock of the node must be moved ahead once it is
+    /// restarted. Neither the node nor the processors may be running.
+    async fn apply(
+        &self,
+        action: SnapshotAction,
+        clock_offset: Duration,
+    ) -> Result<(u64, Duration)> {
+        let snapshots = self.snapshots.clone();
+        match action {
+            SnapshotAction::Snapshot => {
+                // The indexer DB is snapshotted first, as the snapshot of the node is
+                // what makes the snapshot exist.
+                let id = snapshots.next_id()?;
+                if let Some(postgres_snapshots) = &self.postgres_snapshots {
+                    postgres_snapshots.take(id).await?;
+                }
+                tokio::task::spawn_blocking(move || snapshots.take(id, clock_offset)).await??;
+                Ok((id, clock_offset))
+            },
+            SnapshotAction::Revert(id) => {
+                snapshots.check_exists(id)?;
+                if let Some(postgres_snapshots) = &self.postgres_snapshots {
+                    postgres_snapshots.revert(id).await?;
+                }
+                let clock_offset =
+                    tokio::task::spawn_blocking(move || snapshots.revert(id)).await??;
+                Ok((id, clock_offset))
+            },
+        }
+    }
+}
+
+/// The consensus clock of a node run in its own process, set through its admin service.
+struct NodeClock {
+    admin_service_url: Url,
+}
+
+impl NodeClock {
+    fn new(config: &NodeConfig) -> Result<Self> {
+        let admin_service_url = Url::parse(&format!(
+            "http://{}:{}",
+            config.admin_service.address, config.admin_service.port
+        ))
+        .context("Failed to build the URL of the admin service")?;
+        Ok(Self { admin_service_url })
+    }
+
+    /// How far the clock has been moved ahead of the system clock.
+    async fn offset(&self) -> Result<Duration> {
+        let offset = reqwest::get(self.admin_service_url.join("debug/consensus/time/offset")?)
+            .await?
+            .error_for_status()
+            .context("Failed to get the clock of the node")?
+            .text()
+            .await?;
+        Ok(Duration::from_micros(offset.trim().parse()?))
+    }
+
+    async fn advance(&self, duration: Duration) -> Result<()> {
+        if duration.is_zero() {
+            return Ok(());
+        }
+        reqwest::Client::new()
+            .post(self.admin_service_url.join("debug/consensus/time")?)
+            .query(&[("advance_usecs", duration.as_micros().to_string())])
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to set the clock of the node")?;
+        Ok(())
+    }
+}
+
+fn spawn_node_process(config_path: &Path, test_dir: &Path, print_output: bool) -> Result<Child> {
+    let stdout = if print_output {
+        Stdio::inherit()
+    } else {
+        Stdio::null()
+    };
+    let mut command =
+        Command::new(std::env::current_exe().context("Failed to find the CLI executable")?);
+    // Keep ctrl-c from stopping the node before the localnet shuts down. The node is
+    // killed when the localnet exits.
+    #[cfg(unix)]
+    command.process_group(0);
+    command
+        .args(["node", "run-localnet-node", "--config-path"])
+        .arg(config_path)
+        .arg("--test-dir")
+        .arg(test_dir)
+        .stdout(stdout)
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start the node process")
+}
+
+/// Stops the node process, letting it close its DBs first so they are consistent when
+/// they are snapshotted. It is killed if it doesn't stop in time.
+async fn stop_node_process(child: &mut Child) -> Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: The child hasn't been waited for, so its pid can't have been reused.
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        match tokio::time::timeout(NODE_STOP_TIMEOUT, child.wait()).await {
+            Ok(status) => {
+                status.context("Failed to stop the node process")?;
+                return Ok(());
+            },
+            Err(_) => eprintln!(
+                "The node process didn't stop within {:?}, killing it",
+                NODE_STOP_TIMEOUT
+            ),
+        }
+    }
+    child
+        .kill()
+        .await
+        .context("Failed to stop the node process")
+}
+
+/// Run the node of a localnet that can be snapshotted
+///
+/// This is run by `aptos node run-localnet --with-snapshots` in its own process.
+#[derive(Debug, Parser)]
+pub struct RunLocalnetNode {
+    /// The config of the node
+    #[clap(long, value_parser)]
+    config_path: PathBuf,
+
+    /// The test directory of the localnet
+    #[clap(long, value_parser)]
+    test_dir: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<()> for RunLocalnetNode {
+    fn command_name(&self) -> &'static str {
+        "RunLocalnetNode"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let config = NodeConfig::load_from_path(&self.config_path)?;
+
+        // The localnet asks the node to stop with SIGTERM before it snapshots the data
+        // of the node, so the node stops gracefully then.
+        let term = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        {
+            let mut sigterm =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            let term = term.clone();
+            tokio::spawn(async move {
+                sigterm.recv().await;
+                term.store(true, Ordering::Release);
+            });
+        }
+        tokio::task::spawn_blocking(move || {
+            start_test_environment_node_until(config, self.test_dir, false, term)
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))??;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -176,6 +543,15 @@ impl ServiceManager for NodeManager {
         hashset! {}
     }
 
+    fn get_shutdown_steps(&self) -> Vec<Box<dyn ShutdownStep>> {
+        match &self.fork {
+            Some(fork) if fork.state().is_remote() => vec![Box::new(
+                SaveForkFixtureShutdownStep::new(fork.clone(), &self.test_dir),
+            )],
+            _ => vec![],
+        }
+    }
+
     /// Spawn the node on a thread and then create a future that just waits for it to
     /// exit (which should never happen) forever. This is necessary because there is
     /// no async function we can use to run the node.
@@ -187,8 +563,16 @@ impl ServiceManager for NodeManager {
             }
         }
 
+        let mut manager = *self;
+        if let Some(snapshots) = manager.snapshots.take() {
+            return manager.run_node_process(snapshots).await;
+        }
+
         let node_thread_handle = thread::spawn(move || {
-            let result = start_test_environment_node(self.config, self.test_dir, false);
+            let result = match manager.fork {
+                Some(fork) => run_fork_node(manager.config, fork),
+                None => start_test_environment_node(manager.config, manager.test_dir, false),
+            };
             eprintln!("Node stopped unexpectedly {:#?}", result);
         });
 
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
+use super::{
+    health_checker::HealthChecker, snapshot::NodeRestarts, traits::ServiceManager, RunLocalnet,
+};
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use clap::Parser;
@@ -57,6 +59,9 @@ pub struct ProcessorArgs {
 pub struct ProcessorManager {
     config: IndexerGrpcProcessorConfig,
     prerequisite_health_checkers: HashSet<HealthChecker>,
+    /// If the node can be snapshotted, the processor is stopped while the node is,
+    /// so the indexer DB can be snapshotted too.
+    node_restarts: Option<NodeRestarts>,
 }
 
 impl ProcessorManager {
@@ -65,6 +70,7 @@ impl ProcessorManager {
         prerequisite_health_checkers: HashSet<HealthChecker>,
         data_service_url: Url,
         postgres_connection_string: String,
+        node_restarts: Option<NodeRestarts>,
     ) -> Result<Self> {
         let processor_config = match processor_name {
             ProcessorName::AccountTransactionsProcessor => {
@@ -136,6 +142,7 @@ impl ProcessorManager {
         let manager = Self {
             config,
             prerequisite_health_checkers,
+            node_restarts,
         };
         Ok(manager)
     }
@@ -146,6 +153,7 @@ impl ProcessorManager {
         prerequisite_health_checkers: HashSet<HealthChecker>,
         data_service_url: Url,
         postgres_connection_string: String,
+        node_restarts: Option<NodeRestarts>,
     ) -> Result<Vec<Self>> {
         if args.processor_args.processors.is_empty() {
             bail!("Must specify at least one processor to run");
@@ -157,6 +165,7 @@ impl ProcessorManager {
                 prerequisite_health_checkers.clone(),
                 data_service_url.clone(),
                 postgres_connection_string.clone(),
+                node_restarts.clone(),
             )?);
         }
         Ok(managers)
@@ -221,6 +230,16 @@ impl ServiceManager for ProcessorManager {
             .await;
 
         // Run the processor.
-        self.config.run().await
+        match &self.node_restarts {
+            Some(node_restarts) => {
+                node_restarts
+                    .run_service(&self.get_name(), || {
+                        let config = self.config.clone();
+                        async move { config.run().await }
+                    })
+                    .await
+            },
+            None => self.config.run().await,
+        }
     }
 }
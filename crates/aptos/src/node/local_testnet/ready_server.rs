@@ -1,7 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
+use super::{
+    health_checker::HealthChecker,
+    snapshot::{SnapshotAction, SnapshotRequest, SnapshotResponse},
+    traits::ServiceManager,
+    RunLocalnet,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use clap::Parser;
@@ -11,7 +16,8 @@ use poem::{
     http::StatusCode,
     listener::TcpListener,
     middleware::Tracing,
-    web::{Data, Json},
+    post,
+    web::{Data, Json, Path},
     EndpointExt, IntoResponse, Route, Server,
 };
 use serde::Serialize;
@@ -20,11 +26,15 @@ use std::{
     net::{Ipv4Addr, SocketAddrV4},
     time::Duration,
 };
-use tokio::time::timeout;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::timeout,
+};
 
 /// Args related to running a ready server in the localnet. The ready server lets
 /// users / clients check that if all the services in the localnet are ready
-/// without having to ping each service individually.
+/// without having to ping each service individually. If the localnet can be
+/// snapshotted, it also takes the requests to snapshot and revert it.
 #[derive(Debug, Clone, Parser)]
 pub struct ReadyServerArgs {
     /// The port to run the ready server. This exposes an endpoint at `/` that you can
@@ -38,6 +48,7 @@ pub struct ReadyServerManager {
     config: ReadyServerArgs,
     bind_to: Ipv4Addr,
     health_checkers: HashSet<HealthChecker>,
+    snapshot_request_sender: Option<mpsc::Sender<SnapshotRequest>>,
 }
 
 impl ReadyServerManager {
@@ -45,11 +56,13 @@ impl ReadyServerManager {
         args: &RunLocalnet,
         bind_to: Ipv4Addr,
         health_checkers: HashSet<HealthChecker>,
+        snapshot_request_sender: Option<mpsc::Sender<SnapshotRequest>>,
     ) -> Result<Self> {
         Ok(ReadyServerManager {
             config: args.ready_server_args.clone(),
             bind_to,
             health_checkers,
+            snapshot_request_sender,
         })
     }
 }
@@ -71,7 +84,13 @@ impl ServiceManager for ReadyServerManager {
     }
 
     async fn run_service(self: Box<ReadyServerManager>) -> Result<()> {
-        run_ready_server(self.health_checkers, self.config, self.bind_to).await
+        run_ready_server(
+            self.health_checkers,
+            self.snapshot_request_sender,
+            self.config,
+            self.bind_to,
+        )
+        .await
     }
 }
 
@@ -79,12 +98,18 @@ impl ServiceManager for ReadyServerManager {
 /// checking port. Clients can use this to check if all the services are ready.
 pub async fn run_ready_server(
     health_checkers: HashSet<HealthChecker>,
+    snapshot_request_sender: Option<mpsc::Sender<SnapshotRequest>>,
     config: ReadyServerArgs,
     bind_to: Ipv4Addr,
 ) -> Result<()> {
     let app = Route::new()
         .at("/", get(root))
+        .at("/snapshot", post(snapshot))
+        .at("/revert/:snapshot_id", post(revert))
         .data(HealthCheckers { health_checkers })
+        .data(SnapshotRequestSender {
+            sender: snapshot_request_sender,
+        })
         .with(Tracing);
     Server::new(TcpListener::bind(SocketAddrV4::new(
         bind_to,
@@ -129,3 +154,70 @@ async fn root(health_checkers: Data<&HealthCheckers>) -> impl IntoResponse {
     };
     Json(ReadyData { ready, not_ready }).with_status(status_code)
 }
+
+#[derive(Clone, Debug)]
+struct SnapshotRequestSender {
+    pub sender: Option<mpsc::Sender<SnapshotRequest>>,
+}
+
+impl SnapshotRequestSender {
+    /// Asks the node to take or revert to a snapshot, waiting until it and the services
+    /// restarted with it are ready again.
+    async fn send(
+        &self,
+        action: SnapshotAction,
+        health_checkers: &HealthCheckers,
+    ) -> poem::Result<Json<SnapshotResponse>> {
+        let sender = self.sender.as_ref().ok_or_else(|| {
+            poem::Error::from_string(
+                "Snapshots are not enabled, run the localnet with --with-snapshots",
+                StatusCode::NOT_FOUND,
+            )
+        })?;
+        let (reply, receiver) = oneshot::channel();
+        sender
+            .send(SnapshotRequest { action, reply })
+            .await
+            .map_err(|_| {
+                poem::Error::from_string("The node is not running", StatusCode::SERVICE_UNAVAILABLE)
+            })?;
+        let snapshot_id = receiver
+            .await
+            .map_err(|_| {
+                poem::Error::from_string("The node is not running", StatusCode::SERVICE_UNAVAILABLE)
+            })?
+            .map_err(|err| {
+                poem::Error::from_string(format!("{:#}", err), StatusCode::INTERNAL_SERVER_ERROR)
+            })?;
+        for health_checker in &health_checkers.health_checkers {
+            health_checker.wait(None).await.map_err(|err| {
+                poem::Error::from_string(
+                    format!("{:#}", err),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )
+            })?;
+        }
+        Ok(Json(SnapshotResponse { snapshot_id }))
+    }
+}
+
+#[handler]
+async fn snapshot(
+    sender: Data<&SnapshotRequestSender>,
+    health_checkers: Data<&HealthCheckers>,
+) -> poem::Result<Json<SnapshotResponse>> {
+    sender
+        .send(SnapshotAction::Snapshot, &health_checkers)
+        .await
+}
+
+#[handler]
+async fn revert(
+    sender: Data<&SnapshotRequestSender>,
+    health_checkers: Data<&HealthCheckers>,
+    Path(snapshot_id): Path<u64>,
+) -> poem::Result<Json<SnapshotResponse>> {
+    sender
+        .send(SnapshotAction::Revert(snapshot_id), &health_checkers)
+        .await
+}
@@ -0,0 +1,689 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::block_production::{
+    AdvanceEpochLocalnet, AdvanceTimeLocalnet, MineLocalnet, PauseLocalnet, ResumeLocalnet,
+    SetTimeLocalnet,
+};
+use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult};
+use anyhow::{bail, Context, Result};
+use aptos_config::config::{NodeConfig, SecureBackend};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use diesel_async::{pg::AsyncPgConnection, AsyncConnection, RunQueryDsl};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::{oneshot, watch, RwLock, RwLockWriteGuard},
+    task::JoinHandle,
+};
+use tracing::info;
+
+/// The folder in the test directory that snapshots of the localnet are saved to.
+pub const SNAPSHOTS_FOLDER: &str = "snapshots";
+
+const SNAPSHOT_DB_FOLDER: &str = "db";
+const SNAPSHOT_SECURE_STORAGE_FILE: &str = "secure-data.json";
+const SNAPSHOT_CLOCK_OFFSET_FILE: &str = "clock-offset-usecs";
+
+/// How long a service that is stopped while the node is snapshotted gets to finish
+/// its tasks before they are dropped.
+const SERVICE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tool for controlling a running localnet
+#[derive(Subcommand)]
+pub enum LocalnetTool {
+    Snapshot(SnapshotLocalnet),
+    Revert(RevertLocalnet),
+    Pause(PauseLocalnet),
+    Resume(ResumeLocalnet),
+    Mine(MineLocalnet),
+    AdvanceTime(AdvanceTimeLocalnet),
+    SetTime(SetTimeLocalnet),
+    AdvanceEpoch(AdvanceEpochLocalnet),
+}
+
+impl LocalnetTool {
+    pub async fn execute(self) -> CliResult {
+        use LocalnetTool::*;
+        match self {
+            Snapshot(tool) => tool.execute_serialized().await,
+            Revert(tool) => tool.execute_serialized().await,
+            Pause(tool) => tool.execute_serialized().await,
+            Resume(tool) => tool.execute_serialized().await,
+            Mine(tool) => tool.execute_serialized().await,
+            AdvanceTime(tool) => tool.execute_serialized().await,
+            SetTime(tool) => tool.execute_serialized().await,
+            AdvanceEpoch(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct LocalnetOptions {
+    /// URL of the ready server of the localnet
+    #[clap(long, default_value = "http://127.0.0.1:8070")]
+    pub(crate) ready_server_url: Url,
+}
+
+impl LocalnetOptions {
+    /// Sends a snapshot request to the localnet, returning the id of the snapshot
+    /// taken or reverted to.
+    async fn request(&self, path: &str) -> CliTypedResult<String> {
+        let url = self
+            .ready_server_url
+            .join(path)
+            .map_err(|err| CliError::UnableToParse("ready-server-url", err.to_string()))?;
+        let response = reqwest::Client::new()
+            .post(url)
+            .send()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let message = response.text().await.unwrap_or_default();
+            return Err(CliError::ApiError(format!("{}: {}", status, message)));
+        }
+        let response: SnapshotResponse = response
+            .json()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        Ok(response.snapshot_id)
+    }
+}
+
+/// Snapshot the state of a running localnet
+///
+/// The localnet must have been started with --with-snapshots. The node, the faucet
+/// and the indexer processors are stopped while the data of the node and the indexer
+/// DB are saved, and the command returns the id of the snapshot once the localnet is
+/// ready again. The id can be passed to `aptos node localnet revert`.
+#[derive(Debug, Parser)]
+pub struct SnapshotLocalnet {
+    #[clap(flatten)]
+    pub(crate) localnet_options: LocalnetOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for SnapshotLocalnet {
+    fn command_name(&self) -> &'static str {
+        "SnapshotLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        self.localnet_options.request("snapshot").await
+    }
+}
+
+/// Revert a running localnet to a snapshot
+///
+/// The node is restarted from the snapshot and the indexer DB is restored, after which
+/// the faucet and the indexer processors are restarted. Clients of the txn stream must
+/// reconnect. A snapshot can be reverted to any number of times.
+#[derive(Debug, Parser)]
+pub struct RevertLocalnet {
+    /// Id of the snapshot, as returned by `aptos node localnet snapshot`
+    pub(crate) snapshot_id: u64,
+
+    #[clap(flatten)]
+    pub(crate) localnet_options: LocalnetOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for RevertLocalnet {
+    fn command_name(&self) -> &'static str {
+        "RevertLocalnet"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        self.localnet_options
+            .request(&format!("revert/{}", self.snapshot_id))
+            .await
+    }
+}
+
+/// The response of the ready server to a snapshot request.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SnapshotResponse {
+    pub snapshot_id: String,
+}
+
+#[derive(Debug)]
+pub enum SnapshotAction {
+    Snapshot,
+    Revert(u64),
+}
+
+/// A request for the node of the localnet to take a snapshot or revert to one. The
+/// id of the snapshot is sent back once the node and the services stopped with it
+/// have been started again.
+#[derive(Debug)]
+pub struct SnapshotRequest {
+    pub action: SnapshotAction,
+    pub reply: oneshot::Sender<Result<String>>,
+}
+
+/// The snapshots of the data of a node, saved in the test directory.
+#[derive(Clone, Debug)]
+pub struct Snapshots {
+    dir: PathBuf,
+    storage_dir: PathBuf,
+    secure_storage_path: Option<PathBuf>,
+    enable_storage_sharding: bool,
+}
+
+impl Snapshots {
+    pub fn new(config: &NodeConfig, test_dir: &Path) -> Self {
+        let secure_storage_path = match &config.consensus.safety_rules.backend {
+            SecureBackend::OnDiskStorage(storage) => Some(storage.path()),
+            _ => None,
+        };
+        Self {
+            dir: test_dir.join(SNAPSHOTS_FOLDER),
+            storage_dir: config.storage.dir(),
+            secure_storage_path,
+            enable_storage_sharding: config.storage.rocksdb_configs.enable_storage_sharding,
+        }
+    }
+
+    /// The id the next snapshot will be saved with.
+    pub fn next_id(&self) -> Result<u64> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        Ok(self.ids()?.into_iter().max().unwrap_or(0) + 1)
+    }
+
+    /// Snapshots the data of the node with the given id, see `next_id`, along with how
+    /// far its consensus clock was moved ahead, which isn't part of its data. The node
+    /// must not be running.
+    pub fn take(&self, id: u64, clock_offset: Duration) -> Result<()> {
+        // Build the snapshot in a temporary folder so a failed snapshot is never used.
+        let tmp_dir = self.dir.join(format!("{}.tmp", id));
+        if tmp_dir.exists() {
+            fs::remove_dir_all(&tmp_dir)?;
+        }
+        let db_dir = tmp_dir.join(SNAPSHOT_DB_FOLDER);
+        aptos_node::create_rocksdb_checkpoint(
+            &self.storage_dir,
+            &db_dir,
+            self.enable_storage_sharding,
+        )?;
+        // Copy the DBs there is no checkpoint for, e.g. the quorum store and indexer DBs.
+        for entry in fs::read_dir(&self.storage_dir)? {
+            let entry = entry?;
+            let target = db_dir.join(entry.file_name());
+            if !target.exists() {
+                copy_recursively(&entry.path(), &target)?;
+            }
+        }
+        // The safety rules must agree with the consensus DB, or the node would refuse
+        // to vote until it got back to the round it was at.
+        if let Some(path) = &self.secure_storage_path {
+            fs::copy(path, tmp_dir.join(SNAPSHOT_SECURE_STORAGE_FILE))
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+        }
+
+        fs::write(
+            tmp_dir.join(SNAPSHOT_CLOCK_OFFSET_FILE),
+            clock_offset.as_micros().to_string(),
+        )?;
+
+        fs::rename(&tmp_dir, self.dir.join(id.to_string()))?;
+        Ok(())
+    }
+
+    /// Fails if there is no snapshot with the given id.
+    pub fn check_exists(&self, id: u64) -> Result<()> {
+        if !self.dir.join(id.to_string()).is_dir() {
+            bail!("There is no snapshot {}", id);
+        }
+        Ok(())
+    }
+
+    /// Replaces the data of the node with a snapshot, returning how far the consensus
+    /// clock of the node was moved ahead when the snapshot was taken. The node must not
+    /// be running.
+    pub fn revert(&self, id: u64) -> Result<Duration> {
+        self.check_exists(id)?;
+        let snapshot_dir = self.dir.join(id.to_string());
+        let clock_offset = fs::read_to_string(snapshot_dir.join(SNAPSHOT_CLOCK_OFFSET_FILE))
+            .context("Failed to read the clock offset of the snapshot")?;
+        let clock_offset = Duration::from_micros(clock_offset.trim().parse()?);
+
+        // The snapshot is copied rather than moved so it can be reverted to again. It is
+        // copied next to the data of the node first, so a failed copy leaves the data of
+        // the node as it is.
+        let tmp_dir = with_suffix(&self.storage_dir, "reverting");
+        if tmp_dir.exists() {
+            fs::remove_dir_all(&tmp_dir)?;
+        }
+        copy_recursively(&snapshot_dir.join(SNAPSHOT_DB_FOLDER), &tmp_dir)?;
+        let secure_storage_paths = match &self.secure_storage_path {
+            Some(path) => {
+                let tmp_path = with_suffix(path, "reverting");
+                fs::copy(snapshot_dir.join(SNAPSHOT_SECURE_STORAGE_FILE), &tmp_path)
+                    .with_context(|| format!("Failed to restore {}", path.display()))?;
+                Some((tmp_path, path))
+            },
+            None => None,
+        };
+
+        // A directory can't be renamed over another one, so the data of the node is
+        // moved out of the way and deleted once the snapshot is in its place.
+        let old_dir = with_suffix(&self.storage_dir, "old");
+        if old_dir.exists() {
+            fs::remove_dir_all(&old_dir)?;
+        }
+        if self.storage_dir.exists() {
+            fs::rename(&self.storage_dir, &old_dir)?;
+        }
+        fs::rename(&tmp_dir, &self.storage_dir)?;
+        if let Some((tmp_path, path)) = secure_storage_paths {
+            fs::rename(tmp_path, path)?;
+        }
+        if old_dir.exists() {
+            fs::remove_dir_all(&old_dir)
+                .with_context(|| format!("Failed to delete {}", old_dir.display()))?;
+        }
+        Ok(clock_offset)
+    }
+
+    fn ids(&self) -> Result<Vec<u64>> {
+        let mut ids = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            if let Ok(id) = entry?.file_name().to_string_lossy().parse() {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// The snapshots of the indexer DB, saved as copies of the DB in the same postgres.
+#[derive(Clone, Debug)]
+pub struct PostgresSnapshots {
+    /// A connection string for the `postgres` DB, since a DB can't be copied or
+    /// dropped while connected to it.
+    maintenance_connection_string: String,
+    database: String,
+}
+
+impl PostgresSnapshots {
+    pub fn new(maintenance_connection_string: String, database: String) -> Self {
+        Self {
+            maintenance_connection_string,
+            database,
+        }
+    }
+
+    fn snapshot_database(&self, id: u64) -> String {
+        format!("{}_snapshot_{}", self.database, id)
+    }
+
+    /// Copies the indexer DB to a DB for the snapshot with the given id. The
+    /// processors must not be running.
+    pub async fn take(&self, id: u64) -> Result<()> {
+        let mut connection = self.connect().await?;
+        let snapshot_database = self.snapshot_database(id);
+        execute(
+            &mut connection,
+            format!("DROP DATABASE IF EXISTS {}", snapshot_database),
+        )
+        .await?;
+        // A DB can only be copied while nothing else is connected to it, e.g. the
+        // indexer API, which reconnects when it is next queried.
+        terminate_connections(&mut connection, &self.database).await?;
+        execute(
+            &mut connection,
+            format!(
+                "CREATE DATABASE {} TEMPLATE {}",
+                snapshot_database, self.database
+            ),
+        )
+        .await?;
+        info!("Copied database {} to {}", self.database, snapshot_database);
+        Ok(())
+    }
+
+    /// Replaces the indexer DB with a copy of the DB of the snapshot with the given id.
+    /// The processors must not be running.
+    pub async fn revert(&self, id: u64) -> Result<()> {
+        let mut connection = self.connect().await?;
+        let snapshot_database = self.snapshot_database(id);
+        let reverted_database = format!("{}_reverted", self.database);
+
+        // Copy the snapshot before dropping the DB, so the DB is left as it is if there
+        // is no snapshot to copy.
+        execute(
+            &mut connection,
+            format!("DROP DATABASE IF EXISTS {}", reverted_database),
+        )
+        .await?;
+        execute(
+            &mut connection,
+            format!(
+                "CREATE DATABASE {} TEMPLATE {}",
+                reverted_database, snapshot_database
+            ),
+        )
+        .await
+        .with_context(|| format!("There is no indexer DB for snapshot {}", id))?;
+        terminate_connections(&mut connection, &self.database).await?;
+        execute(&mut connection, format!("DROP DATABASE {}", self.database)).await?;
+        execute(
+            &mut connection,
+            format!(
+                "ALTER DATABASE {} RENAME TO {}",
+                reverted_database, self.database
+            ),
+        )
+        .await?;
+        info!(
+            "Reverted database {} to {}",
+            self.database, snapshot_database
+        );
+        Ok(())
+    }
+
+    async fn connect(&self) -> Result<AsyncPgConnection> {
+        AsyncPgConnection::establish(&self.maintenance_connection_string)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to connect to postgres at {}",
+                    self.maintenance_connection_string
+                )
+            })
+    }
+}
+
+async fn execute(connection: &mut AsyncPgConnection, query: String) -> Result<()> {
+    diesel::sql_query(&query)
+        .execute(connection)
+        .await
+        .with_context(|| format!("Failed to run `{}`", query))?;
+    Ok(())
+}
+
+async fn terminate_connections(connection: &mut AsyncPgConnection, database: &str) -> Result<()> {
+    execute(
+        connection,
+        format!(
+            "SELECT pg_terminate_backend(pid) FROM pg_stat_activity \
+             WHERE datname = '{}' AND pid <> pg_backend_pid()",
+            database
+        ),
+    )
+    .await
+}
+
+/// Lets the services that depend on the state of the node, e.g. the faucet and the
+/// processors, be stopped while the node is snapshotted or reverted, and started
+/// again once the node is running, so they pick up its state as it is then.
+#[derive(Clone, Debug)]
+pub struct NodeRestarts {
+    node_running: Arc<watch::Sender<bool>>,
+    services: Arc<RwLock<()>>,
+}
+
+impl NodeRestarts {
+    pub fn new() -> Self {
+        Self {
+            node_running: Arc::new(watch::Sender::new(true)),
+            services: Arc::new(RwLock::new(())),
+        }
+    }
+
+    /// Stops the services, returning once all of them have stopped. They are started
+    /// again when the returned guard is passed to `start`.
+    pub async fn stop(&self) -> RwLockWriteGuard<'_, ()> {
+        self.node_running.send_replace(false);
+        self.services.write().await
+    }
+
+    /// Starts the services stopped by `stop`.
+    pub fn start(&self, stopped: RwLockWriteGuard<'_, ()>) {
+        self.node_running.send_replace(true);
+        drop(stopped);
+    }
+
+    /// Runs a service on a runtime of its own, shutting the runtime down whenever the
+    /// node is stopped and running the service again once the node is back. Shutting
+    /// the runtime down also stops any tasks the service spawned.
+    pub async fn run_service<F, Fut>(&self, name: &str, run: F) -> Result<()>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let mut node_running = self.node_running.subscribe();
+        loop {
+            node_running.wait_for(|running| *running).await?;
+            let _running = self.services.read().await;
+            if !*node_running.borrow_and_update() {
+                continue;
+            }
+
+            let mut runtime = ServiceRuntime(Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .thread_name(name)
+                    .enable_all()
+                    .build()
+                    .with_context(|| format!("Failed to create a runtime for {}", name))?,
+            ));
+            let mut service = runtime.spawn(run());
+            let result = tokio::select! {
+                result = &mut service => Some(result),
+                _ = node_running.wait_for(|running| !*running) => None,
+            };
+            runtime.shutdown().await?;
+            match result {
+                Some(result) => {
+                    return result.with_context(|| format!("{} panicked", name))?;
+                },
+                None => info!("Stopped {} while the node is stopped", name),
+            }
+        }
+    }
+}
+
+/// The runtime of a service run by `NodeRestarts`. A runtime can't be dropped in an
+/// async context, so if the service is dropped while running, e.g. when the localnet
+/// exits, the runtime is shut down without waiting for its tasks.
+struct ServiceRuntime(Option<Runtime>);
+
+impl ServiceRuntime {
+    fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.0
+            .as_ref()
+            .expect("Runtime was shut down")
+            .spawn(future)
+    }
+
+    /// Shuts the runtime down, giving its tasks some time to finish.
+    async fn shutdown(&mut self) -> Result<()> {
+        if let Some(runtime) = self.0.take() {
+            tokio::task::spawn_blocking(move || runtime.shutdown_timeout(SERVICE_SHUTDOWN_TIMEOUT))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ServiceRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl Default for NodeRestarts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The path with `.<suffix>` appended to its file name, which is in the same directory
+/// so it can be renamed to the path.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}", suffix));
+    path.with_file_name(file_name)
+}
+
+fn copy_recursively(source: &Path, target: &Path) -> Result<()> {
+    if source.is_dir() {
+        fs::create_dir_all(target)
+            .with_context(|| format!("Failed to create {}", target.display()))?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(source, target).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                source.display(),
+                target.display()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_take_and_revert() {
+        let test_dir = TempPath::new();
+        test_dir.create_as_dir().unwrap();
+        let storage_dir = test_dir.path().join("db");
+        let quorum_store_file = storage_dir.join("quorumstoreDB").join("data");
+        let secure_storage_path = test_dir.path().join("secure-data.json");
+        fs::create_dir_all(quorum_store_file.parent().unwrap()).unwrap();
+        fs::write(&quorum_store_file, "1").unwrap();
+        fs::write(&secure_storage_path, "1").unwrap();
+        let snapshots = Snapshots {
+            dir: test_dir.path().join(SNAPSHOTS_FOLDER),
+            storage_dir: storage_dir.clone(),
+            secure_storage_path: Some(secure_storage_path.clone()),
+            enable_storage_sharding: false,
+        };
+
+        let id = snapshots.next_id().unwrap();
+        assert_eq!(id, 1);
+        snapshots.take(id, Duration::from_secs(60)).unwrap();
+        assert_eq!(snapshots.next_id().unwrap(), 2);
+
+        // Reverting restores the data of the node as it was when the snapshot was taken,
+        // including the files there are no checkpoints for.
+        fs::write(&quorum_store_file, "2").unwrap();
+        fs::write(storage_dir.join("new-file"), "2").unwrap();
+        fs::write(&secure_storage_path, "2").unwrap();
+        assert_eq!(snapshots.revert(id).unwrap(), Duration::from_secs(60));
+        assert_eq!(fs::read_to_string(&quorum_store_file).unwrap(), "1");
+        assert!(!storage_dir.join("new-file").exists());
+        assert_eq!(fs::read_to_string(&secure_storage_path).unwrap(), "1");
+
+        // A snapshot can be reverted to again.
+        fs::write(&secure_storage_path, "3").unwrap();
+        snapshots.revert(id).unwrap();
+        assert_eq!(fs::read_to_string(&secure_storage_path).unwrap(), "1");
+
+        assert!(snapshots.revert(2).is_err());
+
+        // A revert that fails leaves the data of the node as it is.
+        fs::write(&quorum_store_file, "4").unwrap();
+        fs::write(&secure_storage_path, "4").unwrap();
+        fs::remove_dir_all(snapshots.dir.join("1").join(SNAPSHOT_DB_FOLDER)).unwrap();
+        assert!(snapshots.revert(id).is_err());
+        assert_eq!(fs::read_to_string(&quorum_store_file).unwrap(), "4");
+        assert_eq!(fs::read_to_string(&secure_storage_path).unwrap(), "4");
+    }
+
+    /// Counts the running instances of a service, an instance stops running when the
+    /// runtime it runs on is shut down.
+    struct Running(Arc<AtomicUsize>);
+
+    impl Running {
+        fn new(count: &Arc<AtomicUsize>) -> Self {
+            count.fetch_add(1, Ordering::SeqCst);
+            Self(count.clone())
+        }
+    }
+
+    impl Drop for Running {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_services_restart_with_node() {
+        let restarts = NodeRestarts::new();
+        let running = Arc::new(AtomicUsize::new(0));
+        let (started_sender, mut started) = mpsc::unbounded_channel();
+        let service = tokio::spawn({
+            let restarts = restarts.clone();
+            let running = running.clone();
+            async move {
+                restarts
+                    .run_service("test-service", move || {
+                        let running = Running::new(&running);
+                        let started_sender = started_sender.clone();
+                        async move {
+                            let _running = running;
+                            started_sender.send(()).unwrap();
+                            std::future::pending::<()>().await;
+                            Ok(())
+                        }
+                    })
+                    .await
+            }
+        });
+        started.recv().await.unwrap();
+        assert_eq!(running.load(Ordering::SeqCst), 1);
+
+        // Stopping the node returns once the service has stopped, and the service isn't
+        // started again until the node is.
+        let stopped = restarts.stop().await;
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(started.try_recv().is_err());
+
+        restarts.start(stopped);
+        started.recv().await.unwrap();
+        assert_eq!(running.load(Ordering::SeqCst), 1);
+        assert!(!service.is_finished());
+
+        // Leave the service stopped, so its runtime isn't dropped with the test.
+        let _stopped = restarts.stop().await;
+        service.abort();
+    }
+
+    #[tokio::test]
+    async fn test_service_failure_is_returned() {
+        let restarts = NodeRestarts::new();
+        let result = restarts
+            .run_service("test-service", || async { bail!("Service failed") })
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "Service failed");
+    }
+}
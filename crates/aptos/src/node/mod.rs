@@ -4,7 +4,7 @@
 pub mod analyze;
 pub mod local_testnet;
 
-use self::local_testnet::RunLocalnet;
+use self::local_testnet::{LocalnetTool, RunLocalnet, RunLocalnetNode};
 use crate::{
     common::{
         types::{
@@ -72,12 +72,16 @@ pub enum NodeTool {
     InitializeValidator(InitializeValidator),
     JoinValidatorSet(JoinValidatorSet),
     LeaveValidatorSet(LeaveValidatorSet),
+    #[clap(subcommand)]
+    Localnet(LocalnetTool),
     ShowEpochInfo(ShowEpochInfo),
     ShowValidatorConfig(ShowValidatorConfig),
     ShowValidatorSet(ShowValidatorSet),
     ShowValidatorStake(ShowValidatorStake),
     #[clap(aliases = &["run-local-testnet"])]
     RunLocalnet(RunLocalnet),
+    #[clap(hide = true)]
+    RunLocalnetNode(RunLocalnetNode),
     UpdateConsensusKey(UpdateConsensusKey),
     UpdateValidatorNetworkAddresses(UpdateValidatorNetworkAddresses),
 }
@@ -97,6 +101,7 @@ impl NodeTool {
             InitializeValidator(tool) => tool.execute_serialized().await,
             JoinValidatorSet(tool) => tool.execute_serialized().await,
             LeaveValidatorSet(tool) => tool.execute_serialized().await,
+            Localnet(tool) => tool.execute().await,
             ShowEpochInfo(tool) => tool.execute_serialized().await,
             ShowValidatorSet(tool) => tool.execute_serialized().await,
             ShowValidatorStake(tool) => tool.execute_serialized().await,
@@ -105,6 +110,10 @@ impl NodeTool {
                 .execute_serialized_without_logger()
                 .await
                 .map(|_| "".to_string()),
+            RunLocalnetNode(tool) => tool
+                .execute_serialized_without_logger()
+                .await
+                .map(|_| "".to_string()),
             UpdateConsensusKey(tool) => tool.execute_serialized().await,
             UpdateValidatorNetworkAddresses(tool) => tool.execute_serialized().await,
         }
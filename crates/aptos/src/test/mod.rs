@@ -42,6 +42,12 @@ use crate::{
         AddStake, IncreaseLockup, InitializeStakeOwner, SetDelegatedVoter, SetOperator,
         UnlockStake, WithdrawStake,
     },
+    txn::{
+        build::{BuildTransaction, DEFAULT_OFFLINE_EXPIRATION_SECS},
+        sign::SignTransaction,
+        submit::SubmitTransaction,
+        OfflineTransactionSummary,
+    },
     CliCommand,
 };
 use aptos_config::config::Peer;
@@ -302,6 +308,100 @@ impl CliTestFramework {
         .await
     }
 
+    pub async fn transfer_coins_with_fee_payer_transaction(
+        &self,
+        sender_index: usize,
+        receiver_index: usize,
+        amount: u64,
+        fee_payer_transaction_file: PathBuf,
+    ) -> CliTypedResult<TransferSummary> {
+        let mut txn_options = self.transaction_options(sender_index, None);
+        txn_options.fee_payer_options.fee_payer_transaction_file = Some(fee_payer_transaction_file);
+        TransferCoins {
+            txn_options,
+            account: self.account_id(receiver_index),
+            amount,
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn build_transfer_transaction(
+        &self,
+        sender_index: usize,
+        receiver_index: usize,
+        amount: u64,
+        fee_payer_index: Option<usize>,
+        output_file: PathBuf,
+    ) -> CliTypedResult<OfflineTransactionSummary> {
+        BuildTransaction {
+            entry_function_args: EntryFunctionArguments {
+                function_id: Some(MemberId {
+                    module_id: ModuleId::new(
+                        AccountAddress::ONE,
+                        ident_str!("aptos_account").into(),
+                    ),
+                    member_id: ident_str!("transfer").into(),
+                }),
+                arg_vec: ArgWithTypeVec {
+                    args: vec![
+                        ArgWithType::address(self.account_id(receiver_index)),
+                        ArgWithType::u64(amount),
+                    ],
+                },
+                type_arg_vec: TypeArgVec { type_args: vec![] },
+                json_file: None,
+            },
+            sender_address: self.account_id(sender_index),
+            sequence_number: None,
+            secondary_signer_addresses: vec![],
+            fee_payer_address: fee_payer_index.map(|index| self.account_id(index)),
+            gas_unit_price: None,
+            max_gas: 10_000,
+            expiration_secs: DEFAULT_OFFLINE_EXPIRATION_SECS,
+            output_file,
+            rest_options: self.rest_options(),
+            profile_options: Default::default(),
+            prompt_options: PromptOptions::yes(),
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn sign_transaction(
+        &self,
+        signer_index: usize,
+        transaction_file: PathBuf,
+    ) -> CliTypedResult<OfflineTransactionSummary> {
+        SignTransaction {
+            transaction_file,
+            output_file: None,
+            signer_address: Some(self.account_id(signer_index)),
+            private_key_options: PrivateKeyInputOptions::from_private_key(
+                self.private_key(signer_index),
+            )?,
+            encoding_options: Default::default(),
+            profile_options: Default::default(),
+            prompt_options: PromptOptions::yes(),
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn submit_signed_transaction(
+        &self,
+        transaction_file: PathBuf,
+    ) -> CliTypedResult<TransactionSummary> {
+        SubmitTransaction {
+            transaction_file,
+            rest_options: self.rest_options(),
+            profile_options: Default::default(),
+            prompt_options: PromptOptions::yes(),
+        }
+        .execute()
+        .await
+    }
+
     pub async fn transfer_invalid_addr(
         &self,
         sender_index: usize,
@@ -861,6 +961,11 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            fuzz_seed: None,
+            report: None,
+            gas_snapshot: false,
+            update_gas_snapshot: false,
+            gas_tolerance: 0.0,
         }
         .execute()
         .await
@@ -0,0 +1,219 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{OfflineTransaction, OfflineTransactionSummary};
+use crate::common::{
+    types::{
+        CliCommand, CliError, CliTypedResult, EntryFunctionArguments, ProfileOptions,
+        PromptOptions, RestOptions,
+    },
+    utils::{check_if_file_exists, get_account_with_state, parse_json_file},
+};
+use aptos_api_types::{AptosError, AptosErrorCode, MoveType};
+use aptos_global_constants::MAX_GAS_AMOUNT;
+use aptos_rest_client::{
+    error::{AptosErrorResponse, RestError},
+    Client,
+};
+use aptos_sdk::transaction_builder::TransactionFactory;
+use aptos_types::{
+    chain_id::ChainId,
+    transaction::{EntryFunction, TransactionPayload},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::account_address::AccountAddress;
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Default time until an offline transaction expires, which leaves time to carry it to and
+/// from the signing machines
+pub const DEFAULT_OFFLINE_EXPIRATION_SECS: u64 = 60 * 60;
+
+/// Build an unsigned transaction for signing with `aptos txn sign`
+///
+/// The sequence number, chain id and gas unit price are fetched from the
+/// network unless they are given, but no key is needed.  The ABI of the
+/// function called is fetched too, so `sign` and `submit` can show the
+/// arguments decoded.
+///
+/// Only entry function payloads are supported.  To publish a package, write
+/// its payload with `aptos move build-publish-payload` and pass it with
+/// `--json-file`.  Scripts are not supported.
+#[derive(Debug, Parser)]
+pub struct BuildTransaction {
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+
+    /// Address of the account sending the transaction
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) sender_address: AccountAddress,
+
+    /// Sequence number of the transaction
+    ///
+    /// Defaults to the current sequence number of the sender account
+    #[clap(long)]
+    pub(crate) sequence_number: Option<u64>,
+
+    /// Addresses of the additional signers of a multi-agent transaction
+    #[clap(long, num_args = 0.., value_parser = crate::common::types::load_account_arg)]
+    pub(crate) secondary_signer_addresses: Vec<AccountAddress>,
+
+    /// Address of the account paying the gas of the transaction
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) fee_payer_address: Option<AccountAddress>,
+
+    /// Gas multiplier per unit of gas
+    ///
+    /// Defaults to the gas unit price estimated by the network
+    #[clap(long)]
+    pub(crate) gas_unit_price: Option<u64>,
+
+    /// Maximum amount of gas units to be used to send this transaction
+    ///
+    /// The transaction is not simulated, as its signers are not known yet, so
+    /// this defaults to the maximum amount allowed.
+    #[clap(long, default_value_t = MAX_GAS_AMOUNT)]
+    pub(crate) max_gas: u64,
+
+    /// Number of seconds until the transaction expires
+    #[clap(long, default_value_t = DEFAULT_OFFLINE_EXPIRATION_SECS)]
+    pub(crate) expiration_secs: u64,
+
+    /// File to write the unsigned transaction to
+    #[clap(long, value_parser)]
+    pub(crate) output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<OfflineTransactionSummary> for BuildTransaction {
+    fn command_name(&self) -> &'static str {
+        "BuildTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<OfflineTransactionSummary> {
+        check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
+        if let Some(json_file) = &self.entry_function_args.json_file {
+            // Script arguments have the same format, but without a function id
+            let json: serde_json::Value = parse_json_file(json_file)?;
+            if json.get("function_id").is_none() {
+                return Err(CliError::CommandArgumentError(format!(
+                    "{} has no function id. Only entry function payloads can be built, scripts are not supported",
+                    json_file.display()
+                )));
+            }
+        }
+        let entry_function: EntryFunction = self.entry_function_args.try_into()?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let argument_types = fetch_argument_types(&client, &entry_function).await?;
+        let (account, state) = get_account_with_state(&client, self.sender_address).await?;
+        let sequence_number = self.sequence_number.unwrap_or(account.sequence_number);
+        let gas_unit_price = if let Some(gas_unit_price) = self.gas_unit_price {
+            gas_unit_price
+        } else {
+            client.estimate_gas_price().await?.into_inner().gas_estimate
+        };
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .as_secs()
+            + self.expiration_secs;
+
+        let raw_txn = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_gas_unit_price(gas_unit_price)
+            .with_max_gas_amount(self.max_gas)
+            .payload(TransactionPayload::EntryFunction(entry_function))
+            .sender(self.sender_address)
+            .sequence_number(sequence_number)
+            .expiration_timestamp_secs(expiration_timestamp_secs)
+            .build();
+
+        let txn = OfflineTransaction::new(
+            raw_txn,
+            self.secondary_signer_addresses,
+            self.fee_payer_address,
+        )
+        .with_argument_types(argument_types);
+        txn.save(self.output_file.as_path())?;
+        Ok(txn.summary())
+    }
+}
+
+/// Fetches the types of the arguments of an entry function from its ABI, with its type
+/// arguments substituted. Returns `None` if the module or function doesn't have an ABI, e.g.
+/// because it's not published yet.
+async fn fetch_argument_types(
+    client: &Client,
+    entry_function: &EntryFunction,
+) -> CliTypedResult<Option<Vec<MoveType>>> {
+    let module = entry_function.module();
+    let abi = match client
+        .get_account_module(*module.address(), module.name().as_str())
+        .await
+    {
+        Ok(response) => response.into_inner().try_parse_abi()?.abi,
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::AccountNotFound | AptosErrorCode::ModuleNotFound,
+                    ..
+                },
+            ..
+        })) => None,
+        Err(err) => return Err(CliError::ApiError(err.to_string())),
+    };
+    let Some(function) = abi.and_then(|abi| {
+        abi.exposed_functions
+            .into_iter()
+            .find(|function| function.name.as_str() == entry_function.function().as_str())
+    }) else {
+        return Ok(None);
+    };
+
+    let type_arguments: Vec<MoveType> = entry_function
+        .ty_args()
+        .iter()
+        .map(MoveType::from)
+        .collect();
+    Ok(function
+        .params
+        .iter()
+        // Signers are not passed as arguments
+        .filter(|param| !param.is_signer())
+        .map(|param| substitute(param, &type_arguments))
+        .collect())
+}
+
+/// Replaces the generic type parameters of a type with the type arguments
+fn substitute(ty: &MoveType, type_arguments: &[MoveType]) -> Option<MoveType> {
+    Some(match ty {
+        MoveType::GenericTypeParam { index } => type_arguments.get(*index as usize)?.clone(),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, type_arguments)?),
+        },
+        MoveType::Struct(tag) => {
+            let mut tag = tag.clone();
+            tag.generic_type_params = tag
+                .generic_type_params
+                .iter()
+                .map(|ty| substitute(ty, type_arguments))
+                .collect::<Option<_>>()?;
+            MoveType::Struct(tag)
+        },
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute(to, type_arguments)?),
+        },
+        ty => ty.clone(),
+    })
+}
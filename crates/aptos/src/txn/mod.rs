@@ -0,0 +1,625 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{AccountSigner, CliCommand, CliError, CliResult, CliTypedResult},
+    utils::{read_from_file, write_to_file},
+};
+use aptos_api_types::{MoveStructTag, MoveType};
+use aptos_crypto::HashValue;
+use aptos_types::transaction::{
+    authenticator::AccountAuthenticator, MultisigTransactionPayload, RawTransaction,
+    RawTransactionWithData, SignedTransaction, TransactionPayload,
+};
+use clap::Subcommand;
+use move_core_types::{account_address::AccountAddress, u256::U256};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, path::Path};
+
+pub mod build;
+pub mod show;
+pub mod sign;
+pub mod submit;
+
+/// Tool for building, signing, submitting and inspecting transactions
+///
+/// This allows keys to sign transactions on a machine that is never connected
+/// to the network.  `build` writes an unsigned transaction to a file, `sign`
+/// adds a signature to the file with a local key or a Ledger device, and
+/// `submit` sends the transaction once every signer has signed it.  `show`
+/// decodes a committed transaction.
+#[derive(Debug, Subcommand)]
+pub enum TxnTool {
+    Build(build::BuildTransaction),
+    Show(show::ShowTransaction),
+    Sign(sign::SignTransaction),
+    Submit(submit::SubmitTransaction),
+}
+
+impl TxnTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            TxnTool::Build(tool) => tool.execute_serialized().await,
+            TxnTool::Show(tool) => tool.execute_serialized().await,
+            TxnTool::Sign(tool) => tool.execute_serialized().await,
+            TxnTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// A transaction waiting for signatures, stored as BCS between the build, sign and submit steps
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OfflineTransaction {
+    pub raw_txn: RawTransaction,
+    pub secondary_signer_addresses: Vec<AccountAddress>,
+    pub fee_payer_address: Option<AccountAddress>,
+    pub signatures: BTreeMap<AccountAddress, AccountAuthenticator>,
+    /// Types of the entry function arguments, taken from the ABI of the function when the
+    /// transaction was built. The arguments shown are still decoded from the payload, so
+    /// they are what gets signed.
+    pub argument_types: Option<Vec<MoveType>>,
+}
+
+impl OfflineTransaction {
+    pub fn new(
+        raw_txn: RawTransaction,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: Option<AccountAddress>,
+    ) -> Self {
+        Self {
+            raw_txn,
+            secondary_signer_addresses,
+            fee_payer_address,
+            signatures: BTreeMap::new(),
+            argument_types: None,
+        }
+    }
+
+    pub fn with_argument_types(mut self, argument_types: Option<Vec<MoveType>>) -> Self {
+        self.argument_types = argument_types;
+        self
+    }
+
+    pub fn load(path: &Path) -> CliTypedResult<Self> {
+        bcs::from_bytes(&read_from_file(path)?)
+            .map_err(|err| CliError::BCS("offline transaction", err))
+    }
+
+    pub fn save(&self, path: &Path) -> CliTypedResult<()> {
+        write_to_file(path, "Offline transaction", &bcs::to_bytes(self)?)
+    }
+
+    /// Every account that has to sign the transaction: the sender, the secondary signers and
+    /// the fee payer
+    pub fn signers(&self) -> Vec<AccountAddress> {
+        let mut signers = vec![self.raw_txn.sender()];
+        signers.extend(self.secondary_signer_addresses.iter().copied());
+        signers.extend(self.fee_payer_address);
+        signers
+    }
+
+    /// Whether the transaction has other signers than its sender, in which case every signer
+    /// signs the transaction along with the addresses of the other signers
+    fn has_additional_signers(&self) -> bool {
+        !self.secondary_signer_addresses.is_empty() || self.fee_payer_address.is_some()
+    }
+
+    fn raw_txn_with_data(&self) -> RawTransactionWithData {
+        match self.fee_payer_address {
+            Some(fee_payer_address) => RawTransactionWithData::new_fee_payer(
+                self.raw_txn.clone(),
+                self.secondary_signer_addresses.clone(),
+                fee_payer_address,
+            ),
+            None => RawTransactionWithData::new_multi_agent(
+                self.raw_txn.clone(),
+                self.secondary_signer_addresses.clone(),
+            ),
+        }
+    }
+
+    /// Signs the transaction on behalf of one of its signers
+    pub fn sign(&mut self, address: AccountAddress, signer: &AccountSigner) -> CliTypedResult<()> {
+        if !self.signers().contains(&address) {
+            return Err(CliError::CommandArgumentError(format!(
+                "{} is not a signer of the transaction",
+                address
+            )));
+        }
+
+        let authenticator = if self.has_additional_signers() {
+            signer.sign(&self.raw_txn_with_data())?
+        } else {
+            signer.sign(&self.raw_txn)?
+        };
+        self.signatures.insert(address, authenticator);
+        Ok(())
+    }
+
+    /// Checks that the transaction is signed by one of its signers, and that the signature is
+    /// valid
+    pub fn verify_signature(&self, address: AccountAddress) -> CliTypedResult<()> {
+        let authenticator = self.signatures.get(&address).ok_or_else(|| {
+            CliError::CommandArgumentError(format!(
+                "Transaction is missing a signature from {}",
+                address
+            ))
+        })?;
+        let result = if self.has_additional_signers() {
+            authenticator.verify(&self.raw_txn_with_data())
+        } else {
+            authenticator.verify(&self.raw_txn)
+        };
+        result.map_err(|err| {
+            CliError::CommandArgumentError(format!("Invalid signature from {}: {}", address, err))
+        })
+    }
+
+    /// Signers that have not signed the transaction yet
+    pub fn missing_signatures(&self) -> Vec<AccountAddress> {
+        self.signers()
+            .into_iter()
+            .filter(|address| !self.signatures.contains_key(address))
+            .collect()
+    }
+
+    /// Assembles the signatures into a transaction ready to be submitted
+    pub fn into_signed_transaction(self) -> CliTypedResult<SignedTransaction> {
+        let missing_signatures = self.missing_signatures();
+        if !missing_signatures.is_empty() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Transaction is missing signatures from {:?}",
+                missing_signatures
+            )));
+        }
+
+        let authenticator = |address: &AccountAddress| self.signatures[address].clone();
+        let sender = authenticator(&self.raw_txn.sender());
+        let secondary_signers = self
+            .secondary_signer_addresses
+            .iter()
+            .map(authenticator)
+            .collect();
+        let txn = if let Some(fee_payer_address) = self.fee_payer_address {
+            SignedTransaction::new_fee_payer(
+                self.raw_txn.clone(),
+                sender,
+                self.secondary_signer_addresses.clone(),
+                secondary_signers,
+                fee_payer_address,
+                authenticator(&fee_payer_address),
+            )
+        } else if !self.secondary_signer_addresses.is_empty() {
+            SignedTransaction::new_multi_agent(
+                self.raw_txn.clone(),
+                sender,
+                self.secondary_signer_addresses.clone(),
+                secondary_signers,
+            )
+        } else if let AccountAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        } = sender
+        {
+            SignedTransaction::new(self.raw_txn.clone(), public_key, signature)
+        } else {
+            SignedTransaction::new_single_sender(self.raw_txn.clone(), sender)
+        };
+
+        txn.verify_signature()
+            .map_err(|err| CliError::CommandArgumentError(format!("Invalid signature: {}", err)))?;
+        Ok(txn)
+    }
+
+    pub fn summary(&self) -> OfflineTransactionSummary {
+        OfflineTransactionSummary {
+            sender: self.raw_txn.sender(),
+            sequence_number: self.raw_txn.sequence_number(),
+            payload: payload_summary(self.raw_txn.payload(), self.argument_types.as_deref()),
+            max_gas_amount: self.raw_txn.max_gas_amount(),
+            gas_unit_price: self.raw_txn.gas_unit_price(),
+            expiration_timestamp_secs: self.raw_txn.expiration_timestamp_secs(),
+            chain_id: self.raw_txn.chain_id().id(),
+            secondary_signers: self.secondary_signer_addresses.clone(),
+            fee_payer: self.fee_payer_address,
+            signed_by: self.signatures.keys().copied().collect(),
+            missing_signatures: self.missing_signatures(),
+        }
+    }
+}
+
+/// A human readable description of an offline transaction
+#[derive(Clone, Debug, Serialize)]
+pub struct OfflineTransactionSummary {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub payload: serde_json::Value,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+    pub chain_id: u8,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub secondary_signers: Vec<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<AccountAddress>,
+    pub signed_by: Vec<AccountAddress>,
+    pub missing_signatures: Vec<AccountAddress>,
+}
+
+impl OfflineTransactionSummary {
+    /// Prints the summary to stderr, so the user can review the transaction before acting on it
+    pub fn print(&self) -> CliTypedResult<()> {
+        eprintln!(
+            "{}",
+            serde_json::to_string_pretty(self)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+        );
+        Ok(())
+    }
+}
+
+/// Decodes a payload without fetching ABIs. Entry function arguments are decoded with the
+/// given argument types, and shown as BCS hex without them or if they don't decode.
+pub fn payload_summary(
+    payload: &TransactionPayload,
+    argument_types: Option<&[MoveType]>,
+) -> serde_json::Value {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => json!({
+            "type": "entry_function",
+            "function": format!(
+                "{}::{}",
+                entry_function.module().short_str_lossless(),
+                entry_function.function()
+            ),
+            "type_arguments": entry_function
+                .ty_args()
+                .iter()
+                .map(|ty| ty.to_canonical_string())
+                .collect::<Vec<_>>(),
+            "arguments": match argument_types {
+                Some(types) if types.len() == entry_function.args().len() => entry_function
+                    .args()
+                    .iter()
+                    .zip(types)
+                    .map(|(arg, ty)| json!({
+                        "type": ty.to_string(),
+                        "value": decode_argument(ty, arg).unwrap_or_else(|| hex_value(arg)),
+                    }))
+                    .collect::<Vec<_>>(),
+                _ => entry_function.args().iter().map(|arg| hex_value(arg)).collect(),
+            },
+        }),
+        TransactionPayload::Script(script) => json!({
+            "type": "script",
+            "code_hash": HashValue::sha3_256_of(script.code()).to_hex_literal(),
+            "type_arguments": script
+                .ty_args()
+                .iter()
+                .map(|ty| ty.to_canonical_string())
+                .collect::<Vec<_>>(),
+            "arguments": script
+                .args()
+                .iter()
+                .map(|arg| format!("{:?}", arg))
+                .collect::<Vec<_>>(),
+        }),
+        TransactionPayload::Multisig(multisig) => json!({
+            "type": "multisig",
+            "multisig_address": multisig.multisig_address,
+            "transaction_payload": multisig.transaction_payload.as_ref().map(|payload| match payload {
+                MultisigTransactionPayload::EntryFunction(entry_function) => payload_summary(
+                    &TransactionPayload::EntryFunction(entry_function.clone()),
+                    None,
+                ),
+            }),
+        }),
+        TransactionPayload::ModuleBundle(_) => json!({
+            "type": "module_bundle",
+        }),
+    }
+}
+
+fn hex_value(bytes: &[u8]) -> Value {
+    Value::String(format!("0x{}", hex::encode(bytes)))
+}
+
+/// Decodes a BCS encoded argument of the given type into the JSON format of the API. Returns
+/// `None` for types that can't be decoded without more ABIs, and for bytes that don't decode
+/// to exactly one value of the type.
+pub fn decode_argument(ty: &MoveType, bytes: &[u8]) -> Option<Value> {
+    let mut bytes = bytes;
+    let value = decode_value(ty, &mut bytes)?;
+    bytes.is_empty().then_some(value)
+}
+
+fn decode_value(ty: &MoveType, bytes: &mut &[u8]) -> Option<Value> {
+    Some(match ty {
+        MoveType::Bool => match take::<1>(bytes)? {
+            [0] => json!(false),
+            [1] => json!(true),
+            _ => return None,
+        },
+        MoveType::U8 => json!(u8::from_le_bytes(take(bytes)?)),
+        MoveType::U16 => json!(u16::from_le_bytes(take(bytes)?)),
+        MoveType::U32 => json!(u32::from_le_bytes(take(bytes)?)),
+        // Integers that don't fit in a JSON number are strings, as in the API
+        MoveType::U64 => json!(u64::from_le_bytes(take(bytes)?).to_string()),
+        MoveType::U128 => json!(u128::from_le_bytes(take(bytes)?).to_string()),
+        MoveType::U256 => json!(U256::from_le_bytes(&take(bytes)?).to_string()),
+        MoveType::Address => json!(AccountAddress::new(take(bytes)?).to_standard_string()),
+        MoveType::Vector { items } => {
+            let len = take_uleb128(bytes)?;
+            if **items == MoveType::U8 {
+                hex_value(take_slice(bytes, len)?)
+            } else {
+                Value::Array(
+                    (0..len)
+                        .map(|_| decode_value(items, bytes))
+                        .collect::<Option<_>>()?,
+                )
+            }
+        },
+        MoveType::Struct(tag) => decode_struct(tag, bytes)?,
+        MoveType::Signer
+        | MoveType::GenericTypeParam { .. }
+        | MoveType::Reference { .. }
+        | MoveType::Unparsable(_) => return None,
+    })
+}
+
+/// Decodes the standard library structs that entry functions can take as arguments
+fn decode_struct(tag: &MoveStructTag, bytes: &mut &[u8]) -> Option<Value> {
+    if *tag.address.inner() != AccountAddress::ONE {
+        return None;
+    }
+    Some(
+        match (
+            tag.module.as_str(),
+            tag.name.as_str(),
+            &tag.generic_type_params[..],
+        ) {
+            ("string", "String", []) => {
+                let len = take_uleb128(bytes)?;
+                json!(std::str::from_utf8(take_slice(bytes, len)?).ok()?)
+            },
+            ("object", "Object", [_]) => decode_value(&MoveType::Address, bytes)?,
+            ("option", "Option", [ty]) => match take_uleb128(bytes)? {
+                0 => Value::Null,
+                1 => decode_value(ty, bytes)?,
+                _ => return None,
+            },
+            _ => return None,
+        },
+    )
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    take_slice(bytes, N)?.try_into().ok()
+}
+
+fn take_slice<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(taken)
+}
+
+fn take_uleb128(bytes: &mut &[u8]) -> Option<usize> {
+    let mut value: u64 = 0;
+    for shift in (0..32).step_by(7) {
+        let [byte] = take::<1>(bytes)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return usize::try_from(value).ok();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::account_address_from_public_key;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_crypto::PrivateKey;
+    use aptos_keygen::KeyGen;
+    use aptos_sdk::transaction_builder::TransactionFactory;
+    use aptos_temppath::TempPath;
+    use aptos_types::{chain_id::ChainId, transaction::authenticator::TransactionAuthenticator};
+
+    fn signer(seed: u8) -> (AccountAddress, AccountSigner) {
+        let private_key = KeyGen::from_seed([seed; 32]).generate_ed25519_private_key();
+        (
+            account_address_from_public_key(&private_key.public_key()),
+            AccountSigner::Local(private_key),
+        )
+    }
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        TransactionFactory::new(ChainId::test())
+            .payload(aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, 5))
+            .sender(sender)
+            .sequence_number(3)
+            .build()
+    }
+
+    /// Signs the transaction one signer at a time, saving and loading it in between as the
+    /// `build`, `sign` and `submit` steps do
+    fn sign_round_trip(
+        txn: OfflineTransaction,
+        signers: &[(AccountAddress, AccountSigner)],
+    ) -> SignedTransaction {
+        let path = TempPath::new();
+        txn.save(path.path()).unwrap();
+        for (address, signer) in signers {
+            let mut txn = OfflineTransaction::load(path.path()).unwrap();
+            assert!(txn.missing_signatures().contains(address));
+            txn.sign(*address, signer).unwrap();
+            txn.verify_signature(*address).unwrap();
+            txn.save(path.path()).unwrap();
+        }
+        let txn = OfflineTransaction::load(path.path()).unwrap();
+        assert!(txn.missing_signatures().is_empty());
+        txn.into_signed_transaction().unwrap()
+    }
+
+    #[test]
+    fn test_single_signer_round_trip() {
+        let sender = signer(1);
+        let txn = OfflineTransaction::new(raw_txn(sender.0), vec![], None);
+        assert_eq!(txn.signers(), vec![sender.0]);
+
+        let signed_txn = sign_round_trip(txn, &[sender]);
+        assert!(matches!(
+            signed_txn.authenticator(),
+            TransactionAuthenticator::Ed25519 { .. }
+        ));
+        assert_eq!(signed_txn.sequence_number(), 3);
+    }
+
+    #[test]
+    fn test_multi_agent_round_trip() {
+        let sender = signer(1);
+        let secondary_signer = signer(2);
+        let txn = OfflineTransaction::new(raw_txn(sender.0), vec![secondary_signer.0], None);
+        assert_eq!(txn.signers(), vec![sender.0, secondary_signer.0]);
+
+        // Signers may sign in any order
+        let signed_txn = sign_round_trip(txn, &[secondary_signer, sender]);
+        assert!(matches!(
+            signed_txn.authenticator(),
+            TransactionAuthenticator::MultiAgent { .. }
+        ));
+    }
+
+    #[test]
+    fn test_fee_payer_round_trip() {
+        let sender = signer(1);
+        let fee_payer = signer(3);
+        let txn = OfflineTransaction::new(raw_txn(sender.0), vec![], Some(fee_payer.0));
+        assert_eq!(txn.signers(), vec![sender.0, fee_payer.0]);
+
+        let signed_txn = sign_round_trip(txn, &[fee_payer, sender]);
+        match signed_txn.authenticator() {
+            TransactionAuthenticator::FeePayer {
+                fee_payer_address, ..
+            } => assert_eq!(fee_payer_address, signer(3).0),
+            authenticator => panic!("unexpected authenticator {:?}", authenticator),
+        }
+    }
+
+    #[test]
+    fn test_decode_argument() {
+        let ty = |ty: &str| ty.parse::<MoveType>().unwrap();
+        let decode = |ty: &str, value: &[u8]| decode_argument(&ty(ty), value);
+
+        assert_eq!(
+            decode("bool", &bcs::to_bytes(&true).unwrap()),
+            Some(json!(true))
+        );
+        assert_eq!(decode("u8", &[7]), Some(json!(7)));
+        assert_eq!(
+            decode("u64", &bcs::to_bytes(&5u64).unwrap()),
+            Some(json!("5"))
+        );
+        assert_eq!(
+            decode("u128", &bcs::to_bytes(&u128::MAX).unwrap()),
+            Some(json!(u128::MAX.to_string()))
+        );
+        assert_eq!(
+            decode("address", &bcs::to_bytes(&AccountAddress::ONE).unwrap()),
+            Some(json!(AccountAddress::ONE.to_standard_string()))
+        );
+        assert_eq!(
+            decode("vector<u8>", &bcs::to_bytes(&vec![1u8, 2]).unwrap()),
+            Some(json!("0x0102"))
+        );
+        assert_eq!(
+            decode("vector<u16>", &bcs::to_bytes(&vec![1u16, 300]).unwrap()),
+            Some(json!([1, 300]))
+        );
+        assert_eq!(
+            decode("0x1::string::String", &bcs::to_bytes("hello").unwrap()),
+            Some(json!("hello"))
+        );
+        assert_eq!(
+            decode(
+                "0x1::option::Option<u64>",
+                &bcs::to_bytes(&Some(3u64)).unwrap()
+            ),
+            Some(json!("3"))
+        );
+        assert_eq!(
+            decode(
+                "0x1::option::Option<u64>",
+                &bcs::to_bytes(&None::<u64>).unwrap()
+            ),
+            Some(Value::Null)
+        );
+
+        // Bytes that don't decode to exactly one value of the type are not decoded
+        assert_eq!(decode("u64", &bcs::to_bytes(&5u32).unwrap()), None);
+        assert_eq!(decode("u8", &[1, 2]), None);
+        assert_eq!(decode("bool", &[2]), None);
+        assert_eq!(decode("0x1::string::String", &[1, 0xff]), None);
+        // Nor are structs without a known layout
+        assert_eq!(decode("0x1::fixed_point32::FixedPoint32", &[0; 8]), None);
+    }
+
+    #[test]
+    fn test_summary_with_argument_types() {
+        let sender = signer(1);
+        let txn = OfflineTransaction::new(raw_txn(sender.0), vec![], None);
+        assert_eq!(
+            txn.summary().payload["arguments"],
+            json!([
+                format!("0x{}", hex::encode(AccountAddress::ONE)),
+                "0x0500000000000000",
+            ])
+        );
+
+        let txn = txn.with_argument_types(Some(vec![MoveType::Address, MoveType::U64]));
+        assert_eq!(
+            txn.summary().payload["arguments"],
+            json!([
+                { "type": "address", "value": AccountAddress::ONE.to_standard_string() },
+                { "type": "u64", "value": "5" },
+            ])
+        );
+
+        // The argument types are kept in the file
+        let path = TempPath::new();
+        txn.save(path.path()).unwrap();
+        let txn = OfflineTransaction::load(path.path()).unwrap();
+        assert_eq!(
+            txn.argument_types,
+            Some(vec![MoveType::Address, MoveType::U64])
+        );
+    }
+
+    #[test]
+    fn test_missing_and_foreign_signatures() {
+        let sender = signer(1);
+        let fee_payer = signer(3);
+        let mut txn = OfflineTransaction::new(raw_txn(sender.0), vec![], Some(fee_payer.0));
+
+        // Only signers of the transaction can sign it
+        let stranger = signer(4);
+        assert!(txn.sign(stranger.0, &stranger.1).is_err());
+
+        txn.sign(sender.0, &sender.1).unwrap();
+        assert_eq!(txn.missing_signatures(), vec![fee_payer.0]);
+        assert!(txn.verify_signature(fee_payer.0).is_err());
+        assert!(txn.clone().into_signed_transaction().is_err());
+
+        // Signatures do not carry over to a different transaction
+        txn.sign(fee_payer.0, &fee_payer.1).unwrap();
+        txn.raw_txn = raw_txn(stranger.0);
+        assert!(txn.verify_signature(fee_payer.0).is_err());
+        assert!(txn.into_signed_transaction().is_err());
+    }
+}
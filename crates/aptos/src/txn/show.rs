@@ -0,0 +1,643 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    local_simulation,
+    types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+};
+use aptos_api_types::{
+    EntryFunctionPayload, MoveResource, MoveType, MultisigTransactionPayload, Transaction,
+    TransactionPayload, UserTransaction, WriteSetChange,
+};
+use aptos_crypto::HashValue;
+use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::{state_key::StateKey, table::TableHandle, StateView},
+    transaction::{Transaction as CommittedTransaction, TransactionStatus},
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::language_storage::StructTag;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const FEE_STATEMENT_EVENT_TYPE: &str = "0x1::transaction_fee::FeeStatement";
+
+/// Show a decoded view of a committed transaction
+///
+/// Shows the payload with the arguments typed by the ABI of the function called, the
+/// events, the resources changed with their values before and after the transaction,
+/// and the gas used. With `--replay`, the transaction is also executed again locally.
+#[derive(Debug, Parser)]
+pub struct ShowTransaction {
+    /// Hash of the transaction
+    #[clap(long, required_unless_present = "version", value_parser = parse_hash)]
+    pub(crate) hash: Option<HashValue>,
+
+    /// Version of the transaction
+    #[clap(long, conflicts_with = "hash")]
+    pub(crate) version: Option<u64>,
+
+    /// Execute the transaction again locally with the debugger, and compare the result with
+    /// the one on chain
+    #[clap(long)]
+    pub(crate) replay: bool,
+
+    /// Profile the gas usage of the replayed transaction, and generate a report of it
+    #[clap(long, requires = "replay")]
+    pub(crate) profile_gas: bool,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+fn parse_hash(hash: &str) -> Result<HashValue, String> {
+    HashValue::from_hex(hash.strip_prefix("0x").unwrap_or(hash)).map_err(|err| err.to_string())
+}
+
+/// A decoded view of a committed transaction
+#[derive(Debug, Serialize)]
+pub struct TransactionView {
+    pub version: u64,
+    pub hash: HashValue,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub timestamp_us: u64,
+    pub success: bool,
+    pub vm_status: String,
+    pub payload: Value,
+    pub events: Vec<Value>,
+    pub changes: Vec<Value>,
+    pub gas: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayResult>,
+}
+
+/// The outcome of executing a transaction again locally
+#[derive(Debug, Serialize)]
+pub struct ReplayResult {
+    pub success: Option<bool>,
+    pub vm_status: String,
+    pub gas_used: u64,
+    /// Whether the output matches the one committed on chain, or why it doesn't
+    pub matches_chain: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatch: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<TransactionView> for ShowTransaction {
+    fn command_name(&self) -> &'static str {
+        "ShowTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionView> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let txn = match (self.hash, self.version) {
+            (Some(hash), _) => client.get_transaction_by_hash(hash).await,
+            (None, Some(version)) => client.get_transaction_by_version(version).await,
+            (None, None) => {
+                return Err(CliError::CommandArgumentError(
+                    "Either --hash or --version must be given".to_string(),
+                ))
+            },
+        }
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner();
+        let txn = match txn {
+            Transaction::UserTransaction(txn) => *txn,
+            _ => {
+                return Err(CliError::UnexpectedError(
+                    "Unsupported transaction type. Only user transactions are supported."
+                        .to_string(),
+                ))
+            },
+        };
+        let version = txn.info.version.0;
+
+        // Single transactions are always returned with their payload
+        let payload = match &txn.request.payload {
+            Some(payload) => decode_payload(&client, payload).await?,
+            None => Value::Null,
+        };
+        let debugger = AptosDebugger::rest_client(client.clone())?;
+        let state_view = debugger.state_view_at_version(version);
+        let changes = txn.info.changes.clone();
+        // Reading the state before the transaction blocks on requests to the node.
+        let changes = tokio::task::spawn_blocking(move || {
+            let annotator = AptosValueAnnotator::new(&state_view);
+            changes
+                .iter()
+                .map(|change| decode_change(&state_view, &annotator, change))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+
+        let replay = if self.replay {
+            Some(replay(&debugger, version, self.profile_gas).await?)
+        } else {
+            None
+        };
+
+        Ok(TransactionView {
+            version,
+            hash: txn.info.hash.into(),
+            sender: *txn.request.sender.inner(),
+            sequence_number: txn.request.sequence_number.0,
+            timestamp_us: txn.timestamp.0,
+            success: txn.info.success,
+            vm_status: txn.info.vm_status.clone(),
+            payload,
+            events: txn
+                .events
+                .iter()
+                .map(|event| {
+                    json!({
+                        "type": event.typ.to_string(),
+                        "sequence_number": event.sequence_number,
+                        "data": event.data,
+                    })
+                })
+                .collect(),
+            changes,
+            gas: gas_breakdown(&txn),
+            replay,
+        })
+    }
+}
+
+/// Decodes the payload, giving each argument of an entry function the type of its parameter.
+async fn decode_payload(client: &Client, payload: &TransactionPayload) -> CliTypedResult<Value> {
+    Ok(match payload {
+        TransactionPayload::EntryFunctionPayload(entry_function) => {
+            decode_entry_function(client, entry_function).await?
+        },
+        TransactionPayload::MultisigPayload(multisig) => {
+            let transaction_payload = match &multisig.transaction_payload {
+                Some(MultisigTransactionPayload::EntryFunctionPayload(entry_function)) => {
+                    Some(decode_entry_function(client, entry_function).await?)
+                },
+                None => None,
+            };
+            json!({
+                "type": "multisig",
+                "multisig_address": multisig.multisig_address,
+                "transaction_payload": transaction_payload,
+            })
+        },
+        TransactionPayload::ScriptPayload(script) => json!({
+            "type": "script",
+            "code_hash": HashValue::sha3_256_of(script.code.bytecode.inner()).to_hex_literal(),
+            "type_arguments": script.type_arguments,
+            "arguments": script.arguments,
+        }),
+        TransactionPayload::ModuleBundlePayload(_) => json!({
+            "type": "module_bundle",
+        }),
+    })
+}
+
+async fn decode_entry_function(
+    client: &Client,
+    entry_function: &EntryFunctionPayload,
+) -> CliTypedResult<Value> {
+    let module = &entry_function.function.module;
+    let abi = client
+        .get_account_module(*module.address.inner(), module.name.as_str())
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner()
+        .try_parse_abi()?
+        .abi;
+    let params = abi.and_then(|abi| {
+        abi.exposed_functions
+            .into_iter()
+            .find(|function| function.name == entry_function.function.name)
+            .map(|function| function.params)
+    });
+
+    // Signers are not passed as arguments.
+    let types: Vec<Option<String>> = match params {
+        Some(params) => params
+            .into_iter()
+            .filter(|param| !is_signer(param))
+            .map(|param| Some(param.to_string()))
+            .collect(),
+        None => vec![None; entry_function.arguments.len()],
+    };
+    let arguments = entry_function
+        .arguments
+        .iter()
+        .zip(types.into_iter().chain(std::iter::repeat(None)))
+        .map(|(value, ty)| json!({ "type": ty, "value": value }))
+        .collect::<Vec<_>>();
+    Ok(json!({
+        "type": "entry_function",
+        "function": entry_function.function.to_string(),
+        "type_arguments": entry_function.type_arguments,
+        "arguments": arguments,
+    }))
+}
+
+fn is_signer(ty: &MoveType) -> bool {
+    match ty {
+        MoveType::Signer => true,
+        MoveType::Reference { to, .. } => matches!(**to, MoveType::Signer),
+        _ => false,
+    }
+}
+
+/// Decodes a change of the write set, with the value before the transaction read from the state
+/// the transaction was executed on.
+fn decode_change<S: StateView>(
+    state_view: &S,
+    annotator: &AptosValueAnnotator<S>,
+    change: &WriteSetChange,
+) -> Value {
+    match change {
+        WriteSetChange::WriteResource(write) => {
+            let address = *write.address.inner();
+            let before = resource_before(state_view, annotator, address, &write.data.typ);
+            let after = serde_json::to_value(&write.data.data).unwrap_or(Value::Null);
+            let (kind, diff) = match &before {
+                Ok(Some(before)) => ("modified", Some(diff(before, &after))),
+                Ok(None) => ("created", None),
+                Err(_) => ("written", None),
+            };
+            json!({
+                "type": kind,
+                "address": write.address,
+                "resource": write.data.typ.to_string(),
+                "before": before.as_ref().ok().cloned().flatten(),
+                "after": after,
+                "diff": diff,
+                "error": before.err(),
+            })
+        },
+        WriteSetChange::DeleteResource(delete) => {
+            let before = resource_before(
+                state_view,
+                annotator,
+                *delete.address.inner(),
+                &delete.resource,
+            );
+            json!({
+                "type": "deleted",
+                "address": delete.address,
+                "resource": delete.resource.to_string(),
+                "before": before.as_ref().ok().cloned().flatten(),
+                "error": before.err(),
+            })
+        },
+        WriteSetChange::WriteTableItem(write) => {
+            let before = AccountAddress::from_bytes(write.handle.inner())
+                .ok()
+                .and_then(|handle| {
+                    let key = StateKey::table_item(&TableHandle(handle), write.key.inner());
+                    state_view.get_state_value_bytes(&key).ok().flatten()
+                })
+                .map(|bytes| format!("0x{}", hex::encode(bytes)));
+            json!({
+                "type": if before.is_some() { "modified" } else { "created" },
+                "table_handle": write.handle,
+                "key": write.key,
+                "before": before,
+                "after": write.value,
+                "data": write.data,
+            })
+        },
+        change => serde_json::to_value(change).unwrap_or(Value::Null),
+    }
+}
+
+/// Reads and decodes a resource as it was before the transaction, looking it up in its
+/// resource group if it belongs to one.
+fn resource_before<S: StateView>(
+    state_view: &S,
+    annotator: &AptosValueAnnotator<S>,
+    address: AccountAddress,
+    typ: &aptos_api_types::MoveStructTag,
+) -> Result<Option<Value>, String> {
+    let tag = StructTag::try_from(typ.clone()).map_err(|err| err.to_string())?;
+    let bytes = match annotator.view_resource_group_member(&tag) {
+        Some(group_tag) => {
+            let key = StateKey::resource_group(&address, &group_tag);
+            let group = state_view
+                .get_state_value_bytes(&key)
+                .map_err(|err| err.to_string())?;
+            match group {
+                Some(group) => {
+                    let mut group: BTreeMap<StructTag, Vec<u8>> =
+                        bcs::from_bytes(&group).map_err(|err| err.to_string())?;
+                    group.remove(&tag)
+                },
+                None => None,
+            }
+        },
+        None => {
+            let key = StateKey::resource(&address, &tag).map_err(|err| err.to_string())?;
+            state_view
+                .get_state_value_bytes(&key)
+                .map_err(|err| err.to_string())?
+                .map(|bytes| bytes.to_vec())
+        },
+    };
+    match bytes {
+        Some(bytes) => {
+            let resource = annotator
+                .view_resource(&tag, &bytes)
+                .and_then(MoveResource::try_from)
+                .map_err(|err| err.to_string())?;
+            serde_json::to_value(resource.data)
+                .map(Some)
+                .map_err(|err| err.to_string())
+        },
+        None => Ok(None),
+    }
+}
+
+/// Lists the fields which differ between two values, by their path in the values.
+fn diff(before: &Value, after: &Value) -> Vec<Value> {
+    let mut diffs = vec![];
+    diff_at(&mut diffs, String::new(), before, after);
+    diffs
+}
+
+fn diff_at(diffs: &mut Vec<Value>, path: String, before: &Value, after: &Value) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, before_value) in before {
+                let path = format!("{}.{}", path, key);
+                match after.get(key) {
+                    Some(after_value) => diff_at(diffs, path, before_value, after_value),
+                    None => diffs.push(json!({ "path": path, "before": before_value })),
+                }
+            }
+            for (key, after_value) in after {
+                if !before.contains_key(key) {
+                    diffs
+                        .push(json!({ "path": format!("{}.{}", path, key), "after": after_value }));
+                }
+            }
+        },
+        (Value::Array(before_values), Value::Array(after_values))
+            if before_values.len() == after_values.len() =>
+        {
+            for (i, (before, after)) in before_values.iter().zip(after_values).enumerate() {
+                diff_at(diffs, format!("{}[{}]", path, i), before, after);
+            }
+        },
+        (before, after) if before != after => {
+            diffs.push(json!({ "path": path, "before": before, "after": after }));
+        },
+        _ => {},
+    }
+}
+
+/// The gas used by the transaction, broken down by the fee statement it emitted.
+fn gas_breakdown(txn: &UserTransaction) -> Value {
+    let gas_used = txn.info.gas_used.0;
+    let gas_unit_price = txn.request.gas_unit_price.0;
+    let fee_statement = txn
+        .events
+        .iter()
+        .find(|event| event.typ.to_string() == FEE_STATEMENT_EVENT_TYPE)
+        .map(|event| event.data.clone());
+    json!({
+        "gas_used": gas_used,
+        "gas_unit_price": gas_unit_price,
+        "max_gas_amount": txn.request.max_gas_amount.0,
+        "total_fee_octas": gas_used * gas_unit_price,
+        "fee_statement": fee_statement,
+    })
+}
+
+/// Executes the transaction at the version again, comparing the output with the one on chain.
+async fn replay(
+    debugger: &AptosDebugger,
+    version: u64,
+    profile_gas: bool,
+) -> CliTypedResult<ReplayResult> {
+    let (txn, txn_info) = debugger
+        .get_committed_transaction_at_version(version)
+        .await?;
+    let txn = match txn {
+        CommittedTransaction::UserTransaction(txn) => txn,
+        _ => {
+            return Err(CliError::UnexpectedError(
+                "Unsupported transaction type. Only user transactions are supported.".to_string(),
+            ))
+        },
+    };
+    let hash = txn.committed_hash();
+    let (vm_status, vm_output) = if profile_gas {
+        local_simulation::profile_transaction_using_debugger(debugger, version, txn, hash)?
+    } else {
+        local_simulation::run_transaction_using_debugger(debugger, version, txn, hash)?
+    };
+
+    let state_view = debugger.state_view_at_version(version);
+    let resolver = state_view.as_move_resolver();
+    let txn_output = vm_output
+        .try_materialize_into_transaction_output(&resolver)
+        .map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to materialize into transaction output: {}",
+                err
+            ))
+        })?;
+    let mismatch = txn_output
+        .ensure_match_transaction_info(version, &txn_info, None, None)
+        .err()
+        .map(|err| err.to_string());
+    let success = match txn_output.status() {
+        TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+        TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+    };
+    Ok(ReplayResult {
+        success,
+        vm_status: vm_status.to_string(),
+        gas_used: txn_output.gas_used(),
+        matches_chain: mismatch.is_none(),
+        mismatch,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        chain_id::ChainId,
+        state_store::{in_memory_state_view::InMemoryStateView, state_value::StateValue},
+    };
+    use std::{collections::HashMap, str::FromStr};
+
+    const CHAIN_ID_TYPE: &str = "0x1::chain_id::ChainId";
+
+    /// A state with the framework, and the chain ID resource if given.
+    fn state(chain_id: Option<u8>) -> HashMap<StateKey, StateValue> {
+        let mut state = HashMap::new();
+        for (code, module) in
+            aptos_cached_packages::head_release_bundle().code_and_compiled_modules()
+        {
+            state.insert(
+                StateKey::module_id(&module.self_id()),
+                StateValue::new_legacy(code.to_vec().into()),
+            );
+        }
+        if let Some(id) = chain_id {
+            let tag = StructTag::from_str(CHAIN_ID_TYPE).unwrap();
+            state.insert(
+                StateKey::resource(&AccountAddress::ONE, &tag).unwrap(),
+                StateValue::new_legacy(bcs::to_bytes(&ChainId::new(id)).unwrap().into()),
+            );
+        }
+        state
+    }
+
+    fn change(change: Value) -> WriteSetChange {
+        serde_json::from_value(change).unwrap()
+    }
+
+    fn decode(state: HashMap<StateKey, StateValue>, write_set_change: Value) -> Value {
+        let state_view = InMemoryStateView::new(state);
+        let annotator = AptosValueAnnotator::new(&state_view);
+        decode_change(&state_view, &annotator, &change(write_set_change))
+    }
+
+    fn write_chain_id(id: u8) -> Value {
+        json!({
+            "type": "write_resource",
+            "address": "0x1",
+            "state_key_hash": HashValue::zero().to_hex_literal(),
+            "data": { "type": CHAIN_ID_TYPE, "data": { "id": id } },
+        })
+    }
+
+    #[test]
+    fn test_diff_objects() {
+        let before = json!({ "a": 1, "b": { "c": "x", "d": true }, "removed": 2 });
+        let after = json!({ "a": 1, "b": { "c": "y", "d": true }, "added": 3 });
+        assert_eq!(
+            diff(&before, &after),
+            vec![
+                json!({ "path": ".b.c", "before": "x", "after": "y" }),
+                json!({ "path": ".removed", "before": 2 }),
+                json!({ "path": ".added", "after": 3 }),
+            ]
+        );
+        assert!(diff(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_diff_arrays() {
+        // Arrays of the same length are compared by element.
+        let before = json!({ "v": [1, { "x": 2 }, 3] });
+        let after = json!({ "v": [1, { "x": 4 }, 5] });
+        assert_eq!(
+            diff(&before, &after),
+            vec![
+                json!({ "path": ".v[1].x", "before": 2, "after": 4 }),
+                json!({ "path": ".v[2]", "before": 3, "after": 5 }),
+            ]
+        );
+
+        // Arrays which changed length are shown as a whole.
+        let after = json!({ "v": [1] });
+        assert_eq!(
+            diff(&before, &after),
+            vec![json!({ "path": ".v", "before": [1, { "x": 2 }, 3], "after": [1] }),]
+        );
+    }
+
+    #[test]
+    fn test_diff_scalars() {
+        let mut diffs = vec![];
+        diff_at(&mut diffs, "x".to_string(), &json!("1"), &json!("2"));
+        diff_at(&mut diffs, "y".to_string(), &json!(true), &json!(true));
+        diff_at(
+            &mut diffs,
+            "z".to_string(),
+            &json!(null),
+            &json!({ "a": 1 }),
+        );
+        assert_eq!(
+            diffs,
+            vec![
+                json!({ "path": "x", "before": "1", "after": "2" }),
+                json!({ "path": "z", "before": null, "after": { "a": 1 } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_created_resource() {
+        let decoded = decode(state(None), write_chain_id(5));
+        assert_eq!(decoded["type"], "created");
+        assert_eq!(decoded["resource"], CHAIN_ID_TYPE);
+        assert_eq!(decoded["before"], Value::Null);
+        assert_eq!(decoded["after"], json!({ "id": 5 }));
+        assert_eq!(decoded["diff"], Value::Null);
+        assert_eq!(decoded["error"], Value::Null);
+    }
+
+    #[test]
+    fn test_decode_modified_resource() {
+        let decoded = decode(state(Some(4)), write_chain_id(5));
+        assert_eq!(decoded["type"], "modified");
+        assert_eq!(decoded["before"], json!({ "id": 4 }));
+        assert_eq!(decoded["after"], json!({ "id": 5 }));
+        assert_eq!(
+            decoded["diff"],
+            json!([{ "path": ".id", "before": 4, "after": 5 }])
+        );
+    }
+
+    #[test]
+    fn test_decode_deleted_resource() {
+        let delete = json!({
+            "type": "delete_resource",
+            "address": "0x1",
+            "state_key_hash": HashValue::zero().to_hex_literal(),
+            "resource": CHAIN_ID_TYPE,
+        });
+        let decoded = decode(state(Some(4)), delete);
+        assert_eq!(decoded["type"], "deleted");
+        assert_eq!(decoded["resource"], CHAIN_ID_TYPE);
+        assert_eq!(decoded["before"], json!({ "id": 4 }));
+        assert_eq!(decoded["error"], Value::Null);
+    }
+
+    #[test]
+    fn test_decode_table_item() {
+        let handle = AccountAddress::from_hex_literal("0x7ab1e").unwrap();
+        let write = json!({
+            "type": "write_table_item",
+            "state_key_hash": HashValue::zero().to_hex_literal(),
+            "handle": handle.to_hex_literal(),
+            "key": "0x01",
+            "value": "0x02",
+        });
+        let decoded = decode(state(None), write.clone());
+        assert_eq!(decoded["type"], "created");
+        assert_eq!(decoded["before"], Value::Null);
+
+        let mut state = state(None);
+        state.insert(
+            StateKey::table_item(&TableHandle(handle), &[1]),
+            StateValue::new_legacy(vec![3].into()),
+        );
+        let decoded = decode(state, write);
+        assert_eq!(decoded["type"], "modified");
+        assert_eq!(decoded["before"], "0x03");
+        assert_eq!(decoded["after"], "0x02");
+    }
+}
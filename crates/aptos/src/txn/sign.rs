@@ -0,0 +1,80 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{OfflineTransaction, OfflineTransactionSummary};
+use crate::common::{
+    types::{
+        AccountSigner, CliCommand, CliTypedResult, EncodingOptions, PrivateKeyInputOptions,
+        ProfileOptions, PromptOptions,
+    },
+    utils::prompt_yes_with_override,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::account_address::AccountAddress;
+use std::path::PathBuf;
+
+/// Sign a transaction built with `aptos txn build`
+///
+/// This does not need network access.  The key is taken from `--private-key`,
+/// `--private-key-file` or the profile, which may be a Ledger profile.  The
+/// transaction is shown before signing, and the signature is added to the file.
+#[derive(Debug, Parser)]
+pub struct SignTransaction {
+    /// File containing the transaction to sign
+    #[clap(long, value_parser)]
+    pub(crate) transaction_file: PathBuf,
+
+    /// File to write the signed transaction to
+    ///
+    /// Defaults to updating `--transaction-file` in place
+    #[clap(long, value_parser)]
+    pub(crate) output_file: Option<PathBuf>,
+
+    /// Address of the account to sign for
+    ///
+    /// Defaults to the account of the profile, or the address derived from the key
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) signer_address: Option<AccountAddress>,
+
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<OfflineTransactionSummary> for SignTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<OfflineTransactionSummary> {
+        let mut txn = OfflineTransaction::load(self.transaction_file.as_path())?;
+        let (signer, address) = AccountSigner::load_with_address(
+            &self.private_key_options,
+            self.encoding_options.encoding,
+            &self.profile_options,
+            self.signer_address,
+        )?;
+
+        txn.summary().print()?;
+        prompt_yes_with_override(
+            &format!("Do you want to sign this transaction as {}?", address),
+            self.prompt_options,
+        )?;
+
+        txn.sign(address, &signer)?;
+        txn.save(
+            self.output_file
+                .as_ref()
+                .unwrap_or(&self.transaction_file)
+                .as_path(),
+        )?;
+        Ok(txn.summary())
+    }
+}
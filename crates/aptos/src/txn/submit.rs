@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::OfflineTransaction;
+use crate::common::{
+    types::{
+        CliCommand, CliError, CliTypedResult, ProfileOptions, PromptOptions, RestOptions,
+        TransactionSummary,
+    },
+    utils::prompt_yes_with_override,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Submit a transaction signed with `aptos txn sign`
+///
+/// Every signer of the transaction must have signed it.
+#[derive(Debug, Parser)]
+pub struct SubmitTransaction {
+    /// File containing the signed transaction
+    #[clap(long, value_parser)]
+    pub(crate) transaction_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let txn = OfflineTransaction::load(self.transaction_file.as_path())?;
+        txn.summary().print()?;
+        let signed_txn = txn.into_signed_transaction()?;
+        prompt_yes_with_override(
+            "Do you want to submit this transaction?",
+            self.prompt_options,
+        )?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let response = client
+            .submit_and_wait(&signed_txn)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?;
+        Ok(TransactionSummary::from(&response.into_inner()))
+    }
+}
@@ -111,7 +111,7 @@ impl CoinActivity {
         let mut entry_function_id_str = None;
         if let Some(user_request) = maybe_user_request {
             entry_function_id_str = match &user_request.payload {
-                TransactionPayload::EntryFunctionPayload(payload) => Some(truncate_str(
+                Some(TransactionPayload::EntryFunctionPayload(payload)) => Some(truncate_str(
                     &payload.function.to_string(),
                     MAX_ENTRY_FUNCTION_LENGTH,
                 )),
@@ -39,7 +39,9 @@ impl NftPoints {
                 if !user_txn.info.success {
                     return None;
                 }
-                if let TransactionPayload::EntryFunctionPayload(entry_function_payload) = payload {
+                if let Some(TransactionPayload::EntryFunctionPayload(entry_function_payload)) =
+                    payload
+                {
                     if entry_function_payload.function.to_string() == contract {
                         let transaction_version = user_txn.info.version.0 as i64;
                         let owner_address = standardize_address(
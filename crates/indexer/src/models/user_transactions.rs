@@ -85,7 +85,7 @@ impl UserTransaction {
                 gas_unit_price: u64_to_bigdecimal(txn.request.gas_unit_price.0),
                 timestamp: parse_timestamp(txn.timestamp.0, version),
                 entry_function_id_str: match &txn.request.payload {
-                    TransactionPayload::EntryFunctionPayload(payload) => {
+                    Some(TransactionPayload::EntryFunctionPayload(payload)) => {
                         payload.function.to_string()
                     },
                     _ => String::default(),
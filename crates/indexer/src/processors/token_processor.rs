@@ -1080,7 +1080,7 @@ fn parse_v2_token(
             let txn_version = user_txn.info.version.0 as i64;
             let txn_timestamp = parse_timestamp(user_txn.timestamp.0, txn_version);
             let entry_function_id_str = match &user_txn.request.payload {
-                TransactionPayload::EntryFunctionPayload(payload) => Some(truncate_str(
+                Some(TransactionPayload::EntryFunctionPayload(payload)) => Some(truncate_str(
                     &payload.function.to_string(),
                     MAX_ENTRY_FUNCTION_LENGTH,
                 )),
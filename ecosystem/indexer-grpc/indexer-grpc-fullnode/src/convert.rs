@@ -776,7 +776,7 @@ pub fn convert_transaction(
                     max_gas_amount: ut.request.max_gas_amount.0,
                     gas_unit_price: ut.request.gas_unit_price.0,
                     expiration_timestamp_secs,
-                    payload: Some(convert_transaction_payload(&ut.request.payload)),
+                    payload: ut.request.payload.as_ref().map(convert_transaction_payload),
                     signature: convert_transaction_signature(&ut.request.signature),
                 }),
                 events: convert_events(&ut.events),
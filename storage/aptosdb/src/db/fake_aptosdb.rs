@@ -938,6 +938,17 @@ impl DbReader for FakeAptosDB {
         self.inner.indexer_enabled()
     }
 
+    fn get_account_participation_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        self.inner
+            .get_account_participation_versions(address, start_version, limit, ledger_version)
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         self.inner.get_state_storage_usage(version)
     }
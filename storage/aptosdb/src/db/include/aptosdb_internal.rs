@@ -13,6 +13,7 @@ impl AptosDB {
         hack_for_tests: bool,
         empty_buffered_state_for_restore: bool,
         skip_index_and_usage: bool,
+        indexer: Option<Arc<Indexer>>,
     ) -> Self {
         let ledger_db = Arc::new(ledger_db);
         let state_merkle_db = Arc::new(state_merkle_db);
@@ -40,8 +41,11 @@ impl AptosDB {
             skip_index_and_usage,
         ));
 
-        let ledger_pruner =
-            LedgerPrunerManager::new(Arc::clone(&ledger_db), pruner_config.ledger_pruner_config);
+        let ledger_pruner = LedgerPrunerManager::new(
+            Arc::clone(&ledger_db),
+            pruner_config.ledger_pruner_config,
+            indexer,
+        );
 
         AptosDB {
             ledger_db: Arc::clone(&ledger_db),
@@ -83,6 +87,17 @@ impl AptosDB {
             max_num_nodes_per_lru_cache_shard,
         )?;
 
+        // The indexer is opened first, as its account participation index is pruned along with
+        // the ledger.
+        let indexer = if !readonly && enable_indexer {
+            Some(Arc::new(Indexer::open(
+                db_paths.default_root_path(),
+                rocksdb_configs.index_db_config,
+            )?))
+        } else {
+            None
+        };
+
         let mut myself = Self::new_with_dbs(
             ledger_db,
             state_merkle_db,
@@ -92,24 +107,19 @@ impl AptosDB {
             readonly,
             empty_buffered_state_for_restore,
             rocksdb_configs.enable_storage_sharding,
+            indexer.clone(),
         );
 
-        if !readonly && enable_indexer {
-            myself.open_indexer(
-                db_paths.default_root_path(),
-                rocksdb_configs.index_db_config,
-            )?;
+        if let Some(indexer) = indexer {
+            myself.open_indexer(indexer)?;
         }
 
         Ok(myself)
     }
 
-    fn open_indexer(
-        &mut self,
-        db_root_path: impl AsRef<Path>,
-        rocksdb_config: RocksdbConfig,
-    ) -> Result<()> {
-        let indexer = Indexer::open(&db_root_path, rocksdb_config)?;
+    fn open_indexer(&mut self, indexer: Arc<Indexer>) -> Result<()> {
+        const BATCH_SIZE: Version = 10000;
+
         let ledger_next_version = self.get_synced_version().map_or(0, |v| v + 1);
         info!(
             indexer_next_version = indexer.next_version(),
@@ -124,7 +134,6 @@ impl AptosDB {
             let state_view = db.state_view_at_version(Some(ledger_next_version - 1))?;
             let annotator = AptosValueAnnotator::new(&state_view);
 
-            const BATCH_SIZE: Version = 10000;
             let mut next_version = indexer.next_version();
             while next_version < ledger_next_version {
                 info!(next_version = next_version, "AptosDB Indexer catching up. ",);
@@ -139,6 +148,41 @@ impl AptosDB {
                 next_version = end_version;
             }
         }
+
+        // The account participation index keeps its own progress, as it may have been
+        // introduced after the indexer was enabled.
+        let first_readable_version = self.ledger_pruner.get_min_readable_version();
+        indexer.skip_account_participation_to(std::cmp::min(
+            first_readable_version,
+            ledger_next_version,
+        ))?;
+        let mut next_version = indexer.account_participation_next_version();
+        while next_version < ledger_next_version {
+            info!(
+                next_version = next_version,
+                "AptosDB Indexer catching up on account participation. ",
+            );
+            let end_version = std::cmp::min(ledger_next_version, next_version + BATCH_SIZE);
+            let txns = (next_version..end_version)
+                .map(|version| self.ledger_db.transaction_db().get_transaction(version))
+                .collect::<Result<Vec<_>>>()?;
+            let events = (next_version..end_version)
+                .map(|version| self.ledger_db.event_db().get_events_by_version(version))
+                .collect::<Result<Vec<_>>>()?;
+            let write_sets = self
+                .ledger_db
+                .write_set_db()
+                .get_write_sets(next_version, end_version)?;
+            let txns_ref: Vec<_> = txns
+                .iter()
+                .zip(events.iter())
+                .zip(write_sets.iter())
+                .map(|((txn, events), write_set)| (txn, events.as_slice(), write_set))
+                .collect();
+            indexer.index_account_participation(next_version, &txns_ref)?;
+
+            next_version = end_version;
+        }
         info!("AptosDB Indexer caught up.");
 
         self.indexer = Some(indexer);
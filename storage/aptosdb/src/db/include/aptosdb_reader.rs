@@ -716,6 +716,28 @@ impl DbReader for AptosDB {
         self.indexer.is_some()
     }
 
+    fn get_account_participation_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        gauged_api("get_account_participation_versions", || {
+            error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+
+            match &self.indexer {
+                Some(indexer) => indexer.get_account_participation_versions(
+                    address,
+                    start_version,
+                    limit,
+                    ledger_version,
+                ),
+                None => bail!("Indexer not enabled."),
+            }
+        })
+    }
+
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         gauged_api("get_state_storage_usage", || {
             if let Some(v) = version {
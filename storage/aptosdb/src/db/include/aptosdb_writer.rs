@@ -617,6 +617,11 @@ impl AptosDB {
                 .start_timer();
             let write_sets: Vec<_> = txns_to_commit.iter().map(|txn| txn.write_set()).collect();
             indexer.index(self.state_store.clone(), first_version, &write_sets)?;
+            let txns: Vec<_> = txns_to_commit
+                .iter()
+                .map(|txn| (txn.transaction(), txn.events(), txn.write_set()))
+                .collect();
+            indexer.index_account_participation(first_version, &txns)?;
         }
 
         // Once everything is successfully persisted, update the latest in-memory ledger info.
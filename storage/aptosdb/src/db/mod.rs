@@ -28,7 +28,7 @@ use crate::{
     utils::new_sharded_kv_schema_batch,
 };
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, StorageDirPaths, NO_OP_STORAGE_PRUNER_CONFIG,
+    PrunerConfig, RocksdbConfigs, StorageDirPaths, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::HashValue;
 use aptos_db_indexer::Indexer;
@@ -97,7 +97,7 @@ pub struct AptosDB {
     ledger_pruner: LedgerPrunerManager,
     _rocksdb_property_reporter: RocksdbPropertyReporter,
     ledger_commit_lock: std::sync::Mutex<()>,
-    indexer: Option<Indexer>,
+    indexer: Option<Arc<Indexer>>,
     skip_index_and_usage: bool,
 }
 
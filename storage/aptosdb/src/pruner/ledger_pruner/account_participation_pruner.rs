@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::pruner::db_sub_pruner::DBSubPruner;
+use aptos_db_indexer::Indexer;
+use aptos_logger::info;
+use aptos_storage_interface::Result;
+use aptos_types::transaction::Version;
+use std::sync::Arc;
+
+/// Prunes the account participation index of the internal indexer along with the ledger, so
+/// that it doesn't refer to transactions which no longer exist.
+#[derive(Debug)]
+pub struct AccountParticipationPruner {
+    indexer: Arc<Indexer>,
+}
+
+impl DBSubPruner for AccountParticipationPruner {
+    fn name(&self) -> &str {
+        "AccountParticipationPruner"
+    }
+
+    fn prune(&self, current_progress: Version, target_version: Version) -> Result<()> {
+        self.indexer
+            .prune_account_participation(current_progress, target_version)
+    }
+}
+
+impl AccountParticipationPruner {
+    pub(in crate::pruner) fn new(
+        indexer: Arc<Indexer>,
+        metadata_progress: Version,
+    ) -> Result<Self> {
+        let progress = indexer.account_participation_pruner_progress()?;
+
+        let myself = AccountParticipationPruner { indexer };
+
+        info!(
+            progress = progress,
+            metadata_progress = metadata_progress,
+            "Catching up AccountParticipationPruner."
+        );
+        myself.prune(progress, metadata_progress)?;
+
+        Ok(myself)
+    }
+}
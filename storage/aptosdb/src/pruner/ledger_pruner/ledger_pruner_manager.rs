@@ -10,6 +10,7 @@ use crate::{
     },
 };
 use aptos_config::config::LedgerPrunerConfig;
+use aptos_db_indexer::Indexer;
 use aptos_infallible::Mutex;
 use aptos_storage_interface::Result;
 use aptos_types::transaction::{AtomicVersion, Version};
@@ -103,12 +104,18 @@ impl PrunerManager for LedgerPrunerManager {
 }
 
 impl LedgerPrunerManager {
-    /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(ledger_db: Arc<LedgerDb>, ledger_pruner_config: LedgerPrunerConfig) -> Self {
+    /// Creates a worker thread that waits on a channel for pruning commands. If the internal
+    /// indexer is given, its account participation index is pruned along with the ledger.
+    pub fn new(
+        ledger_db: Arc<LedgerDb>,
+        ledger_pruner_config: LedgerPrunerConfig,
+        indexer: Option<Arc<Indexer>>,
+    ) -> Self {
         let pruner_worker = if ledger_pruner_config.enable {
             Some(Self::init_pruner(
                 Arc::clone(&ledger_db),
                 ledger_pruner_config,
+                indexer,
             ))
         } else {
             None
@@ -135,9 +142,11 @@ impl LedgerPrunerManager {
     fn init_pruner(
         ledger_db: Arc<LedgerDb>,
         ledger_pruner_config: LedgerPrunerConfig,
+        indexer: Option<Arc<Indexer>>,
     ) -> PrunerWorker {
-        let pruner =
-            Arc::new(LedgerPruner::new(ledger_db).expect("Failed to create ledger pruner."));
+        let pruner = Arc::new(
+            LedgerPruner::new(ledger_db, indexer).expect("Failed to create ledger pruner."),
+        );
 
         PRUNER_WINDOW
             .with_label_values(&["ledger_pruner"])
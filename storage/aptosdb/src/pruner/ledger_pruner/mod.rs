@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod account_participation_pruner;
 mod event_store_pruner;
 mod ledger_metadata_pruner;
 pub(crate) mod ledger_pruner_manager;
@@ -17,6 +18,7 @@ use crate::{
         db_pruner::DBPruner,
         db_sub_pruner::DBSubPruner,
         ledger_pruner::{
+            account_participation_pruner::AccountParticipationPruner,
             event_store_pruner::EventStorePruner, ledger_metadata_pruner::LedgerMetadataPruner,
             transaction_accumulator_pruner::TransactionAccumulatorPruner,
             transaction_auxiliary_data_pruner::TransactionAuxiliaryDataPruner,
@@ -27,6 +29,7 @@ use crate::{
     transaction_store::TransactionStore,
 };
 use anyhow::anyhow;
+use aptos_db_indexer::Indexer;
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_logger::info;
 use aptos_storage_interface::Result;
@@ -112,7 +115,7 @@ impl DBPruner for LedgerPruner {
 }
 
 impl LedgerPruner {
-    pub fn new(ledger_db: Arc<LedgerDb>) -> Result<Self> {
+    pub fn new(ledger_db: Arc<LedgerDb>, indexer: Option<Arc<Indexer>>) -> Result<Self> {
         info!(name = LEDGER_PRUNER_NAME, "Initializing...");
 
         let ledger_metadata_pruner = Box::new(
@@ -157,18 +160,26 @@ impl LedgerPruner {
             metadata_progress,
         )?);
 
+        let mut sub_pruners: Vec<Box<dyn DBSubPruner + Send + Sync>> = vec![
+            event_store_pruner,
+            transaction_accumulator_pruner,
+            transaction_auxiliary_data_pruner,
+            transaction_info_pruner,
+            transaction_pruner,
+            write_set_pruner,
+        ];
+        if let Some(indexer) = indexer {
+            sub_pruners.push(Box::new(AccountParticipationPruner::new(
+                indexer,
+                metadata_progress,
+            )?));
+        }
+
         let pruner = LedgerPruner {
             target_version: AtomicVersion::new(metadata_progress),
             progress: AtomicVersion::new(metadata_progress),
             ledger_metadata_pruner,
-            sub_pruners: vec![
-                event_store_pruner,
-                transaction_accumulator_pruner,
-                transaction_auxiliary_data_pruner,
-                transaction_info_pruner,
-                transaction_pruner,
-                write_set_pruner,
-            ],
+            sub_pruners,
         };
 
         info!(
@@ -13,7 +13,9 @@ use crate::{
     db::INDEX_DB_NAME,
     metadata::{MetadataKey, MetadataValue},
     schema::{
-        column_families, indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
+        account_participation::AccountParticipationSchema,
+        account_participation_by_version::AccountParticipationByVersionSchema, column_families,
+        indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
     },
 };
 use aptos_config::config::RocksdbConfig;
@@ -27,21 +29,26 @@ use aptos_storage_interface::{
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
+    account_config::{fungible_store::FungibleStoreResource, ObjectGroupResource},
+    contract_event::ContractEvent,
+    event::EventHandle,
     state_store::{
         state_key::{inner::StateKeyInner, StateKey},
         table::{TableHandle, TableInfo},
         StateView,
     },
-    transaction::{AtomicVersion, Version},
+    transaction::{AtomicVersion, Transaction, Version},
     write_set::{WriteOp, WriteSet},
 };
 use bytes::Bytes;
 use move_core_types::{
     ident_str,
     language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
 };
+use serde::Deserialize;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryInto,
     sync::{atomic::Ordering, Arc},
 };
@@ -50,6 +57,7 @@ use std::{
 pub struct Indexer {
     db: DB,
     next_version: AtomicVersion,
+    account_participation_next_version: AtomicVersion,
 }
 
 impl Indexer {
@@ -69,10 +77,16 @@ impl Indexer {
         let next_version = db
             .get::<IndexerMetadataSchema>(&MetadataKey::LatestVersion)?
             .map_or(0, |v| v.expect_version());
+        let account_participation_next_version = db
+            .get::<IndexerMetadataSchema>(&MetadataKey::AccountParticipationLatestVersion)?
+            .map_or(0, |v| v.expect_version() + 1);
 
         Ok(Self {
             db,
             next_version: AtomicVersion::new(next_version),
+            account_participation_next_version: AtomicVersion::new(
+                account_participation_next_version,
+            ),
         })
     }
 
@@ -150,6 +164,190 @@ impl Indexer {
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    /// Indexes the accounts participating in each of the given transactions. Transactions are
+    /// given along with their events and write sets, starting from `first_version`.
+    pub fn index_account_participation(
+        &self,
+        first_version: Version,
+        txns: &[(&Transaction, &[ContractEvent], &WriteSet)],
+    ) -> Result<()> {
+        let next_version = self.account_participation_next_version();
+        db_ensure!(
+            first_version <= next_version,
+            "Indexer expects to see continuous transaction versions. Expecting: {}, got: {}",
+            next_version,
+            first_version,
+        );
+        let end_version = first_version + txns.len() as Version;
+        if end_version <= next_version {
+            return Ok(());
+        }
+
+        let batch = SchemaBatch::new();
+        for (version, (txn, events, write_set)) in (first_version..end_version).zip(txns) {
+            if version < next_version {
+                continue;
+            }
+            for address in participants(txn, events, write_set) {
+                batch.put::<AccountParticipationSchema>(&(address, version), &())?;
+                batch.put::<AccountParticipationByVersionSchema>(&(version, address), &())?;
+            }
+        }
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataKey::AccountParticipationLatestVersion,
+            &MetadataValue::Version(end_version - 1),
+        )?;
+        self.db.write_schemas(batch)?;
+        self.account_participation_next_version
+            .store(end_version, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Marks the versions before `version` as indexed without indexing them, for when they are
+    /// no longer available, e.g. because they were pruned.
+    pub fn skip_account_participation_to(&self, version: Version) -> Result<()> {
+        if version <= self.account_participation_next_version() {
+            return Ok(());
+        }
+        warn!(
+            next_version = self.account_participation_next_version(),
+            skip_to = version,
+            "Skipping transactions for the account participation index.",
+        );
+        self.db.put::<IndexerMetadataSchema>(
+            &MetadataKey::AccountParticipationLatestVersion,
+            &MetadataValue::Version(version - 1),
+        )?;
+        self.account_participation_next_version
+            .store(version, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn account_participation_next_version(&self) -> Version {
+        self.account_participation_next_version
+            .load(Ordering::Relaxed)
+    }
+
+    /// Deletes the account participation of the transactions from `begin` to `end` (exclusive)
+    /// from the index, along with the ledger data they refer to.
+    pub fn prune_account_participation(&self, begin: Version, end: Version) -> Result<()> {
+        let batch = SchemaBatch::new();
+        let mut iter = self.db.iter::<AccountParticipationByVersionSchema>()?;
+        iter.seek(&(begin, AccountAddress::ZERO))?;
+        for res in iter {
+            let ((version, address), ()) = res?;
+            if version >= end {
+                break;
+            }
+            batch.delete::<AccountParticipationByVersionSchema>(&(version, address))?;
+            batch.delete::<AccountParticipationSchema>(&(address, version))?;
+        }
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataKey::AccountParticipationPrunerProgress,
+            &MetadataValue::Version(end),
+        )?;
+        self.db.write_schemas(batch)
+    }
+
+    /// Returns the version up to which the account participation index has been pruned.
+    pub fn account_participation_pruner_progress(&self) -> Result<Version> {
+        Ok(self
+            .db
+            .get::<IndexerMetadataSchema>(&MetadataKey::AccountParticipationPrunerProgress)?
+            .map_or(0, |v| v.expect_version()))
+    }
+
+    /// Returns the versions of up to `limit` transactions `address` participated in, in
+    /// ascending order, starting from `start_version` and up to `ledger_version`.
+    pub fn get_account_participation_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        let mut iter = self.db.iter::<AccountParticipationSchema>()?;
+        iter.seek(&(address, start_version))?;
+
+        let mut versions = vec![];
+        for res in iter {
+            let ((account, version), ()) = res?;
+            if account != address || version > ledger_version || versions.len() as u64 >= limit {
+                break;
+            }
+            versions.push(version);
+        }
+        Ok(versions)
+    }
+}
+
+/// Collects the addresses participating in a transaction: its signers and fee payer, the owners
+/// of the event handles it emitted events to, the owners of the resources it wrote and the owners
+/// of the fungible stores it wrote, e.g. the recipient of a fungible asset transfer.
+///
+/// Note that module events carry no owner, so they are not taken into account. Apart from
+/// fungible stores, neither are the owners of objects, which are not necessarily the accounts
+/// the objects are stored under.
+fn participants(
+    txn: &Transaction,
+    events: &[ContractEvent],
+    write_set: &WriteSet,
+) -> BTreeSet<AccountAddress> {
+    let mut addresses = BTreeSet::new();
+    if let Transaction::UserTransaction(signed_txn) = txn {
+        let authenticator = signed_txn.authenticator_ref();
+        addresses.insert(signed_txn.sender());
+        addresses.extend(authenticator.secondary_signer_addresses());
+        addresses.extend(authenticator.fee_payer_address());
+    }
+    for event in events {
+        if let Ok(event) = event.v1() {
+            addresses.insert(event.key().get_creator_address());
+        }
+    }
+    for (state_key, write_op) in write_set.iter() {
+        if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+            addresses.insert(access_path.address);
+            if let (Ok(Path::ResourceGroup(tag)), Some(bytes)) =
+                (Path::try_from(&access_path.path), write_op.bytes())
+            {
+                if tag == ObjectGroupResource::struct_tag() {
+                    addresses.extend(fungible_store_owner(bytes));
+                }
+            }
+        }
+    }
+    addresses
+}
+
+/// The fields of `0x1::object::ObjectCore`.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+#[allow(dead_code)]
+struct ObjectCore {
+    guid_creation_num: u64,
+    owner: AccountAddress,
+    allow_ungated_transfer: bool,
+    transfer_events: EventHandle,
+}
+
+/// Returns the owner of the object with the given object group, if the object is a
+/// fungible store.
+fn fungible_store_owner(bytes: &Bytes) -> Option<AccountAddress> {
+    let group: BTreeMap<StructTag, Bytes> = bcs::from_bytes(bytes).ok()?;
+    if !group.contains_key(&FungibleStoreResource::struct_tag()) {
+        return None;
+    }
+    let object_core_tag = StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("object").to_owned(),
+        name: ident_str!("ObjectCore").to_owned(),
+        type_args: vec![],
+    };
+    let object_core: ObjectCore = bcs::from_bytes(group.get(&object_core_tag)?).ok()?;
+    Some(object_core.owner)
 }
 
 struct TableInfoParser<'a, R> {
@@ -307,3 +505,97 @@ impl<'a, R: StateView> TableInfoParser<'a, R> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        account_address::create_derived_object_address, event::EventKey, write_set::WriteSetMut,
+    };
+
+    /// Returns a write set which writes the primary APT store of `owner`.
+    fn fungible_store_write_set(owner: AccountAddress) -> WriteSet {
+        let store_address = create_derived_object_address(owner, AccountAddress::TEN);
+        let object_core = ObjectCore {
+            guid_creation_num: 0,
+            owner,
+            allow_ungated_transfer: false,
+            transfer_events: EventHandle::new(EventKey::new(0, store_address), 0),
+        };
+        let object_core_tag = StructTag {
+            address: AccountAddress::ONE,
+            module: ident_str!("object").to_owned(),
+            name: ident_str!("ObjectCore").to_owned(),
+            type_args: vec![],
+        };
+        let store = FungibleStoreResource::new(AccountAddress::TEN, 100, false);
+        let group: BTreeMap<StructTag, Bytes> = [
+            (object_core_tag, bcs::to_bytes(&object_core).unwrap().into()),
+            (
+                FungibleStoreResource::struct_tag(),
+                bcs::to_bytes(&store).unwrap().into(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        WriteSetMut::new(vec![(
+            StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag()),
+            WriteOp::legacy_modification(bcs::to_bytes(&group).unwrap().into()),
+        )])
+        .freeze()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fungible_store_owner_participates() {
+        let owner = AccountAddress::random();
+        let write_set = fungible_store_write_set(owner);
+        let addresses = participants(&Transaction::dummy(), &[], &write_set);
+        assert!(addresses.contains(&owner));
+        assert!(addresses.contains(&create_derived_object_address(owner, AccountAddress::TEN)));
+    }
+
+    #[test]
+    fn test_prune_account_participation() {
+        let tmp_dir = TempPath::new();
+        tmp_dir.create_as_dir().unwrap();
+        let indexer = Indexer::open(&tmp_dir, RocksdbConfig::default()).unwrap();
+        let (alice, bob) = (AccountAddress::random(), AccountAddress::random());
+        let txn = Transaction::dummy();
+        let events: &[ContractEvent] = &[];
+        let (alice_write_set, bob_write_set) = (
+            fungible_store_write_set(alice),
+            fungible_store_write_set(bob),
+        );
+        indexer
+            .index_account_participation(
+                0,
+                &[
+                    (&txn, events, &alice_write_set),
+                    (&txn, events, &bob_write_set),
+                    (&txn, events, &alice_write_set),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            indexer
+                .get_account_participation_versions(alice, 0, 10, 2)
+                .unwrap(),
+            vec![0, 2]
+        );
+
+        indexer.prune_account_participation(0, 2).unwrap();
+        assert_eq!(indexer.account_participation_pruner_progress().unwrap(), 2);
+        assert_eq!(
+            indexer
+                .get_account_participation_versions(alice, 0, 10, 2)
+                .unwrap(),
+            vec![2]
+        );
+        assert!(indexer
+            .get_account_participation_versions(bob, 0, 10, 2)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -22,4 +22,6 @@ impl MetadataValue {
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
 pub(crate) enum MetadataKey {
     LatestVersion,
+    AccountParticipationLatestVersion,
+    AccountParticipationPrunerProgress,
 }
@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of the transactions every account
+//! participated in, be it as the sender, a secondary signer, the fee payer, the owner of an
+//! event handle an event was emitted to, or the owner of a resource that was written.
+//!
+//! ```text
+//! |<-------key------->|<-value->|
+//! | address | txn_ver |  empty  |
+//! ```
+
+use crate::schema::ACCOUNT_PARTICIPATION_CF_NAME;
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    AccountParticipationSchema,
+    Key,
+    (),
+    ACCOUNT_PARTICIPATION_CF_NAME
+);
+
+type Key = (AccountAddress, Version);
+
+impl KeyCodec<AccountParticipationSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref account_address, version) = *self;
+
+        let mut encoded = account_address.to_vec();
+        encoded.extend_from_slice(&version.to_be_bytes());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == size_of::<Self>(),
+            "Unexpected data len {}, expected {}.",
+            data.len(),
+            size_of::<Self>(),
+        );
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = Version::from_be_bytes(data[AccountAddress::LENGTH..].try_into()?);
+
+        Ok((address, version))
+    }
+}
+
+impl ValueCodec<AccountParticipationSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.is_empty(),
+            "Unexpected data len {}, expected 0.",
+            data.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the reverse of the account participation
+//! index, which lists the accounts that participated in every transaction. It is used to prune
+//! the account participation index by version.
+//!
+//! ```text
+//! |<-------key------->|<-value->|
+//! | txn_ver | address |  empty  |
+//! ```
+
+use crate::schema::ACCOUNT_PARTICIPATION_BY_VERSION_CF_NAME;
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    AccountParticipationByVersionSchema,
+    Key,
+    (),
+    ACCOUNT_PARTICIPATION_BY_VERSION_CF_NAME
+);
+
+type Key = (Version, AccountAddress);
+
+impl KeyCodec<AccountParticipationByVersionSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (version, ref account_address) = *self;
+
+        let mut encoded = version.to_be_bytes().to_vec();
+        encoded.extend_from_slice(account_address.as_ref());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == size_of::<Self>(),
+            "Unexpected data len {}, expected {}.",
+            data.len(),
+            size_of::<Self>(),
+        );
+
+        let version = Version::from_be_bytes(data[..size_of::<Version>()].try_into()?);
+        let address = AccountAddress::try_from(&data[size_of::<Version>()..])?;
+
+        Ok((version, address))
+    }
+}
+
+impl ValueCodec<AccountParticipationByVersionSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.is_empty(),
+            "Unexpected data len {}, expected 0.",
+            data.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
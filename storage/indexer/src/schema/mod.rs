@@ -6,6 +6,8 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod account_participation;
+pub(crate) mod account_participation_by_version;
 pub(crate) mod indexer_metadata;
 pub(crate) mod table_info;
 
@@ -14,11 +16,16 @@ use aptos_schemadb::ColumnFamilyName;
 pub const DEFAULT_COLUMN_FAMILY_NAME: ColumnFamilyName = "default";
 pub const INDEXER_METADATA_CF_NAME: ColumnFamilyName = "indexer_metadata";
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
+pub const ACCOUNT_PARTICIPATION_CF_NAME: ColumnFamilyName = "account_participation";
+pub const ACCOUNT_PARTICIPATION_BY_VERSION_CF_NAME: ColumnFamilyName =
+    "account_participation_by_version";
 
 pub fn column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         INDEXER_METADATA_CF_NAME,
         TABLE_INFO_CF_NAME,
+        ACCOUNT_PARTICIPATION_CF_NAME,
+        ACCOUNT_PARTICIPATION_BY_VERSION_CF_NAME,
     ]
 }
@@ -453,6 +453,16 @@ pub trait DbReader: Send + Sync {
         /// Returns whether the internal indexer DB has been enabled or not
         fn indexer_enabled(&self) -> bool;
 
+        /// Returns the versions of up to `limit` transactions `address` participated in, in
+        /// ascending order, starting from `start_version`. Requires the internal indexer.
+        fn get_account_participation_versions(
+            &self,
+            address: AccountAddress,
+            start_version: Version,
+            limit: u64,
+            ledger_version: Version,
+        ) -> Result<Vec<Version>>;
+
         /// Returns state storage usage at the end of an epoch.
         fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage>;
     ); // end delegated
@@ -5,6 +5,7 @@ use crate::smoke_test_environment::SwarmBuilder;
 use aptos::{account::create::DEFAULT_FUNDED_COINS, common::types::GasOptions};
 use aptos_crypto::{PrivateKey, ValidCryptoMaterialStringExt};
 use aptos_keygen::KeyGen;
+use aptos_temppath::TempPath;
 
 #[tokio::test]
 async fn test_account_flow() {
@@ -136,3 +137,90 @@ async fn test_account_key_rotation() {
         .await
         .expect("New key should be able to transfer");
 }
+
+#[tokio::test]
+async fn test_transfer_with_fee_payer_transaction() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(3)
+        .await;
+
+    // The fee payer signs the transfer offline
+    let transaction_file = TempPath::new();
+    let transaction_path = transaction_file.path().to_path_buf();
+    cli.build_transfer_transaction(0, 1, 5, Some(2), transaction_path.clone())
+        .await
+        .unwrap();
+    cli.sign_transaction(2, transaction_path.clone())
+        .await
+        .unwrap();
+
+    // A transaction with a different payload is rejected
+    cli.transfer_coins_with_fee_payer_transaction(0, 1, 6, transaction_path.clone())
+        .await
+        .expect_err("Fee payer signature should not cover a different amount");
+
+    let summary = cli
+        .transfer_coins_with_fee_payer_transaction(0, 1, 5, transaction_path)
+        .await
+        .unwrap();
+    assert!(summary.success);
+
+    // The sender only pays for the transfer, and the fee payer pays for gas
+    cli.assert_account_balance_now(0, DEFAULT_FUNDED_COINS - 5)
+        .await;
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS + 5)
+        .await;
+    cli.assert_account_balance_now(2, DEFAULT_FUNDED_COINS - summary.octa_spent())
+        .await;
+}
+
+#[tokio::test]
+async fn test_offline_transaction_flow() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(3)
+        .await;
+    let transaction_file = TempPath::new();
+    let transaction_path = transaction_file.path().to_path_buf();
+
+    let summary = cli
+        .build_transfer_transaction(0, 1, 5, Some(2), transaction_path.clone())
+        .await
+        .unwrap();
+    assert_eq!(summary.sender, cli.account_id(0));
+    assert_eq!(summary.fee_payer, Some(cli.account_id(2)));
+    assert_eq!(summary.missing_signatures, vec![
+        cli.account_id(0),
+        cli.account_id(2)
+    ]);
+
+    // Only signers of the transaction can sign it
+    cli.sign_transaction(1, transaction_path.clone())
+        .await
+        .expect_err("Receiver should not be able to sign");
+
+    let summary = cli
+        .sign_transaction(0, transaction_path.clone())
+        .await
+        .unwrap();
+    assert_eq!(summary.signed_by, vec![cli.account_id(0)]);
+    cli.submit_signed_transaction(transaction_path.clone())
+        .await
+        .expect_err("Transaction should not be submitted without the fee payer signature");
+
+    cli.sign_transaction(2, transaction_path.clone())
+        .await
+        .unwrap();
+    let summary = cli
+        .submit_signed_transaction(transaction_path)
+        .await
+        .unwrap();
+    assert_eq!(summary.success, Some(true));
+
+    // The fee payer paid for gas
+    cli.assert_account_balance_now(0, DEFAULT_FUNDED_COINS - 5)
+        .await;
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS + 5)
+        .await;
+}
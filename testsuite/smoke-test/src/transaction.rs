@@ -94,11 +94,11 @@ async fn test_external_transaction_signer() {
             assert_eq!(user_txn.request.gas_unit_price.0, test_gas_unit_price);
             assert_eq!(user_txn.request.max_gas_amount.0, test_max_gas_amount);
 
-            if let TransactionPayload::EntryFunctionPayload(EntryFunctionPayload {
+            if let Some(TransactionPayload::EntryFunctionPayload(EntryFunctionPayload {
                 function: _,
                 type_arguments: _,
                 arguments,
-            }) = user_txn.request.payload
+            })) = user_txn.request.payload
             {
                 assert_eq!(
                     arguments
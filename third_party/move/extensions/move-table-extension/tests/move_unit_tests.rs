@@ -30,6 +30,7 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>) {
         natives,
         ChangeSet::new(),
         None,
+        None,
         /* compute_coverage */ false,
         &mut std::io::stdout(),
     )
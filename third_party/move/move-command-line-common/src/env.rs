@@ -78,3 +78,7 @@ pub static MOVE_HOME: Lazy<String> = Lazy::new(|| {
         )
     })
 });
+
+/// An environment variable which can be set to the registry that dependencies on versions of
+/// packages are resolved in, a directory or the URL of a git repository.
+pub const MOVE_REGISTRY_ENV_VAR: &str = "MOVE_REGISTRY";
@@ -61,6 +61,16 @@ pub fn acquires_checker(env: &GlobalEnv) {
     }
 }
 
+/// Returns the resources of the module that each of its functions acquires, directly or
+/// by calling other functions of the module.
+pub(crate) fn acquired_resources(module: ModuleEnv) -> BTreeMap<FunId, BTreeSet<StructId>> {
+    AcquireChecker::new(module)
+        .analyze()
+        .into_iter()
+        .map(|(fun_id, acquired)| (fun_id, acquired.0.into_keys().collect()))
+        .collect()
+}
+
 /// Gets the acquired resources declared by `acquires R`
 fn get_acquired_resources(fun_env: &FunctionEnv) -> BTreeMap<StructId, Loc> {
     if let Some(access_specifiers) = fun_env.get_access_specifiers() {
@@ -12,6 +12,7 @@ mod file_format_generator;
 pub mod flow_insensitive_checkers;
 pub mod function_checker;
 pub mod inliner;
+pub mod linter;
 pub mod logging;
 pub mod options;
 pub mod pipeline;
@@ -24,6 +25,7 @@ use crate::{
         lambda_lifter, lambda_lifter::LambdaLiftingOptions, rewrite_target::RewritingScope,
         seqs_in_binop_checker, spec_checker, spec_rewriter, EnvProcessorPipeline,
     },
+    linter::{LintConfig, LintDiagnostic},
     pipeline::{
         ability_processor::AbilityProcessor, avail_copies_analysis::AvailCopiesAnalysisProcessor,
         copy_propagation::CopyPropagation, dead_store_elimination::DeadStoreElimination,
@@ -196,6 +198,22 @@ pub fn run_checker(options: Options) -> anyhow::Result<GlobalEnv> {
     Ok(env)
 }
 
+/// Run the type checker and, if there are no errors, the linter on the compilation targets,
+/// returning the global env and the violations of lint rules. The violations are not
+/// reported to the env.
+pub fn run_checker_and_linter(
+    options: Options,
+    lint_config: &LintConfig,
+) -> anyhow::Result<(GlobalEnv, Vec<LintDiagnostic>)> {
+    let env = run_checker(options)?;
+    if env.has_errors() {
+        return Ok((env, vec![]));
+    }
+    info!("Linting");
+    let diags = linter::run_linter(&env, lint_config);
+    Ok((env, diags))
+}
+
 /// Run the type checker as well as the AST rewriting pipeline and related additional
 /// checks, returning the global env (with errors if encountered). The result
 /// fails not on context checking errors, but possibly on i/o errors.
@@ -0,0 +1,465 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An opt-in linter, run on the model of the compilation targets after type checking.
+//!
+//! Each lint is a `LintRule`, reported at the level the `LintConfig` sets for it. Code can
+//! change the level of rules for a module or function with the attributes
+//! `#[lint::allow(rule, ..)]` and `#[lint::deny(rule, ..)]`, where the attributes of a
+//! function take precedence over those of its module.
+//!
+//! The rules are:
+//! - `needless_borrow`: borrowing a dereference, as in `&*r`, or dereferencing a borrow,
+//!   as in `*&x`.
+//! - `unnecessary_copy`: an explicit `copy x` where `x` is not used afterwards, so it can
+//!   be moved instead.
+//! - `bool_comparison`: comparing a boolean with `true` or `false`.
+//! - `unused_acquires`: an `acquires R` annotation for a resource the function doesn't
+//!   acquire. The compiler checks this for functions other than inline ones.
+//! - `public_entry_returns_value`: a public entry function with a return value, which is
+//!   discarded when the function is called in a transaction.
+//! - `self_assignment`: assigning a variable or location to itself.
+
+use crate::acquires_checker;
+use codespan_reporting::diagnostic::Severity;
+use move_binary_format::file_format::AccessKind;
+use move_model::{
+    ast::{
+        Attribute, ExpData, Operation, Pattern, ResourceSpecifier, TempIndex, Value,
+        VisitorPosition,
+    },
+    model::{
+        FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, NodeId, Parameter, StructId, Visibility,
+    },
+    symbol::Symbol,
+    ty::{ReferenceKind, Type},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+/// Attribute which allows the given lint rules for a module or function.
+pub const LINT_ALLOW_ATTRIBUTE: &str = "lint::allow";
+/// Attribute which denies the given lint rules for a module or function.
+pub const LINT_DENY_ATTRIBUTE: &str = "lint::deny";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintRule {
+    NeedlessBorrow,
+    UnnecessaryCopy,
+    BoolComparison,
+    UnusedAcquires,
+    PublicEntryReturnsValue,
+    SelfAssignment,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 6] = [
+        LintRule::NeedlessBorrow,
+        LintRule::UnnecessaryCopy,
+        LintRule::BoolComparison,
+        LintRule::UnusedAcquires,
+        LintRule::PublicEntryReturnsValue,
+        LintRule::SelfAssignment,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::NeedlessBorrow => "needless_borrow",
+            LintRule::UnnecessaryCopy => "unnecessary_copy",
+            LintRule::BoolComparison => "bool_comparison",
+            LintRule::UnusedAcquires => "unused_acquires",
+            LintRule::PublicEntryReturnsValue => "public_entry_returns_value",
+            LintRule::SelfAssignment => "self_assignment",
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LintRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LintRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown lint rule `{}`, expected one of: {}",
+                    s,
+                    LintRule::ALL.map(|rule| rule.name()).join(", ")
+                )
+            })
+    }
+}
+
+/// The level a lint rule is reported at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The rule is not checked.
+    Allow,
+    /// Violations are reported as warnings.
+    #[default]
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+impl LintLevel {
+    fn severity(&self) -> Option<Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+/// The levels of the lint rules. Rules are reported as warnings unless configured
+/// otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct LintConfig {
+    levels: BTreeMap<LintRule, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: LintRule) -> LintLevel {
+        self.levels.get(&rule).copied().unwrap_or_default()
+    }
+
+    pub fn set_level(&mut self, rule: LintRule, level: LintLevel) {
+        self.levels.insert(rule, level);
+    }
+
+    /// Returns the config with the levels set by lint attributes applied.
+    fn with_attributes(&self, env: &GlobalEnv, attributes: &[Attribute]) -> Self {
+        let mut config = self.clone();
+        for attribute in attributes {
+            if let Attribute::Apply(_, name, args) = attribute {
+                let level = match env.symbol_pool().string(*name).as_str() {
+                    LINT_ALLOW_ATTRIBUTE => LintLevel::Allow,
+                    LINT_DENY_ATTRIBUTE => LintLevel::Deny,
+                    _ => continue,
+                };
+                for arg in args {
+                    let rule_name = env.symbol_pool().string(arg.name());
+                    match rule_name.parse() {
+                        Ok(rule) => config.set_level(rule, level),
+                        Err(err) => {
+                            let loc = env.get_node_loc(attribute_node_id(arg));
+                            env.diag(Severity::Warning, &loc, &err)
+                        },
+                    }
+                }
+            }
+        }
+        config
+    }
+}
+
+fn attribute_node_id(attribute: &Attribute) -> NodeId {
+    match attribute {
+        Attribute::Apply(id, _, _) | Attribute::Assign(id, _, _) => *id,
+    }
+}
+
+/// A violation of a lint rule.
+#[derive(Clone, Debug)]
+pub struct LintDiagnostic {
+    pub rule: LintRule,
+    pub severity: Severity,
+    pub loc: Loc,
+    pub message: String,
+}
+
+/// Runs the linter on the target modules of the environment, returning the violations of
+/// the lint rules which aren't allowed.
+pub fn run_linter(env: &GlobalEnv, config: &LintConfig) -> Vec<LintDiagnostic> {
+    let mut diags = vec![];
+    for module in env.get_modules() {
+        if module.is_target() {
+            let module_config = config.with_attributes(env, module.get_attributes());
+            let acquired = acquires_checker::acquired_resources(module.clone());
+            for fun in module.get_functions() {
+                let config = module_config.with_attributes(env, fun.get_attributes());
+                let mut linter = FunctionLinter {
+                    env,
+                    config: &config,
+                    diags: &mut diags,
+                };
+                linter.check_public_entry_returns_value(&fun);
+                linter.check_unused_acquires(&module, &fun, &acquired);
+                if let Some(def) = fun.get_def() {
+                    linter.check_body(&fun.get_parameters(), def);
+                }
+            }
+        }
+    }
+    diags
+}
+
+/// Reports the violations of lint rules to the environment.
+pub fn report_lint_diagnostics(env: &GlobalEnv, diags: &[LintDiagnostic]) {
+    for diag in diags {
+        env.diag_with_notes(diag.severity, &diag.loc, &diag.message, vec![format!(
+            "this is the `{}` lint, which can be allowed with `#[{}({})]`",
+            diag.rule, LINT_ALLOW_ATTRIBUTE, diag.rule
+        )]);
+    }
+}
+
+/// A variable of a function body: a local, or a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Var {
+    Local(Symbol),
+    Param(TempIndex),
+}
+
+impl Var {
+    fn of(exp: &ExpData) -> Option<Self> {
+        match exp {
+            ExpData::LocalVar(_, sym) => Some(Var::Local(*sym)),
+            ExpData::Temporary(_, idx) => Some(Var::Param(*idx)),
+            _ => None,
+        }
+    }
+}
+
+struct FunctionLinter<'a> {
+    env: &'a GlobalEnv,
+    config: &'a LintConfig,
+    diags: &'a mut Vec<LintDiagnostic>,
+}
+
+impl<'a> FunctionLinter<'a> {
+    fn report(&mut self, rule: LintRule, loc: Loc, message: impl Into<String>) {
+        if let Some(severity) = self.config.level(rule).severity() {
+            self.diags.push(LintDiagnostic {
+                rule,
+                severity,
+                loc,
+                message: message.into(),
+            });
+        }
+    }
+
+    fn check_public_entry_returns_value(&mut self, fun: &FunctionEnv) {
+        if fun.is_entry()
+            && fun.visibility() == Visibility::Public
+            && !fun.get_result_type().is_unit()
+        {
+            self.report(
+                LintRule::PublicEntryReturnsValue,
+                fun.get_id_loc(),
+                "public entry function returns a value, which is discarded when it is called \
+                 in a transaction",
+            );
+        }
+    }
+
+    fn check_unused_acquires(
+        &mut self,
+        module: &ModuleEnv,
+        fun: &FunctionEnv,
+        acquired: &BTreeMap<FunId, BTreeSet<StructId>>,
+    ) {
+        let acquired = acquired.get(&fun.get_id());
+        for specifier in fun.get_access_specifiers().unwrap_or_default() {
+            if specifier.kind != AccessKind::Acquires || specifier.negated {
+                continue;
+            }
+            if let (loc, ResourceSpecifier::Resource(struct_id)) = &specifier.resource {
+                if struct_id.module_id == module.get_id()
+                    && !acquired.is_some_and(|acquired| acquired.contains(&struct_id.id))
+                {
+                    self.report(
+                        LintRule::UnusedAcquires,
+                        loc.clone(),
+                        format!(
+                            "function doesn't acquire `{}`",
+                            module
+                                .get_struct(struct_id.id)
+                                .get_name()
+                                .display(self.env.symbol_pool())
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_body(&mut self, params: &[Parameter], body: &ExpData) {
+        // Explicit copies, with their position in the visit, the variable copied, and
+        // whether they are in a loop.
+        let mut copies: Vec<(usize, Var, NodeId, bool)> = vec![];
+        let mut uses: BTreeMap<Var, usize> = BTreeMap::new();
+        let mut borrowed: BTreeSet<Var> = BTreeSet::new();
+        let mut position = 0;
+        let mut loop_depth = 0;
+        let mut spec_depth = 0;
+        let env = self.env;
+        body.visit_positions(&mut |visitor_position, exp| {
+            match (&visitor_position, exp) {
+                (VisitorPosition::Pre, ExpData::SpecBlock(..)) => spec_depth += 1,
+                (VisitorPosition::Post, ExpData::SpecBlock(..)) => spec_depth -= 1,
+                (VisitorPosition::Pre, ExpData::Loop(..)) => loop_depth += 1,
+                (VisitorPosition::Post, ExpData::Loop(..)) => loop_depth -= 1,
+                _ => {},
+            }
+            if spec_depth > 0 || !matches!(visitor_position, VisitorPosition::Pre) {
+                return true;
+            }
+            position += 1;
+            match exp {
+                ExpData::Call(id, Operation::Borrow(kind), args) => {
+                    if let Some(var) = Var::of(&args[0]) {
+                        borrowed.insert(var);
+                    }
+                    self.check_borrow_of_deref(*id, *kind, &args[0]);
+                },
+                ExpData::Call(id, Operation::Deref, args) => {
+                    if let ExpData::Call(_, Operation::Borrow(_), _) = args[0].as_ref() {
+                        self.report(
+                            LintRule::NeedlessBorrow,
+                            env.get_node_loc(*id),
+                            "needless dereference of a borrow, use the value directly",
+                        );
+                    }
+                },
+                ExpData::Call(id, Operation::Eq | Operation::Neq, args) => {
+                    self.check_bool_comparison(*id, args[0].as_ref(), args[1].as_ref());
+                },
+                ExpData::Call(id, Operation::Copy, args) => {
+                    if let Some(var) = Var::of(&args[0]) {
+                        copies.push((position, var, *id, loop_depth > 0));
+                    }
+                },
+                ExpData::LocalVar(..) | ExpData::Temporary(..) => {
+                    let var = Var::of(exp).expect("variable");
+                    uses.insert(var, position);
+                },
+                ExpData::Assign(id, pattern, rhs) => {
+                    for (_, sym) in pattern.vars() {
+                        uses.insert(Var::Local(sym), position);
+                    }
+                    if let Pattern::Var(_, sym) = pattern {
+                        let rhs_sym = match rhs.as_ref() {
+                            ExpData::LocalVar(_, sym) => Some(*sym),
+                            ExpData::Temporary(_, idx) => params.get(*idx).map(|param| param.0),
+                            _ => None,
+                        };
+                        if rhs_sym == Some(*sym) {
+                            self.report(
+                                LintRule::SelfAssignment,
+                                env.get_node_loc(*id),
+                                format!(
+                                    "`{}` is assigned to itself",
+                                    sym.display(env.symbol_pool())
+                                ),
+                            );
+                        }
+                    }
+                },
+                ExpData::Mutate(id, lhs, rhs) => {
+                    // The left hand side is a reference to the location assigned to.
+                    let self_assigned = match (lhs.as_ref(), rhs.as_ref()) {
+                        (_, ExpData::Call(_, Operation::Deref, args)) => {
+                            same_location(lhs, &args[0])
+                        },
+                        (ExpData::Call(_, Operation::Borrow(_), args), _) => {
+                            same_location(&args[0], rhs)
+                        },
+                        _ => false,
+                    };
+                    if self_assigned {
+                        self.report(
+                            LintRule::SelfAssignment,
+                            env.get_node_loc(*id),
+                            "location is assigned to itself",
+                        );
+                    }
+                },
+                _ => {},
+            }
+            true
+        });
+
+        // A copy of a variable which is not used afterwards can be a move. The copied
+        // variable itself is visited right after the copy.
+        for (position, var, id, in_loop) in copies {
+            if !in_loop
+                && !borrowed.contains(&var)
+                && uses
+                    .get(&var)
+                    .is_some_and(|last_use| *last_use <= position + 1)
+            {
+                let name = match var {
+                    Var::Local(sym) => sym,
+                    Var::Param(idx) => params[idx].0,
+                };
+                self.report(
+                    LintRule::UnnecessaryCopy,
+                    env.get_node_loc(id),
+                    format!(
+                        "unnecessary copy, `{}` is not used afterwards and can be moved",
+                        name.display(env.symbol_pool())
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_borrow_of_deref(&mut self, id: NodeId, kind: ReferenceKind, arg: &ExpData) {
+        if let ExpData::Call(_, Operation::Deref, inner) = arg {
+            // `&mut *r` is only needless if `r` is a mutable reference, `&*r` can be
+            // written as `r` or `freeze(r)`.
+            let inner_ty = self.env.get_node_type(inner[0].node_id());
+            if matches!(inner_ty, Type::Reference(..))
+                && (kind == ReferenceKind::Immutable || inner_ty.is_mutable_reference())
+            {
+                self.report(
+                    LintRule::NeedlessBorrow,
+                    self.env.get_node_loc(id),
+                    "needless borrow of a dereference, use the reference directly",
+                );
+            }
+        }
+    }
+
+    fn check_bool_comparison(&mut self, id: NodeId, lhs: &ExpData, rhs: &ExpData) {
+        let is_bool_constant = |exp: &ExpData| matches!(exp, ExpData::Value(_, Value::Bool(_)));
+        if is_bool_constant(lhs) || is_bool_constant(rhs) {
+            self.report(
+                LintRule::BoolComparison,
+                self.env.get_node_loc(id),
+                "comparison with a boolean constant, use the boolean expression directly or \
+                 negate it with `!`",
+            );
+        }
+    }
+}
+
+/// Whether two expressions denote the same location, e.g. `*r = *r` or
+/// `s.f = s.f`.
+fn same_location(lhs: &ExpData, rhs: &ExpData) -> bool {
+    match (lhs, rhs) {
+        (ExpData::LocalVar(_, a), ExpData::LocalVar(_, b)) => a == b,
+        (ExpData::Temporary(_, a), ExpData::Temporary(_, b)) => a == b,
+        (
+            ExpData::Call(_, Operation::Select(m1, s1, f1), a),
+            ExpData::Call(_, Operation::Select(m2, s2, f2), b),
+        ) => (m1, s1, f1) == (m2, s2, f2) && same_location(&a[0], &b[0]),
+        (ExpData::Call(_, Operation::Borrow(_), a), ExpData::Call(_, Operation::Borrow(_), b))
+        | (ExpData::Call(_, Operation::Deref, a), ExpData::Call(_, Operation::Deref, b)) => {
+            same_location(&a[0], &b[0])
+        },
+        _ => false,
+    }
+}
@@ -17,7 +17,10 @@ use codespan_reporting::diagnostic::Severity;
 use move_command_line_common::{address::NumericalAddress, parser::NumberFormat};
 use move_compiler::{
     shared::known_attributes::{AttributeKind, TestingAttribute},
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{
+        ExpectedFailure, ExpectedMoveError, FuzzConfig, FuzzParameter, FuzzParameterKind, FuzzType,
+        ModuleTestPlan, TestCase, DEFAULT_FUZZ_RUNS,
+    },
 };
 use move_core_types::{
     identifier::Identifier, language_storage::ModuleId, value::MoveValue, vm_status::StatusCode,
@@ -118,9 +121,11 @@ fn build_test_info(
     let expected_failure_name = env.symbol_pool().make(TestingAttribute::EXPECTED_FAILURE);
     let test_name = env.symbol_pool().make(TestingAttribute::TEST);
     let test_only_name = env.symbol_pool().make(TestingAttribute::TEST_ONLY);
+    let fuzz_name = env.symbol_pool().make(TestingAttribute::FUZZ);
 
     let test_attribute_opt = attrs.iter().find(|a| a.name() == test_name);
     let abort_attribute_opt = attrs.iter().find(|a| a.name() == expected_failure_name);
+    let fuzz_attribute_opt = attrs.iter().find(|a| a.name() == fuzz_name);
 
     let test_attribute = match test_attribute_opt {
         None => {
@@ -133,6 +138,14 @@ fn build_test_info(
                 let abort_loc = env.get_node_loc(abort_id);
                 env.error_with_labels(&fn_id_loc, fn_msg, vec![(abort_loc, abort_msg.to_string())]);
             }
+            // neither can fuzz attributes
+            if let Some(fuzz_attribute) = fuzz_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[fuzz] attribute";
+                let fuzz_msg = "Attributed as #[fuzz] here";
+                let fuzz_loc = env.get_node_loc(fuzz_attribute.node_id());
+                env.error_with_labels(&fn_id_loc, fn_msg, vec![(fuzz_loc, fuzz_msg.to_string())]);
+            }
             return None;
         },
         Some(test_attribute) => test_attribute,
@@ -158,14 +171,44 @@ fn build_test_info(
         ]);
     }
 
+    // an invalid #[fuzz] attribute has been reported, but the test is still treated as a fuzz
+    // test to not also report its parameters as unassigned
+    let fuzz_runs = fuzz_attribute_opt.map(|fuzz_attribute| {
+        parse_fuzz_attribute(env, fuzz_attribute).unwrap_or(DEFAULT_FUZZ_RUNS)
+    });
+
     let test_annotation_params = parse_test_attribute(env, test_attribute, 0);
 
     let mut arguments = Vec::new();
+    let mut fuzz_parameters = Vec::new();
     for param in function.get_parameters_ref() {
-        let Parameter(var, _ty, var_loc) = &param;
+        let Parameter(var, ty, var_loc) = &param;
+        let name = env.symbol_pool().string(*var).to_string();
 
         match test_annotation_params.get(var) {
-            Some(value) => arguments.push(value.clone()),
+            Some(value) => {
+                arguments.push(value.clone());
+                fuzz_parameters.push(FuzzParameter {
+                    name,
+                    kind: FuzzParameterKind::Fixed(value.clone()),
+                });
+            },
+            None if fuzz_runs.is_some() => match type_to_fuzz_type(env, ty, 0) {
+                Some(fuzz_type) => fuzz_parameters.push(FuzzParameter {
+                    name,
+                    kind: FuzzParameterKind::Generated(fuzz_type),
+                }),
+                None => {
+                    let unsupported_msg = "Values of this type cannot be generated. Parameters \
+                                           of a #[fuzz] test must either be assigned in the test \
+                                           attribute, or be booleans, integers, addresses, \
+                                           vectors, or structs without type parameters of these";
+                    env.error_with_labels(&fn_id_loc, "unable to generate test", vec![(
+                        var_loc.clone(),
+                        unsupported_msg.to_string(),
+                    )]);
+                },
+            },
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -190,9 +233,58 @@ fn build_test_info(
         test_name: fn_name_str.to_string(),
         arguments,
         expected_failure,
+        fuzz: fuzz_runs.map(|runs| FuzzConfig {
+            runs,
+            parameters: fuzz_parameters,
+        }),
     })
 }
 
+// The maximum nesting of vectors and structs in generated values
+const MAX_FUZZ_TYPE_DEPTH: usize = 8;
+
+fn type_to_fuzz_type(env: &GlobalEnv, ty: &Type, depth: usize) -> Option<FuzzType> {
+    if depth > MAX_FUZZ_TYPE_DEPTH {
+        return None;
+    }
+    match ty {
+        Type::Primitive(primitive) => match primitive {
+            PrimitiveType::Bool => Some(FuzzType::Bool),
+            PrimitiveType::U8 => Some(FuzzType::U8),
+            PrimitiveType::U16 => Some(FuzzType::U16),
+            PrimitiveType::U32 => Some(FuzzType::U32),
+            PrimitiveType::U64 => Some(FuzzType::U64),
+            PrimitiveType::U128 => Some(FuzzType::U128),
+            PrimitiveType::U256 => Some(FuzzType::U256),
+            PrimitiveType::Address => Some(FuzzType::Address),
+            PrimitiveType::Signer
+            | PrimitiveType::Num
+            | PrimitiveType::Range
+            | PrimitiveType::EventStore => None,
+        },
+        Type::Vector(element_type) => Some(FuzzType::Vector(Box::new(type_to_fuzz_type(
+            env,
+            element_type,
+            depth + 1,
+        )?))),
+        Type::Struct(module_id, struct_id, ty_args) if ty_args.is_empty() => {
+            let struct_env = env.get_struct(module_id.qualified(*struct_id));
+            if struct_env.is_intrinsic()
+                || !struct_env.get_type_parameters().is_empty()
+                || struct_env.get_field_count() == 0
+            {
+                return None;
+            }
+            struct_env
+                .get_fields()
+                .map(|field| type_to_fuzz_type(env, &field.get_type(), depth + 1))
+                .collect::<Option<Vec<_>>>()
+                .map(FuzzType::Struct)
+        },
+        _ => None,
+    }
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
@@ -244,6 +336,58 @@ fn parse_test_attribute(
     }
 }
 
+fn parse_fuzz_attribute(env: &GlobalEnv, fuzz_attribute: &Attribute) -> Option<u64> {
+    match fuzz_attribute {
+        Attribute::Assign(id, _, _) => {
+            let loc = env.get_node_loc(*id);
+            env.error(
+                &loc,
+                "Invalid fuzz attribute assignment, expected a #[fuzz] or #[fuzz(runs = ...)] \
+                 attribute",
+            );
+            None
+        },
+        Attribute::Apply(_, _, attrs) => {
+            let mut runs = DEFAULT_FUZZ_RUNS;
+            for attr in attrs {
+                if env.symbol_pool().string(attr.name()).as_str()
+                    != TestingAttribute::FUZZ_RUNS_NAME
+                {
+                    let loc = env.get_node_loc(attr.node_id());
+                    env.error(
+                        &loc,
+                        &format!("Unsupported attribute for `{}`", TestingAttribute::FUZZ),
+                    );
+                    return None;
+                }
+                let (value_loc, value) =
+                    get_assigned_attribute(env, TestingAttribute::FUZZ_RUNS_NAME, attr.clone())?;
+                let u = match value {
+                    AttributeValue::Value(_, val) => {
+                        convert_model_ast_value_u64(env, value_loc.clone(), &val)?.1
+                    },
+                    AttributeValue::Name(..) => {
+                        env.error(
+                            &value_loc,
+                            "Invalid attribute value: only u64 literal values permitted",
+                        );
+                        return None;
+                    },
+                };
+                if u == 0 {
+                    env.error(
+                        &value_loc,
+                        "Invalid attribute value: expected at least one run",
+                    );
+                    return None;
+                }
+                runs = u;
+            }
+            Some(runs)
+        },
+    }
+}
+
 fn parse_failure_attribute(
     env: &GlobalEnv,
     current_module: &ModuleName,
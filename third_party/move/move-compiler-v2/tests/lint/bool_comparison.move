@@ -0,0 +1,17 @@
+module 0x42::bool_comparison {
+    fun eq_true(x: bool): bool {
+        x == true
+    }
+
+    fun neq_false(x: bool): bool {
+        false != x
+    }
+
+    fun eq_var(x: bool, y: bool): bool {
+        x == y
+    }
+
+    fun negated(x: bool): bool {
+        !x
+    }
+}
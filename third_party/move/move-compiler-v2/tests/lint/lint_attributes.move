@@ -0,0 +1,28 @@
+#[lint::allow(bool_comparison)]
+module 0x42::lint_attributes {
+    fun allowed_by_module(x: bool): bool {
+        x == true
+    }
+
+    #[lint::deny(bool_comparison)]
+    fun denied_by_function(x: bool): bool {
+        x == false
+    }
+
+    #[lint::allow(self_assignment, needless_borrow)]
+    fun allowed_by_function(x: u64): u64 {
+        x = x;
+        *&x
+    }
+
+    #[lint::deny(needless_borrow)]
+    fun denied(x: u64): u64 {
+        *&x
+    }
+
+    #[lint::allow(no_such_lint)]
+    fun unknown(x: u64): u64 {
+        x = x;
+        x
+    }
+}
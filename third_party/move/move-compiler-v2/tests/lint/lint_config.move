@@ -0,0 +1,17 @@
+// lint: bool_comparison=deny
+// lint: self_assignment=allow
+module 0x42::lint_config {
+    fun denied_by_config(x: bool): bool {
+        x == true
+    }
+
+    fun allowed_by_config(x: u64): u64 {
+        x = x;
+        x
+    }
+
+    #[lint::allow(bool_comparison)]
+    fun allowed_by_attribute(x: bool): bool {
+        x != false
+    }
+}
@@ -0,0 +1,25 @@
+module 0x42::needless_borrow {
+    fun borrow_of_deref(r: &u64): &u64 {
+        &*r
+    }
+
+    fun mut_borrow_of_deref(r: &mut u64): &mut u64 {
+        &mut *r
+    }
+
+    fun freeze_of_deref(r: &mut u64): &u64 {
+        &*r
+    }
+
+    fun deref_of_borrow(x: u64): u64 {
+        *&x
+    }
+
+    fun borrow_of_field(s: &S): &u64 {
+        &s.x
+    }
+
+    struct S has drop {
+        x: u64,
+    }
+}
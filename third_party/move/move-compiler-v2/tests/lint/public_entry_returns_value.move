@@ -0,0 +1,17 @@
+module 0x42::public_entry_returns_value {
+    public entry fun returns_value(x: u64): u64 {
+        x
+    }
+
+    public entry fun returns_nothing(x: u64) {
+        assert!(x > 0, 1);
+    }
+
+    entry fun private_returns_value(x: u64): u64 {
+        x
+    }
+
+    public fun public_returns_value(x: u64): u64 {
+        x
+    }
+}
@@ -0,0 +1,27 @@
+module 0x42::self_assignment {
+    struct S has drop { f: u64 }
+
+    fun local(x: u64): u64 {
+        let y = x;
+        y = y;
+        y
+    }
+
+    fun param(x: u64): u64 {
+        x = x;
+        x
+    }
+
+    fun field(s: &mut S) {
+        s.f = s.f;
+    }
+
+    fun deref(r: &mut u64) {
+        *r = *r;
+    }
+
+    fun other(x: u64, y: u64): u64 {
+        x = y;
+        x
+    }
+}
@@ -0,0 +1,33 @@
+module 0x42::unnecessary_copy {
+    struct S has copy, drop { x: u64 }
+
+    fun copy_last_use(s: S): S {
+        copy s
+    }
+
+    fun copy_used_later(s: S): (S, S) {
+        (copy s, s)
+    }
+
+    fun copy_in_loop(s: S): S {
+        let r = s;
+        let i = 0;
+        while (i < 2) {
+            r = copy s;
+            i = i + 1;
+        };
+        r
+    }
+
+    fun copy_of_local(): S {
+        let s = S { x: 1 };
+        let t = copy s;
+        t
+    }
+
+    fun copy_of_borrowed(s: S): u64 {
+        let r = &s;
+        let t = copy s;
+        t.x + r.x
+    }
+}
@@ -0,0 +1,17 @@
+module 0x42::unused_acquires {
+    struct R has key { x: u64 }
+
+    struct T has key { x: u64 }
+
+    inline fun read_r(addr: address): u64 acquires R, T {
+        borrow_global<R>(addr).x
+    }
+
+    fun read(addr: address): u64 acquires R {
+        read_r(addr)
+    }
+
+    inline fun read_both(addr: address): u64 acquires R, T {
+        borrow_global<R>(addr).x + borrow_global<T>(addr).x
+    }
+}
@@ -2,12 +2,16 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use anyhow::{anyhow, bail};
 use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
 use datatest_stable::Requirements;
 use itertools::Itertools;
 use log::debug;
+use move_compiler::shared::known_attributes::KnownAttribute;
 use move_compiler_v2::{
-    annotate_units, disassemble_compiled_units, env_pipeline::rewrite_target::RewritingScope,
+    annotate_units, disassemble_compiled_units,
+    env_pipeline::rewrite_target::RewritingScope,
+    linter::{self, LintConfig, LintLevel, LintRule, LINT_ALLOW_ATTRIBUTE, LINT_DENY_ATTRIBUTE},
     logging, pipeline, plan_builder, run_bytecode_verifier, run_file_format_gen, Experiment,
     Options,
 };
@@ -541,6 +545,19 @@ const TEST_CONFIGS: Lazy<BTreeMap<&str, TestConfig>> = Lazy::new(|| {
             dump_bytecode: DumpLevel::None,
             dump_bytecode_filter: None,
         },
+        // Tests for the linter, which runs on the model after checking
+        TestConfig {
+            name: "lint",
+            runner: |p| run_test(p, get_config_by_name("lint")),
+            include: vec!["/lint/"],
+            exclude: vec![],
+            exp_suffix: None,
+            options: opts.clone(),
+            stop_after: StopAfter::AstPipeline,
+            dump_ast: DumpLevel::None,
+            dump_bytecode: DumpLevel::None,
+            dump_bytecode_filter: None,
+        },
     ];
     configs.into_iter().map(|c| (c.name, c)).collect()
 });
@@ -576,6 +593,17 @@ fn run_test(path: &Path, config: TestConfig) -> datatest_stable::Result<()> {
         "K=0x19".to_string(),
         "Async=0x20".to_string(),
     ];
+    let lint_config = if path_str.contains("/lint/") {
+        // Lint attributes are only known when the Aptos extended checks are used, so
+        // add them to the builtin attributes here.
+        options.known_attributes = KnownAttribute::get_all_attribute_names().clone();
+        options
+            .known_attributes
+            .extend([LINT_ALLOW_ATTRIBUTE, LINT_DENY_ATTRIBUTE].map(String::from));
+        Some(parse_lint_config(path)?)
+    } else {
+        None
+    };
 
     // Putting the generated test baseline into a Refcell to avoid problems with mut borrow
     // in closures.
@@ -585,6 +613,15 @@ fn run_test(path: &Path, config: TestConfig) -> datatest_stable::Result<()> {
     let mut env = move_compiler_v2::run_checker(options.clone())?;
     let mut ok = check_diags(&mut test_output.borrow_mut(), &env);
 
+    if ok {
+        if let Some(lint_config) = &lint_config {
+            // Run the linter on the checked model, as `run_checker_and_linter` does.
+            let diags = linter::run_linter(&env, lint_config);
+            linter::report_lint_diagnostics(&env, &diags);
+            ok = check_diags(&mut test_output.borrow_mut(), &env);
+        }
+    }
+
     if ok {
         // Run env processor pipeline.
         let env_pipeline = move_compiler_v2::check_and_rewrite_pipeline(
@@ -720,6 +757,29 @@ fn bytecode_dump_enabled(config: &TestConfig, is_last: bool, name: &str) -> bool
                 .contains(&name))
 }
 
+/// Parses the lint levels set by `// lint: <rule>=<allow|warn|deny>` directives in the
+/// test file.
+fn parse_lint_config(path: &Path) -> anyhow::Result<LintConfig> {
+    let mut config = LintConfig::default();
+    for directive in extract_test_directives(path, "// lint:")? {
+        let (rule, level) = directive
+            .split_once('=')
+            .ok_or_else(|| anyhow!("malformed lint directive `{}`", directive))?;
+        let rule = rule
+            .trim()
+            .parse::<LintRule>()
+            .map_err(|err| anyhow!(err))?;
+        let level = match level.trim() {
+            "allow" => LintLevel::Allow,
+            "warn" => LintLevel::Warn,
+            "deny" => LintLevel::Deny,
+            level => bail!("unknown lint level `{}`", level),
+        };
+        config.set_level(rule, level);
+    }
+    Ok(config)
+}
+
 /// Checks for diagnostics and adds them to the baseline.
 fn check_diags(baseline: &mut String, env: &GlobalEnv) -> bool {
     let mut error_writer = Buffer::no_color();
@@ -632,6 +632,8 @@ pub mod known_attributes {
         Test,
         // This test is expected to fail
         ExpectedFailure,
+        // This test is run with randomly generated arguments
+        Fuzz,
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -677,6 +679,7 @@ pub mod known_attributes {
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
                 },
+                TestingAttribute::FUZZ => Self::Testing(TestingAttribute::Fuzz),
                 VerificationAttribute::VERIFY_ONLY => {
                     Self::Verification(VerificationAttribute::VerifyOnly)
                 },
@@ -730,11 +733,17 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
-        const ALL_ATTRIBUTE_NAMES: [&'static str; 3] =
-            [Self::TEST, Self::TEST_ONLY, Self::EXPECTED_FAILURE];
+        const ALL_ATTRIBUTE_NAMES: [&'static str; 4] = [
+            Self::TEST,
+            Self::TEST_ONLY,
+            Self::EXPECTED_FAILURE,
+            Self::FUZZ,
+        ];
         pub const ARITHMETIC_ERROR_NAME: &'static str = "arithmetic_error";
         pub const ERROR_LOCATION: &'static str = "location";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
+        pub const FUZZ: &'static str = "fuzz";
+        pub const FUZZ_RUNS_NAME: &'static str = "runs";
         pub const MAJOR_STATUS_NAME: &'static str = "major_status";
         pub const MINOR_STATUS_NAME: &'static str = "minor_status";
         pub const OUT_OF_GAS_NAME: &'static str = "out_of_gas";
@@ -764,6 +773,7 @@ pub mod known_attributes {
                 Self::Test => Self::TEST,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
+                Self::Fuzz => Self::FUZZ,
             }
         }
 
@@ -784,10 +794,13 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             static EXPECTED_FAILURE_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static FUZZ_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
                 TestingAttribute::Test => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
+                TestingAttribute::Fuzz => &FUZZ_POSITIONS,
             }
         }
     }
@@ -36,6 +36,75 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    // set for #[fuzz] tests, whose parameters not assigned in #[test(...)] are generated
+    pub fuzz: Option<FuzzConfig>,
+}
+
+pub const DEFAULT_FUZZ_RUNS: u64 = 256;
+
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    // number of times the test is run, each time with newly generated arguments
+    pub runs: u64,
+    // all parameters of the test, in order
+    pub parameters: Vec<FuzzParameter>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzParameter {
+    pub name: String,
+    pub kind: FuzzParameterKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum FuzzParameterKind {
+    // assigned in the #[test(...)] attribute
+    Fixed(MoveValue),
+    // generated for every run
+    Generated(FuzzType),
+}
+
+// The types of values that can be generated for a #[fuzz] test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuzzType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Vector(Box<FuzzType>),
+    // a struct without type parameters, by the types of its fields
+    Struct(Vec<FuzzType>),
+}
+
+impl FuzzConfig {
+    // The arguments of a run, given the values generated for it in order
+    pub fn arguments(&self, generated: &[MoveValue]) -> Vec<MoveValue> {
+        let mut generated = generated.iter();
+        self.parameters
+            .iter()
+            .map(|parameter| match &parameter.kind {
+                FuzzParameterKind::Fixed(value) => value.clone(),
+                FuzzParameterKind::Generated(_) => generated
+                    .next()
+                    .expect("a value for every generated parameter")
+                    .clone(),
+            })
+            .collect()
+    }
+
+    pub fn generated_types(&self) -> Vec<&FuzzType> {
+        self.parameters
+            .iter()
+            .filter_map(|parameter| match &parameter.kind {
+                FuzzParameterKind::Fixed(_) => None,
+                FuzzParameterKind::Generated(ty) => Some(ty),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
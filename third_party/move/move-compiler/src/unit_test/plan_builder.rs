@@ -8,13 +8,18 @@ use crate::{
     expansion::ast::{
         self as E, Address, Attribute, AttributeValue, ModuleAccess_, ModuleIdent, ModuleIdent_,
     },
-    parser::ast::ConstantName,
+    hlir::ast as H,
+    naming::ast::BuiltinTypeName_,
+    parser::ast::{ConstantName, StructName},
     shared::{
         known_attributes::{AttributeKind, KnownAttribute, TestingAttribute},
         unique_map::UniqueMap,
         CompilationEnv, Identifier, NumericalAddress,
     },
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{
+        ExpectedFailure, ExpectedMoveError, FuzzConfig, FuzzParameter, FuzzParameterKind, FuzzType,
+        ModuleTestPlan, TestCase, DEFAULT_FUZZ_RUNS,
+    },
 };
 use move_core_types::{
     account_address::AccountAddress as MoveAddress, language_storage::ModuleId, u256::U256,
@@ -27,6 +32,8 @@ use std::collections::BTreeMap;
 struct Context<'env> {
     env: &'env mut CompilationEnv,
     constants: UniqueMap<ModuleIdent, UniqueMap<ConstantName, (Loc, Option<u64>)>>,
+    // the field types of structs, if they have no type parameters and are not native
+    struct_fields: UniqueMap<ModuleIdent, UniqueMap<StructName, Option<Vec<H::BaseType>>>>,
 }
 
 impl<'env> Context<'env> {
@@ -40,9 +47,20 @@ impl<'env> Context<'env> {
                 (constant.loc, v_opt)
             })
         });
+        let struct_fields = prog.modules.ref_map(|_mident, module| {
+            module
+                .structs
+                .ref_map(|_name, struct_def| match &struct_def.fields {
+                    H::StructFields::Defined(fields) if struct_def.type_parameters.is_empty() => {
+                        Some(fields.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>())
+                    },
+                    _ => None,
+                })
+        });
         Self {
             env: compilation_env,
             constants,
+            struct_fields,
         }
     }
 
@@ -53,6 +71,10 @@ impl<'env> Context<'env> {
     fn constants(&self) -> &UniqueMap<ModuleIdent, UniqueMap<ConstantName, (Loc, Option<u64>)>> {
         &self.constants
     }
+
+    fn struct_fields(&self, module: &ModuleIdent, name: &StructName) -> Option<&Vec<H::BaseType>> {
+        self.struct_fields.get(module)?.get(name)?.as_ref()
+    }
 }
 
 //***************************************************************************
@@ -126,6 +148,7 @@ fn build_test_info<'func>(
     let test_attribute_opt = get_attrs(TestingAttribute::Test);
     let abort_attribute_opt = get_attrs(TestingAttribute::ExpectedFailure);
     let test_only_attribute_opt = get_attrs(TestingAttribute::TestOnly);
+    let fuzz_attribute_opt = get_attrs(TestingAttribute::Fuzz);
 
     let test_attribute = match test_attribute_opt {
         None => {
@@ -140,6 +163,17 @@ fn build_test_info<'func>(
                     (abort_attribute.loc, abort_msg),
                 ))
             }
+            // neither can fuzz attributes
+            if let Some(fuzz_attribute) = fuzz_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[fuzz] attribute";
+                let fuzz_msg = "Attributed as #[fuzz] here";
+                context.env.add_diag(diag!(
+                    Attributes::InvalidUsage,
+                    (fn_loc, fn_msg),
+                    (fuzz_attribute.loc, fuzz_msg),
+                ))
+            }
             return None;
         },
         Some(test_attribute) => test_attribute,
@@ -157,11 +191,43 @@ fn build_test_info<'func>(
         ))
     }
 
+    // an invalid #[fuzz] attribute has been reported, but the test is still treated as a fuzz
+    // test to not also report its parameters as unassigned
+    let fuzz_runs = fuzz_attribute_opt.map(|fuzz_attribute| {
+        parse_fuzz_attribute(context, fuzz_attribute).unwrap_or(DEFAULT_FUZZ_RUNS)
+    });
+
     let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
     let mut arguments = Vec::new();
-    for (var, _) in &function.signature.parameters {
+    let mut fuzz_parameters = Vec::new();
+    for (var, ty) in &function.signature.parameters {
+        let name = var.value().to_string();
         match test_annotation_params.get(&var.value()) {
-            Some(value) => arguments.push(value.clone()),
+            Some(value) => {
+                arguments.push(value.clone());
+                fuzz_parameters.push(FuzzParameter {
+                    name,
+                    kind: FuzzParameterKind::Fixed(value.clone()),
+                });
+            },
+            None if fuzz_runs.is_some() => match single_type_to_fuzz_type(context, ty) {
+                Some(fuzz_type) => fuzz_parameters.push(FuzzParameter {
+                    name,
+                    kind: FuzzParameterKind::Generated(fuzz_type),
+                }),
+                None => {
+                    let unsupported_msg = "Values of this type cannot be generated. Parameters \
+                                           of a #[fuzz] test must either be assigned in the test \
+                                           attribute, or be booleans, integers, addresses, \
+                                           vectors, or structs without type parameters of these";
+                    context.env.add_diag(diag!(
+                        Attributes::InvalidTest,
+                        (ty.loc, unsupported_msg),
+                        (var.loc(), "For this parameter"),
+                        (fn_loc, IN_THIS_TEST_MSG),
+                    ))
+                },
+            },
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -184,9 +250,59 @@ fn build_test_info<'func>(
         test_name: fn_name.to_string(),
         arguments,
         expected_failure,
+        fuzz: fuzz_runs.map(|runs| FuzzConfig {
+            runs,
+            parameters: fuzz_parameters,
+        }),
     })
 }
 
+// The maximum nesting of vectors and structs in generated values
+const MAX_FUZZ_TYPE_DEPTH: usize = 8;
+
+fn single_type_to_fuzz_type(context: &Context, sp!(_, ty): &H::SingleType) -> Option<FuzzType> {
+    match ty {
+        H::SingleType_::Base(base_type) => base_type_to_fuzz_type(context, base_type, 0),
+        H::SingleType_::Ref(_, _) => None,
+    }
+}
+
+fn base_type_to_fuzz_type(
+    context: &Context,
+    sp!(_, ty): &H::BaseType,
+    depth: usize,
+) -> Option<FuzzType> {
+    use BuiltinTypeName_ as BT;
+    use H::{BaseType_ as B, TypeName_ as TN};
+    if depth > MAX_FUZZ_TYPE_DEPTH {
+        return None;
+    }
+    match ty {
+        B::Apply(_, sp!(_, TN::Builtin(sp!(_, builtin))), ty_args) => match builtin {
+            BT::Bool => Some(FuzzType::Bool),
+            BT::U8 => Some(FuzzType::U8),
+            BT::U16 => Some(FuzzType::U16),
+            BT::U32 => Some(FuzzType::U32),
+            BT::U64 => Some(FuzzType::U64),
+            BT::U128 => Some(FuzzType::U128),
+            BT::U256 => Some(FuzzType::U256),
+            BT::Address => Some(FuzzType::Address),
+            BT::Vector => {
+                let element_type = base_type_to_fuzz_type(context, ty_args.first()?, depth + 1)?;
+                Some(FuzzType::Vector(Box::new(element_type)))
+            },
+            BT::Signer => None,
+        },
+        B::Apply(_, sp!(_, TN::ModuleType(module, name)), _) => context
+            .struct_fields(module, name)?
+            .iter()
+            .map(|field_type| base_type_to_fuzz_type(context, field_type, depth + 1))
+            .collect::<Option<Vec<_>>>()
+            .map(FuzzType::Struct),
+        B::Param(_) | B::Unreachable | B::UnresolvedError => None,
+    }
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
@@ -251,6 +367,53 @@ fn parse_test_attribute(
     }
 }
 
+fn parse_fuzz_attribute(
+    context: &mut Context,
+    sp!(aloc, fuzz_attribute): &E::Attribute,
+) -> Option<u64> {
+    use E::Attribute_ as EA;
+    match fuzz_attribute {
+        EA::Name(_) => Some(DEFAULT_FUZZ_RUNS),
+        EA::Assigned(_, value) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (value.loc, "Invalid fuzz attribute assignment"),
+                (*aloc, "Expect a #[fuzz] or #[fuzz(runs = ...)] attribute"),
+            ));
+            None
+        },
+        EA::Parameterized(_, attrs) => {
+            let mut runs = DEFAULT_FUZZ_RUNS;
+            for (sp!(kloc, k_), attr) in attrs.key_cloned_iter() {
+                if k_.to_string() != TestingAttribute::FUZZ_RUNS_NAME {
+                    let msg = format!("Unsupported attribute for {}", TestingAttribute::FUZZ);
+                    context
+                        .env
+                        .add_diag(diag!(Attributes::InvalidValue, (kloc, msg)));
+                    return None;
+                }
+                let (value_loc, value) = get_assigned_attribute(
+                    context,
+                    TestingAttribute::FUZZ_RUNS_NAME,
+                    kloc,
+                    attr.clone(),
+                )?;
+                let (vloc, u) = convert_attribute_value_u64(context, value_loc, &value)?;
+                if u == 0 {
+                    context.env.add_diag(diag!(
+                        Attributes::InvalidValue,
+                        (value_loc, INVALID_VALUE),
+                        (vloc, "Expected at least one run"),
+                    ));
+                    return None;
+                }
+                runs = u;
+            }
+            Some(runs)
+        },
+    }
+}
+
 const BAD_ABORT_VALUE_WARNING: &str = "WARNING: passes for an abort from any module.";
 const INVALID_VALUE: &str = "Invalid value in attribute assignment";
 
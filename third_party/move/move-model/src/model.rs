@@ -1234,6 +1234,15 @@ impl GlobalEnv {
             .unwrap_or(Ordering::Equal)
     }
 
+    /// Returns the accumulated diagnostics, whether reported or not.
+    pub fn get_diagnostics(&self) -> Vec<Diagnostic<FileId>> {
+        self.diags
+            .borrow()
+            .iter()
+            .map(|(diag, _)| diag.clone())
+            .collect()
+    }
+
     /// Writes accumulated diagnostics that pass through `filter`
     pub fn report_diag_with_filter<W: WriteColor, F: FnMut(&Diagnostic<FileId>) -> bool>(
         &self,
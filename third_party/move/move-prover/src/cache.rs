@@ -0,0 +1,554 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caching of verification results across runs of the prover.
+//!
+//! Each verification target, that is each function which has a verification variant, is
+//! assigned a hash of everything its verification depends on: the prover and backend options,
+//! the source of its module and of the modules used by it (transitively, and including their
+//! specs, spec functions and schemas wherever they are declared), and the global invariants.
+//! From this source, the functions which are not called by the target are cut out, so changing
+//! them does not invalidate the target. Once a target has been verified successfully, its hash
+//! is recorded in the cache, and later runs skip the target as long as its hash does not change.
+
+use crate::cli::Options;
+use anyhow::Context;
+use codespan::Span;
+use codespan_reporting::diagnostic::{LabelStyle, Severity};
+use itertools::Itertools;
+#[allow(unused_imports)]
+use log::{debug, info, warn};
+use move_model::{
+    ast::SpecBlockTarget,
+    model::{FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, QualifiedId},
+};
+use move_stackless_bytecode::function_target_pipeline::FunctionTargetsHolder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+};
+
+/// The name of the file in the cache directory which holds the verified targets.
+const CACHE_FILE: &str = "verified.toml";
+
+/// The version of the cache format. Bump this whenever the way hashes are computed changes.
+const CACHE_VERSION: u64 = 2;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheContent {
+    version: u64,
+    /// Maps the full name of each verified function to the hash it was verified with.
+    verified: BTreeMap<String, String>,
+}
+
+/// A cache of the verification targets which have been verified successfully.
+pub struct VerificationCache {
+    path: PathBuf,
+    content: CacheContent,
+    /// The hashes of the verification targets of the current run. Targets whose source is not
+    /// available are not cached, and do not appear here.
+    hashes: BTreeMap<QualifiedId<FunId>, String>,
+}
+
+impl VerificationCache {
+    /// Loads the cache from `options.cache_dir`, and computes the hashes of the verification
+    /// targets in `targets`.
+    pub fn new(
+        env: &GlobalEnv,
+        options: &Options,
+        targets: &FunctionTargetsHolder,
+    ) -> anyhow::Result<Self> {
+        let path =
+            PathBuf::from(options.cache_dir.as_ref().expect("cache directory")).join(CACHE_FILE);
+        let content = if path.exists() {
+            let content: CacheContent = toml::from_str(&fs::read_to_string(&path)?)
+                .with_context(|| format!("reading verification cache `{}`", path.display()))?;
+            if content.version == CACHE_VERSION {
+                content
+            } else {
+                CacheContent::default()
+            }
+        } else {
+            CacheContent::default()
+        };
+        let fingerprint = fingerprint(env, options);
+        let hashes = targets
+            .get_funs_and_variants()
+            .filter(|(_, variant)| variant.is_verified())
+            .map(|(fun_id, _)| fun_id)
+            .unique()
+            .filter_map(|fun_id| {
+                let fun_env = env.get_function(fun_id);
+                target_hash(&fun_env, &fingerprint).map(|hash| (fun_id, hash))
+            })
+            .collect();
+        Ok(Self {
+            path,
+            content: CacheContent {
+                version: CACHE_VERSION,
+                ..content
+            },
+            hashes,
+        })
+    }
+
+    /// Loads the cache if `options.cache_dir` is set and verification is not skipped, and
+    /// unless `options.force` is set, removes the targets which are already verified according
+    /// to it from `targets`.
+    pub fn load(
+        env: &GlobalEnv,
+        options: &Options,
+        targets: &mut FunctionTargetsHolder,
+    ) -> anyhow::Result<Option<Self>> {
+        if options.cache_dir.is_none() || options.prover.generate_only {
+            return Ok(None);
+        }
+        let cache = Self::new(env, options, targets)?;
+        if !options.force {
+            let cached = cache.remove_verified_targets(env, targets);
+            info!("skipping {} cached verification targets", cached);
+        }
+        Ok(Some(cache))
+    }
+
+    /// Removes the verification variants of the targets which are already verified according
+    /// to the cache, and returns the number of targets removed.
+    pub fn remove_verified_targets(
+        &self,
+        env: &GlobalEnv,
+        targets: &mut FunctionTargetsHolder,
+    ) -> usize {
+        let cached = self
+            .hashes
+            .iter()
+            .filter(|(fun_id, hash)| {
+                let name = env.get_function(**fun_id).get_full_name_with_address();
+                self.content.verified.get(&name) == Some(*hash)
+            })
+            .map(|(fun_id, _)| *fun_id)
+            .collect_vec();
+        for fun_id in &cached {
+            let variants = targets
+                .get_funs_and_variants()
+                .filter(|(id, variant)| id == fun_id && variant.is_verified())
+                .map(|(_, variant)| variant)
+                .collect_vec();
+            for variant in variants {
+                debug!(
+                    "skipping {} ({}), which is cached as verified",
+                    env.get_function(*fun_id).get_full_name_str(),
+                    variant
+                );
+                targets.remove_target_data(fun_id, &variant);
+            }
+        }
+        cached.len()
+    }
+
+    /// Records the targets which have been verified in this run, based on the errors reported
+    /// in the environment. A target is only recorded if none of the errors is located in it.
+    /// If an error cannot be attributed to a target, no target is recorded.
+    pub fn record_verified(&mut self, env: &GlobalEnv, targets: &FunctionTargetsHolder) {
+        let verified = targets
+            .get_funs_and_variants()
+            .filter(|(_, variant)| variant.is_verified())
+            .map(|(fun_id, _)| fun_id)
+            .filter(|fun_id| self.hashes.contains_key(fun_id))
+            .collect::<BTreeSet<_>>();
+        let mut failed = BTreeSet::new();
+        for diag in env.get_diagnostics() {
+            if diag.severity < Severity::Error {
+                continue;
+            }
+            let locs = diag
+                .labels
+                .iter()
+                .filter(|label| label.style == LabelStyle::Primary)
+                .map(|label| {
+                    Loc::new(
+                        label.file_id,
+                        Span::new(label.range.start as u32, label.range.end as u32),
+                    )
+                })
+                .collect_vec();
+            let enclosing = verified
+                .iter()
+                .filter(|fun_id| {
+                    let fun_env = env.get_function(**fun_id);
+                    locs.iter().any(|loc| is_enclosing_function(&fun_env, loc))
+                })
+                .copied()
+                .collect_vec();
+            if enclosing.is_empty() {
+                debug!("not caching verification results due to an unattributed error");
+                return;
+            }
+            failed.extend(enclosing);
+        }
+        for fun_id in verified.difference(&failed) {
+            let name = env.get_function(*fun_id).get_full_name_with_address();
+            self.content
+                .verified
+                .insert(name, self.hashes[fun_id].clone());
+        }
+    }
+
+    /// Writes the cache back to disk.
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, toml::to_string(&self.content)?)
+            .with_context(|| format!("writing verification cache `{}`", self.path.display()))
+    }
+}
+
+/// Returns true if `loc` is in the declaration or the spec of the function.
+fn is_enclosing_function(fun_env: &FunctionEnv, loc: &Loc) -> bool {
+    fun_env.get_loc().is_enclosing(loc)
+        || fun_env
+            .get_spec()
+            .loc
+            .as_ref()
+            .map_or(false, |spec_loc| spec_loc.is_enclosing(loc))
+}
+
+/// Computes the part of the hash shared by all targets: the options which influence the result
+/// of verification, and the global invariants, which may be verified by any function.
+fn fingerprint(env: &GlobalEnv, options: &Options) -> String {
+    let mut prover = options.prover.clone();
+    prover.verify_scope = Default::default();
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}:{}", CACHE_VERSION, env!("CARGO_PKG_VERSION")));
+    hasher.update(format!("{:?}", prover));
+    hasher.update(format!("{:?}", options.backend));
+    for module_env in env.get_modules() {
+        for inv in env.get_global_invariants_for_module(module_env.get_id()) {
+            hasher.update(env.get_source(&inv.loc).unwrap_or_default());
+        }
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Computes the hash of a verification target, or None if the source of one of the modules it
+/// depends on is not available.
+fn target_hash(fun_env: &FunctionEnv, fingerprint: &str) -> Option<String> {
+    let env = fun_env.module_env.env;
+    let mut functions = fun_env.get_transitive_closure_of_called_functions();
+    functions.insert(fun_env.get_qualified_id());
+    let mut modules = BTreeSet::new();
+    let mut todo = vec![fun_env.module_env.get_id()];
+    while let Some(module_id) = todo.pop() {
+        if modules.insert(module_id) {
+            todo.extend(env.get_module(module_id).get_used_modules(true));
+        }
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(fingerprint);
+    for module_id in modules {
+        let module_env = env.get_module(module_id);
+        hasher.update(module_env.get_full_name_str());
+        hasher.update(module_source(&module_env, &functions)?);
+        // Specs may be declared outside of the module, e.g. in `.spec.move` files.
+        let mut spec_locs = module_env
+            .get_spec()
+            .conditions
+            .iter()
+            .map(|cond| cond.loc.clone())
+            .collect_vec();
+        // Spec functions and schemas may be used by the specs of any function, and module
+        // blocks declare spec variables and lets
+        for info in module_env.get_spec_block_infos() {
+            if matches!(
+                info.target,
+                SpecBlockTarget::Module(_) | SpecBlockTarget::Schema(..)
+            ) {
+                spec_locs.push(info.loc.clone());
+            }
+        }
+        spec_locs.extend(
+            module_env
+                .get_spec_funs()
+                .filter(|(_, decl)| !decl.is_move_fun)
+                .map(|(_, decl)| decl.loc.clone()),
+        );
+        for struct_env in module_env.get_structs() {
+            spec_locs.extend(struct_env.get_spec().loc.clone());
+        }
+        for fun_env in module_env.get_functions() {
+            if functions.contains(&fun_env.get_qualified_id()) {
+                spec_locs.extend(fun_env.get_spec().loc.clone());
+            }
+        }
+        for loc in spec_locs {
+            hasher.update(env.get_source(&loc).ok()?);
+        }
+    }
+    Some(hex::encode(hasher.finalize()))
+}
+
+/// Returns the source of the module, with the functions cut out which are not in `functions`.
+/// Inline functions, and functions which are used in specs, are always retained.
+fn module_source(
+    module_env: &ModuleEnv,
+    functions: &BTreeSet<QualifiedId<FunId>>,
+) -> Option<String> {
+    let env = module_env.env;
+    let loc = module_env.get_loc();
+    let source = env.get_source(&loc).ok()?;
+    let start = loc.span().start().to_usize();
+    let omitted = module_env
+        .get_functions()
+        .filter(|fun_env| {
+            !fun_env.is_inline()
+                && !functions.contains(&fun_env.get_qualified_id())
+                && !module_env
+                    .get_spec_funs_of_name(fun_env.get_name())
+                    .any(|(id, decl)| decl.is_move_fun && module_env.spec_fun_is_used(*id))
+        })
+        .map(|fun_env| fun_env.get_loc())
+        .filter(|fun_loc| loc.is_enclosing(fun_loc))
+        .map(|fun_loc| {
+            (
+                fun_loc.span().start().to_usize() - start,
+                fun_loc.span().end().to_usize() - start,
+            )
+        })
+        .sorted()
+        .collect_vec();
+    let mut result = String::new();
+    let mut pos = 0;
+    for (from, to) in omitted {
+        if from >= pos {
+            result.push_str(&source[pos..from]);
+            pos = to;
+        }
+    }
+    result.push_str(&source[pos..]);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_compiler::shared::{known_attributes::KnownAttribute, PackagePaths};
+    use move_compiler_v2::{env_pipeline::rewrite_target::RewritingScope, Experiment};
+    use move_model::{parse_addresses_from_options, run_model_builder_with_options};
+    use std::path::Path;
+
+    const MODULE: &str = r#"module 0x42::m {
+    struct R has key { v: u64 }
+
+    public fun callee(x: u64): u64 {
+        x + 1
+    }
+
+    public fun caller(x: u64): u64 {
+        callee(x)
+    }
+
+    public fun uncalled(x: u64): u64 {
+        x
+    }
+}
+"#;
+
+    const SPEC: &str = r#"spec 0x42::m {
+    spec caller {
+        ensures result == x + 1;
+        ensures helper(result) == result;
+        include NoOverflow;
+    }
+
+    spec fun helper(y: u64): u64 { y }
+
+    spec schema NoOverflow {
+        x: u64;
+        aborts_if x == MAX_U64;
+    }
+
+    spec module {
+        invariant forall a: address where exists<R>(a): global<R>(a).v > 0;
+    }
+}
+"#;
+
+    struct Package {
+        dir: tempfile::TempDir,
+        options: Options,
+    }
+
+    impl Package {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let options = Options {
+                move_sources: vec![
+                    dir.path().join("m.move").to_string_lossy().to_string(),
+                    dir.path().join("m.spec.move").to_string_lossy().to_string(),
+                ],
+                cache_dir: Some(dir.path().join("cache").to_string_lossy().to_string()),
+                ..Options::default()
+            };
+            let package = Self { dir, options };
+            package.write("m.move", MODULE);
+            package.write("m.spec.move", SPEC);
+            package
+        }
+
+        fn write(&self, file: &str, content: &str) {
+            fs::write(self.dir.path().join(file), content).unwrap();
+        }
+
+        fn edit(&self, file: &str, from: &str, to: &str) {
+            let path = self.dir.path().join(file);
+            let content = fs::read_to_string(&path).unwrap();
+            assert!(content.contains(from));
+            fs::write(path, content.replacen(from, to, 1)).unwrap();
+        }
+
+        /// Runs the prover with the cache up to verification, which is simulated: the targets
+        /// in `failing` fail, and all others are verified. Returns the names of the targets which
+        /// were not skipped.
+        fn run(&self, failing: &[&str]) -> BTreeSet<String> {
+            let env = build_env(&self.options.move_sources);
+            env.set_extension(self.options.prover.clone());
+            crate::create_init_num_operation_state(&env);
+            let mut targets = crate::create_and_process_bytecode(&self.options, &env);
+            assert!(!env.has_errors());
+
+            let mut cache = VerificationCache::load(&env, &self.options, &mut targets)
+                .unwrap()
+                .unwrap();
+            let remaining = targets
+                .get_funs_and_variants()
+                .filter(|(_, variant)| variant.is_verified())
+                .map(|(fun_id, _)| env.get_function(fun_id))
+                .filter(|fun_env| fun_env.module_env.is_target())
+                .map(|fun_env| fun_env.get_name_str())
+                .collect::<BTreeSet<_>>();
+            for fun_env in env
+                .get_modules()
+                .flat_map(|module_env| module_env.into_functions())
+            {
+                if failing.contains(&fun_env.get_name_str().as_str()) {
+                    env.error(&fun_env.get_loc(), "post-condition does not hold");
+                }
+            }
+            cache.record_verified(&env, &targets);
+            cache.save().unwrap();
+            remaining
+        }
+    }
+
+    fn build_env(sources: &[String]) -> GlobalEnv {
+        let mut env = run_model_builder_with_options(
+            vec![PackagePaths {
+                name: None,
+                paths: sources.to_vec(),
+                named_address_map: parse_addresses_from_options(vec![]).unwrap(),
+            }],
+            vec![],
+            vec![],
+            Default::default(),
+            false,
+            KnownAttribute::get_all_attribute_names(),
+        )
+        .unwrap();
+        let compiler_options = move_compiler_v2::Options::default()
+            .set_experiment(Experiment::OPTIMIZE, false)
+            .set_experiment(Experiment::SPEC_REWRITE, true);
+        env.set_extension(compiler_options.clone());
+        move_compiler_v2::check_and_rewrite_pipeline(
+            &compiler_options,
+            true,
+            RewritingScope::Everything,
+        )
+        .run(&mut env);
+        assert!(!env.has_errors());
+        env
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn all() -> BTreeSet<String> {
+        names(&["callee", "caller", "uncalled"])
+    }
+
+    #[test]
+    fn test_unchanged_targets_are_skipped() {
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        assert_eq!(package.run(&[]), names(&[]));
+        assert!(Path::new(package.options.cache_dir.as_ref().unwrap())
+            .join(CACHE_FILE)
+            .exists());
+    }
+
+    #[test]
+    fn test_editing_callee_invalidates_caller() {
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.move", "x + 1", "1 + x");
+        assert_eq!(package.run(&[]), names(&["callee", "caller"]));
+    }
+
+    #[test]
+    fn test_editing_uncalled_function() {
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.move", "        x\n", "        x * 1\n");
+        assert_eq!(package.run(&[]), names(&["uncalled"]));
+    }
+
+    #[test]
+    fn test_editing_spec_file() {
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.spec.move", "result == x + 1", "result > x");
+        assert_eq!(package.run(&[]), names(&["caller"]));
+    }
+
+    #[test]
+    fn test_editing_spec_helpers() {
+        // Spec functions and schemas are hashed with their module, so all its targets are
+        // re-verified
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.spec.move", "{ y }", "{ y + 0 }");
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.spec.move", "x == MAX_U64", "x + 1 > MAX_U64");
+        assert_eq!(package.run(&[]), all());
+        assert_eq!(package.run(&[]), names(&[]));
+    }
+
+    #[test]
+    fn test_editing_global_invariant() {
+        let package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.edit("m.spec.move", "global<R>(a).v > 0", "global<R>(a).v >= 1");
+        assert_eq!(package.run(&[]), all());
+    }
+
+    #[test]
+    fn test_force() {
+        let mut package = Package::new();
+        assert_eq!(package.run(&[]), all());
+        package.options.force = true;
+        assert_eq!(package.run(&[]), all());
+        package.options.force = false;
+        assert_eq!(package.run(&[]), names(&[]));
+    }
+
+    #[test]
+    fn test_failing_target_is_not_recorded() {
+        let package = Package::new();
+        assert_eq!(package.run(&["caller"]), all());
+        assert_eq!(package.run(&[]), names(&["caller"]));
+        assert_eq!(package.run(&[]), names(&[]));
+    }
+}
@@ -81,6 +81,11 @@ pub struct Options {
     pub compiler_v2: bool,
     /// The language version to use
     pub language_version: Option<LanguageVersion>,
+    /// The directory in which to cache the verification results of functions. If set, functions
+    /// whose verification conditions did not change since they were last verified are skipped.
+    pub cache_dir: Option<String>,
+    /// Whether to verify all functions, ignoring the results in `cache_dir`.
+    pub force: bool,
     /// BEGIN OF STRUCTURED OPTIONS. DO NOT ADD VALUE FIELDS AFTER THIS
     /// Options for the model builder.
     pub model_builder: ModelBuilderOptions,
@@ -123,6 +128,8 @@ impl Default for Options {
                 CompilerVersion::V2_0 => true,
             },
             language_version: None,
+            cache_dir: None,
+            force: false,
         }
     }
 }
@@ -245,6 +252,19 @@ impl Options {
                     .action(SetTrue)
                     .help("keeps intermediate artifacts of the backend around")
             )
+            .arg(
+                Arg::new("cache-dir")
+                    .long("cache-dir")
+                    .value_name("DIR")
+                    .help("caches verification results in DIR, and only verifies functions \
+                     which changed since they were last verified")
+            )
+            .arg(
+                Arg::new("force")
+                    .long("force")
+                    .action(SetTrue)
+                    .help("verifies all functions, ignoring the results cached in --cache-dir")
+            )
             .arg(
                 Arg::new("boogie-poly")
                     .long("boogie-poly")
@@ -742,6 +762,12 @@ impl Options {
         if matches.get_flag("keep") {
             options.backend.keep_artifacts = true;
         }
+        if matches.contains_id("cache-dir") {
+            options.cache_dir = Some(matches.get_one::<String>("cache-dir").unwrap().to_string());
+        }
+        if matches.get_flag("force") {
+            options.force = true;
+        }
         if matches.get_flag("boogie-poly") {
             options.prover.boogie_poly = true;
         }
@@ -4,7 +4,7 @@
 
 #![forbid(unsafe_code)]
 
-use crate::cli::Options;
+use crate::{cache::VerificationCache, cli::Options};
 use anyhow::anyhow;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream, WriteColor};
 #[allow(unused_imports)]
@@ -31,6 +31,7 @@ use std::{
     time::Instant,
 };
 
+pub mod cache;
 pub mod cli;
 
 // =================================================================================================
@@ -175,7 +176,7 @@ pub fn run_move_prover_with_model_v2<W: WriteColor>(
 
     // Create and process bytecode
     let now = Instant::now();
-    let targets = create_and_process_bytecode(&options, env);
+    let mut targets = create_and_process_bytecode(&options, env);
     let trafo_duration = now.elapsed();
     check_errors(
         env,
@@ -184,6 +185,9 @@ pub fn run_move_prover_with_model_v2<W: WriteColor>(
         "exiting with bytecode transformation errors",
     )?;
 
+    // Skip the targets which are verified according to the cache.
+    let mut cache = VerificationCache::load(env, &options, &mut targets)?;
+
     // Generate boogie code
     let now = Instant::now();
     let code_writer = generate_boogie(env, &options, &targets)?;
@@ -199,6 +203,10 @@ pub fn run_move_prover_with_model_v2<W: WriteColor>(
     let now = Instant::now();
     verify_boogie(env, &options, &targets, code_writer)?;
     let verify_duration = now.elapsed();
+    if let Some(cache) = &mut cache {
+        cache.record_verified(env, &targets);
+        cache.save()?;
+    }
 
     // Report durations.
     info!(
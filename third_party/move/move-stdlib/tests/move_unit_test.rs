@@ -36,6 +36,7 @@ fn run_tests_for_pkg(path_to_pkg: impl Into<String>, include_nursery_natives: bo
         natives,
         ChangeSet::new(),
         None,
+        None,
         /* compute_coverage */ false,
         &mut std::io::stdout(),
     )
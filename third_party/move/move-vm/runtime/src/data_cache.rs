@@ -182,6 +182,19 @@ impl<'r> TransactionDataCache<'r> {
         total_mutated_accounts
     }
 
+    /// The resources loaded by the transaction so far, for debuggers.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_resources(
+        &self,
+    ) -> impl Iterator<Item = (&AccountAddress, &Type, &MoveTypeLayout, &GlobalValue)> {
+        self.account_map.iter().flat_map(|(addr, account_cache)| {
+            account_cache
+                .data_map
+                .iter()
+                .map(move |(ty, (layout, value, _))| (addr, ty, layout, value))
+        })
+    }
+
     fn get_mut_or_insert_with<'a, K, V, F>(map: &'a mut BTreeMap<K, V>, k: &K, gen: F) -> &'a mut V
     where
         F: FnOnce() -> (K, V),
@@ -3,17 +3,190 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    interpreter::Interpreter,
-    loader::{Function, Loader},
+    data_cache::TransactionDataCache,
+    interpreter::{Frame, Interpreter},
+    loader::{Function, Loader, ModuleStorageAdapter},
 };
-use move_binary_format::file_format::Bytecode;
-use move_vm_types::values::{self, Locals};
+use move_binary_format::file_format::{Bytecode, FunctionDefinitionIndex};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{self, Locals, Reference, Value},
+};
+use once_cell::sync::Lazy;
 use std::{
     collections::BTreeSet,
     io::{self, Write},
     str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
+pub(crate) static DEBUG_HOOK: Lazy<Mutex<Option<Box<dyn DebugHook>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+// Checked before every instruction, so that no lock is taken unless a hook is set
+pub(crate) static DEBUG_HOOK_SET: AtomicBool = AtomicBool::new(false);
+
+/// Receives the state of the interpreter before every instruction it executes, e.g. to implement
+/// a debugger. Execution continues when `on_instruction` returns, so a debugger can pause it by
+/// blocking.
+pub trait DebugHook: Send {
+    fn on_instruction(&mut self, state: &DebugState);
+}
+
+/// Sets the hook called before every instruction executed by any VM in this process, or removes
+/// it with `None`.
+pub fn set_debug_hook(hook: Option<Box<dyn DebugHook>>) {
+    let mut debug_hook = DEBUG_HOOK.lock().unwrap();
+    DEBUG_HOOK_SET.store(hook.is_some(), Ordering::Relaxed);
+    *debug_hook = hook;
+}
+
+/// A value in the interpreter, as its type and BCS serialization. References are represented by
+/// the values they refer to.
+#[derive(Clone, Debug)]
+pub struct DebugValue {
+    pub type_tag: TypeTag,
+    pub bytes: Vec<u8>,
+}
+
+/// A frame of the call stack
+#[derive(Clone, Debug)]
+pub struct DebugFrame {
+    /// The module of the function, or `None` for scripts
+    pub module_id: Option<ModuleId>,
+    pub function_name: String,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: u16,
+    /// The locals of the function by index, `None` for those without a value
+    pub locals: Vec<Option<DebugValue>>,
+}
+
+/// The state of the interpreter before it executes an instruction. Where execution is can be
+/// checked cheaply, while the call stack and the global resources are only rendered on request.
+pub struct DebugState<'a> {
+    frame: &'a Frame,
+    instruction: &'a Bytecode,
+    loader: &'a Loader,
+    interpreter: &'a Interpreter,
+    data_store: &'a TransactionDataCache<'a>,
+    module_store: &'a ModuleStorageAdapter,
+}
+
+impl<'a> DebugState<'a> {
+    pub(crate) fn new(
+        frame: &'a Frame,
+        instruction: &'a Bytecode,
+        loader: &'a Loader,
+        interpreter: &'a Interpreter,
+        data_store: &'a TransactionDataCache<'a>,
+        module_store: &'a ModuleStorageAdapter,
+    ) -> Self {
+        Self {
+            frame,
+            instruction,
+            loader,
+            interpreter,
+            data_store,
+            module_store,
+        }
+    }
+
+    /// The module of the function being executed, or `None` for scripts
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.frame.function.module_id()
+    }
+
+    pub fn function_index(&self) -> FunctionDefinitionIndex {
+        self.frame.function.index()
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.frame.pc
+    }
+
+    pub fn instruction(&self) -> &Bytecode {
+        self.instruction
+    }
+
+    /// The number of frames on the call stack, including the one being executed
+    pub fn depth(&self) -> usize {
+        self.interpreter.debug_call_stack().len() + 1
+    }
+
+    /// The frames of the call stack, innermost first
+    pub fn frames(&self) -> Vec<DebugFrame> {
+        std::iter::once(self.frame)
+            .chain(self.interpreter.debug_call_stack().iter().rev())
+            .map(|frame| self.debug_frame(frame))
+            .collect()
+    }
+
+    /// The resources that exist among those loaded so far, by the address they are stored at
+    pub fn globals(&self) -> Vec<(AccountAddress, DebugValue)> {
+        self.data_store
+            .debug_resources()
+            .filter_map(|(addr, ty, _, global_value)| {
+                if !global_value.exists().ok()? {
+                    return None;
+                }
+                let value = global_value.borrow_global().ok()?;
+                let value = value.value_as::<Reference>().ok()?.read_ref().ok()?;
+                let value = self.debug_value(ty, value)?;
+                Some((*addr, value))
+            })
+            .collect()
+    }
+
+    fn debug_frame(&self, frame: &Frame) -> DebugFrame {
+        let function = &frame.function;
+        let locals = (0..function.local_count())
+            .map(|idx| {
+                let ty = match frame.local_tys.get(idx) {
+                    Some(ty) => ty.clone(),
+                    None => self
+                        .loader
+                        .ty_builder()
+                        .create_ty_with_subst(&function.local_tys()[idx], &frame.ty_args)
+                        .ok()?,
+                };
+                let value = frame.locals.copy_loc(idx).ok()?;
+                match ty {
+                    Type::Reference(ty) | Type::MutableReference(ty) => {
+                        let value = value.value_as::<Reference>().ok()?.read_ref().ok()?;
+                        self.debug_value(&ty, value)
+                    },
+                    ty => self.debug_value(&ty, value),
+                }
+            })
+            .collect();
+        DebugFrame {
+            module_id: function.module_id().cloned(),
+            function_name: function.name().to_string(),
+            function_index: function.index(),
+            pc: frame.pc,
+            locals,
+        }
+    }
+
+    fn debug_value(&self, ty: &Type, value: Value) -> Option<DebugValue> {
+        let layout = self
+            .loader
+            .type_to_type_layout(ty, self.module_store)
+            .ok()?;
+        Some(DebugValue {
+            type_tag: self.loader.type_to_type_tag(ty).ok()?,
+            bytes: value.simple_serialize(&layout)?,
+        })
+    }
+}
+
 #[derive(Debug)]
 enum DebugCommand {
     PrintStack,
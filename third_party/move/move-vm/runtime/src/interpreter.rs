@@ -1024,6 +1024,12 @@ impl Interpreter {
         Ok(())
     }
 
+    /// The frames of the callers of the function being executed, outermost first.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_call_stack(&self) -> &[Frame] {
+        &self.call_stack.0
+    }
+
     #[allow(dead_code)]
     pub(crate) fn debug_print_stack_trace<B: Write>(
         &self,
@@ -1346,12 +1352,13 @@ fn check_depth_of_type_impl(
 /// A `Frame` is the execution context for a function. It holds the locals of the function and
 /// the function itself.
 // #[derive(Debug)]
-struct Frame {
-    pc: u16,
-    locals: Locals,
-    function: Arc<Function>,
-    ty_args: Vec<Type>,
-    local_tys: Vec<Type>,
+pub(crate) struct Frame {
+    pub(crate) pc: u16,
+    pub(crate) locals: Locals,
+    pub(crate) function: Arc<Function>,
+    pub(crate) ty_args: Vec<Type>,
+    // Only set with paranoid type checks
+    pub(crate) local_tys: Vec<Type>,
     ty_cache: FrameTypeCache,
 }
 
@@ -2112,12 +2119,12 @@ impl Frame {
         loop {
             for instruction in &code[self.pc as usize..] {
                 trace!(
-                    &self.function,
-                    &self.locals,
-                    self.pc,
+                    self,
                     instruction,
                     resolver,
-                    interpreter
+                    interpreter,
+                    data_store,
+                    module_store
                 );
 
                 fail_point!("move_vm::interpreter_loop", |_| {
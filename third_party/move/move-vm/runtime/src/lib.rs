@@ -27,6 +27,8 @@ pub mod module_traversal;
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 mod debug;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub use debug::{set_debug_hook, DebugFrame, DebugHook, DebugState, DebugValue};
 
 mod access_control;
 
@@ -3,22 +3,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::debug::DebugContext;
+use crate::debug::{DebugContext, DebugState};
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use crate::{
-    interpreter::Interpreter,
-    loader::{Function, Loader},
+    data_cache::TransactionDataCache,
+    debug::{DEBUG_HOOK, DEBUG_HOOK_SET},
+    interpreter::{Frame, Interpreter},
+    loader::{Loader, ModuleStorageAdapter},
 };
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use ::{
     move_binary_format::file_format::Bytecode,
-    move_vm_types::values::Locals,
     once_cell::sync::Lazy,
     std::{
         env,
         fs::{File, OpenOptions},
         io::Write,
-        sync::Mutex,
+        sync::{atomic::Ordering, Mutex},
     },
 };
 
@@ -67,13 +68,15 @@ static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugC
 // Only include in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 pub(crate) fn trace(
-    function_desc: &Function,
-    locals: &Locals,
-    pc: u16,
+    frame: &Frame,
     instr: &Bytecode,
     loader: &Loader,
     interp: &Interpreter,
+    data_store: &TransactionDataCache,
+    module_store: &ModuleStorageAdapter,
 ) {
+    let function_desc = &frame.function;
+    let pc = frame.pc;
     if *TRACING_ENABLED {
         let buf_writer = &mut *LOGGING_FILE_WRITER.lock().unwrap();
         buf_writer
@@ -84,25 +87,43 @@ pub(crate) fn trace(
         }
     }
     if *DEBUGGING_ENABLED {
-        DEBUG_CONTEXT
-            .lock()
-            .unwrap()
-            .debug_loop(function_desc, locals, pc, instr, loader, interp);
+        DEBUG_CONTEXT.lock().unwrap().debug_loop(
+            function_desc,
+            &frame.locals,
+            pc,
+            instr,
+            loader,
+            interp,
+        );
+    }
+    if DEBUG_HOOK_SET.load(Ordering::Relaxed) {
+        if let Some(hook) = DEBUG_HOOK.lock().unwrap().as_mut() {
+            hook.on_instruction(&DebugState::new(
+                frame,
+                instr,
+                loader,
+                interp,
+                data_store,
+                module_store,
+            ));
+        }
     }
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    (
+        $frame:expr, $instr:tt, $resolver:expr, $interp:expr, $data_store:expr, $module_store:expr
+    ) => {
         // Only include this code in debug releases
         #[cfg(any(debug_assertions, feature = "debugging"))]
         $crate::tracing::trace(
-            &$function_desc,
-            $locals,
-            $pc,
+            $frame,
             &$instr,
             $resolver.loader(),
             $interp,
+            $data_store,
+            $module_store,
         )
     };
 }
@@ -20,7 +20,7 @@ use move_package::{
     compilation::{build_plan::BuildPlan, compiled_package::build_and_report_v2_driver},
     BuildConfig,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{debug_adapter::debug_unit_test, gas_meter::GasMeterHook, UnitTestingConfig};
 use move_vm_runtime::tracing::{LOGGING_FILE_WRITER, TRACING_ENABLED};
 use move_vm_test_utils::gas_schedule::CostTable;
 // if unix
@@ -32,7 +32,7 @@ use std::os::windows::process::ExitStatusExt;
 use std::{
     collections::HashMap,
     fs,
-    io::Write,
+    io::{Read, Write},
     ops::Deref,
     path::{Path, PathBuf},
     process::ExitStatus,
@@ -84,6 +84,9 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Seed for the generated arguments of `#[fuzz]` tests. A random seed is used if not given
+    #[clap(long = "fuzz-seed")]
+    pub fuzz_seed: Option<u64>,
 
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
@@ -113,6 +116,7 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            fuzz_seed,
             #[cfg(feature = "evm-backend")]
             evm,
         } = self;
@@ -126,6 +130,7 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            fuzz_seed,
             #[cfg(feature = "evm-backend")]
             evm,
 
@@ -138,6 +143,7 @@ impl Test {
             natives,
             genesis,
             cost_table,
+            None,
             compute_coverage,
             &mut std::io::stdout(),
         )?;
@@ -157,16 +163,14 @@ pub enum UnitTestResult {
     Failure,
 }
 
-pub fn run_move_unit_tests<W: Write + Send>(
+/// Compiles the package at `pkg_path` in test mode and builds the plan of its unit tests. The
+/// named addresses of the package are recorded in `unit_test_config`.
+pub fn build_test_plan<W: Write + Send>(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
-    mut unit_test_config: UnitTestingConfig,
-    natives: Vec<NativeFunctionRecord>,
-    genesis: ChangeSet,
-    cost_table: Option<CostTable>,
-    compute_coverage: bool,
+    unit_test_config: &mut UnitTestingConfig,
     writer: &mut W,
-) -> Result<UnitTestResult> {
+) -> Result<TestPlan> {
     let mut test_plan = None;
     let mut test_plan_v2 = None;
 
@@ -278,8 +282,22 @@ pub fn run_move_unit_tests<W: Write + Send>(
     let (test_plan, mut files, units) = test_plan.unwrap();
     files.extend(dep_file_map);
     let test_plan = test_plan.unwrap();
-    let no_tests = test_plan.is_empty();
-    let test_plan = TestPlan::new(test_plan, files, units);
+    Ok(TestPlan::new(test_plan, files, units))
+}
+
+pub fn run_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    mut unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    genesis: ChangeSet,
+    cost_table: Option<CostTable>,
+    gas_meter_hook: Option<GasMeterHook>,
+    compute_coverage: bool,
+    writer: &mut W,
+) -> Result<UnitTestResult> {
+    let test_plan = build_test_plan(pkg_path, build_config, &mut unit_test_config, writer)?;
+    let no_tests = test_plan.module_tests.is_empty();
 
     let trace_path = pkg_path.join(".trace");
     let coverage_map_path = pkg_path
@@ -302,7 +320,14 @@ pub fn run_move_unit_tests<W: Write + Send>(
     // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
     // the trace files.
     if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), Some(genesis), cost_table, writer)
+        .run_and_report_unit_tests(
+            test_plan,
+            Some(natives),
+            Some(genesis),
+            cost_table,
+            gas_meter_hook,
+            writer,
+        )
         .unwrap()
         .1
     {
@@ -322,6 +347,43 @@ pub fn run_move_unit_tests<W: Write + Send>(
     Ok(UnitTestResult::Success)
 }
 
+/// Runs the unit test `test_name` of the package at `pkg_path` under a Debug Adapter Protocol
+/// server, which speaks to the editor over `input` and `output`.
+pub fn debug_move_unit_test<R: Read + Send + 'static, W: Write>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    mut unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    genesis: ChangeSet,
+    cost_table: Option<CostTable>,
+    test_name: &str,
+    input: R,
+    output: W,
+) -> Result<UnitTestResult> {
+    // Compiler diagnostics go to stderr, as the output belongs to the protocol
+    let test_plan = build_test_plan(
+        pkg_path,
+        build_config,
+        &mut unit_test_config,
+        &mut std::io::stderr(),
+    )?;
+    let passed = debug_unit_test(
+        &unit_test_config,
+        test_plan,
+        test_name,
+        Some(natives),
+        Some(genesis),
+        cost_table,
+        input,
+        output,
+    )?;
+    Ok(if passed == Some(true) {
+        UnitTestResult::Success
+    } else {
+        UnitTestResult::Failure
+    })
+}
+
 impl From<UnitTestResult> for ExitStatus {
     fn from(result: UnitTestResult) -> Self {
         match result {
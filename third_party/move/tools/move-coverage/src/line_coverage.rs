@@ -0,0 +1,337 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Line coverage of source files, for tools that read LCOV or Cobertura reports. Bytecode
+//! coverage is mapped back to source lines through the source maps of the modules: a line is
+//! hit as many times as the most executed instruction starting on it.
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{bail, format_err, Result};
+use codespan::{FileId, Files};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::xml::xml_escape;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub struct FunctionLineCoverage {
+    /// The name of the function, qualified by its module
+    pub name: String,
+    /// The 1-based line the function is defined on
+    pub line: u32,
+    /// How many times the function was called
+    pub hits: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct FileLineCoverage {
+    pub functions: Vec<FunctionLineCoverage>,
+    /// The number of hits of the 1-based lines with code on them
+    pub lines: BTreeMap<u32, u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct LineCoverage {
+    pub files: BTreeMap<PathBuf, FileLineCoverage>,
+}
+
+impl FileLineCoverage {
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    fn functions_hit(&self) -> usize {
+        self.functions
+            .iter()
+            .filter(|function| function.hits > 0)
+            .count()
+    }
+}
+
+impl LineCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the coverage of `module`, whose source is at `file_path`.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        coverage_map: &ExecCoverageMap,
+        source_map: &SourceMap,
+        file_path: &Path,
+    ) -> Result<()> {
+        let file_contents = fs::read_to_string(file_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                file_path.display()
+            );
+        }
+        let mut files = Files::new();
+        let file_id = files.add(file_path.as_os_str().to_os_string(), file_contents);
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+        let file_coverage = self.files.entry(file_path.to_path_buf()).or_default();
+
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                Some(code_unit) => code_unit,
+                None => continue,
+            };
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let function_coverage = module_map.and_then(|fn_map| fn_map.function_maps.get(fn_name));
+            let hits_at = |code_offset: usize| {
+                function_coverage
+                    .and_then(|coverage| coverage.get(&(code_offset as u64)))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            let function_map = source_map.get_function_source_map(function_def_idx)?;
+            file_coverage.functions.push(FunctionLineCoverage {
+                name: format!("{}::{}", module_id.name(), fn_name),
+                line: line_number(&files, file_id, function_map.definition_location.start())?,
+                hits: hits_at(0),
+            });
+            for code_offset in 0..code_unit.code.len() {
+                let loc =
+                    source_map.get_code_location(function_def_idx, code_offset as CodeOffset)?;
+                let line = line_number(&files, file_id, loc.start())?;
+                let hits = file_coverage.lines.entry(line).or_insert(0);
+                *hits = std::cmp::max(*hits, hits_at(code_offset));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the coverage as an LCOV tracefile.
+    pub fn output_lcov<W: Write>(&self, output_writer: &mut W) -> io::Result<()> {
+        for (path, file_coverage) in &self.files {
+            writeln!(output_writer, "TN:")?;
+            writeln!(output_writer, "SF:{}", path.display())?;
+            for function in &file_coverage.functions {
+                writeln!(output_writer, "FN:{},{}", function.line, function.name)?;
+            }
+            for function in &file_coverage.functions {
+                writeln!(output_writer, "FNDA:{},{}", function.hits, function.name)?;
+            }
+            writeln!(output_writer, "FNF:{}", file_coverage.functions.len())?;
+            writeln!(output_writer, "FNH:{}", file_coverage.functions_hit())?;
+            for (line, hits) in &file_coverage.lines {
+                writeln!(output_writer, "DA:{},{}", line, hits)?;
+            }
+            writeln!(output_writer, "LF:{}", file_coverage.lines.len())?;
+            writeln!(output_writer, "LH:{}", file_coverage.lines_hit())?;
+            writeln!(output_writer, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the coverage as a Cobertura XML report of a single package, with the paths of the
+    /// files relative to `source_root` where possible.
+    pub fn output_cobertura<W: Write>(
+        &self,
+        package_name: &str,
+        source_root: &Path,
+        timestamp: u64,
+        output_writer: &mut W,
+    ) -> io::Result<()> {
+        let lines_valid: usize = self.files.values().map(|file| file.lines.len()).sum();
+        let lines_covered: usize = self.files.values().map(|file| file.lines_hit()).sum();
+
+        writeln!(output_writer, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            output_writer,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            output_writer,
+            r#"<coverage line-rate="{}" branch-rate="0" lines-covered="{}" lines-valid="{}" branches-covered="0" branches-valid="0" complexity="0" version="0" timestamp="{}">"#,
+            rate(lines_covered, lines_valid),
+            lines_covered,
+            lines_valid,
+            timestamp,
+        )?;
+        writeln!(output_writer, "  <sources>")?;
+        writeln!(
+            output_writer,
+            "    <source>{}</source>",
+            xml_escape(&source_root.display().to_string())
+        )?;
+        writeln!(output_writer, "  </sources>")?;
+        writeln!(output_writer, "  <packages>")?;
+        writeln!(
+            output_writer,
+            r#"    <package name="{}" line-rate="{}" branch-rate="0" complexity="0">"#,
+            xml_escape(package_name),
+            rate(lines_covered, lines_valid),
+        )?;
+        writeln!(output_writer, "      <classes>")?;
+        for (path, file_coverage) in &self.files {
+            let relative_path = path.strip_prefix(source_root).unwrap_or(path);
+            let class_name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            writeln!(
+                output_writer,
+                r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="0" complexity="0">"#,
+                xml_escape(&class_name),
+                xml_escape(&relative_path.display().to_string()),
+                rate(file_coverage.lines_hit(), file_coverage.lines.len()),
+            )?;
+            writeln!(output_writer, "          <methods>")?;
+            for function in &file_coverage.functions {
+                writeln!(
+                    output_writer,
+                    r#"            <method name="{}" signature="" line-rate="{}" branch-rate="0" complexity="0">"#,
+                    xml_escape(&function.name),
+                    (function.hits > 0) as u8,
+                )?;
+                writeln!(
+                    output_writer,
+                    r#"              <lines><line number="{}" hits="{}"/></lines>"#,
+                    function.line, function.hits,
+                )?;
+                writeln!(output_writer, "            </method>")?;
+            }
+            writeln!(output_writer, "          </methods>")?;
+            writeln!(output_writer, "          <lines>")?;
+            for (line, hits) in &file_coverage.lines {
+                writeln!(
+                    output_writer,
+                    r#"            <line number="{}" hits="{}" branch="false"/>"#,
+                    line, hits,
+                )?;
+            }
+            writeln!(output_writer, "          </lines>")?;
+            writeln!(output_writer, "        </class>")?;
+        }
+        writeln!(output_writer, "      </classes>")?;
+        writeln!(output_writer, "    </package>")?;
+        writeln!(output_writer, "  </packages>")?;
+        writeln!(output_writer, "</coverage>")
+    }
+}
+
+fn line_number(files: &Files<String>, file_id: FileId, byte_index: u32) -> Result<u32> {
+    let location = files
+        .location(file_id, byte_index)
+        .map_err(|err| format_err!("Invalid source location: {:?}", err))?;
+    Ok(location.line.0 + 1)
+}
+
+fn rate(covered: usize, valid: usize) -> f64 {
+    if valid == 0 {
+        1.0
+    } else {
+        covered as f64 / valid as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_coverage() -> LineCoverage {
+        let file_coverage = FileLineCoverage {
+            functions: vec![
+                FunctionLineCoverage {
+                    name: "m::f".to_string(),
+                    line: 3,
+                    hits: 2,
+                },
+                FunctionLineCoverage {
+                    name: "m::g".to_string(),
+                    line: 7,
+                    hits: 0,
+                },
+            ],
+            lines: BTreeMap::from([(4, 2), (5, 2), (8, 0)]),
+        };
+        LineCoverage {
+            files: BTreeMap::from([(PathBuf::from("/pkg/sources/m.move"), file_coverage)]),
+        }
+    }
+
+    #[test]
+    fn test_output_lcov() {
+        let mut output = vec![];
+        line_coverage().output_lcov(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "TN:
+SF:/pkg/sources/m.move
+FN:3,m::f
+FN:7,m::g
+FNDA:2,m::f
+FNDA:0,m::g
+FNF:2
+FNH:1
+DA:4,2
+DA:5,2
+DA:8,0
+LF:3
+LH:2
+end_of_record
+"
+        );
+    }
+
+    #[test]
+    fn test_output_cobertura() {
+        let mut output = vec![];
+        line_coverage()
+            .output_cobertura("pkg<&>", Path::new("/pkg"), 1700000000, &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"<?xml version="1.0" ?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+<coverage line-rate="0.6666666666666666" branch-rate="0" lines-covered="2" lines-valid="3" branches-covered="0" branches-valid="0" complexity="0" version="0" timestamp="1700000000">
+  <sources>
+    <source>/pkg</source>
+  </sources>
+  <packages>
+    <package name="pkg&lt;&amp;&gt;" line-rate="0.6666666666666666" branch-rate="0" complexity="0">
+      <classes>
+        <class name="m" filename="sources/m.move" line-rate="0.6666666666666666" branch-rate="0" complexity="0">
+          <methods>
+            <method name="m::f" signature="" line-rate="1" branch-rate="0" complexity="0">
+              <lines><line number="3" hits="2"/></lines>
+            </method>
+            <method name="m::g" signature="" line-rate="0" branch-rate="0" complexity="0">
+              <lines><line number="7" hits="0"/></lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="4" hits="2" branch="false"/>
+            <line number="5" hits="2" branch="false"/>
+            <line number="8" hits="0" branch="false"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#
+        );
+    }
+}
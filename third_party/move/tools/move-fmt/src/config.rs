@@ -0,0 +1,46 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// The name of the file configuring the formatter, at the root of a package
+pub const CONFIG_FILE: &str = "movefmt.toml";
+
+/// How sources are formatted
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    /// The number of spaces to indent by
+    pub indent_size: usize,
+    /// The maximum number of consecutive blank lines kept
+    pub max_blank_lines: usize,
+    /// Whether adjacent use declarations, and the members imported by each, are sorted
+    pub sort_uses: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_size: 4,
+            max_blank_lines: 1,
+            sort_uses: true,
+        }
+    }
+}
+
+impl FormatConfig {
+    /// Reads the configuration of the package at `package_path`, or the default one if the
+    /// package has none
+    pub fn load(package_path: &Path) -> Result<Self> {
+        let path = package_path.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read `{}`", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Invalid formatter configuration `{}`", path.display()))
+    }
+}
@@ -0,0 +1,454 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lays out the tokens of a source file. Comments are kept where they are, and so are line
+//! breaks within declarations and statements, modulo where they fall around operators and
+//! braces. Everything else is derived from the tokens, and what the syntax tree tells of them.
+
+use crate::config::FormatConfig;
+use anyhow::{anyhow, Result};
+use move_command_line_common::files::FileHash;
+use move_compiler::parser::lexer::{Lexer, Tok};
+
+/// What separates two tokens, besides spaces
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Trivia<'a> {
+    /// A number of consecutive line breaks
+    Newlines(usize),
+    /// A line or block comment, including its delimiters
+    Comment(&'a str),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Token<'a> {
+    pub tok: Tok,
+    pub text: &'a str,
+    /// The offset of the token in the source
+    pub start: usize,
+    /// The comments and line breaks between the previous token and this one
+    pub leading: Vec<Trivia<'a>>,
+    /// Whether there is whitespace right before the token
+    pub space_before: bool,
+    /// Whether the token has to start a line
+    pub break_before: bool,
+    /// Whether the token is a binary operator
+    pub binary: bool,
+}
+
+impl Token<'_> {
+    pub fn has_comments(&self) -> bool {
+        self.leading
+            .iter()
+            .any(|trivia| matches!(trivia, Trivia::Comment(_)))
+    }
+
+    pub fn newlines(&self) -> usize {
+        self.leading
+            .iter()
+            .map(|trivia| match trivia {
+                Trivia::Newlines(count) => *count,
+                Trivia::Comment(_) => 0,
+            })
+            .sum()
+    }
+}
+
+/// Splits `source` into tokens, ending with an `EOF` token which holds the trailing comments
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token<'_>>> {
+    let mut lexer = Lexer::new(source, FileHash::new(source));
+    let mut tokens = vec![];
+    let mut prev_end = 0;
+    loop {
+        lexer
+            .advance()
+            .map_err(|_| anyhow!("Invalid token after offset {}", prev_end))?;
+        let start = lexer.start_loc();
+        let gap = &source[prev_end..start];
+        let tok = lexer.peek();
+        tokens.push(Token {
+            tok,
+            text: lexer.content(),
+            start,
+            leading: trivia(gap),
+            space_before: gap.ends_with(char::is_whitespace),
+            break_before: false,
+            binary: false,
+        });
+        if tok == Tok::EOF {
+            return Ok(tokens);
+        }
+        prev_end = start + lexer.content().len();
+    }
+}
+
+/// Splits the whitespace and comments between two tokens into trivia
+fn trivia(gap: &str) -> Vec<Trivia<'_>> {
+    let mut trivia = vec![];
+    let mut rest = gap;
+    while let Some(c) = rest.chars().next() {
+        let len = if rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            trivia.push(Trivia::Comment(rest[..len].trim_end()));
+            len
+        } else if rest.starts_with("/*") {
+            let len = block_comment_len(rest);
+            trivia.push(Trivia::Comment(&rest[..len]));
+            len
+        } else {
+            if c == '\n' {
+                match trivia.last_mut() {
+                    Some(Trivia::Newlines(count)) => *count += 1,
+                    _ => trivia.push(Trivia::Newlines(1)),
+                }
+            }
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    trivia
+}
+
+/// The length of the block comment `text` starts with. As in the lexer, block comments nest.
+fn block_comment_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    text.len()
+}
+
+/// Puts the item following each attribute on a line of its own
+pub(crate) fn break_after_attributes(tokens: &mut [Token]) {
+    let mut i = 0;
+    while i + 1 < tokens.len() {
+        if tokens[i].tok != Tok::NumSign || tokens[i + 1].tok != Tok::LBracket {
+            i += 1;
+            continue;
+        }
+        let mut depth = 0;
+        let mut j = i + 1;
+        while j < tokens.len() {
+            match tokens[j].tok {
+                Tok::LBracket => depth += 1,
+                Tok::RBracket => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                },
+                _ => (),
+            }
+            j += 1;
+        }
+        if let Some(next) = tokens.get_mut(j + 1) {
+            if next.tok != Tok::EOF {
+                next.break_before = true;
+            }
+        }
+        i = j + 1;
+    }
+}
+
+/// Normalizes where lines break around tokens, unless comments are in the way: use
+/// declarations start a line, commas, semicolons and binary operators end one, opening braces
+/// and `else` stay on the line before, and the contents of braces spanning lines go on lines
+/// of their own.
+pub(crate) fn normalize_line_breaks(tokens: &mut [Token]) {
+    for i in 0..tokens.len() {
+        let prev = i.checked_sub(1).map(|j| tokens[j].tok);
+        match tokens[i].tok {
+            Tok::Use => tokens[i].break_before = true,
+            Tok::Comma | Tok::Semicolon => move_break_after(tokens, i),
+            _ if tokens[i].binary => move_break_after(tokens, i),
+            // An opening brace starts a line only where a block is a statement or an argument
+            Tok::LBrace
+                if !matches!(
+                    prev,
+                    None | Some(
+                        Tok::LBrace
+                            | Tok::RBrace
+                            | Tok::Semicolon
+                            | Tok::Comma
+                            | Tok::LParen
+                            | Tok::LBracket
+                    )
+                ) =>
+            {
+                join_line(&mut tokens[i])
+            },
+            Tok::Else if prev == Some(Tok::RBrace) => join_line(&mut tokens[i]),
+            Tok::RBrace if prev == Some(Tok::LBrace) => {
+                join_line(&mut tokens[i]);
+                tokens[i].space_before = false;
+            },
+            _ => (),
+        }
+    }
+    break_braces(tokens);
+}
+
+/// Drops the line breaks before a token, if there are no comments before it
+fn join_line(token: &mut Token) {
+    if !token.has_comments() {
+        token.leading.clear();
+    }
+}
+
+/// Moves the line breaks before `tokens[i]` after it, if there are no comments before it
+fn move_break_after(tokens: &mut [Token], i: usize) {
+    let newlines = tokens[i].newlines();
+    if newlines == 0 || tokens[i].has_comments() {
+        return;
+    }
+    tokens[i].leading.clear();
+    if let Some(next) = tokens.get_mut(i + 1) {
+        if next.newlines() == 0 {
+            next.leading.insert(0, Trivia::Newlines(newlines));
+        }
+    }
+}
+
+/// Puts the contents of braces on lines of their own, if a line breaks within them
+fn break_braces(tokens: &mut [Token]) {
+    // The open brackets, and whether a line breaks within them
+    let mut brackets: Vec<(usize, bool)> = vec![];
+    for i in 0..tokens.len() {
+        if tokens[i].break_before || tokens[i].newlines() > 0 {
+            for (_, breaks) in &mut brackets {
+                *breaks = true;
+            }
+        }
+        match tokens[i].tok {
+            Tok::LParen | Tok::LBracket | Tok::LBrace => brackets.push((i, false)),
+            Tok::RParen | Tok::RBracket => {
+                brackets.pop();
+            },
+            Tok::RBrace => {
+                if let Some((open, true)) = brackets.pop() {
+                    if tokens[open].tok == Tok::LBrace {
+                        tokens[open + 1].break_before = true;
+                        tokens[i].break_before = true;
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Prints the tokens, with their comments, as formatted source
+pub(crate) fn layout(tokens: &[Token], config: &FormatConfig) -> String {
+    let mut printer = Printer {
+        config,
+        out: String::new(),
+        brackets: vec![],
+        indent: 0,
+        last_tok: None,
+        after_comment: false,
+        at_line_start: true,
+        blank_lines: 0,
+    };
+    for (i, token) in tokens.iter().enumerate() {
+        for trivia in &token.leading {
+            match trivia {
+                Trivia::Newlines(count) => printer.newline(*count),
+                Trivia::Comment(comment) => printer.comment(comment),
+            }
+        }
+        if token.tok == Tok::EOF {
+            break;
+        }
+        if token.break_before {
+            printer.newline(1);
+        }
+        let space = if token.has_comments() {
+            token.space_before
+        } else {
+            i > 0 && space_between(i.checked_sub(2).map(|j| &tokens[j]), &tokens[i - 1], token)
+        };
+        printer.token(token.tok, token.text, space);
+    }
+    if !printer.at_line_start {
+        printer.out.push('\n');
+    }
+    printer.out
+}
+
+struct Printer<'a> {
+    config: &'a FormatConfig,
+    out: String,
+    /// The open brackets, with the indentation of the lines within them
+    brackets: Vec<(Tok, usize)>,
+    /// The indentation brackets opened on the current line are recorded with. This is the
+    /// indentation of the line, unless it closes brackets opened on a less indented line.
+    indent: usize,
+    /// The last token printed
+    last_tok: Option<Tok>,
+    /// Whether a comment has been printed after the last token
+    after_comment: bool,
+    /// Whether nothing has been printed on the current line yet
+    at_line_start: bool,
+    /// The number of blank lines to print before the next line
+    blank_lines: usize,
+}
+
+impl Printer<'_> {
+    fn newline(&mut self, count: usize) {
+        if !self.at_line_start {
+            self.out.push('\n');
+            self.at_line_start = true;
+            self.blank_lines = 0;
+        }
+        let blank_lines = (count - 1).min(self.config.max_blank_lines);
+        self.blank_lines = self.blank_lines.max(blank_lines);
+    }
+
+    /// Starts a line, which begins with `first`, or with a comment if that is `None`
+    fn begin_line(&mut self, first: Option<Tok>) {
+        let closes = matches!(first, Some(Tok::RParen | Tok::RBracket | Tok::RBrace));
+        // Blank lines are dropped at the start of the file and of blocks, and at their end
+        let after_open_brace = !self.after_comment && self.last_tok == Some(Tok::LBrace);
+        if !self.out.is_empty() && !after_open_brace && first != Some(Tok::RBrace) {
+            for _ in 0..self.blank_lines {
+                self.out.push('\n');
+            }
+        }
+        self.blank_lines = 0;
+
+        self.indent = match self.brackets.last() {
+            Some((_, indent)) if closes => *indent,
+            Some((_, indent)) => indent + 1,
+            None => 0,
+        };
+        // A statement or declaration continued from the previous line is indented once more,
+        // except for an `acquires` or `else` clause. Within parentheses and brackets, the
+        // bracket is indentation enough.
+        let in_block = matches!(self.brackets.last(), None | Some((Tok::LBrace, _)));
+        if in_block
+            && !closes
+            && !matches!(first, Some(Tok::Acquires | Tok::Else))
+            && !matches!(
+                self.last_tok,
+                None | Some(
+                    Tok::LBrace
+                        | Tok::RBrace
+                        | Tok::LParen
+                        | Tok::LBracket
+                        | Tok::RBracket
+                        | Tok::Comma
+                        | Tok::Semicolon
+                )
+            )
+        {
+            self.indent += 1;
+        }
+        self.out
+            .extend(std::iter::repeat(' ').take(self.indent * self.config.indent_size));
+        self.at_line_start = false;
+    }
+
+    fn comment(&mut self, comment: &str) {
+        if self.at_line_start {
+            self.begin_line(None);
+        } else if self.after_comment || !matches!(self.last_tok, Some(Tok::LParen | Tok::LBracket))
+        {
+            self.out.push(' ');
+        }
+        for (i, line) in comment.lines().enumerate() {
+            if i > 0 {
+                self.out.push('\n');
+            }
+            self.out.push_str(line.trim_end());
+        }
+        self.after_comment = true;
+    }
+
+    fn token(&mut self, tok: Tok, text: &str, space: bool) {
+        if self.at_line_start {
+            self.begin_line(Some(tok));
+        } else if space {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        match tok {
+            Tok::LParen | Tok::LBracket | Tok::LBrace => self.brackets.push((tok, self.indent)),
+            Tok::RParen | Tok::RBracket | Tok::RBrace => {
+                if let Some((_, indent)) = self.brackets.pop() {
+                    self.indent = self.indent.min(indent);
+                }
+            },
+            _ => (),
+        }
+        self.last_tok = Some(tok);
+        self.after_comment = false;
+    }
+}
+
+/// Whether a space separates `prev` from `next` on a line, given the token before `prev`.
+/// Binary operators are spaced, while `*` and `&` otherwise dereference and borrow, and `<`
+/// and `>` otherwise enclose type arguments. Elsewhere the source is followed if spacing is
+/// optional.
+fn space_between(before_prev: Option<&Token>, prev: &Token, next: &Token) -> bool {
+    use Tok::*;
+    match (prev.tok, next.tok) {
+        (_, Comma | Semicolon | Colon) => false,
+        (LParen | LBracket, _) | (_, RParen | RBracket) => false,
+        (ColonColon | Period | AtSign | NumSign | Exclaim, _) | (_, ColonColon | Period) => false,
+        // Function declarations
+        (Identifier, LParen) if before_prev.map(|token| token.tok) == Some(Fun) => false,
+        // The token includes the space following `&mut`
+        (AmpMut, _) => false,
+        _ if prev.binary || next.binary => true,
+        (Star | Amp | Less, _) | (_, Less | Greater | GreaterGreater) => false,
+        (Comma | Colon | If | While, _) => true,
+        (_, LBrace) => true,
+        (LBrace, _) | (_, RBrace) => next.space_before,
+        // Always binary operators, or `=` and the `+` of ability constraints
+        (
+            Equal
+            | EqualEqual
+            | ExclaimEqual
+            | LessEqual
+            | GreaterEqual
+            | EqualEqualGreater
+            | LessEqualEqualGreater
+            | Plus
+            | Minus
+            | Slash
+            | Percent
+            | Caret
+            | LessLess,
+            _,
+        )
+        | (
+            _,
+            Equal
+            | EqualEqual
+            | ExclaimEqual
+            | LessEqual
+            | GreaterEqual
+            | EqualEqualGreater
+            | LessEqualEqualGreater
+            | Plus
+            | Minus
+            | Slash
+            | Percent
+            | Caret
+            | LessLess,
+        ) => true,
+        _ => next.space_before,
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A source formatter for Move.
+//!
+//! Formatting keeps the comments of a source file, and the line breaks within its
+//! declarations and statements, and derives everything else from its tokens and syntax tree:
+//! declarations and the statements of blocks start lines, lines are indented by nesting, with
+//! expressions continued on the next line indented once more, operators and punctuation are
+//! spaced by what they are, e.g. `*` in `a * b` and `*r`, continued lines break after
+//! operators and commas, attributes end their line, blank lines are capped, and use
+//! declarations are sorted. The result always has the same tokens, modulo the order of use
+//! declarations, which is checked before it is returned.
+
+mod config;
+mod format;
+mod syntax;
+mod uses;
+
+use anyhow::{bail, Result};
+pub use config::{FormatConfig, CONFIG_FILE};
+use move_command_line_common::files::{find_move_filenames, FileHash};
+use move_compiler::{
+    diagnostics::{report_diagnostics_to_buffer, FilesSourceText},
+    parser::{ast::Definition, syntax::parse_file_string},
+    shared::{CompilationEnv, Flags},
+};
+use move_package::source_package::layout::SourcePackageLayout;
+use move_symbol_pool::Symbol;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Formats Move source code. Sources which don't parse are rejected, with `name` identifying
+/// the source in the errors.
+pub fn format_source(name: &str, source: &str, config: &FormatConfig) -> Result<String> {
+    let definitions = parse(name, source)?;
+    let mut tokens = format::tokenize(source)?;
+    syntax::mark_tokens(&mut tokens, &definitions);
+    format::break_after_attributes(&mut tokens);
+    format::normalize_line_breaks(&mut tokens);
+    if config.sort_uses {
+        uses::sort_uses(&mut tokens);
+    }
+    let formatted = format::layout(&tokens, config);
+
+    let formatted_tokens = format::tokenize(&formatted)?;
+    let comments = |tokens: &[format::Token]| {
+        tokens
+            .iter()
+            .flat_map(|token| &token.leading)
+            .filter(|trivia| matches!(trivia, format::Trivia::Comment(_)))
+            .count()
+    };
+    if formatted_tokens.len() != tokens.len()
+        || formatted_tokens
+            .iter()
+            .zip(&tokens)
+            .any(|(formatted, token)| formatted.tok != token.tok || formatted.text != token.text)
+        || comments(&formatted_tokens) != comments(&tokens)
+    {
+        bail!("Formatting `{}` would change its meaning", name)
+    }
+    Ok(formatted)
+}
+
+/// The Move source files of the package at `package_path`
+pub fn package_files(package_path: &Path) -> Result<Vec<PathBuf>> {
+    let dirs: Vec<PathBuf> = [
+        SourcePackageLayout::Sources,
+        SourcePackageLayout::Specifications,
+        SourcePackageLayout::Tests,
+        SourcePackageLayout::Scripts,
+        SourcePackageLayout::Examples,
+    ]
+    .iter()
+    .map(|layout| package_path.join(layout.path()))
+    .filter(|dir| dir.exists())
+    .collect();
+    Ok(find_move_filenames(&dirs, false)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+fn parse(name: &str, source: &str) -> Result<Vec<Definition>> {
+    let file_hash = FileHash::new(source);
+    let mut env = CompilationEnv::new(Flags::empty(), BTreeSet::new());
+    match parse_file_string(&mut env, file_hash, source) {
+        Ok((definitions, _)) => Ok(definitions),
+        Err(diags) => {
+            let files: FilesSourceText =
+                HashMap::from([(file_hash, (Symbol::from(name), source.to_string()))]);
+            let report = report_diagnostics_to_buffer(&files, diags);
+            bail!("{}", String::from_utf8_lossy(&report))
+        },
+    }
+}
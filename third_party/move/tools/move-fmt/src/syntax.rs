@@ -0,0 +1,219 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! What the layout takes from the syntax tree of a source file: which tokens are binary
+//! operators, as `*`, `&`, `|`, `<` and `>` can be other things, and which tokens start a
+//! declaration or statement, and so a line.
+
+use crate::format::Token;
+use move_compiler::parser::ast::{
+    Attributes, BinOp_, Definition, Exp, Exp_, FunctionBody_, ModuleDefinition, ModuleMember,
+    Sequence, SequenceItem_, SpecBlock, SpecBlockMember_,
+};
+use move_ir_types::location::Loc;
+use std::collections::BTreeSet;
+
+/// Marks the binary operators of `definitions`, parsed from the source of `tokens`, and
+/// breaks the line before the members of modules and spec blocks, and before the statements
+/// of blocks which have more than one
+pub(crate) fn mark_tokens(tokens: &mut [Token], definitions: &[Definition]) {
+    let mut facts = SyntaxFacts::default();
+    for definition in definitions {
+        facts.definition(definition);
+    }
+    for token in tokens {
+        token.binary |= facts.binary_ops.contains(&token.start);
+        token.break_before |= facts.line_starts.contains(&token.start);
+    }
+}
+
+#[derive(Default)]
+struct SyntaxFacts {
+    /// The offsets of the binary operators
+    binary_ops: BTreeSet<usize>,
+    /// The offsets of the tokens which start a line
+    line_starts: BTreeSet<usize>,
+}
+
+impl SyntaxFacts {
+    fn line_start(&mut self, attributes: &[Attributes], loc: Loc) {
+        let start = attributes.first().map_or(loc.start(), |attributes| {
+            attributes.loc.start().min(loc.start())
+        });
+        self.line_starts.insert(start as usize);
+    }
+
+    fn definition(&mut self, definition: &Definition) {
+        match definition {
+            Definition::Module(module) => self.module(module),
+            Definition::Address(address) => {
+                for module in &address.modules {
+                    self.line_start(&module.attributes, module.loc);
+                    self.module(module);
+                }
+            },
+            Definition::Script(script) => {
+                for constant in &script.constants {
+                    self.line_start(&constant.attributes, constant.loc);
+                    self.exp(&constant.value);
+                }
+                self.line_start(&script.function.attributes, script.function.loc);
+                if let FunctionBody_::Defined(sequence) = &script.function.body.value {
+                    self.sequence(sequence);
+                }
+                for spec in &script.specs {
+                    self.line_start(&spec.value.attributes, spec.loc);
+                    self.spec_block(spec);
+                }
+            },
+        }
+    }
+
+    fn module(&mut self, module: &ModuleDefinition) {
+        for member in &module.members {
+            match member {
+                ModuleMember::Function(function) => {
+                    self.line_start(&function.attributes, function.loc);
+                    if let FunctionBody_::Defined(sequence) = &function.body.value {
+                        self.sequence(sequence);
+                    }
+                },
+                ModuleMember::Struct(struct_def) => {
+                    self.line_start(&struct_def.attributes, struct_def.loc)
+                },
+                ModuleMember::Friend(friend) => self.line_start(&friend.attributes, friend.loc),
+                ModuleMember::Constant(constant) => {
+                    self.line_start(&constant.attributes, constant.loc);
+                    self.exp(&constant.value);
+                },
+                ModuleMember::Spec(spec) => {
+                    self.line_start(&spec.value.attributes, spec.loc);
+                    self.spec_block(spec);
+                },
+                // Use declarations have no location, the layout starts a line at each `use`
+                ModuleMember::Use(_) => (),
+            }
+        }
+    }
+
+    fn spec_block(&mut self, spec: &SpecBlock) {
+        for member in &spec.value.members {
+            self.line_starts.insert(member.loc.start() as usize);
+            match &member.value {
+                SpecBlockMember_::Condition {
+                    exp,
+                    additional_exps,
+                    ..
+                } => {
+                    self.exp(exp);
+                    additional_exps.iter().for_each(|exp| self.exp(exp));
+                },
+                SpecBlockMember_::Function { body, .. } => {
+                    if let FunctionBody_::Defined(sequence) = &body.value {
+                        self.sequence(sequence);
+                    }
+                },
+                SpecBlockMember_::Variable { init, .. } => {
+                    init.iter().for_each(|exp| self.exp(exp))
+                },
+                SpecBlockMember_::Let { def, .. } => self.exp(def),
+                SpecBlockMember_::Update { lhs, rhs } => {
+                    self.exp(lhs);
+                    self.exp(rhs);
+                },
+                SpecBlockMember_::Include { exp, .. } | SpecBlockMember_::Apply { exp, .. } => {
+                    self.exp(exp)
+                },
+                SpecBlockMember_::Pragma { .. } => (),
+            }
+        }
+    }
+
+    fn sequence(&mut self, (uses, items, _, last): &Sequence) {
+        // A block with a single expression may stay on one line
+        let breaks = !uses.is_empty() || !items.is_empty();
+        for item in items {
+            if breaks {
+                self.line_starts.insert(item.loc.start() as usize);
+            }
+            match &item.value {
+                SequenceItem_::Seq(exp) | SequenceItem_::Bind(_, _, exp) => self.exp(exp),
+                SequenceItem_::Declare(..) => (),
+            }
+        }
+        if let Some(last) = &**last {
+            if breaks {
+                self.line_starts.insert(last.loc.start() as usize);
+            }
+            self.exp(last);
+        }
+    }
+
+    fn exp(&mut self, exp: &Exp) {
+        match &exp.value {
+            Exp_::BinopExp(lhs, op, rhs) => {
+                self.exp(lhs);
+                // Ranges are written without spaces, e.g. `0..len`
+                if op.value != BinOp_::Range {
+                    self.binary_ops.insert(op.loc.start() as usize);
+                }
+                self.exp(rhs);
+            },
+            Exp_::Call(_, _, _, args) | Exp_::Vector(_, _, args) => {
+                args.value.iter().for_each(|arg| self.exp(arg))
+            },
+            Exp_::Pack(_, _, fields) => fields.iter().for_each(|(_, exp)| self.exp(exp)),
+            Exp_::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                if let Some(if_false) = if_false {
+                    self.exp(if_false);
+                }
+            },
+            Exp_::While(cond, body) => {
+                self.exp(cond);
+                self.exp(body);
+            },
+            Exp_::Block(sequence) => self.sequence(sequence),
+            Exp_::Quant(_, ranges, triggers, condition, body) => {
+                ranges
+                    .value
+                    .iter()
+                    .for_each(|range| self.exp(&range.value.1));
+                triggers.iter().flatten().for_each(|exp| self.exp(exp));
+                if let Some(condition) = condition {
+                    self.exp(condition);
+                }
+                self.exp(body);
+            },
+            Exp_::ExpList(exps) => exps.iter().for_each(|exp| self.exp(exp)),
+            Exp_::Assign(lhs, rhs) | Exp_::Index(lhs, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs);
+            },
+            Exp_::Return(exp) => {
+                if let Some(exp) = exp {
+                    self.exp(exp);
+                }
+            },
+            Exp_::Loop(exp)
+            | Exp_::Lambda(_, exp)
+            | Exp_::Abort(exp)
+            | Exp_::Dereference(exp)
+            | Exp_::UnaryExp(_, exp)
+            | Exp_::Borrow(_, exp)
+            | Exp_::Dot(exp, _)
+            | Exp_::Cast(exp, _)
+            | Exp_::Annotate(exp, _) => self.exp(exp),
+            Exp_::Spec(spec) => self.spec_block(spec),
+            Exp_::Value(_)
+            | Exp_::Move(_)
+            | Exp_::Copy(_)
+            | Exp_::Name(..)
+            | Exp_::Unit
+            | Exp_::Break
+            | Exp_::Continue
+            | Exp_::UnresolvedError => (),
+        }
+    }
+}
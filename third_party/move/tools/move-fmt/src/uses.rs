@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sorts use declarations. Declarations are only moved within a run of adjacent ones, not
+//! separated by blank lines, comments or attributes, so comments stay with what they are on.
+
+use crate::format::{Token, Trivia};
+use move_compiler::parser::lexer::Tok;
+use std::ops::Range;
+
+/// Sorts the members imported by each use declaration on a single line, and runs of adjacent
+/// use declarations
+pub(crate) fn sort_uses(tokens: &mut [Token]) {
+    let mut uses = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].tok != Tok::Use {
+            i += 1;
+            continue;
+        }
+        match tokens[i..]
+            .iter()
+            .position(|token| token.tok == Tok::Semicolon)
+        {
+            Some(len) => {
+                uses.push(i..i + len + 1);
+                i += len + 1;
+            },
+            None => break,
+        }
+    }
+
+    let mut runs: Vec<Vec<Range<usize>>> = vec![];
+    for range in uses {
+        sort_members(&mut tokens[range.clone()]);
+        let first = &tokens[range.start];
+        let joins = match runs.last() {
+            Some(run) => {
+                // A declaration with attributes starts a run, and is kept first
+                let attributed = run[0].start > 0 && tokens[run[0].start - 1].tok == Tok::RBracket;
+                run[run.len() - 1].end == range.start
+                    && !attributed
+                    && !first.has_comments()
+                    && first.newlines() <= 1
+            },
+            None => false,
+        };
+        match runs.last_mut() {
+            Some(run) if joins => run.push(range),
+            _ => runs.push(vec![range]),
+        }
+    }
+
+    for run in runs.into_iter().filter(|run| run.len() > 1) {
+        let start = run[0].start;
+        let end = run[run.len() - 1].end;
+        let leading = tokens[start].leading.clone();
+        let mut declarations: Vec<Vec<Token>> = run
+            .into_iter()
+            .map(|range| {
+                let mut declaration = tokens[range].to_vec();
+                declaration[0].leading = vec![Trivia::Newlines(1)];
+                declaration
+            })
+            .collect();
+        declarations.sort_by_cached_key(|declaration| {
+            declaration[1..]
+                .iter()
+                .map(|token| token.text)
+                .collect::<String>()
+        });
+        declarations[0][0].leading = leading;
+        tokens[start..end].clone_from_slice(&declarations.concat());
+    }
+}
+
+/// Sorts the members imported by a use declaration, e.g. `use std::vector::{Self, length}`,
+/// if they are on a single line. `Self` comes first.
+fn sort_members(declaration: &mut [Token]) {
+    let open = declaration
+        .iter()
+        .position(|token| token.tok == Tok::LBrace);
+    let close = declaration
+        .iter()
+        .position(|token| token.tok == Tok::RBrace);
+    let (open, close) = match (open, close) {
+        (Some(open), Some(close)) if open < close => (open, close),
+        _ => return,
+    };
+    let members = &declaration[open + 1..close];
+    if members
+        .iter()
+        .chain(std::iter::once(&declaration[close]))
+        .any(|token| token.tok == Tok::LBrace || !token.leading.is_empty())
+    {
+        return;
+    }
+    let commas: Vec<Token> = members
+        .iter()
+        .filter(|token| token.tok == Tok::Comma)
+        .cloned()
+        .collect();
+    let mut groups: Vec<Vec<Token>> = members
+        .split(|token| token.tok == Tok::Comma)
+        .map(<[Token]>::to_vec)
+        .collect();
+    if groups.last().map_or(false, Vec::is_empty) {
+        groups.pop();
+    }
+    if groups.iter().any(Vec::is_empty) {
+        return;
+    }
+    let space_before = groups[0][0].space_before;
+    groups.sort_by_cached_key(|group| {
+        let text: String = group.iter().map(|token| token.text).collect();
+        (text != "Self", text)
+    });
+
+    let mut sorted = vec![];
+    let mut commas = commas.into_iter();
+    for (i, mut group) in groups.into_iter().enumerate() {
+        group[0].space_before = i > 0 || space_before;
+        sorted.extend(group);
+        sorted.extend(commas.next());
+    }
+    declaration[open + 1..close].clone_from_slice(&sorted);
+}
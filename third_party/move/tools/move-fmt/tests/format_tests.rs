@@ -0,0 +1,207 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_command_line_common::files::find_move_filenames;
+use move_fmt::{format_source, FormatConfig};
+use std::{fs, path::Path};
+
+const SOURCE: &str = "module 0x1::m {
+use std::vector; use std::signer::{address_of,Self};
+    use std::option::{Self, Option};
+  /// A counter
+      struct S has copy,drop { x: u64, /* inline */ y: vector<u8> }
+
+
+    #[test_only] const E:u64=1;
+    #[test(a=@0x1)] #[expected_failure]
+    fun f(a:&signer) acquires S {
+        let x=1+2*3;   // trailing
+        if(x>1){ x = x-1 } else { x = x+1 };
+
+        while (x < 10 &&
+               x > 0) {
+            x = x + 1;
+        };
+    }
+    spec f {
+    aborts_if !exists<S>(signer::address_of(a));
+    ensures result ==> old(x)
+    && true;
+    }
+}
+";
+
+const FORMATTED: &str = "module 0x1::m {
+    use std::option::{Self, Option};
+    use std::signer::{Self, address_of};
+    use std::vector;
+    /// A counter
+    struct S has copy, drop { x: u64, /* inline */ y: vector<u8> }
+
+    #[test_only]
+    const E: u64 = 1;
+    #[test(a = @0x1)]
+    #[expected_failure]
+    fun f(a: &signer) acquires S {
+        let x = 1 + 2 * 3; // trailing
+        if (x > 1) { x = x - 1 } else { x = x + 1 };
+
+        while (x < 10 &&
+            x > 0) {
+            x = x + 1;
+        };
+    }
+    spec f {
+        aborts_if !exists<S>(signer::address_of(a));
+        ensures result ==> old(x) &&
+            true;
+    }
+}
+";
+
+#[test]
+fn formats_source() {
+    let formatted = format_source("m.move", SOURCE, &FormatConfig::default()).unwrap();
+    assert_eq!(formatted, FORMATTED);
+}
+
+#[test]
+fn follows_config() {
+    let config = FormatConfig {
+        indent_size: 2,
+        max_blank_lines: 0,
+        sort_uses: false,
+    };
+    let formatted = format_source("m.move", SOURCE, &config).unwrap();
+    assert!(formatted.starts_with(
+        "module 0x1::m {\n  use std::vector;\n  use std::signer::{address_of, Self};\n"
+    ));
+    assert!(formatted.contains("\n    let x = 1 + 2 * 3; // trailing\n"));
+    assert!(!formatted.contains("\n\n"));
+}
+
+/// Asserts that every source is formatted as `expected`
+fn assert_formats_as(sources: &[&str], expected: &str) {
+    for source in sources {
+        let formatted = format_source("m.move", source, &FormatConfig::default()).unwrap();
+        assert_eq!(formatted, expected, "Formatting:\n{}", source);
+    }
+}
+
+#[test]
+fn tells_operators_apart() {
+    assert_formats_as(
+        &[
+            "module 0x1::m { fun f(r: &u64, v: vector<u64>): bool { *r*2>*r&&!(1>*r)||r==&v } }",
+            "module 0x1::m { fun f(r:&u64,v:vector<u64>):bool{ * r * 2 > * r && ! (1 > * r) || r == & v } }",
+        ],
+        "module 0x1::m {
+    fun f(r: &u64, v: vector<u64>): bool { *r * 2 > *r && !(1 > *r) || r == &v }
+}
+",
+    );
+    assert_formats_as(
+        &["module 0x1::m { spec module { fun f(v: vector<u64>): bool { forall i in 0..len(v): v[i]>1 ==> exists<S<u8>>(@0x1) } } }"],
+        "module 0x1::m {
+    spec module {
+        fun f(v: vector<u64>): bool { forall i in 0..len(v): v[i] > 1 ==> exists<S<u8>>(@0x1) }
+    }
+}
+",
+    );
+}
+
+#[test]
+fn normalizes_line_breaks() {
+    assert_formats_as(
+        &[
+            "module 0x1::m {
+    fun f(x: u64): u64 {
+        let y = x * 2;
+        if (y > 10 && x > 3) {
+            y = y - 1;
+        } else {
+            y = *&y;
+        };
+        y
+    }
+}
+",
+            "module 0x1::m { fun f(x:u64):u64
+{ let y=x*2; if (y>10&&x>3) { y=y-1; }
+else { y=*&y; }; y } }",
+            "module 0x1::m
+{
+    fun f(x: u64): u64
+    {
+        let y = x * 2
+        ;
+        if (y > 10 && x > 3)
+        {
+            y = y - 1; }
+        else
+        {
+            y = *&y; }; y
+    }
+}
+",
+        ],
+        "module 0x1::m {
+    fun f(x: u64): u64 {
+        let y = x * 2;
+        if (y > 10 && x > 3) {
+            y = y - 1;
+        } else {
+            y = *&y;
+        };
+        y
+    }
+}
+",
+    );
+    assert_formats_as(
+        &[
+            "module 0x1::m { fun f(a: bool, b: bool): bool { g(a,
+    b) &&
+    a } }",
+            "module 0x1::m { fun f(a: bool, b: bool): bool { g(a
+    , b)
+    && a } }",
+        ],
+        "module 0x1::m {
+    fun f(a: bool, b: bool): bool {
+        g(a,
+            b) &&
+            a
+    }
+}
+",
+    );
+}
+
+#[test]
+fn rejects_syntax_errors() {
+    let source = "module 0x1::m { fun f( }";
+    let error = format_source("m.move", source, &FormatConfig::default()).unwrap_err();
+    assert!(error.to_string().contains("m.move"));
+}
+
+/// Every framework source is formatted, and formatting it again doesn't change it
+#[test]
+fn framework_is_idempotent() {
+    let framework = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../aptos-move/framework");
+    let files = find_move_filenames(&[framework], false).unwrap();
+    assert!(!files.is_empty());
+    let config = FormatConfig::default();
+    for file in files {
+        let source = fs::read_to_string(&file).unwrap();
+        let formatted = format_source(&file, &source, &config)
+            .unwrap_or_else(|err| panic!("Failed to format {}: {}", file, err));
+        let reformatted = format_source(&file, &formatted, &config).unwrap();
+        assert_eq!(
+            formatted, reformatted,
+            "Formatting {} is not idempotent",
+            file
+        );
+    }
+}
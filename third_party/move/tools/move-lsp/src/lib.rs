@@ -0,0 +1,29 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A language server for Move, built on the front end of the v2 compiler. Packages are resolved
+//! with `move-package` and type checked into a `GlobalEnv`, whose diagnostics are published, and
+//! whose symbols serve go-to-definition, find-references, hover, completion of module members and
+//! struct fields, and inlay type hints.
+//!
+//! The compiler reads sources from disk, so packages are checked again when a file is saved;
+//! completion works on the text being edited, with the symbols of the last check.
+
+#![forbid(unsafe_code)]
+
+pub mod line_index;
+pub mod protocol;
+pub mod server;
+pub mod symbols;
+
+use move_model::metadata::LanguageVersion;
+use std::io::{BufRead, Write};
+
+/// Serves the Language Server Protocol over `input` and `output` until the client exits
+pub fn run<R: BufRead, W: Write>(
+    input: R,
+    output: W,
+    language_version: LanguageVersion,
+) -> anyhow::Result<()> {
+    server::Server::new(output, language_version).run(input)
+}
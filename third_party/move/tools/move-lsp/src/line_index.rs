@@ -0,0 +1,67 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde_json::{json, Value as Json};
+
+/// Converts between byte offsets in a file and LSP positions, whose characters are counted in
+/// UTF-16 code units.
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    /// The 0-based line and UTF-16 column of the byte `offset`
+    pub fn position(&self, offset: usize) -> (u32, u32) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let column = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        (line as u32, column as u32)
+    }
+
+    /// The byte offset of a 0-based line and UTF-16 column, clamped to the end of the line
+    pub fn offset(&self, line: u32, column: u32) -> usize {
+        let line_start = match self.line_starts.get(line as usize) {
+            Some(line_start) => *line_start,
+            None => return self.text.len(),
+        };
+        let line_text = self.text[line_start..]
+            .split('\n')
+            .next()
+            .unwrap_or_default();
+        let mut units = 0;
+        for (index, c) in line_text.char_indices() {
+            if units >= column as usize {
+                return line_start + index;
+            }
+            units += c.len_utf16();
+        }
+        line_start + line_text.len()
+    }
+
+    pub fn lsp_position(&self, offset: usize) -> Json {
+        let (line, character) = self.position(offset);
+        json!({ "line": line, "character": character })
+    }
+
+    pub fn lsp_range(&self, start: usize, end: usize) -> Json {
+        json!({ "start": self.lsp_position(start), "end": self.lsp_position(end) })
+    }
+
+    /// The byte offset of an LSP position
+    pub fn lsp_offset(&self, position: &Json) -> usize {
+        self.offset(
+            position["line"].as_u64().unwrap_or_default() as u32,
+            position["character"].as_u64().unwrap_or_default() as u32,
+        )
+    }
+}
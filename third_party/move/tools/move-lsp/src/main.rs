@@ -0,0 +1,25 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use clap::Parser;
+use move_model::metadata::LanguageVersion;
+
+/// Language server for Move, speaking over stdin and stdout
+#[derive(Parser)]
+#[clap(author, version, about)]
+struct Args {
+    /// The language version packages are checked with
+    #[clap(long, value_parser = clap::value_parser!(LanguageVersion))]
+    language_version: Option<LanguageVersion>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    move_lsp::run(
+        std::io::stdin().lock(),
+        std::io::stdout(),
+        args.language_version.unwrap_or_default(),
+    )
+}
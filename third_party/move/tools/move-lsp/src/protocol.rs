@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Messages of the Language Server Protocol, which are JSON-RPC messages with a
+//! `Content-Length` header.
+
+use anyhow::{bail, Result};
+use serde_json::Value as Json;
+use std::io::{BufRead, Write};
+
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const REQUEST_FAILED: i64 = -32803;
+
+/// Reads the next message, or `None` at the end of the input.
+pub fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Json>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let content_length = match content_length {
+        Some(content_length) => content_length,
+        None => bail!("Message without a Content-Length header"),
+    };
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &Json) -> Result<()> {
+    let content = serde_json::to_vec(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n", content.len())?;
+    output.write_all(&content)?;
+    output.flush()?;
+    Ok(())
+}
@@ -0,0 +1,676 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    line_index::LineIndex,
+    protocol::{self, INVALID_REQUEST, METHOD_NOT_FOUND, REQUEST_FAILED},
+    symbols::{FileSymbols, Item, SymbolIndex, TextRange},
+};
+use anyhow::{anyhow, Result};
+use codespan::FileId;
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use move_model::{metadata::LanguageVersion, model::GlobalEnv, ty::Type};
+use move_package::{source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig};
+use serde_json::{json, Value as Json};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+// Kinds of completion items, diagnostic severities and inlay hints of the protocol
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_FIELD: u32 = 5;
+const COMPLETION_CONSTANT: u32 = 21;
+const COMPLETION_STRUCT: u32 = 22;
+const INLAY_HINT_TYPE: u32 = 1;
+
+/// A package type checked with the v2 compiler
+struct Package {
+    env: GlobalEnv,
+    index: SymbolIndex,
+    /// The ids in `env` of the files of the package and its dependencies, by canonical path
+    file_ids: HashMap<PathBuf, FileId>,
+    /// The files of the package itself, which diagnostics are published for
+    target_files: BTreeSet<PathBuf>,
+}
+
+impl Package {
+    fn new(root: &Path, language_version: LanguageVersion) -> Result<Self> {
+        let config = BuildConfig {
+            dev_mode: true,
+            test_mode: true,
+            skip_fetch_latest_git_deps: true,
+            compiler_config: CompilerConfig {
+                // Attributes of the frameworks built on Move are not known here
+                skip_attribute_checks: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let env = config.check_package(root, language_version)?;
+        let index = SymbolIndex::new(&env);
+        let file_ids = env
+            .get_source_file_ids()
+            .into_iter()
+            .map(|file_id| (canonical_path(Path::new(env.get_file(file_id))), file_id))
+            .collect();
+        let target_files = env
+            .get_modules()
+            .filter(|module| module.is_target())
+            .map(|module| canonical_path(Path::new(env.get_file(module.get_loc().file_id()))))
+            .collect();
+        Ok(Self {
+            env,
+            index,
+            file_ids,
+            target_files,
+        })
+    }
+
+    fn path(&self, file_id: FileId) -> PathBuf {
+        canonical_path(Path::new(self.env.get_file(file_id)))
+    }
+
+    fn location(&self, file_id: FileId, range: TextRange) -> Json {
+        let line_index = LineIndex::new(self.env.get_file_source(file_id));
+        json!({
+            "uri": file_uri(&self.path(file_id)),
+            "range": line_index.lsp_range(range.start, range.end),
+        })
+    }
+
+    /// The diagnostics of checking the package, by file
+    fn diagnostics(&self) -> HashMap<PathBuf, Vec<Json>> {
+        let mut diagnostics: HashMap<_, Vec<_>> = HashMap::new();
+        for diagnostic in self.env.get_diagnostics() {
+            let primary = match diagnostic
+                .labels
+                .iter()
+                .find(|label| label.style == LabelStyle::Primary)
+            {
+                Some(primary) => primary,
+                None => continue,
+            };
+            let path = self.path(primary.file_id);
+            if !self.target_files.contains(&path) {
+                continue;
+            }
+            diagnostics.entry(path).or_default().push(self.diagnostic(
+                &diagnostic,
+                primary.file_id,
+                &primary.range,
+            ));
+        }
+        diagnostics
+    }
+
+    fn diagnostic(
+        &self,
+        diagnostic: &Diagnostic<FileId>,
+        file_id: FileId,
+        range: &std::ops::Range<usize>,
+    ) -> Json {
+        let line_index = LineIndex::new(self.env.get_file_source(file_id));
+        let severity = match diagnostic.severity {
+            Severity::Bug | Severity::Error => 1,
+            Severity::Warning => 2,
+            Severity::Note => 3,
+            Severity::Help => 4,
+        };
+        let mut message = diagnostic.message.clone();
+        for label in &diagnostic.labels {
+            if label.style == LabelStyle::Primary && !label.message.is_empty() {
+                message.push_str(&format!("\n{}", label.message));
+            }
+        }
+        for note in &diagnostic.notes {
+            message.push_str(&format!("\n{}", note));
+        }
+        let related_information: Vec<_> = diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.style == LabelStyle::Secondary)
+            .map(|label| {
+                json!({
+                    "location": self.location(label.file_id, TextRange {
+                        start: label.range.start,
+                        end: label.range.end,
+                    }),
+                    "message": label.message,
+                })
+            })
+            .collect();
+        json!({
+            "range": line_index.lsp_range(range.start, range.end),
+            "severity": severity,
+            "source": "move",
+            "message": message,
+            "relatedInformation": related_information,
+        })
+    }
+
+    fn hover(&self, item: Item) -> Option<String> {
+        let env = &self.env;
+        let (signature, doc) = match item {
+            Item::Module(module_id) => {
+                let module = env.get_module(module_id);
+                (
+                    format!("module {}", module.get_full_name_str()),
+                    module.get_doc().to_string(),
+                )
+            },
+            Item::Function(function_id) => {
+                let function = env.get_function(function_id);
+                (
+                    format!(
+                        "module {}\n{}",
+                        function.module_env.get_full_name_str(),
+                        function.get_header_string()
+                    ),
+                    function.get_doc().to_string(),
+                )
+            },
+            Item::Struct(struct_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let display_context = struct_env.get_type_display_ctx();
+                let mut signature = format!(
+                    "module {}\nstruct {}",
+                    struct_env.module_env.get_full_name_str(),
+                    struct_env.get_name().display(env.symbol_pool())
+                );
+                let type_parameters = struct_env.get_type_parameters();
+                if !type_parameters.is_empty() {
+                    let names: Vec<_> = type_parameters
+                        .iter()
+                        .map(|param| param.0.display(env.symbol_pool()).to_string())
+                        .collect();
+                    signature.push_str(&format!("<{}>", names.join(", ")));
+                }
+                let abilities = struct_env.get_abilities();
+                if !abilities.is_empty() {
+                    let abilities: Vec<_> = abilities.iter().map(|a| a.to_string()).collect();
+                    signature.push_str(&format!(" has {}", abilities.join(", ")));
+                }
+                signature.push_str(" {\n");
+                for field in struct_env.get_fields() {
+                    signature.push_str(&format!(
+                        "    {}: {},\n",
+                        field.get_name().display(env.symbol_pool()),
+                        field.get_type().display(&display_context)
+                    ));
+                }
+                signature.push('}');
+                (signature, struct_env.get_doc().to_string())
+            },
+            Item::Field(struct_id, field_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let field = struct_env.get_field(field_id);
+                (
+                    format!(
+                        "{}\n{}: {}",
+                        struct_env.get_full_name_str(),
+                        field.get_name().display(env.symbol_pool()),
+                        field.get_type().display(&struct_env.get_type_display_ctx())
+                    ),
+                    field.get_doc().to_string(),
+                )
+            },
+            Item::Local(..) => {
+                let local = self.index.local(item)?;
+                (
+                    format!(
+                        "let {}: {}",
+                        local.name.display(env.symbol_pool()),
+                        local.type_string
+                    ),
+                    String::new(),
+                )
+            },
+        };
+        let mut hover = format!("```move\n{}\n```", signature);
+        if !doc.trim().is_empty() {
+            hover.push_str(&format!("\n\n{}", doc.trim()));
+        }
+        Some(hover)
+    }
+
+    /// Completes the members of `module` after `module::`
+    fn module_members(&self, module_name: &str) -> Vec<Json> {
+        let env = &self.env;
+        let mut items = vec![];
+        let modules = env.get_modules().filter(|module| {
+            module
+                .get_name()
+                .name()
+                .display(env.symbol_pool())
+                .to_string()
+                == module_name
+        });
+        for module in modules {
+            for function in module.get_functions() {
+                items.push(json!({
+                    "label": function.get_name().display(env.symbol_pool()).to_string(),
+                    "kind": COMPLETION_FUNCTION,
+                    "detail": function.get_header_string(),
+                    "documentation": function.get_doc().trim(),
+                }));
+            }
+            for struct_env in module.get_structs() {
+                items.push(json!({
+                    "label": struct_env.get_name().display(env.symbol_pool()).to_string(),
+                    "kind": COMPLETION_STRUCT,
+                    "documentation": struct_env.get_doc().trim(),
+                }));
+            }
+            let display_context = env.get_type_display_ctx();
+            for constant in module.get_named_constants() {
+                items.push(json!({
+                    "label": constant.get_name().display(env.symbol_pool()).to_string(),
+                    "kind": COMPLETION_CONSTANT,
+                    "detail": constant.get_type().display(&display_context).to_string(),
+                    "documentation": constant.get_doc().trim(),
+                }));
+            }
+        }
+        items
+    }
+
+    /// Completes the fields of the struct `path` evaluates to after `path.`, where `path` is a
+    /// local variable followed by field names, e.g. `a.b` in `a.b.c`
+    fn fields(&self, file_id: FileId, offset: usize, path: &[&str]) -> Vec<Json> {
+        let env = &self.env;
+        let file_symbols: &FileSymbols = match self.index.files.get(&file_id) {
+            Some(file_symbols) => file_symbols,
+            None => return vec![],
+        };
+        // The closest definition of the variable before the position, which the code being
+        // edited most likely refers to
+        let local = file_symbols
+            .locals
+            .iter()
+            .filter(|local| {
+                local.range.start <= offset
+                    && local.name.display(env.symbol_pool()).to_string() == path[0]
+            })
+            .max_by_key(|local| local.range.start);
+        let mut ty = match local {
+            Some(local) => local.ty.clone(),
+            None => return vec![],
+        };
+        for field_name in &path[1..] {
+            ty = match ty.skip_reference() {
+                Type::Struct(module_id, struct_id, _) => {
+                    let struct_env = env.get_module(*module_id).into_struct(*struct_id);
+                    let field = struct_env.get_fields().find(|field| {
+                        field.get_name().display(env.symbol_pool()).to_string() == *field_name
+                    });
+                    match field {
+                        Some(field) => field.get_type(),
+                        None => return vec![],
+                    }
+                },
+                _ => return vec![],
+            };
+        }
+        match ty.skip_reference() {
+            Type::Struct(module_id, struct_id, _) => {
+                let struct_env = env.get_module(*module_id).into_struct(*struct_id);
+                let display_context = struct_env.get_type_display_ctx();
+                struct_env
+                    .get_fields()
+                    .map(|field| {
+                        json!({
+                            "label": field.get_name().display(env.symbol_pool()).to_string(),
+                            "kind": COMPLETION_FIELD,
+                            "detail": field.get_type().display(&display_context).to_string(),
+                            "documentation": field.get_doc().trim(),
+                        })
+                    })
+                    .collect()
+            },
+            _ => vec![],
+        }
+    }
+}
+
+/// A language server for Move, speaking over `output`
+pub struct Server<W> {
+    output: W,
+    language_version: LanguageVersion,
+    /// The text of the open documents, by canonical path
+    documents: HashMap<PathBuf, String>,
+    /// The checked packages, by root directory
+    packages: HashMap<PathBuf, Package>,
+    shutdown: bool,
+}
+
+impl<W: Write> Server<W> {
+    pub fn new(output: W, language_version: LanguageVersion) -> Self {
+        Self {
+            output,
+            language_version,
+            documents: HashMap::new(),
+            packages: HashMap::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Serves the messages of `input` until the client exits
+    pub fn run<R: BufRead>(&mut self, mut input: R) -> Result<()> {
+        while let Some(message) = protocol::read_message(&mut input)? {
+            let method = message["method"].as_str().unwrap_or_default().to_string();
+            if method == "exit" {
+                break;
+            }
+            if message.get("id").is_some() {
+                let response = match self.handle_request(&method, &message["params"]) {
+                    Ok(result) => json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"].clone(),
+                        "result": result,
+                    }),
+                    Err((code, error)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"].clone(),
+                        "error": { "code": code, "message": error },
+                    }),
+                };
+                protocol::write_message(&mut self.output, &response)?;
+            } else {
+                self.handle_notification(&method, &message["params"])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Json) -> Result<Json, (i64, String)> {
+        if self.shutdown {
+            return Err((INVALID_REQUEST, "The server is shut down".to_string()));
+        }
+        let failed = |err: anyhow::Error| (REQUEST_FAILED, format!("{:#}", err));
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        // Documents are sent whole on every change
+                        "change": 1,
+                        "save": { "includeText": false },
+                    },
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": [".", ":"] },
+                    "inlayHintProvider": true,
+                },
+                "serverInfo": { "name": "move-lsp", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            },
+            "textDocument/definition" => self.definition(params).map_err(failed),
+            "textDocument/references" => self.references(params).map_err(failed),
+            "textDocument/hover" => self.hover(params).map_err(failed),
+            "textDocument/completion" => self.completion(params).map_err(failed),
+            "textDocument/inlayHint" => self.inlay_hints(params).map_err(failed),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported request {}", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Json) -> Result<()> {
+        let path = match document_path(params) {
+            Ok(path) => path,
+            // Notifications not about documents are ignored
+            Err(_) => return Ok(()),
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(path.clone(), text.to_string());
+                if let Some(root) = package_root(&path) {
+                    if !self.packages.contains_key(&root) {
+                        self.check(root)?;
+                    }
+                }
+            },
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(path, text.to_string());
+                }
+            },
+            "textDocument/didSave" => {
+                // The compiler reads the sources from disk, so they are checked again on save
+                if let Some(root) = package_root(&path) {
+                    self.check(root)?;
+                }
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Checks the package at `root`, and publishes its diagnostics
+    fn check(&mut self, root: PathBuf) -> Result<()> {
+        let mut published: BTreeSet<_> = self
+            .packages
+            .get(&root)
+            .map(|package| package.target_files.clone())
+            .unwrap_or_default();
+        let package = match Package::new(&root, self.language_version) {
+            Ok(package) => package,
+            Err(err) => {
+                self.packages.remove(&root);
+                return self.send_notification(
+                    "window/showMessage",
+                    json!({
+                        "type": 1,
+                        "message": format!("Failed to check the Move package at {}: {:#}", root.display(), err),
+                    }),
+                );
+            },
+        };
+        let mut diagnostics = package.diagnostics();
+        published.extend(package.target_files.iter().cloned());
+        self.packages.insert(root, package);
+        // Files without diagnostics are published too, to clear those previously published
+        for path in published {
+            let file_diagnostics = diagnostics.remove(&path).unwrap_or_default();
+            self.send_notification(
+                "textDocument/publishDiagnostics",
+                json!({ "uri": file_uri(&path), "diagnostics": file_diagnostics }),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn send_notification(&mut self, method: &str, params: Json) -> Result<()> {
+        protocol::write_message(
+            &mut self.output,
+            &json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+    }
+
+    /// The package of the document of a request, checking it if that was not done yet
+    fn package(&mut self, path: &Path) -> Result<&Package> {
+        let root = package_root(path)
+            .ok_or_else(|| anyhow!("{} is not in a Move package", path.display()))?;
+        if !self.packages.contains_key(&root) {
+            self.check(root.clone())?;
+        }
+        self.packages
+            .get(&root)
+            .ok_or_else(|| anyhow!("Failed to check the Move package at {}", root.display()))
+    }
+
+    /// The package, file and offset of the position of a request
+    fn position(&mut self, params: &Json) -> Result<Option<(&Package, FileId, usize)>> {
+        let path = document_path(params)?;
+        let package = self.package(&path)?;
+        let file_id = match package.file_ids.get(&path) {
+            Some(file_id) => *file_id,
+            None => return Ok(None),
+        };
+        let line_index = LineIndex::new(package.env.get_file_source(file_id));
+        let offset = line_index.lsp_offset(&params["position"]);
+        Ok(Some((package, file_id, offset)))
+    }
+
+    fn definition(&mut self, params: &Json) -> Result<Json> {
+        let (package, file_id, offset) = match self.position(params)? {
+            Some(position) => position,
+            None => return Ok(Json::Null),
+        };
+        let definition = package
+            .index
+            .occurrence_at(file_id, offset)
+            .and_then(|occurrence| package.index.definitions.get(&occurrence.item));
+        Ok(match definition {
+            Some((file_id, range)) => package.location(*file_id, *range),
+            None => Json::Null,
+        })
+    }
+
+    fn references(&mut self, params: &Json) -> Result<Json> {
+        let include_declaration = params["context"]["includeDeclaration"]
+            .as_bool()
+            .unwrap_or(true);
+        let (package, file_id, offset) = match self.position(params)? {
+            Some(position) => position,
+            None => return Ok(Json::Null),
+        };
+        let item = match package.index.occurrence_at(file_id, offset) {
+            Some(occurrence) => occurrence.item,
+            None => return Ok(Json::Null),
+        };
+        let definition = package.index.definitions.get(&item).copied();
+        let mut references = package.index.occurrences(item);
+        if !include_declaration {
+            references.retain(|reference| Some(*reference) != definition);
+        }
+        references.sort_by_key(|(file_id, range)| (package.path(*file_id), range.start));
+        Ok(Json::Array(
+            references
+                .into_iter()
+                .map(|(file_id, range)| package.location(file_id, range))
+                .collect(),
+        ))
+    }
+
+    fn hover(&mut self, params: &Json) -> Result<Json> {
+        let (package, file_id, offset) = match self.position(params)? {
+            Some(position) => position,
+            None => return Ok(Json::Null),
+        };
+        let occurrence = match package.index.occurrence_at(file_id, offset) {
+            Some(occurrence) => occurrence,
+            None => return Ok(Json::Null),
+        };
+        let line_index = LineIndex::new(package.env.get_file_source(file_id));
+        Ok(match package.hover(occurrence.item) {
+            Some(hover) => json!({
+                "contents": { "kind": "markdown", "value": hover },
+                "range": line_index.lsp_range(occurrence.range.start, occurrence.range.end),
+            }),
+            None => Json::Null,
+        })
+    }
+
+    fn completion(&mut self, params: &Json) -> Result<Json> {
+        let path = document_path(params)?;
+        // Completion is on the text being edited, which may not have been checked yet
+        let text = match self.documents.get(&path) {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(&path)?,
+        };
+        let offset = LineIndex::new(&text).lsp_offset(&params["position"]);
+        let line = text[..offset].rsplit('\n').next().unwrap_or_default();
+        let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let before_partial = line.trim_end_matches(is_identifier_char);
+
+        let package = self.package(&path)?;
+        let items = if let Some(qualifier) = before_partial.strip_suffix("::") {
+            let module_name = &qualifier[qualifier.trim_end_matches(is_identifier_char).len()..];
+            package.module_members(module_name)
+        } else if let Some(receiver) = before_partial.strip_suffix('.') {
+            // The variable and fields before the dot, e.g. `a.b` of `x = a.b.`
+            let start = receiver
+                .rfind(|c: char| !is_identifier_char(c) && c != '.')
+                .map_or(0, |index| index + 1);
+            let field_path: Vec<_> = receiver[start..].split('.').collect();
+            match (package.file_ids.get(&path), field_path.first()) {
+                (Some(file_id), Some(first)) if !first.is_empty() => {
+                    package.fields(*file_id, offset, &field_path)
+                },
+                _ => vec![],
+            }
+        } else {
+            vec![]
+        };
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    fn inlay_hints(&mut self, params: &Json) -> Result<Json> {
+        let path = document_path(params)?;
+        let package = self.package(&path)?;
+        let file_id = match package.file_ids.get(&path) {
+            Some(file_id) => *file_id,
+            None => return Ok(json!([])),
+        };
+        let line_index = LineIndex::new(package.env.get_file_source(file_id));
+        let start = line_index.lsp_offset(&params["range"]["start"]);
+        let end = line_index.lsp_offset(&params["range"]["end"]);
+        let hints: Vec<_> = package
+            .index
+            .files
+            .get(&file_id)
+            .map(|file_symbols| file_symbols.type_hints.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|(offset, _)| start <= *offset && *offset <= end)
+            .map(|(offset, type_string)| {
+                json!({
+                    "position": line_index.lsp_position(*offset),
+                    "label": format!(": {}", type_string),
+                    "kind": INLAY_HINT_TYPE,
+                })
+            })
+            .collect();
+        Ok(Json::Array(hints))
+    }
+}
+
+fn document_path(params: &Json) -> Result<PathBuf> {
+    let uri = params["textDocument"]["uri"]
+        .as_str()
+        .ok_or_else(|| anyhow!("No document given"))?;
+    let path = Url::parse(uri)?
+        .to_file_path()
+        .map_err(|_| anyhow!("{} is not a file", uri))?;
+    Ok(canonical_path(&path))
+}
+
+fn package_root(path: &Path) -> Option<PathBuf> {
+    SourcePackageLayout::try_find_root(path.parent()?)
+        .ok()
+        .map(|root| canonical_path(&root))
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn file_uri(path: &Path) -> String {
+    Url::from_file_path(path)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| path.display().to_string())
+}
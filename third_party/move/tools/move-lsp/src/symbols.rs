@@ -0,0 +1,478 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An index of where the items of a type checked package are defined and referenced, to map a
+//! source position to the item under it. Only the target modules of the package are indexed for
+//! references; definitions are indexed for dependencies as well.
+
+use codespan::FileId;
+use move_model::{
+    ast::{ExpData, Operation, Pattern, VisitorPosition},
+    model::{
+        FieldId, FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, ModuleId, NodeId, Parameter,
+        QualifiedId, StructId,
+    },
+    symbol::Symbol,
+    ty::{Type, TypeDisplayContext},
+};
+use std::collections::HashMap;
+
+/// An item that can be defined and referenced
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Item {
+    Module(ModuleId),
+    Function(QualifiedId<FunId>),
+    Struct(QualifiedId<StructId>),
+    Field(QualifiedId<StructId>, FieldId),
+    /// A parameter or local variable, by the start of its definition
+    Local(FileId, usize),
+}
+
+/// A range of a file, in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TextRange {
+    fn contains(&self, offset: usize) -> bool {
+        // The end is included, so that a name is found with the cursor right after it
+        self.start <= offset && offset <= self.end
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// A definition of or reference to an item
+pub struct Occurrence {
+    pub range: TextRange,
+    pub item: Item,
+    /// The expression or pattern the occurrence is part of, if any, whose type is shown on hover
+    pub node: Option<NodeId>,
+}
+
+pub struct LocalVariable {
+    pub name: Symbol,
+    pub range: TextRange,
+    pub ty: Type,
+    /// The type as shown in the function the variable is defined in
+    pub type_string: String,
+}
+
+#[derive(Default)]
+pub struct FileSymbols {
+    pub occurrences: Vec<Occurrence>,
+    pub locals: Vec<LocalVariable>,
+    /// The types of the variables bound by `let` without a type annotation, after their names
+    pub type_hints: Vec<(usize, String)>,
+}
+
+#[derive(Default)]
+pub struct SymbolIndex {
+    pub files: HashMap<FileId, FileSymbols>,
+    pub definitions: HashMap<Item, (FileId, TextRange)>,
+}
+
+/// Where to look for a name in the source of a construct
+#[derive(Clone, Copy)]
+enum NamePosition {
+    First,
+    Last,
+    /// The last occurrence before any of the characters, e.g. the name of the function called by
+    /// `m::f<T>(x)` is the last one before `<` or `(`
+    LastBefore(&'static [char]),
+}
+
+impl SymbolIndex {
+    pub fn new(env: &GlobalEnv) -> Self {
+        let mut builder = IndexBuilder {
+            env,
+            display_context: env.get_type_display_ctx(),
+            index: SymbolIndex::default(),
+        };
+        for module in env.get_modules() {
+            builder.module(&module);
+        }
+        for file_symbols in builder.index.files.values_mut() {
+            file_symbols
+                .occurrences
+                .sort_by_key(|occurrence| occurrence.range.start);
+        }
+        builder.index
+    }
+
+    /// The innermost occurrence at `offset`
+    pub fn occurrence_at(&self, file_id: FileId, offset: usize) -> Option<&Occurrence> {
+        self.files
+            .get(&file_id)?
+            .occurrences
+            .iter()
+            .filter(|occurrence| occurrence.range.contains(offset))
+            .min_by_key(|occurrence| occurrence.range.len())
+    }
+
+    /// The definition of a local variable
+    pub fn local(&self, item: Item) -> Option<&LocalVariable> {
+        match item {
+            Item::Local(file_id, start) => self
+                .files
+                .get(&file_id)?
+                .locals
+                .iter()
+                .find(|local| local.range.start == start),
+            _ => None,
+        }
+    }
+
+    /// The occurrences of `item`, including its definition
+    pub fn occurrences(&self, item: Item) -> Vec<(FileId, TextRange)> {
+        let mut occurrences = vec![];
+        for (file_id, file_symbols) in &self.files {
+            for occurrence in &file_symbols.occurrences {
+                if occurrence.item == item {
+                    occurrences.push((*file_id, occurrence.range));
+                }
+            }
+        }
+        occurrences
+    }
+}
+
+struct IndexBuilder<'env> {
+    env: &'env GlobalEnv,
+    /// Displays types in the scope of the function being indexed
+    display_context: TypeDisplayContext<'env>,
+    index: SymbolIndex,
+}
+
+impl<'env> IndexBuilder<'env> {
+    fn module(&mut self, module: &ModuleEnv) {
+        let name = module
+            .get_name()
+            .name()
+            .display(self.env.symbol_pool())
+            .to_string();
+        self.define(
+            Item::Module(module.get_id()),
+            &module.get_loc(),
+            &name,
+            NamePosition::LastBefore(&['{']),
+        );
+        for struct_env in module.get_structs() {
+            let struct_id = struct_env.get_qualified_id();
+            self.define(
+                Item::Struct(struct_id),
+                &struct_env.get_loc(),
+                &self.name(struct_env.get_name()),
+                NamePosition::First,
+            );
+            for field in struct_env.get_fields() {
+                self.define(
+                    Item::Field(struct_id, field.get_id()),
+                    field.get_loc(),
+                    &self.name(field.get_name()),
+                    NamePosition::First,
+                );
+            }
+        }
+        for function in module.get_functions() {
+            self.define(
+                Item::Function(function.get_qualified_id()),
+                &function.get_id_loc(),
+                &self.name(function.get_name()),
+                NamePosition::First,
+            );
+            if module.is_target() {
+                self.function_body(&function);
+            }
+        }
+    }
+
+    fn function_body(&mut self, function: &FunctionEnv) {
+        let type_parameters = function.get_type_parameters();
+        self.display_context = TypeDisplayContext::new_with_params(
+            self.env,
+            type_parameters.iter().map(|param| param.0).collect(),
+        );
+        let mut scopes = vec![];
+        let mut parameters = vec![];
+        for Parameter(name, ty, loc) in function.get_parameters() {
+            let item = self.define_local(name, &loc, ty, None);
+            parameters.push(item);
+            if let Some(item) = item {
+                scopes.push((name, item));
+            }
+        }
+        let body = match function.get_def() {
+            Some(body) => body,
+            None => return,
+        };
+
+        // The length of `scopes` when each scope still open was entered
+        let mut scope_starts = vec![];
+        body.visit_positions(&mut |position, exp| {
+            match (position, exp) {
+                (VisitorPosition::BeforeBody, ExpData::Block(_, pattern, _, _)) => {
+                    scope_starts.push(scopes.len());
+                    self.define_pattern(pattern, &mut scopes);
+                    self.type_hints(pattern);
+                },
+                (VisitorPosition::Pre, ExpData::Lambda(_, pattern, _)) => {
+                    scope_starts.push(scopes.len());
+                    self.define_pattern(pattern, &mut scopes);
+                },
+                (VisitorPosition::Pre, ExpData::Quant(_, _, ranges, ..)) => {
+                    scope_starts.push(scopes.len());
+                    for (pattern, _) in ranges {
+                        self.define_pattern(pattern, &mut scopes);
+                    }
+                },
+                (
+                    VisitorPosition::Post,
+                    ExpData::Block(..) | ExpData::Lambda(..) | ExpData::Quant(..),
+                ) => {
+                    if let Some(scope_start) = scope_starts.pop() {
+                        scopes.truncate(scope_start);
+                    }
+                },
+                (VisitorPosition::Pre, ExpData::Assign(_, pattern, _)) => {
+                    self.reference_pattern(pattern, &scopes);
+                },
+                (VisitorPosition::Pre, ExpData::LocalVar(id, name)) => {
+                    if let Some((_, item)) = scopes.iter().rev().find(|(local, _)| local == name) {
+                        self.reference(*item, *id, *name, NamePosition::First);
+                    }
+                },
+                (VisitorPosition::Pre, ExpData::Temporary(id, index)) => {
+                    if let Some(Some(item)) = parameters.get(*index) {
+                        let name = function.get_local_name(*index);
+                        self.reference(*item, *id, name, NamePosition::First);
+                    }
+                },
+                (VisitorPosition::Pre, ExpData::Call(id, operation, _)) => {
+                    self.reference_operation(*id, operation);
+                },
+                _ => (),
+            }
+            true
+        });
+    }
+
+    fn reference_operation(&mut self, id: NodeId, operation: &Operation) {
+        match operation {
+            Operation::MoveFunction(module_id, fun_id) | Operation::Closure(module_id, fun_id) => {
+                let function = module_id.qualified(*fun_id);
+                self.reference(
+                    Item::Function(function),
+                    id,
+                    fun_id.symbol(),
+                    NamePosition::LastBefore(&['<', '(']),
+                );
+            },
+            Operation::Pack(module_id, struct_id) => {
+                self.reference(
+                    Item::Struct(module_id.qualified(*struct_id)),
+                    id,
+                    struct_id.symbol(),
+                    NamePosition::LastBefore(&['<', '{', '(']),
+                );
+            },
+            Operation::Select(module_id, struct_id, field_id) => {
+                self.reference(
+                    Item::Field(module_id.qualified(*struct_id), *field_id),
+                    id,
+                    field_id.symbol(),
+                    NamePosition::Last,
+                );
+            },
+            Operation::Exists(_)
+            | Operation::BorrowGlobal(_)
+            | Operation::MoveFrom
+            | Operation::MoveTo => {
+                if let Some(Type::Struct(module_id, struct_id, _)) =
+                    self.env.get_node_instantiation(id).first()
+                {
+                    self.reference(
+                        Item::Struct(module_id.qualified(*struct_id)),
+                        id,
+                        struct_id.symbol(),
+                        NamePosition::LastBefore(&['(']),
+                    );
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Defines the variables bound by `pattern`, and references the structs it unpacks
+    fn define_pattern(&mut self, pattern: &Pattern, scopes: &mut Vec<(Symbol, Item)>) {
+        match pattern {
+            Pattern::Var(id, name) => {
+                let loc = self.env.get_node_loc(*id);
+                let ty = self.env.get_node_type(*id);
+                if let Some(item) = self.define_local(*name, &loc, ty, Some(*id)) {
+                    scopes.push((*name, item));
+                }
+            },
+            Pattern::Tuple(_, patterns) => {
+                for pattern in patterns {
+                    self.define_pattern(pattern, scopes);
+                }
+            },
+            Pattern::Struct(id, struct_id, patterns) => {
+                self.reference_struct_pattern(*id, struct_id.to_qualified_id());
+                for pattern in patterns {
+                    self.define_pattern(pattern, scopes);
+                }
+            },
+            Pattern::Wildcard(_) | Pattern::Error(_) => (),
+        }
+    }
+
+    /// References the variables assigned by `pattern`
+    fn reference_pattern(&mut self, pattern: &Pattern, scopes: &[(Symbol, Item)]) {
+        match pattern {
+            Pattern::Var(id, name) => {
+                if let Some((_, item)) = scopes.iter().rev().find(|(local, _)| local == name) {
+                    self.reference(*item, *id, *name, NamePosition::First);
+                }
+            },
+            Pattern::Tuple(_, patterns) => {
+                for pattern in patterns {
+                    self.reference_pattern(pattern, scopes);
+                }
+            },
+            Pattern::Struct(id, struct_id, patterns) => {
+                self.reference_struct_pattern(*id, struct_id.to_qualified_id());
+                for pattern in patterns {
+                    self.reference_pattern(pattern, scopes);
+                }
+            },
+            Pattern::Wildcard(_) | Pattern::Error(_) => (),
+        }
+    }
+
+    fn reference_struct_pattern(&mut self, id: NodeId, struct_id: QualifiedId<StructId>) {
+        self.reference(
+            Item::Struct(struct_id),
+            id,
+            struct_id.id.symbol(),
+            NamePosition::LastBefore(&['<', '{', '(']),
+        );
+    }
+
+    /// Records the types of the variables of a `let` without a type annotation
+    fn type_hints(&mut self, pattern: &Pattern) {
+        let loc = self.env.get_node_loc(pattern.node_id());
+        let rest = &self.env.get_file_source(loc.file_id())[loc.span().end().0 as usize..];
+        if rest.trim_start().starts_with(':') {
+            return;
+        }
+        for (id, name) in pattern.vars() {
+            let var_loc = self.env.get_node_loc(id);
+            if let Some(range) = self.name_range(&var_loc, &self.name(name), NamePosition::First) {
+                self.index
+                    .files
+                    .entry(loc.file_id())
+                    .or_default()
+                    .type_hints
+                    .push((range.end, self.type_string(&self.env.get_node_type(id))));
+            }
+        }
+    }
+
+    fn define_local(
+        &mut self,
+        name: Symbol,
+        loc: &Loc,
+        ty: Type,
+        node: Option<NodeId>,
+    ) -> Option<Item> {
+        let range = self.name_range(loc, &self.name(name), NamePosition::First)?;
+        let item = Item::Local(loc.file_id(), range.start);
+        let type_string = self.type_string(&ty);
+        self.index.definitions.insert(item, (loc.file_id(), range));
+        let file_symbols = self.index.files.entry(loc.file_id()).or_default();
+        file_symbols
+            .occurrences
+            .push(Occurrence { range, item, node });
+        file_symbols.locals.push(LocalVariable {
+            name,
+            range,
+            ty,
+            type_string,
+        });
+        Some(item)
+    }
+
+    fn define(&mut self, item: Item, loc: &Loc, name: &str, position: NamePosition) {
+        if let Some(range) = self.name_range(loc, name, position) {
+            self.index.definitions.insert(item, (loc.file_id(), range));
+            self.index
+                .files
+                .entry(loc.file_id())
+                .or_default()
+                .occurrences
+                .push(Occurrence {
+                    range,
+                    item,
+                    node: None,
+                });
+        }
+    }
+
+    fn reference(&mut self, item: Item, id: NodeId, name: Symbol, position: NamePosition) {
+        let loc = self.env.get_node_loc(id);
+        if let Some(range) = self.name_range(&loc, &self.name(name), position) {
+            self.index
+                .files
+                .entry(loc.file_id())
+                .or_default()
+                .occurrences
+                .push(Occurrence {
+                    range,
+                    item,
+                    node: Some(id),
+                });
+        }
+    }
+
+    fn type_string(&self, ty: &Type) -> String {
+        ty.display(&self.display_context).to_string()
+    }
+
+    fn name(&self, symbol: Symbol) -> String {
+        symbol.display(self.env.symbol_pool()).to_string()
+    }
+
+    /// Finds `name` as a whole word in the source of `loc`. There is no range for constructs the
+    /// compiler introduced, whose source does not contain the name.
+    fn name_range(&self, loc: &Loc, name: &str, position: NamePosition) -> Option<TextRange> {
+        let source = self.env.get_source(loc).ok()?;
+        let source = match position {
+            NamePosition::LastBefore(delimiters) => match source.find(delimiters) {
+                Some(end) => &source[..end],
+                None => source,
+            },
+            NamePosition::First | NamePosition::Last => source,
+        };
+        let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut matches = source.match_indices(name).filter(|(index, _)| {
+            let before = source[..*index].chars().next_back();
+            let after = source[index + name.len()..].chars().next();
+            !before.map_or(false, is_identifier_char) && !after.map_or(false, is_identifier_char)
+        });
+        let index = match position {
+            NamePosition::First => matches.next()?.0,
+            NamePosition::Last | NamePosition::LastBefore(_) => matches.last()?.0,
+        };
+        let start = loc.span().start().0 as usize + index;
+        Some(TextRange {
+            start,
+            end: start + name.len(),
+        })
+    }
+}
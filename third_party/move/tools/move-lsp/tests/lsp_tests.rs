@@ -0,0 +1,200 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_lsp::protocol::{read_message, write_message};
+use move_model::metadata::LanguageVersion;
+use serde_json::{json, Value as Json};
+use std::{fs, io::Cursor, path::Path};
+use tempfile::tempdir;
+use url::Url;
+
+const MANIFEST: &str = r#"[package]
+name = "test_pkg"
+version = "0.0.1"
+
+[addresses]
+test_pkg = "0x42"
+"#;
+
+const SOURCE: &str = "module test_pkg::m {
+    /// A counter
+    struct Counter has drop { value: u64 }
+
+    public fun new(value: u64): Counter {
+        Counter { value }
+    }
+
+    fun get(c: &Counter): u64 {
+        let v = c.value;
+        v
+    }
+
+    fun use_new(): u64 {
+        let c = new(1);
+        get(&c)
+    }
+}
+";
+
+/// The LSP position of the `index`th occurrence of `pattern` in `text`
+fn position_of(text: &str, pattern: &str, index: usize) -> Json {
+    let offset = text.match_indices(pattern).nth(index).unwrap().0;
+    let line = text[..offset].matches('\n').count();
+    let character = offset - text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    json!({ "line": line, "character": character })
+}
+
+/// Sends the requests to a server, with the package opened, and returns the results of the
+/// requests and the notifications sent
+fn serve(package: &Path, requests: Vec<(&str, Json)>) -> (Vec<Json>, Vec<Json>) {
+    let uri = Url::from_file_path(package.join("sources/m.move").canonicalize().unwrap())
+        .unwrap()
+        .to_string();
+    let mut input = vec![];
+    let mut send = |message: Json| write_message(&mut input, &message).unwrap();
+    send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }));
+    send(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "languageId": "move", "version": 1, "text": SOURCE } },
+    }));
+    for (id, (method, mut params)) in requests.into_iter().enumerate() {
+        params["textDocument"] = json!({ "uri": uri });
+        send(json!({ "jsonrpc": "2.0", "id": id + 1, "method": method, "params": params }));
+    }
+    send(json!({ "jsonrpc": "2.0", "id": 1000, "method": "shutdown" }));
+    send(json!({ "jsonrpc": "2.0", "method": "exit" }));
+
+    let mut output = vec![];
+    move_lsp::run(Cursor::new(input), &mut output, LanguageVersion::V2_0).unwrap();
+    let mut output = Cursor::new(output);
+    let mut results = vec![];
+    let mut notifications = vec![];
+    while let Some(message) = read_message(&mut output).unwrap() {
+        match message["id"].as_u64() {
+            Some(0) | Some(1000) => (),
+            Some(_) => results.push(message["result"].clone()),
+            None => notifications.push(message),
+        }
+    }
+    (results, notifications)
+}
+
+fn new_package() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Move.toml"), MANIFEST).unwrap();
+    fs::create_dir(dir.path().join("sources")).unwrap();
+    fs::write(dir.path().join("sources/m.move"), SOURCE).unwrap();
+    dir
+}
+
+#[test]
+fn navigation_and_hover() {
+    let package = new_package();
+    let (results, notifications) = serve(package.path(), vec![
+        (
+            "textDocument/definition",
+            json!({ "position": position_of(SOURCE, "new(1)", 0) }),
+        ),
+        (
+            "textDocument/references",
+            json!({
+                "position": position_of(SOURCE, "value", 0),
+                "context": { "includeDeclaration": true },
+            }),
+        ),
+        (
+            "textDocument/hover",
+            json!({ "position": position_of(SOURCE, "Counter", 0) }),
+        ),
+        (
+            "textDocument/inlayHint",
+            json!({ "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 20, "character": 0 },
+            } }),
+        ),
+    ]);
+
+    // The package type checks
+    let diagnostics = &notifications
+        .iter()
+        .find(|notification| notification["method"] == "textDocument/publishDiagnostics")
+        .unwrap()["params"]["diagnostics"];
+    assert_eq!(diagnostics, &json!([]));
+
+    assert_eq!(
+        results[0]["range"]["start"],
+        position_of(SOURCE, "new(value", 0)
+    );
+
+    // The field is defined and selected, while the other `value`s are the parameter of `new`
+    let references = results[1].as_array().unwrap();
+    let starts: Vec<_> = references
+        .iter()
+        .map(|reference| reference["range"]["start"].clone())
+        .collect();
+    assert_eq!(starts, vec![
+        position_of(SOURCE, "value", 0),
+        position_of(SOURCE, "value", 3),
+    ]);
+
+    let hover = results[2]["contents"]["value"].as_str().unwrap();
+    assert!(hover.contains("struct Counter has drop {\n    value: u64,\n}"));
+    assert!(hover.contains("A counter"));
+
+    let hints: Vec<_> = results[3]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hint| hint["label"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(hints.len(), 2);
+    assert_eq!(hints[0], ": u64");
+    assert!(hints[1].ends_with("Counter"));
+}
+
+#[test]
+fn completion() {
+    let package = new_package();
+    let (results, _) = serve(package.path(), vec![
+        (
+            "textDocument/completion",
+            json!({ "position": position_of(SOURCE, "c.value", 0) }),
+        ),
+        (
+            "textDocument/completion",
+            json!({ "position": position_of(SOURCE, "value;", 0) }),
+        ),
+    ]);
+    // Nothing is completed before the variable
+    assert_eq!(results[0]["items"], json!([]));
+    // The fields of the struct the variable refers to are completed after the dot
+    let labels: Vec<_> = results[1]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].clone())
+        .collect();
+    assert_eq!(labels, vec![json!("value")]);
+}
+
+#[test]
+fn diagnostics() {
+    let package = new_package();
+    fs::write(
+        package.path().join("sources/m.move"),
+        SOURCE.replace("let v = c.value;", "let v: bool = c.value;"),
+    )
+    .unwrap();
+    let (_, notifications) = serve(package.path(), vec![]);
+    let diagnostics = notifications
+        .iter()
+        .find(|notification| notification["method"] == "textDocument/publishDiagnostics")
+        .unwrap()["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert!(!diagnostics.is_empty());
+    assert_eq!(diagnostics[0]["severity"], json!(1));
+}
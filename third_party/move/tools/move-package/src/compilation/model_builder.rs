@@ -9,7 +9,10 @@ use crate::{
 use anyhow::Result;
 use itertools::Itertools;
 use move_compiler::shared::PackagePaths;
-use move_compiler_v2::Options;
+use move_compiler_v2::{
+    linter::{LintConfig, LintDiagnostic},
+    Options,
+};
 use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
 use termcolor::{ColorChoice, StandardStream};
 
@@ -33,6 +36,51 @@ impl ModelBuilder {
     // TODO: In the future we will need a better way to do this to support renaming in packages
     // where we want to support building a Move model.
     pub fn build_model(&self) -> Result<GlobalEnv> {
+        let (all_targets, all_deps) = self.targets_and_deps()?;
+        let skip_attribute_checks = self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .skip_attribute_checks;
+        let known_attributes = &self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .known_attributes;
+        match self.model_config.compiler_version {
+            CompilerVersion::V1 => run_model_builder_with_options(
+                all_targets,
+                vec![],
+                all_deps,
+                ModelBuilderOptions::default(),
+                skip_attribute_checks,
+                known_attributes,
+            ),
+            CompilerVersion::V2_0 => {
+                let options = self.options_for_v2_compiler(all_targets, all_deps);
+                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
+            },
+        }
+    }
+
+    /// Type checks the target packages with the v2 compiler and runs the linter on them,
+    /// returning the model and the violations of lint rules.
+    pub fn lint(&self, lint_config: &LintConfig) -> Result<(GlobalEnv, Vec<LintDiagnostic>)> {
+        let (all_targets, all_deps) = self.targets_and_deps()?;
+        let options = self.options_for_v2_compiler(all_targets, all_deps);
+        move_compiler_v2::run_checker_and_linter(options, lint_config)
+    }
+
+    /// Returns the targets and dependencies of the model.
+    pub fn check(&self) -> Result<GlobalEnv> {
+        let (all_targets, all_deps) = self.targets_and_deps()?;
+        let mut options = self.options_for_v2_compiler(all_targets, all_deps);
+        options.compile_test_code = self.resolution_graph.build_options.test_mode;
+        move_compiler_v2::run_checker(options)
+    }
+
+    fn targets_and_deps(&self) -> Result<(Vec<PackagePaths>, Vec<PackagePaths>)> {
         // Make sure no renamings have been performed
         if let Some(pkg_name) = self.resolution_graph.contains_renaming() {
             anyhow::bail!(
@@ -116,39 +164,22 @@ impl ModelBuilder {
                 all_deps.into_iter().map(|(p, _)| p).collect_vec(),
             ),
         };
+        Ok((all_targets, all_deps))
+    }
 
-        let skip_attribute_checks = self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .skip_attribute_checks;
-        let known_attributes = &self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .known_attributes;
-        match self.model_config.compiler_version {
-            CompilerVersion::V1 => run_model_builder_with_options(
-                all_targets,
-                vec![],
-                all_deps,
-                ModelBuilderOptions::default(),
-                skip_attribute_checks,
-                known_attributes,
-            ),
-            CompilerVersion::V2_0 => {
-                let mut options = make_options_for_v2_compiler(all_targets, all_deps);
-                options.language_version = self
-                    .resolution_graph
-                    .build_options
-                    .compiler_config
-                    .language_version;
-                options.known_attributes.clone_from(known_attributes);
-                options.skip_attribute_checks = skip_attribute_checks;
-                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
-                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
-            },
-        }
+    fn options_for_v2_compiler(
+        &self,
+        targets: Vec<PackagePaths>,
+        deps: Vec<PackagePaths>,
+    ) -> Options {
+        let compiler_config = &self.resolution_graph.build_options.compiler_config;
+        let mut options = make_options_for_v2_compiler(targets, deps);
+        options.language_version = compiler_config.language_version;
+        options
+            .known_attributes
+            .clone_from(&compiler_config.known_attributes);
+        options.skip_attribute_checks = compiler_config.skip_attribute_checks;
+        options
     }
 }
 
@@ -14,7 +14,10 @@ use crate::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
     },
     package_lock::PackageLock,
-    resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
+    resolution::{
+        registry::LockFile,
+        resolution_graph::{ResolutionGraph, ResolvedGraph},
+    },
     source_package::manifest_parser,
 };
 use anyhow::{bail, Result};
@@ -22,6 +25,7 @@ use clap::*;
 use move_compiler::{
     command_line::SKIP_ATTRIBUTE_CHECKS, shared::known_attributes::KnownAttribute,
 };
+use move_compiler_v2::linter::{LintConfig, LintDiagnostic};
 use move_core_types::account_address::AccountAddress;
 use move_model::{
     metadata::{CompilerVersion, LanguageVersion},
@@ -251,6 +255,47 @@ impl BuildConfig {
         ret
     }
 
+    /// Type checks the package with the v2 compiler and runs the linter on it, returning the
+    /// model and the violations of lint rules. Dependencies are not linted.
+    pub fn lint_package(
+        self,
+        path: &Path,
+        language_version: LanguageVersion,
+        lint_config: &LintConfig,
+    ) -> Result<(model::GlobalEnv, Vec<LintDiagnostic>)> {
+        let resolved_graph = self.resolution_graph_for_package(path, &mut Vec::new())?;
+        let mutx = PackageLock::lock();
+        let ret = ModelBuilder::create(resolved_graph, ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+            compiler_version: CompilerVersion::V2_0,
+            language_version,
+        })
+        .lint(lint_config);
+        mutx.unlock();
+        ret
+    }
+
+    /// Type checks the package with the v2 compiler, returning the model with the diagnostics of
+    /// checking rather than failing on errors. Test code is included in test mode.
+    pub fn check_package(
+        self,
+        path: &Path,
+        language_version: LanguageVersion,
+    ) -> Result<model::GlobalEnv> {
+        let resolved_graph = self.resolution_graph_for_package(path, &mut Vec::new())?;
+        let mutx = PackageLock::lock();
+        let ret = ModelBuilder::create(resolved_graph, ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+            compiler_version: CompilerVersion::V2_0,
+            language_version,
+        })
+        .check();
+        mutx.unlock();
+        ret
+    }
+
     pub fn download_deps_for_package<W: Write>(&self, path: &Path, writer: &mut W) -> Result<()> {
         let path = SourcePackageLayout::try_find_root(path)?;
         let toml_manifest =
@@ -264,6 +309,47 @@ impl BuildConfig {
         Ok(())
     }
 
+    /// Resolves the registry dependencies of the package at `path` to the latest versions which
+    /// satisfy their requirements, instead of the versions locked in `Move.lock`, and updates the
+    /// lock file. Only `packages` are updated if any are given. Returns the lock file before and
+    /// after the update.
+    pub fn update_dependencies<W: Write>(
+        mut self,
+        path: &Path,
+        packages: &[String],
+        writer: &mut W,
+    ) -> Result<(LockFile, LockFile)> {
+        // Dev dependencies are resolved too, so that they stay locked
+        self.dev_mode = true;
+        let path = SourcePackageLayout::try_find_root(path)?;
+        let lock_path = path.join(SourcePackageLayout::Lock.path());
+        let old_lock = LockFile::read(&lock_path)?;
+        for package in packages {
+            if old_lock.get(package).is_none() {
+                bail!(
+                    "Package '{}' is not locked in {}",
+                    package,
+                    lock_path.display()
+                );
+            }
+        }
+        if old_lock.packages.is_empty() {
+            self.resolution_graph_for_package(&path, writer)?;
+            return Ok((old_lock, LockFile::read(&lock_path)?));
+        }
+
+        let mut unlocked = old_lock.clone();
+        unlocked
+            .packages
+            .retain(|locked| !packages.is_empty() && !packages.contains(&locked.name));
+        unlocked.write(&lock_path)?;
+        if let Err(err) = self.resolution_graph_for_package(&path, writer) {
+            old_lock.write(&lock_path)?;
+            return Err(err);
+        }
+        Ok((old_lock, LockFile::read(&lock_path)?))
+    }
+
     pub fn resolution_graph_for_package<W: Write>(
         mut self,
         path: &Path,
@@ -0,0 +1,391 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    resolution::{digest::compute_digest, resolution_graph::confirm_git_available},
+    source_package::{
+        layout::SourcePackageLayout,
+        manifest_parser::git_repo_cache_path,
+        parsed_manifest::{Dependency, GitInfo, PackageDigest, PackageName},
+    },
+};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use move_command_line_common::env::MOVE_REGISTRY_ENV_VAR;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The directory of a registry containing the metadata of its packages, `index/<package>.toml`
+const INDEX_DIR: &str = "index";
+
+const LOCK_FILE_HEADER: &str =
+    "# This file is generated by the Move package system. It is not intended for manual editing.\n";
+
+/// The metadata of a package in the index of a registry
+///
+/// ```toml
+/// [[versions]]
+/// version = "1.2.0"
+/// digest = "6A88B7888D6049EB0121900E22B6FA2C0E702F042C8C8D4FD62AD5C990B9F9A8"
+/// local = "packages/Foo/1.2.0"
+///
+/// [[versions]]
+/// version = "1.3.0"
+/// digest = "..."
+/// git = "https://github.com/org/foo.git"
+/// rev = "v1.3.0"
+/// yanked = true
+/// ```
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    versions: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    version: Version,
+    /// The digest of the sources and manifest of the package, see `package_digest`
+    digest: String,
+    /// The package directory, relative to the registry
+    local: Option<PathBuf>,
+    git: Option<String>,
+    rev: Option<String>,
+    subdir: Option<PathBuf>,
+    /// Yanked versions are only used if they are already locked
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// The contents of `Move.lock`, which pins the versions of the registry dependencies of a package
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LockFile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+    pub digest: String,
+}
+
+impl LockFile {
+    /// Reads the lock file at `path`, which is empty if there is none
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).with_context(|| format!("Unable to parse {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self)?;
+        fs::write(path, format!("{}\n{}", LOCK_FILE_HEADER, contents))
+            .with_context(|| format!("Unable to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    fn insert(&mut self, package: LockedPackage) {
+        self.packages.retain(|locked| locked.name != package.name);
+        self.packages.push(package);
+        self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// A package resolved in a registry, with the digest its sources must have
+#[derive(Debug)]
+struct ResolvedVersion {
+    version: Version,
+    digest: String,
+    dependency: Dependency,
+}
+
+/// Resolves dependencies on versions of packages in a registry, for the packages depended on by a
+/// root package. A package is resolved to the version locked in the `Move.lock` of the root
+/// package if it satisfies the requirement, and otherwise to the greatest version in the index of
+/// the registry which does, which is then locked.
+pub(crate) struct Registry {
+    lock_path: PathBuf,
+    lock: LockFile,
+    lock_changed: bool,
+    resolved: BTreeMap<PackageName, ResolvedVersion>,
+    skip_fetch_latest_git_deps: bool,
+}
+
+impl Registry {
+    pub(crate) fn new(root_package_path: &Path, skip_fetch_latest_git_deps: bool) -> Result<Self> {
+        let lock_path = root_package_path.join(SourcePackageLayout::Lock.path());
+        Ok(Self {
+            lock: LockFile::read(&lock_path)?,
+            lock_path,
+            lock_changed: false,
+            resolved: BTreeMap::new(),
+            skip_fetch_latest_git_deps,
+        })
+    }
+
+    /// Returns the dependency on the package a registry dependency is resolved to, or the
+    /// dependency itself if it isn't on a registry. `package_path` is the path of the package
+    /// declaring the dependency.
+    pub(crate) fn resolve<W: Write>(
+        &mut self,
+        dep_name: PackageName,
+        dep: Dependency,
+        package_path: &Path,
+        writer: &mut W,
+    ) -> Result<Dependency> {
+        let info = match dep.registry_info.clone() {
+            Some(info) => info,
+            None => return Ok(dep),
+        };
+        if let Some(resolved) = self.resolved.get(&dep_name) {
+            if !info.version_req.matches(&resolved.version) {
+                bail!(
+                    "Conflicting version requirements for package '{}': version {} was selected, \
+                     which does not satisfy '{}'",
+                    dep_name,
+                    resolved.version,
+                    info.version_req
+                );
+            }
+            return Ok(Dependency {
+                subst: dep.subst,
+                ..resolved.dependency.clone()
+            });
+        }
+
+        let registry = match &info.registry {
+            Some(registry) => registry.to_string(),
+            None => std::env::var(MOVE_REGISTRY_ENV_VAR).with_context(|| {
+                format!(
+                    "No registry to resolve dependency '{}' in. Set 'registry' in the dependency \
+                     or the {} environment variable",
+                    dep_name, MOVE_REGISTRY_ENV_VAR
+                )
+            })?,
+        };
+        let registry_path = self.registry_path(&registry, package_path, writer)?;
+        let index_path = registry_path
+            .join(INDEX_DIR)
+            .join(format!("{}.toml", dep_name));
+        let index: IndexFile = match fs::read_to_string(&index_path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("Unable to parse {}", index_path.display()))?,
+            Err(_) => bail!(
+                "Package '{}' not found in registry '{}'",
+                dep_name,
+                registry
+            ),
+        };
+
+        let locked = self
+            .lock
+            .get(dep_name.as_str())
+            .filter(|locked| info.version_req.matches(&locked.version))
+            .cloned();
+        let entry = match locked {
+            Some(locked) => {
+                let entry = index
+                    .versions
+                    .iter()
+                    .find(|entry| entry.version == locked.version)
+                    .with_context(|| {
+                        format!(
+                            "Locked version {} of package '{}' not found in registry '{}'",
+                            locked.version, dep_name, registry
+                        )
+                    })?;
+                if entry.digest != locked.digest {
+                    bail!(
+                        "Digest mismatch for version {} of package '{}' in registry '{}'. \
+                         Expected '{}' from {} but got '{}'",
+                        entry.version,
+                        dep_name,
+                        registry,
+                        locked.digest,
+                        self.lock_path.display(),
+                        entry.digest
+                    );
+                }
+                entry
+            },
+            None => {
+                let entry = index
+                    .versions
+                    .iter()
+                    .filter(|entry| !entry.yanked && info.version_req.matches(&entry.version))
+                    .max_by(|a, b| a.version.cmp(&b.version))
+                    .with_context(|| {
+                        format!(
+                            "No version of package '{}' in registry '{}' satisfies '{}'",
+                            dep_name, registry, info.version_req
+                        )
+                    })?;
+                self.lock.insert(LockedPackage {
+                    name: dep_name.to_string(),
+                    version: entry.version.clone(),
+                    digest: entry.digest.clone(),
+                });
+                self.lock_changed = true;
+                entry
+            },
+        };
+
+        let (local, git_info) = match (&entry.local, &entry.git) {
+            (Some(local), None) => (registry_path.join(local), None),
+            (None, Some(git_url)) => {
+                let git_rev = entry.rev.as_deref().with_context(|| {
+                    format!(
+                        "Git revision not supplied for version {} of package '{}' in registry '{}'",
+                        entry.version, dep_name, registry
+                    )
+                })?;
+                let download_to = git_repo_cache_path(git_url, git_rev);
+                let subdir = entry.subdir.clone().unwrap_or_default();
+                (download_to.join(&subdir), Some(GitInfo {
+                    git_url: git_url.as_str().into(),
+                    git_rev: git_rev.into(),
+                    subdir,
+                    download_to,
+                }))
+            },
+            _ => bail!(
+                "Version {} of package '{}' in registry '{}' must have exactly one of 'local' or \
+                 'git'",
+                entry.version,
+                dep_name,
+                registry
+            ),
+        };
+        let dependency = Dependency {
+            local,
+            version: Some((
+                entry.version.major,
+                entry.version.minor,
+                entry.version.patch,
+            )),
+            git_info,
+            ..dep
+        };
+        self.resolved.insert(dep_name, ResolvedVersion {
+            version: entry.version.clone(),
+            digest: entry.digest.clone(),
+            dependency: dependency.clone(),
+        });
+        Ok(dependency)
+    }
+
+    /// Checks that the sources of a package resolved in a registry, at `package_path`, have the
+    /// digest recorded in the registry
+    pub(crate) fn verify_digest(&self, dep_name: PackageName, package_path: &Path) -> Result<()> {
+        if let Some(resolved) = self.resolved.get(&dep_name) {
+            let digest = package_digest(package_path)?;
+            if digest.as_str() != resolved.digest {
+                bail!(
+                    "Source digest mismatch in version {} of package '{}'. Expected '{}' but got \
+                     '{}'.",
+                    resolved.version,
+                    dep_name,
+                    resolved.digest,
+                    digest
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the versions resolved to `Move.lock`, if any weren't locked before
+    pub(crate) fn write_lock(&self) -> Result<()> {
+        if self.lock_changed {
+            self.lock.write(&self.lock_path)?;
+        }
+        Ok(())
+    }
+
+    /// The directory of a registry, which is fetched to `MOVE_HOME` if it's a git repository
+    fn registry_path<W: Write>(
+        &self,
+        registry: &str,
+        package_path: &Path,
+        writer: &mut W,
+    ) -> Result<PathBuf> {
+        if !registry.contains("://") && !registry.starts_with("git@") {
+            return Ok(package_path.join(registry));
+        }
+        let path = git_repo_cache_path(registry, "registry");
+        let git_path = path.display().to_string();
+        if !path.exists() {
+            writeln!(
+                writer,
+                "{} {}",
+                "FETCHING REGISTRY".bold().green(),
+                registry
+            )?;
+            confirm_git_available()?;
+            let status = Command::new("git")
+                .args(["clone", registry, &git_path])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .with_context(|| format!("Failed to clone registry '{}'", registry))?;
+            if !status.success() {
+                bail!(
+                    "Failed to clone registry '{}' | Exit status: {}",
+                    registry,
+                    status
+                );
+            }
+        } else if !self.skip_fetch_latest_git_deps {
+            writeln!(
+                writer,
+                "{} {}",
+                "UPDATING REGISTRY".bold().green(),
+                registry
+            )?;
+            confirm_git_available()?;
+            let status = Command::new("git")
+                .args(["-C", &git_path, "pull", "--ff-only"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .with_context(|| format!("Failed to update registry '{}'", registry))?;
+            if !status.success() {
+                bail!(
+                    "Failed to update registry '{}', to skip set --skip-fetch-latest-git-deps | \
+                     Exit status: {}",
+                    registry,
+                    status
+                );
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// The digest of a package recorded in a registry, of its manifest and the sources and scripts
+/// which are published, so it doesn't depend on the build mode
+pub fn package_digest(package_path: &Path) -> Result<PackageDigest> {
+    let paths: Vec<_> = [
+        SourcePackageLayout::Manifest,
+        SourcePackageLayout::Sources,
+        SourcePackageLayout::Scripts,
+    ]
+    .iter()
+    .map(|layout| package_path.join(layout.path()))
+    .filter(|path| path.exists())
+    .collect();
+    compute_digest(&paths)
+}
@@ -4,7 +4,7 @@
 
 use crate::{
     package_hooks,
-    resolution::digest::compute_digest,
+    resolution::{digest::compute_digest, registry::Registry},
     source_package::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
@@ -115,6 +115,8 @@ impl ResolvingGraph {
         };
 
         let override_std = &build_options.override_std;
+        let mut registry =
+            Registry::new(&root_package_path, build_options.skip_fetch_latest_git_deps)?;
 
         resolution_graph
             .build_resolution_graph(
@@ -122,6 +124,7 @@ impl ResolvingGraph {
                 root_package_path,
                 true,
                 override_std,
+                &mut registry,
                 writer,
             )
             .with_context(|| {
@@ -130,6 +133,7 @@ impl ResolvingGraph {
                     root_package.package.name
                 )
             })?;
+        registry.write_lock()?;
         Ok(resolution_graph)
     }
 
@@ -209,6 +213,7 @@ impl ResolvingGraph {
         package_path: PathBuf,
         is_root_package: bool,
         override_std: &Option<StdVersion>,
+        registry: &mut Registry,
         writer: &mut W,
     ) -> Result<()> {
         let package_name = package.package.name;
@@ -267,7 +272,14 @@ impl ResolvingGraph {
             self.graph.add_edge(package_node_id, dep_node_id, ());
 
             let (dep_renaming, dep_resolution_table) = self
-                .process_dependency(dep_name, dep, package_path.clone(), override_std, writer)
+                .process_dependency(
+                    dep_name,
+                    dep,
+                    package_path.clone(),
+                    override_std,
+                    registry,
+                    writer,
+                )
                 .with_context(|| {
                     format!(
                         "While resolving dependency '{}' in package '{}'",
@@ -409,8 +421,10 @@ impl ResolvingGraph {
         dep: Dependency,
         root_path: PathBuf,
         override_std: &Option<StdVersion>,
+        registry: &mut Registry,
         writer: &mut W,
     ) -> Result<(Renaming, ResolvingTable)> {
+        let dep = registry.resolve(dep_name_in_pkg, dep, &root_path, writer)?;
         Self::download_and_update_if_remote(
             dep_name_in_pkg,
             &dep,
@@ -420,11 +434,13 @@ impl ResolvingGraph {
         let (dep_package, dep_package_dir) =
             Self::parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
+        registry.verify_digest(dep_name_in_pkg, &dep_package_dir)?;
         self.build_resolution_graph(
             dep_package.clone(),
             dep_package_dir,
             false,
             override_std,
+            registry,
             writer,
         )
         .with_context(|| format!("Unable to resolve package dependency '{}'", dep_name_in_pkg))?;
@@ -548,6 +564,24 @@ impl ResolvingGraph {
         build_options: &BuildConfig,
         root_path: &Path,
         writer: &mut W,
+    ) -> Result<()> {
+        let mut registry = Registry::new(root_path, build_options.skip_fetch_latest_git_deps)?;
+        Self::download_dependency_repos_with_registry(
+            manifest,
+            build_options,
+            root_path,
+            &mut registry,
+            writer,
+        )?;
+        registry.write_lock()
+    }
+
+    fn download_dependency_repos_with_registry<W: Write>(
+        manifest: &SourceManifest,
+        build_options: &BuildConfig,
+        root_path: &Path,
+        registry: &mut Registry,
+        writer: &mut W,
     ) -> Result<()> {
         // include dev dependencies if in dev mode
         let empty_deps;
@@ -559,18 +593,26 @@ impl ResolvingGraph {
         };
 
         for (dep_name, dep) in manifest.dependencies.iter().chain(additional_deps.iter()) {
+            let dep = registry.resolve(*dep_name, dep.clone(), root_path, writer)?;
             Self::download_and_update_if_remote(
                 *dep_name,
-                dep,
+                &dep,
                 build_options.skip_fetch_latest_git_deps,
                 writer,
             )?;
 
-            let (dep_manifest, _) =
-                Self::parse_package_manifest(dep, dep_name, root_path.to_path_buf())
+            let (dep_manifest, dep_path) =
+                Self::parse_package_manifest(&dep, dep_name, root_path.to_path_buf())
                     .with_context(|| format!("While processing dependency '{}'", *dep_name))?;
+            registry.verify_digest(*dep_name, &dep_path)?;
             // download dependencies of dependencies
-            Self::download_dependency_repos(&dep_manifest, build_options, root_path, writer)?;
+            Self::download_dependency_repos_with_registry(
+                &dep_manifest,
+                build_options,
+                root_path,
+                registry,
+                writer,
+            )?;
         }
         Ok(())
     }
@@ -966,7 +1008,7 @@ impl ResolvedPackage {
     }
 }
 
-fn confirm_git_available() -> Result<()> {
+pub(crate) fn confirm_git_available() -> Result<()> {
     match Command::new("git").arg("--version").output() {
         Ok(_) => Ok(()),
         Err(e) => {
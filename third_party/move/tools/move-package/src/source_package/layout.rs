@@ -16,6 +16,7 @@ pub enum SourcePackageLayout {
     Scripts,
     Examples,
     Manifest,
+    Lock,
     DocTemplates,
     Build,
 }
@@ -24,6 +25,7 @@ impl SourcePackageLayout {
     /// A Move source package is laid out on-disk as
     /// a_move_package
     /// ├── Move.toml      (required)
+    /// ├── Move.lock      (optional, created by resolving registry dependencies)
     /// ├── sources        (required)
     /// ├── examples       (optional, dev mode)
     /// ├── scripts        (optional)
@@ -54,6 +56,7 @@ impl SourcePackageLayout {
         match self {
             Self::Sources => "sources",
             Self::Manifest => "Move.toml",
+            Self::Lock => "Move.lock",
             Self::Tests => "tests",
             Self::Scripts => "scripts",
             Self::Examples => "examples",
@@ -67,6 +70,7 @@ impl SourcePackageLayout {
         match self {
             Self::Sources | Self::Manifest => false,
             Self::Tests
+            | Self::Lock
             | Self::Scripts
             | Self::Examples
             | Self::Specifications
@@ -8,6 +8,7 @@ use anyhow::{bail, format_err, Context, Result};
 use move_command_line_common::env::MOVE_HOME;
 use move_core_types::account_address::{AccountAddress, AccountAddressParseError};
 use move_symbol_pool::symbol::Symbol;
+use semver::VersionReq;
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
@@ -307,6 +308,19 @@ fn parse_address_literal(address_str: &str) -> Result<AccountAddress, AccountAdd
 
 fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
     match tval {
+        // A version requirement on its own, e.g. `Foo = "^1.2"`, is resolved in the registry
+        TV::String(version_req) => Ok(PM::Dependency {
+            subst: None,
+            version: None,
+            digest: None,
+            local: PathBuf::new(),
+            git_info: None,
+            node_info: None,
+            registry_info: Some(PM::RegistryInfo {
+                version_req: parse_version_req(&version_req)?,
+                registry: None,
+            }),
+        }),
         TV::Table(mut table) => {
             let mut known_fields = vec![
                 "addr_subst",
@@ -317,6 +331,7 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                 "rev",
                 "subdir",
                 "address",
+                "registry",
             ];
             let custom_key_opt = &package_hooks::custom_dependency_key();
             if let Some(key) = custom_key_opt {
@@ -327,7 +342,35 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                 .remove("addr_subst")
                 .map(parse_substitution)
                 .transpose()?;
-            let version = table.remove("version").map(parse_version).transpose()?;
+            // Without a source, the version is a requirement resolved in the registry
+            let has_source = ["local", "git"]
+                .into_iter()
+                .chain(custom_key_opt.as_deref())
+                .any(|key| table.contains_key(key));
+            let (version, registry_info) = match table.remove("version") {
+                Some(version_req) if !has_source => {
+                    let version_req = version_req
+                        .as_str()
+                        .ok_or_else(|| format_err!("Version requirement not a string"))?;
+                    let registry = table
+                        .remove("registry")
+                        .map(|registry| {
+                            registry
+                                .as_str()
+                                .map(Symbol::from)
+                                .ok_or_else(|| format_err!("Registry not a string"))
+                        })
+                        .transpose()?;
+                    (
+                        None,
+                        Some(PM::RegistryInfo {
+                            version_req: parse_version_req(version_req)?,
+                            registry,
+                        }),
+                    )
+                },
+                version => (version.map(parse_version).transpose()?, None),
+            };
             let digest = table.remove("digest").map(parse_digest).transpose()?;
             let mut git_info = None;
             let mut node_info = None;
@@ -352,6 +395,7 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path,
                         git_info,
                         node_info,
+                        registry_info,
                     })
                 },
                 (None, Some(git), None) => {
@@ -388,6 +432,7 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path.join(subdir),
                         git_info,
                         node_info,
+                        registry_info,
                     })
                 },
                 (None, None, Some(custom_key)) => {
@@ -422,8 +467,18 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path,
                         git_info,
                         node_info,
+                        registry_info,
                     })
                 },
+                (None, None, None) if registry_info.is_some() => Ok(PM::Dependency {
+                    subst,
+                    version,
+                    digest,
+                    local: PathBuf::new(),
+                    git_info,
+                    node_info,
+                    registry_info,
+                }),
                 _ => {
                     let mut keys = vec!["local", "git"];
                     if let Some(k) = custom_key_opt {
@@ -434,7 +489,7 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         .map(|s| format!("'{}'", s))
                         .collect::<Vec<_>>();
                     bail!(
-                        "must provide exactly one of {} for dependency.",
+                        "must provide exactly one of {} or a 'version' for dependency.",
                         keys.join(" or ")
                     )
                 },
@@ -506,6 +561,11 @@ fn parse_version(tval: TV) -> Result<PM::Version> {
     ))
 }
 
+fn parse_version_req(version_req: &str) -> Result<VersionReq> {
+    VersionReq::parse(version_req)
+        .with_context(|| format!("Invalid version requirement '{}'", version_req))
+}
+
 fn parse_architecture(tval: TV) -> Result<Architecture> {
     Architecture::try_parse_from_str(tval.as_str().unwrap())
 }
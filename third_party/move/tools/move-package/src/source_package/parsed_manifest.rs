@@ -5,6 +5,7 @@
 use crate::Architecture;
 use move_core_types::account_address::AccountAddress;
 use move_symbol_pool::symbol::Symbol;
+use semver::VersionReq;
 use std::{collections::BTreeMap, fmt, fmt::Debug, path::PathBuf};
 
 pub type NamedAddress = Symbol;
@@ -79,6 +80,7 @@ pub struct Dependency {
     pub digest: Option<PackageDigest>,
     pub git_info: Option<GitInfo>,
     pub node_info: Option<CustomDepInfo>,
+    pub registry_info: Option<RegistryInfo>,
 }
 
 impl fmt::Display for Dependency {
@@ -114,6 +116,15 @@ pub struct CustomDepInfo {
     pub download_to: PathBuf,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RegistryInfo {
+    /// The versions of the package which can be used, e.g. `^1.2`
+    pub version_req: VersionReq,
+    /// The registry the package is resolved in, a directory relative to the depending package
+    /// or the URL of a git repository. Defaults to the `MOVE_REGISTRY` environment variable.
+    pub registry: Option<Symbol>,
+}
+
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct BuildInfo {
     pub language_version: Option<Version>,
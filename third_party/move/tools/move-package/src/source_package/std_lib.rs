@@ -36,6 +36,7 @@ impl StdLib {
                 download_to: local,
             }),
             node_info: None,
+            registry_info: None,
         }
     }
 
@@ -0,0 +1,116 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{
+    resolution::registry::{package_digest, LockFile},
+    BuildConfig,
+};
+use std::{fs, path::Path};
+use tempfile::{tempdir, TempDir};
+
+/// Adds a version of package `Foo` to the registry at `registry`
+fn publish(registry: &Path, version: &str, yanked: bool) {
+    let package = registry.join("packages").join(version);
+    fs::create_dir_all(package.join("sources")).unwrap();
+    fs::write(
+        package.join("Move.toml"),
+        format!("[package]\nname = \"Foo\"\nversion = \"{}\"\n", version),
+    )
+    .unwrap();
+    fs::write(package.join("sources/foo.move"), "module 0x1::foo {}\n").unwrap();
+
+    let index = registry.join("index/Foo.toml");
+    fs::create_dir_all(index.parent().unwrap()).unwrap();
+    let mut contents = fs::read_to_string(&index).unwrap_or_default();
+    contents.push_str(&format!(
+        "[[versions]]\nversion = \"{}\"\ndigest = \"{}\"\nlocal = \"packages/{}\"\nyanked = {}\n",
+        version,
+        package_digest(&package).unwrap(),
+        version,
+        yanked
+    ));
+    fs::write(index, contents).unwrap();
+}
+
+/// A registry with versions 1.2.0, 1.3.0, a yanked 1.4.0 and 2.0.0 of `Foo`, and a package
+/// depending on `Foo = "^1.2"`
+fn setup() -> TempDir {
+    let dir = tempdir().unwrap();
+    let registry = dir.path().join("registry");
+    publish(&registry, "1.2.0", false);
+    publish(&registry, "1.3.0", false);
+    publish(&registry, "1.4.0", true);
+    publish(&registry, "2.0.0", false);
+
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n\
+         [dependencies]\nFoo = { version = \"^1.2\", registry = \"../registry\" }\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn resolve(root: &Path) -> anyhow::Result<String> {
+    let graph = BuildConfig {
+        install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+        ..Default::default()
+    }
+    .resolution_graph_for_package(root, &mut Vec::new())?;
+    let foo = graph.get_package(&"Foo".into());
+    let version = foo.source_package.package.version;
+    Ok(format!("{}.{}.{}", version.0, version.1, version.2))
+}
+
+fn locked_version(root: &Path) -> String {
+    LockFile::read(&root.join("Move.lock"))
+        .unwrap()
+        .get("Foo")
+        .unwrap()
+        .version
+        .to_string()
+}
+
+#[test]
+fn resolves_and_locks_greatest_matching_version() {
+    let dir = setup();
+    let root = dir.path().join("root");
+    assert_eq!(resolve(&root).unwrap(), "1.3.0");
+    assert_eq!(locked_version(&root), "1.3.0");
+}
+
+#[test]
+fn keeps_locked_version_until_updated() {
+    let dir = setup();
+    let root = dir.path().join("root");
+    resolve(&root).unwrap();
+    publish(&dir.path().join("registry"), "1.5.0", false);
+    assert_eq!(resolve(&root).unwrap(), "1.3.0");
+
+    let (old, new) = BuildConfig::default()
+        .update_dependencies(&root, &[], &mut Vec::new())
+        .unwrap();
+    assert_eq!(old.get("Foo").unwrap().version.to_string(), "1.3.0");
+    assert_eq!(new.get("Foo").unwrap().version.to_string(), "1.5.0");
+    assert_eq!(resolve(&root).unwrap(), "1.5.0");
+}
+
+#[test]
+fn rejects_modified_sources() {
+    let dir = setup();
+    let root = dir.path().join("root");
+    resolve(&root).unwrap();
+    fs::write(
+        dir.path().join("registry/packages/1.3.0/sources/foo.move"),
+        "module 0x1::foo { fun f() {} }\n",
+    )
+    .unwrap();
+    let err = resolve(&root).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("Source digest mismatch in version 1.3.0 of package 'Foo'"),
+        "{:#}",
+        err
+    );
+}
@@ -41,6 +41,7 @@ pub fn run_tests_with_config_and_filter(
             native_function_table,
             genesis_state,
             cost_table,
+            None,
             std::io::stdout(),
         )
         .expect("Failed to execute tests");
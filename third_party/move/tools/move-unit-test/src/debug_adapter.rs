@@ -0,0 +1,1113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A server for the Debug Adapter Protocol (DAP), to step through a Move unit test from an editor
+//! such as VS Code. The test runs on its own thread, in a Move VM whose debug hook pauses it
+//! before any instruction where a breakpoint is hit or a step ends. Instructions are mapped to
+//! source lines with the source maps of the compiled modules, and values are rendered with
+//! `move-resource-viewer`.
+
+use crate::{
+    format_module_id,
+    test_runner::{setup_test_storage, TestRunner},
+    UnitTestingConfig, DEFAULT_EXECUTION_BOUND,
+};
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+};
+use move_command_line_common::files::FileHash;
+use move_compiler::unit_test::TestPlan;
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ChangeSet,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
+use move_vm_runtime::{
+    native_functions::NativeFunctionTable, set_debug_hook, DebugFrame, DebugHook, DebugState,
+    DebugValue,
+};
+use move_vm_test_utils::{gas_schedule::CostTable, InMemoryStorage};
+use serde_json::{json, Value as Json};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::{BufRead, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// Tests run on a single thread, which is the only one reported to the editor
+const THREAD_ID: u64 = 1;
+
+/// Where an instruction is in the source
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SourcePosition {
+    file: FileHash,
+    line: u32,
+    column: u32,
+}
+
+/// Where execution is, for deciding when a step ends
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Position {
+    depth: usize,
+    file: FileHash,
+    line: u32,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    line_starts: Vec<usize>,
+    // lines that instructions start on, which are the only ones breakpoints can be set on
+    code_lines: BTreeSet<u32>,
+}
+
+/// Maps the instructions of the modules of a test plan to source positions
+struct SourceIndex {
+    files: HashMap<FileHash, SourceFile>,
+    test_plan: TestPlan,
+}
+
+impl SourceIndex {
+    fn new(test_plan: TestPlan) -> Self {
+        let files = test_plan
+            .files
+            .iter()
+            .map(|(file_hash, (name, contents))| {
+                let path = Path::new(name.as_str());
+                let line_starts = std::iter::once(0)
+                    .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+                    .collect();
+                let source_file = SourceFile {
+                    path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+                    line_starts,
+                    code_lines: BTreeSet::new(),
+                };
+                (*file_hash, source_file)
+            })
+            .collect();
+        let mut index = Self { files, test_plan };
+
+        let mut code_lines = vec![];
+        for (module_id, named_module) in &index.test_plan.module_info {
+            let module = &named_module.module;
+            for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+                let code_len = function_def
+                    .code
+                    .as_ref()
+                    .map_or(0, |code_unit| code_unit.code.len());
+                for code_offset in 0..code_len {
+                    let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+                    if let Some(position) =
+                        index.position(module_id, function_def_idx, code_offset as CodeOffset)
+                    {
+                        code_lines.push(position);
+                    }
+                }
+            }
+        }
+        for position in code_lines {
+            if let Some(source_file) = index.files.get_mut(&position.file) {
+                source_file.code_lines.insert(position.line);
+            }
+        }
+        index
+    }
+
+    fn position(
+        &self,
+        module_id: &ModuleId,
+        function_def_idx: FunctionDefinitionIndex,
+        code_offset: CodeOffset,
+    ) -> Option<SourcePosition> {
+        let loc = self
+            .test_plan
+            .module_info
+            .get(module_id)?
+            .source_map
+            .get_code_location(function_def_idx, code_offset)
+            .ok()?;
+        let source_file = self.files.get(&loc.file_hash())?;
+        let offset = loc.start() as usize;
+        let line = source_file
+            .line_starts
+            .partition_point(|line_start| *line_start <= offset);
+        Some(SourcePosition {
+            file: loc.file_hash(),
+            line: line as u32,
+            column: (offset - source_file.line_starts[line - 1] + 1) as u32,
+        })
+    }
+
+    fn file(&self, path: &Path) -> Option<FileHash> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.files
+            .iter()
+            .find(|(_, source_file)| source_file.path == path)
+            .map(|(file_hash, _)| *file_hash)
+    }
+
+    /// The line a breakpoint requested at `line` of `path` is set on: the line itself if there
+    /// is code on it, and otherwise the next line with code.
+    fn breakpoint_line(&self, path: &Path, line: u32) -> Option<(FileHash, u32)> {
+        let file_hash = self.file(path)?;
+        let code_line = self.files[&file_hash].code_lines.range(line..).next()?;
+        Some((file_hash, *code_line))
+    }
+
+    fn local_name(&self, frame: &DebugFrame, index: usize) -> Option<String> {
+        let (name, _) = self
+            .test_plan
+            .module_info
+            .get(frame.module_id.as_ref()?)?
+            .source_map
+            .get_parameter_or_local_name(frame.function_index, index as u64)
+            .ok()?;
+        // Skip temporaries introduced by the compiler
+        if name.starts_with('%') || name.starts_with('$') {
+            None
+        } else {
+            Some(name)
+        }
+    }
+}
+
+/// The state shared by the adapter and the hook in the VM
+#[derive(Default)]
+struct SharedState {
+    breakpoints: Mutex<HashSet<(FileHash, u32)>>,
+    pause: AtomicBool,
+    stop_on_abort: AtomicBool,
+}
+
+/// How the test is resumed after it stopped
+#[derive(Clone, Copy, Debug)]
+enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum StepMode {
+    Entry,
+    Continue,
+    StepIn,
+    StepOver(Position),
+    StepOut(usize),
+    // The adapter is gone, so the test runs to completion
+    Detached,
+}
+
+/// The call stack and global resources of a stopped test
+struct Snapshot {
+    frames: Vec<(DebugFrame, Option<SourcePosition>)>,
+    globals: Vec<(AccountAddress, DebugValue)>,
+}
+
+enum Event {
+    Request(Json),
+    InputClosed,
+    Stopped {
+        reason: &'static str,
+        snapshot: Snapshot,
+    },
+    Finished {
+        output: String,
+        passed: bool,
+    },
+}
+
+struct AdapterHook {
+    index: Arc<SourceIndex>,
+    shared: Arc<SharedState>,
+    events: Sender<Event>,
+    resumes: Receiver<Resume>,
+    mode: StepMode,
+    last_position: Option<Position>,
+    positions: HashMap<(ModuleId, FunctionDefinitionIndex, CodeOffset), Option<SourcePosition>>,
+}
+
+impl AdapterHook {
+    fn stop_reason(&mut self, state: &DebugState, position: Position) -> Option<&'static str> {
+        let entered_line = self.last_position != Some(position);
+        self.last_position = Some(position);
+
+        if self.shared.pause.swap(false, Ordering::SeqCst) {
+            return Some("pause");
+        }
+        if matches!(state.instruction(), Bytecode::Abort)
+            && self.shared.stop_on_abort.load(Ordering::SeqCst)
+        {
+            return Some("exception");
+        }
+        if entered_line
+            && self
+                .shared
+                .breakpoints
+                .lock()
+                .unwrap()
+                .contains(&(position.file, position.line))
+        {
+            return Some("breakpoint");
+        }
+        let step_ended = match self.mode {
+            StepMode::Entry => true,
+            StepMode::Continue | StepMode::Detached => false,
+            StepMode::StepIn => entered_line,
+            StepMode::StepOver(from) => {
+                position.depth < from.depth
+                    || (entered_line && position.depth == from.depth && position != from)
+            },
+            StepMode::StepOut(depth) => position.depth < depth,
+        };
+        if step_ended {
+            Some(
+                if let StepMode::Entry = self.mode {
+                    "entry"
+                } else {
+                    "step"
+                },
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl DebugHook for AdapterHook {
+    fn on_instruction(&mut self, state: &DebugState) {
+        if let StepMode::Detached = self.mode {
+            return;
+        }
+        let module_id = match state.module_id() {
+            Some(module_id) => module_id,
+            None => return,
+        };
+        let index = &self.index;
+        let source_position = *self
+            .positions
+            .entry((module_id.clone(), state.function_index(), state.pc()))
+            .or_insert_with(|| index.position(module_id, state.function_index(), state.pc()));
+        let position = match source_position {
+            Some(source_position) => Position {
+                depth: state.depth(),
+                file: source_position.file,
+                line: source_position.line,
+            },
+            None => return,
+        };
+        let reason = match self.stop_reason(state, position) {
+            Some(reason) => reason,
+            None => return,
+        };
+
+        let frames = state
+            .frames()
+            .into_iter()
+            .map(|frame| {
+                let source_position = frame.module_id.as_ref().and_then(|module_id| {
+                    self.index
+                        .position(module_id, frame.function_index, frame.pc)
+                });
+                (frame, source_position)
+            })
+            .collect();
+        let snapshot = Snapshot {
+            frames,
+            globals: state.globals(),
+        };
+        if self
+            .events
+            .send(Event::Stopped { reason, snapshot })
+            .is_err()
+        {
+            self.mode = StepMode::Detached;
+            return;
+        }
+        self.mode = match self.resumes.recv() {
+            Ok(Resume::Continue) => StepMode::Continue,
+            Ok(Resume::StepIn) => StepMode::StepIn,
+            Ok(Resume::StepOver) => StepMode::StepOver(position),
+            Ok(Resume::StepOut) => StepMode::StepOut(position.depth),
+            Err(_) => StepMode::Detached,
+        };
+    }
+}
+
+/// Debugs the test `test_name` of `test_plan`, named by its function, optionally qualified by
+/// its module and address, as a DAP server speaking over `input` and `output`. Returns whether
+/// the test passed, or `None` if the session ended before it finished.
+pub fn debug_unit_test<R: Read + Send + 'static, W: Write>(
+    config: &UnitTestingConfig,
+    mut test_plan: TestPlan,
+    test_name: &str,
+    native_function_table: Option<NativeFunctionTable>,
+    genesis_state: Option<ChangeSet>,
+    cost_table: Option<CostTable>,
+    input: R,
+    output: W,
+) -> Result<Option<bool>> {
+    let matches: Vec<_> = test_plan
+        .module_tests
+        .iter()
+        .flat_map(|(module_id, module_test_plan)| {
+            module_test_plan.tests.keys().filter_map(move |name| {
+                let qualified_name = format!("{}::{}", format_module_id(module_id), name);
+                let module_name = format!("{}::{}", module_id.name(), name);
+                if test_name == name || test_name == module_name || test_name == qualified_name {
+                    Some((module_id.clone(), name.clone(), qualified_name))
+                } else {
+                    None
+                }
+            })
+        })
+        .collect();
+    let (module_id, function_name) = match matches.as_slice() {
+        [] => bail!("No test named {}", test_name),
+        [(module_id, function_name, _)] => (module_id.clone(), function_name.clone()),
+        _ => bail!(
+            "Several tests are named {}, qualify it with its module: {}",
+            test_name,
+            matches
+                .iter()
+                .map(|(_, _, qualified_name)| qualified_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    test_plan.module_tests.retain(|id, _| id == &module_id);
+    for module_test_plan in test_plan.module_tests.values_mut() {
+        module_test_plan
+            .tests
+            .retain(|name, _| name == &function_name);
+    }
+
+    let mut storage = setup_test_storage(
+        test_plan
+            .module_info
+            .values()
+            .map(|named_module| &named_module.module),
+    )?;
+    if let Some(genesis_state) = &genesis_state {
+        storage.apply(genesis_state.clone())?;
+    }
+    let index = Arc::new(SourceIndex::new(test_plan.clone()));
+    let test_runner = TestRunner::new(
+        config.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
+        1,
+        config.report_storage_on_error,
+        true,
+        test_plan,
+        native_function_table,
+        genesis_state,
+        cost_table,
+        None,
+        false,
+        config.fuzz_seed.unwrap_or_else(rand::random),
+        #[cfg(feature = "evm-backend")]
+        false,
+    )?;
+
+    let (events, event_receiver) = mpsc::channel();
+    let request_events = events.clone();
+    thread::spawn(move || read_requests(input, request_events));
+
+    let (resumes, resume_receiver) = mpsc::channel();
+    let mut adapter = DebugAdapter {
+        output,
+        seq: 0,
+        index,
+        shared: Arc::new(SharedState::default()),
+        annotator: MoveValueAnnotator::new(storage),
+        resumes,
+        snapshot: None,
+        variables: vec![],
+        stop_on_entry: false,
+        passed: None,
+    };
+    let mut pending = Some((test_runner, events, resume_receiver));
+    let result = loop {
+        let event = match event_receiver.recv() {
+            Ok(event) => event,
+            Err(_) => break Ok(()),
+        };
+        match event {
+            Event::Request(request) => {
+                let command = request["command"].as_str().unwrap_or_default().to_string();
+                match adapter.handle_request(&request) {
+                    Ok(body) => adapter.respond(&request, Ok(body))?,
+                    Err(err) => adapter.respond(&request, Err(err.to_string()))?,
+                }
+                match command.as_str() {
+                    "configurationDone" => {
+                        if let Some((test_runner, events, resume_receiver)) = pending.take() {
+                            adapter.start(test_runner, events, resume_receiver);
+                        }
+                    },
+                    "initialize" => adapter.send_event("initialized", json!({}))?,
+                    "disconnect" | "terminate" => break Ok(()),
+                    _ => (),
+                }
+            },
+            Event::Stopped { reason, snapshot } => {
+                adapter.snapshot = Some(snapshot);
+                adapter.variables.clear();
+                adapter.send_event(
+                    "stopped",
+                    json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+                )?;
+            },
+            Event::Finished { output, passed } => {
+                adapter.passed = Some(passed);
+                adapter.send_event("output", json!({ "category": "stdout", "output": output }))?;
+                adapter.send_event("exited", json!({ "exitCode": if passed { 0 } else { 1 } }))?;
+                adapter.send_event("terminated", json!({}))?;
+            },
+            Event::InputClosed => break Ok(()),
+        }
+    };
+    // Closing the channels lets a test that is still stopped run to completion, without the
+    // debug hook stopping it again
+    let passed = adapter.passed;
+    drop(adapter);
+    drop(event_receiver);
+    result.map(|()| passed)
+}
+
+/// Reads DAP messages, which are JSON with a `Content-Length` header
+fn read_requests<R: Read>(input: R, events: Sender<Event>) {
+    let mut input = std::io::BufReader::new(input);
+    loop {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            match input.read_line(&mut header) {
+                Ok(0) | Err(_) => {
+                    let _ = events.send(Event::InputClosed);
+                    return;
+                },
+                Ok(_) => (),
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("Content-Length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+        let content_length = match content_length {
+            Some(content_length) => content_length,
+            None => continue,
+        };
+        let mut content = vec![0; content_length];
+        if input.read_exact(&mut content).is_err() {
+            let _ = events.send(Event::InputClosed);
+            return;
+        }
+        if let Ok(request) = serde_json::from_slice(&content) {
+            if events.send(Event::Request(request)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+struct DebugAdapter<W> {
+    output: W,
+    seq: u64,
+    index: Arc<SourceIndex>,
+    shared: Arc<SharedState>,
+    annotator: MoveValueAnnotator<InMemoryStorage>,
+    resumes: Sender<Resume>,
+    snapshot: Option<Snapshot>,
+    // The children of structured values, by variables reference minus one. Only valid while
+    // the test is stopped.
+    variables: Vec<Vec<Json>>,
+    stop_on_entry: bool,
+    passed: Option<bool>,
+}
+
+impl<W: Write> DebugAdapter<W> {
+    fn send(&mut self, mut message: Json) -> Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let content = serde_json::to_vec(&message)?;
+        write!(self.output, "Content-Length: {}\r\n\r\n", content.len())?;
+        self.output.write_all(&content)?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    fn send_event(&mut self, event: &str, body: Json) -> Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn respond(&mut self, request: &Json, body: Result<Json, String>) -> Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"].clone(),
+            "command": request["command"].clone(),
+            "success": body.is_ok(),
+        });
+        match body {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.send(response)
+    }
+
+    fn start(
+        &mut self,
+        test_runner: TestRunner,
+        events: Sender<Event>,
+        resume_receiver: Receiver<Resume>,
+    ) {
+        let hook = AdapterHook {
+            index: self.index.clone(),
+            shared: self.shared.clone(),
+            events: events.clone(),
+            resumes: resume_receiver,
+            mode: if self.stop_on_entry {
+                StepMode::Entry
+            } else {
+                StepMode::Continue
+            },
+            last_position: None,
+            positions: HashMap::new(),
+        };
+        thread::spawn(move || {
+            set_debug_hook(Some(Box::new(hook)));
+            let writer = Mutex::new(Vec::new());
+            let passed = test_runner
+                .run(&writer)
+                .and_then(|test_results| test_results.summarize(&writer));
+            set_debug_hook(None);
+            let mut output = String::from_utf8_lossy(&writer.into_inner().unwrap()).to_string();
+            if let Err(err) = &passed {
+                output.push_str(&format!("Failed to run the test: {:#}\n", err));
+            }
+            let _ = events.send(Event::Finished {
+                output,
+                passed: passed.unwrap_or(false),
+            });
+        });
+    }
+
+    fn resume(&mut self, resume: Resume) -> Result<Json> {
+        if self.snapshot.take().is_none() {
+            bail!("The test is not stopped");
+        }
+        self.variables.clear();
+        self.resumes
+            .send(resume)
+            .map_err(|_| anyhow!("The test has finished"))?;
+        Ok(json!({ "allThreadsContinued": true }))
+    }
+
+    fn handle_request(&mut self, request: &Json) -> Result<Json> {
+        let arguments = &request["arguments"];
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsTerminateRequest": true,
+                "exceptionBreakpointFilters": [{
+                    "filter": "abort",
+                    "label": "Move aborts",
+                    "description": "Stop before an `abort` instruction is executed",
+                    "default": false,
+                }],
+            })),
+            "launch" | "attach" => {
+                self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(json!({}))
+            },
+            "setBreakpoints" => self.set_breakpoints(arguments),
+            "setExceptionBreakpoints" => {
+                let stop_on_abort = arguments["filters"].as_array().map_or(false, |filters| {
+                    filters.iter().any(|filter| filter == "abort")
+                });
+                self.shared
+                    .stop_on_abort
+                    .store(stop_on_abort, Ordering::SeqCst);
+                Ok(json!({}))
+            },
+            "configurationDone" | "disconnect" | "terminate" => Ok(json!({})),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "pause" => {
+                self.shared.pause.store(true, Ordering::SeqCst);
+                Ok(json!({}))
+            },
+            "continue" => self.resume(Resume::Continue),
+            "next" => self.resume(Resume::StepOver).map(|_| json!({})),
+            "stepIn" => self.resume(Resume::StepIn).map(|_| json!({})),
+            "stepOut" => self.resume(Resume::StepOut).map(|_| json!({})),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => self.scopes(arguments),
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                let variables = self
+                    .variables
+                    .get((reference as usize).wrapping_sub(1))
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Unknown variables reference {}", reference))?;
+                Ok(json!({ "variables": variables }))
+            },
+            command => bail!("Unsupported request {}", command),
+        }
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Json) -> Result<Json> {
+        let path = arguments["source"]["path"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Breakpoints can only be set in files"))?;
+        let path = PathBuf::from(path);
+        let mut breakpoints = self.shared.breakpoints.lock().unwrap();
+        // Breakpoints replace those previously set in the same file
+        if let Some(file_hash) = self.index.file(&path) {
+            breakpoints.retain(|(file, _)| *file != file_hash);
+        }
+        let lines = arguments["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let results: Vec<_> = lines
+            .into_iter()
+            .map(
+                |line| match self.index.breakpoint_line(&path, line as u32) {
+                    Some((file_hash, code_line)) => {
+                        breakpoints.insert((file_hash, code_line));
+                        json!({ "verified": true, "line": code_line })
+                    },
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "No code of the test's modules is on or after this line",
+                    }),
+                },
+            )
+            .collect();
+        Ok(json!({ "breakpoints": results }))
+    }
+
+    fn stack_trace(&self) -> Result<Json> {
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .ok_or_else(|| anyhow!("The test is not stopped"))?;
+        let frames: Vec<_> = snapshot
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(frame_id, (frame, source_position))| {
+                let name = match &frame.module_id {
+                    Some(module_id) => {
+                        format!("{}::{}", format_module_id(module_id), frame.function_name)
+                    },
+                    None => frame.function_name.clone(),
+                };
+                let mut stack_frame = json!({
+                    "id": frame_id,
+                    "name": name,
+                    "line": 0,
+                    "column": 0,
+                });
+                if let Some(source_position) = source_position {
+                    let source_file = &self.index.files[&source_position.file];
+                    stack_frame["source"] = json!({
+                        "name": source_file.path.file_name().map(|name| name.to_string_lossy()),
+                        "path": source_file.path,
+                    });
+                    stack_frame["line"] = json!(source_position.line);
+                    stack_frame["column"] = json!(source_position.column);
+                }
+                stack_frame
+            })
+            .collect();
+        Ok(json!({ "stackFrames": frames, "totalFrames": snapshot.frames.len() }))
+    }
+
+    fn scopes(&mut self, arguments: &Json) -> Result<Json> {
+        let frame_id = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .ok_or_else(|| anyhow!("The test is not stopped"))?;
+        let (frame, _) = snapshot
+            .frames
+            .get(frame_id)
+            .ok_or_else(|| anyhow!("Unknown frame {}", frame_id))?;
+
+        let locals: Vec<_> = frame
+            .locals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let name = self.index.local_name(frame, index)?;
+                Some((name, value.clone()))
+            })
+            .collect();
+        let globals: Vec<_> = snapshot
+            .globals
+            .iter()
+            .map(|(address, value)| {
+                (
+                    format!("{}@{}", value.type_tag, address.to_hex_literal()),
+                    Some(value.clone()),
+                )
+            })
+            .collect();
+
+        let locals = locals
+            .into_iter()
+            .map(|(name, value)| self.variable(name, value.as_ref()))
+            .collect();
+        let locals_reference = self.add_variables(locals);
+        let globals = globals
+            .into_iter()
+            .map(|(name, value)| self.variable(name, value.as_ref()))
+            .collect();
+        let globals_reference = self.add_variables(globals);
+        Ok(json!({
+            "scopes": [
+                { "name": "Locals", "variablesReference": locals_reference, "expensive": false },
+                { "name": "Globals", "variablesReference": globals_reference, "expensive": false },
+            ]
+        }))
+    }
+
+    fn add_variables(&mut self, variables: Vec<Json>) -> usize {
+        self.variables.push(variables);
+        self.variables.len()
+    }
+
+    fn variable(&mut self, name: String, value: Option<&DebugValue>) -> Json {
+        let value = match value {
+            Some(value) => value,
+            None => return json!({ "name": name, "value": "<no value>", "variablesReference": 0 }),
+        };
+        match &value.type_tag {
+            // Signers are serialized as their address, but can't be annotated
+            TypeTag::Signer => {
+                let value = match AccountAddress::from_bytes(&value.bytes) {
+                    Ok(address) => format!("signer({})", address.to_hex_literal()),
+                    Err(_) => "signer".to_string(),
+                };
+                json!({ "name": name, "value": value, "variablesReference": 0 })
+            },
+            type_tag => match self.annotator.view_value(type_tag, &value.bytes) {
+                Ok(annotated_value) => {
+                    let mut variable = self.annotated_variable(name, &annotated_value);
+                    variable["type"] = json!(type_tag.to_string());
+                    variable
+                },
+                Err(err) => json!({
+                    "name": name,
+                    "value": format!("<failed to render: {}>", err),
+                    "variablesReference": 0,
+                }),
+            },
+        }
+    }
+
+    fn annotated_variable(&mut self, name: String, value: &AnnotatedMoveValue) -> Json {
+        let (summary, children) = match value {
+            AnnotatedMoveValue::Vector(_, elements) => {
+                let children = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, element)| self.annotated_variable(index.to_string(), element))
+                    .collect();
+                (format!("vector[{} elements]", elements.len()), children)
+            },
+            AnnotatedMoveValue::Struct(move_struct) => {
+                let children = move_struct
+                    .value
+                    .iter()
+                    .map(|(field, field_value)| {
+                        self.annotated_variable(field.to_string(), field_value)
+                    })
+                    .collect();
+                (move_struct.ty_tag.to_string(), children)
+            },
+            value => (value.to_string(), vec![]),
+        };
+        let reference = if children.is_empty() {
+            0
+        } else {
+            self.add_variables(children)
+        };
+        json!({ "name": name, "value": summary, "variablesReference": reference })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::VecDeque, io::Cursor, time::Duration};
+
+    const SOURCE: &str = r#"module 0x42::m {
+    fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+
+    #[test]
+    fun test_add() {
+        let x = 1;
+        let y = add(x, 2);
+        assert!(y == 3, 0);
+    }
+}
+"#;
+
+    /// Feeds the requests sent by the test to the adapter
+    struct ChannelReader {
+        requests: Receiver<Vec<u8>>,
+        buffer: Cursor<Vec<u8>>,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.buffer.position() as usize == self.buffer.get_ref().len() {
+                match self.requests.recv() {
+                    Ok(request) => self.buffer = Cursor::new(request),
+                    Err(_) => return Ok(0),
+                }
+            }
+            self.buffer.read(buf)
+        }
+    }
+
+    /// Parses the messages written by the adapter, and passes them to the test
+    struct ChannelWriter {
+        messages: Sender<Json>,
+        buffer: Vec<u8>,
+    }
+
+    impl Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            while let Some(header_end) = self.buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                let header = String::from_utf8_lossy(&self.buffer[..header_end]).to_string();
+                let content_length: usize = header
+                    .trim_start_matches("Content-Length:")
+                    .trim()
+                    .parse()
+                    .unwrap();
+                let content_start = header_end + 4;
+                if self.buffer.len() < content_start + content_length {
+                    break;
+                }
+                let content: Vec<_> = self
+                    .buffer
+                    .drain(..content_start + content_length)
+                    .skip(content_start)
+                    .collect();
+                let _ = self
+                    .messages
+                    .send(serde_json::from_slice(&content).unwrap());
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The editor side of a debugging session
+    struct Client {
+        requests: Sender<Vec<u8>>,
+        messages: Receiver<Json>,
+        events: VecDeque<Json>,
+        seq: u64,
+    }
+
+    impl Client {
+        fn receive(&mut self) -> Json {
+            self.messages
+                .recv_timeout(Duration::from_secs(60))
+                .expect("the adapter should send a message")
+        }
+
+        fn request(&mut self, command: &str, arguments: Json) -> Json {
+            self.seq += 1;
+            let content = serde_json::to_vec(&json!({
+                "seq": self.seq,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            }))
+            .unwrap();
+            let mut request = format!("Content-Length: {}\r\n\r\n", content.len()).into_bytes();
+            request.extend(content);
+            self.requests.send(request).unwrap();
+            loop {
+                let message = self.receive();
+                if message["type"] == "response" && message["request_seq"] == self.seq {
+                    assert_eq!(message["success"], true, "{} failed: {}", command, message);
+                    return message["body"].clone();
+                }
+                self.events.push_back(message);
+            }
+        }
+
+        /// Waits for the event `event`, skipping any other event
+        fn event(&mut self, event: &str) -> Json {
+            loop {
+                let message = match self.events.pop_front() {
+                    Some(message) => message,
+                    None => self.receive(),
+                };
+                if message["type"] == "event" && message["event"] == event {
+                    return message["body"].clone();
+                }
+            }
+        }
+
+        fn stopped(&mut self, reason: &str) -> Vec<Json> {
+            assert_eq!(self.event("stopped")["reason"], reason);
+            let stack_trace = self.request("stackTrace", json!({ "threadId": THREAD_ID }));
+            stack_trace["stackFrames"].as_array().unwrap().clone()
+        }
+
+        fn locals(&mut self, frame_id: u64) -> Vec<(String, String)> {
+            let scopes = self.request("scopes", json!({ "frameId": frame_id }));
+            assert_eq!(scopes["scopes"][0]["name"], "Locals");
+            let reference = scopes["scopes"][0]["variablesReference"].clone();
+            let variables = self.request("variables", json!({ "variablesReference": reference }));
+            variables["variables"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|variable| {
+                    let value = variable["value"].as_str().unwrap().to_string();
+                    (variable["name"].as_str().unwrap().to_string(), value)
+                })
+                .collect()
+        }
+    }
+
+    fn line_of(text: &str) -> u64 {
+        SOURCE.lines().position(|line| line.contains(text)).unwrap() as u64 + 1
+    }
+
+    fn assert_frame(frame: &Json, name: &str, line: u64) {
+        assert_eq!(frame["name"], format!("0x42::m::{}", name));
+        assert_eq!(frame["line"], line);
+    }
+
+    #[test]
+    fn test_debug_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("m.move");
+        std::fs::write(&path, SOURCE).unwrap();
+        let config = UnitTestingConfig {
+            source_files: vec![path.to_string_lossy().to_string()],
+            dep_files: move_stdlib::move_stdlib_files(),
+            ..UnitTestingConfig::default_with_bound(None)
+        }
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+        let test_plan = config.build_test_plan().unwrap();
+
+        let (requests, request_receiver) = mpsc::channel();
+        let (message_sender, messages) = mpsc::channel();
+        let input = ChannelReader {
+            requests: request_receiver,
+            buffer: Cursor::new(vec![]),
+        };
+        let output = ChannelWriter {
+            messages: message_sender,
+            buffer: vec![],
+        };
+        let session = thread::spawn(move || {
+            debug_unit_test(
+                &config, test_plan, "test_add", None, None, None, input, output,
+            )
+        });
+        let mut client = Client {
+            requests,
+            messages,
+            events: VecDeque::new(),
+            seq: 0,
+        };
+
+        client.request("initialize", json!({ "adapterID": "move" }));
+        client.event("initialized");
+        client.request("launch", json!({ "stopOnEntry": false }));
+        let call_line = line_of("let y = add(x, 2);");
+        let breakpoints = client.request(
+            "setBreakpoints",
+            json!({
+                "source": { "path": path },
+                "breakpoints": [{ "line": call_line }],
+            }),
+        );
+        assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+        assert_eq!(breakpoints["breakpoints"][0]["line"], call_line);
+        client.request("configurationDone", json!({}));
+
+        // The test stops at the breakpoint, once `x` is set
+        let frames = client.stopped("breakpoint");
+        assert_eq!(frames.len(), 1);
+        assert_frame(&frames[0], "test_add", call_line);
+        assert_eq!(
+            frames[0]["source"]["path"],
+            json!(path.canonicalize().unwrap())
+        );
+        let locals = client.locals(0);
+        assert!(locals.contains(&("x".to_string(), "1".to_string())));
+        assert!(locals.contains(&("y".to_string(), "<no value>".to_string())));
+
+        // Stepping in enters `add`, with its caller below it
+        client.request("stepIn", json!({ "threadId": THREAD_ID }));
+        let frames = client.stopped("step");
+        assert_eq!(frames.len(), 2);
+        assert_frame(&frames[0], "add", line_of("a + b"));
+        assert_frame(&frames[1], "test_add", call_line);
+        assert_eq!(
+            client.locals(0),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+            ]
+        );
+
+        // Stepping out returns to the call, where the result is stored
+        client.request("stepOut", json!({ "threadId": THREAD_ID }));
+        let frames = client.stopped("step");
+        assert_eq!(frames.len(), 1);
+        assert_frame(&frames[0], "test_add", call_line);
+
+        // Stepping over moves to the next line
+        client.request("next", json!({ "threadId": THREAD_ID }));
+        let frames = client.stopped("step");
+        assert_eq!(frames.len(), 1);
+        assert_frame(&frames[0], "test_add", line_of("assert!(y == 3, 0);"));
+        assert!(client
+            .locals(0)
+            .contains(&("y".to_string(), "3".to_string())));
+
+        client.request("continue", json!({ "threadId": THREAD_ID }));
+        assert_eq!(client.event("exited")["exitCode"], 0);
+        client.event("terminated");
+        client.request("disconnect", json!({}));
+        assert_eq!(session.join().unwrap().unwrap(), Some(true));
+    }
+}
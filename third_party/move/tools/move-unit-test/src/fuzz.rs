@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the arguments of `#[fuzz]` tests.
+//!
+//! Integers are biased towards edge cases such as zero, the maximum value and powers of two,
+//! as those are where arithmetic tends to go wrong. A failing input is shrunk by repeatedly
+//! replacing one of its values with a simpler one for as long as the test still fails.
+
+use move_compiler::unit_test::FuzzType;
+use move_core_types::{
+    account_address::AccountAddress,
+    u256::U256,
+    value::{MoveStruct, MoveValue},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The maximum length of generated vectors
+const MAX_VECTOR_LENGTH: usize = 32;
+
+/// The maximum number of times a failing input is executed while shrinking it
+const MAX_SHRINK_EXECUTIONS: usize = 2_000;
+
+/// The maximum number of elements removed one at a time when shrinking a vector
+const MAX_VECTOR_REMOVALS: usize = 16;
+
+/// Returns the seed of the arguments of a test, so that they don't depend on the order in which
+/// tests are run.
+pub fn test_seed(seed: u64, module_name: &str, function_name: &str) -> u64 {
+    // FNV-1a, which unlike the standard library's hasher is stable across releases
+    let mut hash = 0xCBF2_9CE4_8422_2325u64 ^ seed;
+    for byte in module_name
+        .bytes()
+        .chain(std::iter::once(b':'))
+        .chain(function_name.bytes())
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01B3);
+    }
+    hash
+}
+
+pub fn new_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+pub fn generate(rng: &mut StdRng, ty: &FuzzType) -> MoveValue {
+    match ty {
+        FuzzType::Bool => MoveValue::Bool(rng.gen()),
+        FuzzType::Address => {
+            if rng.gen_range(0, 4) == 0 {
+                // Addresses of the framework and named addresses in tests are mostly small
+                let mut bytes = [0u8; AccountAddress::LENGTH];
+                bytes[AccountAddress::LENGTH - 1] = rng.gen_range(0, 16);
+                MoveValue::Address(AccountAddress::new(bytes))
+            } else {
+                MoveValue::Address(AccountAddress::new(rng.gen()))
+            }
+        },
+        FuzzType::Vector(element_type) => {
+            let length = if rng.gen_range(0, 8) == 0 {
+                0
+            } else {
+                rng.gen_range(0, MAX_VECTOR_LENGTH + 1)
+            };
+            MoveValue::Vector((0..length).map(|_| generate(rng, element_type)).collect())
+        },
+        FuzzType::Struct(field_types) => MoveValue::Struct(MoveStruct::Runtime(
+            field_types
+                .iter()
+                .map(|field_type| generate(rng, field_type))
+                .collect(),
+        )),
+        _ => {
+            let bits = integer_bits(ty);
+            to_integer(ty, generate_integer(rng, bits))
+        },
+    }
+}
+
+fn generate_integer(rng: &mut StdRng, bits: u32) -> U256 {
+    let max = max_integer(bits);
+    match rng.gen_range(0, 10) {
+        0 => U256::zero(),
+        1 => max,
+        2 => U256::one().checked_shl(rng.gen_range(0, bits)).unwrap(),
+        3 => max_integer(rng.gen_range(1, bits + 1)),
+        4..=6 => std::cmp::min(U256::from(rng.gen_range(0u64, 1_000)), max),
+        // A uniformly distributed number of bits, as uniform values would almost always be huge
+        _ => {
+            let width = rng.gen_range(1, bits + 1);
+            let bytes: [u8; 32] = rng.gen();
+            U256::from_le_bytes(&bytes)
+                .checked_shr(256 - width)
+                .unwrap_or_else(U256::zero)
+        },
+    }
+}
+
+fn integer_bits(ty: &FuzzType) -> u32 {
+    match ty {
+        FuzzType::U8 => 8,
+        FuzzType::U16 => 16,
+        FuzzType::U32 => 32,
+        FuzzType::U64 => 64,
+        FuzzType::U128 => 128,
+        FuzzType::U256 => 256,
+        _ => unreachable!("not an integer type: {:?}", ty),
+    }
+}
+
+fn max_integer(bits: u32) -> U256 {
+    U256::max_value()
+        .checked_shr(256 - bits)
+        .unwrap_or_else(U256::zero)
+}
+
+fn to_integer(ty: &FuzzType, value: U256) -> MoveValue {
+    match ty {
+        FuzzType::U8 => MoveValue::U8(value.unchecked_as_u8()),
+        FuzzType::U16 => MoveValue::U16(value.unchecked_as_u16()),
+        FuzzType::U32 => MoveValue::U32(value.unchecked_as_u32()),
+        FuzzType::U64 => MoveValue::U64(value.unchecked_as_u64()),
+        FuzzType::U128 => MoveValue::U128(value.unchecked_as_u128()),
+        FuzzType::U256 => MoveValue::U256(value),
+        _ => unreachable!("not an integer type: {:?}", ty),
+    }
+}
+
+fn from_integer(value: &MoveValue) -> Option<U256> {
+    Some(match value {
+        MoveValue::U8(u) => U256::from(*u),
+        MoveValue::U16(u) => U256::from(*u),
+        MoveValue::U32(u) => U256::from(*u),
+        MoveValue::U64(u) => U256::from(*u),
+        MoveValue::U128(u) => U256::from(*u),
+        MoveValue::U256(u) => *u,
+        _ => return None,
+    })
+}
+
+/// Values simpler than `value`, simplest first
+fn simplifications(ty: &FuzzType, value: &MoveValue) -> Vec<MoveValue> {
+    match (ty, value) {
+        (FuzzType::Bool, MoveValue::Bool(true)) => vec![MoveValue::Bool(false)],
+        (FuzzType::Address, MoveValue::Address(address)) if *address != AccountAddress::ZERO => {
+            vec![MoveValue::Address(AccountAddress::ZERO)]
+        },
+        (FuzzType::Vector(element_type), MoveValue::Vector(elements)) => {
+            let mut candidates = vec![];
+            if !elements.is_empty() {
+                candidates.push(MoveValue::Vector(vec![]));
+            }
+            if elements.len() > 1 {
+                let half = elements.len() / 2;
+                candidates.push(MoveValue::Vector(elements[..half].to_vec()));
+                candidates.push(MoveValue::Vector(elements[half..].to_vec()));
+            }
+            for index in 0..std::cmp::min(elements.len(), MAX_VECTOR_REMOVALS) {
+                let mut shorter = elements.clone();
+                shorter.remove(index);
+                candidates.push(MoveValue::Vector(shorter));
+            }
+            for (index, element) in elements.iter().enumerate() {
+                for simpler in simplifications(element_type, element) {
+                    let mut simpler_elements = elements.clone();
+                    simpler_elements[index] = simpler;
+                    candidates.push(MoveValue::Vector(simpler_elements));
+                }
+            }
+            candidates
+        },
+        (FuzzType::Struct(field_types), MoveValue::Struct(MoveStruct::Runtime(fields))) => {
+            let mut candidates = vec![];
+            for (index, (field_type, field)) in field_types.iter().zip(fields).enumerate() {
+                for simpler in simplifications(field_type, field) {
+                    let mut simpler_fields = fields.clone();
+                    simpler_fields[index] = simpler;
+                    candidates.push(MoveValue::Struct(MoveStruct::Runtime(simpler_fields)));
+                }
+            }
+            candidates
+        },
+        _ => match from_integer(value) {
+            Some(u) if u != U256::zero() => {
+                // Zero, then values closing in on `u`: u - u/2, u - u/4, ..., u - 1
+                let mut candidates = vec![to_integer(ty, U256::zero())];
+                let mut delta = u.checked_shr(1).unwrap();
+                while delta != U256::zero() {
+                    candidates.push(to_integer(ty, u.wrapping_sub(delta)));
+                    delta = delta.checked_shr(1).unwrap();
+                }
+                candidates
+            },
+            _ => vec![],
+        },
+    }
+}
+
+/// Shrinks the failing `values` of the given types, returning the simplest failing values found
+/// and how many times they were shrunk.
+pub fn shrink(
+    types: &[&FuzzType],
+    mut values: Vec<MoveValue>,
+    mut fails: impl FnMut(&[MoveValue]) -> bool,
+) -> (Vec<MoveValue>, usize) {
+    let mut shrinks = 0;
+    let mut executions = 0;
+    'shrink: loop {
+        for index in 0..values.len() {
+            for candidate in simplifications(types[index], &values[index]) {
+                if executions == MAX_SHRINK_EXECUTIONS {
+                    break 'shrink;
+                }
+                executions += 1;
+                let mut attempt = values.clone();
+                attempt[index] = candidate;
+                if fails(&attempt) {
+                    values = attempt;
+                    shrinks += 1;
+                    continue 'shrink;
+                }
+            }
+        }
+        break;
+    }
+    (values, shrinks)
+}
@@ -0,0 +1,30 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module allows tests to be executed with a gas meter living outside of the Move repo,
+//! e.g. the gas meter of a production environment, instead of a cost table. The gas used by a
+//! test is then as it would be on chain. The hook is passed to the test runner in place of the
+//! cost table.
+
+use move_binary_format::errors::VMResult;
+use move_core_types::{identifier::IdentStr, language_storage::ModuleId};
+use move_vm_runtime::{
+    module_traversal::TraversalContext,
+    session::{SerializedReturnValues, Session},
+};
+
+/// Executes a test function in the session with a custom gas meter, with the gas limit of the
+/// unit testing config, and returns the result together with the gas used. If no hook is passed
+/// to the test runner, tests are metered with its cost table.
+pub type GasMeterHook = Box<
+    dyn Fn(
+            &mut Session,
+            &ModuleId,
+            &IdentStr,
+            &[Vec<u8>],
+            u64,
+            &mut TraversalContext,
+        ) -> (VMResult<SerializedReturnValues>, u64)
+        + Send
+        + Sync,
+>;
@@ -0,0 +1,243 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A gas snapshot records the gas used by each test in a file, which is meant to be checked in.
+//! Later runs compare the gas used with the snapshot, so that changes to the gas used by a
+//! test don't go unnoticed. The file has a line per test, as in `0x1::m::test (gas: 42)`.
+
+use colored::*;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind, Result, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    entries: BTreeMap<String, u64>,
+}
+
+/// A test whose gas used differs from the snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasChange {
+    pub test_name: String,
+    /// The gas used according to the snapshot, or None if the test is not in it.
+    pub old: Option<u64>,
+    pub new: u64,
+    /// Whether the change is beyond the tolerance.
+    pub exceeds_tolerance: bool,
+}
+
+impl GasSnapshot {
+    pub fn new(entries: BTreeMap<String, u64>) -> Self {
+        Self { entries }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let entry = line.strip_suffix(')').and_then(|line| {
+                let (name, gas) = line.rsplit_once(" (gas: ")?;
+                Some((name.to_string(), gas.parse::<u64>().ok()?))
+            });
+            match entry {
+                Some((name, gas)) => {
+                    entries.insert(name, gas);
+                },
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "invalid gas snapshot entry on line {}, expected `<test> (gas: <gas>)`",
+                            idx + 1
+                        ),
+                    ))
+                },
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Returns the tests whose gas used differs from this snapshot. `tolerance` is the change
+    /// of the gas used, in percent, up to which it is still considered to match the snapshot.
+    /// Tests which are not in the snapshot always exceed the tolerance.
+    pub fn compare(&self, gas_used: &BTreeMap<String, u64>, tolerance: f64) -> Vec<GasChange> {
+        gas_used
+            .iter()
+            .filter_map(|(test_name, &new)| {
+                let old = self.entries.get(test_name).copied();
+                let exceeds_tolerance = match old {
+                    Some(old) if old == new => return None,
+                    Some(0) | None => true,
+                    Some(old) => percent_change(old, new).abs() > tolerance,
+                };
+                Some(GasChange {
+                    test_name: test_name.clone(),
+                    old,
+                    new,
+                    exceeds_tolerance,
+                })
+            })
+            .collect()
+    }
+
+    /// Updates the snapshot with the gas used in a run. Tests which were not run are retained.
+    pub fn update(&mut self, gas_used: &BTreeMap<String, u64>) {
+        self.entries
+            .extend(gas_used.iter().map(|(name, gas)| (name.clone(), *gas)));
+    }
+}
+
+impl std::fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (test_name, gas) in &self.entries {
+            writeln!(f, "{} (gas: {})", test_name, gas)?;
+        }
+        Ok(())
+    }
+}
+
+fn percent_change(old: u64, new: u64) -> f64 {
+    (new as f64 - old as f64) / old as f64 * 100.0
+}
+
+/// Compares the gas used by the tests with the gas snapshot at `path` and writes the changes,
+/// returning whether all of them are within `tolerance`. If `update` is set, or there is no
+/// snapshot yet, the snapshot is written instead.
+pub fn report_gas_snapshot<W: Write>(
+    path: &Path,
+    update: bool,
+    tolerance: f64,
+    gas_used: &BTreeMap<String, u64>,
+    writer: &Mutex<W>,
+) -> Result<bool> {
+    if update || !path.exists() {
+        let mut snapshot = if path.exists() {
+            GasSnapshot::read(path)?
+        } else {
+            GasSnapshot::default()
+        };
+        snapshot.update(gas_used);
+        snapshot.write(path)?;
+        writeln!(
+            writer.lock().unwrap(),
+            "\nWrote the gas used by {} tests to the gas snapshot {}",
+            gas_used.len(),
+            path.display()
+        )?;
+        return Ok(true);
+    }
+
+    let changes = GasSnapshot::read(path)?.compare(gas_used, tolerance);
+    if changes.is_empty() {
+        return Ok(true);
+    }
+    writeln!(
+        writer.lock().unwrap(),
+        "\nGas used differs from the gas snapshot {}:\n",
+        path.display()
+    )?;
+    for change in &changes {
+        let description = match change.old {
+            Some(old) if old > 0 => format!(
+                "{} -> {} ({:+.2}%)",
+                old,
+                change.new,
+                percent_change(old, change.new)
+            ),
+            Some(old) => format!("{} -> {}", old, change.new),
+            None => format!("{} (not in snapshot)", change.new),
+        };
+        let description = if !change.exceeds_tolerance {
+            description.normal()
+        } else if change.old.map_or(false, |old| change.new < old) {
+            description.bright_green()
+        } else {
+            description.bright_red()
+        };
+        writeln!(
+            writer.lock().unwrap(),
+            "  {}: {}",
+            change.test_name,
+            description
+        )?;
+    }
+    let ok = changes.iter().all(|change| !change.exceeds_tolerance);
+    if !ok {
+        writeln!(
+            writer.lock().unwrap(),
+            "\nThe gas used changed beyond the tolerance of {}%. Update the gas snapshot to \
+             accept the changes.",
+            tolerance
+        )?;
+    }
+    Ok(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas_used(entries: &[(&str, u64)]) -> BTreeMap<String, u64> {
+        entries
+            .iter()
+            .map(|(name, gas)| (name.to_string(), *gas))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_and_print() {
+        let s = "0x1::m::a (gas: 10)\n0x1::m::b (gas: 200)\n";
+        let snapshot = GasSnapshot::parse(s).unwrap();
+        assert_eq!(
+            snapshot,
+            GasSnapshot::new(gas_used(&[("0x1::m::a", 10), ("0x1::m::b", 200)]))
+        );
+        assert_eq!(snapshot.to_string(), s);
+        assert!(GasSnapshot::parse("0x1::m::a 10").is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        let snapshot = GasSnapshot::new(gas_used(&[
+            ("0x1::m::same", 100),
+            ("0x1::m::within", 100),
+            ("0x1::m::beyond", 100),
+        ]));
+        let changes = snapshot.compare(
+            &gas_used(&[
+                ("0x1::m::same", 100),
+                ("0x1::m::within", 105),
+                ("0x1::m::beyond", 80),
+                ("0x1::m::new", 1),
+            ]),
+            5.0,
+        );
+        let exceeds = changes
+            .iter()
+            .map(|change| (change.test_name.as_str(), change.exceeds_tolerance))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            exceeds,
+            vec![
+                ("0x1::m::beyond", true),
+                ("0x1::m::new", true),
+                ("0x1::m::within", false),
+            ]
+        );
+    }
+}
@@ -3,11 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod cargo_runner;
+#[cfg(feature = "debugging")]
+pub mod debug_adapter;
 pub mod extensions;
+pub mod fuzz;
+pub mod gas_meter;
+pub mod gas_snapshot;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{gas_meter::GasMeterHook, test_runner::TestRunner};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -22,8 +27,10 @@ use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::BTreeMap,
-    io::{Result, Write},
+    fs::File,
+    io::{BufWriter, Result, Write},
     marker::Send,
+    path::PathBuf,
     sync::Mutex,
 };
 
@@ -107,6 +114,37 @@ pub struct UnitTestingConfig {
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
 
+    /// Seed for the generated arguments of `#[fuzz]` tests. A random seed is used if not given,
+    /// and the seed is reported with any failure so that it can be reproduced.
+    #[clap(name = "fuzz_seed", long = "fuzz_seed")]
+    pub fuzz_seed: Option<u64>,
+
+    /// Write the test results as a JUnit XML report to this file
+    #[clap(name = "junit_report", long = "junit_report")]
+    pub junit_report: Option<PathBuf>,
+
+    /// Compare the gas used by each test with the gas snapshot in this file, and fail if it
+    /// changed beyond the tolerance. The snapshot is written if the file does not exist yet.
+    #[clap(name = "gas_snapshot", long = "gas_snapshot")]
+    pub gas_snapshot: Option<PathBuf>,
+
+    /// Write the gas used by each test to the gas snapshot, instead of comparing with it
+    #[clap(
+        name = "update_gas_snapshot",
+        long = "update_gas_snapshot",
+        requires = "gas_snapshot"
+    )]
+    pub update_gas_snapshot: bool,
+
+    /// The change of the gas used by a test, in percent, up to which it still matches the gas
+    /// snapshot
+    #[clap(
+        name = "gas_snapshot_tolerance",
+        long = "gas_snapshot_tolerance",
+        default_value_t = 0.0
+    )]
+    pub gas_snapshot_tolerance: f64,
+
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
     #[cfg(feature = "evm-backend")]
@@ -137,6 +175,11 @@ impl UnitTestingConfig {
             dep_files: vec![],
             check_stackless_vm: false,
             verbose: false,
+            fuzz_seed: None,
+            junit_report: None,
+            gas_snapshot: None,
+            update_gas_snapshot: false,
+            gas_snapshot_tolerance: 0.0,
             list: false,
             named_address_values: vec![],
 
@@ -217,6 +260,7 @@ impl UnitTestingConfig {
         native_function_table: Option<NativeFunctionTable>,
         genesis_state: Option<ChangeSet>,
         cost_table: Option<CostTable>,
+        gas_meter_hook: Option<GasMeterHook>,
         writer: W,
     ) -> Result<(W, bool)> {
         let shared_writer = Mutex::new(writer);
@@ -245,7 +289,9 @@ impl UnitTestingConfig {
             native_function_table,
             genesis_state,
             cost_table,
+            gas_meter_hook,
             self.verbose,
+            self.fuzz_seed.unwrap_or_else(rand::random),
             #[cfg(feature = "evm-backend")]
             self.evm,
         )
@@ -264,7 +310,24 @@ impl UnitTestingConfig {
             test_results.report_goldens(&shared_writer)?;
         }
 
-        let ok = test_results.summarize(&shared_writer)?;
+        if let Some(junit_report) = &self.junit_report {
+            let mut junit_writer = BufWriter::new(File::create(junit_report)?);
+            test_results.report_junit(&mut junit_writer)?;
+            junit_writer.flush()?;
+        }
+
+        let gas_snapshot_ok = match &self.gas_snapshot {
+            Some(gas_snapshot) => gas_snapshot::report_gas_snapshot(
+                gas_snapshot,
+                self.update_gas_snapshot,
+                self.gas_snapshot_tolerance,
+                &test_results.gas_used(),
+                &shared_writer,
+            )?,
+            None => true,
+        };
+
+        let ok = test_results.summarize(&shared_writer)? && gas_snapshot_ok;
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
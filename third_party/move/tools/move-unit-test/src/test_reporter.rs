@@ -9,7 +9,7 @@ use move_binary_format::{
     access::ModuleAccess,
     errors::{ExecutionState, Location, VMError, VMResult},
 };
-use move_command_line_common::{env::read_bool_env_var, files::FileHash};
+use move_command_line_common::{env::read_bool_env_var, files::FileHash, xml::xml_escape};
 pub use move_compiler::unit_test::ExpectedMoveError as MoveError;
 use move_compiler::{
     diagnostics::{self, Diagnostic, Diagnostics},
@@ -19,6 +19,7 @@ use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status:
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
 use once_cell::sync::Lazy;
+use regex::Regex;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     io::{Result, Write},
@@ -59,6 +60,8 @@ pub struct TestFailure {
     pub vm_error: Option<VMError>,
     pub failure_reason: FailureReason,
     pub storage_state: Option<String>,
+    // the generated arguments a #[fuzz] test failed with, and how to reproduce them
+    pub fuzz_input: Option<String>,
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -156,9 +159,15 @@ impl TestFailure {
             vm_error,
             failure_reason,
             storage_state,
+            fuzz_input: None,
         }
     }
 
+    pub fn with_fuzz_input(mut self, fuzz_input: Option<String>) -> Self {
+        self.fuzz_input = fuzz_input;
+        self
+    }
+
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
         let error_string = match &self.failure_reason {
             FailureReason::NoError(message) => message.to_string(),
@@ -235,6 +244,11 @@ impl TestFailure {
             },
         };
 
+        let error_string = match &self.fuzz_input {
+            None => error_string,
+            Some(fuzz_input) => format!("{}\n{}", error_string, fuzz_input),
+        };
+
         match &self.storage_state {
             None => error_string,
             Some(storage_state) => {
@@ -557,6 +571,105 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Returns the gas used by each passed test, by its fully qualified name.
+    pub fn gas_used(&self) -> BTreeMap<String, u64> {
+        self.final_statistics
+            .passed
+            .iter()
+            .flat_map(|(module_id, test_results)| {
+                test_results.iter().map(move |test_result| {
+                    (
+                        format!(
+                            "{}::{}",
+                            format_module_id(module_id),
+                            test_result.function_ident
+                        ),
+                        test_result.instructions_executed,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the results as a JUnit XML report, with a test suite per module. The gas used by
+    /// each test is reported as a property of it.
+    pub fn report_junit<W: Write>(&self, writer: &mut W) -> Result<()> {
+        static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+
+        let mut suites: BTreeMap<&ModuleId, Vec<(&TestRunInfo, Option<String>)>> = BTreeMap::new();
+        for (module_id, test_results) in &self.final_statistics.passed {
+            let suite = suites.entry(module_id).or_default();
+            suite.extend(test_results.iter().map(|test_result| (test_result, None)));
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            let suite = suites.entry(module_id).or_default();
+            suite.extend(test_failures.iter().map(|test_failure| {
+                let error = test_failure.render_error(&self.test_plan);
+                (
+                    &test_failure.test_run_info,
+                    Some(ANSI_ESCAPE.replace_all(&error, "").to_string()),
+                )
+            }));
+        }
+
+        let seconds = |tests: &[(&TestRunInfo, Option<String>)]| {
+            tests
+                .iter()
+                .map(|(test_run_info, _)| test_run_info.elapsed_time.as_secs_f64())
+                .sum::<f64>()
+        };
+        let failures = |tests: &[(&TestRunInfo, Option<String>)]| {
+            tests.iter().filter(|(_, error)| error.is_some()).count()
+        };
+        let all_tests: Vec<_> = suites.values().flatten().cloned().collect();
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<testsuites name="Move unit tests" tests="{}" failures="{}" time="{:.3}">"#,
+            all_tests.len(),
+            failures(&all_tests),
+            seconds(&all_tests),
+        )?;
+        for (module_id, mut tests) in suites {
+            tests.sort_by(|(a, _), (b, _)| a.function_ident.cmp(&b.function_ident));
+            let suite_name = xml_escape(&format_module_id(module_id));
+            writeln!(
+                writer,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{:.3}">"#,
+                suite_name,
+                tests.len(),
+                failures(&tests),
+                seconds(&tests),
+            )?;
+            for (test_run_info, error) in tests {
+                writeln!(
+                    writer,
+                    r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+                    xml_escape(&test_run_info.function_ident),
+                    suite_name,
+                    test_run_info.elapsed_time.as_secs_f64(),
+                )?;
+                writeln!(
+                    writer,
+                    r#"      <properties><property name="gas_used" value="{}"/></properties>"#,
+                    test_run_info.instructions_executed,
+                )?;
+                if let Some(error) = error {
+                    writeln!(
+                        writer,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        xml_escape(error.lines().next().unwrap_or_default().trim()),
+                        xml_escape(&error),
+                    )?;
+                }
+                writeln!(writer, "    </testcase>")?;
+            }
+            writeln!(writer, "  </testsuite>")?;
+        }
+        writeln!(writer, "</testsuites>")
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -610,3 +723,58 @@ impl TestResults {
         Ok(num_failed_tests == 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+    #[test]
+    fn test_report_junit() {
+        let module_test_plan = ModuleTestPlan {
+            module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap()),
+            tests: BTreeMap::new(),
+        };
+        let mut statistics = TestStatistics::new();
+        statistics.test_success(
+            TestRunInfo::new("test_ok".to_string(), Duration::from_millis(1500), 42),
+            &module_test_plan,
+        );
+        statistics.test_failure(
+            TestFailure::new(
+                FailureReason::property("expected <1> & got 'two'\nat line 2".to_string()),
+                TestRunInfo::new("test_fails".to_string(), Duration::from_millis(250), 7),
+                None,
+                None,
+            ),
+            &module_test_plan,
+        );
+        let test_plan = TestPlan {
+            files: HashMap::new(),
+            module_tests: BTreeMap::new(),
+            module_info: BTreeMap::new(),
+        };
+
+        let mut report = vec![];
+        TestResults::new(statistics, test_plan)
+            .report_junit(&mut report)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(report).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="Move unit tests" tests="2" failures="1" time="1.750">
+  <testsuite name="0x1::m" tests="2" failures="1" errors="0" skipped="0" time="1.750">
+    <testcase name="test_fails" classname="0x1::m" time="0.250">
+      <properties><property name="gas_used" value="7"/></properties>
+      <failure message="expected &lt;1&gt; &amp; got &apos;two&apos;">expected &lt;1&gt; &amp; got &apos;two&apos;
+at line 2</failure>
+    </testcase>
+    <testcase name="test_ok" classname="0x1::m" time="1.500">
+      <properties><property name="gas_used" value="42"/></properties>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    extensions, format_module_id,
+    extensions, format_module_id, fuzz,
+    gas_meter::GasMeterHook,
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
@@ -12,12 +13,14 @@ use anyhow::Result;
 use colored::*;
 use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_bytecode_utils::Modules;
-use move_compiler::unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan};
+use move_compiler::unit_test::{
+    ExpectedFailure, FuzzConfig, FuzzParameterKind, ModuleTestPlan, TestCase, TestPlan,
+};
 use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_resource_viewer::MoveValueAnnotator;
@@ -50,11 +53,14 @@ pub struct SharedTestingConfig {
     report_stacktrace_on_abort: bool,
     execution_bound: u64,
     cost_table: CostTable,
+    /// Meters the tests instead of the cost table, if set
+    gas_meter_hook: Option<GasMeterHook>,
     native_function_table: NativeFunctionTable,
     starting_storage_state: InMemoryStorage,
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    fuzz_seed: u64,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
@@ -77,7 +83,7 @@ fn unit_cost_table() -> CostTable {
 }
 
 /// Setup storage state with the set of modules that will be needed for all tests
-fn setup_test_storage<'a>(
+pub(crate) fn setup_test_storage<'a>(
     modules: impl Iterator<Item = &'a CompiledModule>,
 ) -> Result<InMemoryStorage> {
     let mut storage = InMemoryStorage::new();
@@ -95,6 +101,30 @@ fn setup_test_storage<'a>(
     Ok(storage)
 }
 
+/// Whether the result of executing a test is what the test expects
+fn passes(
+    expected_failure: &Option<ExpectedFailure>,
+    exec_result: &VMResult<Vec<Vec<u8>>>,
+) -> bool {
+    match (exec_result, expected_failure) {
+        (Ok(_), expected_failure) => expected_failure.is_none(),
+        (Err(_), None) => false,
+        (Err(_), Some(ExpectedFailure::Expected)) => true,
+        (Err(err), Some(ExpectedFailure::ExpectedWithError(expected_err))) => {
+            expected_err
+                == &MoveError(
+                    err.major_status(),
+                    err.sub_status(),
+                    err.location().clone(),
+                    err.message().cloned(),
+                )
+        },
+        (Err(err), Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))) => {
+            err.major_status() == StatusCode::ABORTED && err.sub_status() == Some(*code)
+        },
+    }
+}
+
 /// Print the updates to storage represented by `cs` in the context of the starting storage state
 /// `storage`.
 fn print_resources_and_extensions(
@@ -135,7 +165,9 @@ impl TestRunner {
         native_function_table: Option<NativeFunctionTable>,
         genesis_state: Option<ChangeSet>,
         cost_table: Option<CostTable>,
+        gas_meter_hook: Option<GasMeterHook>,
         record_writeset: bool,
+        fuzz_seed: u64,
         #[cfg(feature = "evm-backend")] evm: bool,
     ) -> Result<Self> {
         let source_files = tests
@@ -167,8 +199,10 @@ impl TestRunner {
                 //
                 // From the API standpoint, we should let the client specify the cost table.
                 cost_table: cost_table.unwrap_or_else(unit_cost_table),
+                gas_meter_hook,
                 source_files,
                 record_writeset,
+                fuzz_seed,
                 #[cfg(feature = "evm-backend")]
                 evm,
             },
@@ -274,19 +308,43 @@ impl SharedTestingConfig {
         let extensions = extensions::new_extensions();
         let mut session =
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);
-        let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
 
         let now = Instant::now();
         let storage = TraversalStorage::new();
-        let serialized_return_values_result = session.execute_function_bypass_visibility(
-            &test_plan.module_id,
-            IdentStr::new(function_name).unwrap(),
-            vec![], // no ty args, at least for now
-            serialize_values(test_info.arguments.iter()),
-            &mut gas_meter,
-            &mut TraversalContext::new(&storage),
-        );
+        let module_id = &test_plan.module_id;
+        let function_name_ident = IdentStr::new(function_name).unwrap();
+        let args = serialize_values(test_info.arguments.iter());
+        let mut traversal_context = TraversalContext::new(&storage);
+        let (serialized_return_values_result, gas_used) = match &self.gas_meter_hook {
+            Some(hook) => hook(
+                &mut session,
+                module_id,
+                function_name_ident,
+                &args,
+                self.execution_bound,
+                &mut traversal_context,
+            ),
+            None => {
+                let mut gas_meter =
+                    GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
+                let result = session.execute_function_bypass_visibility(
+                    module_id,
+                    function_name_ident,
+                    vec![], // no ty args, at least for now
+                    args,
+                    &mut gas_meter,
+                    &mut traversal_context,
+                );
+                // TODO(Gas): This doesn't look quite right...
+                //            We're not computing the number of instructions executed even with a unit gas schedule.
+                let gas_used = Gas::new(self.execution_bound)
+                    .checked_sub(gas_meter.remaining_gas())
+                    .unwrap()
+                    .into();
+                (result, gas_used)
+            },
+        };
         let mut return_result = serialized_return_values_result.map(|res| {
             res.return_values
                 .into_iter()
@@ -298,22 +356,69 @@ impl SharedTestingConfig {
                 err.remove_exec_state();
             }
         }
-        let test_run_info = TestRunInfo::new(
-            function_name.to_string(),
-            now.elapsed(),
-            // TODO(Gas): This doesn't look quite right...
-            //            We're not computing the number of instructions executed even with a unit gas schedule.
-            Gas::new(self.execution_bound)
-                .checked_sub(gas_meter.remaining_gas())
-                .unwrap()
-                .into(),
-        );
+        let test_run_info = TestRunInfo::new(function_name.to_string(), now.elapsed(), gas_used);
         match session.finish_with_extensions() {
             Ok((cs, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
             Err(err) => (Err(err.clone()), Err(err), return_result, test_run_info),
         }
     }
 
+    /// Runs a `#[fuzz]` test with newly generated arguments until it fails or runs out of runs.
+    /// Returns the test case to report, i.e. the shrunk failing case or the last passing one, and
+    /// a description of the failing arguments if there are any.
+    fn fuzz(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        fuzz_config: &FuzzConfig,
+    ) -> (TestCase, Option<String>) {
+        let with_arguments = |values: &[MoveValue]| TestCase {
+            arguments: fuzz_config.arguments(values),
+            ..test_info.clone()
+        };
+        let fails = |values: &[MoveValue]| {
+            let (_, _, exec_result, _) =
+                self.execute_via_move_vm(test_plan, function_name, &with_arguments(values));
+            !passes(&test_info.expected_failure, &exec_result)
+        };
+
+        let types = fuzz_config.generated_types();
+        let mut rng = fuzz::new_rng(fuzz::test_seed(
+            self.fuzz_seed,
+            test_plan.module_id.name().as_str(),
+            function_name,
+        ));
+        let mut values = vec![];
+        for run in 1..=fuzz_config.runs {
+            values = types
+                .iter()
+                .map(|ty| fuzz::generate(&mut rng, ty))
+                .collect();
+            if !fails(&values) {
+                continue;
+            }
+
+            let (values, shrinks) = fuzz::shrink(&types, values, &fails);
+            let names = fuzz_config
+                .parameters
+                .iter()
+                .filter(|parameter| matches!(parameter.kind, FuzzParameterKind::Generated(_)));
+            let arguments = names
+                .zip(&values)
+                .map(|(parameter, value)| format!("    {} = {}", parameter.name, value))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let description = format!(
+                "Failed on run {} of {} with the fuzz seed {} (shrunk {} times):\n{}\n\
+                Pass the fuzz seed {} to reproduce",
+                run, fuzz_config.runs, self.fuzz_seed, shrinks, arguments, self.fuzz_seed,
+            );
+            return (with_arguments(&values), Some(description));
+        }
+        (with_arguments(&values), None)
+    }
+
     fn exec_module_tests_move_vm_and_stackless_vm(
         &self,
         test_plan: &ModuleTestPlan,
@@ -322,6 +427,15 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
+            let (fuzz_case, fuzz_input) = match &test_info.fuzz {
+                Some(fuzz_config) => {
+                    let (fuzz_case, fuzz_input) =
+                        self.fuzz(test_plan, function_name, test_info, fuzz_config);
+                    (Some(fuzz_case), fuzz_input)
+                },
+                None => (None, None),
+            };
+            let test_info = fuzz_case.as_ref().unwrap_or(test_info);
             let (cs_result, ext_result, exec_result, test_run_info) =
                 self.execute_via_move_vm(test_plan, function_name, test_info);
 
@@ -386,7 +500,8 @@ impl SharedTestingConfig {
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
+                                )
+                                .with_fuzz_input(fuzz_input),
                                 test_plan,
                             )
                         },
@@ -401,7 +516,8 @@ impl SharedTestingConfig {
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
+                                )
+                                .with_fuzz_input(fuzz_input),
                                 test_plan,
                             )
                         },
@@ -414,7 +530,8 @@ impl SharedTestingConfig {
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
+                                )
+                                .with_fuzz_input(fuzz_input),
                                 test_plan,
                             )
                         },
@@ -426,7 +543,8 @@ impl SharedTestingConfig {
                                     test_run_info,
                                     Some(err),
                                     save_session_state(),
-                                ),
+                                )
+                                .with_fuzz_input(fuzz_input),
                                 test_plan,
                             )
                         },
@@ -442,7 +560,8 @@ impl SharedTestingConfig {
                                 test_run_info,
                                 None,
                                 save_session_state(),
-                            ),
+                            )
+                            .with_fuzz_input(fuzz_input),
                             test_plan,
                         )
                     } else {
@@ -523,6 +642,21 @@ impl SharedTestingConfig {
 
         let gen_options = move_to_yul::options::Options::default();
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.fuzz.is_some() {
+                output.fail(function_name);
+                stats.test_failure(
+                    TestFailure::new(
+                        FailureReason::property(
+                            "#[fuzz] tests are not supported by the EVM backend".to_string(),
+                        ),
+                        TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                        None,
+                        None,
+                    ),
+                    test_plan,
+                );
+                continue;
+            }
             let yul_code = match move_to_yul::generator::Generator::run_for_unit_test(
                 &gen_options,
                 &model,
@@ -60,6 +60,7 @@ fn run_test_with_modifiers(
                     None,
                     None,
                     None,
+                    None,
                     buffer,
                 )?,
                 modified_exp_path,
@@ -75,7 +76,14 @@ fn run_test_with_modifiers(
     }
 
     results.push((
-        unit_test_config.run_and_report_unit_tests(test_plan.unwrap(), None, None, None, buffer)?,
+        unit_test_config.run_and_report_unit_tests(
+            test_plan.unwrap(),
+            None,
+            None,
+            None,
+            None,
+            buffer,
+        )?,
         path.with_extension(exp_ext),
     ));
 
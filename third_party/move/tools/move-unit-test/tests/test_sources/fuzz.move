@@ -0,0 +1,42 @@
+address 0x1 {
+module M {
+    use std::signer;
+    use std::vector;
+
+    struct Point has drop {
+        x: u64,
+        y: u64,
+    }
+
+    #[test]
+    #[fuzz]
+    fun add_commutes(a: u32, b: u32) {
+        assert!((a as u64) + (b as u64) == (b as u64) + (a as u64), 0);
+    }
+
+    #[test]
+    #[fuzz(runs = 16)]
+    #[expected_failure(abort_code = 7)]
+    fun always_aborts(_x: u64) {
+        abort 7
+    }
+
+    #[test]
+    #[fuzz(runs = 64)]
+    fun point_sum(p: Point) {
+        assert!((p.x as u128) + (p.y as u128) >= (p.x as u128), 0);
+    }
+
+    #[test]
+    #[fuzz(runs = 16)]
+    fun vector_length(v: vector<u8>) {
+        assert!(vector::length(&v) <= 32, 0);
+    }
+
+    #[test(s = @0x42)]
+    #[fuzz(runs = 16)]
+    fun with_signer(s: signer, x: u8) {
+        assert!(signer::address_of(&s) == @0x42, (x as u64));
+    }
+}
+}
@@ -55,6 +55,10 @@ impl AccountResource {
         &self.authentication_key
     }
 
+    pub fn set_authentication_key(&mut self, authentication_key: Vec<u8>) {
+        self.authentication_key = authentication_key;
+    }
+
     pub fn coin_register_events(&self) -> &EventHandle {
         &self.coin_register_events
     }